@@ -0,0 +1,256 @@
+//! Stable JSON-RPC error codes and messages of the DEX RPC methods.
+//!
+//! Codes are never reused or renumbered, so client libraries can safely match on them.
+
+use crate::RpcError;
+
+/// An unexpected runtime error occurred.
+pub const RUNTIME_ERROR: i32 = 1;
+/// No exchange found for the given asset.
+pub const EXCHANGE_NOT_FOUND: i32 = 2;
+/// There is not enough liquidity in the exchange to perform the trade.
+pub const NOT_ENOUGH_LIQUIDITY: i32 = 3;
+/// An overflow occurred during price computation.
+pub const OVERFLOW: i32 = 4;
+/// Too many quote requests passed to `dex_batchQuote`.
+pub const BATCH_TOO_LARGE: i32 = 5;
+/// Too many exchanges passed to `dex_subscribeExchange`.
+pub const TOO_MANY_EXCHANGES: i32 = 6;
+/// Asset with the specified ID does not exist.
+pub const ASSET_NOT_FOUND: i32 = 7;
+/// Exchange for the given asset already exists.
+pub const EXCHANGE_ALREADY_EXISTS: i32 = 8;
+/// Provided liquidity token ID is already taken.
+pub const TOKEN_ID_TAKEN: i32 = 9;
+/// Not enough free balance to add liquidity or perform trade.
+pub const BALANCE_TOO_LOW: i32 = 10;
+/// Not enough tokens to add liquidity or perform trade.
+pub const NOT_ENOUGH_TOKENS: i32 = 11;
+/// Specified account doesn't own enough liquidity in the exchange.
+pub const PROVIDER_LIQUIDITY_TOO_LOW: i32 = 12;
+/// Zero value provided for trade amount parameter.
+pub const TRADE_AMOUNT_IS_ZERO: i32 = 13;
+/// Zero value provided for `token_amount` parameter.
+pub const TOKEN_AMOUNT_IS_ZERO: i32 = 14;
+/// Zero value provided for `max_tokens` parameter.
+pub const MAX_TOKENS_IS_ZERO: i32 = 15;
+/// Zero value provided for `currency_amount` parameter.
+pub const CURRENCY_AMOUNT_IS_ZERO: i32 = 16;
+/// Value provided for `currency_amount` parameter is too high.
+pub const CURRENCY_AMOUNT_TOO_HIGH: i32 = 17;
+/// Value provided for `currency_amount` parameter is too low.
+pub const CURRENCY_AMOUNT_TOO_LOW: i32 = 18;
+/// Zero value provided for `min_liquidity` parameter.
+pub const MIN_LIQUIDITY_IS_ZERO: i32 = 19;
+/// Value provided for `max_tokens` parameter is too low.
+pub const MAX_TOKENS_TOO_LOW: i32 = 20;
+/// Value provided for `min_liquidity` parameter is too high.
+pub const MIN_LIQUIDITY_TOO_HIGH: i32 = 21;
+/// Zero value provided for `liquidity_amount` parameter.
+pub const LIQUIDITY_AMOUNT_IS_ZERO: i32 = 22;
+/// Zero value provided for `min_currency` parameter.
+pub const MIN_CURRENCY_IS_ZERO: i32 = 23;
+/// Zero value provided for `min_tokens` parameter.
+pub const MIN_TOKENS_IS_ZERO: i32 = 24;
+/// Value provided for `min_currency` parameter is too high.
+pub const MIN_CURRENCY_TOO_HIGH: i32 = 25;
+/// Value provided for `min_tokens` parameter is too high.
+pub const MIN_TOKENS_TOO_HIGH: i32 = 26;
+/// Value provided for `max_currency` parameter is too low.
+pub const MAX_CURRENCY_TOO_LOW: i32 = 27;
+/// Value provided for `min_bought_tokens` parameter is too high.
+pub const MIN_BOUGHT_TOKENS_TOO_HIGH: i32 = 28;
+/// Value provided for `max_sold_tokens` parameter is too low.
+pub const MAX_SOLD_TOKENS_TOO_LOW: i32 = 29;
+/// An underflow occurred.
+pub const UNDERFLOW: i32 = 30;
+/// Deadline specified for the operation has passed.
+pub const DEADLINE_PASSED: i32 = 31;
+/// No route found between the tokens passed to `dex_findBestRoute`.
+pub const ROUTE_NOT_FOUND: i32 = 32;
+/// Zero value provided for `limit_price` parameter.
+pub const LIMIT_PRICE_IS_ZERO: i32 = 33;
+/// No limit order found for the given ID.
+pub const LIMIT_ORDER_NOT_FOUND: i32 = 34;
+/// Limit order is owned by another account.
+pub const NOT_LIMIT_ORDER_OWNER: i32 = 35;
+/// Zero value provided for DCA `interval` parameter.
+pub const DCA_INTERVAL_IS_ZERO: i32 = 36;
+/// Zero value provided for DCA `executions` parameter.
+pub const DCA_EXECUTIONS_IS_ZERO: i32 = 37;
+/// All blocks in which a DCA schedule could be executed are full.
+pub const DCA_QUEUE_FULL: i32 = 38;
+/// No DCA schedule found for the given ID.
+pub const DCA_SCHEDULE_NOT_FOUND: i32 = 39;
+/// DCA schedule is owned by another account.
+pub const NOT_DCA_SCHEDULE_OWNER: i32 = 40;
+/// Too many trades already queued on an exchange in batch auction mode.
+pub const BATCH_FULL: i32 = 41;
+/// Exchange is in batch auction mode.
+pub const BATCH_AUCTION_ENABLED: i32 = 42;
+/// The caller has already made the same trade commitment.
+pub const COMMITMENT_ALREADY_EXISTS: i32 = 43;
+/// No trade commitment matches the revealed trade.
+pub const COMMITMENT_NOT_FOUND: i32 = 44;
+/// Too many trade commitments made in a single block.
+pub const TOO_MANY_COMMITMENTS: i32 = 45;
+/// Signature doesn't match the swap intent.
+pub const INVALID_SIGNATURE: i32 = 46;
+/// Swap intent nonce isn't the next nonce of the signer.
+pub const INVALID_INTENT_NONCE: i32 = 47;
+/// Relayer tip is higher than the trade output.
+pub const TIP_TOO_HIGH: i32 = 48;
+/// Deadline is a timestamp, but the runtime has no time provider.
+pub const TIMESTAMP_DEADLINE_UNSUPPORTED: i32 = 49;
+/// Zero value provided for `max_currency` parameter.
+pub const MAX_CURRENCY_IS_ZERO: i32 = 50;
+/// Amplification coefficient is zero or greater than the maximum.
+pub const INVALID_AMPLIFICATION: i32 = 51;
+/// The exchange doesn't use the StableSwap curve.
+pub const NOT_STABLE_SWAP_EXCHANGE: i32 = 52;
+/// The end of an amplification ramp isn't after the current block.
+pub const INVALID_RAMP_END: i32 = 53;
+/// The operation isn't supported for the pricing curve of the exchange.
+pub const UNSUPPORTED_CURVE: i32 = 54;
+/// Weight of a reserve of a weighted exchange is lower than the minimum weight.
+pub const INVALID_WEIGHT: i32 = 55;
+/// Weight schedule doesn't end after its start and the current block.
+pub const INVALID_WEIGHT_SCHEDULE: i32 = 56;
+/// Tick spacing is zero or greater than the maximum tick spacing.
+pub const INVALID_TICK_SPACING: i32 = 57;
+/// Price is zero or outside of the prices of the ticks.
+pub const INVALID_PRICE: i32 = 58;
+/// Ticks of a position aren't a valid price range of the exchange.
+pub const INVALID_TICK_RANGE: i32 = 59;
+/// The exchange already has the maximum number of initialized ticks.
+pub const TOO_MANY_TICKS: i32 = 60;
+/// The trade would cross more than the maximum number of ticks.
+pub const TOO_MANY_TICKS_CROSSED: i32 = 61;
+/// No position found in the given price range.
+pub const POSITION_NOT_FOUND: i32 = 62;
+/// Liquidity is greater than the liquidity of the position.
+pub const POSITION_LIQUIDITY_TOO_LOW: i32 = 63;
+/// The deposited amounts are too low to provide any liquidity.
+pub const POSITION_LIQUIDITY_IS_ZERO: i32 = 64;
+/// Minimum fee is greater than the maximum fee, or the maximum fee isn't lower than 100%.
+pub const INVALID_FEE_RANGE: i32 = 65;
+/// The runtime's trade filter doesn't allow the operation.
+pub const OPERATION_NOT_ALLOWED: i32 = 66;
+/// The exchange is permissioned and the account isn't an approved liquidity provider.
+pub const PROVIDER_NOT_APPROVED: i32 = 67;
+
+/// The currency paid out would leave the recipient below the minimum balance.
+pub const CURRENCY_BELOW_MINIMUM: i32 = 68;
+/// The method isn't supported by the runtime API version of the queried block.
+pub const UNSUPPORTED_BY_RUNTIME: i32 = 69;
+/// The amount of a route search isn't an amount of the sold token.
+pub const AMOUNT_TOKEN_MISMATCH: i32 = 70;
+/// Tip of the swap intent isn't in the token bought by its trade.
+pub const TIP_TOKEN_MISMATCH: i32 = 71;
+/// The runtime has no DEX instance with the requested `PalletId`.
+pub const INSTANCE_NOT_FOUND: i32 = 72;
+
+/// JSON-RPC representation of a DEX RPC error.
+pub trait RpcErrorInfo {
+    /// Stable numeric code of the error.
+    fn code(&self) -> i32;
+    /// Human readable error message.
+    fn message(&self) -> &'static str;
+}
+
+impl RpcErrorInfo for RpcError {
+    fn code(&self) -> i32 {
+        code_and_message(self).0
+    }
+
+    fn message(&self) -> &'static str {
+        code_and_message(self).1
+    }
+}
+
+fn code_and_message(err: &RpcError) -> (i32, &'static str) {
+    match err {
+        RpcError::ExchangeNotFound => (EXCHANGE_NOT_FOUND, "Exchange not found"),
+        RpcError::NotEnoughLiquidity => (NOT_ENOUGH_LIQUIDITY, "Not enough liquidity"),
+        RpcError::Overflow => (OVERFLOW, "Overflow"),
+        RpcError::Unexpected(_) => (RUNTIME_ERROR, "Runtime error"),
+        RpcError::AssetNotFound => (ASSET_NOT_FOUND, "Asset not found"),
+        RpcError::ExchangeAlreadyExists => (EXCHANGE_ALREADY_EXISTS, "Exchange already exists"),
+        RpcError::TokenIdTaken => (TOKEN_ID_TAKEN, "Liquidity token ID already taken"),
+        RpcError::BalanceTooLow => (BALANCE_TOO_LOW, "Balance too low"),
+        RpcError::NotEnoughTokens => (NOT_ENOUGH_TOKENS, "Not enough tokens"),
+        RpcError::ProviderLiquidityTooLow => {
+            (PROVIDER_LIQUIDITY_TOO_LOW, "Provider liquidity too low")
+        }
+        RpcError::TradeAmountIsZero => (TRADE_AMOUNT_IS_ZERO, "Trade amount is zero"),
+        RpcError::TokenAmountIsZero => (TOKEN_AMOUNT_IS_ZERO, "Token amount is zero"),
+        RpcError::MaxTokensIsZero => (MAX_TOKENS_IS_ZERO, "Max tokens is zero"),
+        RpcError::CurrencyAmountIsZero => (CURRENCY_AMOUNT_IS_ZERO, "Currency amount is zero"),
+        RpcError::CurrencyAmountTooHigh => (CURRENCY_AMOUNT_TOO_HIGH, "Currency amount too high"),
+        RpcError::CurrencyAmountTooLow => (CURRENCY_AMOUNT_TOO_LOW, "Currency amount too low"),
+        RpcError::MinLiquidityIsZero => (MIN_LIQUIDITY_IS_ZERO, "Min liquidity is zero"),
+        RpcError::MaxTokensTooLow => (MAX_TOKENS_TOO_LOW, "Max tokens too low"),
+        RpcError::MinLiquidityTooHigh => (MIN_LIQUIDITY_TOO_HIGH, "Min liquidity too high"),
+        RpcError::LiquidityAmountIsZero => (LIQUIDITY_AMOUNT_IS_ZERO, "Liquidity amount is zero"),
+        RpcError::MinCurrencyIsZero => (MIN_CURRENCY_IS_ZERO, "Min currency is zero"),
+        RpcError::MinTokensIsZero => (MIN_TOKENS_IS_ZERO, "Min tokens is zero"),
+        RpcError::MinCurrencyTooHigh => (MIN_CURRENCY_TOO_HIGH, "Min currency too high"),
+        RpcError::MinTokensTooHigh => (MIN_TOKENS_TOO_HIGH, "Min tokens too high"),
+        RpcError::MaxCurrencyTooLow => (MAX_CURRENCY_TOO_LOW, "Max currency too low"),
+        RpcError::MinBoughtTokensTooHigh => {
+            (MIN_BOUGHT_TOKENS_TOO_HIGH, "Min bought tokens too high")
+        }
+        RpcError::MaxSoldTokensTooLow => (MAX_SOLD_TOKENS_TOO_LOW, "Max sold tokens too low"),
+        RpcError::Underflow => (UNDERFLOW, "Underflow"),
+        RpcError::DeadlinePassed => (DEADLINE_PASSED, "Deadline passed"),
+        RpcError::RouteNotFound => (ROUTE_NOT_FOUND, "Route not found"),
+        RpcError::LimitPriceIsZero => (LIMIT_PRICE_IS_ZERO, "Limit price is zero"),
+        RpcError::LimitOrderNotFound => (LIMIT_ORDER_NOT_FOUND, "Limit order not found"),
+        RpcError::NotLimitOrderOwner => (NOT_LIMIT_ORDER_OWNER, "Not limit order owner"),
+        RpcError::DcaIntervalIsZero => (DCA_INTERVAL_IS_ZERO, "DCA interval is zero"),
+        RpcError::DcaExecutionsIsZero => (DCA_EXECUTIONS_IS_ZERO, "DCA executions is zero"),
+        RpcError::DcaQueueFull => (DCA_QUEUE_FULL, "DCA queue full"),
+        RpcError::DcaScheduleNotFound => (DCA_SCHEDULE_NOT_FOUND, "DCA schedule not found"),
+        RpcError::NotDcaScheduleOwner => (NOT_DCA_SCHEDULE_OWNER, "Not DCA schedule owner"),
+        RpcError::BatchFull => (BATCH_FULL, "Batch full"),
+        RpcError::BatchAuctionEnabled => (BATCH_AUCTION_ENABLED, "Batch auction enabled"),
+        RpcError::CommitmentAlreadyExists => {
+            (COMMITMENT_ALREADY_EXISTS, "Commitment already exists")
+        }
+        RpcError::CommitmentNotFound => (COMMITMENT_NOT_FOUND, "Commitment not found"),
+        RpcError::TooManyCommitments => (TOO_MANY_COMMITMENTS, "Too many commitments"),
+        RpcError::InvalidSignature => (INVALID_SIGNATURE, "Invalid signature"),
+        RpcError::InvalidIntentNonce => (INVALID_INTENT_NONCE, "Invalid intent nonce"),
+        RpcError::TipTooHigh => (TIP_TOO_HIGH, "Tip too high"),
+        RpcError::TimestampDeadlineUnsupported => {
+            (TIMESTAMP_DEADLINE_UNSUPPORTED, "Timestamp deadline unsupported")
+        }
+        RpcError::MaxCurrencyIsZero => (MAX_CURRENCY_IS_ZERO, "Max currency is zero"),
+        RpcError::InvalidAmplification => (INVALID_AMPLIFICATION, "Invalid amplification"),
+        RpcError::NotStableSwapExchange => (NOT_STABLE_SWAP_EXCHANGE, "Not a StableSwap exchange"),
+        RpcError::InvalidRampEnd => (INVALID_RAMP_END, "Invalid ramp end"),
+        RpcError::UnsupportedCurve => (UNSUPPORTED_CURVE, "Unsupported curve"),
+        RpcError::InvalidWeight => (INVALID_WEIGHT, "Invalid weight"),
+        RpcError::InvalidWeightSchedule => (INVALID_WEIGHT_SCHEDULE, "Invalid weight schedule"),
+        RpcError::InvalidTickSpacing => (INVALID_TICK_SPACING, "Invalid tick spacing"),
+        RpcError::InvalidPrice => (INVALID_PRICE, "Invalid price"),
+        RpcError::InvalidTickRange => (INVALID_TICK_RANGE, "Invalid tick range"),
+        RpcError::TooManyTicks => (TOO_MANY_TICKS, "Too many ticks"),
+        RpcError::TooManyTicksCrossed => (TOO_MANY_TICKS_CROSSED, "Too many ticks crossed"),
+        RpcError::PositionNotFound => (POSITION_NOT_FOUND, "Position not found"),
+        RpcError::PositionLiquidityTooLow => {
+            (POSITION_LIQUIDITY_TOO_LOW, "Position liquidity too low")
+        }
+        RpcError::PositionLiquidityIsZero => {
+            (POSITION_LIQUIDITY_IS_ZERO, "Position liquidity is zero")
+        }
+        RpcError::InvalidFeeRange => (INVALID_FEE_RANGE, "Invalid fee range"),
+        RpcError::OperationNotAllowed => (OPERATION_NOT_ALLOWED, "Operation not allowed"),
+        RpcError::ProviderNotApproved => (PROVIDER_NOT_APPROVED, "Provider not approved"),
+        RpcError::CurrencyBelowMinimum => (CURRENCY_BELOW_MINIMUM, "Currency below minimum"),
+        RpcError::BatchTooLarge => (BATCH_TOO_LARGE, "Too many quote requests"),
+        RpcError::AmountTokenMismatch => (AMOUNT_TOKEN_MISMATCH, "Amount token mismatch"),
+        RpcError::TipTokenMismatch => (TIP_TOKEN_MISMATCH, "Tip token mismatch"),
+        RpcError::InstanceNotFound => (INSTANCE_NOT_FOUND, "DEX instance not found"),
+    }
+}
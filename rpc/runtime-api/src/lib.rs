@@ -1,18 +1,83 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 
 use codec::Codec;
-pub use pallet_dex::rpc::{RpcError, RpcResult};
+pub use error::RpcErrorInfo;
+pub use frame_support::PalletId;
+pub use pallet_dex::rpc::{
+    BalanceChange, ExchangeState, Quote, QuoteAmount, QuoteRequest, Route, RpcError, RpcResult,
+    Simulation, Token, TokenAmount, MAX_BATCH_QUOTES,
+};
 use sp_runtime::traits::MaybeDisplay;
+use sp_std::vec::Vec;
+
+pub mod error;
 
 sp_api::decl_runtime_apis! {
+    /// Quotes and state of the exchanges.
+    ///
+    /// Since version 4, every method selects the DEX instance by its `PalletId`, `None` selecting
+    /// the runtime's default instance. Runtimes with several instances dispatch the calls to the
+    /// selected one, failing with `InstanceNotFound` for unknown instances. The served instances
+    /// must share the asset ID and balance types.
+    ///
+    /// Methods added after the first version are only available if the runtime implements
+    /// (at least) the version they were added in, which clients should check with `api_version`.
+    #[api_version(4)]
     pub trait DexApi<AssetId, Balance, AssetBalance> where
         AssetId: Codec + MaybeDisplay,
         Balance: Codec + MaybeDisplay,
         AssetBalance: Codec + MaybeDisplay,
     {
+        #[changed_in(4)]
         fn get_currency_to_asset_output_amount(asset_id: AssetId, currency_amount: Balance) -> RpcResult<AssetBalance>;
+        fn get_currency_to_asset_output_amount(instance: Option<PalletId>, asset_id: AssetId, currency_amount: Balance) -> RpcResult<AssetBalance>;
+        #[changed_in(4)]
         fn get_currency_to_asset_input_amount(asset_id: AssetId, token_amount: AssetBalance) -> RpcResult<Balance>;
+        fn get_currency_to_asset_input_amount(instance: Option<PalletId>, asset_id: AssetId, token_amount: AssetBalance) -> RpcResult<Balance>;
+        #[changed_in(4)]
         fn get_asset_to_currency_output_amount(asset_id: AssetId, token_amount: AssetBalance) -> RpcResult<Balance>;
+        fn get_asset_to_currency_output_amount(instance: Option<PalletId>, asset_id: AssetId, token_amount: AssetBalance) -> RpcResult<Balance>;
+        #[changed_in(4)]
         fn get_asset_to_currency_input_amount(asset_id: AssetId, currency_amount: Balance) -> RpcResult<AssetBalance>;
+        fn get_asset_to_currency_input_amount(instance: Option<PalletId>, asset_id: AssetId, currency_amount: Balance) -> RpcResult<AssetBalance>;
+
+        /***** Added in v2 *****/
+        #[changed_in(4)]
+        fn batch_quote(requests: Vec<QuoteRequest<AssetId, Balance, AssetBalance>>) -> RpcResult<Vec<RpcResult<Quote<Balance, AssetBalance>>>>;
+        /// Answer at most `MAX_BATCH_QUOTES` quotes, failing with `BatchTooLarge` otherwise.
+        fn batch_quote(instance: Option<PalletId>, requests: Vec<QuoteRequest<AssetId, Balance, AssetBalance>>) -> RpcResult<Vec<RpcResult<Quote<Balance, AssetBalance>>>>;
+
+        /***** Added in v3 *****/
+        #[changed_in(4)]
+        fn get_exchange_state(asset_id: AssetId) -> RpcResult<ExchangeState<Balance, AssetBalance>>;
+        fn get_exchange_state(instance: Option<PalletId>, asset_id: AssetId) -> RpcResult<ExchangeState<Balance, AssetBalance>>;
+        #[changed_in(4)]
+        fn exchange_storage_key(asset_id: AssetId) -> Vec<u8>;
+        /// Storage key of the exchange, whose changes are watched by exchange subscriptions.
+        fn exchange_storage_key(instance: Option<PalletId>, asset_id: AssetId) -> RpcResult<Vec<u8>>;
+        #[changed_in(4)]
+        fn find_best_route(from: Token<AssetId>, to: Token<AssetId>, amount: TokenAmount<Balance, AssetBalance>) -> RpcResult<Route<AssetId, Balance, AssetBalance>>;
+        /// Route of at most two hops, as every exchange pairs its asset with the currency.
+        fn find_best_route(instance: Option<PalletId>, from: Token<AssetId>, to: Token<AssetId>, amount: TokenAmount<Balance, AssetBalance>) -> RpcResult<Route<AssetId, Balance, AssetBalance>>;
+    }
+
+    /// Dry-run DEX calls. Kept separate from `DexApi`, as it depends on the runtime's
+    /// `RuntimeCall` and `RuntimeEvent` types.
+    ///
+    /// Since version 2, `Call` is the runtime's `RuntimeCall`, whose variant selects the DEX
+    /// instance. Calls of other pallets fail with `InstanceNotFound`.
+    #[api_version(2)]
+    pub trait DexSimulationApi<AccountId, AssetId, Balance, AssetBalance, Call, Event> where
+        AccountId: Codec,
+        AssetId: Codec,
+        Balance: Codec,
+        AssetBalance: Codec,
+        Call: Codec,
+        Event: Codec,
+    {
+        #[changed_in(2)]
+        fn simulate(caller: AccountId, call: Call) -> Simulation<AccountId, AssetId, Balance, AssetBalance, Event>;
+
+        fn simulate(caller: AccountId, call: Call) -> RpcResult<Simulation<AccountId, AssetId, Balance, AssetBalance, Event>>;
     }
 }
@@ -0,0 +1,53 @@
+//! JSON encoding of DEX instance selectors.
+
+use pallet_dex_rpc_runtime_api::PalletId;
+use serde::{
+    de::{self, Unexpected},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::fmt;
+
+/// The `PalletId` of a DEX instance as represented in RPC requests.
+///
+/// Serialized as the 8 characters of the ID (e.g. `"dex_stbl"`) if they are printable ASCII,
+/// or as a `0x`-prefixed hex string of its 8 bytes otherwise. Both forms are accepted in requests.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct RpcPalletId(pub PalletId);
+
+impl From<PalletId> for RpcPalletId {
+    fn from(id: PalletId) -> Self {
+        Self(id)
+    }
+}
+
+impl fmt::Debug for RpcPalletId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("RpcPalletId").field(&self.0 .0).finish()
+    }
+}
+
+impl Serialize for RpcPalletId {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let bytes = self.0 .0;
+        match std::str::from_utf8(&bytes) {
+            Ok(id) if bytes.iter().all(u8::is_ascii_graphic) => serializer.serialize_str(id),
+            _ => serializer.collect_str(&format_args!("{:#018x}", u64::from_be_bytes(bytes))),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for RpcPalletId {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        let bytes = match value.strip_prefix("0x") {
+            Some(hex) if hex.len() == 16 => u64::from_str_radix(hex, 16).ok().map(u64::to_be_bytes),
+            _ => value.as_bytes().try_into().ok(),
+        };
+        bytes.map(|bytes| Self(PalletId(bytes))).ok_or_else(|| {
+            de::Error::invalid_value(
+                Unexpected::Str(&value),
+                &"an 8 character pallet ID or a 0x-prefixed hex string of 8 bytes",
+            )
+        })
+    }
+}
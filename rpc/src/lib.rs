@@ -1,26 +1,93 @@
-use codec::Codec;
+use codec::{Codec, Encode};
+use futures::{future, stream, FutureExt, StreamExt};
 use jsonrpsee::{
     core::{async_trait, Error as RpcError, RpcResult},
     proc_macros::rpc,
-    types::error::{CallError, ErrorObject},
+    types::{
+        error::{CallError, ErrorObject},
+        SubscriptionResult,
+    },
+    SubscriptionSink,
 };
-use sp_api::ProvideRuntimeApi;
+use sc_client_api::BlockchainEvents;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sp_api::{ApiExt, ProvideRuntimeApi};
 use sp_blockchain::HeaderBackend;
+use sp_core::{storage::StorageKey, traits::SpawnNamed};
 use sp_runtime::traits::MaybeDisplay;
+use std::collections::VecDeque;
 use std::fmt::Debug;
 use std::marker::PhantomData;
 use std::sync::Arc;
 
-pub use pallet_dex_rpc_runtime_api::{DexApi as DexRuntimeApi, RpcError as DexRpcError};
+pub use instance::RpcPalletId;
+pub use number::RpcBalance;
+use pallet_dex_rpc_runtime_api::error::{
+    BATCH_TOO_LARGE, RUNTIME_ERROR, TOO_MANY_EXCHANGES, UNSUPPORTED_BY_RUNTIME,
+};
+pub use pallet_dex_rpc_runtime_api::{
+    error, DexApi as DexRuntimeApi, ExchangeState, PalletId, Quote, QuoteAmount, QuoteRequest,
+    Route, RpcError as DexRpcError, RpcErrorInfo, Token, TokenAmount, MAX_BATCH_QUOTES,
+};
+
+mod instance;
+mod number;
+
+/// Maximum number of exchanges which can be watched by a single subscription.
+pub const MAX_SUBSCRIBED_EXCHANGES: usize = 128;
 
-const RUNTIME_ERROR: i32 = 1;
-const EXCHANGE_NOT_FOUND: i32 = 2;
-const NOT_ENOUGH_LIQUIDITY: i32 = 3;
-const OVERFLOW: i32 = 4;
+/// Maximum number of unfinalized best blocks remembered by a subscription.
+const MAX_TRACKED_BLOCKS: usize = 256;
+
+/// Executor used for spawning subscription tasks.
+pub type SubscriptionTaskExecutor = Arc<dyn SpawnNamed>;
+
+/// Exchange state pushed to `dex_subscribeExchange` subscribers.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExchangeUpdate<BlockHash, AssetId, Balance, AssetBalance> {
+    /// Block in which the state was changed.
+    pub block: BlockHash,
+    /// Whether `block` was finalized (`true`) or imported as the new best block (`false`).
+    pub finalized: bool,
+    pub asset_id: AssetId,
+    #[serde(flatten)]
+    pub state: ExchangeState<Balance, AssetBalance>,
+}
+
+/// Result of a single quote in a batch: either the quoted amount or an error.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum QuoteResult<Balance, AssetBalance> {
+    Ok(Quote<Balance, AssetBalance>),
+    Err {
+        code: i32,
+        message: String,
+        data: Option<Value>,
+    },
+}
+
+impl<Balance, AssetBalance> From<Result<Quote<Balance, AssetBalance>, DexRpcError>>
+    for QuoteResult<Balance, AssetBalance>
+{
+    fn from(result: Result<Quote<Balance, AssetBalance>, DexRpcError>) -> Self {
+        match result {
+            Ok(quote) => Self::Ok(quote),
+            Err(err) => Self::Err {
+                code: err.code(),
+                message: err.message().into(),
+                data: error_data(&err, None),
+            },
+        }
+    }
+}
 
 #[cfg(test)]
 mod tests;
 
+/// DEX RPC methods. Each method takes an optional trailing `instance`, the `PalletId` of the
+/// queried DEX instance, which defaults to the runtime's default instance.
 #[rpc(client, server)]
 pub trait DexApi<BlockHash, AssetId, Balance, AssetBalance> {
     #[method(name = "dex_get_currency_to_asset_output_amount")]
@@ -29,6 +96,7 @@ pub trait DexApi<BlockHash, AssetId, Balance, AssetBalance> {
         asset_id: AssetId,
         currency_amount: Balance,
         at: Option<BlockHash>,
+        instance: Option<RpcPalletId>,
     ) -> RpcResult<AssetBalance>;
 
     #[method(name = "dex_get_currency_to_asset_input_amount")]
@@ -37,6 +105,7 @@ pub trait DexApi<BlockHash, AssetId, Balance, AssetBalance> {
         asset_id: AssetId,
         token_amount: AssetBalance,
         at: Option<BlockHash>,
+        instance: Option<RpcPalletId>,
     ) -> RpcResult<Balance>;
 
     #[method(name = "dex_get_asset_to_currency_output_amount")]
@@ -45,6 +114,7 @@ pub trait DexApi<BlockHash, AssetId, Balance, AssetBalance> {
         asset_id: AssetId,
         token_amount: AssetBalance,
         at: Option<BlockHash>,
+        instance: Option<RpcPalletId>,
     ) -> RpcResult<Balance>;
 
     #[method(name = "dex_get_asset_to_currency_input_amount")]
@@ -53,11 +123,38 @@ pub trait DexApi<BlockHash, AssetId, Balance, AssetBalance> {
         asset_id: AssetId,
         currency_amount: Balance,
         at: Option<BlockHash>,
+        instance: Option<RpcPalletId>,
     ) -> RpcResult<AssetBalance>;
+
+    #[method(name = "dex_batchQuote")]
+    fn batch_quote(
+        &self,
+        requests: Vec<QuoteRequest<AssetId, Balance, AssetBalance>>,
+        at: Option<BlockHash>,
+        instance: Option<RpcPalletId>,
+    ) -> RpcResult<Vec<QuoteResult<Balance, AssetBalance>>>;
+
+    #[method(name = "dex_findBestRoute")]
+    fn find_best_route(
+        &self,
+        from: Token<AssetId>,
+        to: Token<AssetId>,
+        amount: TokenAmount<Balance, AssetBalance>,
+        at: Option<BlockHash>,
+        instance: Option<RpcPalletId>,
+    ) -> RpcResult<Route<AssetId, Balance, AssetBalance>>;
+
+    #[subscription(
+        name = "dex_subscribeExchange" => "dex_exchange",
+        unsubscribe = "dex_unsubscribeExchange",
+        item = ExchangeUpdate<BlockHash, AssetId, Balance, AssetBalance>
+    )]
+    fn subscribe_exchange(&self, asset_ids: Vec<AssetId>, instance: Option<RpcPalletId>);
 }
 
 pub struct Dex<Client, Block> {
     client: Arc<Client>,
+    executor: SubscriptionTaskExecutor,
     _marker: PhantomData<Block>,
 }
 
@@ -68,9 +165,10 @@ where
     Block: sp_runtime::traits::Block,
     Client: HeaderBackend<Block>,
 {
-    pub fn new(client: Arc<Client>) -> Self {
+    pub fn new(client: Arc<Client>, executor: SubscriptionTaskExecutor) -> Self {
         Self {
             client,
+            executor,
             _marker: Default::default(),
         }
     }
@@ -78,83 +176,582 @@ where
 
 #[async_trait]
 impl<Client, Block, AssetId, Balance, AssetBalance>
-    DexApiServer<HashOf<Block>, AssetId, Balance, AssetBalance> for Dex<Client, Block>
+    DexApiServer<HashOf<Block>, AssetId, RpcBalance<Balance>, RpcBalance<AssetBalance>>
+    for Dex<Client, Block>
 where
     Block: sp_runtime::traits::Block,
-    Client: ProvideRuntimeApi<Block> + HeaderBackend<Block> + Send + Sync + 'static,
+    Client: ProvideRuntimeApi<Block>
+        + HeaderBackend<Block>
+        + BlockchainEvents<Block>
+        + Send
+        + Sync
+        + 'static,
     Client::Api: DexRuntimeApi<Block, AssetId, Balance, AssetBalance>,
-    AssetId: Codec + MaybeDisplay + Copy + Send + Sync + 'static,
-    Balance: Codec + MaybeDisplay + Copy + Send + Sync + 'static,
-    AssetBalance: Codec + MaybeDisplay + Copy + Send + Sync + 'static,
+    AssetId: Codec + MaybeDisplay + Copy + Serialize + Send + Sync + 'static,
+    Balance: Codec + MaybeDisplay + Copy + TryFrom<u128> + Send + Sync + 'static,
+    AssetBalance: Codec + MaybeDisplay + Copy + TryFrom<u128> + Send + Sync + 'static,
 {
     fn get_currency_to_asset_output_amount(
         &self,
         asset_id: AssetId,
-        currency_amount: Balance,
+        currency_amount: RpcBalance<Balance>,
         at: Option<Block::Hash>,
-    ) -> RpcResult<AssetBalance> {
+        instance: Option<RpcPalletId>,
+    ) -> RpcResult<RpcBalance<AssetBalance>> {
         let at = at.unwrap_or_default();
-        self.client
-            .runtime_api()
-            .get_currency_to_asset_output_amount(at, asset_id, currency_amount)
+        let instance = instance.map(|id| id.0);
+        let api = self.client.runtime_api();
+        #[allow(deprecated)]
+        let result =
+            match selects_instance::<_, Block, AssetId, Balance, AssetBalance>(&*api, at, instance)
+                .map_err(CallError::Custom)?
+            {
+                true => api.get_currency_to_asset_output_amount(
+                    at,
+                    instance,
+                    asset_id,
+                    currency_amount.0,
+                ),
+                false => api.get_currency_to_asset_output_amount_before_version_4(
+                    at,
+                    asset_id,
+                    currency_amount.0,
+                ),
+            };
+        result
             .map_err(runtime_error)?
-            .map_err(dex_rpc_error)
+            .map(RpcBalance)
+            .map_err(|err| dex_rpc_error(err, asset_id, currency_amount))
     }
 
     fn get_currency_to_asset_input_amount(
         &self,
         asset_id: AssetId,
-        token_amount: AssetBalance,
+        token_amount: RpcBalance<AssetBalance>,
         at: Option<Block::Hash>,
-    ) -> RpcResult<Balance> {
+        instance: Option<RpcPalletId>,
+    ) -> RpcResult<RpcBalance<Balance>> {
         let at = at.unwrap_or_default();
-        self.client
-            .runtime_api()
-            .get_currency_to_asset_input_amount(at, asset_id, token_amount)
+        let instance = instance.map(|id| id.0);
+        let api = self.client.runtime_api();
+        #[allow(deprecated)]
+        let result =
+            match selects_instance::<_, Block, AssetId, Balance, AssetBalance>(&*api, at, instance)
+                .map_err(CallError::Custom)?
+            {
+                true => {
+                    api.get_currency_to_asset_input_amount(at, instance, asset_id, token_amount.0)
+                }
+                false => api.get_currency_to_asset_input_amount_before_version_4(
+                    at,
+                    asset_id,
+                    token_amount.0,
+                ),
+            };
+        result
             .map_err(runtime_error)?
-            .map_err(dex_rpc_error)
+            .map(RpcBalance)
+            .map_err(|err| dex_rpc_error(err, asset_id, token_amount))
     }
 
     fn get_asset_to_currency_output_amount(
         &self,
         asset_id: AssetId,
-        token_amount: AssetBalance,
+        token_amount: RpcBalance<AssetBalance>,
         at: Option<Block::Hash>,
-    ) -> RpcResult<Balance> {
+        instance: Option<RpcPalletId>,
+    ) -> RpcResult<RpcBalance<Balance>> {
         let at = at.unwrap_or_default();
-        self.client
-            .runtime_api()
-            .get_asset_to_currency_output_amount(at, asset_id, token_amount)
+        let instance = instance.map(|id| id.0);
+        let api = self.client.runtime_api();
+        #[allow(deprecated)]
+        let result =
+            match selects_instance::<_, Block, AssetId, Balance, AssetBalance>(&*api, at, instance)
+                .map_err(CallError::Custom)?
+            {
+                true => {
+                    api.get_asset_to_currency_output_amount(at, instance, asset_id, token_amount.0)
+                }
+                false => api.get_asset_to_currency_output_amount_before_version_4(
+                    at,
+                    asset_id,
+                    token_amount.0,
+                ),
+            };
+        result
             .map_err(runtime_error)?
-            .map_err(dex_rpc_error)
+            .map(RpcBalance)
+            .map_err(|err| dex_rpc_error(err, asset_id, token_amount))
     }
 
     fn get_asset_to_currency_input_amount(
         &self,
         asset_id: AssetId,
-        currency_amount: Balance,
+        currency_amount: RpcBalance<Balance>,
         at: Option<Block::Hash>,
-    ) -> RpcResult<AssetBalance> {
+        instance: Option<RpcPalletId>,
+    ) -> RpcResult<RpcBalance<AssetBalance>> {
         let at = at.unwrap_or_default();
-        self.client
-            .runtime_api()
-            .get_asset_to_currency_input_amount(at, asset_id, currency_amount)
+        let instance = instance.map(|id| id.0);
+        let api = self.client.runtime_api();
+        #[allow(deprecated)]
+        let result =
+            match selects_instance::<_, Block, AssetId, Balance, AssetBalance>(&*api, at, instance)
+                .map_err(CallError::Custom)?
+            {
+                true => api.get_asset_to_currency_input_amount(
+                    at,
+                    instance,
+                    asset_id,
+                    currency_amount.0,
+                ),
+                false => api.get_asset_to_currency_input_amount_before_version_4(
+                    at,
+                    asset_id,
+                    currency_amount.0,
+                ),
+            };
+        result
             .map_err(runtime_error)?
-            .map_err(dex_rpc_error)
+            .map(RpcBalance)
+            .map_err(|err| dex_rpc_error(err, asset_id, currency_amount))
+    }
+
+    fn batch_quote(
+        &self,
+        requests: Vec<QuoteRequest<AssetId, RpcBalance<Balance>, RpcBalance<AssetBalance>>>,
+        at: Option<Block::Hash>,
+        instance: Option<RpcPalletId>,
+    ) -> RpcResult<Vec<QuoteResult<RpcBalance<Balance>, RpcBalance<AssetBalance>>>> {
+        if requests.len() > MAX_BATCH_QUOTES as usize {
+            return Err(CallError::Custom(ErrorObject::owned(
+                BATCH_TOO_LARGE,
+                "Too many quote requests",
+                Some(MAX_BATCH_QUOTES),
+            ))
+            .into());
+        }
+        let at = at.unwrap_or_default();
+        let instance = instance.map(|id| id.0);
+        let api = self.client.runtime_api();
+        ensure_api_version::<_, Block, AssetId, Balance, AssetBalance>(&*api, at, 2)
+            .map_err(CallError::Custom)?;
+        let requests = requests.into_iter().map(number::from_rpc_request).collect();
+        #[allow(deprecated)]
+        let results =
+            match selects_instance::<_, Block, AssetId, Balance, AssetBalance>(&*api, at, instance)
+                .map_err(CallError::Custom)?
+            {
+                true => api.batch_quote(at, instance, requests),
+                false => api.batch_quote_before_version_4(at, requests),
+            };
+        let results = results
+            .map_err(runtime_error)?
+            .map_err(|err| dex_error(err, None))?;
+        Ok(results
+            .into_iter()
+            .map(|result| result.map(number::to_rpc_quote).into())
+            .collect())
+    }
+
+    fn find_best_route(
+        &self,
+        from: Token<AssetId>,
+        to: Token<AssetId>,
+        amount: TokenAmount<RpcBalance<Balance>, RpcBalance<AssetBalance>>,
+        at: Option<Block::Hash>,
+        instance: Option<RpcPalletId>,
+    ) -> RpcResult<Route<AssetId, RpcBalance<Balance>, RpcBalance<AssetBalance>>> {
+        let at = at.unwrap_or_default();
+        let instance = instance.map(|id| id.0);
+        let api = self.client.runtime_api();
+        ensure_api_version::<_, Block, AssetId, Balance, AssetBalance>(&*api, at, 3)
+            .map_err(CallError::Custom)?;
+        let runtime_amount = number::from_rpc_token_amount(amount.clone());
+        #[allow(deprecated)]
+        let result =
+            match selects_instance::<_, Block, AssetId, Balance, AssetBalance>(&*api, at, instance)
+                .map_err(CallError::Custom)?
+            {
+                true => api.find_best_route(at, instance, from.clone(), to, runtime_amount),
+                false => api.find_best_route_before_version_4(at, from.clone(), to, runtime_amount),
+            };
+        result
+            .map_err(runtime_error)?
+            .map(number::to_rpc_route)
+            .map_err(|err| dex_rpc_error(err, from, amount))
+    }
+
+    fn subscribe_exchange(
+        &self,
+        mut sink: SubscriptionSink,
+        asset_ids: Vec<AssetId>,
+        instance: Option<RpcPalletId>,
+    ) -> SubscriptionResult {
+        let instance = instance.map(|id| id.0);
+        if asset_ids.len() > MAX_SUBSCRIBED_EXCHANGES {
+            let _ = sink.reject(ErrorObject::owned(
+                TOO_MANY_EXCHANGES,
+                "Too many exchanges",
+                Some(MAX_SUBSCRIBED_EXCHANGES),
+            ));
+            return Ok(());
+        }
+
+        let best = self.client.info().best_hash;
+        let keys = match exchange_storage_keys::<_, Block, AssetId, Balance, AssetBalance>(
+            &*self.client,
+            best,
+            instance,
+            &asset_ids,
+        ) {
+            Ok(keys) => keys,
+            Err(err) => {
+                let _ = sink.reject(err);
+                return Ok(());
+            }
+        };
+        let storage_changes = match self
+            .client
+            .storage_changes_notification_stream(Some(&keys), None)
+        {
+            Ok(stream) => stream,
+            Err(err) => {
+                let _ = sink.reject(runtime_error_object(err));
+                return Ok(());
+            }
+        };
+
+        // Storage changes are only notified for new best blocks which change the watched keys
+        let changed_blocks = storage_changes.map(move |notification| {
+            let mut changed = Vec::new();
+            for (child_key, key, _) in notification.changes.iter() {
+                if child_key.is_none() {
+                    changed.extend((0..keys.len()).filter(|index| &keys[*index] == key));
+                }
+            }
+            BlockEvent::Changed(notification.block, changed)
+        });
+        // Best blocks are tracked to tell apart the finalized blocks which never were the best block
+        let best_blocks = self
+            .client
+            .import_notification_stream()
+            .filter_map(|notification| {
+                future::ready(
+                    notification
+                        .is_new_best
+                        .then_some(BlockEvent::Best(notification.hash)),
+                )
+            });
+        let finalized_blocks = self
+            .client
+            .finality_notification_stream()
+            .map(|notification| {
+                let mut blocks = notification.tree_route.to_vec();
+                blocks.push(notification.hash);
+                BlockEvent::Finalized(blocks)
+            });
+
+        let mut watch = ExchangeWatch::<Block, AssetId>::new(asset_ids, instance);
+        let client = self.client.clone();
+        let all_exchanges: Vec<_> = (0..watch.asset_ids.len()).collect();
+        let current_states =
+            watch.updates::<_, Balance, AssetBalance>(&*client, best, false, &all_exchanges);
+        // Pending best blocks are handled first, so that finalized blocks are already known
+        let events = stream::select_with_strategy(
+            stream::select(best_blocks, changed_blocks),
+            finalized_blocks,
+            |_: &mut ()| stream::PollNext::Left,
+        );
+        let updates = stream::iter(current_states)
+            .chain(events.flat_map(move |event| {
+                stream::iter(watch.handle::<_, Balance, AssetBalance>(&*client, event))
+            }))
+            .boxed();
+
+        let fut = async move {
+            sink.pipe_from_stream(updates).await;
+        };
+        self.executor
+            .spawn("dex-rpc-subscription", Some("rpc"), fut.boxed());
+        Ok(())
     }
 }
 
+/// Block notifications driving an exchange subscription.
+enum BlockEvent<Hash> {
+    /// A new best block was imported. Its storage changes, if any, are notified separately.
+    Best(Hash),
+    /// A new best block changed the storage of the watched exchanges with the given indices.
+    Changed(Hash, Vec<usize>),
+    /// Blocks were finalized, the last one being the new finalized block.
+    Finalized(Vec<Hash>),
+}
+
+/// State of an exchange subscription.
+struct ExchangeWatch<Block: sp_runtime::traits::Block, AssetId> {
+    asset_ids: Vec<AssetId>,
+    instance: Option<PalletId>,
+    /// Recent best blocks, with the indices of the watched exchanges they changed.
+    /// Kept until the blocks are finalized, at most `MAX_TRACKED_BLOCKS`.
+    best_blocks: VecDeque<(Block::Hash, Vec<usize>)>,
+    /// Last pushed (encoded) state of each exchange, tracked separately for best and finalized blocks
+    last_best: Vec<Option<Vec<u8>>>,
+    last_finalized: Vec<Option<Vec<u8>>>,
+}
+
+impl<Block, AssetId> ExchangeWatch<Block, AssetId>
+where
+    Block: sp_runtime::traits::Block,
+    AssetId: Codec + MaybeDisplay + Copy,
+{
+    fn new(asset_ids: Vec<AssetId>, instance: Option<PalletId>) -> Self {
+        Self {
+            instance,
+            last_best: vec![None; asset_ids.len()],
+            last_finalized: vec![None; asset_ids.len()],
+            best_blocks: VecDeque::new(),
+            asset_ids,
+        }
+    }
+
+    /// Updates caused by a block notification. The state of an exchange is only queried from the
+    /// runtime if its storage was changed by the new best block, or by any of the finalized blocks.
+    /// All exchanges are queried if a finalized block wasn't tracked as a best block, i.e. it was
+    /// never the best block (after a fork switch) or was dropped from the tracked blocks.
+    fn handle<Client, Balance, AssetBalance>(
+        &mut self,
+        client: &Client,
+        event: BlockEvent<Block::Hash>,
+    ) -> Vec<ExchangeUpdate<Block::Hash, AssetId, RpcBalance<Balance>, RpcBalance<AssetBalance>>>
+    where
+        Client: ProvideRuntimeApi<Block>,
+        Client::Api: DexRuntimeApi<Block, AssetId, Balance, AssetBalance>,
+        Balance: Codec + MaybeDisplay,
+        AssetBalance: Codec + MaybeDisplay,
+    {
+        match event {
+            BlockEvent::Best(block) => {
+                self.track(block, Vec::new());
+                Vec::new()
+            }
+            BlockEvent::Changed(block, changed) => {
+                let updates = self.updates(client, block, false, &changed);
+                self.track(block, changed);
+                updates
+            }
+            BlockEvent::Finalized(blocks) => {
+                let mut changed = Vec::new();
+                let mut untracked = blocks.len();
+                self.best_blocks.retain(|(block, indices)| {
+                    let finalized = blocks.contains(block);
+                    if finalized {
+                        changed.extend(indices);
+                        untracked -= 1;
+                    }
+                    !finalized
+                });
+                if untracked > 0 {
+                    changed = (0..self.asset_ids.len()).collect();
+                }
+                changed.sort();
+                changed.dedup();
+                match blocks.last() {
+                    Some(head) if !changed.is_empty() => {
+                        self.updates(client, *head, true, &changed)
+                    }
+                    _ => Vec::new(),
+                }
+            }
+        }
+    }
+
+    /// Remember a best block with the indices of the watched exchanges it changed, which are
+    /// merged if the block is already tracked. The oldest block is dropped if too many are tracked.
+    fn track(&mut self, block: Block::Hash, changed: Vec<usize>) {
+        if let Some((_, indices)) = self.best_blocks.iter_mut().find(|(hash, _)| *hash == block) {
+            indices.extend(changed);
+            return;
+        }
+        if self.best_blocks.len() == MAX_TRACKED_BLOCKS {
+            self.best_blocks.pop_front();
+        }
+        self.best_blocks.push_back((block, changed));
+    }
+
+    /// Query the state of the exchanges with the given indices at `block` and return those which
+    /// changed since the last update. Exchanges which do not exist at `block` are skipped.
+    fn updates<Client, Balance, AssetBalance>(
+        &mut self,
+        client: &Client,
+        block: Block::Hash,
+        finalized: bool,
+        indices: &[usize],
+    ) -> Vec<ExchangeUpdate<Block::Hash, AssetId, RpcBalance<Balance>, RpcBalance<AssetBalance>>>
+    where
+        Client: ProvideRuntimeApi<Block>,
+        Client::Api: DexRuntimeApi<Block, AssetId, Balance, AssetBalance>,
+        Balance: Codec + MaybeDisplay,
+        AssetBalance: Codec + MaybeDisplay,
+    {
+        let api = client.runtime_api();
+        let instance = self.instance;
+        let instance_aware = match selects_instance::<_, Block, AssetId, Balance, AssetBalance>(
+            &*api, block, instance,
+        ) {
+            Ok(instance_aware) => instance_aware,
+            Err(_) => return Vec::new(),
+        };
+        let last_states = match finalized {
+            true => &mut self.last_finalized,
+            false => &mut self.last_best,
+        };
+        indices
+            .iter()
+            .filter_map(|index| {
+                let asset_id = self.asset_ids[*index];
+                #[allow(deprecated)]
+                let state = match instance_aware {
+                    true => api.get_exchange_state(block, instance, asset_id),
+                    false => api.get_exchange_state_before_version_4(block, asset_id),
+                };
+                let state = state.ok()?.ok()?;
+                let encoded_state = Some(state.encode());
+                if last_states[*index] == encoded_state {
+                    return None;
+                }
+                last_states[*index] = encoded_state;
+                Some(ExchangeUpdate {
+                    block,
+                    finalized,
+                    asset_id,
+                    state: number::to_rpc_exchange_state(state),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Storage keys of the exchanges of the given assets at `at`.
+fn exchange_storage_keys<Client, Block, AssetId, Balance, AssetBalance>(
+    client: &Client,
+    at: Block::Hash,
+    instance: Option<PalletId>,
+    asset_ids: &[AssetId],
+) -> Result<Vec<StorageKey>, ErrorObject<'static>>
+where
+    Block: sp_runtime::traits::Block,
+    Client: ProvideRuntimeApi<Block>,
+    Client::Api: DexRuntimeApi<Block, AssetId, Balance, AssetBalance>,
+    AssetId: Codec + MaybeDisplay + Copy,
+    Balance: Codec + MaybeDisplay,
+    AssetBalance: Codec + MaybeDisplay,
+{
+    let api = client.runtime_api();
+    ensure_api_version::<_, Block, AssetId, Balance, AssetBalance>(&*api, at, 3)?;
+    let instance_aware =
+        selects_instance::<_, Block, AssetId, Balance, AssetBalance>(&*api, at, instance)?;
+    asset_ids
+        .iter()
+        .map(|asset_id| {
+            #[allow(deprecated)]
+            let key = match instance_aware {
+                true => api
+                    .exchange_storage_key(at, instance, *asset_id)
+                    .map_err(runtime_error_object)?
+                    .map_err(|err| dex_error_object(err, None))?,
+                false => api
+                    .exchange_storage_key_before_version_4(at, *asset_id)
+                    .map_err(runtime_error_object)?,
+            };
+            Ok(StorageKey(key))
+        })
+        .collect()
+}
+
+/// Fail unless the runtime at `at` implements at least `version` of the DEX runtime API,
+/// i.e. the version in which the called method was added.
+fn ensure_api_version<Api, Block, AssetId, Balance, AssetBalance>(
+    api: &Api,
+    at: Block::Hash,
+    version: u32,
+) -> Result<(), ErrorObject<'static>>
+where
+    Block: sp_runtime::traits::Block,
+    Api: ApiExt<Block> + DexRuntimeApi<Block, AssetId, Balance, AssetBalance>,
+    AssetId: Codec + MaybeDisplay,
+    Balance: Codec + MaybeDisplay,
+    AssetBalance: Codec + MaybeDisplay,
+{
+    let supported = api
+        .has_api_with::<dyn DexRuntimeApi<Block, AssetId, Balance, AssetBalance>, _>(at, |v| {
+            v >= version
+        })
+        .map_err(runtime_error_object)?;
+    if !supported {
+        return Err(ErrorObject::owned(
+            UNSUPPORTED_BY_RUNTIME,
+            "Method not supported by the runtime",
+            Some(json!({ "apiVersion": version })),
+        ));
+    }
+    Ok(())
+}
+
+/// Whether the runtime at `at` takes the DEX instance selector, i.e. implements version 4 of
+/// the DEX runtime API. Older runtimes only serve their default instance, so selecting another
+/// instance fails.
+fn selects_instance<Api, Block, AssetId, Balance, AssetBalance>(
+    api: &Api,
+    at: Block::Hash,
+    instance: Option<PalletId>,
+) -> Result<bool, ErrorObject<'static>>
+where
+    Block: sp_runtime::traits::Block,
+    Api: ApiExt<Block> + DexRuntimeApi<Block, AssetId, Balance, AssetBalance>,
+    AssetId: Codec + MaybeDisplay,
+    Balance: Codec + MaybeDisplay,
+    AssetBalance: Codec + MaybeDisplay,
+{
+    if instance.is_some() {
+        ensure_api_version::<_, Block, AssetId, Balance, AssetBalance>(api, at, 4)?;
+        return Ok(true);
+    }
+    api.has_api_with::<dyn DexRuntimeApi<Block, AssetId, Balance, AssetBalance>, _>(at, |v| v >= 4)
+        .map_err(runtime_error_object)
+}
+
 fn runtime_error(err: impl Debug) -> RpcError {
-    CallError::Custom(ErrorObject::owned(RUNTIME_ERROR, "Runtime error", Some(format!("{err:?}"))))
-        .into()
-}
-
-fn dex_rpc_error(err: DexRpcError) -> RpcError {
-    let (code, message, data) = match err {
-        DexRpcError::ExchangeNotFound => (EXCHANGE_NOT_FOUND, "Exchange not found", None),
-        DexRpcError::NotEnoughLiquidity => (NOT_ENOUGH_LIQUIDITY, "Not enough liquidity", None),
-        DexRpcError::Overflow => (OVERFLOW, "Overflow", None),
-        DexRpcError::Unexpected(msg) => (RUNTIME_ERROR, "Runtime error", Some(msg)),
-    };
-    CallError::Custom(ErrorObject::owned(code, message, data)).into()
+    CallError::Custom(runtime_error_object(err)).into()
+}
+
+fn runtime_error_object(err: impl Debug) -> ErrorObject<'static> {
+    ErrorObject::owned(RUNTIME_ERROR, "Runtime error", Some(format!("{err:?}")))
+}
+
+fn dex_rpc_error(err: DexRpcError, asset_id: impl Serialize, amount: impl Serialize) -> RpcError {
+    let params = json!({ "assetId": asset_id, "amount": amount });
+    dex_error(err, Some(params))
+}
+
+fn dex_error(err: DexRpcError, params: Option<Value>) -> RpcError {
+    CallError::Custom(dex_error_object(err, params)).into()
+}
+
+fn dex_error_object(err: DexRpcError, params: Option<Value>) -> ErrorObject<'static> {
+    let data = error_data(&err, params);
+    ErrorObject::owned(err.code(), err.message(), data)
+}
+
+/// Structured error data: the raw error for unexpected runtime errors,
+/// or the offending request parameters for errors caused by them.
+fn error_data(err: &DexRpcError, params: Option<Value>) -> Option<Value> {
+    match err {
+        DexRpcError::Unexpected(msg) => Some(json!(msg)),
+        DexRpcError::ExchangeNotFound | DexRpcError::AssetNotFound => {
+            params.map(|params| json!({ "assetId": params["assetId"] }))
+        }
+        DexRpcError::NotEnoughLiquidity | DexRpcError::Overflow => params,
+        _ => None,
+    }
 }
@@ -0,0 +1,225 @@
+//! JSON-safe encoding of balances.
+
+use pallet_dex_rpc_runtime_api::{
+    ExchangeState, Quote, QuoteAmount, QuoteRequest, Route, TokenAmount,
+};
+use serde::{
+    de::{self, Unexpected, Visitor},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use std::fmt::{self, Display};
+use std::marker::PhantomData;
+
+/// A balance as represented in RPC requests and responses.
+///
+/// Balances are usually `u128`, which can't be safely passed as a JSON number (e.g. JavaScript
+/// numbers lose precision above 2^53). This wrapper is serialized as a decimal string, and can be
+/// deserialized from a decimal string, a `0x`-prefixed hex string or (for backwards compatibility)
+/// a plain number.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct RpcBalance<T>(pub T);
+
+impl<T> From<T> for RpcBalance<T> {
+    fn from(balance: T) -> Self {
+        Self(balance)
+    }
+}
+
+impl<T: Display> Serialize for RpcBalance<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(&self.0)
+    }
+}
+
+impl<'de, T: TryFrom<u128>> Deserialize<'de> for RpcBalance<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_any(BalanceVisitor(PhantomData))
+    }
+}
+
+struct BalanceVisitor<T>(PhantomData<T>);
+
+impl<T: TryFrom<u128>> BalanceVisitor<T> {
+    fn convert<E: de::Error>(&self, value: u128) -> Result<RpcBalance<T>, E> {
+        T::try_from(value)
+            .map(RpcBalance)
+            .map_err(|_| E::invalid_value(Unexpected::Other("out of range integer"), self))
+    }
+}
+
+impl<'de, T: TryFrom<u128>> Visitor<'de> for BalanceVisitor<T> {
+    type Value = RpcBalance<T>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a non-negative integer, a decimal string or a 0x-prefixed hex string")
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<Self::Value, E> {
+        self.convert(value.into())
+    }
+
+    fn visit_u128<E: de::Error>(self, value: u128) -> Result<Self::Value, E> {
+        self.convert(value)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<Self::Value, E> {
+        let value = u128::try_from(value)
+            .map_err(|_| E::invalid_value(Unexpected::Signed(value), &self))?;
+        self.convert(value)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+        let parsed = match value.strip_prefix("0x") {
+            Some(hex) => u128::from_str_radix(hex, 16),
+            None => value.parse(),
+        };
+        let parsed = parsed.map_err(|_| E::invalid_value(Unexpected::Str(value), &self))?;
+        self.convert(parsed)
+    }
+}
+
+fn from_rpc_amount<I, O>(amount: QuoteAmount<RpcBalance<I>, RpcBalance<O>>) -> QuoteAmount<I, O> {
+    match amount {
+        QuoteAmount::FixedInput(input) => QuoteAmount::FixedInput(input.0),
+        QuoteAmount::FixedOutput(output) => QuoteAmount::FixedOutput(output.0),
+    }
+}
+
+pub(crate) fn from_rpc_request<AssetId, Balance, AssetBalance>(
+    request: QuoteRequest<AssetId, RpcBalance<Balance>, RpcBalance<AssetBalance>>,
+) -> QuoteRequest<AssetId, Balance, AssetBalance> {
+    match request {
+        QuoteRequest::CurrencyToAsset { asset_id, amount } => QuoteRequest::CurrencyToAsset {
+            asset_id,
+            amount: from_rpc_amount(amount),
+        },
+        QuoteRequest::AssetToCurrency { asset_id, amount } => QuoteRequest::AssetToCurrency {
+            asset_id,
+            amount: from_rpc_amount(amount),
+        },
+        QuoteRequest::AssetToAsset {
+            sold_asset_id,
+            bought_asset_id,
+            amount,
+        } => QuoteRequest::AssetToAsset {
+            sold_asset_id,
+            bought_asset_id,
+            amount: from_rpc_amount(amount),
+        },
+    }
+}
+
+pub(crate) fn to_rpc_quote<Balance, AssetBalance>(
+    quote: Quote<Balance, AssetBalance>,
+) -> Quote<RpcBalance<Balance>, RpcBalance<AssetBalance>> {
+    match quote {
+        Quote::Currency(amount) => Quote::Currency(RpcBalance(amount)),
+        Quote::Asset(amount) => Quote::Asset(RpcBalance(amount)),
+    }
+}
+
+pub(crate) fn to_rpc_exchange_state<Balance, AssetBalance>(
+    state: ExchangeState<Balance, AssetBalance>,
+) -> ExchangeState<RpcBalance<Balance>, RpcBalance<AssetBalance>> {
+    ExchangeState {
+        currency_reserve: RpcBalance(state.currency_reserve),
+        token_reserve: RpcBalance(state.token_reserve),
+        total_liquidity: RpcBalance(state.total_liquidity),
+        spot_price: state.spot_price,
+    }
+}
+
+pub(crate) fn from_rpc_token_amount<Balance, AssetBalance>(
+    amount: TokenAmount<RpcBalance<Balance>, RpcBalance<AssetBalance>>,
+) -> TokenAmount<Balance, AssetBalance> {
+    match amount {
+        TokenAmount::Currency(amount) => TokenAmount::Currency(amount.0),
+        TokenAmount::Asset(amount) => TokenAmount::Asset(amount.0),
+    }
+}
+
+pub(crate) fn to_rpc_route<AssetId, Balance, AssetBalance>(
+    route: Route<AssetId, Balance, AssetBalance>,
+) -> Route<AssetId, RpcBalance<Balance>, RpcBalance<AssetBalance>> {
+    Route {
+        path: route.path,
+        amount_out: match route.amount_out {
+            TokenAmount::Currency(amount) => TokenAmount::Currency(RpcBalance(amount)),
+            TokenAmount::Asset(amount) => TokenAmount::Asset(RpcBalance(amount)),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RpcBalance;
+
+    #[test]
+    fn serialize_as_decimal_string() {
+        assert_eq!(serde_json::to_string(&RpcBalance(100u128)).unwrap(), r#""100""#);
+        assert_eq!(
+            serde_json::to_string(&RpcBalance(u128::MAX)).unwrap(),
+            r#""340282366920938463463374607431768211455""#
+        );
+    }
+
+    #[test]
+    fn deserialize_from_number() {
+        assert_eq!(serde_json::from_str::<RpcBalance<u128>>("100").unwrap(), RpcBalance(100));
+        assert_eq!(
+            serde_json::from_str::<RpcBalance<u128>>("18446744073709551615").unwrap(),
+            RpcBalance(u64::MAX as u128)
+        );
+    }
+
+    #[test]
+    fn deserialize_from_decimal_string() {
+        assert_eq!(
+            serde_json::from_str::<RpcBalance<u128>>(
+                r#""340282366920938463463374607431768211455""#
+            )
+            .unwrap(),
+            RpcBalance(u128::MAX)
+        );
+    }
+
+    #[test]
+    fn deserialize_from_hex_string() {
+        assert_eq!(serde_json::from_str::<RpcBalance<u128>>(r#""0xff""#).unwrap(), RpcBalance(255));
+        assert_eq!(
+            serde_json::from_str::<RpcBalance<u128>>(r#""0xffffffffffffffffffffffffffffffff""#)
+                .unwrap(),
+            RpcBalance(u128::MAX)
+        );
+    }
+
+    #[test]
+    fn deserialize_invalid() {
+        // Negative numbers
+        assert!(serde_json::from_str::<RpcBalance<u128>>("-1").is_err());
+        // Floats (e.g. numbers above u64::MAX, which lose precision)
+        assert!(serde_json::from_str::<RpcBalance<u128>>("1.5").is_err());
+        assert!(serde_json::from_str::<RpcBalance<u128>>(
+            "340282366920938463463374607431768211455"
+        )
+        .is_err());
+        // Malformed strings
+        assert!(serde_json::from_str::<RpcBalance<u128>>(r#""abc""#).is_err());
+        assert!(serde_json::from_str::<RpcBalance<u128>>(r#""0x""#).is_err());
+        assert!(serde_json::from_str::<RpcBalance<u128>>(r#""0xzz""#).is_err());
+        assert!(serde_json::from_str::<RpcBalance<u128>>(r#""-1""#).is_err());
+        // Values which don't fit in the balance type
+        assert!(serde_json::from_str::<RpcBalance<u64>>(r#""18446744073709551616""#).is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        for balance in [0, 1, 1 << 53, (1 << 53) + 1, u64::MAX as u128, u128::MAX] {
+            let json = serde_json::to_string(&RpcBalance(balance)).unwrap();
+            assert_eq!(
+                serde_json::from_str::<RpcBalance<u128>>(&json).unwrap(),
+                RpcBalance(balance)
+            );
+        }
+    }
+}
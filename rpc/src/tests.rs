@@ -1,15 +1,27 @@
 use super::*;
+use error::{
+    BATCH_TOO_LARGE, EXCHANGE_NOT_FOUND, INSTANCE_NOT_FOUND, NOT_ENOUGH_LIQUIDITY, OVERFLOW,
+    RUNTIME_ERROR, TOO_MANY_EXCHANGES, UNSUPPORTED_BY_RUNTIME,
+};
 use jsonrpsee::core::Error;
 use mock::*;
-use pallet_dex::rpc::RpcError;
+use pallet_dex::rpc::{
+    ExchangeState, Quote, QuoteAmount, QuoteRequest, Route, RpcError, Token, TokenAmount,
+};
+use sp_core::H256;
+use sp_runtime::{traits::Header as _, FixedPointNumber, FixedU128};
 use std::sync::Arc;
+use std::time::Duration;
+use substrate_test_runtime_client::runtime::Header;
 
 type AssetId = u32;
 type Balance = u128;
 type AssetBalance = u64;
 type RpcResult<T> = Result<T, RpcError>;
+type Update = ExchangeUpdate<H256, AssetId, RpcBalance<Balance>, RpcBalance<AssetBalance>>;
 
 const ASSET: AssetId = 1;
+const STABLE_INSTANCE: PalletId = PalletId(*b"dex_stbl");
 const CURRENCY_AMOUNT: Balance = 100;
 const TOKEN_AMOUNT: AssetBalance = 100;
 const EXCHANGE_NOT_FOUND_MESSAGE: &str = "Exchange not found";
@@ -20,10 +32,41 @@ const DATA: [u8; 15] = [
     117, 110, 101, 120, 112, 101, 99, 116, 101, 100, 32, 100, 97, 116, 97,
 ];
 
-fn assert(error: Error, code: i32, message: &str, data: Option<&[u8]>) {
+fn executor() -> SubscriptionTaskExecutor {
+    Arc::new(sp_core::testing::TaskExecutor::new())
+}
+
+fn header(number: u64) -> Header {
+    Header::new(
+        number,
+        Default::default(),
+        Default::default(),
+        Default::default(),
+        Default::default(),
+    )
+}
+
+fn exchange_state(
+    currency_reserve: Balance,
+    token_reserve: AssetBalance,
+) -> ExchangeState<Balance, AssetBalance> {
+    ExchangeState {
+        currency_reserve,
+        token_reserve,
+        total_liquidity: token_reserve,
+        spot_price: FixedU128::from_rational(currency_reserve, token_reserve as u128),
+    }
+}
+
+// Currency and token amounts used in tests are equal, so the error data is the same for all methods
+fn amount_data() -> Value {
+    json!({ "assetId": ASSET, "amount": TOKEN_AMOUNT.to_string() })
+}
+
+fn assert(error: Error, code: i32, message: &str, data: Option<Value>) {
     assert!(matches!(error, Error::Call(e) if matches!(&e, CallError::Custom(e)
     if e.code() == code && e.message() == message &&
-        e.data().map(|v| v.get().to_string()) == data.map(|d| format!("{d:?}").replace(' ', "")))));
+        e.data().map(|v| serde_json::from_str::<Value>(v.get()).unwrap()) == data)));
 }
 
 #[tokio::test]
@@ -34,13 +77,18 @@ async fn get_currency_to_asset_output_amount_with_exchange_not_found() {
         Err(RpcError::ExchangeNotFound),
     );
     let client = Arc::new(TestApi::new(expectation));
-    let api = Dex::new(client);
+    let api = Dex::new(client, executor());
 
     let error = api
-        .get_currency_to_asset_output_amount(ASSET, CURRENCY_AMOUNT, None)
+        .get_currency_to_asset_output_amount(ASSET, CURRENCY_AMOUNT.into(), None, None)
         .unwrap_err();
 
-    assert(error, EXCHANGE_NOT_FOUND, EXCHANGE_NOT_FOUND_MESSAGE, None)
+    assert(
+        error,
+        EXCHANGE_NOT_FOUND,
+        EXCHANGE_NOT_FOUND_MESSAGE,
+        Some(json!({ "assetId": ASSET })),
+    )
 }
 
 #[tokio::test]
@@ -51,13 +99,13 @@ async fn get_currency_to_asset_output_amount_with_not_enough_liquidity() {
         Err(RpcError::NotEnoughLiquidity),
     );
     let client = Arc::new(TestApi::new(expectation));
-    let api = Dex::new(client);
+    let api = Dex::new(client, executor());
 
     let error = api
-        .get_currency_to_asset_output_amount(ASSET, CURRENCY_AMOUNT, None)
+        .get_currency_to_asset_output_amount(ASSET, CURRENCY_AMOUNT.into(), None, None)
         .unwrap_err();
 
-    assert(error, NOT_ENOUGH_LIQUIDITY, NOT_ENOUGH_LIQUIDITY_MESSAGE, None)
+    assert(error, NOT_ENOUGH_LIQUIDITY, NOT_ENOUGH_LIQUIDITY_MESSAGE, Some(amount_data()))
 }
 
 #[tokio::test]
@@ -68,13 +116,13 @@ async fn get_currency_to_asset_output_amount_with_overflow() {
         Err(RpcError::Overflow),
     );
     let client = Arc::new(TestApi::new(expectation));
-    let api = Dex::new(client);
+    let api = Dex::new(client, executor());
 
     let error = api
-        .get_currency_to_asset_output_amount(ASSET, CURRENCY_AMOUNT, None)
+        .get_currency_to_asset_output_amount(ASSET, CURRENCY_AMOUNT.into(), None, None)
         .unwrap_err();
 
-    assert(error, OVERFLOW, OVERFLOW_MESSAGE, None)
+    assert(error, OVERFLOW, OVERFLOW_MESSAGE, Some(amount_data()))
 }
 
 #[tokio::test]
@@ -85,13 +133,13 @@ async fn get_currency_to_asset_output_amount_with_unexpected() {
         Err(RpcError::Unexpected(DATA.into())),
     );
     let client = Arc::new(TestApi::new(expectation));
-    let api = Dex::new(client);
+    let api = Dex::new(client, executor());
 
     let error = api
-        .get_currency_to_asset_output_amount(ASSET, CURRENCY_AMOUNT, None)
+        .get_currency_to_asset_output_amount(ASSET, CURRENCY_AMOUNT.into(), None, None)
         .unwrap_err();
 
-    assert(error, RUNTIME_ERROR, RUNTIME_ERROR_MESSAGE, Some(&DATA))
+    assert(error, RUNTIME_ERROR, RUNTIME_ERROR_MESSAGE, Some(json!(DATA)))
 }
 
 #[tokio::test]
@@ -99,13 +147,13 @@ async fn get_currency_to_asset_output_amount_with_success() {
     let expectation = Expectation::GetCurrencyToAssetOutputAmount(ASSET, CURRENCY_AMOUNT, Ok(100));
 
     let client = Arc::new(TestApi::new(expectation));
-    let api = Dex::new(client);
+    let api = Dex::new(client, executor());
 
     let result = api
-        .get_currency_to_asset_output_amount(ASSET, CURRENCY_AMOUNT, None)
+        .get_currency_to_asset_output_amount(ASSET, CURRENCY_AMOUNT.into(), None, None)
         .unwrap();
 
-    assert_eq!(100, result);
+    assert_eq!(RpcBalance(100), result);
 }
 
 #[tokio::test]
@@ -116,13 +164,18 @@ async fn get_currency_to_asset_input_amount_with_exchange_not_found() {
         Err(RpcError::ExchangeNotFound),
     );
     let client = Arc::new(TestApi::new(expectation));
-    let api = Dex::new(client);
+    let api = Dex::new(client, executor());
 
     let error = api
-        .get_currency_to_asset_input_amount(ASSET, TOKEN_AMOUNT, None)
+        .get_currency_to_asset_input_amount(ASSET, TOKEN_AMOUNT.into(), None, None)
         .unwrap_err();
 
-    assert(error, EXCHANGE_NOT_FOUND, EXCHANGE_NOT_FOUND_MESSAGE, None)
+    assert(
+        error,
+        EXCHANGE_NOT_FOUND,
+        EXCHANGE_NOT_FOUND_MESSAGE,
+        Some(json!({ "assetId": ASSET })),
+    )
 }
 
 #[tokio::test]
@@ -133,13 +186,13 @@ async fn get_currency_to_asset_input_amount_with_not_enough_liquidity() {
         Err(RpcError::NotEnoughLiquidity),
     );
     let client = Arc::new(TestApi::new(expectation));
-    let api = Dex::new(client);
+    let api = Dex::new(client, executor());
 
     let error = api
-        .get_currency_to_asset_input_amount(ASSET, TOKEN_AMOUNT, None)
+        .get_currency_to_asset_input_amount(ASSET, TOKEN_AMOUNT.into(), None, None)
         .unwrap_err();
 
-    assert(error, NOT_ENOUGH_LIQUIDITY, NOT_ENOUGH_LIQUIDITY_MESSAGE, None)
+    assert(error, NOT_ENOUGH_LIQUIDITY, NOT_ENOUGH_LIQUIDITY_MESSAGE, Some(amount_data()))
 }
 
 #[tokio::test]
@@ -147,13 +200,13 @@ async fn get_currency_to_asset_input_amount_with_overflow() {
     let expectation =
         Expectation::GetCurrencyToAssetInputAmount(ASSET, TOKEN_AMOUNT, Err(RpcError::Overflow));
     let client = Arc::new(TestApi::new(expectation));
-    let api = Dex::new(client);
+    let api = Dex::new(client, executor());
 
     let error = api
-        .get_currency_to_asset_input_amount(ASSET, TOKEN_AMOUNT, None)
+        .get_currency_to_asset_input_amount(ASSET, TOKEN_AMOUNT.into(), None, None)
         .unwrap_err();
 
-    assert(error, OVERFLOW, OVERFLOW_MESSAGE, None)
+    assert(error, OVERFLOW, OVERFLOW_MESSAGE, Some(amount_data()))
 }
 
 #[tokio::test]
@@ -164,13 +217,13 @@ async fn get_currency_to_asset_input_amount_with_unexpected() {
         Err(RpcError::Unexpected(DATA.into())),
     );
     let client = Arc::new(TestApi::new(expectation));
-    let api = Dex::new(client);
+    let api = Dex::new(client, executor());
 
     let error = api
-        .get_currency_to_asset_input_amount(ASSET, TOKEN_AMOUNT, None)
+        .get_currency_to_asset_input_amount(ASSET, TOKEN_AMOUNT.into(), None, None)
         .unwrap_err();
 
-    assert(error, RUNTIME_ERROR, RUNTIME_ERROR_MESSAGE, Some(&DATA))
+    assert(error, RUNTIME_ERROR, RUNTIME_ERROR_MESSAGE, Some(json!(DATA)))
 }
 
 #[tokio::test]
@@ -178,13 +231,13 @@ async fn get_currency_to_asset_input_amount_with_success() {
     let expectation = Expectation::GetCurrencyToAssetInputAmount(ASSET, TOKEN_AMOUNT, Ok(100));
 
     let client = Arc::new(TestApi::new(expectation));
-    let api = Dex::new(client);
+    let api = Dex::new(client, executor());
 
     let result = api
-        .get_currency_to_asset_input_amount(ASSET, TOKEN_AMOUNT, None)
+        .get_currency_to_asset_input_amount(ASSET, TOKEN_AMOUNT.into(), None, None)
         .unwrap();
 
-    assert_eq!(100, result);
+    assert_eq!(RpcBalance(100), result);
 }
 
 #[tokio::test]
@@ -195,13 +248,18 @@ async fn get_asset_to_currency_output_amount_with_exchange_not_found() {
         Err(RpcError::ExchangeNotFound),
     );
     let client = Arc::new(TestApi::new(expectation));
-    let api = Dex::new(client);
+    let api = Dex::new(client, executor());
 
     let error = api
-        .get_asset_to_currency_output_amount(ASSET, TOKEN_AMOUNT, None)
+        .get_asset_to_currency_output_amount(ASSET, TOKEN_AMOUNT.into(), None, None)
         .unwrap_err();
 
-    assert(error, EXCHANGE_NOT_FOUND, EXCHANGE_NOT_FOUND_MESSAGE, None)
+    assert(
+        error,
+        EXCHANGE_NOT_FOUND,
+        EXCHANGE_NOT_FOUND_MESSAGE,
+        Some(json!({ "assetId": ASSET })),
+    )
 }
 
 #[tokio::test]
@@ -212,13 +270,13 @@ async fn get_asset_to_currency_output_amount_with_not_enough_liquidity() {
         Err(RpcError::NotEnoughLiquidity),
     );
     let client = Arc::new(TestApi::new(expectation));
-    let api = Dex::new(client);
+    let api = Dex::new(client, executor());
 
     let error = api
-        .get_asset_to_currency_output_amount(ASSET, TOKEN_AMOUNT, None)
+        .get_asset_to_currency_output_amount(ASSET, TOKEN_AMOUNT.into(), None, None)
         .unwrap_err();
 
-    assert(error, NOT_ENOUGH_LIQUIDITY, NOT_ENOUGH_LIQUIDITY_MESSAGE, None)
+    assert(error, NOT_ENOUGH_LIQUIDITY, NOT_ENOUGH_LIQUIDITY_MESSAGE, Some(amount_data()))
 }
 
 #[tokio::test]
@@ -226,13 +284,13 @@ async fn get_asset_to_currency_output_amount_with_overflow() {
     let expectation =
         Expectation::GetAssetToCurrencyOutputAmount(ASSET, TOKEN_AMOUNT, Err(RpcError::Overflow));
     let client = Arc::new(TestApi::new(expectation));
-    let api = Dex::new(client);
+    let api = Dex::new(client, executor());
 
     let error = api
-        .get_asset_to_currency_output_amount(ASSET, TOKEN_AMOUNT, None)
+        .get_asset_to_currency_output_amount(ASSET, TOKEN_AMOUNT.into(), None, None)
         .unwrap_err();
 
-    assert(error, OVERFLOW, OVERFLOW_MESSAGE, None)
+    assert(error, OVERFLOW, OVERFLOW_MESSAGE, Some(amount_data()))
 }
 
 #[tokio::test]
@@ -243,13 +301,13 @@ async fn get_asset_to_currency_output_amount_with_unexpected() {
         Err(RpcError::Unexpected(DATA.into())),
     );
     let client = Arc::new(TestApi::new(expectation));
-    let api = Dex::new(client);
+    let api = Dex::new(client, executor());
 
     let error = api
-        .get_asset_to_currency_output_amount(ASSET, TOKEN_AMOUNT, None)
+        .get_asset_to_currency_output_amount(ASSET, TOKEN_AMOUNT.into(), None, None)
         .unwrap_err();
 
-    assert(error, RUNTIME_ERROR, RUNTIME_ERROR_MESSAGE, Some(&DATA))
+    assert(error, RUNTIME_ERROR, RUNTIME_ERROR_MESSAGE, Some(json!(DATA)))
 }
 
 #[tokio::test]
@@ -257,13 +315,13 @@ async fn get_asset_to_currency_output_amount_with_success() {
     let expectation = Expectation::GetAssetToCurrencyOutputAmount(ASSET, TOKEN_AMOUNT, Ok(100));
 
     let client = Arc::new(TestApi::new(expectation));
-    let api = Dex::new(client);
+    let api = Dex::new(client, executor());
 
     let result = api
-        .get_asset_to_currency_output_amount(ASSET, TOKEN_AMOUNT, None)
+        .get_asset_to_currency_output_amount(ASSET, TOKEN_AMOUNT.into(), None, None)
         .unwrap();
 
-    assert_eq!(100, result);
+    assert_eq!(RpcBalance(100), result);
 }
 
 #[tokio::test]
@@ -274,13 +332,18 @@ async fn get_asset_to_currency_input_amount_with_exchange_not_found() {
         Err(RpcError::ExchangeNotFound),
     );
     let client = Arc::new(TestApi::new(expectation));
-    let api = Dex::new(client);
+    let api = Dex::new(client, executor());
 
     let error = api
-        .get_asset_to_currency_input_amount(ASSET, CURRENCY_AMOUNT, None)
+        .get_asset_to_currency_input_amount(ASSET, CURRENCY_AMOUNT.into(), None, None)
         .unwrap_err();
 
-    assert(error, EXCHANGE_NOT_FOUND, EXCHANGE_NOT_FOUND_MESSAGE, None)
+    assert(
+        error,
+        EXCHANGE_NOT_FOUND,
+        EXCHANGE_NOT_FOUND_MESSAGE,
+        Some(json!({ "assetId": ASSET })),
+    )
 }
 
 #[tokio::test]
@@ -291,13 +354,13 @@ async fn get_asset_to_currency_input_amount_with_not_enough_liquidity() {
         Err(RpcError::NotEnoughLiquidity),
     );
     let client = Arc::new(TestApi::new(expectation));
-    let api = Dex::new(client);
+    let api = Dex::new(client, executor());
 
     let error = api
-        .get_asset_to_currency_input_amount(ASSET, CURRENCY_AMOUNT, None)
+        .get_asset_to_currency_input_amount(ASSET, CURRENCY_AMOUNT.into(), None, None)
         .unwrap_err();
 
-    assert(error, NOT_ENOUGH_LIQUIDITY, NOT_ENOUGH_LIQUIDITY_MESSAGE, None)
+    assert(error, NOT_ENOUGH_LIQUIDITY, NOT_ENOUGH_LIQUIDITY_MESSAGE, Some(amount_data()))
 }
 
 #[tokio::test]
@@ -305,13 +368,13 @@ async fn get_asset_to_currency_input_amount_with_overflow() {
     let expectation =
         Expectation::GetAssetToCurrencyInputAmount(ASSET, CURRENCY_AMOUNT, Err(RpcError::Overflow));
     let client = Arc::new(TestApi::new(expectation));
-    let api = Dex::new(client);
+    let api = Dex::new(client, executor());
 
     let error = api
-        .get_asset_to_currency_input_amount(ASSET, CURRENCY_AMOUNT, None)
+        .get_asset_to_currency_input_amount(ASSET, CURRENCY_AMOUNT.into(), None, None)
         .unwrap_err();
 
-    assert(error, OVERFLOW, OVERFLOW_MESSAGE, None)
+    assert(error, OVERFLOW, OVERFLOW_MESSAGE, Some(amount_data()))
 }
 
 #[tokio::test]
@@ -322,13 +385,13 @@ async fn get_asset_to_currency_input_amount_with_unexpected() {
         Err(RpcError::Unexpected(DATA.into())),
     );
     let client = Arc::new(TestApi::new(expectation));
-    let api = Dex::new(client);
+    let api = Dex::new(client, executor());
 
     let error = api
-        .get_asset_to_currency_input_amount(ASSET, CURRENCY_AMOUNT, None)
+        .get_asset_to_currency_input_amount(ASSET, CURRENCY_AMOUNT.into(), None, None)
         .unwrap_err();
 
-    assert(error, RUNTIME_ERROR, RUNTIME_ERROR_MESSAGE, Some(&DATA))
+    assert(error, RUNTIME_ERROR, RUNTIME_ERROR_MESSAGE, Some(json!(DATA)))
 }
 
 #[tokio::test]
@@ -336,30 +399,884 @@ async fn get_asset_to_currency_input_amount_with_success() {
     let expectation = Expectation::GetAssetToCurrencyInputAmount(ASSET, CURRENCY_AMOUNT, Ok(100));
 
     let client = Arc::new(TestApi::new(expectation));
-    let api = Dex::new(client);
+    let api = Dex::new(client, executor());
 
     let result = api
-        .get_asset_to_currency_input_amount(ASSET, CURRENCY_AMOUNT, None)
+        .get_asset_to_currency_input_amount(ASSET, CURRENCY_AMOUNT.into(), None, None)
         .unwrap();
 
-    assert_eq!(100, result);
+    assert_eq!(RpcBalance(100), result);
+}
+
+#[tokio::test]
+async fn batch_quote_with_success() {
+    let requests = vec![
+        QuoteRequest::CurrencyToAsset {
+            asset_id: ASSET,
+            amount: QuoteAmount::FixedInput(CURRENCY_AMOUNT.into()),
+        },
+        QuoteRequest::AssetToCurrency {
+            asset_id: ASSET,
+            amount: QuoteAmount::FixedInput(TOKEN_AMOUNT.into()),
+        },
+        QuoteRequest::AssetToAsset {
+            sold_asset_id: ASSET,
+            bought_asset_id: ASSET + 1,
+            amount: QuoteAmount::FixedOutput(TOKEN_AMOUNT.into()),
+        },
+    ];
+    let expectation = Expectation::BatchQuote(
+        requests
+            .iter()
+            .cloned()
+            .map(number::from_rpc_request)
+            .collect(),
+        Ok(vec![
+            Ok(Quote::Asset(99)),
+            Ok(Quote::Currency(98)),
+            Err(RpcError::ExchangeNotFound),
+        ]),
+    );
+    let client = Arc::new(TestApi::new(expectation));
+    let api = Dex::new(client, executor());
+
+    let result = api.batch_quote(requests, None, None).unwrap();
+
+    assert_eq!(
+        result,
+        vec![
+            QuoteResult::Ok(Quote::Asset(RpcBalance(99))),
+            QuoteResult::Ok(Quote::Currency(RpcBalance(98))),
+            QuoteResult::Err {
+                code: EXCHANGE_NOT_FOUND,
+                message: EXCHANGE_NOT_FOUND_MESSAGE.into(),
+                data: None,
+            },
+        ]
+    );
+}
+
+#[tokio::test]
+async fn batch_quote_with_unexpected() {
+    let requests = vec![QuoteRequest::CurrencyToAsset {
+        asset_id: ASSET,
+        amount: QuoteAmount::FixedOutput(TOKEN_AMOUNT.into()),
+    }];
+    let expectation = Expectation::BatchQuote(
+        requests
+            .iter()
+            .cloned()
+            .map(number::from_rpc_request)
+            .collect(),
+        Ok(vec![Err(RpcError::Unexpected(DATA.into()))]),
+    );
+    let client = Arc::new(TestApi::new(expectation));
+    let api = Dex::new(client, executor());
+
+    let result = api.batch_quote(requests, None, None).unwrap();
+
+    assert_eq!(
+        result,
+        vec![QuoteResult::Err {
+            code: RUNTIME_ERROR,
+            message: RUNTIME_ERROR_MESSAGE.into(),
+            data: Some(json!(DATA)),
+        }]
+    );
+}
+
+#[tokio::test]
+async fn batch_quote_with_too_many_requests() {
+    let requests = vec![
+        QuoteRequest::CurrencyToAsset {
+            asset_id: ASSET,
+            amount: QuoteAmount::FixedInput(CURRENCY_AMOUNT.into()),
+        };
+        MAX_BATCH_QUOTES as usize + 1
+    ];
+    let expectation = Expectation::BatchQuote(vec![], Ok(vec![]));
+    let client = Arc::new(TestApi::new(expectation));
+    let api = Dex::new(client, executor());
+
+    let error = api.batch_quote(requests, None, None).unwrap_err();
+
+    assert!(matches!(error, Error::Call(CallError::Custom(e))
+        if e.code() == BATCH_TOO_LARGE
+            && e.data().map(|v| v.get().to_string()) == Some(MAX_BATCH_QUOTES.to_string())));
+}
+
+#[test]
+fn error_codes_are_unique() {
+    let errors = [
+        RpcError::ExchangeNotFound,
+        RpcError::NotEnoughLiquidity,
+        RpcError::Overflow,
+        RpcError::Unexpected(vec![]),
+        RpcError::AssetNotFound,
+        RpcError::ExchangeAlreadyExists,
+        RpcError::TokenIdTaken,
+        RpcError::BalanceTooLow,
+        RpcError::NotEnoughTokens,
+        RpcError::ProviderLiquidityTooLow,
+        RpcError::TradeAmountIsZero,
+        RpcError::TokenAmountIsZero,
+        RpcError::MaxTokensIsZero,
+        RpcError::CurrencyAmountIsZero,
+        RpcError::CurrencyAmountTooHigh,
+        RpcError::CurrencyAmountTooLow,
+        RpcError::MinLiquidityIsZero,
+        RpcError::MaxTokensTooLow,
+        RpcError::MinLiquidityTooHigh,
+        RpcError::LiquidityAmountIsZero,
+        RpcError::MinCurrencyIsZero,
+        RpcError::MinTokensIsZero,
+        RpcError::MinCurrencyTooHigh,
+        RpcError::MinTokensTooHigh,
+        RpcError::MaxCurrencyTooLow,
+        RpcError::MinBoughtTokensTooHigh,
+        RpcError::MaxSoldTokensTooLow,
+        RpcError::Underflow,
+        RpcError::DeadlinePassed,
+        RpcError::RouteNotFound,
+        RpcError::LimitPriceIsZero,
+        RpcError::LimitOrderNotFound,
+        RpcError::NotLimitOrderOwner,
+        RpcError::DcaIntervalIsZero,
+        RpcError::DcaExecutionsIsZero,
+        RpcError::DcaQueueFull,
+        RpcError::DcaScheduleNotFound,
+        RpcError::NotDcaScheduleOwner,
+        RpcError::BatchFull,
+        RpcError::BatchAuctionEnabled,
+        RpcError::CommitmentAlreadyExists,
+        RpcError::CommitmentNotFound,
+        RpcError::TooManyCommitments,
+        RpcError::InvalidSignature,
+        RpcError::InvalidIntentNonce,
+        RpcError::TipTooHigh,
+        RpcError::TimestampDeadlineUnsupported,
+        RpcError::MaxCurrencyIsZero,
+        RpcError::InvalidAmplification,
+        RpcError::NotStableSwapExchange,
+        RpcError::InvalidRampEnd,
+        RpcError::UnsupportedCurve,
+        RpcError::InvalidWeight,
+        RpcError::InvalidWeightSchedule,
+        RpcError::InvalidTickSpacing,
+        RpcError::InvalidPrice,
+        RpcError::InvalidTickRange,
+        RpcError::TooManyTicks,
+        RpcError::TooManyTicksCrossed,
+        RpcError::PositionNotFound,
+        RpcError::PositionLiquidityTooLow,
+        RpcError::PositionLiquidityIsZero,
+        RpcError::InvalidFeeRange,
+        RpcError::OperationNotAllowed,
+        RpcError::ProviderNotApproved,
+        RpcError::CurrencyBelowMinimum,
+        RpcError::BatchTooLarge,
+        RpcError::AmountTokenMismatch,
+        RpcError::TipTokenMismatch,
+        RpcError::InstanceNotFound,
+    ];
+    let mut codes: Vec<i32> = errors.iter().map(RpcErrorInfo::code).collect();
+    codes.extend([TOO_MANY_EXCHANGES, UNSUPPORTED_BY_RUNTIME]);
+    let count = codes.len();
+    codes.sort();
+    codes.dedup();
+    assert_eq!(codes.len(), count);
+}
+
+#[tokio::test]
+async fn get_currency_to_asset_output_amount_with_deadline_passed() {
+    let expectation = Expectation::GetCurrencyToAssetOutputAmount(
+        ASSET,
+        CURRENCY_AMOUNT,
+        Err(RpcError::DeadlinePassed),
+    );
+    let client = Arc::new(TestApi::new(expectation));
+    let api = Dex::new(client, executor());
+
+    let error = api
+        .get_currency_to_asset_output_amount(ASSET, CURRENCY_AMOUNT.into(), None, None)
+        .unwrap_err();
+
+    assert(error, error::DEADLINE_PASSED, "Deadline passed", None)
+}
+
+#[test]
+fn batch_quote_request_serialization() {
+    let requests: Vec<QuoteRequest<AssetId, RpcBalance<Balance>, RpcBalance<AssetBalance>>> =
+        serde_json::from_str(
+            r#"[
+                {"currencyToAsset": {"assetId": 1, "amount": {"fixedInput": 100}}},
+                {"assetToCurrency": {"assetId": 1, "amount": {"fixedOutput": "340282366920938463463374607431768211455"}}},
+                {"assetToAsset": {"soldAssetId": 1, "boughtAssetId": 2, "amount": {"fixedOutput": "0x64"}}}
+            ]"#,
+        )
+        .unwrap();
+
+    assert_eq!(
+        requests,
+        vec![
+            QuoteRequest::CurrencyToAsset {
+                asset_id: 1,
+                amount: QuoteAmount::FixedInput(RpcBalance(100)),
+            },
+            QuoteRequest::AssetToCurrency {
+                asset_id: 1,
+                amount: QuoteAmount::FixedOutput(RpcBalance(u128::MAX)),
+            },
+            QuoteRequest::AssetToAsset {
+                sold_asset_id: 1,
+                bought_asset_id: 2,
+                amount: QuoteAmount::FixedOutput(RpcBalance(100)),
+            },
+        ]
+    );
+}
+
+#[test]
+fn batch_quote_result_serialization() {
+    let results: Vec<QuoteResult<RpcBalance<Balance>, RpcBalance<AssetBalance>>> = vec![
+        QuoteResult::Ok(Quote::Asset(RpcBalance(100))),
+        QuoteResult::Ok(Quote::Currency(RpcBalance(u128::MAX))),
+        QuoteResult::Err {
+            code: EXCHANGE_NOT_FOUND,
+            message: EXCHANGE_NOT_FOUND_MESSAGE.into(),
+            data: None,
+        },
+    ];
+
+    assert_eq!(
+        serde_json::to_string(&results).unwrap(),
+        concat!(
+            r#"[{"ok":{"asset":"100"}},"#,
+            r#"{"ok":{"currency":"340282366920938463463374607431768211455"}},"#,
+            r#"{"err":{"code":2,"message":"Exchange not found","data":null}}]"#
+        )
+    );
+}
+
+#[tokio::test]
+async fn find_best_route_with_success() {
+    let route = Route {
+        path: vec![
+            Token::Asset(ASSET),
+            Token::Currency,
+            Token::Asset(ASSET + 1),
+        ],
+        amount_out: TokenAmount::Asset(98),
+    };
+    let expectation = Expectation::FindBestRoute(
+        Token::Asset(ASSET),
+        Token::Asset(ASSET + 1),
+        TokenAmount::Asset(TOKEN_AMOUNT),
+        Ok(route),
+    );
+    let client = Arc::new(TestApi::new(expectation));
+    let api = Dex::new(client, executor());
+
+    let result = api
+        .find_best_route(
+            Token::Asset(ASSET),
+            Token::Asset(ASSET + 1),
+            TokenAmount::Asset(TOKEN_AMOUNT.into()),
+            None,
+            None,
+        )
+        .unwrap();
+
+    assert_eq!(
+        result,
+        Route {
+            path: vec![
+                Token::Asset(ASSET),
+                Token::Currency,
+                Token::Asset(ASSET + 1)
+            ],
+            amount_out: TokenAmount::Asset(RpcBalance(98)),
+        }
+    );
+}
+
+#[tokio::test]
+async fn find_best_route_with_route_not_found() {
+    let expectation = Expectation::FindBestRoute(
+        Token::Currency,
+        Token::Asset(ASSET),
+        TokenAmount::Currency(CURRENCY_AMOUNT),
+        Err(RpcError::RouteNotFound),
+    );
+    let client = Arc::new(TestApi::new(expectation));
+    let api = Dex::new(client, executor());
+
+    let error = api
+        .find_best_route(
+            Token::Currency,
+            Token::Asset(ASSET),
+            TokenAmount::Currency(CURRENCY_AMOUNT.into()),
+            None,
+            None,
+        )
+        .unwrap_err();
+
+    assert(error, error::ROUTE_NOT_FOUND, "Route not found", None)
+}
+
+#[tokio::test]
+async fn find_best_route_with_amount_token_mismatch() {
+    let expectation = Expectation::FindBestRoute(
+        Token::Currency,
+        Token::Asset(ASSET),
+        TokenAmount::Asset(TOKEN_AMOUNT),
+        Err(RpcError::AmountTokenMismatch),
+    );
+    let client = Arc::new(TestApi::new(expectation));
+    let api = Dex::new(client, executor());
+
+    let error = api
+        .find_best_route(
+            Token::Currency,
+            Token::Asset(ASSET),
+            TokenAmount::Asset(TOKEN_AMOUNT.into()),
+            None,
+            None,
+        )
+        .unwrap_err();
+
+    assert(error, error::AMOUNT_TOKEN_MISMATCH, "Amount token mismatch", None)
+}
+
+#[test]
+fn route_serialization() {
+    let route = Route {
+        path: vec![Token::Currency, Token::Asset(ASSET)],
+        amount_out: TokenAmount::Asset(RpcBalance(100u64)),
+    };
+
+    let json = serde_json::to_string(&route).unwrap();
+
+    assert_eq!(json, r#"{"path":["currency",{"asset":1}],"amountOut":{"asset":"100"}}"#);
+    assert_eq!(
+        serde_json::from_str::<Route<AssetId, RpcBalance<Balance>, RpcBalance<AssetBalance>>>(
+            &json
+        )
+        .unwrap(),
+        route
+    );
+}
+
+#[tokio::test]
+async fn quote_amounts_as_numbers_or_strings() {
+    let expectation = Expectation::GetCurrencyToAssetOutputAmount(ASSET, u128::MAX, Ok(100));
+    let client = Arc::new(TestApi::new(expectation));
+    let api = Dex::new(client, executor()).into_rpc();
+    let method = "dex_get_currency_to_asset_output_amount";
+
+    let decimal: String = api
+        .call(method, jsonrpsee::rpc_params![ASSET, u128::MAX.to_string()])
+        .await
+        .unwrap();
+    let hex: String = api
+        .call(method, jsonrpsee::rpc_params![ASSET, format!("{:#x}", u128::MAX)])
+        .await
+        .unwrap();
+
+    assert_eq!(decimal, "100");
+    assert_eq!(hex, "100");
+}
+
+#[tokio::test]
+async fn quote_amount_as_number_is_backwards_compatible() {
+    let expectation = Expectation::GetCurrencyToAssetOutputAmount(ASSET, CURRENCY_AMOUNT, Ok(100));
+    let client = Arc::new(TestApi::new(expectation));
+    let api = Dex::new(client, executor()).into_rpc();
+
+    let result: RpcBalance<AssetBalance> = api
+        .call(
+            "dex_get_currency_to_asset_output_amount",
+            jsonrpsee::rpc_params![ASSET, CURRENCY_AMOUNT],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result, RpcBalance(100));
+}
+
+#[tokio::test]
+async fn subscribe_exchange_with_changed_state() {
+    let (block_1, block_2, block_3) = (header(1), header(2), header(3));
+    let (state_1, state_2) = (exchange_state(1_000, 1_000), exchange_state(1_100, 910));
+    let expectation = Expectation::GetExchangeState(vec![
+        (block_1.hash(), ASSET, Ok(state_1.clone())),
+        (block_2.hash(), ASSET, Ok(state_1.clone())),
+        (block_3.hash(), ASSET, Ok(state_2.clone())),
+    ]);
+    let client = Arc::new(TestApi::new(expectation));
+    let api = Dex::new(client.clone(), executor()).into_rpc();
+    let mut subscription = api
+        .subscribe("dex_subscribeExchange", [vec![ASSET]])
+        .await
+        .unwrap();
+
+    client.import(&block_1, true, &[ASSET]);
+    // Block 2 doesn't change the exchange, so no update should be pushed
+    client.import(&block_2, true, &[]);
+    client.import(&block_3, true, &[ASSET]);
+
+    let (update, _) = subscription.next::<Update>().await.unwrap().unwrap();
+    assert_eq!(
+        update,
+        ExchangeUpdate {
+            block: block_1.hash(),
+            finalized: false,
+            asset_id: ASSET,
+            state: number::to_rpc_exchange_state(state_1.clone()),
+        }
+    );
+    let (update, _) = subscription.next::<Update>().await.unwrap().unwrap();
+    assert_eq!(
+        update,
+        ExchangeUpdate {
+            block: block_3.hash(),
+            finalized: false,
+            asset_id: ASSET,
+            state: number::to_rpc_exchange_state(state_2.clone()),
+        }
+    );
+    // The runtime isn't even queried for the state in block 2
+    assert!(!client.state_queries().contains(&(block_2.hash(), ASSET)));
+
+    client.finalize(&[&block_1, &block_2]);
+
+    let (update, _) = subscription.next::<Update>().await.unwrap().unwrap();
+    assert_eq!(
+        update,
+        ExchangeUpdate {
+            block: block_2.hash(),
+            finalized: true,
+            asset_id: ASSET,
+            state: number::to_rpc_exchange_state(state_1),
+        }
+    );
+
+    client.finalize(&[&block_3]);
+
+    let (update, _) = subscription.next::<Update>().await.unwrap().unwrap();
+    assert_eq!(
+        update,
+        ExchangeUpdate {
+            block: block_3.hash(),
+            finalized: true,
+            asset_id: ASSET,
+            state: number::to_rpc_exchange_state(state_2),
+        }
+    );
+}
+
+#[tokio::test]
+async fn subscribe_exchange_ignores_finalized_blocks_without_changes() {
+    let (block_1, block_2) = (header(1), header(2));
+    let state = exchange_state(1_000, 1_000);
+    let expectation = Expectation::GetExchangeState(vec![
+        (block_1.hash(), ASSET, Ok(state.clone())),
+        (block_2.hash(), ASSET, Ok(state.clone())),
+    ]);
+    let client = Arc::new(TestApi::new(expectation));
+    let api = Dex::new(client.clone(), executor()).into_rpc();
+    let mut subscription = api
+        .subscribe("dex_subscribeExchange", [vec![ASSET]])
+        .await
+        .unwrap();
+
+    client.import(&block_1, true, &[ASSET + 1]);
+    client.import(&block_2, true, &[]);
+    client.finalize(&[&block_1, &block_2]);
+
+    let next =
+        tokio::time::timeout(Duration::from_millis(100), subscription.next::<Update>()).await;
+    assert!(next.is_err());
+    assert!(client
+        .state_queries()
+        .iter()
+        .all(|(block, _)| *block == H256::default()));
+}
+
+#[tokio::test]
+async fn subscribe_exchange_with_finalized_block_never_best() {
+    let (block_1, block_2) = (header(1), header(2));
+    let (state_1, state_2) = (exchange_state(1_000, 1_000), exchange_state(1_100, 910));
+    let expectation = Expectation::GetExchangeState(vec![
+        (block_1.hash(), ASSET, Ok(state_1.clone())),
+        (block_2.hash(), ASSET, Ok(state_2.clone())),
+    ]);
+    let client = Arc::new(TestApi::new(expectation));
+    let api = Dex::new(client.clone(), executor()).into_rpc();
+    let mut subscription = api
+        .subscribe("dex_subscribeExchange", [vec![ASSET]])
+        .await
+        .unwrap();
+
+    client.import(&block_1, true, &[ASSET]);
+
+    let (update, _) = subscription.next::<Update>().await.unwrap().unwrap();
+    assert_eq!(
+        update,
+        ExchangeUpdate {
+            block: block_1.hash(),
+            finalized: false,
+            asset_id: ASSET,
+            state: number::to_rpc_exchange_state(state_1),
+        }
+    );
+
+    // Block 2 is on another fork, so its storage changes are never notified
+    client.import(&block_2, false, &[ASSET]);
+    client.finalize(&[&block_2]);
+
+    let (update, _) = subscription.next::<Update>().await.unwrap().unwrap();
+    assert_eq!(
+        update,
+        ExchangeUpdate {
+            block: block_2.hash(),
+            finalized: true,
+            asset_id: ASSET,
+            state: number::to_rpc_exchange_state(state_2),
+        }
+    );
+}
+
+#[tokio::test]
+async fn subscribe_exchange_pushes_current_state() {
+    let block = header(1);
+    let (state_1, state_2) = (exchange_state(1_000, 1_000), exchange_state(1_100, 910));
+    // The mock client's best block has the default hash
+    let expectation = Expectation::GetExchangeState(vec![
+        (H256::default(), ASSET, Ok(state_1.clone())),
+        (block.hash(), ASSET, Ok(state_2.clone())),
+    ]);
+    let client = Arc::new(TestApi::new(expectation));
+    let api = Dex::new(client.clone(), executor()).into_rpc();
+    let mut subscription = api
+        .subscribe("dex_subscribeExchange", [vec![ASSET]])
+        .await
+        .unwrap();
+
+    let (update, _) = subscription.next::<Update>().await.unwrap().unwrap();
+    assert_eq!(
+        update,
+        ExchangeUpdate {
+            block: H256::default(),
+            finalized: false,
+            asset_id: ASSET,
+            state: number::to_rpc_exchange_state(state_1),
+        }
+    );
+
+    client.import(&block, true, &[ASSET]);
+
+    let (update, _) = subscription.next::<Update>().await.unwrap().unwrap();
+    assert_eq!(
+        update,
+        ExchangeUpdate {
+            block: block.hash(),
+            finalized: false,
+            asset_id: ASSET,
+            state: number::to_rpc_exchange_state(state_2),
+        }
+    );
+}
+
+#[tokio::test]
+async fn subscribe_exchange_ignores_non_best_blocks_and_missing_exchanges() {
+    let (block_1, block_2) = (header(1), header(2));
+    let state = exchange_state(1_000, 1_000);
+    let expectation = Expectation::GetExchangeState(vec![
+        (block_1.hash(), ASSET, Ok(state.clone())),
+        (block_2.hash(), ASSET, Ok(state.clone())),
+    ]);
+    let client = Arc::new(TestApi::new(expectation));
+    let api = Dex::new(client.clone(), executor()).into_rpc();
+    let mut subscription = api
+        .subscribe("dex_subscribeExchange", [vec![ASSET + 1, ASSET]])
+        .await
+        .unwrap();
+
+    client.import(&block_1, false, &[ASSET + 1, ASSET]);
+    client.import(&block_2, true, &[ASSET + 1, ASSET]);
+
+    let (update, _) = subscription.next::<Update>().await.unwrap().unwrap();
+    assert_eq!(
+        update,
+        ExchangeUpdate {
+            block: block_2.hash(),
+            finalized: false,
+            asset_id: ASSET,
+            state: number::to_rpc_exchange_state(state),
+        }
+    );
+    let next =
+        tokio::time::timeout(Duration::from_millis(100), subscription.next::<Update>()).await;
+    assert!(next.is_err());
+}
+
+#[tokio::test]
+async fn subscribe_exchange_with_too_many_exchanges() {
+    let client = Arc::new(TestApi::new(Expectation::GetExchangeState(vec![])));
+    let api = Dex::new(client, executor()).into_rpc();
+
+    let asset_ids: Vec<AssetId> = (0..=MAX_SUBSCRIBED_EXCHANGES as AssetId).collect();
+    let error = api
+        .subscribe("dex_subscribeExchange", [asset_ids])
+        .await
+        .unwrap_err();
+
+    assert!(matches!(error, Error::Call(CallError::Custom(e)) if e.code() == TOO_MANY_EXCHANGES));
+}
+
+#[tokio::test]
+async fn get_currency_to_asset_output_amount_on_instance() {
+    let expectation = Expectation::GetCurrencyToAssetOutputAmount(ASSET, CURRENCY_AMOUNT, Ok(100));
+    let client = Arc::new(TestApi::with_instance(expectation, Some(STABLE_INSTANCE)));
+    let api = Dex::new(client, executor()).into_rpc();
+
+    let result: RpcBalance<AssetBalance> = api
+        .call(
+            "dex_get_currency_to_asset_output_amount",
+            jsonrpsee::rpc_params![ASSET, CURRENCY_AMOUNT, Option::<H256>::None, "dex_stbl"],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(result, RpcBalance(100));
+}
+
+#[tokio::test]
+async fn get_currency_to_asset_output_amount_with_instance_not_found() {
+    let expectation = Expectation::GetCurrencyToAssetOutputAmount(ASSET, CURRENCY_AMOUNT, Ok(100));
+    let client = Arc::new(TestApi::new(expectation));
+    let api = Dex::new(client, executor());
+
+    let error = api
+        .get_currency_to_asset_output_amount(
+            ASSET,
+            CURRENCY_AMOUNT.into(),
+            None,
+            Some(STABLE_INSTANCE.into()),
+        )
+        .unwrap_err();
+
+    assert(error, INSTANCE_NOT_FOUND, "DEX instance not found", None)
+}
+
+#[tokio::test]
+async fn batch_quote_on_instance() {
+    let requests = vec![QuoteRequest::CurrencyToAsset {
+        asset_id: ASSET,
+        amount: QuoteAmount::FixedInput(CURRENCY_AMOUNT.into()),
+    }];
+    let expectation = Expectation::BatchQuote(
+        requests
+            .iter()
+            .cloned()
+            .map(number::from_rpc_request)
+            .collect(),
+        Ok(vec![Ok(Quote::Asset(99))]),
+    );
+    let client = Arc::new(TestApi::with_instance(expectation, Some(STABLE_INSTANCE)));
+    let api = Dex::new(client, executor());
+
+    let default_instance = api.batch_quote(requests.clone(), None, None).unwrap_err();
+    let result = api
+        .batch_quote(requests, None, Some(STABLE_INSTANCE.into()))
+        .unwrap();
+
+    assert(default_instance, INSTANCE_NOT_FOUND, "DEX instance not found", None);
+    assert_eq!(result, vec![QuoteResult::Ok(Quote::Asset(RpcBalance(99)))]);
+}
+
+#[tokio::test]
+async fn subscribe_exchange_on_instance() {
+    let state = exchange_state(1_000, 1_000);
+    let expectation =
+        Expectation::GetExchangeState(vec![(H256::default(), ASSET, Ok(state.clone()))]);
+    let client = Arc::new(TestApi::with_instance(expectation, Some(STABLE_INSTANCE)));
+    let api = Dex::new(client, executor()).into_rpc();
+
+    let error = api
+        .subscribe("dex_subscribeExchange", [vec![ASSET]])
+        .await
+        .unwrap_err();
+    let mut subscription = api
+        .subscribe("dex_subscribeExchange", jsonrpsee::rpc_params![vec![ASSET], "dex_stbl"])
+        .await
+        .unwrap();
+
+    assert!(matches!(error, Error::Call(CallError::Custom(e)) if e.code() == INSTANCE_NOT_FOUND));
+    let (update, _) = subscription.next::<Update>().await.unwrap().unwrap();
+    assert_eq!(
+        update,
+        ExchangeUpdate {
+            block: H256::default(),
+            finalized: false,
+            asset_id: ASSET,
+            state: number::to_rpc_exchange_state(state),
+        }
+    );
+}
+
+#[test]
+fn pallet_id_serialization() {
+    let printable = RpcPalletId(STABLE_INSTANCE);
+    let binary = RpcPalletId(PalletId([0, 1, 2, 3, 4, 5, 6, 0xff]));
+
+    assert_eq!(serde_json::to_string(&printable).unwrap(), r#""dex_stbl""#);
+    assert_eq!(serde_json::to_string(&binary).unwrap(), r#""0x00010203040506ff""#);
+    assert_eq!(serde_json::from_str::<RpcPalletId>(r#""0x6465785f7374626c""#).unwrap(), printable);
+    assert_eq!(serde_json::from_str::<RpcPalletId>(r#""0x00010203040506ff""#).unwrap(), binary);
+    assert!(serde_json::from_str::<RpcPalletId>(r#""dex""#).is_err());
+    assert!(serde_json::from_str::<RpcPalletId>(r#""0x0001""#).is_err());
 }
 
 mod mock {
     use crate::tests::{AssetBalance, AssetId, Balance, RpcResult};
-    use pallet_dex_rpc_runtime_api::DexApi as DexRuntimeApi;
-    use sp_api::{ApiRef, ProvideRuntimeApi};
+    use codec::Encode;
+    use pallet_dex::rpc::{
+        ExchangeState, Quote, QuoteRequest, Route, RpcError, Token, TokenAmount,
+    };
+    use pallet_dex_rpc_runtime_api::{DexApi as DexRuntimeApi, PalletId};
+    use sc_client_api::{
+        notifications::StorageNotifications, BlockImportNotification, BlockchainEvents,
+        FinalityNotification, FinalityNotifications, FinalizeSummary, ImportNotifications,
+        StorageEventStream,
+    };
+    use sc_utils::mpsc::{tracing_unbounded, TracingUnboundedReceiver, TracingUnboundedSender};
+    use sp_api::{ApiError, ApiRef, ProvideRuntimeApi};
     use sp_blockchain::HeaderBackend;
-    use sp_runtime::traits::{Block as BlockT, NumberFor, Zero};
-    use substrate_test_runtime_client::runtime::Block;
+    use sp_consensus::BlockOrigin;
+    use sp_core::storage::StorageKey;
+    use sp_runtime::traits::{Block as BlockT, Header as HeaderT, NumberFor, Zero};
+    use std::sync::{Arc, Mutex};
+    use substrate_test_runtime_client::runtime::{Block, Header};
+
+    type Hash = <Block as BlockT>::Hash;
+
+    /// Storage key of an exchange, as returned by the mock runtime API.
+    pub(super) fn exchange_key(asset_id: AssetId) -> Vec<u8> {
+        (b"exchange", asset_id).encode()
+    }
 
     pub struct TestApi {
         pub(super) expectation: Expectation,
+        /// The only DEX instance served by the mock runtime API besides the default one.
+        instance: Option<PalletId>,
+        /// Blocks and assets for which the exchange state was queried.
+        state_queries: Arc<Mutex<Vec<(Hash, AssetId)>>>,
+        import_sinks: Mutex<Vec<TracingUnboundedSender<BlockImportNotification<Block>>>>,
+        finality_sinks: Mutex<Vec<TracingUnboundedSender<FinalityNotification<Block>>>>,
+        storage_notifications: StorageNotifications<Block>,
+        unpin_sender: TracingUnboundedSender<Hash>,
+        _unpin_receiver: TracingUnboundedReceiver<Hash>,
     }
 
     impl TestApi {
         pub(super) fn new(expectation: Expectation) -> Self {
-            Self { expectation }
+            Self::with_instance(expectation, None)
+        }
+
+        /// Expect the calls to select the given DEX instance.
+        pub(super) fn with_instance(expectation: Expectation, instance: Option<PalletId>) -> Self {
+            let (unpin_sender, _unpin_receiver) = tracing_unbounded("test-unpin", 100_000);
+            Self {
+                expectation,
+                instance,
+                state_queries: Default::default(),
+                import_sinks: Default::default(),
+                finality_sinks: Default::default(),
+                storage_notifications: StorageNotifications::new(None),
+                unpin_sender,
+                _unpin_receiver,
+            }
+        }
+
+        pub(super) fn state_queries(&self) -> Vec<(Hash, AssetId)> {
+            self.state_queries.lock().unwrap().clone()
+        }
+
+        /// Emit a block import notification. Storage changes of a new best block, i.e. the
+        /// exchanges of `changed_assets`, are notified as well.
+        pub(super) fn import(
+            &self,
+            header: &Header,
+            is_new_best: bool,
+            changed_assets: &[AssetId],
+        ) {
+            if is_new_best {
+                let changes = changed_assets
+                    .iter()
+                    .map(|asset_id| (exchange_key(*asset_id), Some(vec![])));
+                self.storage_notifications.trigger(
+                    &header.hash(),
+                    changes,
+                    std::iter::empty::<(Vec<u8>, std::iter::Empty<(Vec<u8>, Option<Vec<u8>>)>)>(),
+                );
+            }
+            let notification = BlockImportNotification::new(
+                header.hash(),
+                BlockOrigin::Own,
+                header.clone(),
+                is_new_best,
+                None,
+                self.unpin_sender.clone(),
+            );
+            self.import_sinks
+                .lock()
+                .unwrap()
+                .retain(|sink| sink.unbounded_send(notification.clone()).is_ok());
+        }
+
+        /// Emit a block finality notification, finalizing `headers` up to the last one.
+        pub(super) fn finalize(&self, headers: &[&Header]) {
+            let summary = FinalizeSummary {
+                header: (*headers.last().expect("At least one block is finalized")).clone(),
+                finalized: headers.iter().map(|header| header.hash()).collect(),
+                stale_heads: vec![],
+            };
+            let notification =
+                FinalityNotification::from_summary(summary, self.unpin_sender.clone());
+            self.finality_sinks
+                .lock()
+                .unwrap()
+                .retain(|sink| sink.unbounded_send(notification.clone()).is_ok());
+        }
+    }
+
+    impl BlockchainEvents<Block> for TestApi {
+        fn import_notification_stream(&self) -> ImportNotifications<Block> {
+            let (sink, stream) = tracing_unbounded("test-import", 100_000);
+            self.import_sinks.lock().unwrap().push(sink);
+            stream
+        }
+
+        fn every_import_notification_stream(&self) -> ImportNotifications<Block> {
+            self.import_notification_stream()
+        }
+
+        fn finality_notification_stream(&self) -> FinalityNotifications<Block> {
+            let (sink, stream) = tracing_unbounded("test-finality", 100_000);
+            self.finality_sinks.lock().unwrap().push(sink);
+            stream
+        }
+
+        fn storage_changes_notification_stream(
+            &self,
+            filter_keys: Option<&[StorageKey]>,
+            child_filter_keys: Option<&[(StorageKey, Option<Vec<StorageKey>>)]>,
+        ) -> sp_blockchain::Result<StorageEventStream<Hash>> {
+            Ok(self
+                .storage_notifications
+                .listen(filter_keys, child_filter_keys))
         }
     }
 
@@ -369,6 +1286,8 @@ mod mock {
         fn runtime_api(&self) -> ApiRef<Self::Api> {
             TestRuntimeApi {
                 call: self.expectation.clone(),
+                instance: self.instance,
+                state_queries: self.state_queries.clone(),
             }
             .into()
         }
@@ -419,12 +1338,25 @@ mod mock {
 
     pub struct TestRuntimeApi {
         pub(super) call: Expectation,
+        instance: Option<PalletId>,
+        state_queries: Arc<Mutex<Vec<(Hash, AssetId)>>>,
+    }
+
+    impl TestRuntimeApi {
+        /// Fail with `InstanceNotFound` unless the expected DEX instance is selected.
+        fn select(&self, instance: Option<PalletId>) -> RpcResult<()> {
+            match instance == self.instance {
+                true => Ok(()),
+                false => Err(RpcError::InstanceNotFound),
+            }
+        }
     }
 
     sp_api::mock_impl_runtime_apis! {
         // A simple mock implementation to compare provided values with expected
         impl DexRuntimeApi<Block, AssetId, Balance, AssetBalance> for TestRuntimeApi {
-            fn get_currency_to_asset_output_amount(asset_id: AssetId, currency_amount: Balance) -> RpcResult<AssetBalance> {
+            fn get_currency_to_asset_output_amount(instance: Option<PalletId>, asset_id: AssetId, currency_amount: Balance) -> RpcResult<AssetBalance> {
+                self.select(instance)?;
                 match &self.call {
                     Expectation::GetCurrencyToAssetOutputAmount ( expected_asset, expected_amount, result)
                         if asset_id == *expected_asset && currency_amount == *expected_amount => result.clone(),
@@ -432,7 +1364,8 @@ mod mock {
                 }
             }
 
-            fn get_currency_to_asset_input_amount(asset_id: AssetId, token_amount: AssetBalance) -> RpcResult<Balance> {
+            fn get_currency_to_asset_input_amount(instance: Option<PalletId>, asset_id: AssetId, token_amount: AssetBalance) -> RpcResult<Balance> {
+                self.select(instance)?;
                 match &self.call {
                     Expectation::GetCurrencyToAssetInputAmount ( expected_asset, expected_amount, result)
                         if asset_id == *expected_asset && token_amount == *expected_amount => result.clone(),
@@ -440,7 +1373,8 @@ mod mock {
                 }
             }
 
-            fn get_asset_to_currency_output_amount(asset_id: AssetId, token_amount: AssetBalance) -> RpcResult<Balance>{
+            fn get_asset_to_currency_output_amount(instance: Option<PalletId>, asset_id: AssetId, token_amount: AssetBalance) -> RpcResult<Balance>{
+                self.select(instance)?;
                 match &self.call {
                     Expectation::GetAssetToCurrencyOutputAmount ( expected_asset, expected_amount, result)
                         if asset_id == *expected_asset && token_amount == *expected_amount => result.clone(),
@@ -448,13 +1382,53 @@ mod mock {
                 }
             }
 
-            fn get_asset_to_currency_input_amount(asset_id: AssetId, currency_amount: Balance) -> RpcResult<AssetBalance>{
+            fn get_asset_to_currency_input_amount(instance: Option<PalletId>, asset_id: AssetId, currency_amount: Balance) -> RpcResult<AssetBalance>{
+                self.select(instance)?;
                 match &self.call {
                     Expectation::GetAssetToCurrencyInputAmount ( expected_asset, expected_amount, result)
                         if asset_id == *expected_asset && currency_amount == *expected_amount => result.clone(),
                     _ => panic!()
                 }
             }
+
+            #[advanced]
+            fn get_exchange_state(&self, at: Hash, instance: Option<PalletId>, asset_id: AssetId) -> Result<RpcResult<ExchangeState<Balance, AssetBalance>>, ApiError> {
+                if let Err(err) = self.select(instance) {
+                    return Ok(Err(err));
+                }
+                self.state_queries.lock().unwrap().push((at, asset_id));
+                match &self.call {
+                    Expectation::GetExchangeState(states) => Ok(states
+                        .iter()
+                        .find(|(hash, id, _)| *hash == at && *id == asset_id)
+                        .map(|(_, _, state)| state.clone())
+                        .unwrap_or(Err(RpcError::ExchangeNotFound))),
+                    _ => panic!()
+                }
+            }
+
+            fn exchange_storage_key(instance: Option<PalletId>, asset_id: AssetId) -> RpcResult<Vec<u8>> {
+                self.select(instance)?;
+                Ok(exchange_key(asset_id))
+            }
+
+            fn batch_quote(instance: Option<PalletId>, requests: Vec<QuoteRequest<AssetId, Balance, AssetBalance>>) -> RpcResult<Vec<RpcResult<Quote<Balance, AssetBalance>>>> {
+                self.select(instance)?;
+                match &self.call {
+                    Expectation::BatchQuote ( expected_requests, results)
+                        if requests == *expected_requests => results.clone(),
+                    _ => panic!()
+                }
+            }
+
+            fn find_best_route(instance: Option<PalletId>, from: Token<AssetId>, to: Token<AssetId>, amount: TokenAmount<Balance, AssetBalance>) -> RpcResult<Route<AssetId, Balance, AssetBalance>> {
+                self.select(instance)?;
+                match &self.call {
+                    Expectation::FindBestRoute ( expected_from, expected_to, expected_amount, result)
+                        if from == *expected_from && to == *expected_to && amount == *expected_amount => result.clone(),
+                    _ => panic!()
+                }
+            }
         }
     }
 
@@ -465,5 +1439,16 @@ mod mock {
         GetCurrencyToAssetInputAmount(AssetId, AssetBalance, RpcResult<Balance>),
         GetAssetToCurrencyOutputAmount(AssetId, AssetBalance, RpcResult<Balance>),
         GetAssetToCurrencyInputAmount(AssetId, Balance, RpcResult<AssetBalance>),
+        GetExchangeState(Vec<(Hash, AssetId, RpcResult<ExchangeState<Balance, AssetBalance>>)>),
+        BatchQuote(
+            Vec<QuoteRequest<AssetId, Balance, AssetBalance>>,
+            RpcResult<Vec<RpcResult<Quote<Balance, AssetBalance>>>>,
+        ),
+        FindBestRoute(
+            Token<AssetId>,
+            Token<AssetId>,
+            TokenAmount<Balance, AssetBalance>,
+            RpcResult<Route<AssetId, Balance, AssetBalance>>,
+        ),
     }
 }
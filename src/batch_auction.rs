@@ -0,0 +1,530 @@
+//! Batch auctions. Trades on an exchange in batch auction mode are escrowed and queued, then
+//! settled together at the end of the block, at a single clearing price against the exchange,
+//! so their order within the block doesn't matter.
+
+use crate::{
+    curve::Fee,
+    handlers::{OnSwap, SwapDetails},
+    permissions::ExchangeOperation,
+    AccountIdOf, AssetBalanceOf, AssetIdOf, BalanceOf, BatchQueue, BatchTrade, BatchTradeOf,
+    BatchTrades, Config, ConfigHelper, Error, Event, ExchangeOf, Exchanges, Pallet, SwapAmount,
+    TradeAmount, WeightInfo,
+};
+use frame_support::{
+    dispatch::DispatchResult,
+    ensure,
+    sp_runtime::{
+        traits::{CheckedAdd, CheckedMul, CheckedSub, One, Saturating, Zero},
+        FixedPointNumber, FixedU128,
+    },
+    traits::{fungible::Mutate as _, fungibles::Mutate, tokens::Preservation, Get},
+    transactional,
+    weights::Weight,
+};
+use sp_std::{cmp::Ordering, vec::Vec};
+
+impl<T: Config<I>, I: 'static> Pallet<T, I> {
+    /// Escrow the (maximum) input of a trade on an exchange in batch auction mode,
+    /// and queue the trade to be settled at the end of the block.
+    pub(crate) fn queue_batch_trade(
+        asset_id: AssetIdOf<T, I>,
+        trader: AccountIdOf<T>,
+        recipient: AccountIdOf<T>,
+        amount: SwapAmount<BalanceOf<T, I>, AssetBalanceOf<T, I>>,
+        preservation: Preservation,
+    ) -> DispatchResult {
+        // -------------------------- Validation part --------------------------
+        let (operation, currency_amount, token_amount) = match &amount {
+            SwapAmount::CurrencyToAsset(trade) => {
+                let (currency_amount, token_amount) = Self::trade_limits(trade);
+                (ExchangeOperation::BuyAsset, currency_amount, token_amount)
+            }
+            SwapAmount::AssetToCurrency(trade) => {
+                let (token_amount, currency_amount) = Self::trade_limits(trade);
+                (ExchangeOperation::SellAsset, currency_amount, token_amount)
+            }
+        };
+        Self::check_operation_allowed(
+            operation,
+            &asset_id,
+            &trader,
+            &recipient,
+            currency_amount,
+            token_amount,
+        )?;
+
+        // ------------------------------ Escrow -------------------------------
+        let pallet_account = T::pallet_account();
+        match &amount {
+            SwapAmount::CurrencyToAsset(trade) => {
+                let currency_amount = Self::max_trade_input(trade);
+                Self::check_enough_currency(&trader, &currency_amount, preservation)?;
+                T::QuoteAsset::transfer(&trader, &pallet_account, currency_amount, preservation)?;
+            }
+            SwapAmount::AssetToCurrency(trade) => {
+                let token_amount = Self::max_trade_input(trade);
+                Self::check_enough_tokens(&asset_id, &trader, &token_amount)?;
+                T::Assets::transfer(
+                    asset_id.clone(),
+                    &trader,
+                    &pallet_account,
+                    token_amount,
+                    preservation,
+                )?;
+            }
+        }
+
+        // -------------------------- Update storage ---------------------------
+        let trade = BatchTrade {
+            trader: trader.clone(),
+            recipient,
+            amount,
+        };
+        <BatchTrades<T, I>>::try_mutate(&asset_id, |trades| -> Result<_, Error<T, I>> {
+            // The first trade of the block on the exchange queues its batch for settlement
+            if trades.is_empty() {
+                <BatchQueue<T, I>>::try_append(&asset_id).map_err(|_| Error::<T, I>::BatchFull)?;
+            }
+            trades.try_push(trade).map_err(|_| Error::<T, I>::BatchFull)
+        })?;
+
+        // ---------------------------- Emit event -----------------------------
+        Self::deposit_event(Event::BatchTradeQueued(asset_id, trader));
+        Ok(())
+    }
+
+    /// Settle the trades queued in this block on all exchanges in batch auction mode
+    pub(crate) fn settle_batch_auctions() {
+        for asset_id in <BatchQueue<T, I>>::take() {
+            let trades = <BatchTrades<T, I>>::take(&asset_id);
+            Self::settle_batch(asset_id, trades.into_inner());
+        }
+    }
+
+    /// Weight reserved in `on_initialize` for settling the batches at the end of the block.
+    /// Worst case of `MaxBatchAuctions` batches of `MaxBatchTrades` trades, each charged one
+    /// `settle_batch` plus one `clearing_round` for every extra round needed to find its clearing
+    /// price. There are at most as many rounds as trades, as every round but the last rejects one.
+    pub(crate) fn batch_settlement_weight() -> Weight {
+        let max_trades = T::MaxBatchTrades::get();
+        let extra_rounds = T::WeightInfo::clearing_round(max_trades)
+            .saturating_mul(max_trades.saturating_sub(1).into());
+        T::WeightInfo::settle_batch(max_trades)
+            .saturating_add(extra_rounds)
+            .saturating_mul(T::MaxBatchAuctions::get().into())
+    }
+
+    /// Settle the trades queued on an exchange at a single clearing price, refunding the trades
+    /// which can't be filled within their limits at that price.
+    /// Rejecting a trade moves the clearing price, so the price is recomputed until all remaining
+    /// trades can be filled. Trades are visited in the order they were queued, so the settlement
+    /// only depends on the queued trades and the state of the exchange.
+    fn settle_batch(asset_id: AssetIdOf<T, I>, mut trades: Vec<BatchTradeOf<T, I>>) {
+        let mut rejected = Vec::new();
+        let settlement = Self::get_exchange(&asset_id)
+            .ok()
+            .and_then(|exchange| loop {
+                if trades.is_empty() {
+                    return None;
+                }
+                let (price, unfilled) = Self::clearing_round(&exchange, &mut trades)?;
+                if unfilled.is_empty() {
+                    return Some((exchange, price));
+                }
+                rejected.extend(unfilled);
+            });
+
+        match settlement {
+            Some((exchange, price)) if Self::execute_batch(exchange, price, &trades).is_ok() => {
+                Self::deposit_event(Event::BatchSettled(asset_id.clone(), price));
+            }
+            // If no clearing price can be found, all trades are refunded
+            _ => rejected.append(&mut trades),
+        }
+        for trade in rejected {
+            let trader = trade.trader.clone();
+            if Self::refund_batch_trade(&asset_id, trade).is_ok() {
+                Self::deposit_event(Event::BatchTradeRefunded(asset_id.clone(), trader));
+            }
+        }
+    }
+
+    /// Compute the clearing price of the trades, and remove the ones which can't be filled at
+    /// that price. Return the price and the removed trades, or `None` if there is no clearing price.
+    pub(crate) fn clearing_round(
+        exchange: &ExchangeOf<T, I>,
+        trades: &mut Vec<BatchTradeOf<T, I>>,
+    ) -> Option<(FixedU128, Vec<BatchTradeOf<T, I>>)> {
+        let price = Self::clearing_price(exchange, trades)?;
+        let (accepted, unfilled) = trades
+            .drain(..)
+            .partition(|trade| Self::batch_trade_fill(trade, price).is_some());
+        *trades = accepted;
+        Some((price, unfilled))
+    }
+
+    /// Perform the transfers of all trades at the clearing price, refunding unused inputs,
+    /// and update the exchange reserves with the net flows of the batch
+    #[transactional]
+    fn execute_batch(
+        mut exchange: ExchangeOf<T, I>,
+        price: FixedU128,
+        trades: &[BatchTradeOf<T, I>],
+    ) -> DispatchResult {
+        let asset_id = exchange.asset_id.clone();
+        let pallet_account = T::pallet_account();
+        let fee = Self::trade_fee_rate(&exchange);
+        let mut currency_in = <BalanceOf<T, I>>::zero();
+        let mut currency_out = <BalanceOf<T, I>>::zero();
+        let mut tokens_in = <AssetBalanceOf<T, I>>::zero();
+        let mut tokens_out = <AssetBalanceOf<T, I>>::zero();
+        let mut swaps = Vec::with_capacity(trades.len());
+
+        // --------------------- Currency & token transfer ---------------------
+        for trade in trades {
+            let (currency_amount, token_amount) =
+                Self::batch_trade_fill(trade, price).ok_or(Error::<T, I>::NotEnoughLiquidity)?;
+            match &trade.amount {
+                SwapAmount::CurrencyToAsset(amount) => {
+                    T::Assets::transfer(
+                        asset_id.clone(),
+                        &pallet_account,
+                        &trade.recipient,
+                        token_amount,
+                        Preservation::Expendable,
+                    )?;
+                    let refund = Self::max_trade_input(amount).saturating_sub(currency_amount);
+                    if !refund.is_zero() {
+                        Self::pay_currency(&trade.trader, refund)?;
+                    }
+                    currency_in.saturating_accrue(currency_amount);
+                    tokens_out.saturating_accrue(token_amount);
+                    swaps.push(SwapDetails::CurrencyToAsset {
+                        asset_id: asset_id.clone(),
+                        buyer: trade.trader.clone(),
+                        recipient: trade.recipient.clone(),
+                        currency_amount,
+                        token_amount,
+                        fee,
+                        referral: None,
+                    });
+                    Self::deposit_event(Event::CurrencyTradedForAsset(
+                        asset_id.clone(),
+                        trade.trader.clone(),
+                        trade.recipient.clone(),
+                        currency_amount,
+                        token_amount,
+                        fee,
+                        None,
+                    ));
+                }
+                SwapAmount::AssetToCurrency(amount) => {
+                    Self::pay_currency(&trade.recipient, currency_amount)?;
+                    let refund = Self::max_trade_input(amount).saturating_sub(token_amount);
+                    if !refund.is_zero() {
+                        T::Assets::transfer(
+                            asset_id.clone(),
+                            &pallet_account,
+                            &trade.trader,
+                            refund,
+                            Preservation::Expendable,
+                        )?;
+                    }
+                    tokens_in.saturating_accrue(token_amount);
+                    currency_out.saturating_accrue(currency_amount);
+                    swaps.push(SwapDetails::AssetToCurrency {
+                        asset_id: asset_id.clone(),
+                        buyer: trade.trader.clone(),
+                        recipient: trade.recipient.clone(),
+                        currency_amount,
+                        token_amount,
+                        fee,
+                        referral: None,
+                    });
+                    Self::deposit_event(Event::AssetTradedForCurrency(
+                        asset_id.clone(),
+                        trade.trader.clone(),
+                        trade.recipient.clone(),
+                        currency_amount,
+                        token_amount,
+                        fee,
+                        None,
+                    ));
+                }
+            }
+        }
+
+        // -------------------------- Balances update --------------------------
+        let currency_reserve = exchange
+            .currency_reserve
+            .saturating_add(currency_in)
+            .checked_sub(&currency_out)
+            .ok_or(Error::<T, I>::NotEnoughLiquidity)?;
+        let token_reserve = exchange
+            .token_reserve
+            .saturating_add(tokens_in)
+            .checked_sub(&tokens_out)
+            .ok_or(Error::<T, I>::NotEnoughLiquidity)?;
+        ensure!(
+            !currency_reserve.is_zero() && !token_reserve.is_zero(),
+            Error::<T, I>::NotEnoughLiquidity
+        );
+        // The clearing price keeps the product of the reserves from decreasing, and rounding is
+        // always in favour of the exchange; this only guards against unexpected rounding issues
+        let currency_ratio = FixedU128::saturating_from_rational(
+            T::currency_to_asset(currency_reserve),
+            T::currency_to_asset(exchange.currency_reserve),
+        );
+        let token_ratio =
+            FixedU128::saturating_from_rational(exchange.token_reserve, token_reserve);
+        ensure!(currency_ratio >= token_ratio, Error::<T, I>::NotEnoughLiquidity);
+        exchange.currency_reserve = currency_reserve;
+        exchange.token_reserve = token_reserve;
+        <Exchanges<T, I>>::insert(asset_id, exchange);
+
+        // -------------------------- Notify handlers --------------------------
+        for swap in &swaps {
+            T::OnSwap::on_swap(swap);
+        }
+        Ok(())
+    }
+
+    /// Refund the escrowed input of a trade which wasn't settled
+    fn refund_batch_trade(asset_id: &AssetIdOf<T, I>, trade: BatchTradeOf<T, I>) -> DispatchResult {
+        let pallet_account = T::pallet_account();
+        match &trade.amount {
+            SwapAmount::CurrencyToAsset(amount) => {
+                Self::pay_currency(&trade.trader, Self::max_trade_input(amount))?;
+            }
+            SwapAmount::AssetToCurrency(amount) => {
+                T::Assets::transfer(
+                    asset_id.clone(),
+                    &pallet_account,
+                    &trade.trader,
+                    Self::max_trade_input(amount),
+                    Preservation::Expendable,
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Compute the (currency amount, token amount) of a trade at the clearing price (currency per
+    /// token), or `None` if the trade can't be filled within its `min_output`/`max_input`.
+    /// Amounts received by the trader are rounded down, amounts paid are rounded up.
+    fn batch_trade_fill(
+        trade: &BatchTradeOf<T, I>,
+        price: FixedU128,
+    ) -> Option<(BalanceOf<T, I>, AssetBalanceOf<T, I>)> {
+        let fill = match trade.amount {
+            SwapAmount::CurrencyToAsset(TradeAmount::FixedInput {
+                input_amount,
+                min_output,
+            }) => {
+                let token_amount = price
+                    .reciprocal()?
+                    .saturating_mul_int(T::currency_to_asset(input_amount));
+                (token_amount >= min_output).then_some((input_amount, token_amount))
+            }
+            SwapAmount::CurrencyToAsset(TradeAmount::FixedOutput {
+                max_input,
+                output_amount,
+            }) => {
+                let currency_amount = T::asset_to_currency(
+                    price
+                        .saturating_mul_int(output_amount)
+                        .saturating_add(One::one()),
+                );
+                (currency_amount <= max_input).then_some((currency_amount, output_amount))
+            }
+            SwapAmount::AssetToCurrency(TradeAmount::FixedInput {
+                input_amount,
+                min_output,
+            }) => {
+                let currency_amount = T::asset_to_currency(price.saturating_mul_int(input_amount));
+                (currency_amount >= min_output).then_some((currency_amount, input_amount))
+            }
+            SwapAmount::AssetToCurrency(TradeAmount::FixedOutput {
+                max_input,
+                output_amount,
+            }) => {
+                let token_amount = price
+                    .reciprocal()?
+                    .saturating_mul_int(T::currency_to_asset(output_amount))
+                    .saturating_add(One::one());
+                (token_amount <= max_input).then_some((output_amount, token_amount))
+            }
+        };
+        fill.filter(|(currency_amount, token_amount)| {
+            !currency_amount.is_zero() && !token_amount.is_zero()
+        })
+    }
+
+    /// Compute the uniform clearing price (currency per token) of a batch of trades.
+    ///
+    /// With `C` and `T` the currency and tokens sold by fixed input trades, `E` and `D` the
+    /// currency and tokens bought by fixed output trades, the traders pay `C + p * D` currency
+    /// and receive `p * T + E` currency at price `p`. The difference is traded with the exchange
+    /// (`Rc`, `Rt` – reserves, `f` – part of the input left after the provider fee), at an average
+    /// price equal to `p`, which gives:
+    ///   * `p = (Rc + f * (C - E)) / (f * (Rt + T - D))` if the exchange sells tokens,
+    ///   * `p = f * (Rc + C - E) / (Rt + f * (T - D))` if the exchange buys tokens,
+    ///   * `p = (C - E) / (T - D)` if the trades match without trading with the exchange.
+    fn clearing_price(
+        exchange: &ExchangeOf<T, I>,
+        trades: &[BatchTradeOf<T, I>],
+    ) -> Option<FixedU128> {
+        let volumes = BatchVolumes::<T, I>::new(trades, &Self::trade_fee(exchange))?;
+        let currency_reserve = T::currency_to_asset(exchange.currency_reserve);
+        let token_reserve = exchange.token_reserve;
+        let is_valid = |price: &FixedU128, flow: Ordering| {
+            !price.is_zero() && volumes.net_currency_flow(*price) == flow
+        };
+
+        if let Some(price) = volumes
+            .exchange_sells_price(currency_reserve, token_reserve)
+            .filter(|price| is_valid(price, Ordering::Greater))
+        {
+            return Some(price);
+        }
+        if let Some(price) = volumes
+            .exchange_buys_price(currency_reserve, token_reserve)
+            .filter(|price| is_valid(price, Ordering::Less))
+        {
+            return Some(price);
+        }
+        volumes
+            .matched_price()
+            .filter(|price| is_valid(price, Ordering::Equal))
+    }
+
+    fn max_trade_input<I: Copy, O>(amount: &TradeAmount<I, O>) -> I {
+        match amount {
+            TradeAmount::FixedInput { input_amount, .. } => *input_amount,
+            TradeAmount::FixedOutput { max_input, .. } => *max_input,
+        }
+    }
+
+    /// (maximum input, minimum output) of a trade
+    fn trade_limits<I: Copy, O: Copy>(amount: &TradeAmount<I, O>) -> (I, O) {
+        match amount {
+            TradeAmount::FixedInput {
+                input_amount,
+                min_output,
+            } => (*input_amount, *min_output),
+            TradeAmount::FixedOutput {
+                max_input,
+                output_amount,
+            } => (*max_input, *output_amount),
+        }
+    }
+}
+
+/// Total amounts sold and bought by a batch of trades, in the asset balance type
+struct BatchVolumes<T: Config<I>, I: 'static> {
+    /// Currency sold by fixed input trades (`C`)
+    sold_currency: AssetBalanceOf<T, I>,
+    /// Tokens sold by fixed input trades (`T`)
+    sold_tokens: AssetBalanceOf<T, I>,
+    /// Currency bought by fixed output trades (`E`)
+    bought_currency: AssetBalanceOf<T, I>,
+    /// Tokens bought by fixed output trades (`D`)
+    bought_tokens: AssetBalanceOf<T, I>,
+    /// Numerator of `f`, the part of the input left after the provider fee
+    fee_numerator: AssetBalanceOf<T, I>,
+    /// Denominator of `f`
+    fee_denominator: AssetBalanceOf<T, I>,
+}
+
+impl<T: Config<I>, I: 'static> BatchVolumes<T, I> {
+    fn new(trades: &[BatchTradeOf<T, I>], fee: &Fee<BalanceOf<T, I>>) -> Option<Self> {
+        let mut volumes = Self {
+            sold_currency: Zero::zero(),
+            sold_tokens: Zero::zero(),
+            bought_currency: Zero::zero(),
+            bought_tokens: Zero::zero(),
+            fee_numerator: T::currency_to_asset(fee.net_numerator),
+            fee_denominator: T::currency_to_asset(fee.denominator),
+        };
+        for trade in trades {
+            match trade.amount {
+                SwapAmount::CurrencyToAsset(TradeAmount::FixedInput { input_amount, .. }) => {
+                    let input_amount = T::currency_to_asset(input_amount);
+                    volumes.sold_currency = volumes.sold_currency.checked_add(&input_amount)?;
+                }
+                SwapAmount::CurrencyToAsset(TradeAmount::FixedOutput { output_amount, .. }) => {
+                    volumes.bought_tokens = volumes.bought_tokens.checked_add(&output_amount)?;
+                }
+                SwapAmount::AssetToCurrency(TradeAmount::FixedInput { input_amount, .. }) => {
+                    volumes.sold_tokens = volumes.sold_tokens.checked_add(&input_amount)?;
+                }
+                SwapAmount::AssetToCurrency(TradeAmount::FixedOutput { output_amount, .. }) => {
+                    let output_amount = T::currency_to_asset(output_amount);
+                    volumes.bought_currency =
+                        volumes.bought_currency.checked_add(&output_amount)?;
+                }
+            }
+        }
+        Some(volumes)
+    }
+
+    /// Net currency paid by the traders to the exchange at the given price, compared to zero
+    fn net_currency_flow(&self, price: FixedU128) -> Ordering {
+        let paid = price
+            .saturating_mul_int(self.bought_tokens)
+            .saturating_add(self.sold_currency);
+        let received = price
+            .saturating_mul_int(self.sold_tokens)
+            .saturating_add(self.bought_currency);
+        paid.cmp(&received)
+    }
+
+    /// `(Rc + f * (C - E)) / (f * (Rt + T - D))`
+    fn exchange_sells_price(
+        &self,
+        currency_reserve: AssetBalanceOf<T, I>,
+        token_reserve: AssetBalanceOf<T, I>,
+    ) -> Option<FixedU128> {
+        let numerator = currency_reserve
+            .checked_mul(&self.fee_denominator)?
+            .checked_add(&self.fee_numerator.checked_mul(&self.sold_currency)?)?
+            .checked_sub(&self.fee_numerator.checked_mul(&self.bought_currency)?)?;
+        let denominator = token_reserve
+            .checked_add(&self.sold_tokens)?
+            .checked_sub(&self.bought_tokens)?
+            .checked_mul(&self.fee_numerator)?;
+        FixedU128::checked_from_rational(numerator, denominator)
+    }
+
+    /// `f * (Rc + C - E) / (Rt + f * (T - D))`
+    fn exchange_buys_price(
+        &self,
+        currency_reserve: AssetBalanceOf<T, I>,
+        token_reserve: AssetBalanceOf<T, I>,
+    ) -> Option<FixedU128> {
+        let numerator = currency_reserve
+            .checked_add(&self.sold_currency)?
+            .checked_sub(&self.bought_currency)?
+            .checked_mul(&self.fee_numerator)?;
+        let denominator = token_reserve
+            .checked_mul(&self.fee_denominator)?
+            .checked_add(&self.fee_numerator.checked_mul(&self.sold_tokens)?)?
+            .checked_sub(&self.fee_numerator.checked_mul(&self.bought_tokens)?)?;
+        FixedU128::checked_from_rational(numerator, denominator)
+    }
+
+    /// `(C - E) / (T - D)`
+    fn matched_price(&self) -> Option<FixedU128> {
+        if self.sold_tokens > self.bought_tokens {
+            FixedU128::checked_from_rational(
+                self.sold_currency.checked_sub(&self.bought_currency)?,
+                self.sold_tokens.saturating_sub(self.bought_tokens),
+            )
+        } else {
+            FixedU128::checked_from_rational(
+                self.bought_currency.checked_sub(&self.sold_currency)?,
+                self.bought_tokens.saturating_sub(self.sold_tokens),
+            )
+        }
+    }
+}
@@ -1,9 +1,18 @@
-use crate::{AccountIdOf, Call, Config, Pallet, TradeAmount};
-use frame_benchmarking::{benchmarks, whitelisted_caller};
-use frame_support::pallet_prelude::DispatchResult;
+use crate::{
+    concentrated::MAX_TICK, curve::Side, AccountIdOf, ApprovedProviders, BenchmarkHelper, Call,
+    Config, Curve, DcaSwap, Deadline, DynamicFeeParams, IntentTip, OrderAmount, Pallet,
+    PermissionedExchanges, SwapAmount, SwapIntent, TradeAmount, WeightSchedule,
+};
+use frame_benchmarking::{account, benchmarks_instance_pallet, whitelisted_caller, BenchmarkError};
+use frame_support::pallet_prelude::{DispatchResult, Weight};
+use frame_support::sp_runtime::{
+    traits::{Hash, One},
+    FixedPointNumber, FixedU128, Permill,
+};
 use frame_support::traits::{
+    fungible::Mutate as _,
     fungibles::{Create, Mutate},
-    Currency,
+    EnsureOrigin, EnsureOriginWithArg, Get,
 };
 use frame_system::RawOrigin;
 
@@ -13,34 +22,107 @@ const ASSET_A: u32 = 1;
 const ASSET_B: u32 = 2;
 const LIQ_TOKEN_A: u32 = 11;
 const LIQ_TOKEN_B: u32 = 12;
+const TICK_SPACING: i32 = 10;
+const FULL_RANGE_TICK: i32 = MAX_TICK / TICK_SPACING * TICK_SPACING;
 
-fn prepare_exchange<T>(asset_id: u32, liquidity_token_id: u32) -> DispatchResult
+fn prepare_exchange<T, I: 'static>(asset_id: u32, liquidity_token_id: u32) -> DispatchResult
 where
     frame_system::pallet_prelude::BlockNumberFor<T>: From<u32>,
-    T: Config<AssetId = u32, AssetBalance = u128>,
-    T::Currency: Currency<AccountIdOf<T>, Balance = u128>,
+    T: Config<I, AssetId = u32, AssetBalance = u128, CurrencyBalance = u128>,
     T::Assets: Create<AccountIdOf<T>> + Mutate<AccountIdOf<T>>,
 {
     let caller: T::AccountId = whitelisted_caller();
     T::Assets::create(asset_id, caller.clone(), true, 1)?;
     T::Assets::mint_into(asset_id, &caller, INIT_BALANCE)?;
-    T::Currency::make_free_balance_be(&caller, INIT_BALANCE);
-    Pallet::<T>::create_exchange(
+    T::QuoteAsset::set_balance(&caller, INIT_BALANCE);
+    Pallet::<T, I>::create_exchange(
         RawOrigin::Signed(caller.clone()).into(),
         asset_id,
         liquidity_token_id,
         INIT_LIQUIDITY,
         INIT_LIQUIDITY,
+        Curve::ConstantProduct,
     )?;
     Ok(())
 }
 
-benchmarks! {
+/// Funded account referring the benchmarked trades
+fn prepare_referrer<T, I: 'static>() -> AccountIdOf<T>
+where
+    T: Config<I, CurrencyBalance = u128>,
+{
+    let referrer: T::AccountId = account("referrer", 0, 0);
+    T::QuoteAsset::set_balance(&referrer, INIT_BALANCE);
+    referrer
+}
+
+/// Exchange in batch auction mode, with `n` queued trades each buying `output_amount` tokens
+fn prepare_batch<T, I: 'static>(n: u32, output_amount: u128) -> Result<(), BenchmarkError>
+where
+    frame_system::pallet_prelude::BlockNumberFor<T>: From<u32>,
+    T: Config<I, AssetId = u32, AssetBalance = u128, CurrencyBalance = u128>,
+    T::Assets: Create<AccountIdOf<T>> + Mutate<AccountIdOf<T>>,
+{
+    prepare_exchange::<T, I>(ASSET_A, LIQ_TOKEN_A)?;
+    let origin = T::AdminOrigin::try_successful_origin().map_err(|_| BenchmarkError::Weightless)?;
+    Pallet::<T, I>::set_batch_auction(origin, ASSET_A, true)?;
+    for i in 0..n {
+        let trader: T::AccountId = account("trader", i, 0);
+        T::QuoteAsset::set_balance(&trader, INIT_BALANCE);
+        // Fixed output, so that the unused input is refunded
+        Pallet::<T, I>::currency_to_asset(
+            RawOrigin::Signed(trader).into(),
+            ASSET_A,
+            TradeAmount::FixedOutput {
+                max_input: 2 * output_amount,
+                output_amount,
+            },
+            Deadline::Block(1.into()),
+            None,
+            None,
+            false,
+        )?;
+    }
+    Ok(())
+}
+
+/// Make an exchange permissioned, with the whitelisted caller as an approved provider
+fn prepare_permissioned<T: Config<I, AssetId = u32>, I: 'static>(asset_id: u32) {
+    let caller: T::AccountId = whitelisted_caller();
+    PermissionedExchanges::<T, I>::insert(asset_id, true);
+    ApprovedProviders::<T, I>::insert(asset_id, caller, true);
+}
+
+/// Create a concentrated-liquidity exchange with tick spacing 10 at price 1,
+/// and a position over the whole price range
+fn prepare_concentrated_exchange<T, I: 'static>(asset_id: u32) -> DispatchResult
+where
+    frame_system::pallet_prelude::BlockNumberFor<T>: From<u32>,
+    T: Config<I, AssetId = u32, AssetBalance = u128, CurrencyBalance = u128>,
+    T::Assets: Create<AccountIdOf<T>> + Mutate<AccountIdOf<T>>,
+{
+    let caller: T::AccountId = whitelisted_caller();
+    T::Assets::create(asset_id, caller.clone(), true, 1)?;
+    T::Assets::mint_into(asset_id, &caller, INIT_BALANCE)?;
+    T::QuoteAsset::set_balance(&caller, INIT_BALANCE);
+    Pallet::<T, I>::create_concentrated_exchange(
+        RawOrigin::Signed(caller).into(),
+        asset_id,
+        TICK_SPACING as u32,
+        FixedU128::one(),
+        -FULL_RANGE_TICK,
+        FULL_RANGE_TICK,
+        INIT_LIQUIDITY,
+        INIT_LIQUIDITY,
+        true,
+    )
+}
+
+benchmarks_instance_pallet! {
     where_clause {
         where
             frame_system::pallet_prelude::BlockNumberFor<T>: From<u32>,
-            T: Config<AssetId = u32, AssetBalance = u128>,
-            T::Currency: Currency<AccountIdOf<T>, Balance = u128>,
+            T: Config<I, AssetId = u32, AssetBalance = u128, CurrencyBalance = u128>,
             T::Assets: Create<AccountIdOf<T>> + Mutate<AccountIdOf<T>>,
     }
 
@@ -48,74 +130,519 @@ benchmarks! {
         let caller: T::AccountId = whitelisted_caller();
         T::Assets::create(ASSET_B, caller.clone(), true, 1).unwrap();
         T::Assets::mint_into(ASSET_B, &caller, INIT_BALANCE).unwrap();
-        T::Currency::make_free_balance_be(&caller, INIT_BALANCE);
-    }: _(RawOrigin::Signed(caller), ASSET_B, LIQ_TOKEN_B, INIT_LIQUIDITY, INIT_LIQUIDITY)
+        T::QuoteAsset::set_balance(&caller, INIT_BALANCE);
+        // Computing the StableSwap invariant is more expensive than the constant product liquidity
+        let curve = Curve::StableSwap {
+            amplification: T::MaxAmplification::get(),
+        };
+    }: _(RawOrigin::Signed(caller), ASSET_B, LIQ_TOKEN_B, INIT_LIQUIDITY, INIT_LIQUIDITY, curve)
     verify {
-        assert!(Pallet::<T>::exchanges(ASSET_B).is_some());
+        assert!(Pallet::<T, I>::exchanges(ASSET_B).is_some());
     }
 
     add_liquidity {
-        prepare_exchange::<T>(ASSET_A, LIQ_TOKEN_A)?;
+        prepare_exchange::<T, I>(ASSET_A, LIQ_TOKEN_A)?;
+        prepare_permissioned::<T, I>(ASSET_A);
         let caller: T::AccountId = whitelisted_caller();
         // Token amount is 2, not 1 because of the `+1` in liquidity added formula
-    }: _(RawOrigin::Signed(caller), ASSET_A, 1, 1, 2, 1.into())
+    }: _(RawOrigin::Signed(caller), ASSET_A, 1, 1, 2, Deadline::Block(1.into()), true)
     verify {
-        let exchange = Pallet::<T>::exchanges(ASSET_A).unwrap();
+        let exchange = Pallet::<T, I>::exchanges(ASSET_A).unwrap();
         assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY + 1);
         assert_eq!(exchange.token_reserve, INIT_LIQUIDITY + 2);
     }
 
     remove_liquidity {
-        prepare_exchange::<T>(ASSET_A, LIQ_TOKEN_A)?;
+        prepare_exchange::<T, I>(ASSET_A, LIQ_TOKEN_A)?;
         let caller: T::AccountId = whitelisted_caller();
-    }: _(RawOrigin::Signed(caller), ASSET_A, 1, 1, 1, 1.into())
+    }: _(RawOrigin::Signed(caller), ASSET_A, 1, 1, 1, Deadline::Block(1.into()))
     verify {
-        let exchange = Pallet::<T>::exchanges(ASSET_A).unwrap();
+        let exchange = Pallet::<T, I>::exchanges(ASSET_A).unwrap();
         assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY - 1);
         assert_eq!(exchange.token_reserve, INIT_LIQUIDITY - 1);
     }
 
     currency_to_asset {
-        prepare_exchange::<T>(ASSET_A, LIQ_TOKEN_A)?;
+        prepare_exchange::<T, I>(ASSET_A, LIQ_TOKEN_A)?;
         let caller: T::AccountId = whitelisted_caller();
-        let input_amount = 500;
-        let min_output = 498; // sold amount (500) - provider fee (0.3%) should be ~498
-    }: _(RawOrigin::Signed(caller), ASSET_A, TradeAmount::FixedInput{input_amount, min_output}, 1.into(), None)
+        let referrer = prepare_referrer::<T, I>();
+        let input_amount = 5000;
+        let min_output = 4984; // sold amount (5000) - provider fee (0.3%) should be ~4984
+        let referral_amount = 3; // 20% of the provider fee (15)
+    }: _(RawOrigin::Signed(caller), ASSET_A, TradeAmount::FixedInput{input_amount, min_output}, Deadline::Block(1.into()), None, Some(referrer), false)
     verify {
-        let exchange = Pallet::<T>::exchanges(ASSET_A).unwrap();
-        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY + input_amount);
+        let exchange = Pallet::<T, I>::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY + input_amount - referral_amount);
         assert_eq!(exchange.token_reserve, INIT_LIQUIDITY - min_output);
     }
 
     asset_to_currency {
-        prepare_exchange::<T>(ASSET_A, LIQ_TOKEN_A)?;
+        prepare_exchange::<T, I>(ASSET_A, LIQ_TOKEN_A)?;
         let caller: T::AccountId = whitelisted_caller();
-        let input_amount = 500;
-        let min_output = 498; // sold amount (500) - provider fee (0.3%) should be ~498
-    }: _(RawOrigin::Signed(caller), ASSET_A, TradeAmount::FixedInput{input_amount, min_output}, 1.into(), None)
+        let referrer = prepare_referrer::<T, I>();
+        let input_amount = 5000;
+        let min_output = 4984; // sold amount (5000) - provider fee (0.3%) should be ~4984
+        let referral_amount = 3; // 20% of the provider fee (15)
+    }: _(RawOrigin::Signed(caller), ASSET_A, TradeAmount::FixedInput{input_amount, min_output}, Deadline::Block(1.into()), None, Some(referrer), false)
     verify {
-        let exchange = Pallet::<T>::exchanges(ASSET_A).unwrap();
+        let exchange = Pallet::<T, I>::exchanges(ASSET_A).unwrap();
         assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY - min_output);
-        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY + input_amount);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY + input_amount - referral_amount);
     }
 
     asset_to_asset {
-        prepare_exchange::<T>(ASSET_A, LIQ_TOKEN_A)?;
-        prepare_exchange::<T>(ASSET_B, LIQ_TOKEN_B)?;
+        prepare_exchange::<T, I>(ASSET_A, LIQ_TOKEN_A)?;
+        prepare_exchange::<T, I>(ASSET_B, LIQ_TOKEN_B)?;
         let caller: T::AccountId = whitelisted_caller();
-        let input_amount = 500;
-        let currency_amount = 498; // sold amount (500) - provider fee (0.3%) should be ~498
-        let min_output = 496; // currency amount (498) - provider fee (0.3%) should be ~496
-    }: _(RawOrigin::Signed(caller), ASSET_A, ASSET_B, TradeAmount::FixedInput{input_amount, min_output}, 1.into(), None)
+        let referrer = prepare_referrer::<T, I>();
+        let input_amount = 5000;
+        let currency_amount = 4984; // sold amount (5000) - provider fee (0.3%) should be ~4984
+        let min_output = 4969; // currency amount (4984) - provider fee (0.3%) should be ~4969
+        let token_referral_amount = 3; // 20% of the provider fee (15)
+        let currency_referral_amount = 2; // 20% of the provider fee (14)
+    }: _(RawOrigin::Signed(caller), ASSET_A, ASSET_B, TradeAmount::FixedInput{input_amount, min_output}, Deadline::Block(1.into()), None, Some(referrer), false)
     verify {
-        let exchange_a = Pallet::<T>::exchanges(ASSET_A).unwrap();
+        let exchange_a = Pallet::<T, I>::exchanges(ASSET_A).unwrap();
         assert_eq!(exchange_a.currency_reserve, INIT_LIQUIDITY - currency_amount);
-        assert_eq!(exchange_a.token_reserve, INIT_LIQUIDITY + input_amount);
+        assert_eq!(exchange_a.token_reserve, INIT_LIQUIDITY + input_amount - token_referral_amount);
 
-        let exchange_b = Pallet::<T>::exchanges(ASSET_B).unwrap();
-        assert_eq!(exchange_b.currency_reserve, INIT_LIQUIDITY + currency_amount);
+        let exchange_b = Pallet::<T, I>::exchanges(ASSET_B).unwrap();
+        assert_eq!(exchange_b.currency_reserve, INIT_LIQUIDITY + currency_amount - currency_referral_amount);
         assert_eq!(exchange_b.token_reserve, INIT_LIQUIDITY - min_output);
     }
 
+    place_limit_order {
+        prepare_exchange::<T, I>(ASSET_A, LIQ_TOKEN_A)?;
+        let caller: T::AccountId = whitelisted_caller();
+    }: _(RawOrigin::Signed(caller), ASSET_A, OrderAmount::SellAsset(500), FixedU128::saturating_from_integer(1), 1.into(), false)
+    verify {
+        assert!(Pallet::<T, I>::limit_orders(0).is_some());
+    }
+
+    cancel_limit_order {
+        prepare_exchange::<T, I>(ASSET_A, LIQ_TOKEN_A)?;
+        let caller: T::AccountId = whitelisted_caller();
+        Pallet::<T, I>::place_limit_order(
+            RawOrigin::Signed(caller.clone()).into(),
+            ASSET_A,
+            OrderAmount::SellAsset(500),
+            FixedU128::saturating_from_integer(1),
+            1.into(),
+            false,
+        )?;
+    }: _(RawOrigin::Signed(caller), 0)
+    verify {
+        assert!(Pallet::<T, I>::limit_orders(0).is_none());
+    }
+
+    match_limit_order {
+        prepare_exchange::<T, I>(ASSET_A, LIQ_TOKEN_A)?;
+        let caller: T::AccountId = whitelisted_caller();
+        Pallet::<T, I>::place_limit_order(
+            RawOrigin::Signed(caller).into(),
+            ASSET_A,
+            OrderAmount::SellAsset(500),
+            FixedU128::saturating_from_rational(1, 2),
+            1.into(),
+            false,
+        )?;
+    }: {
+        Pallet::<T, I>::match_limit_orders(1.into(), Weight::MAX);
+    }
+    verify {
+        assert!(Pallet::<T, I>::limit_orders(0).is_none());
+        let exchange = Pallet::<T, I>::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY + 500);
+    }
+
+    schedule_dca {
+        prepare_exchange::<T, I>(ASSET_A, LIQ_TOKEN_A)?;
+        prepare_exchange::<T, I>(ASSET_B, LIQ_TOKEN_B)?;
+        let caller: T::AccountId = whitelisted_caller();
+        let swap = DcaSwap::AssetToAsset {
+            sold_asset_id: ASSET_A,
+            bought_asset_id: ASSET_B,
+            token_amount: 500,
+        };
+    }: _(RawOrigin::Signed(caller), swap, 10.into(), 10, Permill::from_percent(1), false)
+    verify {
+        assert!(Pallet::<T, I>::dca_schedules(0).is_some());
+    }
+
+    cancel_dca {
+        prepare_exchange::<T, I>(ASSET_A, LIQ_TOKEN_A)?;
+        prepare_exchange::<T, I>(ASSET_B, LIQ_TOKEN_B)?;
+        let caller: T::AccountId = whitelisted_caller();
+        let swap = DcaSwap::AssetToAsset {
+            sold_asset_id: ASSET_A,
+            bought_asset_id: ASSET_B,
+            token_amount: 500,
+        };
+        Pallet::<T, I>::schedule_dca(
+            RawOrigin::Signed(caller.clone()).into(),
+            swap,
+            10.into(),
+            10,
+            Permill::from_percent(1),
+            false,
+        )?;
+    }: _(RawOrigin::Signed(caller), 0)
+    verify {
+        assert!(Pallet::<T, I>::dca_schedules(0).is_none());
+    }
+
+    execute_dca_schedule {
+        prepare_exchange::<T, I>(ASSET_A, LIQ_TOKEN_A)?;
+        prepare_exchange::<T, I>(ASSET_B, LIQ_TOKEN_B)?;
+        let caller: T::AccountId = whitelisted_caller();
+        let swap = DcaSwap::AssetToAsset {
+            sold_asset_id: ASSET_A,
+            bought_asset_id: ASSET_B,
+            token_amount: 500,
+        };
+        Pallet::<T, I>::schedule_dca(
+            RawOrigin::Signed(caller).into(),
+            swap,
+            10.into(),
+            10,
+            Permill::from_percent(1),
+            false,
+        )?;
+        let next_execution = Pallet::<T, I>::dca_schedules(0).unwrap().next_execution;
+    }: {
+        Pallet::<T, I>::execute_dca_schedules(next_execution);
+    }
+    verify {
+        assert_eq!(Pallet::<T, I>::dca_schedules(0).unwrap().remaining_executions, 9);
+        let exchange_a = Pallet::<T, I>::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange_a.token_reserve, INIT_LIQUIDITY + 500);
+    }
+
+    set_batch_auction {
+        prepare_exchange::<T, I>(ASSET_A, LIQ_TOKEN_A)?;
+        let origin = T::AdminOrigin::try_successful_origin()
+            .map_err(|_| BenchmarkError::Weightless)?;
+    }: _<T::RuntimeOrigin>(origin, ASSET_A, true)
+    verify {
+        assert!(Pallet::<T, I>::batch_auctions(ASSET_A));
+    }
+
+    // Queueing a trade, which is settled at the end of the block (see `settle_batch`)
+    batch_trade {
+        prepare_exchange::<T, I>(ASSET_A, LIQ_TOKEN_A)?;
+        let caller: T::AccountId = whitelisted_caller();
+        let origin = T::AdminOrigin::try_successful_origin()
+            .map_err(|_| BenchmarkError::Weightless)?;
+        Pallet::<T, I>::set_batch_auction(origin, ASSET_A, true)?;
+    }: currency_to_asset(
+        RawOrigin::Signed(caller),
+        ASSET_A,
+        TradeAmount::FixedOutput { max_input: 2000, output_amount: 1000 },
+        Deadline::Block(1.into()),
+        None,
+        None,
+        false
+    )
+    verify {
+        assert_eq!(Pallet::<T, I>::batch_trades(ASSET_A).len(), 1);
+    }
+
+    // Settling a batch of `n` trades in a single round
+    settle_batch {
+        let n in 1 .. T::MaxBatchTrades::get();
+        let output_amount = 1000;
+        prepare_batch::<T, I>(n, output_amount)?;
+    }: {
+        Pallet::<T, I>::settle_batch_auctions();
+    }
+    verify {
+        let exchange = Pallet::<T, I>::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY - output_amount * n as u128);
+    }
+
+    // Pricing a batch of `n` trades, done again after every round rejecting trades
+    clearing_round {
+        let n in 1 .. T::MaxBatchTrades::get();
+        prepare_batch::<T, I>(n, 1000)?;
+        let exchange = Pallet::<T, I>::exchanges(ASSET_A).unwrap();
+        let mut trades = Pallet::<T, I>::batch_trades(ASSET_A).into_inner();
+    }: {
+        Pallet::<T, I>::clearing_round(&exchange, &mut trades);
+    }
+    verify {
+        assert_eq!(trades.len(), n as usize);
+    }
+
+    commit_trade {
+        let caller: T::AccountId = whitelisted_caller();
+        T::QuoteAsset::set_balance(&caller, INIT_BALANCE);
+        let commitment = T::Hashing::hash_of(&0u32);
+    }: _(RawOrigin::Signed(caller.clone()), commitment, false)
+    verify {
+        assert!(Pallet::<T, I>::trade_commitments(caller, commitment).is_some());
+    }
+
+    reveal_trade {
+        prepare_exchange::<T, I>(ASSET_A, LIQ_TOKEN_A)?;
+        let caller: T::AccountId = whitelisted_caller();
+        let amount = SwapAmount::CurrencyToAsset(TradeAmount::FixedInput {
+            input_amount: 500,
+            min_output: 498, // sold amount (500) - provider fee (0.3%) should be ~498
+        });
+        let salt = [0; 32];
+        let commitment = T::Hashing::hash_of(&(ASSET_A, &amount, None::<T::AccountId>, salt));
+        Pallet::<T, I>::commit_trade(RawOrigin::Signed(caller.clone()).into(), commitment, false)?;
+    }: _(RawOrigin::Signed(caller.clone()), ASSET_A, amount, None, salt, false)
+    verify {
+        assert!(Pallet::<T, I>::trade_commitments(caller, commitment).is_none());
+        let exchange = Pallet::<T, I>::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY - 498);
+    }
+
+    expire_trade_commitment {
+        let caller: T::AccountId = whitelisted_caller();
+        T::QuoteAsset::set_balance(&caller, INIT_BALANCE);
+        let commitment = T::Hashing::hash_of(&0u32);
+        Pallet::<T, I>::commit_trade(RawOrigin::Signed(caller.clone()).into(), commitment, false)?;
+        let expiry = Pallet::<T, I>::trade_commitments(&caller, commitment).unwrap().expiry;
+        let sweep_block = expiry + 1.into();
+    }: {
+        Pallet::<T, I>::expire_trade_commitments(sweep_block);
+    }
+    verify {
+        assert!(Pallet::<T, I>::trade_commitments(caller, commitment).is_none());
+    }
+
+    execute_signed_intent {
+        prepare_exchange::<T, I>(ASSET_A, LIQ_TOKEN_A)?;
+        let relayer: T::AccountId = whitelisted_caller();
+        let signer = T::BenchmarkHelper::signer();
+        T::QuoteAsset::set_balance(&signer, INIT_BALANCE);
+        let intent = SwapIntent {
+            signer: signer.clone(),
+            asset_id: ASSET_A,
+            amount: SwapAmount::CurrencyToAsset(TradeAmount::FixedInput {
+                input_amount: 500,
+                min_output: 498, // sold amount (500) - provider fee (0.3%) should be ~498
+            }),
+            deadline: Deadline::Block(1.into()),
+            recipient: None,
+            nonce: 0,
+            tip: IntentTip::Asset(10),
+            keep_alive: false,
+        };
+        let signature = T::BenchmarkHelper::sign(&Pallet::<T, I>::signed_intent_payload(&intent));
+    }: _(RawOrigin::Signed(relayer), intent, signature)
+    verify {
+        assert_eq!(Pallet::<T, I>::intent_nonces(signer), 1);
+        let exchange = Pallet::<T, I>::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY - 498);
+    }
+
+    zap_in {
+        prepare_exchange::<T, I>(ASSET_A, LIQ_TOKEN_A)?;
+        prepare_permissioned::<T, I>(ASSET_A);
+        let caller: T::AccountId = whitelisted_caller();
+    }: _(RawOrigin::Signed(caller), ASSET_A, 1_000_000, 1, Deadline::Block(1.into()), true)
+    verify {
+        let exchange = Pallet::<T, I>::exchanges(ASSET_A).unwrap();
+        assert!(exchange.currency_reserve > INIT_LIQUIDITY);
+    }
+
+    zap_out {
+        prepare_exchange::<T, I>(ASSET_A, LIQ_TOKEN_A)?;
+        let caller: T::AccountId = whitelisted_caller();
+        let min_output = ZapOutput::Currency(1);
+    }: _(RawOrigin::Signed(caller), ASSET_A, 1_000_000, min_output, Deadline::Block(1.into()))
+    verify {
+        let exchange = Pallet::<T, I>::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY);
+    }
+
+    add_liquidity_exact_tokens {
+        prepare_exchange::<T, I>(ASSET_A, LIQ_TOKEN_A)?;
+        prepare_permissioned::<T, I>(ASSET_A);
+        let caller: T::AccountId = whitelisted_caller();
+        // Currency amount is 2, not 1 because of the `+1` in liquidity added formula
+    }: _(RawOrigin::Signed(caller), ASSET_A, 1, 1, 2, Deadline::Block(1.into()), true)
+    verify {
+        let exchange = Pallet::<T, I>::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY + 2);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY + 1);
+    }
+
+    add_liquidity_bounded {
+        prepare_exchange::<T, I>(ASSET_A, LIQ_TOKEN_A)?;
+        prepare_permissioned::<T, I>(ASSET_A);
+        let caller: T::AccountId = whitelisted_caller();
+    }: _(RawOrigin::Signed(caller), ASSET_A, 2, 2, 1, 1, 1, Deadline::Block(1.into()), true)
+    verify {
+        let exchange = Pallet::<T, I>::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY + 2);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY + 2);
+    }
+
+    ramp_amplification {
+        let caller: T::AccountId = whitelisted_caller();
+        T::Assets::create(ASSET_A, caller.clone(), true, 1)?;
+        T::Assets::mint_into(ASSET_A, &caller, INIT_BALANCE)?;
+        T::QuoteAsset::set_balance(&caller, INIT_BALANCE);
+        Pallet::<T, I>::create_exchange(
+            RawOrigin::Signed(caller).into(),
+            ASSET_A,
+            LIQ_TOKEN_A,
+            INIT_LIQUIDITY,
+            INIT_LIQUIDITY,
+            Curve::StableSwap { amplification: 1 },
+        )?;
+        let origin = T::AdminOrigin::try_successful_origin()
+            .map_err(|_| BenchmarkError::Weightless)?;
+    }: _<T::RuntimeOrigin>(origin, ASSET_A, T::MaxAmplification::get(), 100.into())
+    verify {
+        assert!(Pallet::<T, I>::amplification_ramps(ASSET_A).is_some());
+    }
+
+    create_bootstrapping_exchange {
+        let caller: T::AccountId = whitelisted_caller();
+        T::Assets::create(ASSET_B, caller.clone(), true, 1).unwrap();
+        T::Assets::mint_into(ASSET_B, &caller, INIT_BALANCE).unwrap();
+        T::QuoteAsset::set_balance(&caller, INIT_BALANCE);
+        let schedule = WeightSchedule {
+            start_weight: Permill::from_percent(10),
+            end_weight: Permill::from_percent(50),
+            start: 1.into(),
+            end: 100.into(),
+        };
+    }: _(RawOrigin::Signed(caller), ASSET_B, LIQ_TOKEN_B, INIT_LIQUIDITY, INIT_LIQUIDITY, schedule)
+    verify {
+        assert!(Pallet::<T, I>::weight_schedules(ASSET_B).is_some());
+    }
+
+    create_concentrated_exchange {
+        let caller: T::AccountId = whitelisted_caller();
+        T::Assets::create(ASSET_B, caller.clone(), true, 1).unwrap();
+        T::Assets::mint_into(ASSET_B, &caller, INIT_BALANCE).unwrap();
+        T::QuoteAsset::set_balance(&caller, INIT_BALANCE);
+    }: _(
+        RawOrigin::Signed(caller),
+        ASSET_B,
+        TICK_SPACING as u32,
+        FixedU128::one(),
+        -FULL_RANGE_TICK,
+        FULL_RANGE_TICK,
+        INIT_LIQUIDITY,
+        INIT_LIQUIDITY,
+        true
+    )
+    verify {
+        assert!(Pallet::<T, I>::concentrated_pools(ASSET_B).is_some());
+    }
+
+    mint_position {
+        prepare_concentrated_exchange::<T, I>(ASSET_A)?;
+        prepare_permissioned::<T, I>(ASSET_A);
+        let caller: T::AccountId = whitelisted_caller();
+        // Both ticks and the position are created
+    }: _(
+        RawOrigin::Signed(caller),
+        ASSET_A,
+        -TICK_SPACING,
+        TICK_SPACING,
+        1_000_000,
+        1_000_000,
+        Deadline::Block(1.into()),
+        true
+    )
+    verify {
+        assert_eq!(Pallet::<T, I>::initialized_ticks(ASSET_A).len(), 4);
+    }
+
+    burn_position {
+        prepare_concentrated_exchange::<T, I>(ASSET_A)?;
+        let caller: T::AccountId = whitelisted_caller();
+        let liquidity = Pallet::<T, I>::positions(&caller, (ASSET_A, -FULL_RANGE_TICK, FULL_RANGE_TICK))
+            .unwrap()
+            .liquidity;
+        // Both ticks and the position are removed
+    }: _(
+        RawOrigin::Signed(caller),
+        ASSET_A,
+        -FULL_RANGE_TICK,
+        FULL_RANGE_TICK,
+        liquidity,
+        1,
+        1,
+        Deadline::Block(1.into())
+    )
+    verify {
+        assert!(Pallet::<T, I>::initialized_ticks(ASSET_A).is_empty());
+    }
+
+    swap_crossing_ticks {
+        let n in 0 .. T::MaxTicksCrossed::get();
+        prepare_concentrated_exchange::<T, I>(ASSET_A)?;
+        let caller: T::AccountId = whitelisted_caller();
+        // Ranges around the price with `n` lower ticks, all crossed by the swap
+        for k in 1..=n as i32 {
+            Pallet::<T, I>::mint_position(
+                RawOrigin::Signed(caller.clone()).into(),
+                ASSET_A,
+                -TICK_SPACING * k,
+                TICK_SPACING * k,
+                1_000_000,
+                1_000_000,
+                Deadline::Block(1.into()),
+                true,
+            )?;
+        }
+    }: {
+        Pallet::<T, I>::execute_concentrated_swap(
+            &ASSET_A,
+            Side::Currency,
+            INIT_LIQUIDITY / 10,
+            1,
+            true,
+            &Pallet::<T, I>::provider_fee(),
+            Permill::zero(),
+        )?;
+    }
+    verify {
+        let pool = Pallet::<T, I>::concentrated_pools(ASSET_A).unwrap();
+        assert!(pool.tick < -TICK_SPACING * n as i32);
+    }
+
+    set_dynamic_fee {
+        prepare_exchange::<T, I>(ASSET_A, LIQ_TOKEN_A)?;
+        let origin = T::AdminOrigin::try_successful_origin()
+            .map_err(|_| BenchmarkError::Weightless)?;
+        let params = DynamicFeeParams {
+            min_fee: Permill::from_parts(1_000),
+            max_fee: Permill::from_percent(5),
+            volatility_multiplier: 2,
+        };
+    }: _<T::RuntimeOrigin>(origin, ASSET_A, Some(params))
+    verify {
+        assert!(Pallet::<T, I>::dynamic_fees(ASSET_A).is_some());
+    }
+
+    set_permissioned {
+        prepare_exchange::<T, I>(ASSET_A, LIQ_TOKEN_A)?;
+        let origin = T::ExchangeAdmin::try_successful_origin(&ASSET_A)
+            .map_err(|_| BenchmarkError::Weightless)?;
+    }: _<T::RuntimeOrigin>(origin, ASSET_A, true)
+    verify {
+        assert!(Pallet::<T, I>::permissioned_exchanges(ASSET_A));
+    }
+
+    set_provider_approval {
+        prepare_exchange::<T, I>(ASSET_A, LIQ_TOKEN_A)?;
+        let origin = T::ExchangeAdmin::try_successful_origin(&ASSET_A)
+            .map_err(|_| BenchmarkError::Weightless)?;
+        let provider: T::AccountId = account("provider", 0, 0);
+    }: _<T::RuntimeOrigin>(origin, ASSET_A, provider.clone(), true)
+    verify {
+        assert!(Pallet::<T, I>::approved_providers(ASSET_A, provider));
+    }
+
     impl_benchmark_test_suite!(Pallet, crate::mock::new_test_ext(), crate::mock::Test);
 }
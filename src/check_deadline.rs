@@ -0,0 +1,117 @@
+//! Transaction extension checking the `deadline` of DEX calls in the transaction pool.
+
+use crate::{Call, Config, Deadline, DeadlineOf, Error, Pallet};
+use codec::{Decode, Encode};
+use frame_support::{
+    sp_runtime::{
+        traits::{DispatchInfoOf, SaturatedConversion, Saturating, SignedExtension},
+        transaction_validity::{
+            InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+        },
+    },
+    traits::IsSubType,
+};
+use scale_info::TypeInfo;
+use sp_std::{fmt, marker::PhantomData};
+
+/// Transaction extension dropping DEX calls with a `deadline` from the transaction pool once
+/// the deadline has passed. Such calls are rejected as `Stale`, and the longevity of calls with
+/// a block number deadline is limited to the number of blocks remaining until the deadline.
+#[derive(Encode, Decode, Clone, Eq, PartialEq, TypeInfo)]
+#[scale_info(skip_type_params(T, I))]
+pub struct CheckDeadline<T: Config<I> + Send + Sync, I: 'static + Send + Sync = ()>(
+    PhantomData<(T, I)>,
+);
+
+impl<T: Config<I> + Send + Sync, I: 'static + Send + Sync> CheckDeadline<T, I> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+
+    /// Deadline of a DEX call, if it has one
+    fn deadline(call: &Call<T, I>) -> Option<DeadlineOf<T>> {
+        match call {
+            Call::add_liquidity { deadline, .. }
+            | Call::remove_liquidity { deadline, .. }
+            | Call::currency_to_asset { deadline, .. }
+            | Call::asset_to_currency { deadline, .. }
+            | Call::asset_to_asset { deadline, .. }
+            | Call::zap_in { deadline, .. }
+            | Call::zap_out { deadline, .. }
+            | Call::add_liquidity_exact_tokens { deadline, .. }
+            | Call::add_liquidity_bounded { deadline, .. }
+            | Call::mint_position { deadline, .. }
+            | Call::burn_position { deadline, .. } => Some(*deadline),
+            Call::execute_signed_intent { intent, .. } => Some(intent.deadline),
+            _ => None,
+        }
+    }
+}
+
+impl<T: Config<I> + Send + Sync, I: 'static + Send + Sync> Default for CheckDeadline<T, I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Config<I> + Send + Sync, I: 'static + Send + Sync> fmt::Debug for CheckDeadline<T, I> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CheckDeadline")
+    }
+}
+
+impl<T: Config<I> + Send + Sync, I: 'static + Send + Sync> SignedExtension for CheckDeadline<T, I>
+where
+    T::RuntimeCall: IsSubType<Call<T, I>>,
+{
+    const IDENTIFIER: &'static str = "CheckDeadline";
+    type AccountId = T::AccountId;
+    type Call = T::RuntimeCall;
+    type AdditionalSigned = ();
+    type Pre = ();
+
+    fn additional_signed(&self) -> Result<(), TransactionValidityError> {
+        Ok(())
+    }
+
+    fn validate(
+        &self,
+        _who: &Self::AccountId,
+        call: &Self::Call,
+        _info: &DispatchInfoOf<Self::Call>,
+        _len: usize,
+    ) -> TransactionValidity {
+        let deadline = match call.is_sub_type().and_then(Self::deadline) {
+            Some(deadline) => deadline,
+            None => return Ok(ValidTransaction::default()),
+        };
+        match Pallet::<T, I>::check_deadline(&deadline) {
+            Err(Error::DeadlinePassed) => return Err(InvalidTransaction::Stale.into()),
+            Err(_) => return Err(InvalidTransaction::Call.into()),
+            Ok(()) => (),
+        }
+        match deadline {
+            Deadline::Block(block) => {
+                // Valid in all blocks from the current one up to the deadline
+                let now = <frame_system::Pallet<T>>::block_number();
+                let longevity = block.saturating_sub(now).saturated_into::<u64>();
+                Ok(ValidTransaction {
+                    longevity: longevity.saturating_add(1),
+                    ..Default::default()
+                })
+            }
+            // Block times aren't known, so the call is kept until it's found stale
+            Deadline::Moment(_) => Ok(ValidTransaction::default()),
+        }
+    }
+
+    fn pre_dispatch(
+        self,
+        who: &Self::AccountId,
+        call: &Self::Call,
+        info: &DispatchInfoOf<Self::Call>,
+        len: usize,
+    ) -> Result<(), TransactionValidityError> {
+        self.validate(who, call, info, len).map(|_| ())
+    }
+}
@@ -0,0 +1,63 @@
+//! Commit-reveal trades. A trade is first committed as a hash, escrowing `CommitDeposit`, and
+//! revealed and executed within `RevealWindow` blocks. Unrevealed commitments expire in
+//! `on_initialize`, and their deposits are forfeited.
+
+use crate::{
+    AccountIdOf, CommitmentExpiries, Config, ConfigHelper, Event, Pallet, TradeCommitmentOf,
+    TradeCommitments, WeightInfo,
+};
+use frame_support::{
+    dispatch::DispatchResult,
+    sp_runtime::traits::{One, Saturating},
+    traits::{
+        fungible::Balanced,
+        tokens::{Fortitude, Precision, Preservation},
+        Get, OnUnbalanced,
+    },
+    weights::Weight,
+};
+use frame_system::pallet_prelude::BlockNumberFor;
+
+impl<T: Config<I>, I: 'static> Pallet<T, I> {
+    /// Remove the trade commitments whose reveal window ended in the previous block,
+    /// forfeiting their deposits. At most `MaxCommitmentsPerBlock` commitments can expire
+    /// in a single block.
+    pub(crate) fn expire_trade_commitments(now: BlockNumberFor<T>) -> Weight {
+        let expired = <CommitmentExpiries<T, I>>::take(now);
+        let weight = T::DbWeight::get().reads_writes(1, 1).saturating_add(
+            T::WeightInfo::expire_trade_commitment().saturating_mul(expired.len() as u64),
+        );
+        for (account_id, commitment) in expired {
+            if let Some(trade_commitment) = <TradeCommitments<T, I>>::take(&account_id, commitment)
+            {
+                let forfeited = T::QuoteAsset::withdraw(
+                    &T::pallet_account(),
+                    trade_commitment.deposit,
+                    Precision::Exact,
+                    Preservation::Expendable,
+                    Fortitude::Polite,
+                );
+                if let Ok(credit) = forfeited {
+                    T::ForfeitedDeposits::on_unbalanced(credit);
+                }
+                Self::deposit_event(Event::TradeCommitmentForfeited(account_id, commitment));
+            }
+        }
+        weight
+    }
+
+    /// Refund the deposit of a trade commitment and remove it from storage
+    pub(crate) fn close_trade_commitment(
+        account_id: &AccountIdOf<T>,
+        commitment: T::Hash,
+        trade_commitment: TradeCommitmentOf<T, I>,
+    ) -> DispatchResult {
+        Self::pay_currency(account_id, trade_commitment.deposit)?;
+        let sweep_block = trade_commitment.expiry.saturating_add(One::one());
+        <CommitmentExpiries<T, I>>::mutate(sweep_block, |expiring| {
+            expiring.retain(|(id, hash)| id != account_id || *hash != commitment)
+        });
+        <TradeCommitments<T, I>>::remove(account_id, commitment);
+        Ok(())
+    }
+}
@@ -0,0 +1,1022 @@
+//! Concentrated-liquidity exchanges (Uniswap V3 style). Liquidity providers deposit within a price
+//! range between two ticks, and each position is held by its provider instead of being minted
+//! as liquidity tokens. Prices are the amount of the asset per unit of currency (the reciprocal
+//! of the spot price), `1.0001^tick` at each tick. Trades move the price across the ticks at which
+//! positions start or end, and the fees of a trade are shared by the positions in range.
+
+use crate::{
+    curve::{CurveError, Fee, Side},
+    permissions::ExchangeOperation,
+    AccountIdOf, AssetBalanceOf, AssetIdOf, BalanceOf, ConcentratedPools, Config, ConfigHelper,
+    Curve, Error, Event, ExchangeOf, Exchanges, InitializedTicks, LiquidityChange,
+    OnLiquidityChanged, Pallet, Positions, Ticks,
+};
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{
+    dispatch::DispatchResult,
+    ensure,
+    sp_runtime::{
+        traits::{Saturating, UniqueSaturatedInto, Zero},
+        FixedPointNumber, FixedU128, Permill, RuntimeDebug,
+    },
+    traits::{fungible::Mutate as _, fungibles::Mutate, tokens::Preservation, Get},
+};
+use scale_info::TypeInfo;
+use sp_core::{U256, U512};
+use sp_std::vec::Vec;
+
+/// Lowest tick of a position, at which the price is about `2^-128`.
+pub const MIN_TICK: i32 = -887_272;
+
+/// Highest tick of a position, at which the price is about `2^128`.
+pub const MAX_TICK: i32 = 887_272;
+
+/// Greatest tick spacing of a concentrated-liquidity exchange.
+pub const MAX_TICK_SPACING: u32 = 16_384;
+
+/// Square root price at `MIN_TICK`, in Q64.96 fixed point.
+const MIN_SQRT_PRICE: U256 = U256([4_295_128_739, 0, 0, 0]);
+
+/// Square root price at `MAX_TICK`, in Q64.96 fixed point.
+const MAX_SQRT_PRICE: U256 = U256([
+    6_743_328_256_752_651_558,
+    17_280_870_778_742_802_505,
+    4_294_805_859,
+    0,
+]);
+
+/// `2^128 / 1.0001^(2^i / 2)`, rounded, for each bit `i` of a tick.
+const SQRT_PRICE_FACTORS: [u128; 20] = [
+    0xfffcb933bd6fad37aa2d162d1a594001,
+    0xfff97272373d413259a46990580e213a,
+    0xfff2e50f5f656932ef12357cf3c7fdcc,
+    0xffe5caca7e10e4e61c3624eaa0941cd0,
+    0xffcb9843d60f6159c9db58835c926644,
+    0xff973b41fa98c081472e6896dfb254c0,
+    0xff2ea16466c96a3843ec78b326b52861,
+    0xfe5dee046a99a2a811c461f1969c3053,
+    0xfcbe86c7900a88aedcffc83b479aa3a4,
+    0xf987a7253ac413176f2b074cf7815e54,
+    0xf3392b0822b70005940c7a398e4b70f3,
+    0xe7159475a2c29b7443b29c7fa6e889d9,
+    0xd097f3bdfd2022b8845ad8f792aa5825,
+    0xa9f746462d870fdf8a65dc1f90e061e5,
+    0x70d869a156d2a1b890bb3df62baf32f7,
+    0x31be135f97d08fd981231505542fcfa6,
+    0x09aa508b5b7a84e1c677de54f3e99bc9,
+    0x005d6af8dedb81196699c329225ee604,
+    0x00002216e584f5fa1ea926041bedfe98,
+    0x00000000048a170391f7dc42444e8fa2,
+];
+
+/// State of a concentrated-liquidity exchange.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct ConcentratedPool {
+    /// Square root of the price, in Q64.96 fixed point
+    pub sqrt_price: U256,
+    /// Greatest tick at which the price isn't higher than the current price
+    pub tick: i32,
+    /// Liquidity of the positions in range of the current price
+    pub liquidity: u128,
+    /// Fees earned per unit of liquidity since the creation of the exchange
+    pub fee_growth: FeeGrowth,
+}
+
+/// Fees earned per unit of liquidity in each token, in Q64.64 fixed point. The values wrap around
+/// on overflow, so only differences between them are meaningful.
+#[derive(
+    Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, Default, MaxEncodedLen, TypeInfo,
+)]
+pub struct FeeGrowth {
+    pub currency: u128,
+    pub asset: u128,
+}
+
+impl FeeGrowth {
+    /// Growth since `earlier`
+    fn since(self, earlier: FeeGrowth) -> FeeGrowth {
+        FeeGrowth {
+            currency: self.currency.wrapping_sub(earlier.currency),
+            asset: self.asset.wrapping_sub(earlier.asset),
+        }
+    }
+
+    /// Add `fee` paid in the `side` token and shared by `liquidity`
+    fn accrue(&mut self, side: Side, fee: u128, liquidity: u128) {
+        let growth = ((U256::from(fee) << 64) / U256::from(liquidity)).low_u128();
+        match side {
+            Side::Currency => self.currency = self.currency.wrapping_add(growth),
+            Side::Asset => self.asset = self.asset.wrapping_add(growth),
+        }
+    }
+}
+
+/// A tick at which positions start or end.
+#[derive(
+    Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, Default, MaxEncodedLen, TypeInfo,
+)]
+pub struct TickInfo {
+    /// Total liquidity of the positions starting or ending at the tick
+    pub liquidity_gross: u128,
+    /// Liquidity added when the price crosses the tick upwards, and removed when it crosses downwards
+    pub liquidity_net: i128,
+    /// Fee growth on the other side of the tick than the current price
+    pub fee_growth_outside: FeeGrowth,
+}
+
+/// Liquidity provided by an account between two ticks, and the fees it earned.
+#[derive(
+    Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, Default, MaxEncodedLen, TypeInfo,
+)]
+pub struct Position {
+    pub liquidity: u128,
+    /// Fee growth inside the price range when the fees of the position were last updated
+    pub fee_growth_inside: FeeGrowth,
+    /// Uncollected currency fees
+    pub currency_fees: u128,
+    /// Uncollected asset fees
+    pub asset_fees: u128,
+}
+
+/// A swap against a concentrated-liquidity exchange, computed without updating the exchange.
+pub(crate) struct ConcentratedSwap {
+    pub input_amount: u128,
+    pub output_amount: u128,
+    /// Part of the provider fee paid to the referrer of the swap, in the input
+    pub referral_amount: u128,
+    /// State of the exchange after the swap
+    pub pool: ConcentratedPool,
+    /// Ticks crossed by the swap, with their updated state
+    crossed_ticks: Vec<(i32, TickInfo)>,
+}
+
+/// A part of a swap within the price range between two initialized ticks.
+struct SwapStep {
+    sqrt_price: U256,
+    amount_in: u128,
+    amount_out: u128,
+    fee: u128,
+}
+
+impl<T: Config<I>, I: 'static> Pallet<T, I> {
+    /// Output amount of a fixed-input trade adding to the `input` reserve of a concentrated-liquidity exchange
+    pub(crate) fn concentrated_output_amount(
+        asset_id: &AssetIdOf<T, I>,
+        input: Side,
+        input_amount: BalanceOf<T, I>,
+        fee: &Fee<BalanceOf<T, I>>,
+    ) -> Result<BalanceOf<T, I>, Error<T, I>> {
+        let swap = Self::concentrated_swap(
+            asset_id,
+            input,
+            input_amount.unique_saturated_into(),
+            true,
+            fee,
+            Permill::zero(),
+        )?;
+        BalanceOf::<T, I>::try_from(swap.output_amount).map_err(|_| Error::<T, I>::Overflow)
+    }
+
+    /// Input amount of a fixed-output trade adding to the `input` reserve of a concentrated-liquidity exchange
+    pub(crate) fn concentrated_input_amount(
+        asset_id: &AssetIdOf<T, I>,
+        input: Side,
+        output_amount: BalanceOf<T, I>,
+        fee: &Fee<BalanceOf<T, I>>,
+    ) -> Result<BalanceOf<T, I>, Error<T, I>> {
+        let swap = Self::concentrated_swap(
+            asset_id,
+            input,
+            output_amount.unique_saturated_into(),
+            false,
+            fee,
+            Permill::zero(),
+        )?;
+        BalanceOf::<T, I>::try_from(swap.input_amount).map_err(|_| Error::<T, I>::Overflow)
+    }
+
+    /// Spot price (currency per token) of a concentrated-liquidity exchange,
+    /// not including the provider fee
+    pub(crate) fn concentrated_spot_price(asset_id: &AssetIdOf<T, I>) -> Option<FixedU128> {
+        let pool = Self::concentrated_pools(asset_id)?;
+        let price = (U512::from(FixedU128::DIV) << 192) / pool.sqrt_price.full_mul(pool.sqrt_price);
+        let price = U256::try_from(price).ok()?;
+        to_u128(price).ok().map(FixedU128::from_inner)
+    }
+
+    /// Swap `input_amount` added to the `input` reserve of a concentrated-liquidity exchange for
+    /// `output_amount` of the other reserve, and update the price, the crossed ticks and the fee
+    /// growth of the exchange. If `exact_input`, the whole input is swapped, and the output must
+    /// be at least `output_amount`. Otherwise exactly `output_amount` is swapped out, and the input
+    /// it takes must be at most `input_amount`.
+    /// Return the part `referral_share` of the provider fee, withheld from the positions,
+    /// and the number of crossed ticks.
+    pub(crate) fn execute_concentrated_swap(
+        asset_id: &AssetIdOf<T, I>,
+        input: Side,
+        input_amount: BalanceOf<T, I>,
+        output_amount: BalanceOf<T, I>,
+        exact_input: bool,
+        fee: &Fee<BalanceOf<T, I>>,
+        referral_share: Permill,
+    ) -> Result<(BalanceOf<T, I>, u32), Error<T, I>> {
+        let input_amount: u128 = input_amount.unique_saturated_into();
+        let output_amount: u128 = output_amount.unique_saturated_into();
+        let amount = if exact_input {
+            input_amount
+        } else {
+            output_amount
+        };
+        let swap =
+            Self::concentrated_swap(asset_id, input, amount, exact_input, fee, referral_share)?;
+        ensure!(
+            swap.input_amount <= input_amount && swap.output_amount >= output_amount,
+            Error::<T, I>::NotEnoughLiquidity
+        );
+        let crossed_ticks = swap.crossed_ticks.len() as u32;
+        for (tick, info) in swap.crossed_ticks {
+            <Ticks<T, I>>::insert(asset_id, tick, info);
+        }
+        <ConcentratedPools<T, I>>::insert(asset_id, swap.pool);
+        let referral_amount = BalanceOf::<T, I>::try_from(swap.referral_amount)
+            .map_err(|_| Error::<T, I>::Overflow)?;
+        Ok((referral_amount, crossed_ticks))
+    }
+
+    /// Compute a swap of `amount` (input if `exact_input`, output otherwise) added to the `input`
+    /// reserve of a concentrated-liquidity exchange. The price moves from one initialized tick to
+    /// the next until the whole amount is swapped, crossing at most `MaxTicksCrossed` ticks.
+    /// The provider `fee` is shared by the positions in range, except its part `referral_share`.
+    pub(crate) fn concentrated_swap(
+        asset_id: &AssetIdOf<T, I>,
+        input: Side,
+        amount: u128,
+        exact_input: bool,
+        fee: &Fee<BalanceOf<T, I>>,
+        referral_share: Permill,
+    ) -> Result<ConcentratedSwap, Error<T, I>> {
+        let mut pool = Self::concentrated_pools(asset_id).ok_or(Error::<T, I>::ExchangeNotFound)?;
+        let initialized_ticks = Self::initialized_ticks(asset_id);
+        let fee_numerator: u128 = fee.net_numerator.unique_saturated_into();
+        let fee_denominator: u128 = fee.denominator.unique_saturated_into();
+        // Selling currency lowers the price
+        let price_falls = input == Side::Currency;
+        let price_limit = if price_falls {
+            MIN_SQRT_PRICE + 1
+        } else {
+            MAX_SQRT_PRICE - 1
+        };
+        let mut remaining = amount;
+        let mut computed = 0u128;
+        let mut referral_amount = 0u128;
+        let mut crossed_ticks = Vec::new();
+        while remaining > 0 && pool.sqrt_price != price_limit {
+            let above = initialized_ticks.partition_point(|tick| *tick <= pool.tick);
+            let next_tick = if price_falls {
+                above.checked_sub(1).map(|index| initialized_ticks[index])
+            } else {
+                initialized_ticks.get(above).copied()
+            };
+            let target = match next_tick {
+                Some(tick) if price_falls => sqrt_price_at_tick(tick).max(price_limit),
+                Some(tick) => sqrt_price_at_tick(tick).min(price_limit),
+                None => price_limit,
+            };
+            let step = swap_step(
+                pool.sqrt_price,
+                target,
+                pool.liquidity,
+                remaining,
+                exact_input,
+                fee_numerator,
+                fee_denominator,
+            )?;
+            if exact_input {
+                remaining = remaining.saturating_sub(step.amount_in.saturating_add(step.fee));
+                computed = computed
+                    .checked_add(step.amount_out)
+                    .ok_or(Error::<T, I>::Overflow)?;
+            } else {
+                remaining = remaining.saturating_sub(step.amount_out);
+                computed = computed
+                    .checked_add(step.amount_in)
+                    .and_then(|computed| computed.checked_add(step.fee))
+                    .ok_or(Error::<T, I>::Overflow)?;
+            }
+            let step_referral = referral_share.mul_floor(step.fee);
+            referral_amount = referral_amount.saturating_add(step_referral);
+            if pool.liquidity > 0 {
+                pool.fee_growth.accrue(
+                    input,
+                    step.fee.saturating_sub(step_referral),
+                    pool.liquidity,
+                );
+            }
+            pool.sqrt_price = step.sqrt_price;
+
+            match next_tick {
+                Some(tick) if pool.sqrt_price == sqrt_price_at_tick(tick) => {
+                    ensure!(
+                        crossed_ticks.len() < T::MaxTicksCrossed::get() as usize,
+                        Error::<T, I>::TooManyTicksCrossed
+                    );
+                    let mut info = Self::ticks(asset_id, tick).unwrap_or_default();
+                    info.fee_growth_outside = pool.fee_growth.since(info.fee_growth_outside);
+                    let liquidity_delta = if price_falls {
+                        info.liquidity_net
+                            .checked_neg()
+                            .ok_or(Error::<T, I>::Overflow)?
+                    } else {
+                        info.liquidity_net
+                    };
+                    pool.liquidity = add_liquidity_delta(pool.liquidity, liquidity_delta)?;
+                    pool.tick = if price_falls { tick - 1 } else { tick };
+                    crossed_ticks.push((tick, info));
+                }
+                _ => pool.tick = tick_at_sqrt_price(pool.sqrt_price),
+            }
+        }
+        ensure!(remaining.is_zero(), Error::<T, I>::NotEnoughLiquidity);
+        let (input_amount, output_amount) = if exact_input {
+            (amount, computed)
+        } else {
+            (computed, amount)
+        };
+        Ok(ConcentratedSwap {
+            input_amount,
+            output_amount,
+            referral_amount,
+            pool,
+            crossed_ticks,
+        })
+    }
+
+    /// Check that a price range is between `MIN_TICK` and `MAX_TICK`,
+    /// and that its ticks are multiples of the tick spacing of the exchange
+    pub(crate) fn check_tick_range(
+        exchange: &ExchangeOf<T, I>,
+        lower_tick: i32,
+        upper_tick: i32,
+    ) -> Result<(), Error<T, I>> {
+        let tick_spacing = match exchange.curve {
+            Curve::Concentrated { tick_spacing } => tick_spacing as i32,
+            _ => return Err(Error::<T, I>::UnsupportedCurve),
+        };
+        ensure!(
+            MIN_TICK <= lower_tick
+                && lower_tick < upper_tick
+                && upper_tick <= MAX_TICK
+                && lower_tick % tick_spacing == 0
+                && upper_tick % tick_spacing == 0,
+            Error::<T, I>::InvalidTickRange
+        );
+        Ok(())
+    }
+
+    /// Add the position with the most liquidity which the deposit of at most `max_currency` and
+    /// `max_tokens` provides between `lower_tick` and `upper_tick`. Perform currency and asset
+    /// transfers, update exchange balances, emit event
+    #[frame_support::transactional]
+    pub(crate) fn do_mint_position(
+        mut exchange: ExchangeOf<T, I>,
+        provider: AccountIdOf<T>,
+        lower_tick: i32,
+        upper_tick: i32,
+        max_currency: BalanceOf<T, I>,
+        max_tokens: AssetBalanceOf<T, I>,
+        preservation: Preservation,
+    ) -> DispatchResult {
+        // -------------------- Token/liquidity computation --------------------
+        let asset_id = exchange.asset_id.clone();
+        let mut pool =
+            Self::concentrated_pools(&asset_id).ok_or(Error::<T, I>::ExchangeNotFound)?;
+        let liquidity = liquidity_for_amounts(
+            pool.sqrt_price,
+            sqrt_price_at_tick(lower_tick),
+            sqrt_price_at_tick(upper_tick),
+            max_currency.unique_saturated_into(),
+            max_tokens.unique_saturated_into(),
+        )
+        .map_err(Error::<T, I>::from)?;
+        ensure!(liquidity > 0, Error::<T, I>::PositionLiquidityIsZero);
+        let (currency_amount, token_amount) =
+            position_amounts(&pool, lower_tick, upper_tick, liquidity, true)
+                .map_err(Error::<T, I>::from)?;
+        let currency_amount =
+            BalanceOf::<T, I>::try_from(currency_amount).map_err(|_| Error::<T, I>::Overflow)?;
+        let token_amount =
+            AssetBalanceOf::<T, I>::try_from(token_amount).map_err(|_| Error::<T, I>::Overflow)?;
+        ensure!(currency_amount <= max_currency, Error::<T, I>::MaxCurrencyTooLow);
+        ensure!(token_amount <= max_tokens, Error::<T, I>::MaxTokensTooLow);
+        Self::check_provider_approved(&asset_id, &provider)?;
+        Self::check_operation_allowed(
+            ExchangeOperation::AddLiquidity,
+            &asset_id,
+            &provider,
+            &provider,
+            currency_amount,
+            token_amount,
+        )?;
+
+        // ----------------------------- State update ----------------------------
+        let liquidity_delta = i128::try_from(liquidity).map_err(|_| Error::<T, I>::Overflow)?;
+        Self::update_position(
+            &asset_id,
+            &mut pool,
+            &provider,
+            lower_tick,
+            upper_tick,
+            liquidity_delta,
+        )?;
+        <ConcentratedPools<T, I>>::insert(&asset_id, pool);
+
+        // --------------------- Currency & token transfer ---------------------
+        let pallet_account = T::pallet_account();
+        T::QuoteAsset::transfer(&provider, &pallet_account, currency_amount, preservation)?;
+        T::Assets::transfer(
+            asset_id.clone(),
+            &provider,
+            &pallet_account,
+            token_amount,
+            preservation,
+        )?;
+
+        // -------------------------- Balances update --------------------------
+        exchange.currency_reserve.saturating_accrue(currency_amount);
+        exchange.token_reserve.saturating_accrue(token_amount);
+        <Exchanges<T, I>>::insert(asset_id.clone(), exchange);
+
+        // -------------------------- Notify handlers --------------------------
+        T::OnLiquidityChanged::on_liquidity_changed(&LiquidityChange::PositionMinted {
+            provider: provider.clone(),
+            asset_id: asset_id.clone(),
+            lower_tick,
+            upper_tick,
+            liquidity,
+            currency_amount,
+            token_amount,
+        });
+
+        // ---------------------------- Emit event -----------------------------
+        Self::deposit_event(Event::PositionMinted(
+            provider,
+            asset_id,
+            lower_tick,
+            upper_tick,
+            liquidity,
+            currency_amount,
+            token_amount,
+        ));
+        Ok(())
+    }
+
+    /// Remove `liquidity` from a position and collect its fees. Perform currency and asset
+    /// transfers, update exchange balances, emit event
+    #[frame_support::transactional]
+    pub(crate) fn do_burn_position(
+        mut exchange: ExchangeOf<T, I>,
+        provider: AccountIdOf<T>,
+        lower_tick: i32,
+        upper_tick: i32,
+        liquidity: u128,
+        min_currency: BalanceOf<T, I>,
+        min_tokens: AssetBalanceOf<T, I>,
+    ) -> DispatchResult {
+        // --------------- Withdrawn currency/tokens computation ---------------
+        let asset_id = exchange.asset_id.clone();
+        let position_key = (asset_id.clone(), lower_tick, upper_tick);
+        let position =
+            Self::positions(&provider, &position_key).ok_or(Error::<T, I>::PositionNotFound)?;
+        ensure!(position.liquidity >= liquidity, Error::<T, I>::PositionLiquidityTooLow);
+        let mut pool =
+            Self::concentrated_pools(&asset_id).ok_or(Error::<T, I>::ExchangeNotFound)?;
+        let (currency_amount, token_amount) =
+            position_amounts(&pool, lower_tick, upper_tick, liquidity, false)
+                .map_err(Error::<T, I>::from)?;
+
+        // ----------------------------- State update ----------------------------
+        let liquidity_delta = i128::try_from(liquidity)
+            .ok()
+            .and_then(i128::checked_neg)
+            .ok_or(Error::<T, I>::Overflow)?;
+        let position = Self::update_position(
+            &asset_id,
+            &mut pool,
+            &provider,
+            lower_tick,
+            upper_tick,
+            liquidity_delta,
+        )?;
+        <ConcentratedPools<T, I>>::insert(&asset_id, pool);
+        let currency_amount = currency_amount.saturating_add(position.currency_fees);
+        let token_amount = token_amount.saturating_add(position.asset_fees);
+        if position.liquidity.is_zero() {
+            <Positions<T, I>>::remove(&provider, &position_key);
+        } else {
+            let position = Position {
+                currency_fees: 0,
+                asset_fees: 0,
+                ..position
+            };
+            <Positions<T, I>>::insert(&provider, &position_key, position);
+        }
+        let currency_amount =
+            BalanceOf::<T, I>::try_from(currency_amount).map_err(|_| Error::<T, I>::Overflow)?;
+        let token_amount =
+            AssetBalanceOf::<T, I>::try_from(token_amount).map_err(|_| Error::<T, I>::Overflow)?;
+        ensure!(currency_amount >= min_currency, Error::<T, I>::MinCurrencyTooHigh);
+        ensure!(token_amount >= min_tokens, Error::<T, I>::MinTokensTooHigh);
+        Self::check_operation_allowed(
+            ExchangeOperation::RemoveLiquidity,
+            &asset_id,
+            &provider,
+            &provider,
+            currency_amount,
+            token_amount,
+        )?;
+
+        // --------------------- Currency & token transfer ---------------------
+        let pallet_account = T::pallet_account();
+        Self::pay_currency(&provider, currency_amount)?;
+        T::Assets::transfer(
+            asset_id.clone(),
+            &pallet_account,
+            &provider,
+            token_amount,
+            Preservation::Expendable,
+        )?;
+
+        // -------------------------- Balances update --------------------------
+        exchange.currency_reserve.saturating_reduce(currency_amount);
+        exchange.token_reserve.saturating_reduce(token_amount);
+        <Exchanges<T, I>>::insert(asset_id.clone(), exchange);
+
+        // -------------------------- Notify handlers --------------------------
+        T::OnLiquidityChanged::on_liquidity_changed(&LiquidityChange::PositionBurned {
+            provider: provider.clone(),
+            asset_id: asset_id.clone(),
+            lower_tick,
+            upper_tick,
+            liquidity,
+            currency_amount,
+            token_amount,
+        });
+
+        // ---------------------------- Emit event -----------------------------
+        Self::deposit_event(Event::PositionBurned(
+            provider,
+            asset_id,
+            lower_tick,
+            upper_tick,
+            liquidity,
+            currency_amount,
+            token_amount,
+        ));
+        Ok(())
+    }
+
+    /// Add `liquidity_delta` to a position and to the ticks at its ends, and accrue the fees
+    /// the position earned since its last update. Return the updated position,
+    /// which is also stored unless it has no liquidity left.
+    fn update_position(
+        asset_id: &AssetIdOf<T, I>,
+        pool: &mut ConcentratedPool,
+        owner: &AccountIdOf<T>,
+        lower_tick: i32,
+        upper_tick: i32,
+        liquidity_delta: i128,
+    ) -> Result<Position, Error<T, I>> {
+        let lower = Self::update_tick(asset_id, pool, lower_tick, liquidity_delta)?;
+        let upper_delta = liquidity_delta
+            .checked_neg()
+            .ok_or(Error::<T, I>::Overflow)?;
+        let upper = Self::update_tick(asset_id, pool, upper_tick, upper_delta)?;
+        if lower_tick <= pool.tick && pool.tick < upper_tick {
+            pool.liquidity = add_liquidity_delta(pool.liquidity, liquidity_delta)?;
+        }
+
+        // Fee growth below the lower tick and above the upper tick
+        let below = if pool.tick >= lower_tick {
+            lower.fee_growth_outside
+        } else {
+            pool.fee_growth.since(lower.fee_growth_outside)
+        };
+        let above = if pool.tick < upper_tick {
+            upper.fee_growth_outside
+        } else {
+            pool.fee_growth.since(upper.fee_growth_outside)
+        };
+        let fee_growth_inside = pool.fee_growth.since(below).since(above);
+
+        let key = (asset_id.clone(), lower_tick, upper_tick);
+        let mut position = Self::positions(owner, &key).unwrap_or_default();
+        let earned = fee_growth_inside.since(position.fee_growth_inside);
+        position.currency_fees = position
+            .currency_fees
+            .saturating_add(fees_earned(position.liquidity, earned.currency));
+        position.asset_fees = position
+            .asset_fees
+            .saturating_add(fees_earned(position.liquidity, earned.asset));
+        position.fee_growth_inside = fee_growth_inside;
+        position.liquidity = add_liquidity_delta(position.liquidity, liquidity_delta)?;
+        if !position.liquidity.is_zero() {
+            <Positions<T, I>>::insert(owner, &key, position);
+        }
+        Ok(position)
+    }
+
+    /// Add `liquidity_delta` to the liquidity starting at `tick` (or ending, if negative).
+    /// Initialize the tick when its first position starts or ends at it,
+    /// and remove it when no position is left. Return the updated tick.
+    fn update_tick(
+        asset_id: &AssetIdOf<T, I>,
+        pool: &ConcentratedPool,
+        tick: i32,
+        liquidity_delta: i128,
+    ) -> Result<TickInfo, Error<T, I>> {
+        let mut info = match Self::ticks(asset_id, tick) {
+            Some(info) => info,
+            None => {
+                <InitializedTicks<T, I>>::try_mutate(asset_id, |ticks| {
+                    let index = ticks.partition_point(|initialized| *initialized < tick);
+                    ticks
+                        .try_insert(index, tick)
+                        .map_err(|_| Error::<T, I>::TooManyTicks)
+                })?;
+                // All fees so far are assumed to be earned below the tick
+                let fee_growth_outside = if tick <= pool.tick {
+                    pool.fee_growth
+                } else {
+                    Default::default()
+                };
+                TickInfo {
+                    fee_growth_outside,
+                    ..Default::default()
+                }
+            }
+        };
+        info.liquidity_gross = add_liquidity_delta(info.liquidity_gross, liquidity_delta)?;
+        info.liquidity_net = info
+            .liquidity_net
+            .checked_add(liquidity_delta)
+            .ok_or(Error::<T, I>::Overflow)?;
+        if info.liquidity_gross.is_zero() {
+            <Ticks<T, I>>::remove(asset_id, tick);
+            <InitializedTicks<T, I>>::mutate(asset_id, |ticks| ticks.retain(|t| *t != tick));
+        } else {
+            <Ticks<T, I>>::insert(asset_id, tick, info);
+        }
+        Ok(info)
+    }
+}
+
+/// Square root price at `tick`, in Q64.96 fixed point, rounded up
+pub fn sqrt_price_at_tick(tick: i32) -> U256 {
+    let abs_tick = tick.unsigned_abs();
+    debug_assert!(abs_tick <= MAX_TICK as u32);
+    let mut ratio = if abs_tick & 1 != 0 {
+        U256::from(SQRT_PRICE_FACTORS[0])
+    } else {
+        U256::one() << 128
+    };
+    for (bit, factor) in SQRT_PRICE_FACTORS.iter().enumerate().skip(1) {
+        if abs_tick & (1 << bit) != 0 {
+            ratio = (ratio * U256::from(*factor)) >> 128;
+        }
+    }
+    if tick > 0 {
+        ratio = U256::MAX / ratio;
+    }
+    let rounding = if ratio.low_u32() == 0 { 0 } else { 1 };
+    (ratio >> 32) + rounding
+}
+
+/// Greatest tick at which the square root price isn't higher than `sqrt_price`
+pub fn tick_at_sqrt_price(sqrt_price: U256) -> i32 {
+    let (mut low, mut high) = (MIN_TICK, MAX_TICK);
+    while high - low > 1 {
+        let middle = low + (high - low) / 2;
+        if sqrt_price_at_tick(middle) <= sqrt_price {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    low
+}
+
+/// Square root price, in Q64.96 fixed point, of an exchange whose spot price (currency per token)
+/// is `spot_price`, if it's within the prices of the ticks
+pub fn sqrt_price_of(spot_price: FixedU128) -> Option<U256> {
+    if spot_price.is_zero() {
+        return None;
+    }
+    let scaled = (U512::from(FixedU128::DIV) << 192) / U512::from(spot_price.into_inner());
+    let sqrt_price = U256::try_from(scaled.integer_sqrt()).ok()?;
+    (MIN_SQRT_PRICE <= sqrt_price && sqrt_price < MAX_SQRT_PRICE).then_some(sqrt_price)
+}
+
+/// Move the price from `sqrt_price` towards `target` by a swap of `remaining` (input if
+/// `exact_input`, output otherwise), using `liquidity` as long as the price stays in range.
+fn swap_step(
+    sqrt_price: U256,
+    target: U256,
+    liquidity: u128,
+    remaining: u128,
+    exact_input: bool,
+    fee_numerator: u128,
+    fee_denominator: u128,
+) -> Result<SwapStep, CurveError> {
+    let price_falls = sqrt_price >= target;
+    let step_input = |from: U256, to: U256| {
+        if price_falls {
+            currency_delta(to, from, liquidity, true)
+        } else {
+            asset_delta(from, to, liquidity, true)
+        }
+    };
+    let step_output = |from: U256, to: U256| {
+        if price_falls {
+            asset_delta(to, from, liquidity, false)
+        } else {
+            currency_delta(from, to, liquidity, false)
+        }
+    };
+
+    let (next, amount_in, amount_out) = if exact_input {
+        let net_remaining = mul_div(
+            U256::from(remaining),
+            U256::from(fee_numerator),
+            U256::from(fee_denominator),
+            false,
+        )?;
+        let to_target = step_input(sqrt_price, target)?;
+        if net_remaining >= to_target {
+            (target, to_target, step_output(sqrt_price, target)?)
+        } else {
+            let next = if price_falls {
+                sqrt_price_after_currency(sqrt_price, liquidity, net_remaining, true)?
+            } else {
+                sqrt_price_after_asset(sqrt_price, liquidity, net_remaining, true)?
+            };
+            (next, step_input(sqrt_price, next)?, step_output(sqrt_price, next)?)
+        }
+    } else {
+        let to_target = step_output(sqrt_price, target)?;
+        if U256::from(remaining) >= to_target {
+            (target, step_input(sqrt_price, target)?, to_target)
+        } else {
+            let next = if price_falls {
+                sqrt_price_after_asset(sqrt_price, liquidity, U256::from(remaining), false)?
+            } else {
+                sqrt_price_after_currency(sqrt_price, liquidity, U256::from(remaining), false)?
+            };
+            // Rounding can't make the output exceed the remaining amount
+            let amount_out = step_output(sqrt_price, next)?.min(U256::from(remaining));
+            (next, step_input(sqrt_price, next)?, amount_out)
+        }
+    };
+    let amount_in = to_u128(amount_in)?;
+    let fee = if exact_input && next != target {
+        // The price stops within the range, so the rest of the input is the fee
+        remaining.saturating_sub(amount_in)
+    } else {
+        to_u128(mul_div(
+            U256::from(amount_in),
+            U256::from(fee_denominator.saturating_sub(fee_numerator)),
+            U256::from(fee_numerator),
+            true,
+        )?)?
+    };
+    Ok(SwapStep {
+        sqrt_price: next,
+        amount_in,
+        amount_out: to_u128(amount_out)?,
+        fee,
+    })
+}
+
+/// Currency amount of `liquidity` between two square root prices:
+/// `liquidity * (upper - lower) / (upper * lower)`
+fn currency_delta(
+    lower: U256,
+    upper: U256,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<U256, CurveError> {
+    let numerator = U256::from(liquidity) << 96;
+    let scaled = mul_div(numerator, upper - lower, upper, round_up)?;
+    if round_up {
+        div_up(scaled, lower)
+    } else {
+        Ok(scaled / lower)
+    }
+}
+
+/// Asset amount of `liquidity` between two square root prices: `liquidity * (upper - lower)`
+fn asset_delta(
+    lower: U256,
+    upper: U256,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<U256, CurveError> {
+    mul_div(U256::from(liquidity), upper - lower, U256::one() << 96, round_up)
+}
+
+/// Square root price after adding (or removing) `amount` of currency, rounded up:
+/// `liquidity * sqrt_price / (liquidity ± amount * sqrt_price)`
+fn sqrt_price_after_currency(
+    sqrt_price: U256,
+    liquidity: u128,
+    amount: U256,
+    add: bool,
+) -> Result<U256, CurveError> {
+    let numerator = U256::from(liquidity) << 96;
+    let product = amount.full_mul(sqrt_price);
+    let denominator = if add {
+        U512::from(numerator)
+            .checked_add(product)
+            .ok_or(CurveError::Overflow)?
+    } else {
+        U512::from(numerator)
+            .checked_sub(product)
+            .filter(|denominator| !denominator.is_zero())
+            .ok_or(CurveError::NotEnoughLiquidity)?
+    };
+    let (quotient, remainder) = numerator.full_mul(sqrt_price).div_mod(denominator);
+    let quotient = if remainder.is_zero() {
+        quotient
+    } else {
+        quotient + 1
+    };
+    U256::try_from(quotient).map_err(|_| CurveError::Overflow)
+}
+
+/// Square root price after adding (or removing) `amount` of the asset, rounded down:
+/// `sqrt_price ± amount / liquidity`
+fn sqrt_price_after_asset(
+    sqrt_price: U256,
+    liquidity: u128,
+    amount: U256,
+    add: bool,
+) -> Result<U256, CurveError> {
+    if add {
+        let delta = mul_div(amount, U256::one() << 96, U256::from(liquidity), false)?;
+        sqrt_price.checked_add(delta).ok_or(CurveError::Overflow)
+    } else {
+        let delta = mul_div(amount, U256::one() << 96, U256::from(liquidity), true)?;
+        sqrt_price
+            .checked_sub(delta)
+            .filter(|next| !next.is_zero())
+            .ok_or(CurveError::NotEnoughLiquidity)
+    }
+}
+
+/// Greatest liquidity provided between two square root prices by at most `currency_amount`
+/// and `asset_amount`, at the current `sqrt_price`
+fn liquidity_for_amounts(
+    sqrt_price: U256,
+    lower: U256,
+    upper: U256,
+    currency_amount: u128,
+    asset_amount: u128,
+) -> Result<u128, CurveError> {
+    let for_currency = |lower: U256| -> Result<U256, CurveError> {
+        let product = mul_div(lower, upper, U256::one() << 96, false)?;
+        mul_div(U256::from(currency_amount), product, upper - lower, false)
+    };
+    let for_asset = |upper: U256| -> Result<U256, CurveError> {
+        mul_div(U256::from(asset_amount), U256::one() << 96, upper - lower, false)
+    };
+    let liquidity = if sqrt_price <= lower {
+        for_currency(lower)?
+    } else if sqrt_price < upper {
+        for_currency(sqrt_price)?.min(for_asset(sqrt_price)?)
+    } else {
+        for_asset(upper)?
+    };
+    to_u128(liquidity)
+}
+
+/// Currency and asset amounts of `liquidity` between two ticks at the current price of `pool`
+fn position_amounts(
+    pool: &ConcentratedPool,
+    lower_tick: i32,
+    upper_tick: i32,
+    liquidity: u128,
+    round_up: bool,
+) -> Result<(u128, u128), CurveError> {
+    let (lower, upper) = (sqrt_price_at_tick(lower_tick), sqrt_price_at_tick(upper_tick));
+    let (currency_amount, asset_amount) = if pool.tick < lower_tick {
+        (currency_delta(lower, upper, liquidity, round_up)?, U256::zero())
+    } else if pool.tick < upper_tick {
+        (
+            currency_delta(pool.sqrt_price, upper, liquidity, round_up)?,
+            asset_delta(lower, pool.sqrt_price, liquidity, round_up)?,
+        )
+    } else {
+        (U256::zero(), asset_delta(lower, upper, liquidity, round_up)?)
+    };
+    Ok((to_u128(currency_amount)?, to_u128(asset_amount)?))
+}
+
+/// Fees earned by `liquidity` over `fee_growth`, rounded down
+fn fees_earned(liquidity: u128, fee_growth: u128) -> u128 {
+    (U256::from(liquidity).full_mul(U256::from(fee_growth)) >> 64).low_u128()
+}
+
+fn add_liquidity_delta(liquidity: u128, delta: i128) -> Result<u128, CurveError> {
+    if delta < 0 {
+        liquidity.checked_sub(delta.unsigned_abs())
+    } else {
+        liquidity.checked_add(delta.unsigned_abs())
+    }
+    .ok_or(CurveError::Overflow)
+}
+
+/// `a * b / denominator` without overflow of the product
+fn mul_div(a: U256, b: U256, denominator: U256, round_up: bool) -> Result<U256, CurveError> {
+    if denominator.is_zero() {
+        return Err(CurveError::Overflow);
+    }
+    let (quotient, remainder) = a.full_mul(b).div_mod(U512::from(denominator));
+    let quotient = if round_up && !remainder.is_zero() {
+        quotient + 1
+    } else {
+        quotient
+    };
+    U256::try_from(quotient).map_err(|_| CurveError::Overflow)
+}
+
+fn div_up(a: U256, b: U256) -> Result<U256, CurveError> {
+    mul_div(a, U256::one(), b, true)
+}
+
+fn to_u128(value: U256) -> Result<u128, CurveError> {
+    if value.bits() > 128 {
+        return Err(CurveError::Overflow);
+    }
+    Ok(value.low_u128())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const Q96: U256 = U256([0, 1 << 32, 0, 0]);
+
+    #[test]
+    fn sqrt_price_at_tick_matches_reference_values() {
+        assert_eq!(sqrt_price_at_tick(0), Q96);
+        assert_eq!(sqrt_price_at_tick(1), U256::from(79_232_123_823_359_799_118_286_999_568u128));
+        assert_eq!(sqrt_price_at_tick(-1), U256::from(79_224_201_403_219_477_170_569_942_574u128));
+        assert_eq!(sqrt_price_at_tick(MIN_TICK), MIN_SQRT_PRICE);
+        assert_eq!(sqrt_price_at_tick(MAX_TICK), MAX_SQRT_PRICE);
+    }
+
+    #[test]
+    fn tick_at_sqrt_price_inverts_sqrt_price_at_tick() {
+        for tick in [
+            MIN_TICK,
+            -500_000,
+            -1_000,
+            -1,
+            0,
+            1,
+            1_000,
+            500_000,
+            MAX_TICK - 1,
+        ] {
+            let sqrt_price = sqrt_price_at_tick(tick);
+            assert_eq!(tick_at_sqrt_price(sqrt_price), tick);
+            if tick > MIN_TICK {
+                assert_eq!(tick_at_sqrt_price(sqrt_price - 1), tick - 1);
+            }
+        }
+    }
+
+    #[test]
+    fn sqrt_price_of_takes_reciprocal_of_spot_price() {
+        assert_eq!(sqrt_price_of(FixedU128::saturating_from_integer(1)), Some(Q96));
+        assert_eq!(sqrt_price_of(FixedU128::saturating_from_integer(4)), Some(Q96 >> 1));
+        assert_eq!(sqrt_price_of(FixedU128::saturating_from_rational(1, 4)), Some(Q96 << 1));
+        assert_eq!(sqrt_price_of(FixedU128::zero()), None);
+    }
+
+    #[test]
+    fn liquidity_for_amounts_matches_position_amounts() {
+        let pool = ConcentratedPool {
+            sqrt_price: Q96,
+            tick: 0,
+            liquidity: 0,
+            fee_growth: Default::default(),
+        };
+        let (lower, upper) = (sqrt_price_at_tick(-1_000), sqrt_price_at_tick(1_000));
+        let amount = 1_000_000_000_000;
+        let liquidity = liquidity_for_amounts(Q96, lower, upper, amount, amount).unwrap();
+        assert_eq!(liquidity, 20_505_166_268_106);
+        let (currency_amount, asset_amount) =
+            position_amounts(&pool, -1_000, 1_000, liquidity, true).unwrap();
+        assert!(currency_amount <= amount && asset_amount <= amount);
+    }
+}
@@ -0,0 +1,1205 @@
+//! Pricing curves of exchanges, i.e. the invariants which trades against the reserves keep.
+
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::sp_runtime::{
+    traits::{AtLeast32BitUnsigned, Saturating, UniqueSaturatedInto},
+    FixedPointNumber, FixedU128, PerThing, Permill, RuntimeDebug,
+};
+use scale_info::TypeInfo;
+use sp_core::{U256, U512};
+
+/// Maximum number of iterations of the StableSwap Newton's method computations.
+const MAX_ITERATIONS: u32 = 255;
+
+/// One in the 18-decimal fixed point numbers of the weighted curve computations.
+const ONE: u128 = 1_000_000_000_000_000_000;
+
+/// `ln(2)` in 18-decimal fixed point.
+const LN_2: u128 = 693_147_180_559_945_309;
+
+/// Bound of the relative error of fixed point powers, `10^-14` of `ONE`.
+const POW_ERROR: u128 = 10_000;
+
+/// Lowest weight of a reserve of a weighted exchange.
+pub const MIN_WEIGHT: Permill = Permill::from_percent(1);
+
+/// Errors of pricing curve computations.
+#[derive(Clone, Copy, Eq, PartialEq, RuntimeDebug)]
+pub enum CurveError {
+    /// An overflow occurred, or the computation didn't converge
+    Overflow,
+    /// The output amount isn't lower than the output reserve
+    NotEnoughLiquidity,
+    /// The curve prices trades by state other than the reserves
+    Stateful,
+}
+
+/// Provider fee of a trade. The part `net_numerator / denominator` of the input amount is traded,
+/// and the rest is left in the input reserve.
+#[derive(Clone, Copy, Eq, PartialEq, RuntimeDebug)]
+pub struct Fee<Balance> {
+    pub net_numerator: Balance,
+    pub denominator: Balance,
+}
+
+/// Side of an exchange, i.e. one of its reserves.
+#[derive(Clone, Copy, Eq, PartialEq, RuntimeDebug)]
+pub enum Side {
+    Currency,
+    Asset,
+}
+
+/// Pricing of trades against the reserves of an exchange. All amounts are in the same balance type,
+/// and rounding always favours the exchange.
+pub trait PricingCurve<Balance: AtLeast32BitUnsigned + Copy> {
+    /// Output amount for a fixed-input trade adding to the `input` reserve, rounded down.
+    fn output_amount(
+        &self,
+        input: Side,
+        input_amount: Balance,
+        input_reserve: Balance,
+        output_reserve: Balance,
+        fee: &Fee<Balance>,
+    ) -> Result<Balance, CurveError>;
+
+    /// Input amount for a fixed-output trade adding to the `input` reserve, rounded up.
+    fn input_amount(
+        &self,
+        input: Side,
+        output_amount: Balance,
+        input_reserve: Balance,
+        output_reserve: Balance,
+        fee: &Fee<Balance>,
+    ) -> Result<Balance, CurveError>;
+
+    /// Amount of liquidity tokens minted for the first deposit to an exchange.
+    fn initial_liquidity(
+        &self,
+        currency_amount: Balance,
+        token_amount: Balance,
+    ) -> Result<Balance, CurveError>;
+
+    /// Amount of liquidity tokens minted for a deposit to reserves backing `total_liquidity`,
+    /// rounded down. Later deposits are in the ratio of the reserves, and mint the share
+    /// of the existing liquidity which they add to the smaller side.
+    fn liquidity_minted(
+        &self,
+        currency_amount: Balance,
+        token_amount: Balance,
+        currency_reserve: Balance,
+        token_reserve: Balance,
+        total_liquidity: Balance,
+    ) -> Result<Balance, CurveError> {
+        if total_liquidity.is_zero() {
+            return self.initial_liquidity(currency_amount, token_amount);
+        }
+        let currency_liquidity = share(currency_amount, currency_reserve, total_liquidity)?;
+        let token_liquidity = share(token_amount, token_reserve, total_liquidity)?;
+        Ok(currency_liquidity.min(token_liquidity))
+    }
+
+    /// Amounts of the currency and tokens withdrawn by burning `liquidity_amount` of
+    /// `total_liquidity`, i.e. its share of both reserves, rounded down.
+    fn liquidity_withdrawal(
+        &self,
+        liquidity_amount: Balance,
+        currency_reserve: Balance,
+        token_reserve: Balance,
+        total_liquidity: Balance,
+    ) -> Result<(Balance, Balance), CurveError> {
+        Ok((
+            share(liquidity_amount, total_liquidity, currency_reserve)?,
+            share(liquidity_amount, total_liquidity, token_reserve)?,
+        ))
+    }
+
+    /// Marginal price of the asset in currency at the given reserves, not including the fee,
+    /// rounded down.
+    fn spot_price(
+        &self,
+        currency_reserve: Balance,
+        asset_reserve: Balance,
+    ) -> Result<FixedU128, CurveError>;
+}
+
+/// Pricing curve of an exchange, chosen at its creation.
+#[derive(
+    Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, Default, MaxEncodedLen, TypeInfo,
+)]
+pub enum Curve {
+    /// Constant product `x * y = k` of Uniswap V1.
+    #[default]
+    #[codec(index = 0)]
+    ConstantProduct,
+    /// Curve-style StableSwap for pegged pairs. A higher amplification coefficient keeps the price
+    /// closer to 1:1 for a wider range of reserves.
+    #[codec(index = 1)]
+    StableSwap { amplification: u32 },
+    /// Balancer-style weighted constant product, with `currency_weight` the weight
+    /// of the currency reserve and the rest the weight of the asset reserve.
+    #[codec(index = 2)]
+    Weighted { currency_weight: Permill },
+    /// Uniswap V3-style concentrated liquidity, provided within price ranges between ticks
+    /// which are multiples of `tick_spacing`. Trades are priced by the positions in range
+    /// instead of the reserves, see the `concentrated` module.
+    #[codec(index = 3)]
+    Concentrated { tick_spacing: u32 },
+}
+
+impl Curve {
+    /// Amplification coefficient of a StableSwap curve
+    pub fn amplification(&self) -> Option<u32> {
+        match self {
+            Curve::StableSwap { amplification } => Some(*amplification),
+            _ => None,
+        }
+    }
+
+    /// Weight of the currency reserve of a weighted curve
+    pub fn currency_weight(&self) -> Option<Permill> {
+        match self {
+            Curve::Weighted { currency_weight } => Some(*currency_weight),
+            _ => None,
+        }
+    }
+}
+
+/// A linear change of the amplification coefficient of a StableSwap exchange, from `initial`
+/// in block `start` to `target` in block `end`.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct AmplificationRamp<BlockNumber> {
+    pub initial: u32,
+    pub target: u32,
+    pub start: BlockNumber,
+    pub end: BlockNumber,
+}
+
+impl<BlockNumber: AtLeast32BitUnsigned + Copy> AmplificationRamp<BlockNumber> {
+    /// The amplification coefficient in block `now`
+    pub fn at(&self, now: BlockNumber) -> u32 {
+        interpolate(self.initial, self.target, self.start, self.end, now)
+    }
+}
+
+/// Weights of a weighted exchange shifting linearly, from `start_weight` of the currency reserve
+/// in block `start` to `end_weight` in block `end`, e.g. in a liquidity bootstrapping pool.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct WeightSchedule<BlockNumber> {
+    pub start_weight: Permill,
+    pub end_weight: Permill,
+    pub start: BlockNumber,
+    pub end: BlockNumber,
+}
+
+impl<BlockNumber: AtLeast32BitUnsigned + Copy> WeightSchedule<BlockNumber> {
+    /// The weight of the currency reserve in block `now`
+    pub fn at(&self, now: BlockNumber) -> Permill {
+        Permill::from_parts(interpolate(
+            self.start_weight.deconstruct(),
+            self.end_weight.deconstruct(),
+            self.start,
+            self.end,
+            now,
+        ))
+    }
+}
+
+/// Value in block `now` of a linear change from `initial` in block `start`
+/// to `target` in block `end`
+fn interpolate<BlockNumber: AtLeast32BitUnsigned + Copy>(
+    initial: u32,
+    target: u32,
+    start: BlockNumber,
+    end: BlockNumber,
+    now: BlockNumber,
+) -> u32 {
+    if now >= end {
+        return target;
+    }
+    if now <= start {
+        return initial;
+    }
+    let elapsed: u128 = now.saturating_sub(start).unique_saturated_into();
+    let duration: u128 = end.saturating_sub(start).unique_saturated_into();
+    let (initial, target) = (u128::from(initial), u128::from(target));
+    let value = if target > initial {
+        initial + (target - initial) * elapsed / duration
+    } else {
+        initial - (initial - target) * elapsed / duration
+    };
+    value as u32
+}
+
+impl<Balance: AtLeast32BitUnsigned + Copy> PricingCurve<Balance> for Curve {
+    fn output_amount(
+        &self,
+        input: Side,
+        input_amount: Balance,
+        input_reserve: Balance,
+        output_reserve: Balance,
+        fee: &Fee<Balance>,
+    ) -> Result<Balance, CurveError> {
+        match self {
+            Curve::ConstantProduct => ConstantProduct.output_amount(
+                input,
+                input_amount,
+                input_reserve,
+                output_reserve,
+                fee,
+            ),
+            Curve::StableSwap { amplification } => StableSwap::new(*amplification).output_amount(
+                input,
+                input_amount,
+                input_reserve,
+                output_reserve,
+                fee,
+            ),
+            Curve::Weighted { currency_weight } => WeightedProduct::new(*currency_weight)
+                .output_amount(input, input_amount, input_reserve, output_reserve, fee),
+            Curve::Concentrated { .. } => Err(CurveError::Stateful),
+        }
+    }
+
+    fn input_amount(
+        &self,
+        input: Side,
+        output_amount: Balance,
+        input_reserve: Balance,
+        output_reserve: Balance,
+        fee: &Fee<Balance>,
+    ) -> Result<Balance, CurveError> {
+        match self {
+            Curve::ConstantProduct => ConstantProduct.input_amount(
+                input,
+                output_amount,
+                input_reserve,
+                output_reserve,
+                fee,
+            ),
+            Curve::StableSwap { amplification } => StableSwap::new(*amplification).input_amount(
+                input,
+                output_amount,
+                input_reserve,
+                output_reserve,
+                fee,
+            ),
+            Curve::Weighted { currency_weight } => WeightedProduct::new(*currency_weight)
+                .input_amount(input, output_amount, input_reserve, output_reserve, fee),
+            Curve::Concentrated { .. } => Err(CurveError::Stateful),
+        }
+    }
+
+    fn initial_liquidity(
+        &self,
+        currency_amount: Balance,
+        token_amount: Balance,
+    ) -> Result<Balance, CurveError> {
+        match self {
+            Curve::ConstantProduct => {
+                ConstantProduct.initial_liquidity(currency_amount, token_amount)
+            }
+            Curve::StableSwap { amplification } => {
+                StableSwap::new(*amplification).initial_liquidity(currency_amount, token_amount)
+            }
+            Curve::Weighted { currency_weight } => WeightedProduct::new(*currency_weight)
+                .initial_liquidity(currency_amount, token_amount),
+            Curve::Concentrated { .. } => Err(CurveError::Stateful),
+        }
+    }
+
+    fn liquidity_minted(
+        &self,
+        currency_amount: Balance,
+        token_amount: Balance,
+        currency_reserve: Balance,
+        token_reserve: Balance,
+        total_liquidity: Balance,
+    ) -> Result<Balance, CurveError> {
+        match self {
+            Curve::ConstantProduct => ConstantProduct.liquidity_minted(
+                currency_amount,
+                token_amount,
+                currency_reserve,
+                token_reserve,
+                total_liquidity,
+            ),
+            Curve::StableSwap { amplification } => StableSwap::new(*amplification)
+                .liquidity_minted(
+                    currency_amount,
+                    token_amount,
+                    currency_reserve,
+                    token_reserve,
+                    total_liquidity,
+                ),
+            Curve::Weighted { currency_weight } => WeightedProduct::new(*currency_weight)
+                .liquidity_minted(
+                    currency_amount,
+                    token_amount,
+                    currency_reserve,
+                    token_reserve,
+                    total_liquidity,
+                ),
+            Curve::Concentrated { .. } => Err(CurveError::Stateful),
+        }
+    }
+
+    fn liquidity_withdrawal(
+        &self,
+        liquidity_amount: Balance,
+        currency_reserve: Balance,
+        token_reserve: Balance,
+        total_liquidity: Balance,
+    ) -> Result<(Balance, Balance), CurveError> {
+        match self {
+            Curve::ConstantProduct => ConstantProduct.liquidity_withdrawal(
+                liquidity_amount,
+                currency_reserve,
+                token_reserve,
+                total_liquidity,
+            ),
+            Curve::StableSwap { amplification } => StableSwap::new(*amplification)
+                .liquidity_withdrawal(
+                    liquidity_amount,
+                    currency_reserve,
+                    token_reserve,
+                    total_liquidity,
+                ),
+            Curve::Weighted { currency_weight } => WeightedProduct::new(*currency_weight)
+                .liquidity_withdrawal(
+                    liquidity_amount,
+                    currency_reserve,
+                    token_reserve,
+                    total_liquidity,
+                ),
+            Curve::Concentrated { .. } => Err(CurveError::Stateful),
+        }
+    }
+
+    fn spot_price(
+        &self,
+        currency_reserve: Balance,
+        asset_reserve: Balance,
+    ) -> Result<FixedU128, CurveError> {
+        match self {
+            Curve::ConstantProduct => ConstantProduct.spot_price(currency_reserve, asset_reserve),
+            Curve::StableSwap { amplification } => {
+                StableSwap::new(*amplification).spot_price(currency_reserve, asset_reserve)
+            }
+            Curve::Weighted { currency_weight } => {
+                WeightedProduct::new(*currency_weight).spot_price(currency_reserve, asset_reserve)
+            }
+            Curve::Concentrated { .. } => Err(CurveError::Stateful),
+        }
+    }
+}
+
+/// Constant product `x * y = k` curve.
+pub struct ConstantProduct;
+
+impl<Balance: AtLeast32BitUnsigned + Copy> PricingCurve<Balance> for ConstantProduct {
+    fn output_amount(
+        &self,
+        _input: Side,
+        input_amount: Balance,
+        input_reserve: Balance,
+        output_reserve: Balance,
+        fee: &Fee<Balance>,
+    ) -> Result<Balance, CurveError> {
+        let input_amount_with_fee = input_amount
+            .checked_mul(&fee.net_numerator)
+            .ok_or(CurveError::Overflow)?;
+        let numerator = input_amount_with_fee
+            .checked_mul(&output_reserve)
+            .ok_or(CurveError::Overflow)?;
+        let denominator = input_reserve
+            .checked_mul(&fee.denominator)
+            .ok_or(CurveError::Overflow)?
+            .checked_add(&input_amount_with_fee)
+            .ok_or(CurveError::Overflow)?;
+        Ok(numerator / denominator)
+    }
+
+    fn input_amount(
+        &self,
+        _input: Side,
+        output_amount: Balance,
+        input_reserve: Balance,
+        output_reserve: Balance,
+        fee: &Fee<Balance>,
+    ) -> Result<Balance, CurveError> {
+        if output_amount >= output_reserve {
+            return Err(CurveError::NotEnoughLiquidity);
+        }
+        let numerator = input_reserve
+            .checked_mul(&output_amount)
+            .ok_or(CurveError::Overflow)?
+            .checked_mul(&fee.denominator)
+            .ok_or(CurveError::Overflow)?;
+        let denominator = output_reserve
+            .saturating_sub(output_amount)
+            .checked_mul(&fee.net_numerator)
+            .ok_or(CurveError::Overflow)?;
+        Ok((numerator / denominator).saturating_add(Balance::one()))
+    }
+
+    /// The first deposit mints liquidity equal to the deposited currency.
+    fn initial_liquidity(
+        &self,
+        currency_amount: Balance,
+        _token_amount: Balance,
+    ) -> Result<Balance, CurveError> {
+        Ok(currency_amount)
+    }
+
+    /// `x / y`
+    fn spot_price(
+        &self,
+        currency_reserve: Balance,
+        asset_reserve: Balance,
+    ) -> Result<FixedU128, CurveError> {
+        fixed_ratio(to_u256(currency_reserve).into(), to_u256(asset_reserve).into())
+    }
+}
+
+/// StableSwap curve of two tokens, with the invariant
+/// `4A * (x + y) + D = 4A * D + D^3 / (4 * x * y)`, where `A` is the amplification coefficient.
+/// Computations use Newton's method as in Curve's reference implementation.
+pub struct StableSwap {
+    amplification: u32,
+}
+
+impl StableSwap {
+    pub fn new(amplification: u32) -> Self {
+        Self { amplification }
+    }
+
+    /// `A * n^n`, with `n = 2` tokens
+    fn amplification_n(&self) -> U256 {
+        U256::from(self.amplification) * 4
+    }
+
+    /// The invariant `D` of the reserves `x` and `y`, i.e. their total amount when balanced.
+    pub fn invariant(&self, x: U256, y: U256) -> Result<U256, CurveError> {
+        let sum = x.checked_add(y).ok_or(CurveError::Overflow)?;
+        if sum.is_zero() {
+            return Ok(U256::zero());
+        }
+        if x.is_zero() || y.is_zero() || self.amplification == 0 {
+            return Err(CurveError::Overflow);
+        }
+        let ann = self.amplification_n();
+        let mut d = sum;
+        for _ in 0..MAX_ITERATIONS {
+            // D^3 / (4 * x * y)
+            let d_product = checked_mul(d, d)?
+                .checked_div(x * 2)
+                .ok_or(CurveError::Overflow)?;
+            let d_product = checked_mul(d_product, d)?
+                .checked_div(y * 2)
+                .ok_or(CurveError::Overflow)?;
+            let previous = d;
+            let numerator = checked_add(checked_mul(ann, sum)?, checked_mul(d_product, 2.into())?)?;
+            let denominator =
+                checked_add(checked_mul(ann - 1, d)?, checked_mul(d_product, 3.into())?)?;
+            d = checked_mul(numerator, d)?
+                .checked_div(denominator)
+                .ok_or(CurveError::Overflow)?;
+            if difference(d, previous) <= U256::one() {
+                return Ok(d);
+            }
+        }
+        Err(CurveError::Overflow)
+    }
+
+    /// The reserve `y` which keeps the invariant `d` with the other reserve equal to `x`.
+    pub fn reserve(&self, x: U256, d: U256) -> Result<U256, CurveError> {
+        if x.is_zero() || self.amplification == 0 {
+            return Err(CurveError::Overflow);
+        }
+        let ann = self.amplification_n();
+        // c = D^3 / (4 * x * Ann), b = x + D / Ann, and `y^2 + (b - D) * y = c`
+        let c = checked_mul(d, d)?
+            .checked_div(x * 2)
+            .ok_or(CurveError::Overflow)?;
+        let c = checked_mul(c, d)?
+            .checked_div(ann * 2)
+            .ok_or(CurveError::Overflow)?;
+        let b = checked_add(x, d / ann)?;
+        let mut y = d;
+        for _ in 0..MAX_ITERATIONS {
+            let previous = y;
+            let denominator = checked_add(checked_mul(y, 2.into())?, b)?
+                .checked_sub(d)
+                .filter(|denominator| !denominator.is_zero())
+                .ok_or(CurveError::Overflow)?;
+            y = checked_add(checked_mul(y, y)?, c)? / denominator;
+            if difference(y, previous) <= U256::one() {
+                return Ok(y);
+            }
+        }
+        Err(CurveError::Overflow)
+    }
+}
+
+impl<Balance: AtLeast32BitUnsigned + Copy> PricingCurve<Balance> for StableSwap {
+    fn output_amount(
+        &self,
+        _input: Side,
+        input_amount: Balance,
+        input_reserve: Balance,
+        output_reserve: Balance,
+        fee: &Fee<Balance>,
+    ) -> Result<Balance, CurveError> {
+        let input_amount_with_fee = checked_mul(to_u256(input_amount), to_u256(fee.net_numerator))?
+            .checked_div(to_u256(fee.denominator))
+            .ok_or(CurveError::Overflow)?;
+        let (x, y) = (to_u256(input_reserve), to_u256(output_reserve));
+        let d = self.invariant(x, y)?;
+        // Both reserves are computed from the same `D`, so that its rounding doesn't add up
+        // to the output
+        let output_reserve = self.reserve(x, d)?.min(y);
+        let new_output_reserve = self.reserve(checked_add(x, input_amount_with_fee)?, d)?;
+        let output_amount = output_reserve
+            .saturating_sub(new_output_reserve)
+            .saturating_sub(U256::one());
+        from_u256(output_amount)
+    }
+
+    fn input_amount(
+        &self,
+        _input: Side,
+        output_amount: Balance,
+        input_reserve: Balance,
+        output_reserve: Balance,
+        fee: &Fee<Balance>,
+    ) -> Result<Balance, CurveError> {
+        if output_amount >= output_reserve {
+            return Err(CurveError::NotEnoughLiquidity);
+        }
+        let (x, y) = (to_u256(input_reserve), to_u256(output_reserve));
+        let d = self.invariant(x, y)?;
+        let input_reserve = self.reserve(y, d)?.min(x);
+        let new_input_reserve = self.reserve(y - to_u256(output_amount), d)?;
+        let input_amount_with_fee = new_input_reserve
+            .saturating_sub(input_reserve)
+            .saturating_add(U256::one());
+        let input_amount = checked_mul(input_amount_with_fee, to_u256(fee.denominator))?
+            .checked_div(to_u256(fee.net_numerator))
+            .ok_or(CurveError::Overflow)?;
+        from_u256(input_amount.saturating_add(U256::one()))
+    }
+
+    /// The first deposit mints liquidity equal to the invariant `D`.
+    fn initial_liquidity(
+        &self,
+        currency_amount: Balance,
+        token_amount: Balance,
+    ) -> Result<Balance, CurveError> {
+        from_u256(self.invariant(to_u256(currency_amount), to_u256(token_amount))?)
+    }
+
+    /// `x * (4A * y + P) / (y * (4A * x + P))`, with `P = D^3 / (4 * x * y)`, i.e. the ratio
+    /// of the partial derivatives of the invariant
+    fn spot_price(
+        &self,
+        currency_reserve: Balance,
+        asset_reserve: Balance,
+    ) -> Result<FixedU128, CurveError> {
+        let (x, y) = (to_u256(currency_reserve), to_u256(asset_reserve));
+        let d = self.invariant(x, y)?;
+        if d.is_zero() {
+            return Err(CurveError::Overflow);
+        }
+        let d_product = checked_mul(d, d)?
+            .checked_div(x * 2)
+            .ok_or(CurveError::Overflow)?;
+        let d_product = checked_mul(d_product, d)?
+            .checked_div(y * 2)
+            .ok_or(CurveError::Overflow)?;
+        let ann = self.amplification_n();
+        let currency_derivative = checked_add(checked_mul(ann, x)?, d_product)?;
+        let asset_derivative = checked_add(checked_mul(ann, y)?, d_product)?;
+        fixed_ratio(
+            U512::from(x) * U512::from(asset_derivative),
+            U512::from(y) * U512::from(currency_derivative),
+        )
+    }
+}
+
+/// Balancer-style weighted constant product `x^wx * y^wy = k` curve, where `wx` and `wy` are
+/// the weights of the reserves. Powers are computed in 18-decimal fixed point.
+pub struct WeightedProduct {
+    currency_weight: Permill,
+}
+
+impl WeightedProduct {
+    pub fn new(currency_weight: Permill) -> Self {
+        Self { currency_weight }
+    }
+
+    /// Weights (in parts per million) of the input and the output reserves
+    fn weights(&self, input: Side) -> Result<(U256, U256), CurveError> {
+        let currency_weight = self.currency_weight.deconstruct();
+        let asset_weight = Permill::ACCURACY.saturating_sub(currency_weight);
+        if currency_weight == 0 || asset_weight == 0 {
+            return Err(CurveError::Overflow);
+        }
+        match input {
+            Side::Currency => Ok((currency_weight.into(), asset_weight.into())),
+            Side::Asset => Ok((asset_weight.into(), currency_weight.into())),
+        }
+    }
+}
+
+impl<Balance: AtLeast32BitUnsigned + Copy> PricingCurve<Balance> for WeightedProduct {
+    /// `y * (1 - (x / (x + dx))^(wx / wy))`
+    fn output_amount(
+        &self,
+        input: Side,
+        input_amount: Balance,
+        input_reserve: Balance,
+        output_reserve: Balance,
+        fee: &Fee<Balance>,
+    ) -> Result<Balance, CurveError> {
+        let (input_weight, output_weight) = self.weights(input)?;
+        let input_amount_with_fee = checked_mul(to_u256(input_amount), to_u256(fee.net_numerator))?
+            .checked_div(to_u256(fee.denominator))
+            .ok_or(CurveError::Overflow)?;
+        let (x, y, one) = (to_u256(input_reserve), to_u256(output_reserve), U256::from(ONE));
+        // Rounding the base and the power up rounds the output down
+        let base = div_up(checked_mul(x, one)?, checked_add(x, input_amount_with_fee)?)?;
+        let exponent = checked_mul(input_weight, one)? / output_weight;
+        let power = pow_up(base, exponent)?;
+        if power >= one {
+            return Ok(Balance::zero());
+        }
+        from_u256(checked_mul(y, one - power)? / one)
+    }
+
+    /// `x * ((y / (y - dy))^(wy / wx) - 1)`
+    fn input_amount(
+        &self,
+        input: Side,
+        output_amount: Balance,
+        input_reserve: Balance,
+        output_reserve: Balance,
+        fee: &Fee<Balance>,
+    ) -> Result<Balance, CurveError> {
+        if output_amount >= output_reserve {
+            return Err(CurveError::NotEnoughLiquidity);
+        }
+        let (input_weight, output_weight) = self.weights(input)?;
+        let (x, y, one) = (to_u256(input_reserve), to_u256(output_reserve), U256::from(ONE));
+        let base = div_up(checked_mul(y, one)?, y - to_u256(output_amount))?;
+        let exponent = div_up(checked_mul(output_weight, one)?, input_weight)?;
+        let power = pow_up(base, exponent)?;
+        let input_amount_with_fee = div_up(checked_mul(x, power.saturating_sub(one))?, one)?;
+        let input_amount = checked_mul(input_amount_with_fee, to_u256(fee.denominator))?
+            .checked_div(to_u256(fee.net_numerator))
+            .ok_or(CurveError::Overflow)?;
+        from_u256(input_amount.saturating_add(U256::one()))
+    }
+
+    /// The first deposit mints liquidity equal to the deposited currency.
+    fn initial_liquidity(
+        &self,
+        currency_amount: Balance,
+        _token_amount: Balance,
+    ) -> Result<Balance, CurveError> {
+        Ok(currency_amount)
+    }
+
+    /// `(x / wx) / (y / wy)`
+    fn spot_price(
+        &self,
+        currency_reserve: Balance,
+        asset_reserve: Balance,
+    ) -> Result<FixedU128, CurveError> {
+        let (currency_weight, asset_weight) = self.weights(Side::Currency)?;
+        fixed_ratio(
+            U512::from(to_u256(currency_reserve)) * U512::from(asset_weight),
+            U512::from(to_u256(asset_reserve)) * U512::from(currency_weight),
+        )
+    }
+}
+
+/// `base^exponent` of fixed point numbers, rounded up by the error bound of the computation
+fn pow_up(base: U256, exponent: U256) -> Result<U256, CurveError> {
+    let (negative, ln_base) = ln(base)?;
+    let power = exp(negative, checked_mul(ln_base, exponent)? / ONE)?;
+    let error = checked_mul(power, POW_ERROR.into())? / ONE;
+    checked_add(checked_add(power, error)?, U256::one())
+}
+
+/// Natural logarithm of a positive fixed point number, as (whether it's negative, absolute value)
+fn ln(x: U256) -> Result<(bool, U256), CurveError> {
+    let one = U256::from(ONE);
+    if x.is_zero() {
+        return Err(CurveError::Overflow);
+    }
+    if x < one {
+        // ln(x) = -ln(1 / x)
+        Ok((true, ln_at_least_one(one * one / x)))
+    } else {
+        Ok((false, ln_at_least_one(x)))
+    }
+}
+
+/// `ln(x)` of a fixed point `x >= 1`. With `x = 2^k * m` and `m` in `[1, 2)`,
+/// `ln(x) = k * ln(2) + 2 * atanh((m - 1) / (m + 1))`, and the series of `atanh` converges fast.
+fn ln_at_least_one(x: U256) -> U256 {
+    let one = U256::from(ONE);
+    let k = (x / one).bits() - 1;
+    let m = x >> k;
+    let z = (m - one) * one / (m + one);
+    let z_squared = z * z / one;
+    let (mut term, mut sum, mut n) = (z, U256::zero(), 1u32);
+    while !term.is_zero() {
+        sum += term / n;
+        term = term * z_squared / one;
+        n += 2;
+    }
+    U256::from(LN_2) * k + sum * 2
+}
+
+/// `e^y` of a fixed point `y`, which is negative if `negative` is set
+fn exp(negative: bool, y: U256) -> Result<U256, CurveError> {
+    let (one, ln_2) = (U256::from(ONE), U256::from(LN_2));
+    // e^y = 2^k * e^r, with `r` in `[0, ln(2))`
+    let k = y / ln_2;
+    let r = y - k * ln_2;
+    let (mut term, mut sum, mut i) = (one, one, 1u32);
+    loop {
+        term = term * r / one / i;
+        if term.is_zero() {
+            break;
+        }
+        sum += term;
+        i += 1;
+    }
+    if negative {
+        if k >= U256::from(256) {
+            return Ok(U256::zero());
+        }
+        Ok((one * one / sum) >> k.as_usize())
+    } else {
+        // e^r < 2, so the result fits in 256 bits
+        if k > U256::from(190) {
+            return Err(CurveError::Overflow);
+        }
+        Ok(sum << k.as_usize())
+    }
+}
+
+fn div_up(a: U256, b: U256) -> Result<U256, CurveError> {
+    let quotient = a.checked_div(b).ok_or(CurveError::Overflow)?;
+    if quotient * b == a {
+        Ok(quotient)
+    } else {
+        checked_add(quotient, U256::one())
+    }
+}
+
+/// The share `amount / total` of `value`, rounded down as in fixed point arithmetic
+fn share<Balance: AtLeast32BitUnsigned>(
+    amount: Balance,
+    total: Balance,
+    value: Balance,
+) -> Result<Balance, CurveError> {
+    if total.is_zero() {
+        return Err(CurveError::Overflow);
+    }
+    let (amount, total, value): (u128, u128, u128) = (
+        amount.unique_saturated_into(),
+        total.unique_saturated_into(),
+        value.unique_saturated_into(),
+    );
+    let share = FixedU128::saturating_from_rational(amount, total);
+    from_u256(share.saturating_mul_int(value).into())
+}
+
+/// `numerator / denominator` as a fixed point number, rounded down
+fn fixed_ratio(numerator: U512, denominator: U512) -> Result<FixedU128, CurveError> {
+    if denominator.is_zero() {
+        return Err(CurveError::Overflow);
+    }
+    let inner = numerator
+        .checked_mul(FixedU128::DIV.into())
+        .ok_or(CurveError::Overflow)?
+        / denominator;
+    if inner.bits() > 128 {
+        return Err(CurveError::Overflow);
+    }
+    Ok(FixedU128::from_inner(inner.low_u128()))
+}
+
+fn to_u256<Balance: AtLeast32BitUnsigned>(value: Balance) -> U256 {
+    U256::from(UniqueSaturatedInto::<u128>::unique_saturated_into(value))
+}
+
+fn from_u256<Balance: AtLeast32BitUnsigned>(value: U256) -> Result<Balance, CurveError> {
+    if value.bits() > 128 {
+        return Err(CurveError::Overflow);
+    }
+    Balance::try_from(value.low_u128()).map_err(|_| CurveError::Overflow)
+}
+
+fn difference(a: U256, b: U256) -> U256 {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
+fn checked_add(a: U256, b: U256) -> Result<U256, CurveError> {
+    a.checked_add(b).ok_or(CurveError::Overflow)
+}
+
+fn checked_mul(a: U256, b: U256) -> Result<U256, CurveError> {
+    a.checked_mul(b).ok_or(CurveError::Overflow)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FEE: Fee<u128> = Fee {
+        net_numerator: 997,
+        denominator: 1000,
+    };
+    const CASES: usize = 2_000;
+
+    /// xorshift64* generator, so that the property tests are reproducible
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u128 {
+            self.0 ^= self.0 >> 12;
+            self.0 ^= self.0 << 25;
+            self.0 ^= self.0 >> 27;
+            let high = self.0.wrapping_mul(0x2545_f491_4f6c_dd1d) as u128;
+            self.0 ^= self.0 >> 12;
+            self.0 ^= self.0 << 25;
+            self.0 ^= self.0 >> 27;
+            let low = self.0.wrapping_mul(0x2545_f491_4f6c_dd1d) as u128;
+            (high << 64) | low
+        }
+
+        /// A number in `[low, high]`
+        fn range(&mut self, low: u128, high: u128) -> u128 {
+            low + self.next() % (high - low + 1)
+        }
+
+        /// Reserves of up to 10^24, with a ratio of at most 100:1
+        fn reserves(&mut self) -> (u128, u128) {
+            let x = self.range(1_000_000, 1_000_000_000_000_000_000_000_000);
+            let y = x * self.range(1, 100) / self.range(1, 100);
+            (x, y.max(1_000_000))
+        }
+    }
+
+    fn product(x: u128, y: u128) -> U256 {
+        U256::from(x) * U256::from(y)
+    }
+
+    #[test]
+    fn constant_product_output_keeps_invariant() {
+        let mut rng = Rng(1);
+        for _ in 0..CASES {
+            let (x, y) = rng.reserves();
+            let input = rng.range(1, x);
+            let output = ConstantProduct
+                .output_amount(Side::Currency, input, x, y, &FEE)
+                .unwrap();
+            assert!(output < y);
+            assert!(product(x + input, y - output) >= product(x, y));
+        }
+    }
+
+    #[test]
+    fn constant_product_input_keeps_invariant() {
+        let mut rng = Rng(2);
+        for _ in 0..CASES {
+            let (x, y) = rng.reserves();
+            let output = rng.range(1, y - 1);
+            let input = ConstantProduct
+                .input_amount(Side::Currency, output, x, y, &FEE)
+                .unwrap();
+            assert!(product(x + input, y - output) >= product(x, y));
+        }
+    }
+
+    #[test]
+    fn stable_swap_output_keeps_invariant() {
+        let mut rng = Rng(3);
+        for _ in 0..CASES {
+            let (x, y) = rng.reserves();
+            let curve = StableSwap::new(rng.range(1, 10_000) as u32);
+            let input = rng.range(1, x);
+            let output = curve
+                .output_amount(Side::Currency, input, x, y, &FEE)
+                .unwrap();
+            assert!(output < y);
+            let before = curve.invariant(x.into(), y.into()).unwrap();
+            let after = curve
+                .invariant((x + input).into(), (y - output).into())
+                .unwrap();
+            assert!(after >= before);
+        }
+    }
+
+    #[test]
+    fn stable_swap_input_keeps_invariant() {
+        let mut rng = Rng(4);
+        for _ in 0..CASES {
+            let (x, y) = rng.reserves();
+            let curve = StableSwap::new(rng.range(1, 10_000) as u32);
+            let output = rng.range(1, y - 1);
+            let input = curve
+                .input_amount(Side::Currency, output, x, y, &FEE)
+                .unwrap();
+            let before = curve.invariant(x.into(), y.into()).unwrap();
+            let after = curve
+                .invariant((x + input).into(), (y - output).into())
+                .unwrap();
+            assert!(after >= before);
+        }
+    }
+
+    #[test]
+    fn stable_swap_not_enough_liquidity() {
+        let curve = StableSwap::new(100);
+        assert_eq!(
+            PricingCurve::<u128>::input_amount(&curve, Side::Currency, 1_000, 1_000, 1_000, &FEE),
+            Err(CurveError::NotEnoughLiquidity)
+        );
+    }
+
+    #[test]
+    fn stable_swap_lower_slippage_than_constant_product() {
+        let reserve = 1_000_000_000_000;
+        let input = reserve / 10;
+        let constant_product = ConstantProduct
+            .output_amount(Side::Currency, input, reserve, reserve, &FEE)
+            .unwrap();
+        let stable_swap = StableSwap::new(100)
+            .output_amount(Side::Currency, input, reserve, reserve, &FEE)
+            .unwrap();
+        assert_eq!(constant_product, 90_661_089_388);
+        assert_eq!(stable_swap, 99_650_080_568);
+    }
+
+    /// Whether adding `input` to the reserve `x` and removing `output` from the reserve `y`
+    /// doesn't decrease `x^wx * y^wy`, compared in logarithms
+    fn keeps_weighted_invariant(
+        x: u128,
+        y: u128,
+        input: u128,
+        output: u128,
+        weights: (f64, f64),
+    ) -> bool {
+        let input_growth = (input as f64 / x as f64).ln_1p();
+        // ln(y / (y - output)), accurate for both small and large outputs
+        let output_loss = if 2 * output < y {
+            -(-(output as f64) / y as f64).ln_1p()
+        } else {
+            (y as f64 / (y - output) as f64).ln()
+        };
+        weights.1 * output_loss <= weights.0 * input_growth
+    }
+
+    /// A weighted curve with a currency weight between 1% and 99%, and a random trade direction,
+    /// with the (input, output) weights of the trade
+    fn weighted_trade(rng: &mut Rng) -> (WeightedProduct, Side, (f64, f64)) {
+        let currency_weight = rng.range(10_000, 990_000) as u32;
+        let weights = (currency_weight as f64 / 1e6, 1.0 - currency_weight as f64 / 1e6);
+        let curve = WeightedProduct::new(Permill::from_parts(currency_weight));
+        match rng.next() % 2 {
+            0 => (curve, Side::Currency, weights),
+            _ => (curve, Side::Asset, (weights.1, weights.0)),
+        }
+    }
+
+    #[test]
+    fn weighted_output_keeps_invariant() {
+        let mut rng = Rng(5);
+        for _ in 0..CASES {
+            let (x, y) = rng.reserves();
+            let (curve, input_side, weights) = weighted_trade(&mut rng);
+            let input = rng.range(1, x);
+            let output = curve.output_amount(input_side, input, x, y, &FEE).unwrap();
+            assert!(output < y);
+            let input_with_fee = input * 997 / 1000;
+            assert!(keeps_weighted_invariant(x, y, input_with_fee, output, weights));
+        }
+    }
+
+    #[test]
+    fn weighted_input_keeps_invariant() {
+        let mut rng = Rng(6);
+        for _ in 0..CASES {
+            let (x, y) = rng.reserves();
+            let (curve, input_side, weights) = weighted_trade(&mut rng);
+            let output = rng.range(1, y - 1);
+            // Buying almost all of a reserve with a high weight ratio overflows
+            let input = match curve.input_amount(input_side, output, x, y, &FEE) {
+                Ok(input) => input,
+                Err(error) => {
+                    assert_eq!(error, CurveError::Overflow);
+                    continue;
+                }
+            };
+            let input_with_fee = (U256::from(input) * 997 / 1000).low_u128();
+            assert!(keeps_weighted_invariant(x, y, input_with_fee, output, weights));
+        }
+    }
+
+    #[test]
+    fn weighted_equal_weights_match_constant_product() {
+        let reserve = 1_000_000_000_000;
+        let amount = reserve / 10;
+        let curve = WeightedProduct::new(Permill::from_percent(50));
+        assert_eq!(
+            curve.output_amount(Side::Asset, amount, reserve, reserve, &FEE),
+            Ok(90_661_089_388)
+        );
+        // The constant product input is 111_445_447_454
+        assert_eq!(
+            curve.input_amount(Side::Asset, amount, reserve, reserve, &FEE),
+            Ok(111_445_447_455)
+        );
+    }
+
+    #[test]
+    fn weighted_trades_depend_on_direction() {
+        let reserve = 1_000_000_000_000;
+        let amount = reserve / 10;
+        let curve = WeightedProduct::new(Permill::from_percent(80));
+        assert_eq!(
+            curve.output_amount(Side::Currency, amount, reserve, reserve, &FEE),
+            Ok(316_240_930_743)
+        );
+        assert_eq!(
+            curve.output_amount(Side::Asset, amount, reserve, reserve, &FEE),
+            Ok(23_479_322_557)
+        );
+        assert_eq!(
+            curve.input_amount(Side::Currency, amount, reserve, reserve, &FEE),
+            Ok(26_770_407_303)
+        );
+    }
+
+    #[test]
+    fn weighted_spot_price_includes_weights() {
+        let reserve = 1_000_000_000_000u128;
+        let curve = WeightedProduct::new(Permill::from_percent(80));
+        assert_eq!(
+            curve.spot_price(reserve, reserve),
+            Ok(FixedU128::saturating_from_rational(1, 4))
+        );
+        assert_eq!(curve.spot_price(reserve * 4, reserve), Ok(FixedU128::one()));
+    }
+
+    #[test]
+    fn weighted_not_enough_liquidity() {
+        let curve = WeightedProduct::new(Permill::from_percent(80));
+        assert_eq!(
+            PricingCurve::<u128>::input_amount(&curve, Side::Asset, 1_000, 1_000, 1_000, &FEE),
+            Err(CurveError::NotEnoughLiquidity)
+        );
+    }
+
+    #[test]
+    fn weight_schedule_is_linear() {
+        let schedule = WeightSchedule {
+            start_weight: Permill::from_percent(90),
+            end_weight: Permill::from_percent(30),
+            start: 10u64,
+            end: 40,
+        };
+        assert_eq!(schedule.at(0), Permill::from_percent(90));
+        assert_eq!(schedule.at(20), Permill::from_percent(70));
+        assert_eq!(schedule.at(40), Permill::from_percent(30));
+        assert_eq!(schedule.at(50), Permill::from_percent(30));
+    }
+
+    #[test]
+    fn amplification_ramp_is_linear() {
+        let ramp = AmplificationRamp {
+            initial: 100,
+            target: 500,
+            start: 10u64,
+            end: 20,
+        };
+        assert_eq!(ramp.at(5), 100);
+        assert_eq!(ramp.at(10), 100);
+        assert_eq!(ramp.at(13), 220);
+        assert_eq!(ramp.at(20), 500);
+        assert_eq!(ramp.at(30), 500);
+        let ramp = AmplificationRamp {
+            initial: 500,
+            target: 100,
+            ..ramp
+        };
+        assert_eq!(ramp.at(13), 380);
+    }
+
+    #[test]
+    fn stable_swap_initial_liquidity() {
+        let curve = StableSwap::new(100);
+        assert_eq!(curve.initial_liquidity(1_000_000u128, 1_000_000), Ok(2_000_000));
+    }
+
+    #[test]
+    fn liquidity_shares_are_proportional() {
+        let curve = Curve::StableSwap { amplification: 100 };
+        // The first deposit mints the initial liquidity
+        assert_eq!(curve.liquidity_minted(1_000u128, 1_000, 0, 0, 0), Ok(2_000));
+        // A deposit of 10% of the reserves mints 10% of the liquidity, bounded by the smaller side
+        assert_eq!(curve.liquidity_minted(100u128, 51, 1_000, 500, 3_000), Ok(300));
+        assert_eq!(curve.liquidity_withdrawal(300u128, 1_000, 500, 3_000), Ok((100, 50)));
+
+        let curve = Curve::Concentrated { tick_spacing: 10 };
+        assert_eq!(
+            curve.liquidity_minted(100u128, 50, 1_000, 500, 3_000),
+            Err(CurveError::Stateful)
+        );
+        assert_eq!(
+            curve.liquidity_withdrawal(300u128, 1_000, 500, 3_000),
+            Err(CurveError::Stateful)
+        );
+    }
+
+    #[test]
+    fn stable_swap_spot_price_is_marginal_price() {
+        let curve = StableSwap::new(100);
+        let reserve = 1_000_000_000_000u128;
+        assert_eq!(curve.spot_price(reserve, reserve), Ok(FixedU128::one()));
+
+        // The asset is scarcer than the currency, but much less expensive than
+        // the constant product price of 2
+        let spot_price = curve.spot_price(reserve * 2, reserve).unwrap();
+        assert!(spot_price > FixedU128::one());
+        assert!(spot_price < FixedU128::saturating_from_rational(101, 100));
+
+        // A small trade is priced at the spot price
+        let no_fee = Fee {
+            net_numerator: 1,
+            denominator: 1,
+        };
+        let input = 1_000_000;
+        let output = curve
+            .output_amount(Side::Currency, input, reserve * 2, reserve, &no_fee)
+            .unwrap();
+        let trade_price = FixedU128::saturating_from_rational(input, output);
+        let difference = trade_price.saturating_sub(spot_price);
+        assert!(difference < FixedU128::saturating_from_rational(1, 100_000));
+    }
+
+    #[test]
+    fn spot_price_of_empty_reserves_fails() {
+        assert_eq!(ConstantProduct.spot_price(1_000u128, 0), Err(CurveError::Overflow));
+        assert_eq!(StableSwap::new(100).spot_price(0u128, 0), Err(CurveError::Overflow));
+    }
+}
@@ -0,0 +1,281 @@
+//! Dollar-cost averaging schedules, selling an escrowed amount in recurring swaps executed
+//! in `on_initialize`, with a slippage bound from the spot price.
+
+use crate::{
+    handlers::OnSwap, permissions::ExchangeOperation, Config, ConfigHelper, DcaQueue,
+    DcaScheduleId, DcaScheduleOf, DcaSchedules, DcaSwap, Error, Event, Pallet, TradeAmount,
+    WeightInfo,
+};
+use frame_support::{
+    dispatch::DispatchResult,
+    ensure,
+    sp_runtime::{
+        traits::{Saturating, Zero},
+        FixedPointNumber, PerThing,
+    },
+    traits::{fungibles::Mutate, tokens::Preservation, Get},
+    weights::Weight,
+};
+use frame_system::pallet_prelude::BlockNumberFor;
+
+/// Maximum number of blocks a DCA execution can be postponed by, if the block
+/// it should be executed in is full.
+const MAX_DCA_EXECUTION_DELAY: u32 = 10;
+
+impl<T: Config<I>, I: 'static> Pallet<T, I> {
+    /// Execute all DCA schedules queued for the block `now`.
+    /// At most `MaxDcaExecutionsPerBlock` schedules can be queued for a single block.
+    pub(crate) fn execute_dca_schedules(now: BlockNumberFor<T>) -> Weight {
+        let schedule_ids = <DcaQueue<T, I>>::take(now);
+        let weight = T::DbWeight::get().reads_writes(1, 1).saturating_add(
+            T::WeightInfo::execute_dca_schedule()
+                .saturating_add(
+                    // An asset-to-asset swap can cross ticks on both exchanges
+                    T::WeightInfo::swap_crossing_ticks(T::MaxTicksCrossed::get()).saturating_mul(2),
+                )
+                .saturating_add(T::OnSwap::weight().saturating_mul(2))
+                .saturating_mul(schedule_ids.len() as u64),
+        );
+        for schedule_id in schedule_ids {
+            if let Some(schedule) = <DcaSchedules<T, I>>::get(schedule_id) {
+                Self::execute_dca_schedule(now, schedule_id, schedule);
+            }
+        }
+        weight
+    }
+
+    /// Queue a DCA schedule for execution in the first block (starting from `block`)
+    /// which isn't full. Return the block number.
+    pub(crate) fn queue_dca_execution(
+        schedule_id: DcaScheduleId,
+        mut block: BlockNumberFor<T>,
+    ) -> Result<BlockNumberFor<T>, Error<T, I>> {
+        for _ in 0..=MAX_DCA_EXECUTION_DELAY {
+            if <DcaQueue<T, I>>::try_append(block, schedule_id).is_ok() {
+                return Ok(block);
+            }
+            block.saturating_inc();
+        }
+        Err(Error::<T, I>::DcaQueueFull)
+    }
+
+    /// Refund the amount escrowed for the remaining executions of a DCA schedule
+    /// and remove it from storage
+    pub(crate) fn close_dca_schedule(
+        schedule_id: DcaScheduleId,
+        schedule: DcaScheduleOf<T, I>,
+    ) -> DispatchResult {
+        let pallet_account = T::pallet_account();
+        match schedule.swap {
+            DcaSwap::CurrencyToAsset {
+                currency_amount, ..
+            } => {
+                let remaining_amount =
+                    currency_amount.saturating_mul(schedule.remaining_executions.into());
+                Self::pay_currency(&schedule.owner, remaining_amount)?;
+            }
+            DcaSwap::AssetToCurrency {
+                asset_id: sold_asset_id,
+                token_amount,
+            }
+            | DcaSwap::AssetToAsset {
+                sold_asset_id,
+                token_amount,
+                ..
+            } => {
+                let remaining_amount =
+                    token_amount.saturating_mul(schedule.remaining_executions.into());
+                T::Assets::transfer(
+                    sold_asset_id,
+                    &pallet_account,
+                    &schedule.owner,
+                    remaining_amount,
+                    Preservation::Expendable,
+                )?;
+            }
+        }
+        <DcaSchedules<T, I>>::remove(schedule_id);
+        Ok(())
+    }
+
+    /// Execute a single swap of a DCA schedule and queue the next one.
+    /// Failed swaps are retried in the next interval, up to `MaxDcaRetries` times in a row.
+    fn execute_dca_schedule(
+        now: BlockNumberFor<T>,
+        schedule_id: DcaScheduleId,
+        mut schedule: DcaScheduleOf<T, I>,
+    ) {
+        match Self::dca_swap(&schedule) {
+            Ok(()) => {
+                schedule.remaining_executions.saturating_dec();
+                schedule.failed_executions = 0;
+                Self::deposit_event(Event::DcaExecuted(
+                    schedule_id,
+                    schedule.owner.clone(),
+                    schedule.remaining_executions,
+                ));
+                if schedule.remaining_executions.is_zero() {
+                    <DcaSchedules<T, I>>::remove(schedule_id);
+                    Self::deposit_event(Event::DcaCompleted(schedule_id, schedule.owner));
+                    return;
+                }
+            }
+            Err(err) => {
+                schedule.failed_executions.saturating_inc();
+                Self::deposit_event(Event::DcaExecutionFailed(
+                    schedule_id,
+                    schedule.owner.clone(),
+                    err,
+                ));
+                if schedule.failed_executions > T::MaxDcaRetries::get() {
+                    return Self::terminate_dca_schedule(schedule_id, schedule);
+                }
+            }
+        }
+        match Self::queue_dca_execution(schedule_id, now.saturating_add(schedule.interval)) {
+            Ok(next_execution) => {
+                schedule.next_execution = next_execution;
+                <DcaSchedules<T, I>>::insert(schedule_id, schedule);
+            }
+            Err(_) => Self::terminate_dca_schedule(schedule_id, schedule),
+        }
+    }
+
+    fn terminate_dca_schedule(schedule_id: DcaScheduleId, schedule: DcaScheduleOf<T, I>) {
+        let owner = schedule.owner.clone();
+        if Self::close_dca_schedule(schedule_id, schedule).is_ok() {
+            Self::deposit_event(Event::DcaTerminated(schedule_id, owner));
+        }
+    }
+
+    /// Swap the amount of a single execution, paid from the escrow, for the schedule's owner
+    fn dca_swap(schedule: &DcaScheduleOf<T, I>) -> DispatchResult {
+        let pallet_account = T::pallet_account();
+        let owner = schedule.owner.clone();
+        let min_output_ratio = schedule.max_slippage.left_from_one();
+        match schedule.swap.clone() {
+            DcaSwap::CurrencyToAsset {
+                asset_id,
+                currency_amount,
+            } => {
+                let exchange = Self::get_exchange(&asset_id)?;
+                let spot_output = Self::spot_price(&exchange)
+                    .reciprocal()
+                    .unwrap_or_default()
+                    .saturating_mul_int(T::currency_to_asset(currency_amount));
+                let amount = TradeAmount::FixedInput {
+                    input_amount: currency_amount,
+                    min_output: min_output_ratio.mul_floor(spot_output),
+                };
+                let (currency_amount, token_amount) =
+                    Self::get_currency_to_asset_price(&exchange, amount)?;
+                ensure!(!token_amount.is_zero(), Error::<T, I>::TradeAmountIsZero);
+                Self::check_operation_allowed(
+                    ExchangeOperation::BuyAsset,
+                    &asset_id,
+                    &owner,
+                    &owner,
+                    currency_amount,
+                    token_amount,
+                )?;
+                Self::swap_currency_for_asset(
+                    exchange,
+                    currency_amount,
+                    token_amount,
+                    true,
+                    pallet_account,
+                    owner,
+                    None,
+                    Preservation::Expendable,
+                )?;
+            }
+            DcaSwap::AssetToCurrency {
+                asset_id,
+                token_amount,
+            } => {
+                let exchange = Self::get_exchange(&asset_id)?;
+                let spot_output = Self::spot_price(&exchange).saturating_mul_int(token_amount);
+                let amount = TradeAmount::FixedInput {
+                    input_amount: token_amount,
+                    min_output: T::asset_to_currency(min_output_ratio.mul_floor(spot_output)),
+                };
+                let (currency_amount, token_amount) =
+                    Self::get_asset_to_currency_price(&exchange, amount)?;
+                ensure!(!currency_amount.is_zero(), Error::<T, I>::TradeAmountIsZero);
+                Self::check_operation_allowed(
+                    ExchangeOperation::SellAsset,
+                    &asset_id,
+                    &owner,
+                    &owner,
+                    currency_amount,
+                    token_amount,
+                )?;
+                Self::swap_asset_for_currency(
+                    exchange,
+                    currency_amount,
+                    token_amount,
+                    true,
+                    pallet_account,
+                    owner,
+                    None,
+                    Preservation::Expendable,
+                )?;
+            }
+            DcaSwap::AssetToAsset {
+                sold_asset_id,
+                bought_asset_id,
+                token_amount,
+            } => {
+                let sold_asset_exchange = Self::get_exchange(&sold_asset_id)?;
+                let bought_asset_exchange = Self::get_exchange(&bought_asset_id)?;
+                let sold_asset_price = Self::spot_price(&sold_asset_exchange);
+                let bought_asset_price = Self::spot_price(&bought_asset_exchange);
+                let spot_output = bought_asset_price
+                    .reciprocal()
+                    .unwrap_or_default()
+                    .saturating_mul(sold_asset_price)
+                    .saturating_mul_int(token_amount);
+                let amount = TradeAmount::FixedInput {
+                    input_amount: token_amount,
+                    min_output: min_output_ratio.mul_floor(spot_output),
+                };
+                let (sold_token_amount, currency_amount, bought_token_amount) =
+                    Self::get_asset_to_asset_price(
+                        &sold_asset_exchange,
+                        &bought_asset_exchange,
+                        amount,
+                    )?;
+                ensure!(!bought_token_amount.is_zero(), Error::<T, I>::TradeAmountIsZero);
+                Self::check_operation_allowed(
+                    ExchangeOperation::SellAsset,
+                    &sold_asset_id,
+                    &owner,
+                    &owner,
+                    currency_amount,
+                    sold_token_amount,
+                )?;
+                Self::check_operation_allowed(
+                    ExchangeOperation::BuyAsset,
+                    &bought_asset_id,
+                    &owner,
+                    &owner,
+                    currency_amount,
+                    bought_token_amount,
+                )?;
+                Self::swap_asset_for_asset(
+                    sold_asset_exchange,
+                    bought_asset_exchange,
+                    currency_amount,
+                    sold_token_amount,
+                    bought_token_amount,
+                    true,
+                    pallet_account,
+                    owner,
+                    None,
+                    Preservation::Expendable,
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
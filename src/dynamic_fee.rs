@@ -0,0 +1,218 @@
+//! Dynamic provider fees, which follow the volatility of the price of an exchange. The volatility
+//! is estimated by an exponential moving average of the relative change of the spot price per
+//! block, sampled by the first trade of each block, before it moves the price.
+
+use crate::{
+    curve::{Fee, PricingCurve},
+    BalanceOf, Config, ConfigHelper, Curve, DynamicFees, ExchangeOf, Pallet,
+};
+use codec::{Decode, Encode, MaxEncodedLen};
+use frame_support::{
+    sp_runtime::{
+        traits::{AtLeast32BitUnsigned, Saturating, UniqueSaturatedInto, Zero},
+        FixedPointNumber, FixedU128, PerThing, Permill, RuntimeDebug,
+    },
+    traits::Get,
+};
+use scale_info::TypeInfo;
+
+/// Weight of the latest price change in the volatility estimate.
+pub const VOLATILITY_SMOOTHING: Permill = Permill::from_percent(20);
+
+/// Parameters of the dynamic fee of an exchange.
+#[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct DynamicFeeParams {
+    /// Fee when the price doesn't move
+    pub min_fee: Permill,
+    /// Highest fee, whatever the volatility
+    pub max_fee: Permill,
+    /// Fee added per unit of volatility, e.g. with a multiplier of 2, an average price change
+    /// of 1% per block adds 2% to the fee
+    pub volatility_multiplier: u32,
+}
+
+impl DynamicFeeParams {
+    /// Whether the fee range is ordered, and doesn't reach 100%
+    pub fn is_valid(&self) -> bool {
+        self.min_fee <= self.max_fee && self.max_fee < Permill::one()
+    }
+}
+
+/// Dynamic fee of an exchange, and the volatility estimate it follows.
+#[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+pub struct DynamicFee<BlockNumber> {
+    pub params: DynamicFeeParams,
+    /// Average relative change of the spot price per block
+    pub volatility: Permill,
+    /// Spot price (currency per token) at the start of block `updated_at`
+    pub price: FixedU128,
+    /// Block in which the volatility was last sampled
+    pub updated_at: BlockNumber,
+}
+
+impl<BlockNumber: AtLeast32BitUnsigned + Copy> DynamicFee<BlockNumber> {
+    /// The state in block `now`, in which the spot price before the first trade is `price`.
+    /// The price change since the last sample is added to the average, which decays by
+    /// `VOLATILITY_SMOOTHING` for each block elapsed.
+    pub fn at(&self, price: FixedU128, now: BlockNumber) -> Self {
+        if now <= self.updated_at {
+            return self.clone();
+        }
+        let elapsed: u32 = now.saturating_sub(self.updated_at).unique_saturated_into();
+        let change = if self.price.is_zero() {
+            Permill::zero()
+        } else {
+            let difference = if price > self.price {
+                price.saturating_sub(self.price)
+            } else {
+                self.price.saturating_sub(price)
+            };
+            Permill::from_rational(difference.into_inner(), self.price.into_inner())
+        };
+        let decay = VOLATILITY_SMOOTHING
+            .left_from_one()
+            .saturating_pow(elapsed as usize);
+        Self {
+            params: self.params,
+            volatility: (decay * self.volatility).saturating_add(VOLATILITY_SMOOTHING * change),
+            price,
+            updated_at: now,
+        }
+    }
+
+    /// The fee at the current volatility, between `min_fee` and `max_fee`
+    pub fn fee(&self) -> Permill {
+        let volatility_fee = self
+            .volatility
+            .deconstruct()
+            .saturating_mul(self.params.volatility_multiplier);
+        Permill::from_parts(
+            self.params
+                .min_fee
+                .deconstruct()
+                .saturating_add(volatility_fee),
+        )
+        .min(self.params.max_fee)
+    }
+}
+
+impl<T: Config<I>, I: 'static> Pallet<T, I> {
+    /// Provider fee of trades against an exchange in the current block
+    pub(crate) fn trade_fee(exchange: &ExchangeOf<T, I>) -> Fee<BalanceOf<T, I>> {
+        match Self::dynamic_fee(exchange) {
+            Some(fee) => Fee {
+                net_numerator: fee.left_from_one().deconstruct().into(),
+                denominator: Permill::ACCURACY.into(),
+            },
+            None => Self::provider_fee(),
+        }
+    }
+
+    /// Provider fee of trades against an exchange in the current block, as recorded in trade events
+    pub(crate) fn trade_fee_rate(exchange: &ExchangeOf<T, I>) -> Permill {
+        Self::dynamic_fee(exchange).unwrap_or_else(|| {
+            Permill::from_rational::<u128>(
+                T::ProviderFeeNumerator::get().unique_saturated_into(),
+                T::ProviderFeeDenominator::get().unique_saturated_into(),
+            )
+        })
+    }
+
+    /// Dynamic fee of an exchange in the current block, if it has one
+    fn dynamic_fee(exchange: &ExchangeOf<T, I>) -> Option<Permill> {
+        let dynamic_fee = Self::dynamic_fees(&exchange.asset_id)?;
+        let now = <frame_system::Pallet<T>>::block_number();
+        Some(dynamic_fee.at(Self::spot_price(exchange), now).fee())
+    }
+
+    /// Sample the price of an exchange with a dynamic fee by the first trade of the block.
+    /// Must be called before the trade moves the price.
+    pub(crate) fn update_volatility(exchange: &ExchangeOf<T, I>) {
+        let now = <frame_system::Pallet<T>>::block_number();
+        <DynamicFees<T, I>>::mutate_extant(&exchange.asset_id, |dynamic_fee| {
+            if dynamic_fee.updated_at < now {
+                *dynamic_fee = dynamic_fee.at(Self::spot_price(exchange), now);
+            }
+        });
+    }
+
+    /// Spot price of an asset (currency per token), not including the provider fee
+    pub(crate) fn spot_price(exchange: &ExchangeOf<T, I>) -> FixedU128 {
+        Self::checked_spot_price(exchange).unwrap_or_default()
+    }
+
+    /// Marginal price of an asset on the curve of its exchange, if the exchange has liquidity
+    pub(crate) fn checked_spot_price(exchange: &ExchangeOf<T, I>) -> Option<FixedU128> {
+        match exchange.curve {
+            Curve::Concentrated { .. } => Self::concentrated_spot_price(&exchange.asset_id),
+            _ => Self::pricing_curve(exchange)
+                .spot_price(T::currency_to_asset(exchange.currency_reserve), exchange.token_reserve)
+                .ok(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PARAMS: DynamicFeeParams = DynamicFeeParams {
+        min_fee: Permill::from_parts(1_000),
+        max_fee: Permill::from_percent(5),
+        volatility_multiplier: 2,
+    };
+
+    fn dynamic_fee() -> DynamicFee<u64> {
+        DynamicFee {
+            params: PARAMS,
+            volatility: Permill::zero(),
+            price: FixedU128::saturating_from_integer(1),
+            updated_at: 1,
+        }
+    }
+
+    #[test]
+    fn stable_price_keeps_min_fee() {
+        let dynamic_fee = dynamic_fee().at(FixedU128::saturating_from_integer(1), 10);
+        assert_eq!(dynamic_fee.volatility, Permill::zero());
+        assert_eq!(dynamic_fee.fee(), PARAMS.min_fee);
+    }
+
+    #[test]
+    fn price_change_raises_fee() {
+        // A 10% price change adds 2% to the volatility, and 4% to the fee
+        let dynamic_fee = dynamic_fee().at(FixedU128::saturating_from_rational(11, 10), 2);
+        assert_eq!(dynamic_fee.volatility, Permill::from_percent(2));
+        assert_eq!(dynamic_fee.fee(), Permill::from_parts(41_000));
+        assert_eq!(dynamic_fee.updated_at, 2);
+
+        // The volatility decays while the price doesn't move
+        let dynamic_fee = dynamic_fee.at(FixedU128::saturating_from_rational(11, 10), 4);
+        assert_eq!(dynamic_fee.volatility, Permill::from_parts(12_800));
+
+        // The fee doesn't exceed `max_fee`
+        let dynamic_fee = dynamic_fee.at(FixedU128::saturating_from_integer(2), 5);
+        assert_eq!(dynamic_fee.fee(), PARAMS.max_fee);
+    }
+
+    #[test]
+    fn same_block_keeps_sample() {
+        let sampled = dynamic_fee().at(FixedU128::saturating_from_integer(2), 1);
+        assert_eq!(sampled, dynamic_fee());
+    }
+
+    #[test]
+    fn fee_range_must_be_ordered() {
+        assert!(PARAMS.is_valid());
+        let params = DynamicFeeParams {
+            min_fee: Permill::from_percent(6),
+            ..PARAMS
+        };
+        assert!(!params.is_valid());
+        let params = DynamicFeeParams {
+            max_fee: Permill::one(),
+            ..PARAMS
+        };
+        assert!(!params.is_valid());
+    }
+}
@@ -0,0 +1,123 @@
+//! Handlers of the DEX activity, notified of every trade and liquidity change so other pallets
+//! (e.g. rewards or analytics) can react to them without parsing events.
+
+use frame_support::{
+    sp_runtime::{Permill, RuntimeDebug},
+    weights::Weight,
+};
+
+/// Trade performed on an exchange, with the same details as its event
+#[derive(Clone, Eq, PartialEq, RuntimeDebug)]
+pub enum SwapDetails<AccountId, AssetId, Balance, AssetBalance> {
+    CurrencyToAsset {
+        asset_id: AssetId,
+        buyer: AccountId,
+        recipient: AccountId,
+        currency_amount: Balance,
+        token_amount: AssetBalance,
+        fee: Permill,
+        /// Referrer of the trade and the part of the fee paid to it, in currency
+        referral: Option<(AccountId, Balance)>,
+    },
+    AssetToCurrency {
+        asset_id: AssetId,
+        buyer: AccountId,
+        recipient: AccountId,
+        currency_amount: Balance,
+        token_amount: AssetBalance,
+        fee: Permill,
+        /// Referrer of the trade and the part of the fee paid to it, in tokens
+        referral: Option<(AccountId, AssetBalance)>,
+    },
+}
+
+/// Liquidity added to or removed from an exchange, with the same details as its event
+#[derive(Clone, Eq, PartialEq, RuntimeDebug)]
+pub enum LiquidityChange<AccountId, AssetId, Balance, AssetBalance> {
+    Added {
+        provider: AccountId,
+        asset_id: AssetId,
+        currency_amount: Balance,
+        token_amount: AssetBalance,
+        liquidity_minted: AssetBalance,
+    },
+    Removed {
+        provider: AccountId,
+        asset_id: AssetId,
+        currency_amount: Balance,
+        token_amount: AssetBalance,
+        liquidity_amount: AssetBalance,
+    },
+    /// Liquidity added to a position of a concentrated-liquidity exchange
+    PositionMinted {
+        provider: AccountId,
+        asset_id: AssetId,
+        lower_tick: i32,
+        upper_tick: i32,
+        liquidity: u128,
+        currency_amount: Balance,
+        token_amount: AssetBalance,
+    },
+    /// Liquidity removed from a position of a concentrated-liquidity exchange, with the amounts
+    /// including the collected fees
+    PositionBurned {
+        provider: AccountId,
+        asset_id: AssetId,
+        lower_tick: i32,
+        upper_tick: i32,
+        liquidity: u128,
+        currency_amount: Balance,
+        token_amount: AssetBalance,
+    },
+}
+
+/// Handler of the trades performed on exchanges. Implemented for tuples of handlers,
+/// `()` ignores all trades.
+pub trait OnSwap<AccountId, AssetId, Balance, AssetBalance> {
+    /// Called after the trade's transfers and the update of the exchange's reserves
+    fn on_swap(swap: &SwapDetails<AccountId, AssetId, Balance, AssetBalance>);
+
+    /// Upper bound of the weight of `on_swap`, charged for every trade a call can perform
+    fn weight() -> Weight;
+}
+
+/// Handler of the liquidity added to or removed from exchanges, including concentrated-liquidity
+/// positions. Implemented for tuples of handlers, `()` ignores all changes.
+pub trait OnLiquidityChanged<AccountId, AssetId, Balance, AssetBalance> {
+    /// Called after the liquidity's transfers and the update of the exchange's reserves
+    fn on_liquidity_changed(change: &LiquidityChange<AccountId, AssetId, Balance, AssetBalance>);
+
+    /// Upper bound of the weight of `on_liquidity_changed`, charged for every liquidity change
+    /// a call can perform
+    fn weight() -> Weight;
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+impl<AccountId, AssetId, Balance, AssetBalance> OnSwap<AccountId, AssetId, Balance, AssetBalance>
+    for Tuple
+{
+    fn on_swap(swap: &SwapDetails<AccountId, AssetId, Balance, AssetBalance>) {
+        for_tuples!( #( Tuple::on_swap(swap); )* );
+    }
+
+    fn weight() -> Weight {
+        let mut weight = Weight::zero();
+        for_tuples!( #( weight.saturating_accrue(Tuple::weight()); )* );
+        weight
+    }
+}
+
+#[impl_trait_for_tuples::impl_for_tuples(30)]
+impl<AccountId, AssetId, Balance, AssetBalance>
+    OnLiquidityChanged<AccountId, AssetId, Balance, AssetBalance> for Tuple
+{
+    fn on_liquidity_changed(change: &LiquidityChange<AccountId, AssetId, Balance, AssetBalance>) {
+        for_tuples!( #( Tuple::on_liquidity_changed(change); )* );
+    }
+
+    fn weight() -> Weight {
+        let mut weight = Weight::zero();
+        for_tuples!( #( weight.saturating_accrue(Tuple::weight()); )* );
+        weight
+    }
+}
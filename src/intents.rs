@@ -0,0 +1,138 @@
+//! Swap intents: trades signed off-chain, which any relayer can execute on behalf of the signer
+//! for a tip out of the trade output. The signature covers the pallet id and the genesis hash,
+//! and each intent carries the signer's next nonce, so it can be executed only once.
+
+use crate::{
+    permissions::ExchangeOperation, AccountIdOf, Config, ConfigHelper, Error, IntentTip, Pallet,
+    SwapAmount, SwapIntentOf, TradeAmount,
+};
+use codec::Encode;
+use frame_support::{
+    ensure,
+    sp_runtime::{
+        traits::{Get, Saturating, Zero},
+        DispatchError,
+    },
+    traits::{fungibles::Mutate, tokens::Preservation},
+    weights::Weight,
+};
+use frame_system::pallet_prelude::BlockNumberFor;
+use sp_std::vec::Vec;
+
+impl<T: Config<I>, I: 'static> Pallet<T, I> {
+    /// Message the signer of a swap intent signs: the SCALE-encoded pallet id of this instance,
+    /// genesis hash of the chain and intent. It binds the signature to a single instance on a
+    /// single chain, so the intent can't be replayed on another one.
+    pub fn signed_intent_payload(intent: &SwapIntentOf<T, I>) -> Vec<u8> {
+        let genesis_hash = <frame_system::Pallet<T>>::block_hash(BlockNumberFor::<T>::zero());
+        (T::PalletId::get(), genesis_hash, intent).encode()
+    }
+
+    /// Perform the trade of a swap intent on behalf of its signer. The trade output is received
+    /// by the pallet account, then the tip is transferred to the relayer and the rest to the
+    /// intent's recipient. Return the weight of the swap on a concentrated-liquidity exchange.
+    pub(crate) fn execute_swap_intent(
+        relayer: &AccountIdOf<T>,
+        intent: &SwapIntentOf<T, I>,
+    ) -> Result<Weight, DispatchError> {
+        let signer = intent.signer.clone();
+        let recipient = intent.recipient.clone().unwrap_or_else(|| signer.clone());
+        let exchange = Self::get_exchange(&intent.asset_id)?;
+        let pallet_account = T::pallet_account();
+        let preservation = Self::preservation(intent.keep_alive);
+        match intent.amount.clone() {
+            SwapAmount::CurrencyToAsset(amount) => {
+                // --------------------------- Compute price ---------------------------
+                Self::check_trade_amount(&amount)?;
+                let exact_input = matches!(amount, TradeAmount::FixedInput { .. });
+                let tip = match intent.tip {
+                    IntentTip::Asset(tip) => tip,
+                    IntentTip::Currency(_) => Err(Error::<T, I>::TipTokenMismatch)?,
+                };
+                let (currency_amount, token_amount) =
+                    Self::get_currency_to_asset_price(&exchange, amount)?;
+                ensure!(tip <= token_amount, Error::<T, I>::TipTooHigh);
+                Self::check_enough_currency(&signer, &currency_amount, preservation)?;
+                Self::check_operation_allowed(
+                    ExchangeOperation::BuyAsset,
+                    &intent.asset_id,
+                    &signer,
+                    &recipient,
+                    currency_amount,
+                    token_amount,
+                )?;
+
+                // --------------------------- Perform trade ---------------------------
+                let swap_weight = Self::swap_currency_for_asset(
+                    exchange,
+                    currency_amount,
+                    token_amount,
+                    exact_input,
+                    signer,
+                    pallet_account.clone(),
+                    None,
+                    preservation,
+                )?;
+
+                // ------------------------- Output transfers --------------------------
+                if !tip.is_zero() {
+                    T::Assets::transfer(
+                        intent.asset_id.clone(),
+                        &pallet_account,
+                        relayer,
+                        tip,
+                        Preservation::Expendable,
+                    )?;
+                }
+                T::Assets::transfer(
+                    intent.asset_id.clone(),
+                    &pallet_account,
+                    &recipient,
+                    token_amount.saturating_sub(tip),
+                    Preservation::Expendable,
+                )?;
+                Ok(swap_weight)
+            }
+            SwapAmount::AssetToCurrency(amount) => {
+                // --------------------------- Compute price ---------------------------
+                Self::check_trade_amount(&amount)?;
+                let exact_input = matches!(amount, TradeAmount::FixedInput { .. });
+                let tip = match intent.tip {
+                    IntentTip::Currency(tip) => tip,
+                    IntentTip::Asset(_) => Err(Error::<T, I>::TipTokenMismatch)?,
+                };
+                let (currency_amount, token_amount) =
+                    Self::get_asset_to_currency_price(&exchange, amount)?;
+                ensure!(tip <= currency_amount, Error::<T, I>::TipTooHigh);
+                Self::check_enough_tokens(&intent.asset_id, &signer, &token_amount)?;
+                Self::check_operation_allowed(
+                    ExchangeOperation::SellAsset,
+                    &intent.asset_id,
+                    &signer,
+                    &recipient,
+                    currency_amount,
+                    token_amount,
+                )?;
+
+                // --------------------------- Perform trade ---------------------------
+                let swap_weight = Self::swap_asset_for_currency(
+                    exchange,
+                    currency_amount,
+                    token_amount,
+                    exact_input,
+                    signer,
+                    pallet_account.clone(),
+                    None,
+                    preservation,
+                )?;
+
+                // ------------------------- Output transfers --------------------------
+                if !tip.is_zero() {
+                    Self::pay_currency(relayer, tip)?;
+                }
+                Self::pay_currency(&recipient, currency_amount.saturating_sub(tip))?;
+                Ok(swap_weight)
+            }
+        }
+    }
+}
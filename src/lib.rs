@@ -12,63 +12,101 @@
 
 #![cfg_attr(not(feature = "std"), no_std)]
 
+mod batch_auction;
 #[cfg(feature = "runtime-benchmarks")]
 mod benchmarking;
+mod check_deadline;
+mod commit_reveal;
+pub mod concentrated;
+pub mod curve;
+mod dca;
+pub mod dynamic_fee;
+pub mod handlers;
+mod intents;
+mod limit_orders;
+pub mod migrations;
 #[cfg(test)]
 mod mock;
+pub mod permissions;
+mod referral;
 pub mod rpc;
 #[cfg(test)]
 mod tests;
 pub mod weights;
+mod zap;
 
-use frame_support::traits::Currency;
+use frame_support::traits::fungible;
 use sp_std::prelude::*;
 
+pub use check_deadline::CheckDeadline;
+pub use curve::{Curve, WeightSchedule};
+pub use dynamic_fee::DynamicFeeParams;
+pub use handlers::{LiquidityChange, OnLiquidityChanged, OnSwap, SwapDetails};
 pub use pallet::*;
+pub use permissions::{ExchangeOperation, TradeFilter};
 pub use weights::WeightInfo;
 
 type AccountIdOf<T> = <T as frame_system::Config>::AccountId;
-type BalanceOf<T> = <<T as Config>::Currency as Currency<AccountIdOf<T>>>::Balance;
-type AssetIdOf<T> = <T as Config>::AssetId;
-type AssetBalanceOf<T> = <T as Config>::AssetBalance;
+type BalanceOf<T, I = ()> = <T as Config<I>>::CurrencyBalance;
+type AssetIdOf<T, I = ()> = <T as Config<I>>::AssetId;
+type AssetBalanceOf<T, I = ()> = <T as Config<I>>::AssetBalance;
+type CreditOf<T, I = ()> = fungible::Credit<AccountIdOf<T>, <T as Config<I>>::QuoteAsset>;
 
 #[frame_support::pallet]
 pub mod pallet {
     use super::*;
+    use crate::concentrated::{self, ConcentratedPool, Position, TickInfo, MAX_TICK_SPACING};
+    use crate::curve::{AmplificationRamp, CurveError, Fee, PricingCurve, Side, MIN_WEIGHT};
+    use crate::dynamic_fee::DynamicFee;
     use codec::EncodeLike;
     use frame_support::{
         pallet_prelude::*,
         sp_runtime::{
             traits::{
-                AccountIdConversion, CheckedAdd, CheckedMul, CheckedSub, Convert, One, Saturating,
-                Zero,
+                AccountIdConversion, CheckedMul, CheckedSub, Convert, Hash, IdentifyAccount, One,
+                Saturating, Verify, Zero,
             },
-            FixedPointNumber, FixedPointOperand, FixedU128,
+            FixedPointNumber, FixedPointOperand, FixedU128, PerThing, Permill,
         },
         traits::{
+            fungible::{Inspect as _, Mutate as _},
             fungibles::{Create, Destroy, Inspect, Mutate},
-            tokens::{Balance, Fortitude, Precision, Preservation, WithdrawConsequence},
-            ExistenceRequirement,
+            tokens::{
+                Balance, DepositConsequence, Fortitude, Precision, Preservation, Provenance,
+                WithdrawConsequence,
+            },
+            EnsureOriginWithArg, OnUnbalanced, UnixTime,
         },
         transactional, PalletId,
     };
     use frame_system::pallet_prelude::*;
-    use sp_std::fmt::Debug;
+    use sp_std::{fmt::Debug, marker::PhantomData, time::Duration};
+
+    /// The current storage version.
+    const STORAGE_VERSION: StorageVersion = StorageVersion::new(3);
 
     #[pallet::pallet]
-    pub struct Pallet<T>(_);
+    #[pallet::storage_version(STORAGE_VERSION)]
+    pub struct Pallet<T, I = ()>(PhantomData<(T, I)>);
 
     #[pallet::config]
-    pub trait Config: frame_system::Config {
+    pub trait Config<I: 'static = ()>: frame_system::Config {
         /// Pallet ID.
         #[pallet::constant]
         type PalletId: Get<PalletId>;
 
         /// The overarching event type.
-        type RuntimeEvent: From<Event<Self>> + IsType<<Self as frame_system::Config>::RuntimeEvent>;
+        type RuntimeEvent: From<Event<Self, I>>
+            + IsType<<Self as frame_system::Config>::RuntimeEvent>;
 
-        /// The currency trait.
-        type Currency: Currency<Self::AccountId>;
+        /// The balance type of the currency.
+        type CurrencyBalance: Balance + MaxEncodedLen + MaybeSerializeDeserialize + TypeInfo;
+
+        /// The currency, i.e. the quote asset paired with the asset of every exchange: the native
+        /// token (e.g. `Balances`) or any asset (e.g. `ItemOf<Assets, StablecoinId, AccountId>`).
+        type QuoteAsset: fungible::Inspect<Self::AccountId, Balance = Self::CurrencyBalance>
+            + fungible::Mutate<Self::AccountId>
+            + fungible::Balanced<Self::AccountId>;
 
         /// The balance type for assets (i.e. tokens).
         type AssetBalance: Balance
@@ -78,8 +116,8 @@ pub mod pallet {
             + TypeInfo;
 
         // Two-way conversion between asset and currency balances
-        type AssetToCurrencyBalance: Convert<Self::AssetBalance, BalanceOf<Self>>;
-        type CurrencyToAssetBalance: Convert<BalanceOf<Self>, Self::AssetBalance>;
+        type AssetToCurrencyBalance: Convert<Self::AssetBalance, BalanceOf<Self, I>>;
+        type CurrencyToAssetBalance: Convert<BalanceOf<Self, I>, Self::AssetBalance>;
 
         /// The asset ID type.
         type AssetId: MaybeSerializeDeserialize
@@ -106,77 +144,204 @@ pub mod pallet {
 
         /// Provider fee numerator.
         #[pallet::constant]
-        type ProviderFeeNumerator: Get<BalanceOf<Self>>;
+        type ProviderFeeNumerator: Get<BalanceOf<Self, I>>;
 
         /// Provider fee denominator.
         #[pallet::constant]
-        type ProviderFeeDenominator: Get<BalanceOf<Self>>;
+        type ProviderFeeDenominator: Get<BalanceOf<Self, I>>;
+
+        /// Part of the provider fee of a trade paid to its referrer.
+        #[pallet::constant]
+        type ReferralFee: Get<Permill>;
 
         /// Minimum currency deposit for a new exchange.
         #[pallet::constant]
-        type MinDeposit: Get<BalanceOf<Self>>;
+        type MinDeposit: Get<BalanceOf<Self, I>>;
+
+        /// Maximum number of DCA schedules executed in a single block.
+        #[pallet::constant]
+        type MaxDcaExecutionsPerBlock: Get<u32>;
+
+        /// Maximum number of consecutive retries of a failed DCA execution. A schedule which fails
+        /// more times in a row is terminated.
+        #[pallet::constant]
+        type MaxDcaRetries: Get<u32>;
+
+        /// Origin allowed to enable or disable batch auction mode for an exchange.
+        type AdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+        /// Origin allowed to make an exchange permissioned, and to approve its liquidity providers.
+        type ExchangeAdmin: EnsureOriginWithArg<Self::RuntimeOrigin, AssetIdOf<Self, I>>;
+
+        /// Filter of the trades and liquidity operations with exchanges, e.g. `()` to allow all.
+        type TradeFilter: TradeFilter<
+            Self::AccountId,
+            Self::AssetId,
+            BalanceOf<Self, I>,
+            Self::AssetBalance,
+        >;
+
+        /// Handler of the trades performed on exchanges, e.g. `()` to ignore them.
+        type OnSwap: OnSwap<Self::AccountId, Self::AssetId, BalanceOf<Self, I>, Self::AssetBalance>;
+
+        /// Handler of the liquidity added to or removed from exchanges, e.g. `()` to ignore it.
+        type OnLiquidityChanged: OnLiquidityChanged<
+            Self::AccountId,
+            Self::AssetId,
+            BalanceOf<Self, I>,
+            Self::AssetBalance,
+        >;
+
+        /// Maximum number of trades queued on an exchange in batch auction mode in a single block.
+        /// Bounds the weight of settling the batch, which grows quadratically with its size.
+        #[pallet::constant]
+        type MaxBatchTrades: Get<u32>;
+
+        /// Maximum number of exchanges in batch auction mode with trades queued in a single block.
+        /// Together with `MaxBatchTrades`, bounds the weight reserved for settling the batches.
+        #[pallet::constant]
+        type MaxBatchAuctions: Get<u32>;
+
+        /// Deposit escrowed when committing to a trade, forfeited if the trade isn't revealed in time.
+        #[pallet::constant]
+        type CommitDeposit: Get<BalanceOf<Self, I>>;
+
+        /// Number of blocks after a trade commitment in which the trade can be revealed.
+        #[pallet::constant]
+        type RevealWindow: Get<BlockNumberFor<Self>>;
+
+        /// Maximum number of trade commitments made in a single block.
+        #[pallet::constant]
+        type MaxCommitmentsPerBlock: Get<u32>;
+
+        /// Handler for the deposits of trade commitments which weren't revealed in time.
+        type ForfeitedDeposits: OnUnbalanced<CreditOf<Self, I>>;
+
+        /// Source of the current time for timestamp deadlines, e.g. `WithUnixTime<Timestamp>`,
+        /// or `()` to only accept block number deadlines.
+        type Time: MaybeUnixTime;
+
+        /// Signature of off-chain swap intents.
+        type Signature: Verify<Signer = Self::Signer> + Parameter;
+
+        /// Identity of the signer of off-chain swap intents.
+        type Signer: IdentifyAccount<AccountId = Self::AccountId>;
+
+        /// Maximum amplification coefficient of StableSwap exchanges.
+        #[pallet::constant]
+        type MaxAmplification: Get<u32>;
+
+        /// Maximum number of initialized ticks (i.e. ticks at which positions start or end)
+        /// of a concentrated-liquidity exchange.
+        #[pallet::constant]
+        type MaxTicks: Get<u32>;
+
+        /// Maximum number of ticks a single trade can cross on a concentrated-liquidity exchange.
+        #[pallet::constant]
+        type MaxTicksCrossed: Get<u32>;
+
+        /// Helper for signing swap intents in benchmarks.
+        #[cfg(feature = "runtime-benchmarks")]
+        type BenchmarkHelper: BenchmarkHelper<Self::AccountId, Self::Signature>;
+    }
+
+    /// Optional source of the current time.
+    pub trait MaybeUnixTime {
+        /// Time elapsed since the Unix epoch, if known
+        fn now() -> Option<Duration>;
+    }
+
+    impl MaybeUnixTime for () {
+        fn now() -> Option<Duration> {
+            None
+        }
+    }
+
+    /// Time provided by a `UnixTime` implementation (e.g. `pallet_timestamp`).
+    pub struct WithUnixTime<Time>(PhantomData<Time>);
+
+    impl<Time: UnixTime> MaybeUnixTime for WithUnixTime<Time> {
+        fn now() -> Option<Duration> {
+            Some(Time::now())
+        }
+    }
+
+    /// Signs swap intents in benchmarks.
+    #[cfg(feature = "runtime-benchmarks")]
+    pub trait BenchmarkHelper<AccountId, Signature> {
+        /// Account of the signer of swap intents
+        fn signer() -> AccountId;
+        /// Sign `message` with the key of the signer
+        fn sign(message: &[u8]) -> Signature;
     }
 
-    pub trait ConfigHelper: Config {
+    pub trait ConfigHelper<I: 'static = ()>: Config<I> {
         fn pallet_account() -> AccountIdOf<Self>;
-        fn currency_to_asset(curr_balance: BalanceOf<Self>) -> AssetBalanceOf<Self>;
-        fn asset_to_currency(asset_balance: AssetBalanceOf<Self>) -> BalanceOf<Self>;
-        fn net_amount_numerator() -> BalanceOf<Self>;
+        fn currency_to_asset(curr_balance: BalanceOf<Self, I>) -> AssetBalanceOf<Self, I>;
+        fn asset_to_currency(asset_balance: AssetBalanceOf<Self, I>) -> BalanceOf<Self, I>;
+        fn net_amount_numerator() -> BalanceOf<Self, I>;
     }
 
-    impl<T: Config> ConfigHelper for T {
+    impl<T: Config<I>, I: 'static> ConfigHelper<I> for T {
         #[inline(always)]
         fn pallet_account() -> AccountIdOf<Self> {
             Self::PalletId::get().into_account_truncating()
         }
 
         #[inline(always)]
-        fn currency_to_asset(curr_balance: BalanceOf<Self>) -> AssetBalanceOf<Self> {
+        fn currency_to_asset(curr_balance: BalanceOf<Self, I>) -> AssetBalanceOf<Self, I> {
             Self::CurrencyToAssetBalance::convert(curr_balance)
         }
 
         #[inline(always)]
-        fn asset_to_currency(asset_balance: AssetBalanceOf<Self>) -> BalanceOf<Self> {
+        fn asset_to_currency(asset_balance: AssetBalanceOf<Self, I>) -> BalanceOf<Self, I> {
             Self::AssetToCurrencyBalance::convert(asset_balance)
         }
 
         #[inline(always)]
-        fn net_amount_numerator() -> BalanceOf<Self> {
+        fn net_amount_numerator() -> BalanceOf<Self, I> {
             Self::ProviderFeeDenominator::get()
                 .checked_sub(&Self::ProviderFeeNumerator::get())
                 .expect("Provider fee shouldn't be greater than 100%")
         }
     }
 
-    type GenesisExchangeInfo<T> =
-        (AccountIdOf<T>, AssetIdOf<T>, AssetIdOf<T>, BalanceOf<T>, AssetBalanceOf<T>);
+    type GenesisExchangeInfo<T, I = ()> = (
+        AccountIdOf<T>,
+        AssetIdOf<T, I>,
+        AssetIdOf<T, I>,
+        BalanceOf<T, I>,
+        AssetBalanceOf<T, I>,
+    );
 
     #[pallet::genesis_config]
-    pub struct GenesisConfig<T: Config> {
-        pub exchanges: Vec<GenesisExchangeInfo<T>>,
+    pub struct GenesisConfig<T: Config<I>, I: 'static = ()> {
+        pub exchanges: Vec<GenesisExchangeInfo<T, I>>,
     }
 
-    impl<T: Config> Default for GenesisConfig<T> {
-        fn default() -> GenesisConfig<T> {
+    impl<T: Config<I>, I: 'static> Default for GenesisConfig<T, I> {
+        fn default() -> GenesisConfig<T, I> {
             GenesisConfig { exchanges: vec![] }
         }
     }
 
     #[pallet::genesis_build]
-    impl<T: Config> BuildGenesisConfig for GenesisConfig<T> {
+    impl<T: Config<I>, I: 'static> BuildGenesisConfig for GenesisConfig<T, I> {
         fn build(&self) {
             let pallet_account = T::pallet_account();
+            // Provider reference, so the pallet account is never reaped
+            frame_system::Pallet::<T>::inc_providers(&pallet_account);
             for (provider, asset_id, liquidity_token_id, currency_amount, token_amount) in
                 &self.exchanges
             {
                 // ----------------------- Create liquidity token ----------------------
-                assert!(!<Exchanges<T>>::contains_key(asset_id), "Exchange already created");
+                assert!(!<Exchanges<T, I>>::contains_key(asset_id), "Exchange already created");
                 assert!(
                     T::AssetRegistry::create(
                         liquidity_token_id.clone(),
                         pallet_account.clone(),
                         false,
-                        <AssetBalanceOf<T>>::one(),
+                        <AssetBalanceOf<T, I>>::one(),
                     )
                     .is_ok(),
                     "Liquidity token id already in use"
@@ -185,20 +350,21 @@ pub mod pallet {
                 // -------------------------- Update storage ---------------------------
                 let mut exchange = Exchange {
                     asset_id: asset_id.clone(),
-                    currency_reserve: <BalanceOf<T>>::zero(),
-                    token_reserve: <AssetBalanceOf<T>>::zero(),
-                    liquidity_token_id: liquidity_token_id.clone(),
+                    currency_reserve: <BalanceOf<T, I>>::zero(),
+                    token_reserve: <AssetBalanceOf<T, I>>::zero(),
+                    liquidity_token_id: Some(liquidity_token_id.clone()),
+                    curve: Curve::ConstantProduct,
                 };
 
                 let liquidity_minted = T::currency_to_asset(*currency_amount);
 
                 // --------------------- Currency & token transfer ---------------------
                 assert!(
-                    <T as pallet::Config>::Currency::transfer(
+                    T::QuoteAsset::transfer(
                         provider,
                         &pallet_account,
                         *currency_amount,
-                        ExistenceRequirement::KeepAlive,
+                        Preservation::Preserve,
                     )
                     .is_ok(),
                     "Provider does not have enough amount of currency"
@@ -231,52 +397,136 @@ pub mod pallet {
                     .currency_reserve
                     .saturating_accrue(*currency_amount);
                 exchange.token_reserve.saturating_accrue(*token_amount);
-                <Exchanges<T>>::insert(asset_id.clone(), exchange);
+                <Exchanges<T, I>>::insert(asset_id.clone(), exchange);
             }
         }
     }
 
     #[pallet::event]
     #[pallet::generate_deposit(pub(super) fn deposit_event)]
-    pub enum Event<T: Config> {
+    pub enum Event<T: Config<I>, I: 'static = ()> {
         /// A new exchange was created [asset_id, liquidity_token_id]
-        ExchangeCreated(AssetIdOf<T>, AssetIdOf<T>),
+        ExchangeCreated(AssetIdOf<T, I>, AssetIdOf<T, I>),
         /// Liquidity was added to an exchange [provider_id, asset_id, currency_amount, token_amount, liquidity_minted]
         LiquidityAdded(
             T::AccountId,
-            AssetIdOf<T>,
-            BalanceOf<T>,
-            AssetBalanceOf<T>,
-            AssetBalanceOf<T>,
+            AssetIdOf<T, I>,
+            BalanceOf<T, I>,
+            AssetBalanceOf<T, I>,
+            AssetBalanceOf<T, I>,
         ),
         /// Liquidity was removed from an exchange [provider_id, asset_id, currency_amount, token_amount, liquidity_amount]
         LiquidityRemoved(
             T::AccountId,
-            AssetIdOf<T>,
-            BalanceOf<T>,
-            AssetBalanceOf<T>,
-            AssetBalanceOf<T>,
+            AssetIdOf<T, I>,
+            BalanceOf<T, I>,
+            AssetBalanceOf<T, I>,
+            AssetBalanceOf<T, I>,
         ),
-        /// Currency was traded for an asset [asset_id, buyer_id, recipient_id, currency_amount, token_amount]
+        /// Currency was traded for an asset, and part of the fee paid to the referrer of the trade in currency
+        /// [asset_id, buyer_id, recipient_id, currency_amount, token_amount, fee, (referrer_id, referral_amount)]
         CurrencyTradedForAsset(
-            AssetIdOf<T>,
+            AssetIdOf<T, I>,
             T::AccountId,
             T::AccountId,
-            BalanceOf<T>,
-            AssetBalanceOf<T>,
+            BalanceOf<T, I>,
+            AssetBalanceOf<T, I>,
+            Permill,
+            Option<(T::AccountId, BalanceOf<T, I>)>,
         ),
-        /// An asset was traded for currency [asset_id, buyer_id, recipient_id, currency_amount, token_amount]
+        /// An asset was traded for currency, and part of the fee paid to the referrer of the trade in tokens
+        /// [asset_id, buyer_id, recipient_id, currency_amount, token_amount, fee, (referrer_id, referral_amount)]
         AssetTradedForCurrency(
-            AssetIdOf<T>,
+            AssetIdOf<T, I>,
+            T::AccountId,
+            T::AccountId,
+            BalanceOf<T, I>,
+            AssetBalanceOf<T, I>,
+            Permill,
+            Option<(T::AccountId, AssetBalanceOf<T, I>)>,
+        ),
+        /// A limit order was placed [order_id, owner_id, asset_id]
+        LimitOrderPlaced(LimitOrderId, T::AccountId, AssetIdOf<T, I>),
+        /// A limit order was (partially) filled [order_id, owner_id, asset_id, currency_amount, token_amount]
+        LimitOrderFilled(
+            LimitOrderId,
             T::AccountId,
+            AssetIdOf<T, I>,
+            BalanceOf<T, I>,
+            AssetBalanceOf<T, I>,
+        ),
+        /// A limit order was cancelled and its unfilled amount refunded [order_id, owner_id]
+        LimitOrderCancelled(LimitOrderId, T::AccountId),
+        /// A limit order expired and its unfilled amount was refunded [order_id, owner_id]
+        LimitOrderExpired(LimitOrderId, T::AccountId),
+        /// A DCA schedule was created [schedule_id, owner_id]
+        DcaScheduled(DcaScheduleId, T::AccountId),
+        /// A DCA schedule was executed [schedule_id, owner_id, remaining_executions]
+        DcaExecuted(DcaScheduleId, T::AccountId, u32),
+        /// A DCA schedule execution failed and will be retried [schedule_id, owner_id, error]
+        DcaExecutionFailed(DcaScheduleId, T::AccountId, DispatchError),
+        /// All executions of a DCA schedule were completed [schedule_id, owner_id]
+        DcaCompleted(DcaScheduleId, T::AccountId),
+        /// A DCA schedule was cancelled and its remaining funds refunded [schedule_id, owner_id]
+        DcaCancelled(DcaScheduleId, T::AccountId),
+        /// A DCA schedule was terminated after failing too many times (or not finding a block
+        /// to be executed in), and its remaining funds refunded [schedule_id, owner_id]
+        DcaTerminated(DcaScheduleId, T::AccountId),
+        /// Batch auction mode was enabled or disabled for an exchange [asset_id, enabled]
+        BatchAuctionModeSet(AssetIdOf<T, I>, bool),
+        /// A trade was queued to be settled at the end of the block [asset_id, trader_id]
+        BatchTradeQueued(AssetIdOf<T, I>, T::AccountId),
+        /// Trades queued on an exchange were settled at a uniform price [asset_id, clearing_price]
+        BatchSettled(AssetIdOf<T, I>, FixedU128),
+        /// A queued trade couldn't be filled at the clearing price and was refunded [asset_id, trader_id]
+        BatchTradeRefunded(AssetIdOf<T, I>, T::AccountId),
+        /// A trade commitment was made [account_id, commitment]
+        TradeCommitted(T::AccountId, T::Hash),
+        /// A committed trade was revealed and executed [account_id, commitment]
+        TradeRevealed(T::AccountId, T::Hash),
+        /// A trade commitment wasn't revealed in time and its deposit was forfeited [account_id, commitment]
+        TradeCommitmentForfeited(T::AccountId, T::Hash),
+        /// A signed swap intent was executed by a relayer [signer_id, nonce, relayer_id, tip]
+        SwapIntentExecuted(T::AccountId, IntentNonce, T::AccountId, IntentTipOf<T, I>),
+        /// The amplification coefficient of a StableSwap exchange started ramping [asset_id, initial, target, end]
+        AmplificationRampStarted(AssetIdOf<T, I>, u32, u32, BlockNumberFor<T>),
+        /// The weights of a weighted exchange were scheduled to shift
+        /// [asset_id, start_weight, end_weight, start, end]
+        WeightScheduleSet(AssetIdOf<T, I>, Permill, Permill, BlockNumberFor<T>, BlockNumberFor<T>),
+        /// A new concentrated-liquidity exchange was created [asset_id, tick_spacing, tick]
+        ConcentratedExchangeCreated(AssetIdOf<T, I>, u32, i32),
+        /// Liquidity was added to a position
+        /// [provider_id, asset_id, lower_tick, upper_tick, liquidity, currency_amount, token_amount]
+        PositionMinted(
+            T::AccountId,
+            AssetIdOf<T, I>,
+            i32,
+            i32,
+            u128,
+            BalanceOf<T, I>,
+            AssetBalanceOf<T, I>,
+        ),
+        /// Liquidity was removed from a position and its fees collected, the amounts including
+        /// the fees [provider_id, asset_id, lower_tick, upper_tick, liquidity, currency_amount, token_amount]
+        PositionBurned(
             T::AccountId,
-            BalanceOf<T>,
-            AssetBalanceOf<T>,
+            AssetIdOf<T, I>,
+            i32,
+            i32,
+            u128,
+            BalanceOf<T, I>,
+            AssetBalanceOf<T, I>,
         ),
+        /// The dynamic fee of an exchange was set, or removed if `None` [asset_id, params]
+        DynamicFeeSet(AssetIdOf<T, I>, Option<DynamicFeeParams>),
+        /// An exchange was made permissioned or open [asset_id, permissioned]
+        PermissionedModeSet(AssetIdOf<T, I>, bool),
+        /// A liquidity provider of an exchange was approved or revoked [asset_id, provider_id, approved]
+        ProviderApprovalSet(AssetIdOf<T, I>, T::AccountId, bool),
     }
 
     #[pallet::error]
-    pub enum Error<T> {
+    pub enum Error<T, I = ()> {
         /// Asset with the specified ID does not exist
         AssetNotFound,
         /// Exchange for the given asset already exists
@@ -333,6 +583,92 @@ pub mod pallet {
         Underflow,
         /// Deadline specified for the operation has passed
         DeadlinePassed,
+        /// Zero value provided for `limit_price` parameter
+        LimitPriceIsZero,
+        /// No limit order found for the given `order_id`
+        LimitOrderNotFound,
+        /// Limit order is owned by another account
+        NotLimitOrderOwner,
+        /// Zero value provided for `interval` parameter
+        DcaIntervalIsZero,
+        /// Zero value provided for `executions` parameter
+        DcaExecutionsIsZero,
+        /// All blocks in which the DCA schedule could be executed are full
+        DcaQueueFull,
+        /// No DCA schedule found for the given `schedule_id`
+        DcaScheduleNotFound,
+        /// DCA schedule is owned by another account
+        NotDcaScheduleOwner,
+        /// Too many trades are already queued on the exchange, or too many exchanges have queued
+        /// trades, in this block
+        BatchFull,
+        /// Exchange is in batch auction mode, which doesn't support asset-to-asset trades
+        BatchAuctionEnabled,
+        /// The caller has already made the same trade commitment
+        CommitmentAlreadyExists,
+        /// No trade commitment of the caller matches the revealed trade
+        CommitmentNotFound,
+        /// Too many trade commitments were already made in this block
+        TooManyCommitments,
+        /// Signature doesn't match the swap intent and its signer
+        InvalidSignature,
+        /// Swap intent nonce isn't the next nonce of the signer
+        InvalidIntentNonce,
+        /// Relayer tip of the swap intent is higher than the trade output
+        TipTooHigh,
+        /// Deadline is a timestamp, but the runtime has no time provider
+        TimestampDeadlineUnsupported,
+        /// Zero value provided for `max_currency` parameter
+        MaxCurrencyIsZero,
+        /// Amplification coefficient is zero or greater than `MaxAmplification`
+        InvalidAmplification,
+        /// The exchange doesn't use the StableSwap curve
+        NotStableSwapExchange,
+        /// The end of an amplification ramp isn't after the current block
+        InvalidRampEnd,
+        /// The operation isn't supported for the pricing curve of the exchange
+        UnsupportedCurve,
+        /// Weight of a reserve of a weighted exchange is lower than the minimum weight
+        InvalidWeight,
+        /// Weight schedule doesn't end after its start and the current block
+        InvalidWeightSchedule,
+        /// Tick spacing is zero or greater than `MAX_TICK_SPACING`
+        InvalidTickSpacing,
+        /// Price is zero or outside of the prices of the ticks
+        InvalidPrice,
+        /// Ticks of a position aren't ordered, within the tick range,
+        /// and multiples of the tick spacing of the exchange
+        InvalidTickRange,
+        /// The exchange already has `MaxTicks` initialized ticks
+        TooManyTicks,
+        /// The trade would cross more than `MaxTicksCrossed` ticks
+        TooManyTicksCrossed,
+        /// The caller has no position in the given price range
+        PositionNotFound,
+        /// Specified liquidity is greater than the liquidity of the position
+        PositionLiquidityTooLow,
+        /// The deposited amounts are too low to provide any liquidity
+        PositionLiquidityIsZero,
+        /// Minimum fee is greater than the maximum fee, or the maximum fee isn't lower than 100%
+        InvalidFeeRange,
+        /// The trade or liquidity operation was rejected by the `TradeFilter`
+        OperationNotAllowed,
+        /// The exchange is permissioned, and the caller isn't an approved liquidity provider
+        ProviderNotApproved,
+        /// The currency paid out would leave the recipient with less than the minimum balance
+        CurrencyBelowMinimum,
+        /// Tip of the swap intent isn't in the token bought by its trade
+        TipTokenMismatch,
+    }
+
+    impl<T, I> From<CurveError> for Error<T, I> {
+        fn from(error: CurveError) -> Self {
+            match error {
+                CurveError::Overflow => Error::Overflow,
+                CurveError::NotEnoughLiquidity => Error::NotEnoughLiquidity,
+                CurveError::Stateful => Error::UnsupportedCurve,
+            }
+        }
     }
 
     #[derive(
@@ -342,7 +678,10 @@ pub mod pallet {
         pub asset_id: AssetId,
         pub currency_reserve: Balance,
         pub token_reserve: AssetBalance,
-        pub liquidity_token_id: AssetId,
+        /// Liquidity token of the exchange, `None` for concentrated-liquidity exchanges,
+        /// whose liquidity is held in positions instead
+        pub liquidity_token_id: Option<AssetId>,
+        pub curve: Curve,
     }
 
     #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
@@ -357,22 +696,370 @@ pub mod pallet {
         },
     }
 
+    /// Amount of the currency or asset sold by a limit order.
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+    pub enum OrderAmount<Balance, AssetBalance> {
+        SellCurrency(Balance),
+        SellAsset(AssetBalance),
+    }
+
+    /// A limit order. The unfilled `amount` is escrowed in the pallet account.
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+    pub struct LimitOrder<AccountId, AssetId, Balance, AssetBalance, BlockNumber> {
+        pub owner: AccountId,
+        pub asset_id: AssetId,
+        pub amount: OrderAmount<Balance, AssetBalance>,
+        /// Minimum amount of the bought token per unit of the sold token
+        pub limit_price: FixedU128,
+        /// Number of the last block in which the order can be filled
+        pub expiry: BlockNumber,
+    }
+
+    pub type LimitOrderId = u64;
+
+    /// Direction of the swaps of a DCA schedule and the amount sold in each execution.
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+    pub enum DcaSwap<AssetId, Balance, AssetBalance> {
+        CurrencyToAsset {
+            asset_id: AssetId,
+            currency_amount: Balance,
+        },
+        AssetToCurrency {
+            asset_id: AssetId,
+            token_amount: AssetBalance,
+        },
+        AssetToAsset {
+            sold_asset_id: AssetId,
+            bought_asset_id: AssetId,
+            token_amount: AssetBalance,
+        },
+    }
+
+    /// A DCA (dollar-cost averaging) schedule. The amount to be sold in all remaining executions
+    /// is escrowed in the pallet account.
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+    pub struct DcaSchedule<AccountId, AssetId, Balance, AssetBalance, BlockNumber> {
+        pub owner: AccountId,
+        pub swap: DcaSwap<AssetId, Balance, AssetBalance>,
+        /// Number of blocks between executions
+        pub interval: BlockNumber,
+        pub remaining_executions: u32,
+        /// Maximum accepted difference (including the provider fee) between the spot price
+        /// and the execution price
+        pub max_slippage: Permill,
+        /// Number of the block in which the schedule is executed next
+        pub next_execution: BlockNumber,
+        /// Number of consecutive failed executions
+        pub failed_executions: u32,
+    }
+
+    pub type DcaScheduleId = u64;
+
+    /// Direction and amount of a currency-to-asset or asset-to-currency trade.
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+    pub enum SwapAmount<Balance, AssetBalance> {
+        CurrencyToAsset(TradeAmount<Balance, AssetBalance>),
+        AssetToCurrency(TradeAmount<AssetBalance, Balance>),
+    }
+
+    /// A trade queued on an exchange in batch auction mode. The (maximum) input of the trade
+    /// is escrowed in the pallet account until the trade is settled at the end of the block.
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+    pub struct BatchTrade<AccountId, Balance, AssetBalance> {
+        pub trader: AccountId,
+        pub recipient: AccountId,
+        pub amount: SwapAmount<Balance, AssetBalance>,
+    }
+
+    /// A commitment to a trade, to be revealed by `reveal_trade`. The deposit is escrowed
+    /// in the pallet account.
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+    pub struct TradeCommitment<Balance, BlockNumber> {
+        pub deposit: Balance,
+        /// Number of the last block in which the trade can be revealed
+        pub expiry: BlockNumber,
+    }
+
+    /// Deadline of a call: either the number of the last block in which the call can be executed,
+    /// or the last moment (Unix time in milliseconds), which requires a `Time` provider.
+    #[derive(Clone, Copy, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+    pub enum Deadline<BlockNumber> {
+        #[codec(index = 0)]
+        Block(BlockNumber),
+        #[codec(index = 1)]
+        Moment(u64),
+    }
+
+    impl<BlockNumber> From<BlockNumber> for Deadline<BlockNumber> {
+        fn from(block: BlockNumber) -> Self {
+            Deadline::Block(block)
+        }
+    }
+
+    pub type IntentNonce = u64;
+
+    /// Side received by `zap_out`, with the minimum total amount of it to receive.
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+    pub enum ZapOutput<Balance, AssetBalance> {
+        Currency(Balance),
+        Asset(AssetBalance),
+    }
+
+    /// Tip of a swap intent, in the token bought by its trade: tokens for a currency-to-asset
+    /// trade, currency for an asset-to-currency trade.
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+    pub enum IntentTip<Balance, AssetBalance> {
+        Currency(Balance),
+        Asset(AssetBalance),
+    }
+
+    /// A currency-to-asset or asset-to-currency trade signed off-chain by `signer`, which any
+    /// relayer can execute by `execute_signed_intent`.
+    #[derive(Clone, Encode, Decode, Eq, PartialEq, RuntimeDebug, MaxEncodedLen, TypeInfo)]
+    pub struct SwapIntent<AccountId, AssetId, Balance, AssetBalance, BlockNumber> {
+        pub signer: AccountId,
+        pub asset_id: AssetId,
+        pub amount: SwapAmount<Balance, AssetBalance>,
+        /// Last block or moment in which the intent can be executed
+        pub deadline: Deadline<BlockNumber>,
+        /// Account receiving the trade output less the tip, the signer if `None`
+        pub recipient: Option<AccountId>,
+        /// Must equal the signer's next intent nonce
+        pub nonce: IntentNonce,
+        /// Amount of the bought currency or asset paid to the relayer out of the trade output
+        pub tip: IntentTip<Balance, AssetBalance>,
+        /// Whether the signer's balance of the paid currency or asset must stay above its minimum
+        /// balance
+        pub keep_alive: bool,
+    }
+
     // (sold_token_amount, currency_amount, bought_token_amount)
-    type AssetToAssetPrice<T> = (AssetBalanceOf<T>, BalanceOf<T>, AssetBalanceOf<T>);
+    type AssetToAssetPrice<T, I = ()> =
+        (AssetBalanceOf<T, I>, BalanceOf<T, I>, AssetBalanceOf<T, I>);
 
     // Type alias for convenience
-    type ExchangeOf<T> = Exchange<AssetIdOf<T>, BalanceOf<T>, AssetBalanceOf<T>>;
+    pub(crate) type ExchangeOf<T, I = ()> =
+        Exchange<AssetIdOf<T, I>, BalanceOf<T, I>, AssetBalanceOf<T, I>>;
+    pub(crate) type LimitOrderOf<T, I = ()> = LimitOrder<
+        AccountIdOf<T>,
+        AssetIdOf<T, I>,
+        BalanceOf<T, I>,
+        AssetBalanceOf<T, I>,
+        BlockNumberFor<T>,
+    >;
+    pub(crate) type DcaScheduleOf<T, I = ()> = DcaSchedule<
+        AccountIdOf<T>,
+        AssetIdOf<T, I>,
+        BalanceOf<T, I>,
+        AssetBalanceOf<T, I>,
+        BlockNumberFor<T>,
+    >;
+    pub(crate) type DcaSwapOf<T, I = ()> =
+        DcaSwap<AssetIdOf<T, I>, BalanceOf<T, I>, AssetBalanceOf<T, I>>;
+    pub(crate) type BatchTradeOf<T, I = ()> =
+        BatchTrade<AccountIdOf<T>, BalanceOf<T, I>, AssetBalanceOf<T, I>>;
+    pub(crate) type TradeCommitmentOf<T, I = ()> =
+        TradeCommitment<BalanceOf<T, I>, BlockNumberFor<T>>;
+    pub(crate) type DeadlineOf<T> = Deadline<BlockNumberFor<T>>;
+    pub(crate) type AmplificationRampOf<T> = AmplificationRamp<BlockNumberFor<T>>;
+    pub(crate) type WeightScheduleOf<T> = WeightSchedule<BlockNumberFor<T>>;
+    pub(crate) type DynamicFeeOf<T> = DynamicFee<BlockNumberFor<T>>;
+    /// (asset_id, lower_tick, upper_tick)
+    pub(crate) type PositionKey<T, I = ()> = (AssetIdOf<T, I>, i32, i32);
+    pub(crate) type IntentTipOf<T, I = ()> = IntentTip<BalanceOf<T, I>, AssetBalanceOf<T, I>>;
+    pub(crate) type SwapIntentOf<T, I = ()> = SwapIntent<
+        AccountIdOf<T>,
+        AssetIdOf<T, I>,
+        BalanceOf<T, I>,
+        AssetBalanceOf<T, I>,
+        BlockNumberFor<T>,
+    >;
 
     #[pallet::storage]
     #[pallet::getter(fn exchanges)]
-    pub(super) type Exchanges<T: Config> =
-        StorageMap<_, Twox64Concat, AssetIdOf<T>, ExchangeOf<T>, OptionQuery>;
+    pub(super) type Exchanges<T: Config<I>, I: 'static = ()> =
+        StorageMap<_, Twox64Concat, AssetIdOf<T, I>, ExchangeOf<T, I>, OptionQuery>;
+
+    /// Last amplification ramps of StableSwap exchanges
+    #[pallet::storage]
+    #[pallet::getter(fn amplification_ramps)]
+    pub(super) type AmplificationRamps<T: Config<I>, I: 'static = ()> =
+        StorageMap<_, Twox64Concat, AssetIdOf<T, I>, AmplificationRampOf<T>, OptionQuery>;
+
+    /// Weight schedules of weighted exchanges, set at their creation
+    #[pallet::storage]
+    #[pallet::getter(fn weight_schedules)]
+    pub(super) type WeightSchedules<T: Config<I>, I: 'static = ()> =
+        StorageMap<_, Twox64Concat, AssetIdOf<T, I>, WeightScheduleOf<T>, OptionQuery>;
+
+    /// Dynamic fees of exchanges, and the volatility of their price
+    #[pallet::storage]
+    #[pallet::getter(fn dynamic_fees)]
+    pub(super) type DynamicFees<T: Config<I>, I: 'static = ()> =
+        StorageMap<_, Twox64Concat, AssetIdOf<T, I>, DynamicFeeOf<T>, OptionQuery>;
+
+    /// Price and liquidity of concentrated-liquidity exchanges
+    #[pallet::storage]
+    #[pallet::getter(fn concentrated_pools)]
+    pub(super) type ConcentratedPools<T: Config<I>, I: 'static = ()> =
+        StorageMap<_, Twox64Concat, AssetIdOf<T, I>, ConcentratedPool, OptionQuery>;
+
+    /// Sorted ticks at which positions start or end, by concentrated-liquidity exchange
+    #[pallet::storage]
+    #[pallet::getter(fn initialized_ticks)]
+    pub(super) type InitializedTicks<T: Config<I>, I: 'static = ()> =
+        StorageMap<_, Twox64Concat, AssetIdOf<T, I>, BoundedVec<i32, T::MaxTicks>, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn ticks)]
+    pub(super) type Ticks<T: Config<I>, I: 'static = ()> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        AssetIdOf<T, I>,
+        Twox64Concat,
+        i32,
+        TickInfo,
+        OptionQuery,
+    >;
+
+    /// Concentrated-liquidity positions by owner
+    #[pallet::storage]
+    #[pallet::getter(fn positions)]
+    pub(super) type Positions<T: Config<I>, I: 'static = ()> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        AccountIdOf<T>,
+        Blake2_128Concat,
+        PositionKey<T, I>,
+        Position,
+        OptionQuery,
+    >;
+
+    #[pallet::storage]
+    #[pallet::getter(fn limit_orders)]
+    pub(super) type LimitOrders<T: Config<I>, I: 'static = ()> =
+        StorageMap<_, Twox64Concat, LimitOrderId, LimitOrderOf<T, I>, OptionQuery>;
+
+    #[pallet::storage]
+    pub(super) type NextLimitOrderId<T, I = ()> = StorageValue<_, LimitOrderId, ValueQuery>;
+
+    /// The last limit order matched in `on_idle`, matching continues from the next one
+    #[pallet::storage]
+    pub(super) type LimitOrderCursor<T, I = ()> = StorageValue<_, LimitOrderId, OptionQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn dca_schedules)]
+    pub(super) type DcaSchedules<T: Config<I>, I: 'static = ()> =
+        StorageMap<_, Twox64Concat, DcaScheduleId, DcaScheduleOf<T, I>, OptionQuery>;
+
+    #[pallet::storage]
+    pub(super) type NextDcaScheduleId<T, I = ()> = StorageValue<_, DcaScheduleId, ValueQuery>;
+
+    /// DCA schedules to be executed in a given block
+    #[pallet::storage]
+    #[pallet::getter(fn dca_queue)]
+    pub(super) type DcaQueue<T: Config<I>, I: 'static = ()> = StorageMap<
+        _,
+        Twox64Concat,
+        BlockNumberFor<T>,
+        BoundedVec<DcaScheduleId, T::MaxDcaExecutionsPerBlock>,
+        ValueQuery,
+    >;
+
+    /// Exchanges in batch auction mode
+    #[pallet::storage]
+    #[pallet::getter(fn batch_auctions)]
+    pub(super) type BatchAuctions<T: Config<I>, I: 'static = ()> =
+        StorageMap<_, Twox64Concat, AssetIdOf<T, I>, bool, ValueQuery>;
+
+    /// Permissioned exchanges, to which only approved providers can add liquidity
+    #[pallet::storage]
+    #[pallet::getter(fn permissioned_exchanges)]
+    pub(super) type PermissionedExchanges<T: Config<I>, I: 'static = ()> =
+        StorageMap<_, Twox64Concat, AssetIdOf<T, I>, bool, ValueQuery>;
+
+    /// Liquidity providers approved by the admins of permissioned exchanges
+    #[pallet::storage]
+    #[pallet::getter(fn approved_providers)]
+    pub(super) type ApprovedProviders<T: Config<I>, I: 'static = ()> = StorageDoubleMap<
+        _,
+        Twox64Concat,
+        AssetIdOf<T, I>,
+        Blake2_128Concat,
+        AccountIdOf<T>,
+        bool,
+        ValueQuery,
+    >;
+
+    /// Trades queued in the current block on exchanges in batch auction mode
+    #[pallet::storage]
+    #[pallet::getter(fn batch_trades)]
+    pub(super) type BatchTrades<T: Config<I>, I: 'static = ()> = StorageMap<
+        _,
+        Twox64Concat,
+        AssetIdOf<T, I>,
+        BoundedVec<BatchTradeOf<T, I>, T::MaxBatchTrades>,
+        ValueQuery,
+    >;
+
+    /// Exchanges with trades queued in the current block, in the order of their first trade
+    #[pallet::storage]
+    #[pallet::getter(fn batch_queue)]
+    pub(super) type BatchQueue<T: Config<I>, I: 'static = ()> =
+        StorageValue<_, BoundedVec<AssetIdOf<T, I>, T::MaxBatchAuctions>, ValueQuery>;
+
+    #[pallet::storage]
+    #[pallet::getter(fn trade_commitments)]
+    pub(super) type TradeCommitments<T: Config<I>, I: 'static = ()> = StorageDoubleMap<
+        _,
+        Blake2_128Concat,
+        AccountIdOf<T>,
+        Identity,
+        T::Hash,
+        TradeCommitmentOf<T, I>,
+        OptionQuery,
+    >;
+
+    /// Trade commitments to be swept in a given block (the first block after their reveal window)
+    #[pallet::storage]
+    #[pallet::getter(fn commitment_expiries)]
+    pub(super) type CommitmentExpiries<T: Config<I>, I: 'static = ()> = StorageMap<
+        _,
+        Twox64Concat,
+        BlockNumberFor<T>,
+        BoundedVec<(AccountIdOf<T>, T::Hash), T::MaxCommitmentsPerBlock>,
+        ValueQuery,
+    >;
+
+    /// Next nonce of the swap intents signed by an account
+    #[pallet::storage]
+    #[pallet::getter(fn intent_nonces)]
+    pub(super) type IntentNonces<T: Config<I>, I: 'static = ()> =
+        StorageMap<_, Blake2_128Concat, AccountIdOf<T>, IntentNonce, ValueQuery>;
+
+    #[pallet::hooks]
+    impl<T: Config<I>, I: 'static> Hooks<BlockNumberFor<T>> for Pallet<T, I> {
+        fn on_initialize(now: BlockNumberFor<T>) -> Weight {
+            Self::execute_dca_schedules(now)
+                .saturating_add(Self::expire_trade_commitments(now))
+                .saturating_add(Self::batch_settlement_weight())
+        }
+
+        fn on_idle(now: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+            Self::match_limit_orders(now, remaining_weight)
+        }
+
+        fn on_finalize(_now: BlockNumberFor<T>) {
+            Self::settle_batch_auctions()
+        }
+    }
 
     #[pallet::call]
-    impl<T: Config> Pallet<T> {
+    impl<T: Config<I>, I: 'static> Pallet<T, I> {
         /// Create a new exchange. Deposit initial liquidity (currency & assets).
         /// Create a new liquidity token. Mint & transfer to the caller account an amount
-        /// of the liquidity token equal to `currency_amount`.
+        /// of the liquidity token equal to `currency_amount` (or to the StableSwap invariant
+        /// of the deposit, for StableSwap exchanges).
         /// Emit two events on success: `ExchangeCreated` and `LiquidityAdded`.
         ///
         /// **Parameters:**
@@ -381,6 +1068,9 @@ pub mod pallet {
         ///   * `liquidity_token_id` – ID of the liquidity token to be created. Asset with this ID must *not* exist.
         ///   * `currency_amount` – Initial amount of the currency to deposit in the pool. Must be at least equal `MinDeposit`.
         ///   * `token_amount` – Initial amount of tokens to deposit in the pool. Must be greater than 0.
+        ///   * `curve` – Pricing curve of the exchange. The amplification coefficient of a StableSwap
+        ///     curve must be between 1 and `MaxAmplification`, and both weights of a weighted curve
+        ///     must be at least `MIN_WEIGHT` (1%).
         ///
         /// **Errors:**
         ///   * `AssetNotFound` – Asset with the given `asset_id` does not exist or has total supply equal 0.
@@ -388,25 +1078,31 @@ pub mod pallet {
         ///   * `TokenIdTaken` – Specified `liquidity_token_id` is already taken by another liquidity token.
         ///   * `CurrencyAmountTooLow` – Specified `currency_amount` is lower than `MinDeposit`.
         ///   * `TokenAmountIsZero` – Specified `token_amount` equals 0.
+        ///   * `InvalidAmplification` – The amplification coefficient of `curve` is 0 or greater than `MaxAmplification`.
+        ///   * `InvalidWeight` – A weight of `curve` is lower than `MIN_WEIGHT`.
+        ///   * `UnsupportedCurve` – `curve` is the concentrated-liquidity curve (see `create_concentrated_exchange`).
+        ///   * `Overflow` – The initial liquidity of the StableSwap curve couldn't be computed.
         #[pallet::call_index(0)]
-        #[pallet::weight(<T as Config>::WeightInfo::create_exchange())]
+        #[pallet::weight(<T as Config<I>>::WeightInfo::create_exchange().saturating_add(T::OnLiquidityChanged::weight()))]
         #[transactional]
         pub fn create_exchange(
             origin: OriginFor<T>,
-            asset_id: AssetIdOf<T>,
-            liquidity_token_id: AssetIdOf<T>,
-            currency_amount: BalanceOf<T>,
-            token_amount: AssetBalanceOf<T>,
+            asset_id: AssetIdOf<T, I>,
+            liquidity_token_id: AssetIdOf<T, I>,
+            currency_amount: BalanceOf<T, I>,
+            token_amount: AssetBalanceOf<T, I>,
+            curve: Curve,
         ) -> DispatchResult {
             // -------------------------- Validation part --------------------------
             let caller = ensure_signed(origin)?;
-            ensure!(currency_amount >= T::MinDeposit::get(), Error::<T>::CurrencyAmountTooLow);
-            ensure!(token_amount > Zero::zero(), Error::<T>::TokenAmountIsZero);
+            ensure!(currency_amount >= T::MinDeposit::get(), Error::<T, I>::CurrencyAmountTooLow);
+            ensure!(token_amount > Zero::zero(), Error::<T, I>::TokenAmountIsZero);
+            Self::check_curve(&curve)?;
             if T::Assets::total_issuance(asset_id.clone()).is_zero() {
-                Err(Error::<T>::AssetNotFound)?
+                Err(Error::<T, I>::AssetNotFound)?
             }
-            if <Exchanges<T>>::contains_key(asset_id.clone()) {
-                Err(Error::<T>::ExchangeAlreadyExists)?
+            if <Exchanges<T, I>>::contains_key(asset_id.clone()) {
+                Err(Error::<T, I>::ExchangeAlreadyExists)?
             }
 
             // ----------------------- Create liquidity token ----------------------
@@ -414,24 +1110,25 @@ pub mod pallet {
                 liquidity_token_id.clone(),
                 T::pallet_account(),
                 false,
-                <AssetBalanceOf<T>>::one(),
+                <AssetBalanceOf<T, I>>::one(),
             )
-            .map_err(|_| Error::<T>::TokenIdTaken)?;
+            .map_err(|_| Error::<T, I>::TokenIdTaken)?;
 
             // -------------------------- Update storage ---------------------------
             let exchange = Exchange {
                 asset_id: asset_id.clone(),
-                currency_reserve: <BalanceOf<T>>::zero(),
-                token_reserve: <AssetBalanceOf<T>>::zero(),
-                liquidity_token_id: liquidity_token_id.clone(),
+                currency_reserve: <BalanceOf<T, I>>::zero(),
+                token_reserve: <AssetBalanceOf<T, I>>::zero(),
+                liquidity_token_id: Some(liquidity_token_id.clone()),
+                curve,
             };
-            let liquidity_minted = T::currency_to_asset(currency_amount);
             Self::do_add_liquidity(
                 exchange,
                 currency_amount,
                 token_amount,
-                liquidity_minted,
+                Zero::zero(),
                 caller,
+                Preservation::Preserve,
             )?;
 
             // ---------------------------- Emit event -----------------------------
@@ -449,10 +1146,14 @@ pub mod pallet {
         ///   * `currency_amount` – The amount of the currency to deposit in the pool. Must be greater than 0.
         ///   * `min_liquidity` – The minimum amount of liquidity tokens to receive. Must be greater than 0.
         ///   * `max_tokens` – The maximum amount of tokens to be deposited. Must be greater than 0.
-        ///   * `deadline` – Number of the last block in which the transaction can be included.
+        ///   * `deadline` – Number of the last block, or Unix time (in milliseconds) until which
+        ///     the transaction can be included.
+        ///   * `keep_alive` – Whether the caller's balance of the paid currency or asset must stay
+        ///     above its minimum balance.
         ///
         /// **Errors:**
-        ///   * `DeadlinePassed` – Specified `deadline` is lower than the current block number.
+        ///   * `DeadlinePassed` – Specified `deadline` is lower than the current block number (or time).
+        ///   * `TimestampDeadlineUnsupported` – `deadline` is a timestamp, but the runtime has no time provider.
         ///   * `ExchangeNotFound` – There is no exchange for the given `asset_id`.
         ///   * `CurrencyAmountIsZero` – Specified `currency_amount` equals 0.
         ///   * `MinLiquidityIsZero` – Specified `min_liquidity` equals 0.
@@ -463,48 +1164,43 @@ pub mod pallet {
         ///     Currency and tokens need to be added proportionally.
         ///   * `MinLiquidityTooHigh` – The amount of liquidity tokes which would be minted by depositing the specified
         ///     `currency_amount` is lower than the specified `min_liquidity`.
+        ///   * `UnsupportedCurve` – The exchange is a concentrated-liquidity exchange, which has no liquidity token.
+        ///   * `ProviderNotApproved` – The exchange is permissioned, and the caller isn't an approved liquidity provider.
+        ///   * `OperationNotAllowed` – The operation was rejected by the runtime's `TradeFilter`.
         #[pallet::call_index(1)]
-        #[pallet::weight(<T as Config>::WeightInfo::add_liquidity())]
+        #[pallet::weight(<T as Config<I>>::WeightInfo::add_liquidity().saturating_add(T::OnLiquidityChanged::weight()))]
         pub fn add_liquidity(
             origin: OriginFor<T>,
-            asset_id: AssetIdOf<T>,
-            currency_amount: BalanceOf<T>,
-            min_liquidity: AssetBalanceOf<T>,
-            max_tokens: AssetBalanceOf<T>,
-            deadline: BlockNumberFor<T>,
+            asset_id: AssetIdOf<T, I>,
+            currency_amount: BalanceOf<T, I>,
+            min_liquidity: AssetBalanceOf<T, I>,
+            max_tokens: AssetBalanceOf<T, I>,
+            deadline: DeadlineOf<T>,
+            keep_alive: bool,
         ) -> DispatchResult {
             // -------------------------- Validation part --------------------------
             let caller = ensure_signed(origin)?;
             Self::check_deadline(&deadline)?;
-            ensure!(currency_amount > Zero::zero(), Error::<T>::CurrencyAmountIsZero);
-            ensure!(max_tokens > Zero::zero(), Error::<T>::MaxTokensIsZero);
-            ensure!(min_liquidity > Zero::zero(), Error::<T>::MinLiquidityIsZero);
-            Self::check_enough_currency(&caller, &currency_amount)?;
+            ensure!(currency_amount > Zero::zero(), Error::<T, I>::CurrencyAmountIsZero);
+            ensure!(max_tokens > Zero::zero(), Error::<T, I>::MaxTokensIsZero);
+            ensure!(min_liquidity > Zero::zero(), Error::<T, I>::MinLiquidityIsZero);
+            Self::check_enough_currency(&caller, &currency_amount, Self::preservation(keep_alive))?;
             Self::check_enough_tokens(&asset_id, &caller, &max_tokens)?;
             let exchange = Self::get_exchange(&asset_id)?;
+            Self::liquidity_token(&exchange)?;
 
-            // -------------------- Token/liquidity computation --------------------
-            let total_liquidity = T::Assets::total_issuance(exchange.liquidity_token_id.clone());
-            debug_assert!(total_liquidity > Zero::zero());
-            let currency_amount = T::currency_to_asset(currency_amount);
-            let currency_reserve = T::currency_to_asset(exchange.currency_reserve);
-            let token_amount =
-                FixedU128::saturating_from_rational(currency_amount, currency_reserve)
-                    .saturating_mul_int(exchange.token_reserve)
-                    .saturating_add(One::one());
-            let liquidity_minted =
-                FixedU128::saturating_from_rational(currency_amount, currency_reserve)
-                    .saturating_mul_int(total_liquidity);
-            ensure!(token_amount <= max_tokens, Error::<T>::MaxTokensTooLow);
-            ensure!(liquidity_minted >= min_liquidity, Error::<T>::MinLiquidityTooHigh);
+            // ------------------------- Token computation -------------------------
+            let (token_amount, _) = Self::get_liquidity_deposit(&exchange, currency_amount)?;
+            ensure!(token_amount <= max_tokens, Error::<T, I>::MaxTokensTooLow);
 
             // ----------------------------- State update ----------------------------
             Self::do_add_liquidity(
                 exchange,
-                T::asset_to_currency(currency_amount),
+                currency_amount,
                 token_amount,
-                liquidity_minted,
+                min_liquidity,
                 caller,
+                Self::preservation(keep_alive),
             )
         }
 
@@ -518,10 +1214,12 @@ pub mod pallet {
         ///   * `liquidity_amount` – The amount of liquidity tokens to be burned. Must be greater than 0.
         ///   * `min_currency` – The minimum amount of currency to receive. Must be greater than 0.
         ///   * `min_tokens` – The minimum amount of tokens to receive. Must be greater than 0.
-        ///   * `deadline` – Number of the last block in which the transaction can be included.
+        ///   * `deadline` – Number of the last block, or Unix time (in milliseconds) until which
+        ///     the transaction can be included.
         ///
         /// **Errors:**
-        ///   * `DeadlinePassed` – Specified `deadline` is lower than the current block number.
+        ///   * `DeadlinePassed` – Specified `deadline` is lower than the current block number (or time).
+        ///   * `TimestampDeadlineUnsupported` – `deadline` is a timestamp, but the runtime has no time provider.
         ///   * `ExchangeNotFound` – There is no exchange for the given `asset_id`.
         ///   * `LiquidityAmountIsZero` – Specified `liquidity_amount` equals 0.
         ///   * `MinCurrencyIsZero` – Specified `min_currency` equals 0.
@@ -532,46 +1230,37 @@ pub mod pallet {
         ///     `liquidity_amount` is lower than the specified `min_currency`.
         ///   * `MinTokensTooHigh` – The amount of tokens which could be received in exchange for the specified
         ///     `liquidity_amount` is lower than the specified `min_tokens`.
+        ///   * `UnsupportedCurve` – The exchange is a concentrated-liquidity exchange, which has no liquidity token.
+        ///   * `OperationNotAllowed` – The operation was rejected by the runtime's `TradeFilter`.
+        ///   * `CurrencyBelowMinimum` – The currency received would leave the recipient below its minimum balance.
         #[pallet::call_index(2)]
-        #[pallet::weight(<T as Config>::WeightInfo::remove_liquidity())]
+        #[pallet::weight(<T as Config<I>>::WeightInfo::remove_liquidity().saturating_add(T::OnLiquidityChanged::weight()))]
         pub fn remove_liquidity(
             origin: OriginFor<T>,
-            asset_id: AssetIdOf<T>,
-            liquidity_amount: AssetBalanceOf<T>,
-            min_currency: BalanceOf<T>,
-            min_tokens: AssetBalanceOf<T>,
-            deadline: BlockNumberFor<T>,
+            asset_id: AssetIdOf<T, I>,
+            liquidity_amount: AssetBalanceOf<T, I>,
+            min_currency: BalanceOf<T, I>,
+            min_tokens: AssetBalanceOf<T, I>,
+            deadline: DeadlineOf<T>,
         ) -> DispatchResult {
             // -------------------------- Validation part --------------------------
             let caller = ensure_signed(origin)?;
             Self::check_deadline(&deadline)?;
-            ensure!(liquidity_amount > Zero::zero(), Error::<T>::LiquidityAmountIsZero);
-            ensure!(min_currency > Zero::zero(), Error::<T>::MinCurrencyIsZero);
-            ensure!(min_tokens > Zero::zero(), Error::<T>::MinTokensIsZero);
+            ensure!(liquidity_amount > Zero::zero(), Error::<T, I>::LiquidityAmountIsZero);
+            ensure!(min_currency > Zero::zero(), Error::<T, I>::MinCurrencyIsZero);
+            ensure!(min_tokens > Zero::zero(), Error::<T, I>::MinTokensIsZero);
             let exchange = Self::get_exchange(&asset_id)?;
             Self::check_enough_liquidity_owned(&exchange, &caller, &liquidity_amount)?;
 
-            // --------------- Withdrawn currency/tokens computation ---------------
-            let currency_reserve = T::currency_to_asset(exchange.currency_reserve);
-            let total_liquidity = T::Assets::total_issuance(exchange.liquidity_token_id.clone());
-            let currency_amount =
-                FixedU128::saturating_from_rational(liquidity_amount, total_liquidity)
-                    .saturating_mul_int(currency_reserve);
-            let currency_amount = T::asset_to_currency(currency_amount);
-            let token_amount =
-                FixedU128::saturating_from_rational(liquidity_amount, total_liquidity)
-                    .saturating_mul_int(exchange.token_reserve);
-            ensure!(currency_amount >= min_currency, Error::<T>::MinCurrencyTooHigh);
-            ensure!(token_amount >= min_tokens, Error::<T>::MinTokensTooHigh);
-
             // ----------------------------- State update ----------------------------
             Self::do_remove_liquidity(
                 exchange,
-                currency_amount,
-                token_amount,
                 liquidity_amount,
+                min_currency,
+                min_tokens,
                 caller,
-            )
+            )?;
+            Ok(())
         }
 
         /// Exchange currency for asset. Optionally, transfer bought asset to `recipient`. The caller can specify either:
@@ -580,15 +1269,24 @@ pub mod pallet {
         ///
         /// Emit `CurrencyTradedForAsset` event on success.
         ///
+        /// If the exchange is in batch auction mode, the (maximum) input of the trade is escrowed and the trade
+        /// is settled at the end of the block (see `set_batch_auction`). Emit `BatchTradeQueued` event instead.
+        /// Trades settled by batch auction don't pay the referrer.
+        ///
         /// **Parameters:**
         ///   * `origin` – Origin for the call. Must be signed.
         ///   * `asset_id` – ID of the bought asset. An exchange for this asset must exist and have sufficient liquidity.
         ///   * `amount` – Amount of the currency and asset to trade.
-        ///   * `deadline` – Number of the last block in which the transaction can be included.
+        ///   * `deadline` – Number of the last block, or Unix time (in milliseconds) until which
+        ///     the transaction can be included.
         ///   * `recipient` – (Optional) account to transfer the bought tokens to.
+        ///   * `referrer` – (Optional) account paid `ReferralFee` of the provider fee, in currency.
+        ///   * `keep_alive` – Whether the caller's balance of the paid currency or asset must stay
+        ///     above its minimum balance.
         ///
         /// **Errors:**
-        ///   * `DeadlinePassed` – Specified `deadline` is lower than the current block number.
+        ///   * `DeadlinePassed` – Specified `deadline` is lower than the current block number (or time).
+        ///   * `TimestampDeadlineUnsupported` – `deadline` is a timestamp, but the runtime has no time provider.
         ///   * `ExchangeNotFound` – There is no exchange for the given `asset_id`.
         ///   * `TradeAmountIsZero` – Specified currency or token amount equals 0.
         ///   * `MinTokensTooHigh` – The amount of tokens which could be received in exchange for the specified
@@ -598,36 +1296,40 @@ pub mod pallet {
         ///   * `NotEnoughLiquidity` – There is not enough liquidity in the pool to buy the specified
         ///     amount of tokens (`output_amount`).
         ///   * `BalanceTooLow` – The available currency balance of the caller account is not enough to perform the trade.
+        ///   * `BatchFull` – Too many trades are already queued on the exchange, or too many exchanges have queued trades, in this block.
         ///   * `Overflow` – An overflow occurred during price computation.
+        ///   * `OperationNotAllowed` – The operation was rejected by the runtime's `TradeFilter`.
         #[pallet::call_index(3)]
-        #[pallet::weight(<T as Config>::WeightInfo::currency_to_asset())]
+        #[pallet::weight(
+            <T as Config<I>>::WeightInfo::currency_to_asset()
+                .saturating_add(<T as Config<I>>::WeightInfo::swap_crossing_ticks(
+                    T::MaxTicksCrossed::get()
+                ))
+                .max(<T as Config<I>>::WeightInfo::batch_trade())
+                .saturating_add(T::OnSwap::weight())
+        )]
         pub fn currency_to_asset(
             origin: OriginFor<T>,
-            asset_id: AssetIdOf<T>,
-            amount: TradeAmount<BalanceOf<T>, AssetBalanceOf<T>>,
-            deadline: BlockNumberFor<T>,
+            asset_id: AssetIdOf<T, I>,
+            amount: TradeAmount<BalanceOf<T, I>, AssetBalanceOf<T, I>>,
+            deadline: DeadlineOf<T>,
             recipient: Option<AccountIdOf<T>>,
-        ) -> DispatchResult {
+            referrer: Option<AccountIdOf<T>>,
+            keep_alive: bool,
+        ) -> DispatchResultWithPostInfo {
             // -------------------------- Validation part --------------------------
             let caller = ensure_signed(origin)?;
             let recipient = recipient.unwrap_or_else(|| caller.clone());
             Self::check_deadline(&deadline)?;
-            Self::check_trade_amount(&amount)?;
-            let exchange = Self::get_exchange(&asset_id)?;
-
-            // --------------------------- Compute price ---------------------------
-            let (currency_amount, token_amount) =
-                Self::get_currency_to_asset_price(&exchange, amount)?;
-            Self::check_enough_currency(&caller, &currency_amount)?;
-
-            // --------------------------- Perform trade ---------------------------
-            Self::swap_currency_for_asset(
-                exchange,
-                currency_amount,
-                token_amount,
+            let weight = Self::do_currency_to_asset(
                 caller,
+                asset_id,
+                amount,
                 recipient,
-            )
+                referrer,
+                Self::preservation(keep_alive),
+            )?;
+            Ok(Some(weight).into())
         }
 
         /// Exchange asset for currency. Optionally, transfer bought currency to `recipient`. The caller can specify either:
@@ -636,15 +1338,24 @@ pub mod pallet {
         ///
         /// Emit `AssetTradedForCurrency` event on success.
         ///
+        /// If the exchange is in batch auction mode, the (maximum) input of the trade is escrowed and the trade
+        /// is settled at the end of the block (see `set_batch_auction`). Emit `BatchTradeQueued` event instead.
+        /// Trades settled by batch auction don't pay the referrer.
+        ///
         /// **Parameters:**
         ///   * `origin` – Origin for the call. Must be signed.
         ///   * `asset_id` – ID of the sold asset. An exchange for this asset must exist and have sufficient liquidity.
         ///   * `amount` – Amount of the currency and asset to trade.
-        ///   * `deadline` – Number of the last block in which the transaction can be included.
+        ///   * `deadline` – Number of the last block, or Unix time (in milliseconds) until which
+        ///     the transaction can be included.
         ///   * `recipient` – (Optional) account to transfer the currency tokens to.
+        ///   * `referrer` – (Optional) account paid `ReferralFee` of the provider fee, in tokens.
+        ///   * `keep_alive` – Whether the caller's balance of the paid currency or asset must stay
+        ///     above its minimum balance.
         ///
         /// **Errors:**
-        ///   * `DeadlinePassed` – Specified `deadline` is lower than the current block number.
+        ///   * `DeadlinePassed` – Specified `deadline` is lower than the current block number (or time).
+        ///   * `TimestampDeadlineUnsupported` – `deadline` is a timestamp, but the runtime has no time provider.
         ///   * `ExchangeNotFound` – There is no exchange for the given `asset_id`.
         ///   * `TradeAmountIsZero` – Specified currency or token amount equals 0.
         ///   * `MinCurrencyTooHigh` – The amount of currency which could be received in exchange for the specified
@@ -654,36 +1365,41 @@ pub mod pallet {
         ///   * `NotEnoughLiquidity` – There is not enough liquidity in the pool to buy the specified
         ///     amount of currency (`output_amount`).
         ///   * `NotEnoughTokens` – The available asset balance of the caller account is not enough to perform the trade.
+        ///   * `BatchFull` – Too many trades are already queued on the exchange, or too many exchanges have queued trades, in this block.
         ///   * `Overflow` – An overflow occurred during price computation.
+        ///   * `OperationNotAllowed` – The operation was rejected by the runtime's `TradeFilter`.
+        ///   * `CurrencyBelowMinimum` – The currency received would leave the recipient below its minimum balance.
         #[pallet::call_index(4)]
-        #[pallet::weight(<T as Config>::WeightInfo::asset_to_currency())]
+        #[pallet::weight(
+            <T as Config<I>>::WeightInfo::asset_to_currency()
+                .saturating_add(<T as Config<I>>::WeightInfo::swap_crossing_ticks(
+                    T::MaxTicksCrossed::get()
+                ))
+                .max(<T as Config<I>>::WeightInfo::batch_trade())
+                .saturating_add(T::OnSwap::weight())
+        )]
         pub fn asset_to_currency(
             origin: OriginFor<T>,
-            asset_id: AssetIdOf<T>,
-            amount: TradeAmount<AssetBalanceOf<T>, BalanceOf<T>>,
-            deadline: BlockNumberFor<T>,
+            asset_id: AssetIdOf<T, I>,
+            amount: TradeAmount<AssetBalanceOf<T, I>, BalanceOf<T, I>>,
+            deadline: DeadlineOf<T>,
             recipient: Option<AccountIdOf<T>>,
-        ) -> DispatchResult {
+            referrer: Option<AccountIdOf<T>>,
+            keep_alive: bool,
+        ) -> DispatchResultWithPostInfo {
             // -------------------------- Validation part --------------------------
             let caller = ensure_signed(origin)?;
             let recipient = recipient.unwrap_or_else(|| caller.clone());
             Self::check_deadline(&deadline)?;
-            Self::check_trade_amount(&amount)?;
-            let exchange = Self::get_exchange(&asset_id)?;
-
-            // --------------------------- Compute price ---------------------------
-            let (currency_amount, token_amount) =
-                Self::get_asset_to_currency_price(&exchange, amount)?;
-            Self::check_enough_tokens(&asset_id, &caller, &token_amount)?;
-
-            // --------------------------- Perform trade ---------------------------
-            Self::swap_asset_for_currency(
-                exchange,
-                currency_amount,
-                token_amount,
+            let weight = Self::do_asset_to_currency(
                 caller,
+                asset_id,
+                amount,
                 recipient,
-            )
+                referrer,
+                Self::preservation(keep_alive),
+            )?;
+            Ok(Some(weight).into())
         }
 
         /// Exchange asset for another asset. Optionally, transfer bought asset to `recipient`. The caller can specify either:
@@ -695,11 +1411,17 @@ pub mod pallet {
         ///   * `sold_asset_id` – ID of the sold asset. An exchange for this asset must exist and have sufficient liquidity.
         ///   * `bought_asset_id` – ID of the bought asset. An exchange for this asset must exist and have sufficient liquidity.
         ///   * `amount` – Amount of the assets to trade.
-        ///   * `deadline` – Number of the last block in which the transaction can be included.
+        ///   * `deadline` – Number of the last block, or Unix time (in milliseconds) until which
+        ///     the transaction can be included.
         ///   * `recipient` – (Optional) account to transfer the bought tokens to.
+        ///   * `referrer` – (Optional) account paid `ReferralFee` of the provider fees of both trades,
+        ///     in the sold tokens and currency.
+        ///   * `keep_alive` – Whether the caller's balance of the paid currency or asset must stay
+        ///     above its minimum balance.
         ///
         /// **Errors:**
-        ///   * `DeadlinePassed` – Specified `deadline` is lower than the current block number.
+        ///   * `DeadlinePassed` – Specified `deadline` is lower than the current block number (or time).
+        ///   * `TimestampDeadlineUnsupported` – `deadline` is a timestamp, but the runtime has no time provider.
         ///   * `ExchangeNotFound` – There is no exchange for the given `sold_asset_id` or `bought_asset_id`.
         ///   * `TradeAmountIsZero` – Specified bought or sold token amount equals 0.
         ///   * `MinBoughtTokensTooHigh` – The amount of asset which could be bought in exchange for the specified
@@ -709,17 +1431,27 @@ pub mod pallet {
         ///   * `NotEnoughLiquidity` – There is not enough liquidity in one of the pools to buy the specified amount of asset
         ///     (`output_amount`).
         ///   * `NotEnoughTokens` – The available sold asset balance of the caller account is not enough to perform the trade.
+        ///   * `BatchAuctionEnabled` – One of the exchanges is in batch auction mode.
         ///   * `Overflow` – An overflow occurred during price computation.
+        ///   * `OperationNotAllowed` – The operation was rejected by the runtime's `TradeFilter`.
         #[pallet::call_index(5)]
-        #[pallet::weight(<T as Config>::WeightInfo::asset_to_asset())]
+        #[pallet::weight(
+            <T as Config<I>>::WeightInfo::asset_to_asset().saturating_add(
+                <T as Config<I>>::WeightInfo::swap_crossing_ticks(T::MaxTicksCrossed::get())
+                    .saturating_add(T::OnSwap::weight())
+                    .saturating_mul(2)
+            )
+        )]
         pub fn asset_to_asset(
             origin: OriginFor<T>,
-            sold_asset_id: AssetIdOf<T>,
-            bought_asset_id: AssetIdOf<T>,
-            amount: TradeAmount<AssetBalanceOf<T>, AssetBalanceOf<T>>,
-            deadline: BlockNumberFor<T>,
+            sold_asset_id: AssetIdOf<T, I>,
+            bought_asset_id: AssetIdOf<T, I>,
+            amount: TradeAmount<AssetBalanceOf<T, I>, AssetBalanceOf<T, I>>,
+            deadline: DeadlineOf<T>,
             recipient: Option<AccountIdOf<T>>,
-        ) -> DispatchResult {
+            referrer: Option<AccountIdOf<T>>,
+            keep_alive: bool,
+        ) -> DispatchResultWithPostInfo {
             // -------------------------- Validation part --------------------------
             let caller = ensure_signed(origin)?;
             let recipient = recipient.unwrap_or_else(|| caller.clone());
@@ -727,8 +1459,13 @@ pub mod pallet {
             Self::check_trade_amount(&amount)?;
             let sold_asset_exchange = Self::get_exchange(&sold_asset_id)?;
             let bought_asset_exchange = Self::get_exchange(&bought_asset_id)?;
+            ensure!(
+                !Self::batch_auctions(&sold_asset_id) && !Self::batch_auctions(&bought_asset_id),
+                Error::<T, I>::BatchAuctionEnabled
+            );
 
             // --------------------------- Compute price ---------------------------
+            let exact_input = matches!(amount, TradeAmount::FixedInput { .. });
             let (sold_token_amount, currency_amount, bought_token_amount) =
                 Self::get_asset_to_asset_price(
                     &sold_asset_exchange,
@@ -736,141 +1473,1782 @@ pub mod pallet {
                     amount,
                 )?;
             Self::check_enough_tokens(&sold_asset_id, &caller, &sold_token_amount)?;
+            Self::check_operation_allowed(
+                ExchangeOperation::SellAsset,
+                &sold_asset_id,
+                &caller,
+                &recipient,
+                currency_amount,
+                sold_token_amount,
+            )?;
+            Self::check_operation_allowed(
+                ExchangeOperation::BuyAsset,
+                &bought_asset_id,
+                &caller,
+                &recipient,
+                currency_amount,
+                bought_token_amount,
+            )?;
 
             // --------------------------- Perform trade ---------------------------
-            Self::swap_asset_for_asset(
+            let swap_weight = Self::swap_asset_for_asset(
                 sold_asset_exchange,
                 bought_asset_exchange,
                 currency_amount,
                 sold_token_amount,
                 bought_token_amount,
+                exact_input,
                 caller,
                 recipient,
-            )
-        }
-    }
-
-    impl<T: Config> Pallet<T> {
-        pub(crate) fn get_exchange(asset_id: &AssetIdOf<T>) -> Result<ExchangeOf<T>, Error<T>> {
-            <Exchanges<T>>::get(asset_id.clone()).ok_or(Error::<T>::ExchangeNotFound)
+                referrer,
+                Self::preservation(keep_alive),
+            )?;
+            let weight = <T as Config<I>>::WeightInfo::asset_to_asset()
+                .saturating_add(swap_weight)
+                .saturating_add(T::OnSwap::weight().saturating_mul(2));
+            Ok(Some(weight).into())
         }
 
-        fn check_deadline(deadline: &BlockNumberFor<T>) -> Result<(), Error<T>> {
-            ensure!(deadline >= &<frame_system::Pallet<T>>::block_number(), Error::DeadlinePassed);
-            Ok(())
-        }
+        /// Place a limit order, selling currency for an asset or an asset for currency at a price
+        /// not lower than `limit_price`. The sold amount is escrowed in the pallet account until
+        /// the order is filled, cancelled or expires. Orders are matched against the exchange at the
+        /// end of each block (as long as there is weight left in the block), and may be filled
+        /// partially: each time, as much of the order is sold as possible without the average price
+        /// falling below `limit_price`. Emit `LimitOrderPlaced` event on success.
+        ///
+        /// **Parameters:**
+        ///   * `origin` – Origin for the call. Must be signed.
+        ///   * `asset_id` – ID of the bought or sold asset. An exchange for this asset must exist.
+        ///   * `amount` – Amount of the currency or asset to sell. Must be greater than 0.
+        ///   * `limit_price` – Minimum amount of the bought token per unit of the sold token. Must be greater than 0.
+        ///   * `expiry` – Number of the last block in which the order can be filled.
+        ///   * `keep_alive` – Whether the caller's balance of the paid currency or asset must stay
+        ///     above its minimum balance.
+        ///
+        /// **Errors:**
+        ///   * `DeadlinePassed` – Specified `expiry` is lower than the current block number.
+        ///   * `TradeAmountIsZero` – Specified currency or token amount equals 0.
+        ///   * `LimitPriceIsZero` – Specified `limit_price` equals 0.
+        ///   * `ExchangeNotFound` – There is no exchange for the given `asset_id`.
+        ///   * `UnsupportedCurve` – The exchange doesn't use the constant product curve.
+        ///   * `BalanceTooLow` – The sold currency amount is greater than the available currency balance of the caller account.
+        ///   * `NotEnoughTokens` – The sold token amount is greater than the available asset balance of the caller account.
+        ///   * `Overflow` – The limit order ID overflowed.
+        #[pallet::call_index(6)]
+        #[pallet::weight(<T as Config<I>>::WeightInfo::place_limit_order())]
+        pub fn place_limit_order(
+            origin: OriginFor<T>,
+            asset_id: AssetIdOf<T, I>,
+            amount: OrderAmount<BalanceOf<T, I>, AssetBalanceOf<T, I>>,
+            limit_price: FixedU128,
+            expiry: BlockNumberFor<T>,
+            keep_alive: bool,
+        ) -> DispatchResult {
+            // -------------------------- Validation part --------------------------
+            let caller = ensure_signed(origin)?;
+            Self::check_deadline(&Deadline::Block(expiry))?;
+            ensure!(!limit_price.is_zero(), Error::<T, I>::LimitPriceIsZero);
+            let exchange = Self::get_exchange(&asset_id)?;
+            // Orders are filled up to their limit price on the constant product curve
+            ensure!(exchange.curve == Curve::ConstantProduct, Error::<T, I>::UnsupportedCurve);
 
-        fn check_trade_amount<A: Zero, B: Zero>(
-            amount: &TradeAmount<A, B>,
-        ) -> Result<(), Error<T>> {
+            // ------------------------------ Escrow -------------------------------
+            let pallet_account = T::pallet_account();
             match amount {
-                TradeAmount::FixedInput {
-                    input_amount,
-                    min_output,
-                } => {
-                    ensure!(!input_amount.is_zero(), Error::TradeAmountIsZero);
-                    ensure!(!min_output.is_zero(), Error::TradeAmountIsZero);
+                OrderAmount::SellCurrency(currency_amount) => {
+                    ensure!(!currency_amount.is_zero(), Error::<T, I>::TradeAmountIsZero);
+                    Self::check_enough_currency(
+                        &caller,
+                        &currency_amount,
+                        Self::preservation(keep_alive),
+                    )?;
+                    T::QuoteAsset::transfer(
+                        &caller,
+                        &pallet_account,
+                        currency_amount,
+                        Self::preservation(keep_alive),
+                    )?;
                 }
-                TradeAmount::FixedOutput {
-                    output_amount,
-                    max_input,
-                } => {
-                    ensure!(!output_amount.is_zero(), Error::TradeAmountIsZero);
-                    ensure!(!max_input.is_zero(), Error::TradeAmountIsZero);
+                OrderAmount::SellAsset(token_amount) => {
+                    ensure!(!token_amount.is_zero(), Error::<T, I>::TradeAmountIsZero);
+                    Self::check_enough_tokens(&asset_id, &caller, &token_amount)?;
+                    T::Assets::transfer(
+                        asset_id.clone(),
+                        &caller,
+                        &pallet_account,
+                        token_amount,
+                        Self::preservation(keep_alive),
+                    )?;
                 }
+            }
+
+            // -------------------------- Update storage ---------------------------
+            let order_id = <NextLimitOrderId<T, I>>::get();
+            let next_order_id = order_id.checked_add(1).ok_or(Error::<T, I>::Overflow)?;
+            <NextLimitOrderId<T, I>>::put(next_order_id);
+            let order = LimitOrder {
+                owner: caller.clone(),
+                asset_id: asset_id.clone(),
+                amount,
+                limit_price,
+                expiry,
             };
-            Ok(())
-        }
+            <LimitOrders<T, I>>::insert(order_id, order);
 
-        fn check_enough_currency(
-            account_id: &AccountIdOf<T>,
-            amount: &BalanceOf<T>,
-        ) -> Result<(), Error<T>> {
-            ensure!(
-                &<T as Config>::Currency::free_balance(account_id) >= amount,
-                Error::<T>::BalanceTooLow
-            );
+            // ---------------------------- Emit event -----------------------------
+            Self::deposit_event(Event::LimitOrderPlaced(order_id, caller, asset_id));
             Ok(())
         }
 
-        fn check_enough_tokens(
-            asset_id: &AssetIdOf<T>,
-            account_id: &AccountIdOf<T>,
-            amount: &AssetBalanceOf<T>,
-        ) -> Result<(), Error<T>> {
-            match T::Assets::can_withdraw(asset_id.clone(), account_id, *amount) {
-                WithdrawConsequence::Success => Ok(()),
-                WithdrawConsequence::ReducedToZero(_) => Ok(()),
-                WithdrawConsequence::UnknownAsset => Err(Error::<T>::AssetNotFound),
-                _ => Err(Error::<T>::NotEnoughTokens),
-            }
+        /// Cancel a limit order and refund its unfilled amount.
+        /// Emit `LimitOrderCancelled` event on success.
+        ///
+        /// **Parameters:**
+        ///   * `origin` – Origin for the call. Must be signed by the owner of the order.
+        ///   * `order_id` – ID of the cancelled order.
+        ///
+        /// **Errors:**
+        ///   * `LimitOrderNotFound` – There is no limit order with the given `order_id`.
+        ///   * `NotLimitOrderOwner` – The order is owned by another account.
+        #[pallet::call_index(7)]
+        #[pallet::weight(<T as Config<I>>::WeightInfo::cancel_limit_order())]
+        pub fn cancel_limit_order(origin: OriginFor<T>, order_id: LimitOrderId) -> DispatchResult {
+            // -------------------------- Validation part --------------------------
+            let caller = ensure_signed(origin)?;
+            let order =
+                <LimitOrders<T, I>>::get(order_id).ok_or(Error::<T, I>::LimitOrderNotFound)?;
+            ensure!(order.owner == caller, Error::<T, I>::NotLimitOrderOwner);
+
+            // -------------------------- Refund & remove --------------------------
+            Self::close_limit_order(order_id, order)?;
+
+            // ---------------------------- Emit event -----------------------------
+            Self::deposit_event(Event::LimitOrderCancelled(order_id, caller));
+            Ok(())
+        }
+
+        /// Schedule recurring swaps (DCA – dollar-cost averaging): sell a fixed amount of currency
+        /// or asset every `interval` blocks, `executions` times. The amount sold in all executions
+        /// is escrowed in the pallet account. The first swap is executed in the next block.
+        /// A swap fails if the execution price differs from the spot price by more than `max_slippage`
+        /// (the provider fee counts towards slippage). Failed swaps are retried after `interval` blocks,
+        /// and a schedule which fails more than `MaxDcaRetries` times in a row is terminated.
+        /// Emit `DcaScheduled` event on success.
+        ///
+        /// **Parameters:**
+        ///   * `origin` – Origin for the call. Must be signed.
+        ///   * `swap` – Direction of the swaps and the amount sold in each execution. Must be greater than 0.
+        ///     Exchanges for the traded assets must exist.
+        ///   * `interval` – Number of blocks between executions. Must be greater than 0.
+        ///   * `executions` – Number of executions. Must be greater than 0.
+        ///   * `max_slippage` – Maximum accepted difference between the spot price and the execution price.
+        ///   * `keep_alive` – Whether the caller's balance of the paid currency or asset must stay
+        ///     above its minimum balance.
+        ///
+        /// **Errors:**
+        ///   * `TradeAmountIsZero` – Specified currency or token amount equals 0.
+        ///   * `DcaIntervalIsZero` – Specified `interval` equals 0.
+        ///   * `DcaExecutionsIsZero` – Specified `executions` equals 0.
+        ///   * `ExchangeNotFound` – There is no exchange for one of the traded assets.
+        ///   * `BalanceTooLow` – The currency amount sold in all executions is greater than the available
+        ///     currency balance of the caller account.
+        ///   * `NotEnoughTokens` – The token amount sold in all executions is greater than the available
+        ///     asset balance of the caller account.
+        ///   * `DcaQueueFull` – There are already too many DCA schedules to be executed in the next blocks.
+        ///   * `Overflow` – The amount sold in all executions or the schedule ID overflowed.
+        #[pallet::call_index(8)]
+        #[pallet::weight(<T as Config<I>>::WeightInfo::schedule_dca())]
+        pub fn schedule_dca(
+            origin: OriginFor<T>,
+            swap: DcaSwap<AssetIdOf<T, I>, BalanceOf<T, I>, AssetBalanceOf<T, I>>,
+            interval: BlockNumberFor<T>,
+            executions: u32,
+            max_slippage: Permill,
+            keep_alive: bool,
+        ) -> DispatchResult {
+            // -------------------------- Validation part --------------------------
+            let caller = ensure_signed(origin)?;
+            ensure!(!interval.is_zero(), Error::<T, I>::DcaIntervalIsZero);
+            ensure!(executions > 0, Error::<T, I>::DcaExecutionsIsZero);
+            match &swap {
+                DcaSwap::CurrencyToAsset { asset_id, .. }
+                | DcaSwap::AssetToCurrency { asset_id, .. } => {
+                    Self::get_exchange(asset_id)?;
+                }
+                DcaSwap::AssetToAsset {
+                    sold_asset_id,
+                    bought_asset_id,
+                    ..
+                } => {
+                    Self::get_exchange(sold_asset_id)?;
+                    Self::get_exchange(bought_asset_id)?;
+                }
+            }
+
+            // ------------------------------ Escrow -------------------------------
+            let pallet_account = T::pallet_account();
+            match &swap {
+                DcaSwap::CurrencyToAsset {
+                    currency_amount, ..
+                } => {
+                    ensure!(!currency_amount.is_zero(), Error::<T, I>::TradeAmountIsZero);
+                    let total_amount = currency_amount
+                        .checked_mul(&executions.into())
+                        .ok_or(Error::<T, I>::Overflow)?;
+                    Self::check_enough_currency(
+                        &caller,
+                        &total_amount,
+                        Self::preservation(keep_alive),
+                    )?;
+                    T::QuoteAsset::transfer(
+                        &caller,
+                        &pallet_account,
+                        total_amount,
+                        Self::preservation(keep_alive),
+                    )?;
+                }
+                DcaSwap::AssetToCurrency {
+                    asset_id: sold_asset_id,
+                    token_amount,
+                }
+                | DcaSwap::AssetToAsset {
+                    sold_asset_id,
+                    token_amount,
+                    ..
+                } => {
+                    ensure!(!token_amount.is_zero(), Error::<T, I>::TradeAmountIsZero);
+                    let total_amount = token_amount
+                        .checked_mul(&executions.into())
+                        .ok_or(Error::<T, I>::Overflow)?;
+                    Self::check_enough_tokens(sold_asset_id, &caller, &total_amount)?;
+                    T::Assets::transfer(
+                        sold_asset_id.clone(),
+                        &caller,
+                        &pallet_account,
+                        total_amount,
+                        Self::preservation(keep_alive),
+                    )?;
+                }
+            }
+
+            // -------------------------- Update storage ---------------------------
+            let schedule_id = <NextDcaScheduleId<T, I>>::get();
+            let next_schedule_id = schedule_id.checked_add(1).ok_or(Error::<T, I>::Overflow)?;
+            <NextDcaScheduleId<T, I>>::put(next_schedule_id);
+            let next_block = <frame_system::Pallet<T>>::block_number().saturating_add(One::one());
+            let next_execution = Self::queue_dca_execution(schedule_id, next_block)?;
+            let schedule = DcaSchedule {
+                owner: caller.clone(),
+                swap,
+                interval,
+                remaining_executions: executions,
+                max_slippage,
+                next_execution,
+                failed_executions: 0,
+            };
+            <DcaSchedules<T, I>>::insert(schedule_id, schedule);
+
+            // ---------------------------- Emit event -----------------------------
+            Self::deposit_event(Event::DcaScheduled(schedule_id, caller));
+            Ok(())
+        }
+
+        /// Cancel a DCA schedule and refund the amount escrowed for its remaining executions.
+        /// Emit `DcaCancelled` event on success.
+        ///
+        /// **Parameters:**
+        ///   * `origin` – Origin for the call. Must be signed by the owner of the schedule.
+        ///   * `schedule_id` – ID of the cancelled schedule.
+        ///
+        /// **Errors:**
+        ///   * `DcaScheduleNotFound` – There is no DCA schedule with the given `schedule_id`.
+        ///   * `NotDcaScheduleOwner` – The schedule is owned by another account.
+        #[pallet::call_index(9)]
+        #[pallet::weight(<T as Config<I>>::WeightInfo::cancel_dca())]
+        pub fn cancel_dca(origin: OriginFor<T>, schedule_id: DcaScheduleId) -> DispatchResult {
+            // -------------------------- Validation part --------------------------
+            let caller = ensure_signed(origin)?;
+            let schedule =
+                <DcaSchedules<T, I>>::get(schedule_id).ok_or(Error::<T, I>::DcaScheduleNotFound)?;
+            ensure!(schedule.owner == caller, Error::<T, I>::NotDcaScheduleOwner);
+
+            // -------------------------- Refund & remove --------------------------
+            <DcaQueue<T, I>>::mutate(schedule.next_execution, |schedule_ids| {
+                schedule_ids.retain(|id| *id != schedule_id)
+            });
+            Self::close_dca_schedule(schedule_id, schedule)?;
+
+            // ---------------------------- Emit event -----------------------------
+            Self::deposit_event(Event::DcaCancelled(schedule_id, caller));
+            Ok(())
+        }
+
+        /// Enable or disable batch auction mode for an exchange. In batch auction mode, currency-to-asset
+        /// and asset-to-currency trades are not executed right away: they are queued and settled together
+        /// at the end of the block, at a single clearing price against the exchange. Trades which can't be
+        /// filled within their `min_output`/`max_input` at the clearing price are refunded.
+        /// Asset-to-asset trades involving the exchange are rejected, while limit orders and DCA schedules
+        /// keep trading with the exchange directly.
+        /// Emit `BatchAuctionModeSet` event on success.
+        ///
+        /// **Parameters:**
+        ///   * `origin` – Origin for the call. Must be `AdminOrigin`.
+        ///   * `asset_id` – ID of the asset. An exchange for this asset must exist.
+        ///   * `enabled` – Whether batch auction mode is enabled.
+        ///
+        /// **Errors:**
+        ///   * `ExchangeNotFound` – There is no exchange for the given `asset_id`.
+        ///   * `UnsupportedCurve` – Batch auction mode is being enabled for an exchange which doesn't use
+        ///     the constant product curve.
+        #[pallet::call_index(10)]
+        #[pallet::weight(<T as Config<I>>::WeightInfo::set_batch_auction())]
+        pub fn set_batch_auction(
+            origin: OriginFor<T>,
+            asset_id: AssetIdOf<T, I>,
+            enabled: bool,
+        ) -> DispatchResult {
+            // -------------------------- Validation part --------------------------
+            T::AdminOrigin::ensure_origin(origin)?;
+            let exchange = Self::get_exchange(&asset_id)?;
+            // Batches are cleared at a constant product price
+            ensure!(
+                !enabled || exchange.curve == Curve::ConstantProduct,
+                Error::<T, I>::UnsupportedCurve
+            );
+
+            // -------------------------- Update storage ---------------------------
+            if enabled {
+                <BatchAuctions<T, I>>::insert(asset_id.clone(), true);
+            } else {
+                <BatchAuctions<T, I>>::remove(asset_id.clone());
+            }
+
+            // ---------------------------- Emit event -----------------------------
+            Self::deposit_event(Event::BatchAuctionModeSet(asset_id, enabled));
+            Ok(())
+        }
+
+        /// Commit to a currency-to-asset or asset-to-currency trade without disclosing it, so that
+        /// block producers can't act on the trade intent. `CommitDeposit` is escrowed in the pallet
+        /// account, and refunded when the trade is revealed by `reveal_trade` within `RevealWindow`
+        /// blocks. Commitments which aren't revealed in time are removed and their deposit is forfeited.
+        /// Emit `TradeCommitted` event on success.
+        ///
+        /// **Parameters:**
+        ///   * `origin` – Origin for the call. Must be signed.
+        ///   * `commitment` – Hash (`Hashing` of the runtime) of the SCALE-encoded tuple
+        ///     `(asset_id, amount, recipient, salt)` of the `reveal_trade` parameters.
+        ///   * `keep_alive` – Whether the caller's currency balance must stay above its minimum balance
+        ///     after paying the deposit.
+        ///
+        /// **Errors:**
+        ///   * `CommitmentAlreadyExists` – The caller has already made the same commitment.
+        ///   * `BalanceTooLow` – The available currency balance of the caller account is lower than `CommitDeposit`.
+        ///   * `TooManyCommitments` – `MaxCommitmentsPerBlock` commitments were already made in this block.
+        #[pallet::call_index(11)]
+        #[pallet::weight(<T as Config<I>>::WeightInfo::commit_trade())]
+        pub fn commit_trade(
+            origin: OriginFor<T>,
+            commitment: T::Hash,
+            keep_alive: bool,
+        ) -> DispatchResult {
+            // -------------------------- Validation part --------------------------
+            let caller = ensure_signed(origin)?;
+            ensure!(
+                !<TradeCommitments<T, I>>::contains_key(&caller, commitment),
+                Error::<T, I>::CommitmentAlreadyExists
+            );
+            let deposit = T::CommitDeposit::get();
+            Self::check_enough_currency(&caller, &deposit, Self::preservation(keep_alive))?;
+
+            // ------------------------------ Escrow -------------------------------
+            T::QuoteAsset::transfer(
+                &caller,
+                &T::pallet_account(),
+                deposit,
+                Self::preservation(keep_alive),
+            )?;
+
+            // -------------------------- Update storage ---------------------------
+            let now = <frame_system::Pallet<T>>::block_number();
+            let expiry = now.saturating_add(T::RevealWindow::get());
+            <CommitmentExpiries<T, I>>::try_mutate(expiry.saturating_add(One::one()), |expiring| {
+                expiring.try_push((caller.clone(), commitment))
+            })
+            .map_err(|_| Error::<T, I>::TooManyCommitments)?;
+            <TradeCommitments<T, I>>::insert(
+                &caller,
+                commitment,
+                TradeCommitment { deposit, expiry },
+            );
+
+            // ---------------------------- Emit event -----------------------------
+            Self::deposit_event(Event::TradeCommitted(caller, commitment));
+            Ok(())
+        }
+
+        /// Reveal a trade committed to by `commit_trade`, refund the commitment deposit and execute
+        /// the trade like `currency_to_asset` or `asset_to_currency`. If the trade fails, the commitment
+        /// is kept and the trade can be revealed again until the end of the reveal window.
+        /// Emit `TradeRevealed` event on success.
+        ///
+        /// **Parameters:**
+        ///   * `origin` – Origin for the call. Must be signed by the account which made the commitment.
+        ///   * `asset_id` – ID of the bought or sold asset. An exchange for this asset must exist.
+        ///   * `amount` – Direction of the trade and amount of the currency and asset to trade.
+        ///   * `recipient` – (Optional) account to transfer the bought currency or tokens to.
+        ///   * `salt` – Random value hiding the trade in the commitment.
+        ///   * `keep_alive` – Whether the caller's balance of the paid currency or asset must stay
+        ///     above its minimum balance. It isn't part of the commitment.
+        ///
+        /// **Errors:**
+        ///   * `CommitmentNotFound` – The caller has no commitment matching the revealed trade
+        ///     (or its reveal window has passed).
+        ///   * Any error of `currency_to_asset` or `asset_to_currency` (except `DeadlinePassed`).
+        #[pallet::call_index(12)]
+        #[pallet::weight(
+            <T as Config<I>>::WeightInfo::reveal_trade().saturating_add(
+                <T as Config<I>>::WeightInfo::currency_to_asset()
+                    .max(<T as Config<I>>::WeightInfo::asset_to_currency())
+                    .saturating_add(<T as Config<I>>::WeightInfo::swap_crossing_ticks(
+                        T::MaxTicksCrossed::get()
+                    ))
+                    .max(<T as Config<I>>::WeightInfo::batch_trade())
+                    .saturating_add(T::OnSwap::weight())
+            )
+        )]
+        pub fn reveal_trade(
+            origin: OriginFor<T>,
+            asset_id: AssetIdOf<T, I>,
+            amount: SwapAmount<BalanceOf<T, I>, AssetBalanceOf<T, I>>,
+            recipient: Option<AccountIdOf<T>>,
+            salt: [u8; 32],
+            keep_alive: bool,
+        ) -> DispatchResultWithPostInfo {
+            // -------------------------- Validation part --------------------------
+            let caller = ensure_signed(origin)?;
+            let commitment = T::Hashing::hash_of(&(&asset_id, &amount, &recipient, &salt));
+            let trade_commitment = <TradeCommitments<T, I>>::get(&caller, commitment)
+                .ok_or(Error::<T, I>::CommitmentNotFound)?;
+
+            // -------------------------- Refund & remove --------------------------
+            Self::close_trade_commitment(&caller, commitment, trade_commitment)?;
+
+            // --------------------------- Perform trade ---------------------------
+            let recipient = recipient.unwrap_or_else(|| caller.clone());
+            let trade_weight = match amount {
+                SwapAmount::CurrencyToAsset(amount) => Self::do_currency_to_asset(
+                    caller.clone(),
+                    asset_id,
+                    amount,
+                    recipient,
+                    None,
+                    Self::preservation(keep_alive),
+                )?,
+                SwapAmount::AssetToCurrency(amount) => Self::do_asset_to_currency(
+                    caller.clone(),
+                    asset_id,
+                    amount,
+                    recipient,
+                    None,
+                    Self::preservation(keep_alive),
+                )?,
+            };
+
+            // ---------------------------- Emit event -----------------------------
+            Self::deposit_event(Event::TradeRevealed(caller, commitment));
+            let weight = <T as Config<I>>::WeightInfo::reveal_trade().saturating_add(trade_weight);
+            Ok(Some(weight).into())
+        }
+
+        /// Execute a currency-to-asset or asset-to-currency trade signed off-chain by the intent's
+        /// signer, who pays for the trade. The trade output is split between the caller (relayer),
+        /// who receives the tip, and the intent's recipient, who receives the rest.
+        /// Emit `SwapIntentExecuted` event on success.
+        ///
+        /// **Parameters:**
+        ///   * `origin` – Origin for the call. Must be signed.
+        ///   * `intent` – The swap intent. Its minimum or exact output includes the tip.
+        ///   * `signature` – Signature of the SCALE-encoded pallet id, genesis hash and `intent` by
+        ///     its signer (see `signed_intent_payload`).
+        ///
+        /// **Errors:**
+        ///   * `DeadlinePassed` – Deadline of the intent has passed.
+        ///   * `TimestampDeadlineUnsupported` – Deadline of the intent is a timestamp, but the runtime
+        ///     has no time provider.
+        ///   * `InvalidSignature` – `signature` isn't a valid signature of `intent` by its signer.
+        ///   * `InvalidIntentNonce` – Nonce of the intent isn't the next nonce of its signer
+        ///     (e.g. the intent was already executed).
+        ///   * `BatchAuctionEnabled` – The exchange is in batch auction mode.
+        ///   * `TipTokenMismatch` – The tip isn't in the token bought by the trade.
+        ///   * `TipTooHigh` – The tip is higher than the trade output.
+        ///   * Any error of `currency_to_asset` or `asset_to_currency`.
+        #[pallet::call_index(13)]
+        #[pallet::weight(
+            <T as Config<I>>::WeightInfo::execute_signed_intent()
+                .saturating_add(<T as Config<I>>::WeightInfo::swap_crossing_ticks(
+                    T::MaxTicksCrossed::get()
+                ))
+                .saturating_add(T::OnSwap::weight())
+        )]
+        pub fn execute_signed_intent(
+            origin: OriginFor<T>,
+            intent: SwapIntentOf<T, I>,
+            signature: T::Signature,
+        ) -> DispatchResultWithPostInfo {
+            // -------------------------- Validation part --------------------------
+            let relayer = ensure_signed(origin)?;
+            Self::check_deadline(&intent.deadline)?;
+            ensure!(
+                signature.verify(&Self::signed_intent_payload(&intent)[..], &intent.signer),
+                Error::<T, I>::InvalidSignature
+            );
+            let nonce = <IntentNonces<T, I>>::get(&intent.signer);
+            ensure!(intent.nonce == nonce, Error::<T, I>::InvalidIntentNonce);
+            ensure!(!Self::batch_auctions(&intent.asset_id), Error::<T, I>::BatchAuctionEnabled);
+
+            // -------------------------- Update storage ---------------------------
+            let next_nonce = nonce.checked_add(1).ok_or(Error::<T, I>::Overflow)?;
+            <IntentNonces<T, I>>::insert(&intent.signer, next_nonce);
+
+            // --------------------------- Perform trade ---------------------------
+            let swap_weight = Self::execute_swap_intent(&relayer, &intent)?;
+
+            // ---------------------------- Emit event -----------------------------
+            Self::deposit_event(Event::SwapIntentExecuted(
+                intent.signer,
+                intent.nonce,
+                relayer,
+                intent.tip,
+            ));
+            let weight = <T as Config<I>>::WeightInfo::execute_signed_intent()
+                .saturating_add(swap_weight)
+                .saturating_add(T::OnSwap::weight());
+            Ok(Some(weight).into())
+        }
+
+        /// Add liquidity to an existing exchange with currency only. Swap the part of `currency_amount`
+        /// for which the bought tokens and the rest of the currency match the exchange ratio after
+        /// the trade, then deposit both. Rounding leftovers (at most a few units of the currency or tokens)
+        /// stay in the caller account. Emit `CurrencyTradedForAsset` and `LiquidityAdded` events on success.
+        ///
+        /// **Parameters:**
+        ///   * `origin` – Origin for the call. Must be signed.
+        ///   * `asset_id` – ID of the asset. An exchange for this asset must exist.
+        ///   * `currency_amount` – The amount of the currency to swap and deposit. Must be greater than 0.
+        ///   * `min_liquidity` – The minimum amount of liquidity tokens to receive. Must be greater than 0.
+        ///   * `deadline` – Number of the last block, or Unix time (in milliseconds) until which
+        ///     the transaction can be included.
+        ///   * `keep_alive` – Whether the caller's balance of the paid currency or asset must stay
+        ///     above its minimum balance.
+        ///
+        /// **Errors:**
+        ///   * `DeadlinePassed` – Specified `deadline` is lower than the current block number (or time).
+        ///   * `TimestampDeadlineUnsupported` – `deadline` is a timestamp, but the runtime has no time provider.
+        ///   * `CurrencyAmountIsZero` – Specified `currency_amount` equals 0.
+        ///   * `MinLiquidityIsZero` – Specified `min_liquidity` equals 0.
+        ///   * `BalanceTooLow` – Specified `currency_amount` is greater than the available currency balance of the caller account.
+        ///   * `ExchangeNotFound` – There is no exchange for the given `asset_id`.
+        ///   * `BatchAuctionEnabled` – The exchange is in batch auction mode.
+        ///   * `CurrencyAmountTooLow` – Specified `currency_amount` is too low to be split between a trade and a deposit.
+        ///   * `MinLiquidityTooHigh` – The amount of liquidity tokens which would be minted is lower than
+        ///     the specified `min_liquidity`.
+        ///   * `UnsupportedCurve` – The exchange doesn't use the constant product curve.
+        ///   * `ProviderNotApproved` – The exchange is permissioned, and the caller isn't an approved liquidity provider.
+        ///   * `OperationNotAllowed` – The operation was rejected by the runtime's `TradeFilter`.
+        #[pallet::call_index(14)]
+        #[pallet::weight(
+            <T as Config<I>>::WeightInfo::zap_in()
+                .saturating_add(T::OnSwap::weight())
+                .saturating_add(T::OnLiquidityChanged::weight())
+        )]
+        pub fn zap_in(
+            origin: OriginFor<T>,
+            asset_id: AssetIdOf<T, I>,
+            currency_amount: BalanceOf<T, I>,
+            min_liquidity: AssetBalanceOf<T, I>,
+            deadline: DeadlineOf<T>,
+            keep_alive: bool,
+        ) -> DispatchResult {
+            // -------------------------- Validation part --------------------------
+            let caller = ensure_signed(origin)?;
+            Self::check_deadline(&deadline)?;
+            ensure!(currency_amount > Zero::zero(), Error::<T, I>::CurrencyAmountIsZero);
+            ensure!(min_liquidity > Zero::zero(), Error::<T, I>::MinLiquidityIsZero);
+            Self::check_enough_currency(&caller, &currency_amount, Self::preservation(keep_alive))?;
+            let exchange = Self::get_exchange(&asset_id)?;
+            // The swapped amount is computed on the constant product curve
+            ensure!(exchange.curve == Curve::ConstantProduct, Error::<T, I>::UnsupportedCurve);
+            ensure!(!Self::batch_auctions(&asset_id), Error::<T, I>::BatchAuctionEnabled);
+
+            // --------------------------- Perform trade ---------------------------
+            let swapped_currency = Self::zap_in_swap_amount(&exchange, currency_amount);
+            ensure!(!swapped_currency.is_zero(), Error::<T, I>::CurrencyAmountTooLow);
+            let amount = TradeAmount::FixedInput {
+                input_amount: swapped_currency,
+                min_output: One::one(),
+            };
+            let (swapped_currency, bought_tokens) =
+                Self::get_currency_to_asset_price(&exchange, amount)
+                    .map_err(|_| Error::<T, I>::CurrencyAmountTooLow)?;
+            Self::check_operation_allowed(
+                ExchangeOperation::BuyAsset,
+                &asset_id,
+                &caller,
+                &caller,
+                swapped_currency,
+                bought_tokens,
+            )?;
+            Self::swap_currency_for_asset(
+                exchange,
+                swapped_currency,
+                bought_tokens,
+                true,
+                caller.clone(),
+                caller.clone(),
+                None,
+                Self::preservation(keep_alive),
+            )?;
+
+            // -------------------- Token/liquidity computation --------------------
+            let exchange = Self::get_exchange(&asset_id)?;
+            let (currency_amount, token_amount) = Self::zap_in_deposit(
+                &exchange,
+                currency_amount.saturating_sub(swapped_currency),
+                bought_tokens,
+            )?;
+            ensure!(!currency_amount.is_zero(), Error::<T, I>::CurrencyAmountTooLow);
+
+            // ----------------------------- State update ----------------------------
+            Self::do_add_liquidity(
+                exchange,
+                currency_amount,
+                token_amount,
+                min_liquidity,
+                caller,
+                Self::preservation(keep_alive),
+            )
+        }
+
+        /// Remove liquidity from an exchange and swap the withdrawn currency or tokens, so that
+        /// the caller receives only one side. Emit `LiquidityRemoved` and `CurrencyTradedForAsset`
+        /// or `AssetTradedForCurrency` events on success.
+        ///
+        /// **Parameters:**
+        ///   * `origin` – Origin for the call. Must be signed.
+        ///   * `asset_id` – ID of the asset. An exchange for this asset must exist.
+        ///   * `liquidity_amount` – The amount of liquidity tokens to burn. Must be greater than 0.
+        ///   * `min_output` – The received side (currency or asset) and the minimum total amount of it
+        ///     to receive. Must be greater than 0.
+        ///   * `deadline` – Number of the last block, or Unix time (in milliseconds) until which
+        ///     the transaction can be included.
+        ///
+        /// **Errors:**
+        ///   * `DeadlinePassed` – Specified `deadline` is lower than the current block number (or time).
+        ///   * `TimestampDeadlineUnsupported` – `deadline` is a timestamp, but the runtime has no time provider.
+        ///   * `LiquidityAmountIsZero` – Specified `liquidity_amount` equals 0.
+        ///   * `MinCurrencyIsZero` – Specified minimum currency output equals 0.
+        ///   * `MinTokensIsZero` – Specified minimum token output equals 0.
+        ///   * `ExchangeNotFound` – There is no exchange for the given `asset_id`.
+        ///   * `BatchAuctionEnabled` – The exchange is in batch auction mode.
+        ///   * `ProviderLiquidityTooLow` – Specified `liquidity_amount` is greater than the caller's liquidity.
+        ///   * `MinCurrencyTooHigh` – The total amount of currency received is lower than the specified minimum.
+        ///   * `MinTokensTooHigh` – The total amount of tokens received is lower than the specified minimum.
+        ///   * `NotEnoughLiquidity` – No liquidity would be left in the exchange to swap the withdrawn currency or tokens.
+        ///   * `UnsupportedCurve` – The exchange is a concentrated-liquidity exchange, which has no liquidity token.
+        ///   * `OperationNotAllowed` – The operation was rejected by the runtime's `TradeFilter`.
+        ///   * `CurrencyBelowMinimum` – The currency received would leave the recipient below its minimum balance.
+        #[pallet::call_index(15)]
+        #[pallet::weight(
+            <T as Config<I>>::WeightInfo::zap_out()
+                .saturating_add(T::OnSwap::weight())
+                .saturating_add(T::OnLiquidityChanged::weight())
+        )]
+        pub fn zap_out(
+            origin: OriginFor<T>,
+            asset_id: AssetIdOf<T, I>,
+            liquidity_amount: AssetBalanceOf<T, I>,
+            min_output: ZapOutput<BalanceOf<T, I>, AssetBalanceOf<T, I>>,
+            deadline: DeadlineOf<T>,
+        ) -> DispatchResult {
+            // -------------------------- Validation part --------------------------
+            let caller = ensure_signed(origin)?;
+            Self::check_deadline(&deadline)?;
+            ensure!(liquidity_amount > Zero::zero(), Error::<T, I>::LiquidityAmountIsZero);
+            match min_output {
+                ZapOutput::Currency(min_currency) => {
+                    ensure!(min_currency > Zero::zero(), Error::<T, I>::MinCurrencyIsZero)
+                }
+                ZapOutput::Asset(min_tokens) => {
+                    ensure!(min_tokens > Zero::zero(), Error::<T, I>::MinTokensIsZero)
+                }
+            }
+            let exchange = Self::get_exchange(&asset_id)?;
+            ensure!(!Self::batch_auctions(&asset_id), Error::<T, I>::BatchAuctionEnabled);
+            Self::check_enough_liquidity_owned(&exchange, &caller, &liquidity_amount)?;
+
+            // --------------- Withdrawn currency/tokens computation ---------------
+            let (currency_amount, token_amount) =
+                Self::get_liquidity_withdrawal(&exchange, liquidity_amount)?;
+            ensure!(
+                exchange.currency_reserve > currency_amount
+                    && exchange.token_reserve > token_amount,
+                Error::<T, I>::NotEnoughLiquidity
+            );
+
+            // ----------------------------- State update ----------------------------
+            let (currency_amount, token_amount) = Self::do_remove_liquidity(
+                exchange,
+                liquidity_amount,
+                currency_amount,
+                token_amount,
+                caller.clone(),
+            )?;
+
+            // --------------------------- Perform trade ---------------------------
+            let exchange = Self::get_exchange(&asset_id)?;
+            match min_output {
+                ZapOutput::Currency(min_currency) => {
+                    let amount = TradeAmount::FixedInput {
+                        input_amount: token_amount,
+                        min_output: Zero::zero(),
+                    };
+                    let (bought_currency, token_amount) =
+                        Self::get_asset_to_currency_price(&exchange, amount)?;
+                    ensure!(
+                        currency_amount.saturating_add(bought_currency) >= min_currency,
+                        Error::<T, I>::MinCurrencyTooHigh
+                    );
+                    Self::check_operation_allowed(
+                        ExchangeOperation::SellAsset,
+                        &asset_id,
+                        &caller,
+                        &caller,
+                        bought_currency,
+                        token_amount,
+                    )?;
+                    Self::swap_asset_for_currency(
+                        exchange,
+                        bought_currency,
+                        token_amount,
+                        true,
+                        caller.clone(),
+                        caller,
+                        None,
+                        Preservation::Expendable,
+                    )?;
+                }
+                ZapOutput::Asset(min_tokens) => {
+                    let amount = TradeAmount::FixedInput {
+                        input_amount: currency_amount,
+                        min_output: Zero::zero(),
+                    };
+                    let (currency_amount, bought_tokens) =
+                        Self::get_currency_to_asset_price(&exchange, amount)?;
+                    ensure!(
+                        token_amount.saturating_add(bought_tokens) >= min_tokens,
+                        Error::<T, I>::MinTokensTooHigh
+                    );
+                    Self::check_operation_allowed(
+                        ExchangeOperation::BuyAsset,
+                        &asset_id,
+                        &caller,
+                        &caller,
+                        currency_amount,
+                        bought_tokens,
+                    )?;
+                    Self::swap_currency_for_asset(
+                        exchange,
+                        currency_amount,
+                        bought_tokens,
+                        true,
+                        caller.clone(),
+                        caller,
+                        None,
+                        Preservation::Expendable,
+                    )?;
+                }
+            }
+            Ok(())
+        }
+
+        /// Add liquidity to an existing exchange. The caller specifies the exact amount of tokens
+        /// to deposit, minimum amount of liquidity tokens to receive and maximum amount of currency
+        /// to deposit. Only the currency matching the exchange ratio is deposited.
+        /// Emit `LiquidityAdded` event on success.
+        ///
+        /// **Parameters:**
+        ///   * `origin` – Origin for the call. Must be signed.
+        ///   * `asset_id` – ID of the deposited asset. An exchange for this asset must exist.
+        ///   * `token_amount` – The amount of tokens to deposit. Must be greater than 0.
+        ///   * `min_liquidity` – The minimum amount of liquidity tokens to receive. Must be greater than 0.
+        ///   * `max_currency` – The maximum amount of currency to deposit. Must be greater than 0.
+        ///   * `deadline` – Number of the last block, or Unix time (in milliseconds) until which
+        ///     the transaction can be included.
+        ///   * `keep_alive` – Whether the caller's balance of the paid currency or asset must stay
+        ///     above its minimum balance.
+        ///
+        /// **Errors:**
+        ///   * `DeadlinePassed` – Specified `deadline` is lower than the current block number (or time).
+        ///   * `TimestampDeadlineUnsupported` – `deadline` is a timestamp, but the runtime has no time provider.
+        ///   * `ExchangeNotFound` – There is no exchange for the given `asset_id`.
+        ///   * `TokenAmountIsZero` – Specified `token_amount` equals 0.
+        ///   * `MinLiquidityIsZero` – Specified `min_liquidity` equals 0.
+        ///   * `MaxCurrencyIsZero` – Specified `max_currency` equals 0.
+        ///   * `NotEnoughTokens` – Specified `token_amount` is greater than the available asset balance of the caller account.
+        ///   * `MaxCurrencyTooLow` – Specified `max_currency` is too low to match the `token_amount`.
+        ///     Currency and tokens need to be added proportionally.
+        ///   * `BalanceTooLow` – The available currency balance of the caller account is lower than the currency
+        ///     matching the `token_amount`.
+        ///   * `MinLiquidityTooHigh` – The amount of liquidity tokes which would be minted by depositing the specified
+        ///     `token_amount` is lower than the specified `min_liquidity`.
+        ///   * `UnsupportedCurve` – The exchange is a concentrated-liquidity exchange, which has no liquidity token.
+        ///   * `ProviderNotApproved` – The exchange is permissioned, and the caller isn't an approved liquidity provider.
+        ///   * `OperationNotAllowed` – The operation was rejected by the runtime's `TradeFilter`.
+        #[pallet::call_index(16)]
+        #[pallet::weight(<T as Config<I>>::WeightInfo::add_liquidity_exact_tokens().saturating_add(T::OnLiquidityChanged::weight()))]
+        pub fn add_liquidity_exact_tokens(
+            origin: OriginFor<T>,
+            asset_id: AssetIdOf<T, I>,
+            token_amount: AssetBalanceOf<T, I>,
+            min_liquidity: AssetBalanceOf<T, I>,
+            max_currency: BalanceOf<T, I>,
+            deadline: DeadlineOf<T>,
+            keep_alive: bool,
+        ) -> DispatchResult {
+            // -------------------------- Validation part --------------------------
+            let caller = ensure_signed(origin)?;
+            Self::check_deadline(&deadline)?;
+            ensure!(token_amount > Zero::zero(), Error::<T, I>::TokenAmountIsZero);
+            ensure!(min_liquidity > Zero::zero(), Error::<T, I>::MinLiquidityIsZero);
+            ensure!(max_currency > Zero::zero(), Error::<T, I>::MaxCurrencyIsZero);
+            Self::check_enough_tokens(&asset_id, &caller, &token_amount)?;
+            let exchange = Self::get_exchange(&asset_id)?;
+            Self::liquidity_token(&exchange)?;
+
+            // ------------------------ Currency computation -----------------------
+            let (currency_amount, _) =
+                Self::get_liquidity_deposit_for_tokens(&exchange, token_amount)?;
+            ensure!(currency_amount <= max_currency, Error::<T, I>::MaxCurrencyTooLow);
+            Self::check_enough_currency(&caller, &currency_amount, Self::preservation(keep_alive))?;
+
+            // ----------------------------- State update ----------------------------
+            Self::do_add_liquidity(
+                exchange,
+                currency_amount,
+                token_amount,
+                min_liquidity,
+                caller,
+                Self::preservation(keep_alive),
+            )
+        }
+
+        /// Add liquidity to an existing exchange, with independent bounds on both sides.
+        /// The largest amounts of currency and tokens up to `max_currency` and `max_tokens`
+        /// which match the exchange ratio are deposited, and the rest stays in the caller account.
+        /// Emit `LiquidityAdded` event on success.
+        ///
+        /// **Parameters:**
+        ///   * `origin` – Origin for the call. Must be signed.
+        ///   * `asset_id` – ID of the deposited asset. An exchange for this asset must exist.
+        ///   * `max_currency` – The maximum amount of currency to deposit. Must be greater than 0.
+        ///   * `max_tokens` – The maximum amount of tokens to deposit. Must be greater than 0.
+        ///   * `min_currency` – The minimum amount of currency to deposit. Must be greater than 0.
+        ///   * `min_tokens` – The minimum amount of tokens to deposit. Must be greater than 0.
+        ///   * `min_liquidity` – The minimum amount of liquidity tokens to receive. Must be greater than 0.
+        ///   * `deadline` – Number of the last block, or Unix time (in milliseconds) until which
+        ///     the transaction can be included.
+        ///   * `keep_alive` – Whether the caller's balance of the paid currency or asset must stay
+        ///     above its minimum balance.
+        ///
+        /// **Errors:**
+        ///   * `DeadlinePassed` – Specified `deadline` is lower than the current block number (or time).
+        ///   * `TimestampDeadlineUnsupported` – `deadline` is a timestamp, but the runtime has no time provider.
+        ///   * `ExchangeNotFound` – There is no exchange for the given `asset_id`.
+        ///   * `MaxCurrencyIsZero` – Specified `max_currency` equals 0.
+        ///   * `MaxTokensIsZero` – Specified `max_tokens` equals 0.
+        ///   * `MinCurrencyIsZero` – Specified `min_currency` equals 0.
+        ///   * `MinTokensIsZero` – Specified `min_tokens` equals 0.
+        ///   * `MinLiquidityIsZero` – Specified `min_liquidity` equals 0.
+        ///   * `MinCurrencyTooHigh` – The amount of currency matching `max_tokens` is lower than the specified
+        ///     `min_currency`.
+        ///   * `MinTokensTooHigh` – The amount of tokens matching `max_currency` is lower than the specified
+        ///     `min_tokens`.
+        ///   * `MinLiquidityTooHigh` – The amount of liquidity tokes which would be minted is lower than the specified
+        ///     `min_liquidity`.
+        ///   * `BalanceTooLow` – The available currency balance of the caller account is lower than the deposited currency.
+        ///   * `NotEnoughTokens` – The available asset balance of the caller account is lower than the deposited tokens.
+        ///   * `UnsupportedCurve` – The exchange is a concentrated-liquidity exchange, which has no liquidity token.
+        ///   * `ProviderNotApproved` – The exchange is permissioned, and the caller isn't an approved liquidity provider.
+        ///   * `OperationNotAllowed` – The operation was rejected by the runtime's `TradeFilter`.
+        #[pallet::call_index(17)]
+        #[pallet::weight(<T as Config<I>>::WeightInfo::add_liquidity_bounded().saturating_add(T::OnLiquidityChanged::weight()))]
+        pub fn add_liquidity_bounded(
+            origin: OriginFor<T>,
+            asset_id: AssetIdOf<T, I>,
+            max_currency: BalanceOf<T, I>,
+            max_tokens: AssetBalanceOf<T, I>,
+            min_currency: BalanceOf<T, I>,
+            min_tokens: AssetBalanceOf<T, I>,
+            min_liquidity: AssetBalanceOf<T, I>,
+            deadline: DeadlineOf<T>,
+            keep_alive: bool,
+        ) -> DispatchResult {
+            // -------------------------- Validation part --------------------------
+            let caller = ensure_signed(origin)?;
+            Self::check_deadline(&deadline)?;
+            ensure!(max_currency > Zero::zero(), Error::<T, I>::MaxCurrencyIsZero);
+            ensure!(max_tokens > Zero::zero(), Error::<T, I>::MaxTokensIsZero);
+            ensure!(min_currency > Zero::zero(), Error::<T, I>::MinCurrencyIsZero);
+            ensure!(min_tokens > Zero::zero(), Error::<T, I>::MinTokensIsZero);
+            ensure!(min_liquidity > Zero::zero(), Error::<T, I>::MinLiquidityIsZero);
+            let exchange = Self::get_exchange(&asset_id)?;
+            Self::liquidity_token(&exchange)?;
+
+            // ---------------------- Currency/token computation --------------------
+            let (currency_amount, token_amount, _) = Self::get_bounded_liquidity_deposit(
+                &exchange,
+                max_currency,
+                max_tokens,
+                min_currency,
+                min_tokens,
+            )?;
+            Self::check_enough_currency(&caller, &currency_amount, Self::preservation(keep_alive))?;
+            Self::check_enough_tokens(&asset_id, &caller, &token_amount)?;
+
+            // ----------------------------- State update ----------------------------
+            Self::do_add_liquidity(
+                exchange,
+                currency_amount,
+                token_amount,
+                min_liquidity,
+                caller,
+                Self::preservation(keep_alive),
+            )
+        }
+
+        /// Change the amplification coefficient of a StableSwap exchange linearly over a number
+        /// of blocks, from its current value to `target` in block `end`. A ramp in progress
+        /// is replaced, starting from the coefficient reached so far.
+        /// Emit `AmplificationRampStarted` event on success.
+        ///
+        /// **Parameters:**
+        ///   * `origin` – Origin for the call. Must be `AdminOrigin`.
+        ///   * `asset_id` – ID of the asset. A StableSwap exchange for this asset must exist.
+        ///   * `target` – Amplification coefficient at the end of the ramp.
+        ///   * `end` – Number of the block in which `target` is reached. Must be greater than the current block number.
+        ///
+        /// **Errors:**
+        ///   * `ExchangeNotFound` – There is no exchange for the given `asset_id`.
+        ///   * `NotStableSwapExchange` – The exchange doesn't use the StableSwap curve.
+        ///   * `InvalidAmplification` – Specified `target` is 0 or greater than `MaxAmplification`.
+        ///   * `InvalidRampEnd` – Specified `end` isn't greater than the current block number.
+        #[pallet::call_index(18)]
+        #[pallet::weight(<T as Config<I>>::WeightInfo::ramp_amplification())]
+        pub fn ramp_amplification(
+            origin: OriginFor<T>,
+            asset_id: AssetIdOf<T, I>,
+            target: u32,
+            end: BlockNumberFor<T>,
+        ) -> DispatchResult {
+            // -------------------------- Validation part --------------------------
+            T::AdminOrigin::ensure_origin(origin)?;
+            let mut exchange = Self::get_exchange(&asset_id)?;
+            let initial = Self::pricing_curve(&exchange)
+                .amplification()
+                .ok_or(Error::<T, I>::NotStableSwapExchange)?;
+            Self::check_amplification(target)?;
+            let now = <frame_system::Pallet<T>>::block_number();
+            ensure!(end > now, Error::<T, I>::InvalidRampEnd);
+
+            // -------------------------- Update storage ---------------------------
+            let ramp = AmplificationRamp {
+                initial,
+                target,
+                start: now,
+                end,
+            };
+            <AmplificationRamps<T, I>>::insert(asset_id.clone(), ramp);
+            exchange.curve = Curve::StableSwap {
+                amplification: target,
+            };
+            <Exchanges<T, I>>::insert(asset_id.clone(), exchange);
+
+            // ---------------------------- Emit event -----------------------------
+            Self::deposit_event(Event::AmplificationRampStarted(asset_id, initial, target, end));
+            Ok(())
+        }
+
+        /// Create a new weighted exchange with weights shifting linearly over a number of blocks,
+        /// e.g. a liquidity bootstrapping pool for a token launch. The exchange is created as by
+        /// `create_exchange` with a weighted curve, and its weights can't be changed afterwards.
+        /// Emit three events on success: `ExchangeCreated`, `LiquidityAdded` and `WeightScheduleSet`.
+        ///
+        /// **Parameters:**
+        ///   * `origin` – Origin for the call. Must be signed.
+        ///   * `asset_id` – ID of the asset traded on the created exchange. Asset with this ID must exist.
+        ///   * `liquidity_token_id` – ID of the liquidity token to be created. Asset with this ID must *not* exist.
+        ///   * `currency_amount` – Initial amount of the currency to deposit in the pool. Must be at least equal `MinDeposit`.
+        ///   * `token_amount` – Initial amount of tokens to deposit in the pool. Must be greater than 0.
+        ///   * `schedule` – Weights of the currency reserve at the start and the end of the schedule,
+        ///     and the blocks in which they apply. Both weights of the exchange must stay at least
+        ///     `MIN_WEIGHT` (1%), and the schedule must end after its start and the current block.
+        ///
+        /// **Errors:**
+        ///   * `InvalidWeight` – A weight of the exchange would be lower than `MIN_WEIGHT`.
+        ///   * `InvalidWeightSchedule` – The end of `schedule` isn't greater than its start and the current block number.
+        ///   * The errors of `create_exchange`.
+        #[pallet::call_index(19)]
+        #[pallet::weight(<T as Config<I>>::WeightInfo::create_bootstrapping_exchange().saturating_add(T::OnLiquidityChanged::weight()))]
+        pub fn create_bootstrapping_exchange(
+            origin: OriginFor<T>,
+            asset_id: AssetIdOf<T, I>,
+            liquidity_token_id: AssetIdOf<T, I>,
+            currency_amount: BalanceOf<T, I>,
+            token_amount: AssetBalanceOf<T, I>,
+            schedule: WeightScheduleOf<T>,
+        ) -> DispatchResult {
+            // -------------------------- Validation part --------------------------
+            Self::check_weight(schedule.start_weight)?;
+            Self::check_weight(schedule.end_weight)?;
+            let now = <frame_system::Pallet<T>>::block_number();
+            ensure!(
+                schedule.end > schedule.start && schedule.end > now,
+                Error::<T, I>::InvalidWeightSchedule
+            );
+
+            // -------------------------- Create exchange --------------------------
+            let curve = Curve::Weighted {
+                currency_weight: schedule.end_weight,
+            };
+            Self::create_exchange(
+                origin,
+                asset_id.clone(),
+                liquidity_token_id,
+                currency_amount,
+                token_amount,
+                curve,
+            )?;
+
+            // -------------------------- Update storage ---------------------------
+            <WeightSchedules<T, I>>::insert(asset_id.clone(), schedule.clone());
+
+            // ---------------------------- Emit event -----------------------------
+            Self::deposit_event(Event::WeightScheduleSet(
+                asset_id,
+                schedule.start_weight,
+                schedule.end_weight,
+                schedule.start,
+                schedule.end,
+            ));
+            Ok(())
+        }
+
+        /// Create a new concentrated-liquidity exchange (Uniswap V3 style), and mint the caller's
+        /// initial position, which must be in range of the initial price. Liquidity is provided by
+        /// `mint_position` within price ranges between ticks, which are multiples of `tick_spacing`,
+        /// and held by the providers as positions instead of liquidity tokens. The exchange is traded
+        /// by the same calls as other exchanges, and the fees of each trade are shared by the positions
+        /// in range of the price.
+        /// Emit two events on success: `PositionMinted` and `ConcentratedExchangeCreated`.
+        ///
+        /// **Parameters:**
+        ///   * `origin` – Origin for the call. Must be signed.
+        ///   * `asset_id` – ID of the asset traded on the created exchange. Asset with this ID must exist.
+        ///   * `tick_spacing` – Spacing of the ticks at which positions can start or end.
+        ///     Must be between 1 and `MAX_TICK_SPACING` (16384).
+        ///   * `price` – Initial spot price of the asset (currency per token).
+        ///   * `lower_tick` – Tick at which the price range of the initial position starts.
+        ///   * `upper_tick` – Tick at which the price range of the initial position ends. The range must
+        ///     contain the tick of `price`, and be valid as in `mint_position`.
+        ///   * `max_currency` – The maximum amount of currency to deposit. The deposited amount must
+        ///     be at least equal `MinDeposit`.
+        ///   * `max_tokens` – The maximum amount of tokens to deposit.
+        ///   * `keep_alive` – Whether the caller's balance of the paid currency or asset must stay
+        ///     above its minimum balance.
+        ///
+        /// **Errors:**
+        ///   * `AssetNotFound` – Asset with the given `asset_id` does not exist or has total supply equal 0.
+        ///   * `ExchangeAlreadyExists` – An exchange fot the specified asset already exists.
+        ///   * `InvalidTickSpacing` – Specified `tick_spacing` is 0 or greater than `MAX_TICK_SPACING`.
+        ///   * `InvalidPrice` – Specified `price` is 0 or outside of the prices of the ticks.
+        ///   * `InvalidTickRange` – Specified ticks aren't a valid price range of the exchange,
+        ///     or the range doesn't contain the initial price.
+        ///   * `BalanceTooLow` – Specified `max_currency` is greater than the available currency balance of the caller account.
+        ///   * `NotEnoughTokens` – Specified `max_tokens` is greater than the available asset balance of the caller account.
+        ///   * `PositionLiquidityIsZero` – Specified amounts are too low to provide any liquidity in the price range.
+        ///   * `CurrencyAmountTooLow` – The deposited amount of currency is lower than `MinDeposit`.
+        ///   * `OperationNotAllowed` – The operation was rejected by the runtime's `TradeFilter`.
+        #[pallet::call_index(20)]
+        #[pallet::weight(<T as Config<I>>::WeightInfo::create_concentrated_exchange().saturating_add(T::OnLiquidityChanged::weight()))]
+        #[transactional]
+        pub fn create_concentrated_exchange(
+            origin: OriginFor<T>,
+            asset_id: AssetIdOf<T, I>,
+            tick_spacing: u32,
+            price: FixedU128,
+            lower_tick: i32,
+            upper_tick: i32,
+            max_currency: BalanceOf<T, I>,
+            max_tokens: AssetBalanceOf<T, I>,
+            keep_alive: bool,
+        ) -> DispatchResult {
+            // -------------------------- Validation part --------------------------
+            let caller = ensure_signed(origin)?;
+            ensure!(
+                tick_spacing > 0 && tick_spacing <= MAX_TICK_SPACING,
+                Error::<T, I>::InvalidTickSpacing
+            );
+            let sqrt_price =
+                concentrated::sqrt_price_of(price).ok_or(Error::<T, I>::InvalidPrice)?;
+            if T::Assets::total_issuance(asset_id.clone()).is_zero() {
+                Err(Error::<T, I>::AssetNotFound)?
+            }
+            if <Exchanges<T, I>>::contains_key(asset_id.clone()) {
+                Err(Error::<T, I>::ExchangeAlreadyExists)?
+            }
+            let tick = concentrated::tick_at_sqrt_price(sqrt_price);
+            let exchange = Exchange {
+                asset_id: asset_id.clone(),
+                currency_reserve: <BalanceOf<T, I>>::zero(),
+                token_reserve: <AssetBalanceOf<T, I>>::zero(),
+                liquidity_token_id: None,
+                curve: Curve::Concentrated { tick_spacing },
+            };
+            Self::check_tick_range(&exchange, lower_tick, upper_tick)?;
+            ensure!(lower_tick <= tick && tick < upper_tick, Error::<T, I>::InvalidTickRange);
+            let preservation = Self::preservation(keep_alive);
+            Self::check_enough_currency(&caller, &max_currency, preservation)?;
+            Self::check_enough_tokens(&asset_id, &caller, &max_tokens)?;
+
+            // -------------------------- Update storage ---------------------------
+            let pool = ConcentratedPool {
+                sqrt_price,
+                tick,
+                liquidity: 0,
+                fee_growth: Default::default(),
+            };
+            <ConcentratedPools<T, I>>::insert(asset_id.clone(), pool);
+            Self::do_mint_position(
+                exchange,
+                caller,
+                lower_tick,
+                upper_tick,
+                max_currency,
+                max_tokens,
+                preservation,
+            )?;
+            ensure!(
+                Self::get_exchange(&asset_id)?.currency_reserve >= T::MinDeposit::get(),
+                Error::<T, I>::CurrencyAmountTooLow
+            );
+
+            // ---------------------------- Emit event -----------------------------
+            Self::deposit_event(Event::ConcentratedExchangeCreated(asset_id, tick_spacing, tick));
+            Ok(())
+        }
+
+        /// Add liquidity to the caller's position between two ticks of a concentrated-liquidity
+        /// exchange, creating the position if it doesn't exist. The caller specifies maximum amounts
+        /// of currency and tokens to deposit, and the position gets the most liquidity they provide.
+        /// Below the price range only currency is deposited, above it only tokens, and within it both
+        /// in the ratio of the current price. Emit `PositionMinted` event on success.
+        ///
+        /// **Parameters:**
+        ///   * `origin` – Origin for the call. Must be signed.
+        ///   * `asset_id` – ID of the deposited asset. A concentrated-liquidity exchange for this asset must exist.
+        ///   * `lower_tick` – Tick at which the price range of the position starts.
+        ///   * `upper_tick` – Tick at which the price range of the position ends. Must be greater than `lower_tick`.
+        ///     Both ticks must be multiples of the tick spacing of the exchange, between `MIN_TICK` and `MAX_TICK`.
+        ///   * `max_currency` – The maximum amount of currency to deposit.
+        ///   * `max_tokens` – The maximum amount of tokens to deposit.
+        ///   * `deadline` – Number of the last block, or Unix time (in milliseconds) until which
+        ///     the transaction can be included.
+        ///   * `keep_alive` – Whether the caller's balance of the paid currency or asset must stay
+        ///     above its minimum balance.
+        ///
+        /// **Errors:**
+        ///   * `DeadlinePassed` – Specified `deadline` is lower than the current block number (or time).
+        ///   * `TimestampDeadlineUnsupported` – `deadline` is a timestamp, but the runtime has no time provider.
+        ///   * `ExchangeNotFound` – There is no exchange for the given `asset_id`.
+        ///   * `UnsupportedCurve` – The exchange isn't a concentrated-liquidity exchange.
+        ///   * `InvalidTickRange` – Specified ticks aren't a valid price range of the exchange.
+        ///   * `BalanceTooLow` – Specified `max_currency` is greater than the available currency balance of the caller account.
+        ///   * `NotEnoughTokens` – Specified `max_tokens` is greater than the available asset balance of the caller account.
+        ///   * `PositionLiquidityIsZero` – Specified amounts are too low to provide any liquidity in the price range.
+        ///   * `TooManyTicks` – The exchange already has `MaxTicks` ticks at which positions start or end.
+        ///   * `ProviderNotApproved` – The exchange is permissioned, and the caller isn't an approved liquidity provider.
+        ///   * `OperationNotAllowed` – The operation was rejected by the runtime's `TradeFilter`.
+        #[pallet::call_index(21)]
+        #[pallet::weight(<T as Config<I>>::WeightInfo::mint_position().saturating_add(T::OnLiquidityChanged::weight()))]
+        pub fn mint_position(
+            origin: OriginFor<T>,
+            asset_id: AssetIdOf<T, I>,
+            lower_tick: i32,
+            upper_tick: i32,
+            max_currency: BalanceOf<T, I>,
+            max_tokens: AssetBalanceOf<T, I>,
+            deadline: DeadlineOf<T>,
+            keep_alive: bool,
+        ) -> DispatchResult {
+            // -------------------------- Validation part --------------------------
+            let caller = ensure_signed(origin)?;
+            Self::check_deadline(&deadline)?;
+            let exchange = Self::get_exchange(&asset_id)?;
+            Self::check_tick_range(&exchange, lower_tick, upper_tick)?;
+            Self::check_enough_currency(&caller, &max_currency, Self::preservation(keep_alive))?;
+            Self::check_enough_tokens(&asset_id, &caller, &max_tokens)?;
+
+            // ----------------------------- State update ----------------------------
+            Self::do_mint_position(
+                exchange,
+                caller,
+                lower_tick,
+                upper_tick,
+                max_currency,
+                max_tokens,
+                Self::preservation(keep_alive),
+            )
+        }
+
+        /// Remove liquidity from the caller's position between two ticks of a concentrated-liquidity
+        /// exchange, and collect the fees the position earned. Burning zero liquidity only collects
+        /// the fees. The position is removed when no liquidity is left.
+        /// Emit `PositionBurned` event on success.
+        ///
+        /// **Parameters:**
+        ///   * `origin` – Origin for the call. Must be signed.
+        ///   * `asset_id` – ID of the withdrawn asset. A concentrated-liquidity exchange for this asset must exist.
+        ///   * `lower_tick` – Tick at which the price range of the position starts.
+        ///   * `upper_tick` – Tick at which the price range of the position ends.
+        ///   * `liquidity` – The amount of liquidity to remove from the position.
+        ///   * `min_currency` – The minimum amount of currency to receive, including the fees.
+        ///   * `min_tokens` – The minimum amount of tokens to receive, including the fees.
+        ///   * `deadline` – Number of the last block, or Unix time (in milliseconds) until which
+        ///     the transaction can be included.
+        ///
+        /// **Errors:**
+        ///   * `DeadlinePassed` – Specified `deadline` is lower than the current block number (or time).
+        ///   * `TimestampDeadlineUnsupported` – `deadline` is a timestamp, but the runtime has no time provider.
+        ///   * `ExchangeNotFound` – There is no exchange for the given `asset_id`.
+        ///   * `UnsupportedCurve` – The exchange isn't a concentrated-liquidity exchange.
+        ///   * `PositionNotFound` – The caller has no position between the specified ticks.
+        ///   * `PositionLiquidityTooLow` – Specified `liquidity` is greater than the liquidity of the position.
+        ///   * `MinCurrencyTooHigh` – The amount of currency which would be received is lower than the specified `min_currency`.
+        ///   * `MinTokensTooHigh` – The amount of tokens which would be received is lower than the specified `min_tokens`.
+        ///   * `OperationNotAllowed` – The operation was rejected by the runtime's `TradeFilter`.
+        #[pallet::call_index(22)]
+        #[pallet::weight(<T as Config<I>>::WeightInfo::burn_position().saturating_add(T::OnLiquidityChanged::weight()))]
+        pub fn burn_position(
+            origin: OriginFor<T>,
+            asset_id: AssetIdOf<T, I>,
+            lower_tick: i32,
+            upper_tick: i32,
+            liquidity: u128,
+            min_currency: BalanceOf<T, I>,
+            min_tokens: AssetBalanceOf<T, I>,
+            deadline: DeadlineOf<T>,
+        ) -> DispatchResult {
+            // -------------------------- Validation part --------------------------
+            let caller = ensure_signed(origin)?;
+            Self::check_deadline(&deadline)?;
+            let exchange = Self::get_exchange(&asset_id)?;
+            ensure!(
+                matches!(exchange.curve, Curve::Concentrated { .. }),
+                Error::<T, I>::UnsupportedCurve
+            );
+
+            // ----------------------------- State update ----------------------------
+            Self::do_burn_position(
+                exchange,
+                caller,
+                lower_tick,
+                upper_tick,
+                liquidity,
+                min_currency,
+                min_tokens,
+            )
+        }
+
+        /// Set the dynamic fee of an exchange, which replaces the provider fee of its trades.
+        /// The fee rises from `min_fee` with the volatility of the price of the exchange,
+        /// and is capped at `max_fee`. Replacing the parameters keeps the volatility measured
+        /// so far, and `None` restores the provider fee.
+        /// Emit `DynamicFeeSet` event on success.
+        ///
+        /// **Parameters:**
+        ///   * `origin` – Origin for the call. Must be `AdminOrigin`.
+        ///   * `asset_id` – ID of the asset. An exchange for this asset must exist.
+        ///   * `params` – Minimum fee, maximum fee and volatility multiplier of the dynamic fee,
+        ///     or `None` to remove it. The maximum fee must be at least the minimum fee, and lower than 100%.
+        ///
+        /// **Errors:**
+        ///   * `ExchangeNotFound` – There is no exchange for the given `asset_id`.
+        ///   * `InvalidFeeRange` – Specified `min_fee` is greater than `max_fee`, or `max_fee` is 100%.
+        #[pallet::call_index(23)]
+        #[pallet::weight(<T as Config<I>>::WeightInfo::set_dynamic_fee())]
+        pub fn set_dynamic_fee(
+            origin: OriginFor<T>,
+            asset_id: AssetIdOf<T, I>,
+            params: Option<DynamicFeeParams>,
+        ) -> DispatchResult {
+            // -------------------------- Validation part --------------------------
+            T::AdminOrigin::ensure_origin(origin)?;
+            let exchange = Self::get_exchange(&asset_id)?;
+            if let Some(params) = &params {
+                ensure!(params.is_valid(), Error::<T, I>::InvalidFeeRange);
+            }
+
+            // -------------------------- Update storage ---------------------------
+            match params {
+                Some(params) => {
+                    let dynamic_fee = match Self::dynamic_fees(&asset_id) {
+                        Some(dynamic_fee) => DynamicFee {
+                            params,
+                            ..dynamic_fee
+                        },
+                        None => DynamicFee {
+                            params,
+                            volatility: Permill::zero(),
+                            price: Self::spot_price(&exchange),
+                            updated_at: <frame_system::Pallet<T>>::block_number(),
+                        },
+                    };
+                    <DynamicFees<T, I>>::insert(asset_id.clone(), dynamic_fee);
+                }
+                None => <DynamicFees<T, I>>::remove(asset_id.clone()),
+            }
+
+            // ---------------------------- Emit event -----------------------------
+            Self::deposit_event(Event::DynamicFeeSet(asset_id, params));
+            Ok(())
+        }
+
+        /// Make an exchange permissioned or open. Only the liquidity providers approved with
+        /// `set_provider_approval` can add liquidity to a permissioned exchange, while existing
+        /// liquidity can still be removed. Emit `PermissionedModeSet` event on success.
+        ///
+        /// **Parameters:**
+        ///   * `origin` – Origin for the call. Must be `ExchangeAdmin` for the exchange.
+        ///   * `asset_id` – ID of the asset. An exchange for this asset must exist.
+        ///   * `permissioned` – Whether the exchange is permissioned.
+        ///
+        /// **Errors:**
+        ///   * `ExchangeNotFound` – There is no exchange for the given `asset_id`.
+        #[pallet::call_index(24)]
+        #[pallet::weight(<T as Config<I>>::WeightInfo::set_permissioned())]
+        pub fn set_permissioned(
+            origin: OriginFor<T>,
+            asset_id: AssetIdOf<T, I>,
+            permissioned: bool,
+        ) -> DispatchResult {
+            // -------------------------- Validation part --------------------------
+            T::ExchangeAdmin::ensure_origin(origin, &asset_id)?;
+            Self::get_exchange(&asset_id)?;
+
+            // -------------------------- Update storage ---------------------------
+            if permissioned {
+                <PermissionedExchanges<T, I>>::insert(asset_id.clone(), true);
+            } else {
+                <PermissionedExchanges<T, I>>::remove(asset_id.clone());
+            }
+
+            // ---------------------------- Emit event -----------------------------
+            Self::deposit_event(Event::PermissionedModeSet(asset_id, permissioned));
+            Ok(())
+        }
+
+        /// Approve or revoke a liquidity provider of an exchange. Approvals only matter while the
+        /// exchange is permissioned (see `set_permissioned`), and revoking a provider doesn't remove
+        /// its liquidity. Emit `ProviderApprovalSet` event on success.
+        ///
+        /// **Parameters:**
+        ///   * `origin` – Origin for the call. Must be `ExchangeAdmin` for the exchange.
+        ///   * `asset_id` – ID of the asset. An exchange for this asset must exist.
+        ///   * `provider` – Account of the liquidity provider.
+        ///   * `approved` – Whether the provider can add liquidity to the exchange.
+        ///
+        /// **Errors:**
+        ///   * `ExchangeNotFound` – There is no exchange for the given `asset_id`.
+        #[pallet::call_index(25)]
+        #[pallet::weight(<T as Config<I>>::WeightInfo::set_provider_approval())]
+        pub fn set_provider_approval(
+            origin: OriginFor<T>,
+            asset_id: AssetIdOf<T, I>,
+            provider: AccountIdOf<T>,
+            approved: bool,
+        ) -> DispatchResult {
+            // -------------------------- Validation part --------------------------
+            T::ExchangeAdmin::ensure_origin(origin, &asset_id)?;
+            Self::get_exchange(&asset_id)?;
+
+            // -------------------------- Update storage ---------------------------
+            if approved {
+                <ApprovedProviders<T, I>>::insert(asset_id.clone(), provider.clone(), true);
+            } else {
+                <ApprovedProviders<T, I>>::remove(asset_id.clone(), provider.clone());
+            }
+
+            // ---------------------------- Emit event -----------------------------
+            Self::deposit_event(Event::ProviderApprovalSet(asset_id, provider, approved));
+            Ok(())
+        }
+    }
+
+    impl<T: Config<I>, I: 'static> Pallet<T, I> {
+        pub(crate) fn get_exchange(
+            asset_id: &AssetIdOf<T, I>,
+        ) -> Result<ExchangeOf<T, I>, Error<T, I>> {
+            <Exchanges<T, I>>::get(asset_id.clone()).ok_or(Error::<T, I>::ExchangeNotFound)
+        }
+
+        /// Check the parameters of a pricing curve
+        pub(crate) fn check_curve(curve: &Curve) -> Result<(), Error<T, I>> {
+            match curve {
+                Curve::ConstantProduct => Ok(()),
+                Curve::StableSwap { amplification } => Self::check_amplification(*amplification),
+                Curve::Weighted { currency_weight } => Self::check_weight(*currency_weight),
+                // Created by `create_concentrated_exchange`
+                Curve::Concentrated { .. } => Err(Error::<T, I>::UnsupportedCurve),
+            }
+        }
+
+        /// Check that both the currency and the asset weight are at least `MIN_WEIGHT`
+        pub(crate) fn check_weight(currency_weight: Permill) -> Result<(), Error<T, I>> {
+            ensure!(
+                currency_weight >= MIN_WEIGHT && currency_weight <= MIN_WEIGHT.left_from_one(),
+                Error::<T, I>::InvalidWeight
+            );
+            Ok(())
+        }
+
+        pub(crate) fn check_amplification(amplification: u32) -> Result<(), Error<T, I>> {
+            ensure!(
+                amplification > 0 && amplification <= T::MaxAmplification::get(),
+                Error::<T, I>::InvalidAmplification
+            );
+            Ok(())
+        }
+
+        /// The pricing curve of an exchange in the current block. The amplification coefficient
+        /// of a StableSwap curve follows its last ramp, which ends at the coefficient stored
+        /// in the exchange.
+        pub(crate) fn pricing_curve(exchange: &ExchangeOf<T, I>) -> Curve {
+            let now = <frame_system::Pallet<T>>::block_number();
+            match exchange.curve {
+                Curve::ConstantProduct => Curve::ConstantProduct,
+                Curve::StableSwap { amplification } => {
+                    let amplification = match <AmplificationRamps<T, I>>::get(&exchange.asset_id) {
+                        Some(ramp) => ramp.at(now),
+                        None => amplification,
+                    };
+                    Curve::StableSwap { amplification }
+                }
+                Curve::Weighted { currency_weight } => {
+                    let currency_weight = match <WeightSchedules<T, I>>::get(&exchange.asset_id) {
+                        Some(schedule) => schedule.at(now),
+                        None => currency_weight,
+                    };
+                    Curve::Weighted { currency_weight }
+                }
+                Curve::Concentrated { tick_spacing } => Curve::Concentrated { tick_spacing },
+            }
+        }
+
+        pub(crate) fn provider_fee() -> Fee<BalanceOf<T, I>> {
+            Fee {
+                net_numerator: T::net_amount_numerator(),
+                denominator: T::ProviderFeeDenominator::get(),
+            }
+        }
+
+        pub(crate) fn check_deadline(deadline: &DeadlineOf<T>) -> Result<(), Error<T, I>> {
+            let passed = match deadline {
+                Deadline::Block(block) => block < &<frame_system::Pallet<T>>::block_number(),
+                Deadline::Moment(moment) => {
+                    let now = T::Time::now().ok_or(Error::TimestampDeadlineUnsupported)?;
+                    u128::from(*moment) < now.as_millis()
+                }
+            };
+            ensure!(!passed, Error::DeadlinePassed);
+            Ok(())
+        }
+
+        pub(crate) fn check_trade_amount<A: Zero, B: Zero>(
+            amount: &TradeAmount<A, B>,
+        ) -> Result<(), Error<T, I>> {
+            match amount {
+                TradeAmount::FixedInput {
+                    input_amount,
+                    min_output,
+                } => {
+                    ensure!(!input_amount.is_zero(), Error::TradeAmountIsZero);
+                    ensure!(!min_output.is_zero(), Error::TradeAmountIsZero);
+                }
+                TradeAmount::FixedOutput {
+                    output_amount,
+                    max_input,
+                } => {
+                    ensure!(!output_amount.is_zero(), Error::TradeAmountIsZero);
+                    ensure!(!max_input.is_zero(), Error::TradeAmountIsZero);
+                }
+            };
+            Ok(())
+        }
+
+        /// Check that `account_id` can spend `amount` of currency with the given `preservation`
+        pub(crate) fn check_enough_currency(
+            account_id: &AccountIdOf<T>,
+            amount: &BalanceOf<T, I>,
+            preservation: Preservation,
+        ) -> Result<(), Error<T, I>> {
+            let reducible =
+                T::QuoteAsset::reducible_balance(account_id, preservation, Fortitude::Polite);
+            ensure!(&reducible >= amount, Error::<T, I>::BalanceTooLow);
+            Ok(())
+        }
+
+        /// Preservation of the caller's account chosen with the `keep_alive` parameter of a call
+        pub(crate) fn preservation(keep_alive: bool) -> Preservation {
+            if keep_alive {
+                Preservation::Preserve
+            } else {
+                Preservation::Expendable
+            }
+        }
+
+        /// Transfer currency from the pallet account, failing with `CurrencyBelowMinimum`
+        /// rather than a generic error if the recipient would end up below the minimum balance
+        pub(crate) fn pay_currency(
+            recipient: &AccountIdOf<T>,
+            amount: BalanceOf<T, I>,
+        ) -> DispatchResult {
+            ensure!(
+                T::QuoteAsset::can_deposit(recipient, amount, Provenance::Extant)
+                    != DepositConsequence::BelowMinimum,
+                Error::<T, I>::CurrencyBelowMinimum
+            );
+            T::QuoteAsset::transfer(
+                &T::pallet_account(),
+                recipient,
+                amount,
+                Preservation::Expendable,
+            )?;
+            Ok(())
+        }
+
+        pub(crate) fn check_enough_tokens(
+            asset_id: &AssetIdOf<T, I>,
+            account_id: &AccountIdOf<T>,
+            amount: &AssetBalanceOf<T, I>,
+        ) -> Result<(), Error<T, I>> {
+            match T::Assets::can_withdraw(asset_id.clone(), account_id, *amount) {
+                WithdrawConsequence::Success => Ok(()),
+                WithdrawConsequence::ReducedToZero(_) => Ok(()),
+                WithdrawConsequence::UnknownAsset => Err(Error::<T, I>::AssetNotFound),
+                _ => Err(Error::<T, I>::NotEnoughTokens),
+            }
         }
 
         fn check_enough_liquidity_owned(
-            exchange: &ExchangeOf<T>,
+            exchange: &ExchangeOf<T, I>,
             account_id: &AccountIdOf<T>,
-            amount: &AssetBalanceOf<T>,
-        ) -> Result<(), Error<T>> {
-            let asset_id = exchange.liquidity_token_id.clone();
+            amount: &AssetBalanceOf<T, I>,
+        ) -> Result<(), Error<T, I>> {
+            let asset_id = Self::liquidity_token(exchange)?;
             match T::AssetRegistry::can_withdraw(asset_id, account_id, *amount) {
                 WithdrawConsequence::Success => Ok(()),
                 WithdrawConsequence::ReducedToZero(_) => Ok(()),
-                WithdrawConsequence::UnknownAsset => Err(Error::<T>::AssetNotFound),
-                _ => Err(Error::<T>::ProviderLiquidityTooLow),
+                WithdrawConsequence::UnknownAsset => Err(Error::<T, I>::AssetNotFound),
+                _ => Err(Error::<T, I>::ProviderLiquidityTooLow),
+            }
+        }
+
+        /// Liquidity token of an exchange, which concentrated-liquidity exchanges don't have
+        pub(crate) fn liquidity_token(
+            exchange: &ExchangeOf<T, I>,
+        ) -> Result<AssetIdOf<T, I>, Error<T, I>> {
+            exchange
+                .liquidity_token_id
+                .clone()
+                .ok_or(Error::<T, I>::UnsupportedCurve)
+        }
+
+        /// Total supply of the liquidity token of an exchange, zero if it has none
+        pub(crate) fn total_liquidity(exchange: &ExchangeOf<T, I>) -> AssetBalanceOf<T, I> {
+            match &exchange.liquidity_token_id {
+                Some(liquidity_token_id) => T::Assets::total_issuance(liquidity_token_id.clone()),
+                None => Zero::zero(),
             }
         }
 
+        /// Compute the liquidity minted for a deposit of `currency_amount` and `token_amount`
+        /// to the exchange on its pricing curve
+        pub(crate) fn get_liquidity_minted(
+            exchange: &ExchangeOf<T, I>,
+            currency_amount: BalanceOf<T, I>,
+            token_amount: AssetBalanceOf<T, I>,
+        ) -> Result<AssetBalanceOf<T, I>, Error<T, I>> {
+            Self::pricing_curve(exchange)
+                .liquidity_minted(
+                    T::currency_to_asset(currency_amount),
+                    token_amount,
+                    T::currency_to_asset(exchange.currency_reserve),
+                    exchange.token_reserve,
+                    Self::total_liquidity(exchange),
+                )
+                .map_err(Error::<T, I>::from)
+        }
+
+        /// Compute the (token amount, liquidity minted) of a deposit of `currency_amount`
+        /// to the exchange. Tokens are rounded up, liquidity is rounded down.
+        pub(crate) fn get_liquidity_deposit(
+            exchange: &ExchangeOf<T, I>,
+            currency_amount: BalanceOf<T, I>,
+        ) -> Result<(AssetBalanceOf<T, I>, AssetBalanceOf<T, I>), Error<T, I>> {
+            let currency_reserve = T::currency_to_asset(exchange.currency_reserve);
+            let token_amount = FixedU128::saturating_from_rational(
+                T::currency_to_asset(currency_amount),
+                currency_reserve,
+            )
+            .saturating_mul_int(exchange.token_reserve)
+            .saturating_add(One::one());
+            let liquidity_minted =
+                Self::get_liquidity_minted(exchange, currency_amount, token_amount)?;
+            Ok((token_amount, liquidity_minted))
+        }
+
+        /// Compute the (currency amount, liquidity minted) of a deposit of `token_amount`
+        /// to the exchange. Currency is rounded up, liquidity is rounded down.
+        pub(crate) fn get_liquidity_deposit_for_tokens(
+            exchange: &ExchangeOf<T, I>,
+            token_amount: AssetBalanceOf<T, I>,
+        ) -> Result<(BalanceOf<T, I>, AssetBalanceOf<T, I>), Error<T, I>> {
+            let currency_reserve = T::currency_to_asset(exchange.currency_reserve);
+            let currency_amount =
+                FixedU128::saturating_from_rational(token_amount, exchange.token_reserve)
+                    .saturating_mul_int(currency_reserve)
+                    .saturating_add(One::one());
+            let currency_amount = T::asset_to_currency(currency_amount);
+            let liquidity_minted =
+                Self::get_liquidity_minted(exchange, currency_amount, token_amount)?;
+            Ok((currency_amount, liquidity_minted))
+        }
+
+        /// Compute the (currency amount, token amount, liquidity minted) of the largest deposit
+        /// of at most `max_currency` and `max_tokens` which matches the exchange ratio.
+        /// Fail if the deposited currency or tokens would be lower than `min_currency`
+        /// or `min_tokens`.
+        pub(crate) fn get_bounded_liquidity_deposit(
+            exchange: &ExchangeOf<T, I>,
+            max_currency: BalanceOf<T, I>,
+            max_tokens: AssetBalanceOf<T, I>,
+            min_currency: BalanceOf<T, I>,
+            min_tokens: AssetBalanceOf<T, I>,
+        ) -> Result<(BalanceOf<T, I>, AssetBalanceOf<T, I>, AssetBalanceOf<T, I>), Error<T, I>>
+        {
+            let (token_amount, liquidity_minted) =
+                Self::get_liquidity_deposit(exchange, max_currency)?;
+            let (currency_amount, token_amount, liquidity_minted) = if token_amount <= max_tokens {
+                (max_currency, token_amount, liquidity_minted)
+            } else {
+                // All the tokens are deposited. As both directions round up, the matching currency
+                // can exceed `max_currency` by 1.
+                let (currency_amount, _) =
+                    Self::get_liquidity_deposit_for_tokens(exchange, max_tokens)?;
+                let currency_amount = currency_amount.min(max_currency);
+                let liquidity_minted =
+                    Self::get_liquidity_minted(exchange, currency_amount, max_tokens)?;
+                (currency_amount, max_tokens, liquidity_minted)
+            };
+            ensure!(currency_amount >= min_currency, Error::<T, I>::MinCurrencyTooHigh);
+            ensure!(token_amount >= min_tokens, Error::<T, I>::MinTokensTooHigh);
+            Ok((currency_amount, token_amount, liquidity_minted))
+        }
+
+        /// Compute the (currency amount, token amount) withdrawn from the exchange
+        /// by burning `liquidity_amount`. Both are rounded down.
+        pub(crate) fn get_liquidity_withdrawal(
+            exchange: &ExchangeOf<T, I>,
+            liquidity_amount: AssetBalanceOf<T, I>,
+        ) -> Result<(BalanceOf<T, I>, AssetBalanceOf<T, I>), Error<T, I>> {
+            let (currency_amount, token_amount) = Self::pricing_curve(exchange)
+                .liquidity_withdrawal(
+                    liquidity_amount,
+                    T::currency_to_asset(exchange.currency_reserve),
+                    exchange.token_reserve,
+                    Self::total_liquidity(exchange),
+                )
+                .map_err(Error::<T, I>::from)?;
+            Ok((T::asset_to_currency(currency_amount), token_amount))
+        }
+
         pub(crate) fn get_output_amount(
-            input_amount: &BalanceOf<T>,
-            input_reserve: &BalanceOf<T>,
-            output_reserve: &BalanceOf<T>,
-        ) -> Result<BalanceOf<T>, Error<T>> {
+            exchange: &ExchangeOf<T, I>,
+            input: Side,
+            input_amount: &BalanceOf<T, I>,
+            input_reserve: &BalanceOf<T, I>,
+            output_reserve: &BalanceOf<T, I>,
+        ) -> Result<BalanceOf<T, I>, Error<T, I>> {
+            if let Curve::Concentrated { .. } = exchange.curve {
+                return Self::concentrated_output_amount(
+                    &exchange.asset_id,
+                    input,
+                    *input_amount,
+                    &Self::trade_fee(exchange),
+                );
+            }
             debug_assert!(!input_reserve.is_zero());
             debug_assert!(!output_reserve.is_zero());
-            let input_amount_with_fee = input_amount
-                .checked_mul(&T::net_amount_numerator())
-                .ok_or(Error::Overflow)?;
-            let numerator = input_amount_with_fee
-                .checked_mul(output_reserve)
-                .ok_or(Error::Overflow)?;
-            let denominator = input_reserve
-                .checked_mul(&T::ProviderFeeDenominator::get())
-                .ok_or(Error::Overflow)?
-                .checked_add(&input_amount_with_fee)
-                .ok_or(Error::Overflow)?;
-            Ok(numerator / denominator)
+            let output_amount = Self::pricing_curve(exchange).output_amount(
+                input,
+                *input_amount,
+                *input_reserve,
+                *output_reserve,
+                &Self::trade_fee(exchange),
+            )?;
+            Ok(output_amount)
         }
 
         pub(crate) fn get_input_amount(
-            output_amount: &BalanceOf<T>,
-            input_reserve: &BalanceOf<T>,
-            output_reserve: &BalanceOf<T>,
-        ) -> Result<BalanceOf<T>, Error<T>> {
+            exchange: &ExchangeOf<T, I>,
+            input: Side,
+            output_amount: &BalanceOf<T, I>,
+            input_reserve: &BalanceOf<T, I>,
+            output_reserve: &BalanceOf<T, I>,
+        ) -> Result<BalanceOf<T, I>, Error<T, I>> {
+            if let Curve::Concentrated { .. } = exchange.curve {
+                return Self::concentrated_input_amount(
+                    &exchange.asset_id,
+                    input,
+                    *output_amount,
+                    &Self::trade_fee(exchange),
+                );
+            }
             debug_assert!(!input_reserve.is_zero());
             debug_assert!(!output_reserve.is_zero());
-            ensure!(output_amount < output_reserve, Error::<T>::NotEnoughLiquidity);
-            let numerator = input_reserve
-                .checked_mul(output_amount)
-                .ok_or(Error::Overflow)?
-                .checked_mul(&T::ProviderFeeDenominator::get())
-                .ok_or(Error::Overflow)?;
-            let denominator = output_reserve
-                .saturating_sub(*output_amount)
-                .checked_mul(&T::net_amount_numerator())
-                .ok_or(Error::Overflow)?;
-            Ok((numerator / denominator).saturating_add(<BalanceOf<T>>::one()))
-        }
-
-        fn get_currency_to_asset_price(
-            exchange: &ExchangeOf<T>,
-            amount: TradeAmount<BalanceOf<T>, AssetBalanceOf<T>>,
-        ) -> Result<(BalanceOf<T>, AssetBalanceOf<T>), Error<T>> {
+            let input_amount = Self::pricing_curve(exchange).input_amount(
+                input,
+                *output_amount,
+                *input_reserve,
+                *output_reserve,
+                &Self::trade_fee(exchange),
+            )?;
+            Ok(input_amount)
+        }
+
+        pub(crate) fn get_currency_to_asset_price(
+            exchange: &ExchangeOf<T, I>,
+            amount: TradeAmount<BalanceOf<T, I>, AssetBalanceOf<T, I>>,
+        ) -> Result<(BalanceOf<T, I>, AssetBalanceOf<T, I>), Error<T, I>> {
             match amount {
                 TradeAmount::FixedInput {
                     input_amount: currency_amount,
                     min_output: min_tokens,
                 } => {
                     let token_amount = Self::get_output_amount(
+                        exchange,
+                        Side::Currency,
                         &currency_amount,
                         &exchange.currency_reserve,
                         &T::asset_to_currency(exchange.token_reserve),
@@ -884,6 +3262,8 @@ pub mod pallet {
                     output_amount: token_amount,
                 } => {
                     let currency_amount = Self::get_input_amount(
+                        exchange,
+                        Side::Currency,
                         &T::asset_to_currency(token_amount),
                         &exchange.currency_reserve,
                         &T::asset_to_currency(exchange.token_reserve),
@@ -894,16 +3274,18 @@ pub mod pallet {
             }
         }
 
-        fn get_asset_to_currency_price(
-            exchange: &ExchangeOf<T>,
-            amount: TradeAmount<AssetBalanceOf<T>, BalanceOf<T>>,
-        ) -> Result<(BalanceOf<T>, AssetBalanceOf<T>), Error<T>> {
+        pub(crate) fn get_asset_to_currency_price(
+            exchange: &ExchangeOf<T, I>,
+            amount: TradeAmount<AssetBalanceOf<T, I>, BalanceOf<T, I>>,
+        ) -> Result<(BalanceOf<T, I>, AssetBalanceOf<T, I>), Error<T, I>> {
             match amount {
                 TradeAmount::FixedInput {
                     input_amount: token_amount,
                     min_output: min_currency,
                 } => {
                     let currency_amount = Self::get_output_amount(
+                        exchange,
+                        Side::Asset,
                         &T::asset_to_currency(token_amount),
                         &T::asset_to_currency(exchange.token_reserve),
                         &exchange.currency_reserve,
@@ -916,6 +3298,8 @@ pub mod pallet {
                     output_amount: currency_amount,
                 } => {
                     let token_amount = Self::get_input_amount(
+                        exchange,
+                        Side::Asset,
                         &currency_amount,
                         &T::asset_to_currency(exchange.token_reserve),
                         &exchange.currency_reserve,
@@ -927,22 +3311,26 @@ pub mod pallet {
             }
         }
 
-        fn get_asset_to_asset_price(
-            sold_asset_exchange: &ExchangeOf<T>,
-            bought_asset_exchange: &ExchangeOf<T>,
-            amount: TradeAmount<AssetBalanceOf<T>, AssetBalanceOf<T>>,
-        ) -> Result<AssetToAssetPrice<T>, Error<T>> {
+        pub(crate) fn get_asset_to_asset_price(
+            sold_asset_exchange: &ExchangeOf<T, I>,
+            bought_asset_exchange: &ExchangeOf<T, I>,
+            amount: TradeAmount<AssetBalanceOf<T, I>, AssetBalanceOf<T, I>>,
+        ) -> Result<AssetToAssetPrice<T, I>, Error<T, I>> {
             match amount {
                 TradeAmount::FixedInput {
                     input_amount: sold_token_amount,
                     min_output: min_bought_tokens,
                 } => {
                     let currency_amount = Self::get_output_amount(
+                        sold_asset_exchange,
+                        Side::Asset,
                         &T::asset_to_currency(sold_token_amount),
                         &T::asset_to_currency(sold_asset_exchange.token_reserve),
                         &sold_asset_exchange.currency_reserve,
                     )?;
                     let bought_token_amount = Self::get_output_amount(
+                        bought_asset_exchange,
+                        Side::Currency,
                         &currency_amount,
                         &bought_asset_exchange.currency_reserve,
                         &T::asset_to_currency(bought_asset_exchange.token_reserve),
@@ -950,7 +3338,7 @@ pub mod pallet {
                     let bought_token_amount = T::currency_to_asset(bought_token_amount);
                     ensure!(
                         bought_token_amount >= min_bought_tokens,
-                        Error::<T>::MinBoughtTokensTooHigh
+                        Error::<T, I>::MinBoughtTokensTooHigh
                     );
                     Ok((sold_token_amount, currency_amount, bought_token_amount))
                 }
@@ -959,50 +3347,67 @@ pub mod pallet {
                     output_amount: bought_token_amount,
                 } => {
                     let currency_amount = Self::get_input_amount(
+                        bought_asset_exchange,
+                        Side::Currency,
                         &T::asset_to_currency(bought_token_amount),
                         &bought_asset_exchange.currency_reserve,
                         &T::asset_to_currency(bought_asset_exchange.token_reserve),
                     )?;
                     let sold_token_amount = Self::get_input_amount(
+                        sold_asset_exchange,
+                        Side::Asset,
                         &currency_amount,
                         &T::asset_to_currency(sold_asset_exchange.token_reserve),
                         &sold_asset_exchange.currency_reserve,
                     )?;
                     let sold_token_amount = T::currency_to_asset(sold_token_amount);
-                    ensure!(sold_token_amount <= max_sold_tokens, Error::<T>::MaxSoldTokensTooLow);
+                    ensure!(
+                        sold_token_amount <= max_sold_tokens,
+                        Error::<T, I>::MaxSoldTokensTooLow
+                    );
                     Ok((sold_token_amount, currency_amount, bought_token_amount))
                 }
             }
         }
 
-        /// Perform currency and asset transfers, mint liquidity token,
-        /// update exchange balances, emit event
+        /// Compute the liquidity minted for the deposit, perform currency and asset transfers,
+        /// mint liquidity token, update exchange balances, emit event
         #[transactional]
-        fn do_add_liquidity(
-            mut exchange: ExchangeOf<T>,
-            currency_amount: BalanceOf<T>,
-            token_amount: AssetBalanceOf<T>,
-            liquidity_minted: AssetBalanceOf<T>,
+        pub(crate) fn do_add_liquidity(
+            mut exchange: ExchangeOf<T, I>,
+            currency_amount: BalanceOf<T, I>,
+            token_amount: AssetBalanceOf<T, I>,
+            min_liquidity: AssetBalanceOf<T, I>,
             provider: AccountIdOf<T>,
+            preservation: Preservation,
         ) -> DispatchResult {
-            // --------------------- Currency & token transfer ---------------------
+            // -------------------------- Validation part --------------------------
             let asset_id = exchange.asset_id.clone();
-            let pallet_account = T::pallet_account();
-            <T as pallet::Config>::Currency::transfer(
+            let liquidity_minted =
+                Self::get_liquidity_minted(&exchange, currency_amount, token_amount)?;
+            ensure!(liquidity_minted >= min_liquidity, Error::<T, I>::MinLiquidityTooHigh);
+            Self::check_provider_approved(&asset_id, &provider)?;
+            Self::check_operation_allowed(
+                ExchangeOperation::AddLiquidity,
+                &asset_id,
+                &provider,
                 &provider,
-                &pallet_account,
                 currency_amount,
-                ExistenceRequirement::KeepAlive,
+                token_amount,
             )?;
+
+            // --------------------- Currency & token transfer ---------------------
+            let pallet_account = T::pallet_account();
+            T::QuoteAsset::transfer(&provider, &pallet_account, currency_amount, preservation)?;
             T::Assets::transfer(
                 asset_id.clone(),
                 &provider,
                 &pallet_account,
                 token_amount,
-                Preservation::Preserve,
+                preservation,
             )?;
             T::AssetRegistry::mint_into(
-                exchange.liquidity_token_id.clone(),
+                Self::liquidity_token(&exchange)?,
                 &provider,
                 liquidity_minted,
             )?;
@@ -1010,7 +3415,16 @@ pub mod pallet {
             // -------------------------- Balances update --------------------------
             exchange.currency_reserve.saturating_accrue(currency_amount);
             exchange.token_reserve.saturating_accrue(token_amount);
-            <Exchanges<T>>::insert(asset_id.clone(), exchange);
+            <Exchanges<T, I>>::insert(asset_id.clone(), exchange);
+
+            // -------------------------- Notify handlers --------------------------
+            T::OnLiquidityChanged::on_liquidity_changed(&LiquidityChange::Added {
+                provider: provider.clone(),
+                asset_id: asset_id.clone(),
+                currency_amount,
+                token_amount,
+                liquidity_minted,
+            });
 
             // ---------------------------- Emit event -----------------------------
             Self::deposit_event(Event::LiquidityAdded(
@@ -1023,32 +3437,42 @@ pub mod pallet {
             Ok(())
         }
 
-        /// Perform currency and asset transfers, burn liquidity token,
-        /// update exchange balances, emit event
+        /// Compute the withdrawn currency and tokens, perform currency and asset transfers,
+        /// burn liquidity token, update exchange balances, emit event.
+        /// Return the withdrawn (currency amount, token amount).
         #[transactional]
         fn do_remove_liquidity(
-            mut exchange: ExchangeOf<T>,
-            currency_amount: BalanceOf<T>,
-            token_amount: AssetBalanceOf<T>,
-            liquidity_amount: AssetBalanceOf<T>,
+            mut exchange: ExchangeOf<T, I>,
+            liquidity_amount: AssetBalanceOf<T, I>,
+            min_currency: BalanceOf<T, I>,
+            min_tokens: AssetBalanceOf<T, I>,
             provider: AccountIdOf<T>,
-        ) -> DispatchResult {
-            // --------------------- Currency & token transfer ---------------------
+        ) -> Result<(BalanceOf<T, I>, AssetBalanceOf<T, I>), DispatchError> {
+            // -------------------------- Validation part --------------------------
             let asset_id = exchange.asset_id.clone();
+            let (currency_amount, token_amount) =
+                Self::get_liquidity_withdrawal(&exchange, liquidity_amount)?;
+            ensure!(currency_amount >= min_currency, Error::<T, I>::MinCurrencyTooHigh);
+            ensure!(token_amount >= min_tokens, Error::<T, I>::MinTokensTooHigh);
+            Self::check_operation_allowed(
+                ExchangeOperation::RemoveLiquidity,
+                &asset_id,
+                &provider,
+                &provider,
+                currency_amount,
+                token_amount,
+            )?;
+
+            // --------------------- Currency & token transfer ---------------------
             let pallet_account = T::pallet_account();
             T::AssetRegistry::burn_from(
-                exchange.liquidity_token_id.clone(),
+                Self::liquidity_token(&exchange)?,
                 &provider,
                 liquidity_amount,
                 Precision::Exact,
                 Fortitude::Polite,
             )?;
-            <T as pallet::Config>::Currency::transfer(
-                &pallet_account,
-                &provider,
-                currency_amount,
-                ExistenceRequirement::AllowDeath,
-            )?;
+            Self::pay_currency(&provider, currency_amount)?;
             T::Assets::transfer(
                 asset_id.clone(),
                 &pallet_account,
@@ -1060,7 +3484,16 @@ pub mod pallet {
             // -------------------------- Balances update --------------------------
             exchange.currency_reserve.saturating_reduce(currency_amount);
             exchange.token_reserve.saturating_reduce(token_amount);
-            <Exchanges<T>>::insert(asset_id.clone(), exchange);
+            <Exchanges<T, I>>::insert(asset_id.clone(), exchange);
+
+            // -------------------------- Notify handlers --------------------------
+            T::OnLiquidityChanged::on_liquidity_changed(&LiquidityChange::Removed {
+                provider: provider.clone(),
+                asset_id: asset_id.clone(),
+                currency_amount,
+                token_amount,
+                liquidity_amount,
+            });
 
             // ---------------------------- Emit event -----------------------------
             Self::deposit_event(Event::LiquidityRemoved(
@@ -1070,41 +3503,186 @@ pub mod pallet {
                 token_amount,
                 liquidity_amount,
             ));
-            Ok(())
+            Ok((currency_amount, token_amount))
+        }
+
+        /// Check and perform a trade, or queue it if the exchange is in batch auction mode.
+        /// Return the actual weight of the trade.
+        fn do_currency_to_asset(
+            caller: AccountIdOf<T>,
+            asset_id: AssetIdOf<T, I>,
+            amount: TradeAmount<BalanceOf<T, I>, AssetBalanceOf<T, I>>,
+            recipient: AccountIdOf<T>,
+            referrer: Option<AccountIdOf<T>>,
+            preservation: Preservation,
+        ) -> Result<Weight, DispatchError> {
+            // -------------------------- Validation part --------------------------
+            Self::check_trade_amount(&amount)?;
+            let exchange = Self::get_exchange(&asset_id)?;
+            if Self::batch_auctions(&asset_id) {
+                let amount = SwapAmount::CurrencyToAsset(amount);
+                Self::queue_batch_trade(asset_id, caller, recipient, amount, preservation)?;
+                return Ok(<T as Config<I>>::WeightInfo::batch_trade());
+            }
+
+            // --------------------------- Compute price ---------------------------
+            let exact_input = matches!(amount, TradeAmount::FixedInput { .. });
+            let (currency_amount, token_amount) =
+                Self::get_currency_to_asset_price(&exchange, amount)?;
+            Self::check_enough_currency(&caller, &currency_amount, preservation)?;
+            Self::check_operation_allowed(
+                ExchangeOperation::BuyAsset,
+                &asset_id,
+                &caller,
+                &recipient,
+                currency_amount,
+                token_amount,
+            )?;
+
+            // --------------------------- Perform trade ---------------------------
+            let swap_weight = Self::swap_currency_for_asset(
+                exchange,
+                currency_amount,
+                token_amount,
+                exact_input,
+                caller,
+                recipient,
+                referrer,
+                preservation,
+            )?;
+            Ok(<T as Config<I>>::WeightInfo::currency_to_asset()
+                .saturating_add(swap_weight)
+                .saturating_add(T::OnSwap::weight()))
+        }
+
+        /// Check and perform a trade, or queue it if the exchange is in batch auction mode.
+        /// Return the actual weight of the trade.
+        fn do_asset_to_currency(
+            caller: AccountIdOf<T>,
+            asset_id: AssetIdOf<T, I>,
+            amount: TradeAmount<AssetBalanceOf<T, I>, BalanceOf<T, I>>,
+            recipient: AccountIdOf<T>,
+            referrer: Option<AccountIdOf<T>>,
+            preservation: Preservation,
+        ) -> Result<Weight, DispatchError> {
+            // -------------------------- Validation part --------------------------
+            Self::check_trade_amount(&amount)?;
+            let exchange = Self::get_exchange(&asset_id)?;
+            if Self::batch_auctions(&asset_id) {
+                let amount = SwapAmount::AssetToCurrency(amount);
+                Self::queue_batch_trade(asset_id, caller, recipient, amount, preservation)?;
+                return Ok(<T as Config<I>>::WeightInfo::batch_trade());
+            }
+
+            // --------------------------- Compute price ---------------------------
+            let exact_input = matches!(amount, TradeAmount::FixedInput { .. });
+            let (currency_amount, token_amount) =
+                Self::get_asset_to_currency_price(&exchange, amount)?;
+            Self::check_enough_tokens(&asset_id, &caller, &token_amount)?;
+            Self::check_operation_allowed(
+                ExchangeOperation::SellAsset,
+                &asset_id,
+                &caller,
+                &recipient,
+                currency_amount,
+                token_amount,
+            )?;
+
+            // --------------------------- Perform trade ---------------------------
+            let swap_weight = Self::swap_asset_for_currency(
+                exchange,
+                currency_amount,
+                token_amount,
+                exact_input,
+                caller,
+                recipient,
+                referrer,
+                preservation,
+            )?;
+            Ok(<T as Config<I>>::WeightInfo::asset_to_currency()
+                .saturating_add(swap_weight)
+                .saturating_add(T::OnSwap::weight()))
         }
 
-        /// Perform currency and asset transfers, update exchange balances, emit event
+        /// Perform currency and asset transfers, update exchange balances, emit event.
+        /// `exact_input` tells whether the input or the output amount of the trade is fixed,
+        /// and `preservation` applies to the buyer's payment.
+        /// Return the weight of the swap on a concentrated-liquidity exchange, which depends
+        /// on the number of crossed ticks (zero for other curves).
         #[transactional]
-        fn swap_currency_for_asset(
-            mut exchange: ExchangeOf<T>,
-            currency_amount: BalanceOf<T>,
-            token_amount: AssetBalanceOf<T>,
+        pub(crate) fn swap_currency_for_asset(
+            mut exchange: ExchangeOf<T, I>,
+            currency_amount: BalanceOf<T, I>,
+            token_amount: AssetBalanceOf<T, I>,
+            exact_input: bool,
             buyer: AccountIdOf<T>,
             recipient: AccountIdOf<T>,
-        ) -> DispatchResult {
+            referrer: Option<AccountIdOf<T>>,
+            preservation: Preservation,
+        ) -> Result<Weight, DispatchError> {
             // --------------------- Currency & token transfer ---------------------
             let asset_id = exchange.asset_id.clone();
             let pallet_account = T::pallet_account();
             if buyer != pallet_account {
-                <T as pallet::Config>::Currency::transfer(
-                    &buyer,
+                T::QuoteAsset::transfer(&buyer, &pallet_account, currency_amount, preservation)?;
+            }
+            if recipient != pallet_account {
+                T::Assets::transfer(
+                    asset_id.clone(),
                     &pallet_account,
-                    currency_amount,
-                    ExistenceRequirement::AllowDeath,
+                    &recipient,
+                    token_amount,
+                    Preservation::Expendable,
                 )?;
             }
-            T::Assets::transfer(
-                asset_id.clone(),
-                &pallet_account,
-                &recipient,
-                token_amount,
-                Preservation::Expendable,
-            )?;
 
             // -------------------------- Balances update --------------------------
-            exchange.currency_reserve.saturating_accrue(currency_amount);
-            exchange.token_reserve.saturating_reduce(token_amount);
-            <Exchanges<T>>::insert(asset_id.clone(), exchange);
+            let fee = Self::trade_fee_rate(&exchange);
+            let trade_fee = Self::trade_fee(&exchange);
+            let referral_share = Self::referral_share(&referrer);
+            let (referral_amount, swap_weight) = match exchange.curve {
+                Curve::Concentrated { .. } => {
+                    let (referral_amount, crossed_ticks) = Self::execute_concentrated_swap(
+                        &asset_id,
+                        Side::Currency,
+                        currency_amount,
+                        T::asset_to_currency(token_amount),
+                        exact_input,
+                        &trade_fee,
+                        referral_share,
+                    )?;
+                    let swap_weight =
+                        <T as Config<I>>::WeightInfo::swap_crossing_ticks(crossed_ticks);
+                    (referral_amount, swap_weight)
+                }
+                _ => (
+                    Self::referral_amount(&trade_fee, currency_amount, referral_share),
+                    Weight::zero(),
+                ),
+            };
+            Self::update_volatility(&exchange);
+            exchange
+                .currency_reserve
+                .saturating_accrue(currency_amount.saturating_sub(referral_amount));
+            exchange.token_reserve = exchange
+                .token_reserve
+                .checked_sub(&token_amount)
+                .ok_or(Error::<T, I>::NotEnoughLiquidity)?;
+            <Exchanges<T, I>>::insert(asset_id.clone(), exchange);
+
+            // ------------------------- Referral payment --------------------------
+            let referral = Self::pay_currency_referral(referrer, referral_amount)?;
+
+            // -------------------------- Notify handlers --------------------------
+            T::OnSwap::on_swap(&SwapDetails::CurrencyToAsset {
+                asset_id: asset_id.clone(),
+                buyer: buyer.clone(),
+                recipient: recipient.clone(),
+                currency_amount,
+                token_amount,
+                fee,
+                referral: referral.clone(),
+            });
 
             // ---------------------------- Emit event -----------------------------
             Self::deposit_event(Event::CurrencyTradedForAsset(
@@ -1113,42 +3691,95 @@ pub mod pallet {
                 recipient,
                 currency_amount,
                 token_amount,
+                fee,
+                referral,
             ));
-            Ok(())
+            Ok(swap_weight)
         }
 
-        /// Perform currency and asset transfers, update exchange balances, emit event
+        /// Perform currency and asset transfers, update exchange balances, emit event.
+        /// `exact_input` tells whether the input or the output amount of the trade is fixed,
+        /// and `preservation` applies to the buyer's payment.
+        /// Return the weight of the swap on a concentrated-liquidity exchange, which depends
+        /// on the number of crossed ticks (zero for other curves).
         #[transactional]
-        fn swap_asset_for_currency(
-            mut exchange: ExchangeOf<T>,
-            currency_amount: BalanceOf<T>,
-            token_amount: AssetBalanceOf<T>,
+        pub(crate) fn swap_asset_for_currency(
+            mut exchange: ExchangeOf<T, I>,
+            currency_amount: BalanceOf<T, I>,
+            token_amount: AssetBalanceOf<T, I>,
+            exact_input: bool,
             buyer: AccountIdOf<T>,
             recipient: AccountIdOf<T>,
-        ) -> DispatchResult {
+            referrer: Option<AccountIdOf<T>>,
+            preservation: Preservation,
+        ) -> Result<Weight, DispatchError> {
             // --------------------- Currency & token transfer ---------------------
             let asset_id = exchange.asset_id.clone();
             let pallet_account = T::pallet_account();
-            T::Assets::transfer(
-                asset_id.clone(),
-                &buyer,
-                &pallet_account,
-                token_amount,
-                Preservation::Expendable,
-            )?;
-            if recipient != pallet_account {
-                <T as pallet::Config>::Currency::transfer(
+            if buyer != pallet_account {
+                T::Assets::transfer(
+                    asset_id.clone(),
+                    &buyer,
                     &pallet_account,
-                    &recipient,
-                    currency_amount,
-                    ExistenceRequirement::AllowDeath,
+                    token_amount,
+                    preservation,
                 )?;
             }
+            if recipient != pallet_account {
+                Self::pay_currency(&recipient, currency_amount)?;
+            }
 
             // -------------------------- Balances update --------------------------
-            exchange.token_reserve.saturating_accrue(token_amount);
-            exchange.currency_reserve.saturating_reduce(currency_amount);
-            <Exchanges<T>>::insert(asset_id.clone(), exchange);
+            let fee = Self::trade_fee_rate(&exchange);
+            let trade_fee = Self::trade_fee(&exchange);
+            let referral_share = Self::referral_share(&referrer);
+            let (referral_amount, swap_weight) = match exchange.curve {
+                Curve::Concentrated { .. } => {
+                    let (referral_amount, crossed_ticks) = Self::execute_concentrated_swap(
+                        &asset_id,
+                        Side::Asset,
+                        T::asset_to_currency(token_amount),
+                        currency_amount,
+                        exact_input,
+                        &trade_fee,
+                        referral_share,
+                    )?;
+                    let swap_weight =
+                        <T as Config<I>>::WeightInfo::swap_crossing_ticks(crossed_ticks);
+                    (T::currency_to_asset(referral_amount), swap_weight)
+                }
+                _ => (
+                    T::currency_to_asset(Self::referral_amount(
+                        &trade_fee,
+                        T::asset_to_currency(token_amount),
+                        referral_share,
+                    )),
+                    Weight::zero(),
+                ),
+            };
+            Self::update_volatility(&exchange);
+            exchange
+                .token_reserve
+                .saturating_accrue(token_amount.saturating_sub(referral_amount));
+            exchange.currency_reserve = exchange
+                .currency_reserve
+                .checked_sub(&currency_amount)
+                .ok_or(Error::<T, I>::NotEnoughLiquidity)?;
+            <Exchanges<T, I>>::insert(asset_id.clone(), exchange);
+
+            // ------------------------- Referral payment --------------------------
+            let referral = Self::pay_token_referral(&asset_id, referrer, referral_amount)?;
+
+            // -------------------------- Notify handlers --------------------------
+            T::OnSwap::on_swap(&SwapDetails::AssetToCurrency {
+                asset_id: asset_id.clone(),
+                buyer: buyer.clone(),
+                recipient: recipient.clone(),
+                currency_amount,
+                token_amount,
+                fee,
+                referral: referral.clone(),
+            });
 
             // ---------------------------- Emit event -----------------------------
             Self::deposit_event(Event::AssetTradedForCurrency(
@@ -1157,36 +3788,49 @@ pub mod pallet {
                 recipient,
                 currency_amount,
                 token_amount,
+                fee,
+                referral,
             ));
-            Ok(())
+            Ok(swap_weight)
         }
 
-        /// Swap one asset to currency, then currency to another asset
+        /// Swap one asset to currency, then currency to another asset.
+        /// Return the weight of the swaps on concentrated-liquidity exchanges.
         #[transactional]
-        fn swap_asset_for_asset(
-            sold_asset_exchange: ExchangeOf<T>,
-            bought_asset_exchange: ExchangeOf<T>,
-            currency_amount: BalanceOf<T>,
-            sold_token_amount: AssetBalanceOf<T>,
-            bought_token_amount: AssetBalanceOf<T>,
+        pub(crate) fn swap_asset_for_asset(
+            sold_asset_exchange: ExchangeOf<T, I>,
+            bought_asset_exchange: ExchangeOf<T, I>,
+            currency_amount: BalanceOf<T, I>,
+            sold_token_amount: AssetBalanceOf<T, I>,
+            bought_token_amount: AssetBalanceOf<T, I>,
+            exact_input: bool,
             buyer: AccountIdOf<T>,
             recipient: AccountIdOf<T>,
-        ) -> DispatchResult {
+            referrer: Option<AccountIdOf<T>>,
+            preservation: Preservation,
+        ) -> Result<Weight, DispatchError> {
             let pallet_account: AccountIdOf<T> = T::pallet_account();
-            Self::swap_asset_for_currency(
+            let sold_swap_weight = Self::swap_asset_for_currency(
                 sold_asset_exchange,
                 currency_amount,
                 sold_token_amount,
+                exact_input,
                 buyer,
                 pallet_account.clone(),
+                referrer.clone(),
+                preservation,
             )?;
-            Self::swap_currency_for_asset(
+            let bought_swap_weight = Self::swap_currency_for_asset(
                 bought_asset_exchange,
                 currency_amount,
                 bought_token_amount,
+                exact_input,
                 pallet_account,
                 recipient,
-            )
+                referrer,
+                Preservation::Expendable,
+            )?;
+            Ok(sold_swap_weight.saturating_add(bought_swap_weight))
         }
     }
 }
@@ -0,0 +1,257 @@
+//! Limit orders, escrowed in the pallet account and matched against the exchanges in `on_idle`
+//! whenever the exchange price reaches their limit price.
+
+use crate::{
+    curve::Fee, handlers::OnSwap, permissions::ExchangeOperation, AssetBalanceOf, BalanceOf,
+    Config, ConfigHelper, Event, ExchangeOf, LimitOrderCursor, LimitOrderId, LimitOrderOf,
+    LimitOrders, OrderAmount, Pallet, TradeAmount, WeightInfo,
+};
+use frame_support::{
+    dispatch::DispatchResult,
+    sp_runtime::{
+        traits::{One, Saturating, Zero},
+        FixedPointNumber, FixedU128,
+    },
+    traits::{fungibles::Mutate, tokens::Preservation, Get},
+    weights::Weight,
+};
+use frame_system::pallet_prelude::BlockNumberFor;
+use sp_std::vec::Vec;
+
+impl<T: Config<I>, I: 'static> Pallet<T, I> {
+    /// Match limit orders against the exchanges, without exceeding `remaining_weight`.
+    /// Orders are visited round-robin, starting after the last order visited in the previous
+    /// block, so all orders get matched eventually even if only a few fit in a block.
+    /// Expired orders are refunded and removed.
+    pub(crate) fn match_limit_orders(now: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+        let order_weight = T::WeightInfo::match_limit_order().saturating_add(T::OnSwap::weight());
+        // Reading & updating the cursor
+        let mut consumed_weight = T::DbWeight::get().reads_writes(1, 1);
+        if consumed_weight
+            .saturating_add(order_weight)
+            .any_gt(remaining_weight)
+        {
+            return Weight::zero();
+        }
+
+        // ------------------------- Collect orders ---------------------------
+        let mut orders = match <LimitOrderCursor<T, I>>::get() {
+            Some(cursor) => {
+                <LimitOrders<T, I>>::iter_from(<LimitOrders<T, I>>::hashed_key_for(cursor))
+            }
+            None => <LimitOrders<T, I>>::iter(),
+        };
+        let mut batch = Vec::new();
+        let mut exhausted = false;
+        while consumed_weight
+            .saturating_add(order_weight)
+            .all_lte(remaining_weight)
+        {
+            consumed_weight.saturating_accrue(order_weight);
+            match orders.next() {
+                Some(order) => batch.push(order),
+                None => {
+                    exhausted = true;
+                    break;
+                }
+            }
+        }
+
+        // -------------------------- Match orders ----------------------------
+        let cursor = if exhausted {
+            None
+        } else {
+            batch.last().map(|(order_id, _)| *order_id)
+        };
+        for (order_id, order) in batch {
+            if order.expiry < now {
+                let owner = order.owner.clone();
+                if Self::close_limit_order(order_id, order).is_ok() {
+                    Self::deposit_event(Event::LimitOrderExpired(order_id, owner));
+                }
+            } else {
+                // Orders which can't be filled right now are left untouched
+                let _ = Self::fill_limit_order(order_id, order);
+            }
+        }
+        <LimitOrderCursor<T, I>>::set(cursor);
+        consumed_weight
+    }
+
+    /// Refund the unfilled amount of a limit order and remove it from storage
+    pub(crate) fn close_limit_order(
+        order_id: LimitOrderId,
+        order: LimitOrderOf<T, I>,
+    ) -> DispatchResult {
+        let pallet_account = T::pallet_account();
+        match order.amount {
+            OrderAmount::SellCurrency(currency_amount) => {
+                Self::pay_currency(&order.owner, currency_amount)?;
+            }
+            OrderAmount::SellAsset(token_amount) => {
+                T::Assets::transfer(
+                    order.asset_id,
+                    &pallet_account,
+                    &order.owner,
+                    token_amount,
+                    Preservation::Expendable,
+                )?;
+            }
+        }
+        <LimitOrders<T, I>>::remove(order_id);
+        Ok(())
+    }
+
+    /// Sell as much of the order's unfilled amount as possible without going below its limit price
+    fn fill_limit_order(order_id: LimitOrderId, mut order: LimitOrderOf<T, I>) -> DispatchResult {
+        let exchange = Self::get_exchange(&order.asset_id)?;
+        let (currency_amount, token_amount) = match Self::limit_order_fill(&exchange, &order) {
+            Some(fill) => fill,
+            None => return Ok(()),
+        };
+
+        // --------------------------- Perform trade ---------------------------
+        let pallet_account = T::pallet_account();
+        let owner = order.owner.clone();
+        let asset_id = order.asset_id.clone();
+        match &mut order.amount {
+            OrderAmount::SellCurrency(remaining) => {
+                Self::check_operation_allowed(
+                    ExchangeOperation::BuyAsset,
+                    &asset_id,
+                    &owner,
+                    &owner,
+                    currency_amount,
+                    token_amount,
+                )?;
+                Self::swap_currency_for_asset(
+                    exchange,
+                    currency_amount,
+                    token_amount,
+                    true,
+                    pallet_account,
+                    owner.clone(),
+                    None,
+                    Preservation::Expendable,
+                )?;
+                remaining.saturating_reduce(currency_amount);
+            }
+            OrderAmount::SellAsset(remaining) => {
+                Self::check_operation_allowed(
+                    ExchangeOperation::SellAsset,
+                    &asset_id,
+                    &owner,
+                    &owner,
+                    currency_amount,
+                    token_amount,
+                )?;
+                Self::swap_asset_for_currency(
+                    exchange,
+                    currency_amount,
+                    token_amount,
+                    true,
+                    pallet_account,
+                    owner.clone(),
+                    None,
+                    Preservation::Expendable,
+                )?;
+                remaining.saturating_reduce(token_amount);
+            }
+        }
+
+        // -------------------------- Update storage ---------------------------
+        let filled = match order.amount {
+            OrderAmount::SellCurrency(remaining) => remaining.is_zero(),
+            OrderAmount::SellAsset(remaining) => remaining.is_zero(),
+        };
+        if filled {
+            <LimitOrders<T, I>>::remove(order_id);
+        } else {
+            <LimitOrders<T, I>>::insert(order_id, order);
+        }
+
+        // ---------------------------- Emit event -----------------------------
+        Self::deposit_event(Event::LimitOrderFilled(
+            order_id,
+            owner,
+            asset_id,
+            currency_amount,
+            token_amount,
+        ));
+        Ok(())
+    }
+
+    /// Compute the (currency amount, token amount) of the largest trade filling the order
+    /// at or above its limit price, if there is one
+    fn limit_order_fill(
+        exchange: &ExchangeOf<T, I>,
+        order: &LimitOrderOf<T, I>,
+    ) -> Option<(BalanceOf<T, I>, AssetBalanceOf<T, I>)> {
+        let currency_reserve = T::currency_to_asset(exchange.currency_reserve);
+        let fee = Self::trade_fee(exchange);
+        let (currency_amount, token_amount) = match order.amount {
+            OrderAmount::SellCurrency(remaining) => {
+                let max_input = Self::max_limit_order_input(
+                    currency_reserve,
+                    exchange.token_reserve,
+                    order.limit_price,
+                    &fee,
+                );
+                let currency_amount = remaining.min(T::asset_to_currency(max_input));
+                let min_output = order
+                    .limit_price
+                    .saturating_mul_int(T::currency_to_asset(currency_amount));
+                let amount = TradeAmount::FixedInput {
+                    input_amount: currency_amount,
+                    min_output,
+                };
+                Self::get_currency_to_asset_price(exchange, amount).ok()?
+            }
+            OrderAmount::SellAsset(remaining) => {
+                let max_input = Self::max_limit_order_input(
+                    exchange.token_reserve,
+                    currency_reserve,
+                    order.limit_price,
+                    &fee,
+                );
+                let token_amount = remaining.min(max_input);
+                let min_output =
+                    T::asset_to_currency(order.limit_price.saturating_mul_int(token_amount));
+                let amount = TradeAmount::FixedInput {
+                    input_amount: token_amount,
+                    min_output,
+                };
+                Self::get_asset_to_currency_price(exchange, amount).ok()?
+            }
+        };
+        if currency_amount.is_zero() || token_amount.is_zero() {
+            return None;
+        }
+        Some((currency_amount, token_amount))
+    }
+
+    /// The maximum input amount which can be sold at an average price of at least `limit_price`.
+    /// Selling `x` yields `x * f * output_reserve / (input_reserve + x * f)`, where `f` is the
+    /// part of the input left after the provider fee, so the average price is at least
+    /// `limit_price` as long as `x <= output_reserve / limit_price - input_reserve / f`.
+    fn max_limit_order_input(
+        input_reserve: AssetBalanceOf<T, I>,
+        output_reserve: AssetBalanceOf<T, I>,
+        limit_price: FixedU128,
+        fee: &Fee<BalanceOf<T, I>>,
+    ) -> AssetBalanceOf<T, I> {
+        let max_reserve = match limit_price.reciprocal() {
+            Some(inverse_price) => inverse_price.saturating_mul_int(output_reserve),
+            None => return Zero::zero(),
+        };
+        let inverse_fee = FixedU128::saturating_from_rational(
+            T::currency_to_asset(fee.denominator),
+            T::currency_to_asset(fee.net_numerator),
+        );
+        // Rounded up, so that rounding never lets the average price fall below the limit
+        let min_reserve = inverse_fee
+            .saturating_mul_int(input_reserve)
+            .saturating_add(One::one());
+        max_reserve.saturating_sub(min_reserve)
+    }
+}
@@ -0,0 +1,116 @@
+//! Storage migrations of the DEX pallet.
+
+use crate::{AssetBalanceOf, AssetIdOf, BalanceOf, Config, ConfigHelper, Curve, Exchange, Pallet};
+use codec::{Decode, Encode};
+use frame_support::{
+    traits::{Get, GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+    weights::Weight,
+    Twox64Concat,
+};
+use sp_std::marker::PhantomData;
+
+pub mod v1 {
+    use super::*;
+
+    /// An exchange before pricing curves were introduced
+    #[derive(Decode)]
+    struct OldExchange<AssetId, Balance, AssetBalance> {
+        asset_id: AssetId,
+        currency_reserve: Balance,
+        token_reserve: AssetBalance,
+        liquidity_token_id: AssetId,
+    }
+
+    type OldExchangeOf<T, I = ()> =
+        OldExchange<AssetIdOf<T, I>, BalanceOf<T, I>, AssetBalanceOf<T, I>>;
+
+    /// An exchange in storage version 1, in which every exchange has a liquidity token
+    #[derive(Encode, Decode)]
+    pub(super) struct Exchange<AssetId, Balance, AssetBalance> {
+        pub asset_id: AssetId,
+        pub currency_reserve: Balance,
+        pub token_reserve: AssetBalance,
+        pub liquidity_token_id: AssetId,
+        pub curve: Curve,
+    }
+
+    pub(super) type ExchangeOf<T, I = ()> =
+        Exchange<AssetIdOf<T, I>, BalanceOf<T, I>, AssetBalanceOf<T, I>>;
+
+    #[frame_support::storage_alias]
+    pub(super) type Exchanges<T: Config<I>, I: 'static = ()> =
+        StorageMap<Pallet<T, I>, Twox64Concat, AssetIdOf<T, I>, ExchangeOf<T, I>>;
+
+    /// Migrate to storage version 1, in which every exchange has a pricing curve.
+    /// All existing exchanges use the constant product curve.
+    pub struct MigrateToV1<T, I = ()>(PhantomData<(T, I)>);
+
+    impl<T: Config<I>, I: 'static> OnRuntimeUpgrade for MigrateToV1<T, I> {
+        fn on_runtime_upgrade() -> Weight {
+            if Pallet::<T, I>::on_chain_storage_version() >= 1 {
+                return T::DbWeight::get().reads(1);
+            }
+            let mut translated = 0u64;
+            Exchanges::<T, I>::translate::<OldExchangeOf<T, I>, _>(|_, old| {
+                translated += 1;
+                Some(Exchange {
+                    asset_id: old.asset_id,
+                    currency_reserve: old.currency_reserve,
+                    token_reserve: old.token_reserve,
+                    liquidity_token_id: old.liquidity_token_id,
+                    curve: Curve::ConstantProduct,
+                })
+            });
+            StorageVersion::new(1).put::<Pallet<T, I>>();
+            T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+        }
+    }
+}
+
+pub mod v2 {
+    use super::*;
+
+    /// Migrate to storage version 2, in which the liquidity token of an exchange is optional,
+    /// as concentrated-liquidity exchanges don't have one. Must run after `MigrateToV1`.
+    pub struct MigrateToV2<T, I = ()>(PhantomData<(T, I)>);
+
+    impl<T: Config<I>, I: 'static> OnRuntimeUpgrade for MigrateToV2<T, I> {
+        fn on_runtime_upgrade() -> Weight {
+            if Pallet::<T, I>::on_chain_storage_version() != 1 {
+                return T::DbWeight::get().reads(1);
+            }
+            let mut translated = 0u64;
+            crate::Exchanges::<T, I>::translate::<v1::ExchangeOf<T, I>, _>(|_, old| {
+                translated += 1;
+                Some(Exchange {
+                    asset_id: old.asset_id,
+                    currency_reserve: old.currency_reserve,
+                    token_reserve: old.token_reserve,
+                    liquidity_token_id: Some(old.liquidity_token_id),
+                    curve: old.curve,
+                })
+            });
+            StorageVersion::new(2).put::<Pallet<T, I>>();
+            T::DbWeight::get().reads_writes(translated + 1, translated + 1)
+        }
+    }
+}
+
+pub mod v3 {
+    use super::*;
+
+    /// Migrate to storage version 3, in which the pallet account has a provider reference,
+    /// so that it's never reaped. Must run after `MigrateToV2`.
+    pub struct MigrateToV3<T, I = ()>(PhantomData<(T, I)>);
+
+    impl<T: Config<I>, I: 'static> OnRuntimeUpgrade for MigrateToV3<T, I> {
+        fn on_runtime_upgrade() -> Weight {
+            if Pallet::<T, I>::on_chain_storage_version() != 2 {
+                return T::DbWeight::get().reads(1);
+            }
+            frame_system::Pallet::<T>::inc_providers(&T::pallet_account());
+            StorageVersion::new(3).put::<Pallet<T, I>>();
+            T::DbWeight::get().reads_writes(2, 2)
+        }
+    }
+}
@@ -1,14 +1,18 @@
 use crate as dex;
+use codec::{Decode, Encode};
 use frame_support::traits::{
-    AsEnsureOriginWithArg, ConstU128, ConstU16, ConstU32, ConstU64, Everything,
+    fungible::ItemOf, AsEnsureOriginWithArg, ConstU128, ConstU16, ConstU32, ConstU64, Everything,
 };
-use frame_support::{parameter_types, PalletId};
+use frame_support::{instances::Instance1, parameter_types, weights::Weight, PalletId};
 use frame_system::{EnsureRoot, EnsureSigned};
-use sp_core::H256;
+use scale_info::TypeInfo;
+use sp_core::{sr25519, H256};
+use sp_keyring::Sr25519Keyring;
 use sp_runtime::{
-    traits::{BlakeTwo256, Identity, IdentityLookup},
-    BuildStorage,
+    traits::{BlakeTwo256, IdentifyAccount, Identity, IdentityLookup, Lazy, Verify},
+    BuildStorage, Permill, RuntimeDebug,
 };
+use std::time::Duration;
 
 type Block = frame_system::mocking::MockBlock<Test>;
 
@@ -19,6 +23,7 @@ frame_support::construct_runtime!(
         Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>},
         Assets: pallet_assets::{Pallet, Call, Storage, Config<T>, Event<T>},
         Dex: dex::{Pallet, Call, Storage, Event<T>},
+        StableDex: dex::<Instance1>::{Pallet, Call, Storage, Event<T>},
     }
 );
 
@@ -52,7 +57,7 @@ impl pallet_balances::Config for Test {
     type Balance = u128;
     type DustRemoval = ();
     type RuntimeEvent = RuntimeEvent;
-    type ExistentialDeposit = ConstU128<1>;
+    type ExistentialDeposit = ExistentialDeposit;
     type AccountStore = System;
     type WeightInfo = ();
     type MaxLocks = ();
@@ -85,14 +90,116 @@ impl pallet_assets::Config for Test {
     type CallbackHandle = ();
 }
 
+/// sr25519 public key of a test account, which is the first 8 bytes of the key
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct TestSigner(pub sr25519::Public);
+
+impl IdentifyAccount for TestSigner {
+    type AccountId = u64;
+
+    fn into_account(self) -> u64 {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&self.0.as_ref()[..8]);
+        u64::from_le_bytes(bytes)
+    }
+}
+
+/// sr25519 signature together with the signer's public key, since test accounts
+/// can't be converted back to public keys
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug, TypeInfo)]
+pub struct TestSignature(pub sr25519::Public, pub sr25519::Signature);
+
+impl Verify for TestSignature {
+    type Signer = TestSigner;
+
+    fn verify<L: Lazy<[u8]>>(&self, msg: L, signer: &u64) -> bool {
+        TestSigner(self.0).into_account() == *signer && self.1.verify(msg, &self.0)
+    }
+}
+
+#[cfg(feature = "runtime-benchmarks")]
+pub struct DexBenchmarkHelper;
+
+#[cfg(feature = "runtime-benchmarks")]
+impl dex::BenchmarkHelper<u64, TestSignature> for DexBenchmarkHelper {
+    fn signer() -> u64 {
+        intent_signer(Sr25519Keyring::Alice)
+    }
+
+    fn sign(message: &[u8]) -> TestSignature {
+        sign_intent(Sr25519Keyring::Alice, message)
+    }
+}
+
 parameter_types! {
     pub const DexPalletId: PalletId = PalletId(*b"dex_mock");
+    pub const StableDexPalletId: PalletId = PalletId(*b"dex_stbl");
+    /// Existential deposit of the native currency
+    pub static ExistentialDeposit: u128 = 1;
+    /// Current Unix time in milliseconds, `None` if unknown
+    pub static MockTime: Option<u64> = Some(NOW);
+    pub const ReferralFee: Permill = Permill::from_percent(20);
+    /// Account whose trades and liquidity operations are rejected by the trade filter
+    pub static BlockedAccount: Option<u64> = None;
+    /// Trades notified to the `OnSwap` handler
+    pub static SwapLog: Vec<dex::SwapDetails<u64, u32, u128, u128>> = Vec::new();
+    /// Liquidity changes notified to the `OnLiquidityChanged` handler
+    pub static LiquidityLog: Vec<dex::LiquidityChange<u64, u32, u128, u128>> = Vec::new();
+}
+
+pub(crate) const HANDLER_WEIGHT: Weight = Weight::from_parts(1_000_000, 0);
+
+pub struct TestHandler;
+
+impl dex::OnSwap<u64, u32, u128, u128> for TestHandler {
+    fn on_swap(swap: &dex::SwapDetails<u64, u32, u128, u128>) {
+        SwapLog::mutate(|log| log.push(swap.clone()));
+    }
+
+    fn weight() -> Weight {
+        HANDLER_WEIGHT
+    }
+}
+
+impl dex::OnLiquidityChanged<u64, u32, u128, u128> for TestHandler {
+    fn on_liquidity_changed(change: &dex::LiquidityChange<u64, u32, u128, u128>) {
+        LiquidityLog::mutate(|log| log.push(change.clone()));
+    }
+
+    fn weight() -> Weight {
+        HANDLER_WEIGHT
+    }
+}
+
+pub struct TestTradeFilter;
+
+impl dex::TradeFilter<u64, u32, u128, u128> for TestTradeFilter {
+    fn allow(
+        _operation: dex::ExchangeOperation,
+        _asset_id: &u32,
+        caller: &u64,
+        recipient: &u64,
+        _currency_amount: u128,
+        _token_amount: u128,
+    ) -> bool {
+        let blocked = BlockedAccount::get();
+        blocked != Some(*caller) && blocked != Some(*recipient)
+    }
+}
+
+pub struct TestTime;
+
+impl dex::MaybeUnixTime for TestTime {
+    fn now() -> Option<Duration> {
+        MockTime::get().map(Duration::from_millis)
+    }
 }
 
 impl dex::Config for Test {
     type PalletId = DexPalletId;
     type RuntimeEvent = RuntimeEvent;
-    type Currency = Balances;
+    type CurrencyBalance = u128;
+    type QuoteAsset = Balances;
     type AssetBalance = u128;
     type AssetToCurrencyBalance = Identity;
     type CurrencyToAssetBalance = Identity;
@@ -103,7 +210,70 @@ impl dex::Config for Test {
     // Provider fee is 0.3%
     type ProviderFeeNumerator = ConstU128<3>;
     type ProviderFeeDenominator = ConstU128<1000>;
+    type ReferralFee = ReferralFee;
     type MinDeposit = ConstU128<MIN_DEPOSIT>;
+    type MaxDcaExecutionsPerBlock = ConstU32<2>;
+    type MaxDcaRetries = ConstU32<2>;
+    type AdminOrigin = EnsureRoot<u64>;
+    type ExchangeAdmin = AsEnsureOriginWithArg<EnsureRoot<u64>>;
+    type TradeFilter = TestTradeFilter;
+    type OnSwap = (TestHandler, ());
+    type OnLiquidityChanged = TestHandler;
+    type MaxBatchTrades = ConstU32<3>;
+    type MaxBatchAuctions = ConstU32<2>;
+    type CommitDeposit = ConstU128<COMMIT_DEPOSIT>;
+    type RevealWindow = ConstU64<5>;
+    type MaxCommitmentsPerBlock = ConstU32<2>;
+    type ForfeitedDeposits = ();
+    type Time = TestTime;
+    type Signature = TestSignature;
+    type Signer = TestSigner;
+    type MaxAmplification = ConstU32<1_000_000>;
+    type MaxTicks = ConstU32<64>;
+    type MaxTicksCrossed = ConstU32<5>;
+    #[cfg(feature = "runtime-benchmarks")]
+    type BenchmarkHelper = DexBenchmarkHelper;
+}
+
+/// Second instance of the DEX, with its own pallet account and fee, paired with a stablecoin
+impl dex::Config<Instance1> for Test {
+    type PalletId = StableDexPalletId;
+    type RuntimeEvent = RuntimeEvent;
+    type CurrencyBalance = u128;
+    type QuoteAsset = ItemOf<Assets, ConstU32<STABLECOIN>, u64>;
+    type AssetBalance = u128;
+    type AssetToCurrencyBalance = Identity;
+    type CurrencyToAssetBalance = Identity;
+    type AssetId = u32;
+    type Assets = Assets;
+    type AssetRegistry = Assets;
+    type WeightInfo = ();
+    // Provider fee is 0.1%
+    type ProviderFeeNumerator = ConstU128<1>;
+    type ProviderFeeDenominator = ConstU128<1000>;
+    type ReferralFee = ReferralFee;
+    type MinDeposit = ConstU128<MIN_DEPOSIT>;
+    type MaxDcaExecutionsPerBlock = ConstU32<2>;
+    type MaxDcaRetries = ConstU32<2>;
+    type AdminOrigin = EnsureRoot<u64>;
+    type ExchangeAdmin = AsEnsureOriginWithArg<EnsureRoot<u64>>;
+    type TradeFilter = ();
+    type OnSwap = ();
+    type OnLiquidityChanged = ();
+    type MaxBatchTrades = ConstU32<3>;
+    type MaxBatchAuctions = ConstU32<2>;
+    type CommitDeposit = ConstU128<COMMIT_DEPOSIT>;
+    type RevealWindow = ConstU64<5>;
+    type MaxCommitmentsPerBlock = ConstU32<2>;
+    type ForfeitedDeposits = ();
+    type Time = TestTime;
+    type Signature = TestSignature;
+    type Signer = TestSigner;
+    type MaxAmplification = ConstU32<1_000_000>;
+    type MaxTicks = ConstU32<64>;
+    type MaxTicksCrossed = ConstU32<5>;
+    #[cfg(feature = "runtime-benchmarks")]
+    type BenchmarkHelper = DexBenchmarkHelper;
 }
 
 pub(crate) const ACCOUNT_A: u64 = 0;
@@ -112,10 +282,16 @@ pub(crate) const ACCOUNT_C: u64 = 2;
 pub(crate) const INIT_BALANCE: u128 = 1_000_000_000_000_000;
 pub(crate) const INIT_LIQUIDITY: u128 = 1_000_000_000_000;
 pub(crate) const MIN_DEPOSIT: u128 = 1;
+pub(crate) const PROVIDER_FEE: Permill = Permill::from_parts(3_000);
+pub(crate) const STABLE_PROVIDER_FEE: Permill = Permill::from_parts(1_000);
+pub(crate) const COMMIT_DEPOSIT: u128 = 100;
+pub(crate) const NOW: u64 = 1_700_000_000_000;
 pub(crate) const ASSET_A: u32 = 100;
 pub(crate) const ASSET_B: u32 = 101;
+pub(crate) const STABLECOIN: u32 = 102;
 pub(crate) const LIQ_TOKEN_A: u32 = 200;
 pub(crate) const LIQ_TOKEN_B: u32 = 201;
+pub(crate) const STABLE_LIQ_TOKEN_A: u32 = 300;
 
 pub(crate) fn new_test_ext() -> sp_io::TestExternalities {
     let mut storage = frame_system::GenesisConfig::<Test>::default()
@@ -133,7 +309,11 @@ pub(crate) fn new_test_ext() -> sp_io::TestExternalities {
     .unwrap();
 
     pallet_assets::GenesisConfig::<Test> {
-        assets: vec![(ASSET_A, ACCOUNT_A, true, 1), (ASSET_B, ACCOUNT_B, true, 1)],
+        assets: vec![
+            (ASSET_A, ACCOUNT_A, true, 1),
+            (ASSET_B, ACCOUNT_B, true, 1),
+            (STABLECOIN, ACCOUNT_C, true, 1),
+        ],
         metadata: vec![],
         accounts: vec![
             (ASSET_A, ACCOUNT_A, INIT_BALANCE),
@@ -142,6 +322,9 @@ pub(crate) fn new_test_ext() -> sp_io::TestExternalities {
             (ASSET_B, ACCOUNT_A, INIT_BALANCE),
             (ASSET_B, ACCOUNT_B, INIT_BALANCE),
             (ASSET_B, ACCOUNT_C, INIT_BALANCE),
+            (STABLECOIN, ACCOUNT_A, INIT_BALANCE),
+            (STABLECOIN, ACCOUNT_B, INIT_BALANCE),
+            (STABLECOIN, ACCOUNT_C, INIT_BALANCE),
         ],
     }
     .assimilate_storage(&mut storage)
@@ -153,11 +336,23 @@ pub(crate) fn new_test_ext() -> sp_io::TestExternalities {
     .assimilate_storage(&mut storage)
     .unwrap();
 
+    dex::GenesisConfig::<Test, Instance1>::default()
+        .assimilate_storage(&mut storage)
+        .unwrap();
+
     let mut test_ext: sp_io::TestExternalities = storage.into();
     test_ext.execute_with(|| System::set_block_number(1));
     test_ext
 }
 
+pub(crate) fn intent_signer(keyring: Sr25519Keyring) -> u64 {
+    TestSigner(keyring.public()).into_account()
+}
+
+pub(crate) fn sign_intent(keyring: Sr25519Keyring, message: &[u8]) -> TestSignature {
+    TestSignature(keyring.public(), keyring.sign(message))
+}
+
 pub(crate) fn last_event() -> dex::Event<Test> {
     last_n_events(1).pop().unwrap()
 }
@@ -173,3 +368,50 @@ pub(crate) fn last_n_events(n: usize) -> Vec<dex::Event<Test>> {
         .collect();
     events.split_off(events.len() - n)
 }
+
+pub(crate) fn last_stable_event() -> dex::Event<Test, Instance1> {
+    System::events()
+        .into_iter()
+        .filter_map(|r| match r.event {
+            RuntimeEvent::StableDex(inner) => Some(inner),
+            _ => None,
+        })
+        .last()
+        .unwrap()
+}
+
+/// Call a DEX RPC function on the instance selected by its `PalletId` (`None` selecting `Dex`),
+/// as the runtime API implementation of a runtime with several instances does.
+macro_rules! with_dex_instance {
+    ($instance:expr, |$dex:ident| $body:expr) => {{
+        let instance: Option<frame_support::PalletId> = $instance;
+        match instance {
+            None => {
+                type $dex = Dex;
+                $body
+            }
+            Some(id) if id == DexPalletId::get() => {
+                type $dex = Dex;
+                $body
+            }
+            Some(id) if id == StableDexPalletId::get() => {
+                type $dex = StableDex;
+                $body
+            }
+            Some(_) => Err($crate::rpc::RpcError::InstanceNotFound),
+        }
+    }};
+}
+pub(crate) use with_dex_instance;
+
+/// Simulate a call of any DEX instance, as the `DexSimulationApi` implementation does.
+pub(crate) fn simulate_runtime_call(
+    caller: u64,
+    call: RuntimeCall,
+) -> dex::rpc::RpcResult<dex::rpc::SimulationOf<Test>> {
+    match call {
+        RuntimeCall::Dex(call) => Ok(Dex::simulate(caller, call)),
+        RuntimeCall::StableDex(call) => Ok(StableDex::simulate(caller, call)),
+        _ => Err(dex::rpc::RpcError::InstanceNotFound),
+    }
+}
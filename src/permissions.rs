@@ -0,0 +1,89 @@
+//! Restrictions on who can use an exchange, e.g. for regulated assets. The runtime's `TradeFilter`
+//! is asked before every trade and liquidity operation, and on permissioned exchanges only the
+//! providers approved by the `ExchangeAdmin` of the exchange can add liquidity.
+
+use crate::{
+    AccountIdOf, ApprovedProviders, AssetBalanceOf, AssetIdOf, BalanceOf, Config, Error, Pallet,
+    PermissionedExchanges,
+};
+use frame_support::{dispatch::DispatchResult, ensure, sp_runtime::RuntimeDebug};
+
+/// Operation with an exchange, checked by the `TradeFilter`.
+#[derive(Clone, Copy, Eq, PartialEq, RuntimeDebug)]
+pub enum ExchangeOperation {
+    /// Sell currency for the asset
+    BuyAsset,
+    /// Sell the asset for currency
+    SellAsset,
+    AddLiquidity,
+    RemoveLiquidity,
+}
+
+/// Filter of the trades and liquidity operations with exchanges.
+pub trait TradeFilter<AccountId, AssetId, Balance, AssetBalance> {
+    /// Whether `caller` may perform `operation` of `currency_amount` and `token_amount` with the
+    /// exchange of `asset_id`, its output going to `recipient`. For trades whose output isn't
+    /// known yet (e.g. batch auction trades), the amounts are the trade's limits.
+    fn allow(
+        operation: ExchangeOperation,
+        asset_id: &AssetId,
+        caller: &AccountId,
+        recipient: &AccountId,
+        currency_amount: Balance,
+        token_amount: AssetBalance,
+    ) -> bool;
+}
+
+impl<AccountId, AssetId, Balance, AssetBalance>
+    TradeFilter<AccountId, AssetId, Balance, AssetBalance> for ()
+{
+    fn allow(
+        _operation: ExchangeOperation,
+        _asset_id: &AssetId,
+        _caller: &AccountId,
+        _recipient: &AccountId,
+        _currency_amount: Balance,
+        _token_amount: AssetBalance,
+    ) -> bool {
+        true
+    }
+}
+
+impl<T: Config<I>, I: 'static> Pallet<T, I> {
+    /// Check that the `TradeFilter` allows `caller` to perform `operation` with an exchange
+    pub(crate) fn check_operation_allowed(
+        operation: ExchangeOperation,
+        asset_id: &AssetIdOf<T, I>,
+        caller: &AccountIdOf<T>,
+        recipient: &AccountIdOf<T>,
+        currency_amount: BalanceOf<T, I>,
+        token_amount: AssetBalanceOf<T, I>,
+    ) -> DispatchResult {
+        ensure!(
+            T::TradeFilter::allow(
+                operation,
+                asset_id,
+                caller,
+                recipient,
+                currency_amount,
+                token_amount
+            ),
+            Error::<T, I>::OperationNotAllowed
+        );
+        Ok(())
+    }
+
+    /// Check that `provider` can add liquidity to an exchange, i.e. that the exchange isn't
+    /// permissioned or that the provider is approved
+    pub(crate) fn check_provider_approved(
+        asset_id: &AssetIdOf<T, I>,
+        provider: &AccountIdOf<T>,
+    ) -> DispatchResult {
+        ensure!(
+            !<PermissionedExchanges<T, I>>::get(asset_id)
+                || <ApprovedProviders<T, I>>::get(asset_id, provider),
+            Error::<T, I>::ProviderNotApproved
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+//! Referral fees. Trades can name a referrer (e.g. the wallet or frontend which routed them),
+//! which is paid `ReferralFee` of the provider fee, in the input currency or asset of the trade.
+//! The referral is withheld from the input added to the reserves, so it is paid by the liquidity
+//! providers out of their fee, and the price of the trade doesn't change.
+
+use crate::{
+    curve::Fee, AccountIdOf, AssetBalanceOf, AssetIdOf, BalanceOf, Config, ConfigHelper, Pallet,
+};
+use frame_support::{
+    sp_runtime::{
+        traits::{Saturating, Zero},
+        DispatchError, FixedPointNumber, FixedU128, Permill,
+    },
+    traits::{fungibles::Mutate, tokens::Preservation, Get},
+};
+
+impl<T: Config<I>, I: 'static> Pallet<T, I> {
+    /// Part of the provider fee paid to the referrer of a trade, zero if it has none
+    pub(crate) fn referral_share(referrer: &Option<AccountIdOf<T>>) -> Permill {
+        match referrer {
+            Some(_) => T::ReferralFee::get(),
+            None => Permill::zero(),
+        }
+    }
+
+    /// Part `referral_share` of the provider fee paid on `input_amount`, rounded down
+    pub(crate) fn referral_amount(
+        fee: &Fee<BalanceOf<T, I>>,
+        input_amount: BalanceOf<T, I>,
+        referral_share: Permill,
+    ) -> BalanceOf<T, I> {
+        let fee_amount = FixedU128::saturating_from_rational(
+            T::currency_to_asset(fee.denominator.saturating_sub(fee.net_numerator)),
+            T::currency_to_asset(fee.denominator),
+        )
+        .saturating_mul_int(T::currency_to_asset(input_amount));
+        T::asset_to_currency(referral_share.mul_floor(fee_amount))
+    }
+
+    /// Pay the referral of a trade in currency from the pallet account,
+    /// returning the referrer and the amount paid
+    pub(crate) fn pay_currency_referral(
+        referrer: Option<AccountIdOf<T>>,
+        amount: BalanceOf<T, I>,
+    ) -> Result<Option<(AccountIdOf<T>, BalanceOf<T, I>)>, DispatchError> {
+        match referrer {
+            Some(referrer) => {
+                if !amount.is_zero() {
+                    Self::pay_currency(&referrer, amount)?;
+                }
+                Ok(Some((referrer, amount)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Pay the referral of a trade in tokens from the pallet account,
+    /// returning the referrer and the amount paid
+    pub(crate) fn pay_token_referral(
+        asset_id: &AssetIdOf<T, I>,
+        referrer: Option<AccountIdOf<T>>,
+        amount: AssetBalanceOf<T, I>,
+    ) -> Result<Option<(AccountIdOf<T>, AssetBalanceOf<T, I>)>, DispatchError> {
+        match referrer {
+            Some(referrer) => {
+                if !amount.is_zero() {
+                    T::Assets::transfer(
+                        asset_id.clone(),
+                        &T::pallet_account(),
+                        &referrer,
+                        amount,
+                        Preservation::Expendable,
+                    )?;
+                }
+                Ok(Some((referrer, amount)))
+            }
+            None => Ok(None),
+        }
+    }
+}
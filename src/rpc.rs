@@ -1,39 +1,342 @@
-use crate::{AssetBalanceOf, AssetIdOf, BalanceOf, Config, ConfigHelper, Error, Pallet};
+use crate::curve::Side;
+use crate::{
+    AccountIdOf, AssetBalanceOf, AssetIdOf, BalanceOf, Call, Config, ConfigHelper, DcaSchedules,
+    DcaSwap, DcaSwapOf, Error, Exchange, Exchanges, LimitOrders, Pallet,
+};
 use codec::{Decode, Encode};
+use frame_support::{
+    dispatch::DispatchResult,
+    sp_runtime::{traits::Zero, DispatchError, FixedU128},
+    storage::{with_transaction, TransactionOutcome},
+    traits::{fungible::Inspect as _, fungibles::Inspect, UnfilteredDispatchable},
+};
 use scale_info::prelude::format;
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
 use sp_std::fmt::Debug;
-use sp_std::vec::Vec;
+use sp_std::{vec, vec::Vec};
 
+/// Maximum number of quotes which can be requested in a single batch.
+pub const MAX_BATCH_QUOTES: u32 = 128;
+
+/// Errors returned by the RPC runtime API. Mirrors the pallet's `Error`, see the runtime API crate
+/// for the JSON-RPC error codes and messages.
 #[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
 pub enum RpcError {
     ExchangeNotFound,
     NotEnoughLiquidity,
     Overflow,
     Unexpected(Vec<u8>),
+    AssetNotFound,
+    ExchangeAlreadyExists,
+    TokenIdTaken,
+    BalanceTooLow,
+    NotEnoughTokens,
+    ProviderLiquidityTooLow,
+    TradeAmountIsZero,
+    TokenAmountIsZero,
+    MaxTokensIsZero,
+    CurrencyAmountIsZero,
+    CurrencyAmountTooHigh,
+    CurrencyAmountTooLow,
+    MinLiquidityIsZero,
+    MaxTokensTooLow,
+    MinLiquidityTooHigh,
+    LiquidityAmountIsZero,
+    MinCurrencyIsZero,
+    MinTokensIsZero,
+    MinCurrencyTooHigh,
+    MinTokensTooHigh,
+    MaxCurrencyTooLow,
+    MinBoughtTokensTooHigh,
+    MaxSoldTokensTooLow,
+    Underflow,
+    DeadlinePassed,
+    /// No route with a non-zero output found between the given tokens.
+    RouteNotFound,
+    LimitPriceIsZero,
+    LimitOrderNotFound,
+    NotLimitOrderOwner,
+    DcaIntervalIsZero,
+    DcaExecutionsIsZero,
+    DcaQueueFull,
+    DcaScheduleNotFound,
+    NotDcaScheduleOwner,
+    BatchFull,
+    BatchAuctionEnabled,
+    CommitmentAlreadyExists,
+    CommitmentNotFound,
+    TooManyCommitments,
+    InvalidSignature,
+    InvalidIntentNonce,
+    TipTooHigh,
+    TimestampDeadlineUnsupported,
+    MaxCurrencyIsZero,
+    InvalidAmplification,
+    NotStableSwapExchange,
+    InvalidRampEnd,
+    UnsupportedCurve,
+    InvalidWeight,
+    InvalidWeightSchedule,
+    InvalidTickSpacing,
+    InvalidPrice,
+    InvalidTickRange,
+    TooManyTicks,
+    TooManyTicksCrossed,
+    PositionNotFound,
+    PositionLiquidityTooLow,
+    PositionLiquidityIsZero,
+    InvalidFeeRange,
+    OperationNotAllowed,
+    ProviderNotApproved,
+    CurrencyBelowMinimum,
+    /// More than `MAX_BATCH_QUOTES` quote requests passed to `batch_quote`.
+    BatchTooLarge,
+    /// Amount passed to `find_best_route` isn't an amount of the sold token.
+    AmountTokenMismatch,
+    TipTokenMismatch,
+    /// The runtime API was asked for a DEX instance the runtime doesn't have.
+    InstanceNotFound,
 }
 
 pub type RpcResult<T> = Result<T, RpcError>;
 
-impl<T: Config> From<Error<T>> for RpcError {
-    fn from(err: Error<T>) -> Self {
+/// Amount of a quoted trade: either a fixed input (output amount is quoted)
+/// or a fixed output (input amount is quoted).
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub enum QuoteAmount<InputBalance, OutputBalance> {
+    FixedInput(InputBalance),
+    FixedOutput(OutputBalance),
+}
+
+/// A single entry of a batch quote request.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub enum QuoteRequest<AssetId, Balance, AssetBalance> {
+    #[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+    CurrencyToAsset {
+        asset_id: AssetId,
+        amount: QuoteAmount<Balance, AssetBalance>,
+    },
+    #[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+    AssetToCurrency {
+        asset_id: AssetId,
+        amount: QuoteAmount<AssetBalance, Balance>,
+    },
+    #[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+    AssetToAsset {
+        sold_asset_id: AssetId,
+        bought_asset_id: AssetId,
+        amount: QuoteAmount<AssetBalance, AssetBalance>,
+    },
+}
+
+/// The quoted amount – currency or asset, depending on the trade direction.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub enum Quote<Balance, AssetBalance> {
+    Currency(Balance),
+    Asset(AssetBalance),
+}
+
+/// Snapshot of the current state of an exchange.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct ExchangeState<Balance, AssetBalance> {
+    pub currency_reserve: Balance,
+    pub token_reserve: AssetBalance,
+    /// Total supply of the exchange's liquidity token.
+    pub total_liquidity: AssetBalance,
+    /// Price of a single token in currency, not including the provider fee.
+    pub spot_price: FixedU128,
+}
+
+/// A token traded on the DEX: the currency or one of the assets.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub enum Token<AssetId> {
+    Currency,
+    Asset(AssetId),
+}
+
+/// An amount of the currency or of an asset.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub enum TokenAmount<Balance, AssetBalance> {
+    Currency(Balance),
+    Asset(AssetBalance),
+}
+
+impl<Balance: Zero, AssetBalance: Zero> TokenAmount<Balance, AssetBalance> {
+    fn is_zero(&self) -> bool {
+        match self {
+            Self::Currency(amount) => amount.is_zero(),
+            Self::Asset(amount) => amount.is_zero(),
+        }
+    }
+}
+
+/// A route for a fixed-input trade, found by `find_best_route`.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct Route<AssetId, Balance, AssetBalance> {
+    /// Tokens along the route, from the sold token to the bought token. Every consecutive pair
+    /// of tokens is a single trade.
+    pub path: Vec<Token<AssetId>>,
+    /// Expected amount of the bought token.
+    pub amount_out: TokenAmount<Balance, AssetBalance>,
+}
+
+/// Amounts deposited to an exchange by adding liquidity, and the liquidity tokens minted for them.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct LiquidityDeposit<Balance, AssetBalance> {
+    pub currency_amount: Balance,
+    pub token_amount: AssetBalance,
+    pub liquidity_minted: AssetBalance,
+}
+
+/// Balance of an account before and after a simulated call.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub enum BalanceChange<AccountId, AssetId, Balance, AssetBalance> {
+    Currency {
+        account: AccountId,
+        before: Balance,
+        after: Balance,
+    },
+    /// Balance of a traded asset or a liquidity token.
+    Asset {
+        account: AccountId,
+        asset_id: AssetId,
+        before: AssetBalance,
+        after: AssetBalance,
+    },
+}
+
+/// Outcome of a simulated (dry-run) DEX call. All the changes are rolled back after the simulation.
+#[derive(Debug, Clone, PartialEq, Eq, Encode, Decode, scale_info::TypeInfo)]
+pub struct Simulation<AccountId, AssetId, Balance, AssetBalance, Event> {
+    /// Result of the call.
+    pub result: DispatchResult,
+    /// Events which would be emitted by the call (including events of other pallets, e.g. transfers).
+    pub events: Vec<Event>,
+    /// Exchanges affected by the call, as they would be after the call.
+    pub exchanges: Vec<Exchange<AssetId, Balance, AssetBalance>>,
+    /// Balances changed by the call. Covers the caller, the recipient and the pallet account.
+    pub balance_changes: Vec<BalanceChange<AccountId, AssetId, Balance, AssetBalance>>,
+}
+
+pub type ExchangeStateOf<T, I = ()> = ExchangeState<BalanceOf<T, I>, AssetBalanceOf<T, I>>;
+pub type QuoteRequestOf<T, I = ()> =
+    QuoteRequest<AssetIdOf<T, I>, BalanceOf<T, I>, AssetBalanceOf<T, I>>;
+pub type QuoteOf<T, I = ()> = Quote<BalanceOf<T, I>, AssetBalanceOf<T, I>>;
+pub type TokenOf<T, I = ()> = Token<AssetIdOf<T, I>>;
+pub type TokenAmountOf<T, I = ()> = TokenAmount<BalanceOf<T, I>, AssetBalanceOf<T, I>>;
+pub type RouteOf<T, I = ()> = Route<AssetIdOf<T, I>, BalanceOf<T, I>, AssetBalanceOf<T, I>>;
+pub type LiquidityDepositOf<T, I = ()> = LiquidityDeposit<BalanceOf<T, I>, AssetBalanceOf<T, I>>;
+pub type BalanceChangeOf<T, I = ()> =
+    BalanceChange<AccountIdOf<T>, AssetIdOf<T, I>, BalanceOf<T, I>, AssetBalanceOf<T, I>>;
+pub type SimulationOf<T, I = ()> = Simulation<
+    AccountIdOf<T>,
+    AssetIdOf<T, I>,
+    BalanceOf<T, I>,
+    AssetBalanceOf<T, I>,
+    <T as frame_system::Config>::RuntimeEvent,
+>;
+
+impl<T: Config<I>, I: 'static> From<Error<T, I>> for RpcError {
+    fn from(err: Error<T, I>) -> Self {
         match err {
             Error::ExchangeNotFound => Self::ExchangeNotFound,
             Error::NotEnoughLiquidity => Self::NotEnoughLiquidity,
             Error::Overflow => Self::Overflow,
+            Error::AssetNotFound => Self::AssetNotFound,
+            Error::ExchangeAlreadyExists => Self::ExchangeAlreadyExists,
+            Error::TokenIdTaken => Self::TokenIdTaken,
+            Error::BalanceTooLow => Self::BalanceTooLow,
+            Error::NotEnoughTokens => Self::NotEnoughTokens,
+            Error::ProviderLiquidityTooLow => Self::ProviderLiquidityTooLow,
+            Error::TradeAmountIsZero => Self::TradeAmountIsZero,
+            Error::TokenAmountIsZero => Self::TokenAmountIsZero,
+            Error::MaxTokensIsZero => Self::MaxTokensIsZero,
+            Error::CurrencyAmountIsZero => Self::CurrencyAmountIsZero,
+            Error::CurrencyAmountTooHigh => Self::CurrencyAmountTooHigh,
+            Error::CurrencyAmountTooLow => Self::CurrencyAmountTooLow,
+            Error::MinLiquidityIsZero => Self::MinLiquidityIsZero,
+            Error::MaxTokensTooLow => Self::MaxTokensTooLow,
+            Error::MinLiquidityTooHigh => Self::MinLiquidityTooHigh,
+            Error::LiquidityAmountIsZero => Self::LiquidityAmountIsZero,
+            Error::MinCurrencyIsZero => Self::MinCurrencyIsZero,
+            Error::MinTokensIsZero => Self::MinTokensIsZero,
+            Error::MinCurrencyTooHigh => Self::MinCurrencyTooHigh,
+            Error::MinTokensTooHigh => Self::MinTokensTooHigh,
+            Error::MaxCurrencyTooLow => Self::MaxCurrencyTooLow,
+            Error::MinBoughtTokensTooHigh => Self::MinBoughtTokensTooHigh,
+            Error::MaxSoldTokensTooLow => Self::MaxSoldTokensTooLow,
+            Error::Underflow => Self::Underflow,
+            Error::DeadlinePassed => Self::DeadlinePassed,
+            Error::LimitPriceIsZero => Self::LimitPriceIsZero,
+            Error::LimitOrderNotFound => Self::LimitOrderNotFound,
+            Error::NotLimitOrderOwner => Self::NotLimitOrderOwner,
+            Error::DcaIntervalIsZero => Self::DcaIntervalIsZero,
+            Error::DcaExecutionsIsZero => Self::DcaExecutionsIsZero,
+            Error::DcaQueueFull => Self::DcaQueueFull,
+            Error::DcaScheduleNotFound => Self::DcaScheduleNotFound,
+            Error::NotDcaScheduleOwner => Self::NotDcaScheduleOwner,
+            Error::BatchFull => Self::BatchFull,
+            Error::BatchAuctionEnabled => Self::BatchAuctionEnabled,
+            Error::CommitmentAlreadyExists => Self::CommitmentAlreadyExists,
+            Error::CommitmentNotFound => Self::CommitmentNotFound,
+            Error::TooManyCommitments => Self::TooManyCommitments,
+            Error::InvalidSignature => Self::InvalidSignature,
+            Error::InvalidIntentNonce => Self::InvalidIntentNonce,
+            Error::TipTooHigh => Self::TipTooHigh,
+            Error::TimestampDeadlineUnsupported => Self::TimestampDeadlineUnsupported,
+            Error::MaxCurrencyIsZero => Self::MaxCurrencyIsZero,
+            Error::InvalidAmplification => Self::InvalidAmplification,
+            Error::NotStableSwapExchange => Self::NotStableSwapExchange,
+            Error::InvalidRampEnd => Self::InvalidRampEnd,
+            Error::UnsupportedCurve => Self::UnsupportedCurve,
+            Error::InvalidWeight => Self::InvalidWeight,
+            Error::InvalidWeightSchedule => Self::InvalidWeightSchedule,
+            Error::InvalidTickSpacing => Self::InvalidTickSpacing,
+            Error::InvalidPrice => Self::InvalidPrice,
+            Error::InvalidTickRange => Self::InvalidTickRange,
+            Error::TooManyTicks => Self::TooManyTicks,
+            Error::TooManyTicksCrossed => Self::TooManyTicksCrossed,
+            Error::PositionNotFound => Self::PositionNotFound,
+            Error::PositionLiquidityTooLow => Self::PositionLiquidityTooLow,
+            Error::PositionLiquidityIsZero => Self::PositionLiquidityIsZero,
+            Error::InvalidFeeRange => Self::InvalidFeeRange,
+            Error::OperationNotAllowed => Self::OperationNotAllowed,
+            Error::ProviderNotApproved => Self::ProviderNotApproved,
+            Error::CurrencyBelowMinimum => Self::CurrencyBelowMinimum,
+            Error::TipTokenMismatch => Self::TipTokenMismatch,
             err => Self::Unexpected(format!("{err:?}").into_bytes()),
         }
     }
 }
 
-impl<T: Config> Pallet<T> {
+impl<T: Config<I>, I: 'static> Pallet<T, I> {
     /// Get the output amount for a fixed-input currency-to-asset trade,
     /// i.e. 'How much asset would I get if I paid this much currency'?
     pub fn get_currency_to_asset_output_amount(
-        asset_id: AssetIdOf<T>,
-        currency_amount: BalanceOf<T>,
-    ) -> RpcResult<AssetBalanceOf<T>> {
+        asset_id: AssetIdOf<T, I>,
+        currency_amount: BalanceOf<T, I>,
+    ) -> RpcResult<AssetBalanceOf<T, I>> {
         let exchange = Self::get_exchange(&asset_id)?;
         let output_amount = Self::get_output_amount(
+            &exchange,
+            Side::Currency,
             &currency_amount,
             &exchange.currency_reserve,
             &T::asset_to_currency(exchange.token_reserve),
@@ -44,11 +347,13 @@ impl<T: Config> Pallet<T> {
     /// Get the input amount for a fixed-output currency-to-asset trade,
     /// i.e. 'How much currency do I have to pay to get this much asset'?
     pub fn get_currency_to_asset_input_amount(
-        asset_id: AssetIdOf<T>,
-        token_amount: AssetBalanceOf<T>,
-    ) -> RpcResult<BalanceOf<T>> {
+        asset_id: AssetIdOf<T, I>,
+        token_amount: AssetBalanceOf<T, I>,
+    ) -> RpcResult<BalanceOf<T, I>> {
         let exchange = Self::get_exchange(&asset_id)?;
         let input_amount = Self::get_input_amount(
+            &exchange,
+            Side::Currency,
             &T::asset_to_currency(token_amount),
             &exchange.currency_reserve,
             &T::asset_to_currency(exchange.token_reserve),
@@ -59,11 +364,13 @@ impl<T: Config> Pallet<T> {
     /// Get the output amount for a fixed-input asset-to-currency trade,
     /// i.e. 'How much currency would I get if I paid this much asset'?
     pub fn get_asset_to_currency_output_amount(
-        asset_id: AssetIdOf<T>,
-        token_amount: AssetBalanceOf<T>,
-    ) -> RpcResult<BalanceOf<T>> {
+        asset_id: AssetIdOf<T, I>,
+        token_amount: AssetBalanceOf<T, I>,
+    ) -> RpcResult<BalanceOf<T, I>> {
         let exchange = Self::get_exchange(&asset_id)?;
         let output_amount = Self::get_output_amount(
+            &exchange,
+            Side::Asset,
             &T::asset_to_currency(token_amount),
             &T::asset_to_currency(exchange.token_reserve),
             &exchange.currency_reserve,
@@ -74,25 +381,463 @@ impl<T: Config> Pallet<T> {
     /// Get the input amount for a fixed-output currency-to-asset trade,
     /// i.e. 'How much asset do I have to pay to get this much currency'?
     pub fn get_asset_to_currency_input_amount(
-        asset_id: AssetIdOf<T>,
-        currency_amount: BalanceOf<T>,
-    ) -> RpcResult<AssetBalanceOf<T>> {
+        asset_id: AssetIdOf<T, I>,
+        currency_amount: BalanceOf<T, I>,
+    ) -> RpcResult<AssetBalanceOf<T, I>> {
         let exchange = Self::get_exchange(&asset_id)?;
         let input_amount = Self::get_input_amount(
+            &exchange,
+            Side::Asset,
             &currency_amount,
             &T::asset_to_currency(exchange.token_reserve),
             &exchange.currency_reserve,
         )?;
         Ok(T::currency_to_asset(input_amount))
     }
+
+    /// Get the output amount for a fixed-input asset-to-asset trade,
+    /// i.e. 'How much of the bought asset would I get if I paid this much of the sold asset'?
+    pub fn get_asset_to_asset_output_amount(
+        sold_asset_id: AssetIdOf<T, I>,
+        bought_asset_id: AssetIdOf<T, I>,
+        token_amount: AssetBalanceOf<T, I>,
+    ) -> RpcResult<AssetBalanceOf<T, I>> {
+        let currency_amount =
+            Self::get_asset_to_currency_output_amount(sold_asset_id, token_amount)?;
+        Self::get_currency_to_asset_output_amount(bought_asset_id, currency_amount)
+    }
+
+    /// Get the input amount for a fixed-output asset-to-asset trade,
+    /// i.e. 'How much of the sold asset do I have to pay to get this much of the bought asset'?
+    pub fn get_asset_to_asset_input_amount(
+        sold_asset_id: AssetIdOf<T, I>,
+        bought_asset_id: AssetIdOf<T, I>,
+        token_amount: AssetBalanceOf<T, I>,
+    ) -> RpcResult<AssetBalanceOf<T, I>> {
+        let currency_amount =
+            Self::get_currency_to_asset_input_amount(bought_asset_id, token_amount)?;
+        Self::get_asset_to_currency_input_amount(sold_asset_id, currency_amount)
+    }
+
+    /// Get the deposit of `add_liquidity_exact_tokens`, i.e. 'How much currency do I have to add
+    /// with this much asset, and how much liquidity would I get'?
+    pub fn get_add_liquidity_exact_tokens_deposit(
+        asset_id: AssetIdOf<T, I>,
+        token_amount: AssetBalanceOf<T, I>,
+    ) -> RpcResult<LiquidityDepositOf<T, I>> {
+        let exchange = Self::get_exchange(&asset_id)?;
+        let (currency_amount, liquidity_minted) =
+            Self::get_liquidity_deposit_for_tokens(&exchange, token_amount)?;
+        Ok(LiquidityDeposit {
+            currency_amount,
+            token_amount,
+            liquidity_minted,
+        })
+    }
+
+    /// Get the deposit of `add_liquidity_bounded`, i.e. 'How much of my currency and asset would
+    /// be added, and how much liquidity would I get'?
+    pub fn get_add_liquidity_bounded_deposit(
+        asset_id: AssetIdOf<T, I>,
+        max_currency: BalanceOf<T, I>,
+        max_tokens: AssetBalanceOf<T, I>,
+        min_currency: BalanceOf<T, I>,
+        min_tokens: AssetBalanceOf<T, I>,
+    ) -> RpcResult<LiquidityDepositOf<T, I>> {
+        let exchange = Self::get_exchange(&asset_id)?;
+        let (currency_amount, token_amount, liquidity_minted) =
+            Self::get_bounded_liquidity_deposit(
+                &exchange,
+                max_currency,
+                max_tokens,
+                min_currency,
+                min_tokens,
+            )?;
+        Ok(LiquidityDeposit {
+            currency_amount,
+            token_amount,
+            liquidity_minted,
+        })
+    }
+
+    /// Get current reserves, liquidity token supply and spot price of an exchange.
+    pub fn get_exchange_state(asset_id: AssetIdOf<T, I>) -> RpcResult<ExchangeStateOf<T, I>> {
+        let exchange = Self::get_exchange(&asset_id)?;
+        let total_liquidity = Self::total_liquidity(&exchange);
+        let spot_price = Self::checked_spot_price(&exchange).ok_or(RpcError::Overflow)?;
+        Ok(ExchangeState {
+            currency_reserve: exchange.currency_reserve,
+            token_reserve: exchange.token_reserve,
+            total_liquidity,
+            spot_price,
+        })
+    }
+
+    /// Get the storage key of an exchange, e.g. for watching its changes.
+    pub fn exchange_storage_key(asset_id: AssetIdOf<T, I>) -> Vec<u8> {
+        <Exchanges<T, I>>::hashed_key_for(asset_id)
+    }
+
+    /// Answer multiple price quotes at once (at most `MAX_BATCH_QUOTES`). Each request is answered
+    /// independently, i.e. a failure of one quote doesn't affect the others.
+    pub fn batch_quote(
+        requests: Vec<QuoteRequestOf<T, I>>,
+    ) -> RpcResult<Vec<RpcResult<QuoteOf<T, I>>>> {
+        if requests.len() > MAX_BATCH_QUOTES as usize {
+            return Err(RpcError::BatchTooLarge);
+        }
+        Ok(requests.into_iter().map(Self::quote).collect())
+    }
+
+    /// Dry-run a DEX call dispatched by `caller` against the current state. Collect the result,
+    /// emitted events, affected exchanges and balance changes, then roll back all the changes.
+    pub fn simulate(caller: AccountIdOf<T>, call: Call<T, I>) -> SimulationOf<T, I> {
+        let (accounts, asset_ids, liquidity_token_ids) = Self::simulated_parties(&caller, &call);
+        let simulation = with_transaction(|| {
+            let balances_before = Self::balances(&accounts, &asset_ids, &liquidity_token_ids);
+            <frame_system::Pallet<T>>::reset_events();
+
+            let origin = frame_system::RawOrigin::Signed(caller).into();
+            let result = call
+                .dispatch_bypass_filter(origin)
+                .map(|_| ())
+                .map_err(|err| err.error);
+
+            let events = <frame_system::Pallet<T>>::read_events_no_consensus()
+                .map(|record| record.event)
+                .collect();
+            let exchanges = asset_ids
+                .iter()
+                .filter_map(|asset_id| <Exchanges<T, I>>::get(asset_id.clone()))
+                .collect();
+            let balances_after = Self::balances(&accounts, &asset_ids, &liquidity_token_ids);
+            let balance_changes = balances_before
+                .into_iter()
+                .zip(balances_after)
+                .filter_map(|changes| match changes {
+                    (
+                        BalanceChange::Currency {
+                            account, before, ..
+                        },
+                        BalanceChange::Currency { after, .. },
+                    ) if before != after => Some(BalanceChange::Currency {
+                        account,
+                        before,
+                        after,
+                    }),
+                    (
+                        BalanceChange::Asset {
+                            account,
+                            asset_id,
+                            before,
+                            ..
+                        },
+                        BalanceChange::Asset { after, .. },
+                    ) if before != after => Some(BalanceChange::Asset {
+                        account,
+                        asset_id,
+                        before,
+                        after,
+                    }),
+                    _ => None,
+                })
+                .collect();
+
+            TransactionOutcome::Rollback(Ok::<_, DispatchError>(Simulation {
+                result,
+                events,
+                exchanges,
+                balance_changes,
+            }))
+        });
+        // Only fails if the transactional layer limit is reached
+        simulation.unwrap_or_else(|err| Simulation {
+            result: Err(err),
+            events: Vec::new(),
+            exchanges: Vec::new(),
+            balance_changes: Vec::new(),
+        })
+    }
+
+    /// Accounts, exchange asset IDs and liquidity token IDs which can be affected by `call`.
+    fn simulated_parties(
+        caller: &AccountIdOf<T>,
+        call: &Call<T, I>,
+    ) -> (Vec<AccountIdOf<T>>, Vec<AssetIdOf<T, I>>, Vec<AssetIdOf<T, I>>) {
+        let mut accounts = vec![caller.clone(), T::pallet_account()];
+        let mut asset_ids = Vec::new();
+        let mut liquidity_token_ids = Vec::new();
+        let recipient = match call {
+            Call::create_exchange {
+                asset_id,
+                liquidity_token_id,
+                ..
+            } => {
+                asset_ids.push(asset_id.clone());
+                liquidity_token_ids.push(liquidity_token_id.clone());
+                None
+            }
+            Call::add_liquidity { asset_id, .. }
+            | Call::remove_liquidity { asset_id, .. }
+            | Call::zap_in { asset_id, .. }
+            | Call::zap_out { asset_id, .. }
+            | Call::add_liquidity_exact_tokens { asset_id, .. }
+            | Call::add_liquidity_bounded { asset_id, .. }
+            | Call::create_concentrated_exchange { asset_id, .. }
+            | Call::mint_position { asset_id, .. }
+            | Call::burn_position { asset_id, .. }
+            | Call::place_limit_order { asset_id, .. } => {
+                asset_ids.push(asset_id.clone());
+                None
+            }
+            Call::cancel_limit_order { order_id } => {
+                if let Some(order) = <LimitOrders<T, I>>::get(order_id) {
+                    asset_ids.push(order.asset_id);
+                }
+                None
+            }
+            Call::schedule_dca { swap, .. } => {
+                asset_ids.extend(Self::dca_swap_asset_ids(swap));
+                None
+            }
+            Call::cancel_dca { schedule_id } => {
+                if let Some(schedule) = <DcaSchedules<T, I>>::get(schedule_id) {
+                    asset_ids.extend(Self::dca_swap_asset_ids(&schedule.swap));
+                }
+                None
+            }
+            Call::currency_to_asset {
+                asset_id,
+                recipient,
+                ..
+            }
+            | Call::asset_to_currency {
+                asset_id,
+                recipient,
+                ..
+            }
+            | Call::reveal_trade {
+                asset_id,
+                recipient,
+                ..
+            } => {
+                asset_ids.push(asset_id.clone());
+                recipient.clone()
+            }
+            Call::execute_signed_intent { intent, .. } => {
+                asset_ids.push(intent.asset_id.clone());
+                if !accounts.contains(&intent.signer) {
+                    accounts.push(intent.signer.clone());
+                }
+                intent.recipient.clone()
+            }
+            Call::asset_to_asset {
+                sold_asset_id,
+                bought_asset_id,
+                recipient,
+                ..
+            } => {
+                asset_ids.push(sold_asset_id.clone());
+                if bought_asset_id != sold_asset_id {
+                    asset_ids.push(bought_asset_id.clone());
+                }
+                recipient.clone()
+            }
+            _ => None,
+        };
+        let referrer = match call {
+            Call::currency_to_asset { referrer, .. }
+            | Call::asset_to_currency { referrer, .. }
+            | Call::asset_to_asset { referrer, .. } => referrer.clone(),
+            _ => None,
+        };
+        for account in [recipient, referrer].into_iter().flatten() {
+            if !accounts.contains(&account) {
+                accounts.push(account);
+            }
+        }
+        for asset_id in &asset_ids {
+            if let Some(liquidity_token_id) = Self::get_exchange(asset_id)
+                .ok()
+                .and_then(|exchange| exchange.liquidity_token_id)
+            {
+                if !liquidity_token_ids.contains(&liquidity_token_id) {
+                    liquidity_token_ids.push(liquidity_token_id);
+                }
+            }
+        }
+        (accounts, asset_ids, liquidity_token_ids)
+    }
+
+    fn dca_swap_asset_ids(swap: &DcaSwapOf<T, I>) -> Vec<AssetIdOf<T, I>> {
+        match swap {
+            DcaSwap::CurrencyToAsset { asset_id, .. }
+            | DcaSwap::AssetToCurrency { asset_id, .. } => {
+                vec![asset_id.clone()]
+            }
+            DcaSwap::AssetToAsset {
+                sold_asset_id,
+                bought_asset_id,
+                ..
+            } if sold_asset_id == bought_asset_id => vec![sold_asset_id.clone()],
+            DcaSwap::AssetToAsset {
+                sold_asset_id,
+                bought_asset_id,
+                ..
+            } => vec![sold_asset_id.clone(), bought_asset_id.clone()],
+        }
+    }
+
+    /// Current balances of the given accounts, as changes with equal `before` and `after` values.
+    fn balances(
+        accounts: &[AccountIdOf<T>],
+        asset_ids: &[AssetIdOf<T, I>],
+        liquidity_token_ids: &[AssetIdOf<T, I>],
+    ) -> Vec<BalanceChangeOf<T, I>> {
+        let mut balances = Vec::new();
+        for account in accounts {
+            let balance = T::QuoteAsset::balance(account);
+            balances.push(BalanceChange::Currency {
+                account: account.clone(),
+                before: balance,
+                after: balance,
+            });
+            for asset_id in asset_ids {
+                let balance = T::Assets::balance(asset_id.clone(), account);
+                balances.push(BalanceChange::Asset {
+                    account: account.clone(),
+                    asset_id: asset_id.clone(),
+                    before: balance,
+                    after: balance,
+                });
+            }
+            for liquidity_token_id in liquidity_token_ids {
+                let balance = T::AssetRegistry::balance(liquidity_token_id.clone(), account);
+                balances.push(BalanceChange::Asset {
+                    account: account.clone(),
+                    asset_id: liquidity_token_id.clone(),
+                    before: balance,
+                    after: balance,
+                });
+            }
+        }
+        balances
+    }
+
+    /// Find the route for selling `amount` of `from` for `to`, and price it with the on-chain
+    /// pricing functions. As every exchange pairs its asset with the currency, a route has at most
+    /// two hops, i.e. it maps to a single `currency_to_asset`, `asset_to_currency` or
+    /// `asset_to_asset` trade. Asset-to-asset routes through exchanges in batch auction mode aren't
+    /// returned, as `asset_to_asset` rejects them.
+    pub fn find_best_route(
+        from: TokenOf<T, I>,
+        to: TokenOf<T, I>,
+        amount: TokenAmountOf<T, I>,
+    ) -> RpcResult<RouteOf<T, I>> {
+        match (&from, &amount) {
+            (Token::Currency, TokenAmount::Currency(_))
+            | (Token::Asset(_), TokenAmount::Asset(_)) => {}
+            _ => return Err(RpcError::AmountTokenMismatch),
+        }
+        if amount.is_zero() {
+            Err(Error::<T, I>::TradeAmountIsZero)?
+        }
+        let (path, amount_out) = match (from, to, amount) {
+            (Token::Currency, Token::Asset(asset_id), TokenAmount::Currency(amount)) => (
+                vec![Token::Currency, Token::Asset(asset_id.clone())],
+                Self::get_currency_to_asset_output_amount(asset_id, amount).map(TokenAmount::Asset),
+            ),
+            (Token::Asset(asset_id), Token::Currency, TokenAmount::Asset(amount)) => (
+                vec![Token::Asset(asset_id.clone()), Token::Currency],
+                Self::get_asset_to_currency_output_amount(asset_id, amount)
+                    .map(TokenAmount::Currency),
+            ),
+            (
+                Token::Asset(sold_asset_id),
+                Token::Asset(bought_asset_id),
+                TokenAmount::Asset(amount),
+            ) if sold_asset_id != bought_asset_id
+                && !Self::batch_auctions(&sold_asset_id)
+                && !Self::batch_auctions(&bought_asset_id) =>
+            {
+                (
+                    vec![
+                        Token::Asset(sold_asset_id.clone()),
+                        Token::Currency,
+                        Token::Asset(bought_asset_id.clone()),
+                    ],
+                    Self::get_asset_to_asset_output_amount(sold_asset_id, bought_asset_id, amount)
+                        .map(TokenAmount::Asset),
+                )
+            }
+            _ => return Err(RpcError::RouteNotFound),
+        };
+        match amount_out {
+            Ok(amount_out) if !amount_out.is_zero() => Ok(Route { path, amount_out }),
+            _ => Err(RpcError::RouteNotFound),
+        }
+    }
+
+    fn quote(request: QuoteRequestOf<T, I>) -> RpcResult<QuoteOf<T, I>> {
+        match request {
+            QuoteRequest::CurrencyToAsset { asset_id, amount } => match amount {
+                QuoteAmount::FixedInput(currency_amount) => {
+                    Self::get_currency_to_asset_output_amount(asset_id, currency_amount)
+                        .map(Quote::Asset)
+                }
+                QuoteAmount::FixedOutput(token_amount) => {
+                    Self::get_currency_to_asset_input_amount(asset_id, token_amount)
+                        .map(Quote::Currency)
+                }
+            },
+            QuoteRequest::AssetToCurrency { asset_id, amount } => match amount {
+                QuoteAmount::FixedInput(token_amount) => {
+                    Self::get_asset_to_currency_output_amount(asset_id, token_amount)
+                        .map(Quote::Currency)
+                }
+                QuoteAmount::FixedOutput(currency_amount) => {
+                    Self::get_asset_to_currency_input_amount(asset_id, currency_amount)
+                        .map(Quote::Asset)
+                }
+            },
+            QuoteRequest::AssetToAsset {
+                sold_asset_id,
+                bought_asset_id,
+                amount,
+            } => match amount {
+                QuoteAmount::FixedInput(token_amount) => Self::get_asset_to_asset_output_amount(
+                    sold_asset_id,
+                    bought_asset_id,
+                    token_amount,
+                )
+                .map(Quote::Asset),
+                QuoteAmount::FixedOutput(token_amount) => Self::get_asset_to_asset_input_amount(
+                    sold_asset_id,
+                    bought_asset_id,
+                    token_amount,
+                )
+                .map(Quote::Asset),
+            },
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::mock::*;
-    use crate::rpc::RpcError;
-    use crate::{AssetBalanceOf, AssetIdOf, BalanceOf, Exchange, Exchanges};
-    use frame_support::assert_noop;
+    use crate::rpc::{
+        BalanceChange, ExchangeState, LiquidityDeposit, Quote, QuoteAmount, QuoteRequest, Route,
+        RpcError, Token, TokenAmount, MAX_BATCH_QUOTES,
+    };
+    use crate::{
+        AssetBalanceOf, AssetIdOf, BalanceOf, Call, ConfigHelper, Curve, Deadline, Error, Event,
+        Exchange, Exchanges, TradeAmount,
+    };
+    use frame_support::{
+        assert_noop, assert_ok,
+        sp_runtime::{FixedPointNumber, FixedU128},
+        traits::Currency,
+    };
 
     #[test]
     fn get_currency_to_asset_output_amount_exchange_not_found() {
@@ -235,6 +980,528 @@ mod tests {
         })
     }
 
+    #[test]
+    fn get_asset_to_asset_output_amount_exchange_not_found() {
+        new_test_ext().execute_with(|| {
+            assert_noop!(
+                Dex::get_asset_to_asset_output_amount(ASSET_A, u32::MAX, 1_000_000),
+                RpcError::ExchangeNotFound
+            );
+        })
+    }
+
+    #[test]
+    fn get_asset_to_asset_output_amount() {
+        new_test_ext().execute_with(|| {
+            create_exchange_b();
+            assert_eq!(
+                994_007,
+                Dex::get_asset_to_asset_output_amount(ASSET_A, ASSET_B, 1_000_000).unwrap(),
+            );
+        })
+    }
+
+    #[test]
+    fn get_asset_to_asset_input_amount() {
+        new_test_ext().execute_with(|| {
+            create_exchange_b();
+            assert_eq!(
+                1_006_031,
+                Dex::get_asset_to_asset_input_amount(ASSET_A, ASSET_B, 1_000_000).unwrap(),
+            );
+        })
+    }
+
+    #[test]
+    fn batch_quote() {
+        new_test_ext().execute_with(|| {
+            create_exchange_b();
+            let requests = vec![
+                QuoteRequest::CurrencyToAsset {
+                    asset_id: ASSET_A,
+                    amount: QuoteAmount::FixedInput(1_000_000),
+                },
+                QuoteRequest::CurrencyToAsset {
+                    asset_id: ASSET_A,
+                    amount: QuoteAmount::FixedOutput(1_000_000),
+                },
+                QuoteRequest::AssetToCurrency {
+                    asset_id: ASSET_A,
+                    amount: QuoteAmount::FixedInput(1_000_000),
+                },
+                QuoteRequest::AssetToCurrency {
+                    asset_id: u32::MAX,
+                    amount: QuoteAmount::FixedOutput(1_000_000),
+                },
+                QuoteRequest::AssetToAsset {
+                    sold_asset_id: ASSET_A,
+                    bought_asset_id: ASSET_B,
+                    amount: QuoteAmount::FixedInput(1_000_000),
+                },
+                QuoteRequest::AssetToAsset {
+                    sold_asset_id: ASSET_A,
+                    bought_asset_id: ASSET_B,
+                    amount: QuoteAmount::FixedOutput(u128::MAX),
+                },
+            ];
+            assert_eq!(
+                Dex::batch_quote(requests).unwrap(),
+                vec![
+                    Ok(Quote::Asset(996_999)),
+                    Ok(Quote::Currency(1_003_011)),
+                    Ok(Quote::Currency(996_999)),
+                    Err(RpcError::ExchangeNotFound),
+                    Ok(Quote::Asset(994_007)),
+                    Err(RpcError::NotEnoughLiquidity),
+                ]
+            );
+        })
+    }
+
+    #[test]
+    fn batch_quote_empty() {
+        new_test_ext().execute_with(|| {
+            assert_eq!(Dex::batch_quote(vec![]), Ok(vec![]));
+        })
+    }
+
+    #[test]
+    fn batch_quote_too_large() {
+        new_test_ext().execute_with(|| {
+            let request = QuoteRequest::CurrencyToAsset {
+                asset_id: ASSET_A,
+                amount: QuoteAmount::FixedInput(1_000_000),
+            };
+            let requests = vec![request; MAX_BATCH_QUOTES as usize + 1];
+            assert_eq!(Dex::batch_quote(requests), Err(RpcError::BatchTooLarge));
+        })
+    }
+
+    #[test]
+    fn get_exchange_state_exchange_not_found() {
+        new_test_ext().execute_with(|| {
+            assert_noop!(Dex::get_exchange_state(u32::MAX), RpcError::ExchangeNotFound);
+        })
+    }
+
+    #[test]
+    fn get_exchange_state() {
+        new_test_ext().execute_with(|| {
+            assert_eq!(
+                Dex::get_exchange_state(ASSET_A),
+                Ok(ExchangeState {
+                    currency_reserve: INIT_LIQUIDITY,
+                    token_reserve: INIT_LIQUIDITY,
+                    total_liquidity: INIT_LIQUIDITY,
+                    spot_price: FixedU128::one(),
+                })
+            );
+            assert_ok!(Dex::currency_to_asset(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                TradeAmount::FixedOutput {
+                    max_input: INIT_LIQUIDITY,
+                    output_amount: INIT_LIQUIDITY / 2,
+                },
+                Deadline::Block(1),
+                None,
+                None,
+                false,
+            ));
+            let state = Dex::get_exchange_state(ASSET_A).unwrap();
+            assert_eq!(state.token_reserve, INIT_LIQUIDITY / 2);
+            assert_eq!(state.total_liquidity, INIT_LIQUIDITY);
+            assert_eq!(
+                state.spot_price,
+                FixedU128::from_rational(state.currency_reserve, state.token_reserve)
+            );
+        })
+    }
+
+    #[test]
+    fn exchange_storage_key() {
+        new_test_ext().execute_with(|| {
+            let key = Dex::exchange_storage_key(ASSET_A);
+            assert_eq!(key, Exchanges::<Test>::hashed_key_for(ASSET_A));
+            assert!(frame_support::storage::unhashed::exists(&key));
+            assert_ne!(key, Dex::exchange_storage_key(ASSET_B));
+        })
+    }
+
+    #[test]
+    fn get_add_liquidity_exact_tokens_deposit_exchange_not_found() {
+        new_test_ext().execute_with(|| {
+            assert_noop!(
+                Dex::get_add_liquidity_exact_tokens_deposit(u32::MAX, 1_000),
+                RpcError::ExchangeNotFound
+            );
+        })
+    }
+
+    #[test]
+    fn get_add_liquidity_exact_tokens_deposit() {
+        new_test_ext().execute_with(|| {
+            assert_eq!(
+                Dex::get_add_liquidity_exact_tokens_deposit(ASSET_A, 1_000),
+                Ok(LiquidityDeposit {
+                    currency_amount: 1_001,
+                    token_amount: 1_000,
+                    liquidity_minted: 1_000,
+                })
+            );
+        })
+    }
+
+    #[test]
+    fn get_add_liquidity_bounded_deposit() {
+        new_test_ext().execute_with(|| {
+            assert_eq!(
+                Dex::get_add_liquidity_bounded_deposit(ASSET_A, 1_000, 2_000, 1, 1),
+                Ok(LiquidityDeposit {
+                    currency_amount: 1_000,
+                    token_amount: 1_001,
+                    liquidity_minted: 1_000,
+                })
+            );
+            assert_eq!(
+                Dex::get_add_liquidity_bounded_deposit(ASSET_A, 2_000, 1_000, 1, 1),
+                Ok(LiquidityDeposit {
+                    currency_amount: 1_001,
+                    token_amount: 1_000,
+                    liquidity_minted: 1_000,
+                })
+            );
+        })
+    }
+
+    #[test]
+    fn get_add_liquidity_bounded_deposit_min_tokens_too_high() {
+        new_test_ext().execute_with(|| {
+            assert_noop!(
+                Dex::get_add_liquidity_bounded_deposit(ASSET_A, 1_000, 2_000, 1, 1_002),
+                RpcError::MinTokensTooHigh
+            );
+        })
+    }
+
+    #[test]
+    fn rpc_error_from_pallet_error() {
+        assert_eq!(RpcError::from(Error::<Test>::ExchangeNotFound), RpcError::ExchangeNotFound);
+        assert_eq!(RpcError::from(Error::<Test>::AssetNotFound), RpcError::AssetNotFound);
+        assert_eq!(RpcError::from(Error::<Test>::DeadlinePassed), RpcError::DeadlinePassed);
+        assert_eq!(
+            RpcError::from(Error::<Test>::MaxSoldTokensTooLow),
+            RpcError::MaxSoldTokensTooLow
+        );
+    }
+
+    #[test]
+    fn find_best_route_currency_to_asset() {
+        new_test_ext().execute_with(|| {
+            assert_eq!(
+                Dex::find_best_route(
+                    Token::Currency,
+                    Token::Asset(ASSET_A),
+                    TokenAmount::Currency(1_000_000)
+                ),
+                Ok(Route {
+                    path: vec![Token::Currency, Token::Asset(ASSET_A)],
+                    amount_out: TokenAmount::Asset(996_999),
+                })
+            );
+        })
+    }
+
+    #[test]
+    fn find_best_route_asset_to_currency() {
+        new_test_ext().execute_with(|| {
+            assert_eq!(
+                Dex::find_best_route(
+                    Token::Asset(ASSET_A),
+                    Token::Currency,
+                    TokenAmount::Asset(1_000_000)
+                ),
+                Ok(Route {
+                    path: vec![Token::Asset(ASSET_A), Token::Currency],
+                    amount_out: TokenAmount::Currency(
+                        Dex::get_asset_to_currency_output_amount(ASSET_A, 1_000_000).unwrap()
+                    ),
+                })
+            );
+        })
+    }
+
+    #[test]
+    fn find_best_route_asset_to_asset() {
+        new_test_ext().execute_with(|| {
+            create_exchange_b();
+            assert_eq!(
+                Dex::find_best_route(
+                    Token::Asset(ASSET_A),
+                    Token::Asset(ASSET_B),
+                    TokenAmount::Asset(1_000_000)
+                ),
+                Ok(Route {
+                    path: vec![
+                        Token::Asset(ASSET_A),
+                        Token::Currency,
+                        Token::Asset(ASSET_B)
+                    ],
+                    amount_out: TokenAmount::Asset(
+                        Dex::get_asset_to_asset_output_amount(ASSET_A, ASSET_B, 1_000_000).unwrap()
+                    ),
+                })
+            );
+        })
+    }
+
+    #[test]
+    fn find_best_route_batch_auction() {
+        new_test_ext().execute_with(|| {
+            create_exchange_b();
+            assert_ok!(Dex::set_batch_auction(RuntimeOrigin::root(), ASSET_A, true));
+            assert_noop!(
+                Dex::find_best_route(
+                    Token::Asset(ASSET_A),
+                    Token::Asset(ASSET_B),
+                    TokenAmount::Asset(1_000_000)
+                ),
+                RpcError::RouteNotFound
+            );
+            assert_noop!(
+                Dex::find_best_route(
+                    Token::Asset(ASSET_B),
+                    Token::Asset(ASSET_A),
+                    TokenAmount::Asset(1_000_000)
+                ),
+                RpcError::RouteNotFound
+            );
+            assert_ok!(Dex::find_best_route(
+                Token::Currency,
+                Token::Asset(ASSET_A),
+                TokenAmount::Currency(1_000_000)
+            ));
+        })
+    }
+
+    #[test]
+    fn find_best_route_not_found() {
+        new_test_ext().execute_with(|| {
+            assert_noop!(
+                Dex::find_best_route(
+                    Token::Currency,
+                    Token::Asset(ASSET_B),
+                    TokenAmount::Currency(1_000_000)
+                ),
+                RpcError::RouteNotFound
+            );
+            assert_noop!(
+                Dex::find_best_route(
+                    Token::Currency,
+                    Token::Currency,
+                    TokenAmount::Currency(1_000_000)
+                ),
+                RpcError::RouteNotFound
+            );
+            assert_noop!(
+                Dex::find_best_route(
+                    Token::Asset(ASSET_A),
+                    Token::Asset(ASSET_A),
+                    TokenAmount::Asset(1_000_000)
+                ),
+                RpcError::RouteNotFound
+            );
+        })
+    }
+
+    #[test]
+    fn find_best_route_amount_token_mismatch() {
+        new_test_ext().execute_with(|| {
+            assert_noop!(
+                Dex::find_best_route(
+                    Token::Currency,
+                    Token::Asset(ASSET_A),
+                    TokenAmount::Asset(1_000_000)
+                ),
+                RpcError::AmountTokenMismatch
+            );
+            assert_noop!(
+                Dex::find_best_route(
+                    Token::Asset(ASSET_A),
+                    Token::Currency,
+                    TokenAmount::Currency(1_000_000)
+                ),
+                RpcError::AmountTokenMismatch
+            );
+        })
+    }
+
+    #[test]
+    fn find_best_route_amount_is_zero() {
+        new_test_ext().execute_with(|| {
+            assert_noop!(
+                Dex::find_best_route(
+                    Token::Currency,
+                    Token::Asset(ASSET_A),
+                    TokenAmount::Currency(0)
+                ),
+                RpcError::TradeAmountIsZero
+            );
+        })
+    }
+
+    #[test]
+    fn simulate_currency_to_asset() {
+        new_test_ext().execute_with(|| {
+            let pallet_account = <Test as ConfigHelper>::pallet_account();
+            let exchange = Exchanges::<Test>::get(ASSET_A).unwrap();
+            let call = Call::currency_to_asset {
+                asset_id: ASSET_A,
+                amount: TradeAmount::FixedInput {
+                    input_amount: 1_000_000,
+                    min_output: 1,
+                },
+                deadline: Deadline::Block(1),
+                recipient: Some(ACCOUNT_C),
+                referrer: None,
+                keep_alive: false,
+            };
+
+            let simulation = Dex::simulate(ACCOUNT_B, call);
+
+            assert_ok!(simulation.result);
+            assert!(simulation
+                .events
+                .contains(&RuntimeEvent::Dex(Event::CurrencyTradedForAsset(
+                    ASSET_A,
+                    ACCOUNT_B,
+                    ACCOUNT_C,
+                    1_000_000,
+                    996_999,
+                    PROVIDER_FEE,
+                    None
+                ))));
+            assert_eq!(
+                simulation.exchanges,
+                vec![Exchange {
+                    asset_id: ASSET_A,
+                    currency_reserve: INIT_LIQUIDITY + 1_000_000,
+                    token_reserve: INIT_LIQUIDITY - 996_999,
+                    liquidity_token_id: Some(LIQ_TOKEN_A),
+                    curve: Curve::ConstantProduct,
+                }]
+            );
+            assert_eq!(
+                simulation.balance_changes,
+                vec![
+                    BalanceChange::Currency {
+                        account: ACCOUNT_B,
+                        before: INIT_BALANCE,
+                        after: INIT_BALANCE - 1_000_000,
+                    },
+                    BalanceChange::Currency {
+                        account: pallet_account,
+                        before: INIT_LIQUIDITY,
+                        after: INIT_LIQUIDITY + 1_000_000,
+                    },
+                    BalanceChange::Asset {
+                        account: pallet_account,
+                        asset_id: ASSET_A,
+                        before: INIT_LIQUIDITY,
+                        after: INIT_LIQUIDITY - 996_999,
+                    },
+                    BalanceChange::Asset {
+                        account: ACCOUNT_C,
+                        asset_id: ASSET_A,
+                        before: INIT_BALANCE,
+                        after: INIT_BALANCE + 996_999,
+                    },
+                ]
+            );
+
+            // All the changes were rolled back
+            assert_eq!(Exchanges::<Test>::get(ASSET_A).unwrap(), exchange);
+            assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE);
+            assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_C), Some(INIT_BALANCE));
+            assert!(System::events().is_empty());
+        })
+    }
+
+    #[test]
+    fn simulate_create_exchange() {
+        new_test_ext().execute_with(|| {
+            let call = Call::create_exchange {
+                asset_id: ASSET_B,
+                liquidity_token_id: LIQ_TOKEN_B,
+                currency_amount: INIT_LIQUIDITY,
+                token_amount: INIT_LIQUIDITY,
+                curve: Curve::ConstantProduct,
+            };
+
+            let simulation = Dex::simulate(ACCOUNT_B, call);
+
+            assert_ok!(simulation.result);
+            assert!(simulation
+                .events
+                .contains(&RuntimeEvent::Dex(Event::ExchangeCreated(ASSET_B, LIQ_TOKEN_B))));
+            assert_eq!(
+                simulation.exchanges,
+                vec![Exchange {
+                    asset_id: ASSET_B,
+                    currency_reserve: INIT_LIQUIDITY,
+                    token_reserve: INIT_LIQUIDITY,
+                    liquidity_token_id: Some(LIQ_TOKEN_B),
+                    curve: Curve::ConstantProduct,
+                }]
+            );
+            assert!(simulation.balance_changes.contains(&BalanceChange::Asset {
+                account: ACCOUNT_B,
+                asset_id: LIQ_TOKEN_B,
+                before: 0,
+                after: INIT_LIQUIDITY,
+            }));
+
+            assert!(Exchanges::<Test>::get(ASSET_B).is_none());
+            assert_eq!(Assets::maybe_total_supply(LIQ_TOKEN_B), None);
+        })
+    }
+
+    #[test]
+    fn simulate_with_error() {
+        new_test_ext().execute_with(|| {
+            let exchange = Exchanges::<Test>::get(ASSET_A).unwrap();
+            let call = Call::asset_to_currency {
+                asset_id: ASSET_A,
+                amount: TradeAmount::FixedInput {
+                    input_amount: 1_000_000,
+                    min_output: 1,
+                },
+                deadline: Deadline::Block(0),
+                recipient: None,
+                referrer: None,
+                keep_alive: false,
+            };
+
+            let simulation = Dex::simulate(ACCOUNT_B, call);
+
+            assert_eq!(simulation.result, Err(Error::<Test>::DeadlinePassed.into()));
+            assert!(simulation.events.is_empty());
+            assert_eq!(simulation.exchanges, vec![exchange]);
+            assert!(simulation.balance_changes.is_empty());
+        })
+    }
+
+    fn create_exchange_b() {
+        assert_ok!(Dex::create_exchange(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_B,
+            LIQ_TOKEN_B,
+            INIT_LIQUIDITY,
+            INIT_LIQUIDITY,
+            Curve::ConstantProduct,
+        ));
+    }
+
     fn max_exchange_reserves(asset_id: AssetIdOf<Test>) {
         Exchanges::<Test>::insert(
             asset_id,
@@ -242,7 +1509,8 @@ mod tests {
                 asset_id,
                 currency_reserve: u128::MAX,
                 token_reserve: u128::MAX,
-                liquidity_token_id: LIQ_TOKEN_A,
+                liquidity_token_id: Some(LIQ_TOKEN_A),
+                curve: Curve::ConstantProduct,
             },
         );
     }
@@ -1,14 +1,33 @@
+use crate::curve::Side;
+use crate::dynamic_fee::{DynamicFee, DynamicFeeParams};
 use crate::mock::*;
 use crate::pallet::ConfigHelper;
-use crate::{Error, TradeAmount};
+use crate::rpc::RpcError;
+use crate::{
+    CheckDeadline, Curve, DcaSwap, Deadline, Error, IntentTip, LiquidityChange, OrderAmount,
+    SwapAmount, SwapDetails, SwapIntent, TradeAmount, WeightInfo, WeightSchedule, ZapOutput,
+};
+use codec::{Decode, Encode};
 use frame_support::{
     assert_noop, assert_ok,
+    dispatch::{DispatchInfo, DispatchResultWithPostInfo, GetDispatchInfo},
+    instances::Instance1,
+    sp_runtime::{
+        traits::{BlakeTwo256, Hash, SignedExtension, Zero},
+        transaction_validity::{InvalidTransaction, TransactionValidity, ValidTransaction},
+        FixedPointNumber, FixedU128, Permill,
+    },
+    storage::unhashed,
     traits::{
         fungibles::Mutate,
         tokens::{Fortitude, Precision},
-        Currency,
+        Currency, Get, GetStorageVersion, Hooks, OnRuntimeUpgrade, StorageVersion,
     },
+    weights::Weight,
+    PalletId,
 };
+use sp_core::H256;
+use sp_keyring::Sr25519Keyring;
 
 #[test]
 fn create_exchange() {
@@ -18,13 +37,14 @@ fn create_exchange() {
             ASSET_B,
             LIQ_TOKEN_B,
             1,
-            1
+            1,
+            Curve::ConstantProduct,
         ));
         let exchange = Dex::exchanges(ASSET_B).unwrap();
         assert_eq!(exchange.asset_id, ASSET_B);
         assert_eq!(exchange.currency_reserve, 1);
         assert_eq!(exchange.token_reserve, 1);
-        assert_eq!(Assets::total_supply(exchange.liquidity_token_id), 1);
+        assert_eq!(Assets::total_supply(exchange.liquidity_token_id.unwrap()), 1);
         assert!(
             matches!(last_event(), crate::Event::ExchangeCreated(asset, _) if asset == ASSET_B)
         );
@@ -35,7 +55,14 @@ fn create_exchange() {
 fn create_exchange_unsigned() {
     new_test_ext().execute_with(|| {
         assert_noop!(
-            Dex::create_exchange(RuntimeOrigin::none(), ASSET_A, LIQ_TOKEN_A, 1, 1),
+            Dex::create_exchange(
+                RuntimeOrigin::none(),
+                ASSET_A,
+                LIQ_TOKEN_A,
+                1,
+                1,
+                Curve::ConstantProduct
+            ),
             frame_support::error::BadOrigin
         );
     })
@@ -45,7 +72,14 @@ fn create_exchange_unsigned() {
 fn create_exchange_currency_amount_too_low() {
     new_test_ext().execute_with(|| {
         assert_noop!(
-            Dex::create_exchange(RuntimeOrigin::signed(ACCOUNT_A), ASSET_A, LIQ_TOKEN_A, 0, 1),
+            Dex::create_exchange(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                ASSET_A,
+                LIQ_TOKEN_A,
+                0,
+                1,
+                Curve::ConstantProduct
+            ),
             Error::<Test>::CurrencyAmountTooLow
         );
     })
@@ -55,7 +89,14 @@ fn create_exchange_currency_amount_too_low() {
 fn create_exchange_token_amount_zero() {
     new_test_ext().execute_with(|| {
         assert_noop!(
-            Dex::create_exchange(RuntimeOrigin::signed(ACCOUNT_A), ASSET_A, LIQ_TOKEN_A, 1, 0),
+            Dex::create_exchange(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                ASSET_A,
+                LIQ_TOKEN_A,
+                1,
+                0,
+                Curve::ConstantProduct
+            ),
             Error::<Test>::TokenAmountIsZero
         );
     })
@@ -65,7 +106,14 @@ fn create_exchange_token_amount_zero() {
 fn create_exchange_asset_not_found() {
     new_test_ext().execute_with(|| {
         assert_noop!(
-            Dex::create_exchange(RuntimeOrigin::signed(ACCOUNT_A), 2137, LIQ_TOKEN_A, 1, 1),
+            Dex::create_exchange(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                2137,
+                LIQ_TOKEN_A,
+                1,
+                1,
+                Curve::ConstantProduct
+            ),
             Error::<Test>::AssetNotFound
         );
     })
@@ -75,7 +123,14 @@ fn create_exchange_asset_not_found() {
 fn create_exchange_already_exists() {
     new_test_ext().execute_with(|| {
         assert_noop!(
-            Dex::create_exchange(RuntimeOrigin::signed(ACCOUNT_A), ASSET_A, LIQ_TOKEN_A, 1, 1),
+            Dex::create_exchange(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                ASSET_A,
+                LIQ_TOKEN_A,
+                1,
+                1,
+                Curve::ConstantProduct
+            ),
             Error::<Test>::ExchangeAlreadyExists
         );
     })
@@ -85,7 +140,14 @@ fn create_exchange_already_exists() {
 fn create_exchange_token_id_taken() {
     new_test_ext().execute_with(|| {
         assert_noop!(
-            Dex::create_exchange(RuntimeOrigin::signed(ACCOUNT_A), ASSET_B, LIQ_TOKEN_A, 1, 1),
+            Dex::create_exchange(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                ASSET_B,
+                LIQ_TOKEN_A,
+                1,
+                1,
+                Curve::ConstantProduct
+            ),
             Error::<Test>::TokenIdTaken
         );
     })
@@ -100,7 +162,8 @@ fn add_liquidity() {
             1_000,
             1_000,
             1_001,
-            1,
+            Deadline::Block(1),
+            true,
         ));
 
         let exchange = Dex::exchanges(ASSET_A).unwrap();
@@ -108,9 +171,15 @@ fn add_liquidity() {
         assert_eq!(exchange.token_reserve, INIT_LIQUIDITY + 1_001);
         assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE - 1_000);
         assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_B), Some(INIT_BALANCE - 1_001));
-        assert_eq!(Assets::maybe_balance(exchange.liquidity_token_id, ACCOUNT_B), Some(1_000));
-        assert_eq!(Assets::total_supply(exchange.liquidity_token_id), INIT_LIQUIDITY + 1_000);
-        let pallet_account = Test::pallet_account();
+        assert_eq!(
+            Assets::maybe_balance(exchange.liquidity_token_id.unwrap(), ACCOUNT_B),
+            Some(1_000)
+        );
+        assert_eq!(
+            Assets::total_supply(exchange.liquidity_token_id.unwrap()),
+            INIT_LIQUIDITY + 1_000
+        );
+        let pallet_account = <Test as ConfigHelper>::pallet_account();
         assert_eq!(Balances::free_balance(pallet_account), INIT_LIQUIDITY + 1_000);
         assert_eq!(Assets::maybe_balance(ASSET_A, pallet_account), Some(INIT_LIQUIDITY + 1_001));
         assert_eq!(
@@ -124,7 +193,15 @@ fn add_liquidity() {
 fn add_liquidity_unsigned() {
     new_test_ext().execute_with(|| {
         assert_noop!(
-            Dex::add_liquidity(RuntimeOrigin::none(), ASSET_A, 1_000, 1_000, 1_000, 1),
+            Dex::add_liquidity(
+                RuntimeOrigin::none(),
+                ASSET_A,
+                1_000,
+                1_000,
+                1_000,
+                Deadline::Block(1),
+                true
+            ),
             frame_support::error::BadOrigin
         );
     })
@@ -134,7 +211,15 @@ fn add_liquidity_unsigned() {
 fn add_liquidity_deadline_passed() {
     new_test_ext().execute_with(|| {
         assert_noop!(
-            Dex::add_liquidity(RuntimeOrigin::signed(ACCOUNT_A), ASSET_A, 1_000, 1_000, 1_000, 0),
+            Dex::add_liquidity(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                ASSET_A,
+                1_000,
+                1_000,
+                1_000,
+                Deadline::Block(0),
+                true
+            ),
             Error::<Test>::DeadlinePassed
         );
     })
@@ -144,7 +229,15 @@ fn add_liquidity_deadline_passed() {
 fn add_liquidity_zero_currency() {
     new_test_ext().execute_with(|| {
         assert_noop!(
-            Dex::add_liquidity(RuntimeOrigin::signed(ACCOUNT_A), ASSET_A, 0, 1_000, 1_000, 1),
+            Dex::add_liquidity(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                ASSET_A,
+                0,
+                1_000,
+                1_000,
+                Deadline::Block(1),
+                true
+            ),
             Error::<Test>::CurrencyAmountIsZero
         );
     })
@@ -154,7 +247,15 @@ fn add_liquidity_zero_currency() {
 fn add_liquidity_zero_tokens() {
     new_test_ext().execute_with(|| {
         assert_noop!(
-            Dex::add_liquidity(RuntimeOrigin::signed(ACCOUNT_A), ASSET_A, 1_000, 1_000, 0, 1),
+            Dex::add_liquidity(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                ASSET_A,
+                1_000,
+                1_000,
+                0,
+                Deadline::Block(1),
+                true
+            ),
             Error::<Test>::MaxTokensIsZero
         );
     })
@@ -170,7 +271,8 @@ fn add_liquidity_balance_too_low() {
                 INIT_BALANCE + 1,
                 1_000,
                 1_000,
-                1,
+                Deadline::Block(1),
+                true,
             ),
             Error::<Test>::BalanceTooLow
         );
@@ -181,7 +283,15 @@ fn add_liquidity_balance_too_low() {
 fn add_liquidity_asset_not_found() {
     new_test_ext().execute_with(|| {
         assert_noop!(
-            Dex::add_liquidity(RuntimeOrigin::signed(ACCOUNT_A), 2137, 1_000, 1_000, 1_000, 1),
+            Dex::add_liquidity(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                2137,
+                1_000,
+                1_000,
+                1_000,
+                Deadline::Block(1),
+                true
+            ),
             Error::<Test>::AssetNotFound
         );
     })
@@ -197,7 +307,8 @@ fn add_liquidity_not_enough_tokens() {
                 1_000,
                 1_000,
                 INIT_BALANCE + 1,
-                1,
+                Deadline::Block(1),
+                true,
             ),
             Error::<Test>::NotEnoughTokens
         );
@@ -208,7 +319,15 @@ fn add_liquidity_not_enough_tokens() {
 fn add_liquidity_exchange_not_found() {
     new_test_ext().execute_with(|| {
         assert_noop!(
-            Dex::add_liquidity(RuntimeOrigin::signed(ACCOUNT_A), ASSET_B, 1_000, 1_000, 1_000, 1),
+            Dex::add_liquidity(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                ASSET_B,
+                1_000,
+                1_000,
+                1_000,
+                Deadline::Block(1),
+                true
+            ),
             Error::<Test>::ExchangeNotFound
         );
     })
@@ -218,7 +337,15 @@ fn add_liquidity_exchange_not_found() {
 fn add_liquidity_zero_min_liquidity() {
     new_test_ext().execute_with(|| {
         assert_noop!(
-            Dex::add_liquidity(RuntimeOrigin::signed(ACCOUNT_B), ASSET_A, 1_000, 0, 1_001, 1),
+            Dex::add_liquidity(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                1_000,
+                0,
+                1_001,
+                Deadline::Block(1),
+                true
+            ),
             Error::<Test>::MinLiquidityIsZero
         );
     })
@@ -228,7 +355,15 @@ fn add_liquidity_zero_min_liquidity() {
 fn add_liquidity_max_tokens_too_low() {
     new_test_ext().execute_with(|| {
         assert_noop!(
-            Dex::add_liquidity(RuntimeOrigin::signed(ACCOUNT_B), ASSET_A, 1_000, 1_000, 10, 1),
+            Dex::add_liquidity(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                1_000,
+                1_000,
+                10,
+                Deadline::Block(1),
+                true
+            ),
             Error::<Test>::MaxTokensTooLow
         );
     })
@@ -238,7 +373,15 @@ fn add_liquidity_max_tokens_too_low() {
 fn add_liquidity_min_liquidity_too_high() {
     new_test_ext().execute_with(|| {
         assert_noop!(
-            Dex::add_liquidity(RuntimeOrigin::signed(ACCOUNT_B), ASSET_A, 1_000, 10_000, 1_001, 1),
+            Dex::add_liquidity(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                1_000,
+                10_000,
+                1_001,
+                Deadline::Block(1),
+                true
+            ),
             Error::<Test>::MinLiquidityTooHigh
         );
     })
@@ -253,7 +396,7 @@ fn remove_liquidity() {
             500,
             500,
             500,
-            1,
+            Deadline::Block(1),
         ));
         let exchange = Dex::exchanges(ASSET_A).unwrap();
         assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY - 500);
@@ -264,11 +407,14 @@ fn remove_liquidity() {
             Some(INIT_BALANCE - INIT_LIQUIDITY + 500)
         );
         assert_eq!(
-            Assets::maybe_balance(exchange.liquidity_token_id, ACCOUNT_A),
+            Assets::maybe_balance(exchange.liquidity_token_id.unwrap(), ACCOUNT_A),
             Some(INIT_LIQUIDITY - 500)
         );
-        assert_eq!(Assets::total_supply(exchange.liquidity_token_id), INIT_LIQUIDITY - 500);
-        let pallet_account = Test::pallet_account();
+        assert_eq!(
+            Assets::total_supply(exchange.liquidity_token_id.unwrap()),
+            INIT_LIQUIDITY - 500
+        );
+        let pallet_account = <Test as ConfigHelper>::pallet_account();
         assert_eq!(Balances::free_balance(pallet_account), INIT_LIQUIDITY - 500);
         assert_eq!(Assets::maybe_balance(ASSET_A, pallet_account), Some(INIT_LIQUIDITY - 500));
         assert_eq!(last_event(), crate::Event::LiquidityRemoved(ACCOUNT_A, ASSET_A, 500, 500, 500));
@@ -279,7 +425,14 @@ fn remove_liquidity() {
 fn remove_liquidity_unsigned() {
     new_test_ext().execute_with(|| {
         assert_noop!(
-            Dex::remove_liquidity(RuntimeOrigin::none(), ASSET_A, 500, 500, 500, 1),
+            Dex::remove_liquidity(
+                RuntimeOrigin::none(),
+                ASSET_A,
+                500,
+                500,
+                500,
+                Deadline::Block(1)
+            ),
             frame_support::error::BadOrigin
         );
     });
@@ -289,7 +442,14 @@ fn remove_liquidity_unsigned() {
 fn remove_liquidity_deadline_passed() {
     new_test_ext().execute_with(|| {
         assert_noop!(
-            Dex::remove_liquidity(RuntimeOrigin::signed(ACCOUNT_A), ASSET_A, 500, 500, 500, 0),
+            Dex::remove_liquidity(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                ASSET_A,
+                500,
+                500,
+                500,
+                Deadline::Block(0)
+            ),
             Error::<Test>::DeadlinePassed
         );
     });
@@ -299,7 +459,14 @@ fn remove_liquidity_deadline_passed() {
 fn remove_zero_liquidity() {
     new_test_ext().execute_with(|| {
         assert_noop!(
-            Dex::remove_liquidity(RuntimeOrigin::signed(ACCOUNT_A), ASSET_A, 0, 500, 500, 1),
+            Dex::remove_liquidity(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                ASSET_A,
+                0,
+                500,
+                500,
+                Deadline::Block(1)
+            ),
             crate::Error::<Test>::LiquidityAmountIsZero
         );
     });
@@ -309,7 +476,14 @@ fn remove_zero_liquidity() {
 fn remove_liquidity_min_currency_zero() {
     new_test_ext().execute_with(|| {
         assert_noop!(
-            Dex::remove_liquidity(RuntimeOrigin::signed(ACCOUNT_A), ASSET_A, 500, 0, 500, 1),
+            Dex::remove_liquidity(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                ASSET_A,
+                500,
+                0,
+                500,
+                Deadline::Block(1)
+            ),
             crate::Error::<Test>::MinCurrencyIsZero
         );
     });
@@ -319,7 +493,14 @@ fn remove_liquidity_min_currency_zero() {
 fn remove_liquidity_min_tokens_zero() {
     new_test_ext().execute_with(|| {
         assert_noop!(
-            Dex::remove_liquidity(RuntimeOrigin::signed(ACCOUNT_A), ASSET_A, 500, 500, 0, 1),
+            Dex::remove_liquidity(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                ASSET_A,
+                500,
+                500,
+                0,
+                Deadline::Block(1)
+            ),
             crate::Error::<Test>::MinTokensIsZero
         );
     });
@@ -329,7 +510,14 @@ fn remove_liquidity_min_tokens_zero() {
 fn remove_liquidity_exchange_not_found() {
     new_test_ext().execute_with(|| {
         assert_noop!(
-            Dex::remove_liquidity(RuntimeOrigin::signed(ACCOUNT_A), ASSET_B, 500, 500, 500, 1),
+            Dex::remove_liquidity(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                ASSET_B,
+                500,
+                500,
+                500,
+                Deadline::Block(1)
+            ),
             crate::Error::<Test>::ExchangeNotFound
         );
     });
@@ -345,7 +533,7 @@ fn remove_liquidity_provider_liquidity_too_low() {
                 INIT_LIQUIDITY + 500,
                 INIT_LIQUIDITY + 500,
                 INIT_LIQUIDITY + 500,
-                1
+                Deadline::Block(1)
             ),
             crate::Error::<Test>::ProviderLiquidityTooLow
         );
@@ -356,7 +544,14 @@ fn remove_liquidity_provider_liquidity_too_low() {
 fn remove_liquidity_min_currency_too_high() {
     new_test_ext().execute_with(|| {
         assert_noop!(
-            Dex::remove_liquidity(RuntimeOrigin::signed(ACCOUNT_A), ASSET_A, 500, 1_500, 500, 1),
+            Dex::remove_liquidity(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                ASSET_A,
+                500,
+                1_500,
+                500,
+                Deadline::Block(1)
+            ),
             crate::Error::<Test>::MinCurrencyTooHigh
         );
     });
@@ -366,12 +561,41 @@ fn remove_liquidity_min_currency_too_high() {
 fn remove_liquidity_min_tokens_too_high() {
     new_test_ext().execute_with(|| {
         assert_noop!(
-            Dex::remove_liquidity(RuntimeOrigin::signed(ACCOUNT_A), ASSET_A, 500, 500, 1_500, 1),
+            Dex::remove_liquidity(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                ASSET_A,
+                500,
+                500,
+                1_500,
+                Deadline::Block(1)
+            ),
             crate::Error::<Test>::MinTokensTooHigh
         );
     });
 }
 
+#[test]
+fn pallet_account_never_reaped() {
+    new_test_ext().execute_with(|| {
+        let pallet_account = <Test as ConfigHelper>::pallet_account();
+        // Genesis provider reference, and the one of the currency balance
+        assert_eq!(System::providers(&pallet_account), 2);
+        assert_eq!(System::providers(&<Test as ConfigHelper<Instance1>>::pallet_account()), 1);
+
+        // Removing all liquidity empties the pallet account
+        assert_ok!(Dex::remove_liquidity(
+            RuntimeOrigin::signed(ACCOUNT_A),
+            ASSET_A,
+            INIT_LIQUIDITY,
+            1,
+            1,
+            Deadline::Block(1),
+        ));
+        assert_eq!(Balances::free_balance(pallet_account), 0);
+        assert!(System::account_exists(&pallet_account));
+    });
+}
+
 #[test]
 fn currency_to_asset_fixed_input() {
     new_test_ext().execute_with(|| {
@@ -385,8 +609,10 @@ fn currency_to_asset_fixed_input() {
                 input_amount: curr_amount,
                 min_output: token_amount
             },
-            1,
-            None
+            Deadline::Block(1),
+            None,
+            None,
+            false
         ));
 
         let exchange = Dex::exchanges(ASSET_A).unwrap();
@@ -394,7 +620,7 @@ fn currency_to_asset_fixed_input() {
         assert_eq!(exchange.token_reserve, INIT_LIQUIDITY - token_amount);
         assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE - curr_amount);
         assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_B), Some(INIT_BALANCE + token_amount));
-        let pallet_account = Test::pallet_account();
+        let pallet_account = <Test as ConfigHelper>::pallet_account();
         assert_eq!(Balances::free_balance(pallet_account), INIT_LIQUIDITY + curr_amount);
         assert_eq!(
             Assets::maybe_balance(ASSET_A, pallet_account),
@@ -408,6 +634,8 @@ fn currency_to_asset_fixed_input() {
                 ACCOUNT_B,
                 curr_amount,
                 token_amount,
+                PROVIDER_FEE,
+                None,
             )
         );
     });
@@ -426,8 +654,10 @@ fn currency_to_asset_fixed_output() {
                 max_input: curr_amount,
                 output_amount: token_amount,
             },
-            1,
-            None
+            Deadline::Block(1),
+            None,
+            None,
+            false
         ));
 
         let exchange = Dex::exchanges(ASSET_A).unwrap();
@@ -435,7 +665,7 @@ fn currency_to_asset_fixed_output() {
         assert_eq!(exchange.token_reserve, INIT_LIQUIDITY - token_amount);
         assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE - curr_amount);
         assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_B), Some(INIT_BALANCE + token_amount));
-        let pallet_account = Test::pallet_account();
+        let pallet_account = <Test as ConfigHelper>::pallet_account();
         assert_eq!(Balances::free_balance(pallet_account), INIT_LIQUIDITY + curr_amount);
         assert_eq!(
             Assets::maybe_balance(ASSET_A, pallet_account),
@@ -449,6 +679,8 @@ fn currency_to_asset_fixed_output() {
                 ACCOUNT_B,
                 curr_amount,
                 token_amount,
+                PROVIDER_FEE,
+                None,
             )
         );
     });
@@ -465,8 +697,10 @@ fn currency_to_asset_unsigned() {
                     input_amount: 1,
                     min_output: 1
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             frame_support::error::BadOrigin
         );
@@ -484,14 +718,108 @@ fn currency_to_asset_deadline_passed() {
                     input_amount: 1,
                     min_output: 1
                 },
-                0,
-                None
+                Deadline::Block(0),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::DeadlinePassed
         );
     });
 }
 
+#[test]
+fn currency_to_asset_moment_deadline() {
+    new_test_ext().execute_with(|| {
+        let amount = TradeAmount::FixedInput {
+            input_amount: 1_000,
+            min_output: 996,
+        };
+        assert_noop!(
+            Dex::currency_to_asset(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                amount.clone(),
+                Deadline::Moment(NOW - 1),
+                None,
+                None,
+                false
+            ),
+            Error::<Test>::DeadlinePassed
+        );
+        assert_ok!(Dex::currency_to_asset(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            amount,
+            Deadline::Moment(NOW),
+            None,
+            None,
+            false
+        ));
+        assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_B), Some(INIT_BALANCE + 996));
+    });
+}
+
+#[test]
+fn currency_to_asset_moment_deadline_unsupported() {
+    new_test_ext().execute_with(|| {
+        MockTime::set(None);
+        assert_noop!(
+            Dex::currency_to_asset(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                TradeAmount::FixedInput {
+                    input_amount: 1_000,
+                    min_output: 1
+                },
+                Deadline::Moment(u64::MAX),
+                None,
+                None,
+                false
+            ),
+            Error::<Test>::TimestampDeadlineUnsupported
+        );
+    });
+}
+
+#[test]
+fn deadline_encoding() {
+    // Variant indices are fixed, so that adding variants doesn't change the encoding
+    assert_eq!(Deadline::<u64>::Block(1).encode(), [vec![0], 1u64.encode()].concat());
+    assert_eq!(Deadline::<u64>::Moment(NOW).encode(), [vec![1], NOW.encode()].concat());
+    assert_eq!(Deadline::from(5u64), Deadline::Block(5));
+}
+
+#[test]
+fn deadline_call_encoding() {
+    // The deadline of a call is encoded with its variant byte, so calls encoded with a plain block
+    // number deadline (before timestamp deadlines) don't decode anymore
+    let call = crate::Call::<Test>::currency_to_asset {
+        asset_id: ASSET_A,
+        amount: TradeAmount::FixedInput {
+            input_amount: 1_000,
+            min_output: 1,
+        },
+        deadline: Deadline::Block(5),
+        recipient: None,
+        referrer: None,
+        keep_alive: false,
+    };
+    let amount = [vec![0], 1_000u128.encode(), 1u128.encode()].concat();
+    let encoded = [
+        vec![3],
+        ASSET_A.encode(),
+        amount.clone(),
+        vec![0],
+        5u64.encode(),
+        vec![0, 0, 0],
+    ];
+    assert_eq!(call.encode(), encoded.concat());
+
+    let legacy = [vec![3], ASSET_A.encode(), amount, 5u64.encode(), vec![0]].concat();
+    assert!(crate::Call::<Test>::decode(&mut &legacy[..]).is_err());
+}
+
 #[test]
 fn currency_to_asset_currency_amount_zero() {
     new_test_ext().execute_with(|| {
@@ -503,8 +831,10 @@ fn currency_to_asset_currency_amount_zero() {
                     input_amount: 0,
                     min_output: 100
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::TradeAmountIsZero
         );
@@ -522,8 +852,10 @@ fn currency_to_asset_min_tokens_zero() {
                     input_amount: 100,
                     min_output: 0
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::TradeAmountIsZero
         );
@@ -541,8 +873,10 @@ fn currency_to_asset_max_currency_zero() {
                     max_input: 0,
                     output_amount: 100
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::TradeAmountIsZero
         );
@@ -560,8 +894,10 @@ fn currency_to_asset_token_amount_zero() {
                     max_input: 100,
                     output_amount: 0
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::TradeAmountIsZero
         );
@@ -574,7 +910,7 @@ fn currency_to_asset_balance_too_low() {
         let currency_amount = 500;
         let min_tokens = 498; // currency amount (500) - provider fee (0.3%) should be ~498
 
-        <Test as crate::Config>::Currency::make_free_balance_be(&ACCOUNT_B, 1);
+        Balances::make_free_balance_be(&ACCOUNT_B, 1);
         assert_noop!(
             Dex::currency_to_asset(
                 RuntimeOrigin::signed(ACCOUNT_B),
@@ -583,14 +919,48 @@ fn currency_to_asset_balance_too_low() {
                     input_amount: currency_amount,
                     min_output: min_tokens,
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::BalanceTooLow
         );
     });
 }
 
+#[test]
+fn currency_to_asset_keep_alive() {
+    new_test_ext().execute_with(|| {
+        let amount = TradeAmount::FixedInput {
+            input_amount: INIT_BALANCE,
+            min_output: 1,
+        };
+        assert_noop!(
+            Dex::currency_to_asset(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                amount.clone(),
+                Deadline::Block(1),
+                None,
+                None,
+                true
+            ),
+            Error::<Test>::BalanceTooLow
+        );
+        assert_ok!(Dex::currency_to_asset(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            amount,
+            Deadline::Block(1),
+            None,
+            None,
+            false
+        ));
+        assert_eq!(Balances::free_balance(ACCOUNT_B), 0);
+    });
+}
+
 #[test]
 fn currency_to_asset_exchange_not_found() {
     new_test_ext().execute_with(|| {
@@ -602,8 +972,10 @@ fn currency_to_asset_exchange_not_found() {
                     input_amount: 1,
                     min_output: 1
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::ExchangeNotFound
         );
@@ -621,8 +993,10 @@ fn currency_to_asset_min_tokens_too_high() {
                     input_amount: 10,
                     min_output: 50
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::MinTokensTooHigh
         );
@@ -640,8 +1014,10 @@ fn currency_to_asset_max_currency_too_low() {
                     max_input: 10,
                     output_amount: 50
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::MaxCurrencyTooLow
         );
@@ -659,8 +1035,10 @@ fn currency_to_asset_not_enough_liquidity() {
                     max_input: INIT_LIQUIDITY + 1000,
                     output_amount: INIT_LIQUIDITY + 1000
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::NotEnoughLiquidity
         );
@@ -680,8 +1058,10 @@ fn currency_to_asset_transfer() {
                 input_amount: curr_amount,
                 min_output: token_amount,
             },
-            1,
-            Some(ACCOUNT_C)
+            Deadline::Block(1),
+            Some(ACCOUNT_C),
+            None,
+            false
         ));
 
         assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE - curr_amount);
@@ -695,6 +1075,8 @@ fn currency_to_asset_transfer() {
                 ACCOUNT_C,
                 curr_amount,
                 token_amount,
+                PROVIDER_FEE,
+                None,
             )
         );
     });
@@ -713,8 +1095,10 @@ fn asset_to_currency_fixed_input() {
                 input_amount: token_amount,
                 min_output: curr_amount
             },
-            1,
-            None
+            Deadline::Block(1),
+            None,
+            None,
+            false
         ));
 
         let exchange = Dex::exchanges(ASSET_A).unwrap();
@@ -722,7 +1106,7 @@ fn asset_to_currency_fixed_input() {
         assert_eq!(exchange.token_reserve, INIT_LIQUIDITY + token_amount);
         assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE + curr_amount);
         assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_B), Some(INIT_BALANCE - token_amount));
-        let pallet_account = Test::pallet_account();
+        let pallet_account = <Test as ConfigHelper>::pallet_account();
         assert_eq!(Balances::free_balance(pallet_account), INIT_LIQUIDITY - curr_amount);
         assert_eq!(
             Assets::maybe_balance(ASSET_A, pallet_account),
@@ -736,6 +1120,8 @@ fn asset_to_currency_fixed_input() {
                 ACCOUNT_B,
                 curr_amount,
                 token_amount,
+                PROVIDER_FEE,
+                None,
             )
         );
     });
@@ -752,8 +1138,10 @@ fn asset_to_currency_unsigned() {
                     input_amount: 1,
                     min_output: 1
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             frame_support::error::BadOrigin
         );
@@ -771,8 +1159,10 @@ fn asset_to_currency_deadline_passed() {
                     input_amount: 1,
                     min_output: 1
                 },
-                0,
-                None
+                Deadline::Block(0),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::DeadlinePassed
         );
@@ -790,8 +1180,10 @@ fn asset_to_currency_min_currency_zero() {
                     input_amount: 100,
                     min_output: 0
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::TradeAmountIsZero
         );
@@ -809,8 +1201,10 @@ fn asset_to_currency_token_amount_zero() {
                     input_amount: 0,
                     min_output: 100
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::TradeAmountIsZero
         );
@@ -828,8 +1222,10 @@ fn asset_to_currency_currency_amount_zero() {
                     max_input: 100,
                     output_amount: 0
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::TradeAmountIsZero
         );
@@ -847,8 +1243,10 @@ fn asset_to_currency_max_tokens_is_zero() {
                     max_input: 0,
                     output_amount: 100
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::TradeAmountIsZero
         );
@@ -877,14 +1275,53 @@ fn asset_to_currency_not_enough_tokens() {
                     input_amount: token_amount,
                     min_output: min_currency
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::NotEnoughTokens
         );
     });
 }
 
+#[test]
+fn asset_to_currency_below_minimum() {
+    new_test_ext().execute_with(|| {
+        ExistentialDeposit::set(1_000);
+        let new_account = 42;
+        assert_noop!(
+            Dex::asset_to_currency(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                TradeAmount::FixedInput {
+                    input_amount: 500,
+                    min_output: 1
+                },
+                Deadline::Block(1),
+                Some(new_account),
+                None,
+                false
+            ),
+            Error::<Test>::CurrencyBelowMinimum
+        );
+        // Enough currency to create the recipient's account
+        assert_ok!(Dex::asset_to_currency(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            TradeAmount::FixedOutput {
+                output_amount: 1_000,
+                max_input: 2_000
+            },
+            Deadline::Block(1),
+            Some(new_account),
+            None,
+            false
+        ));
+        assert_eq!(Balances::free_balance(new_account), 1_000);
+    });
+}
+
 #[test]
 fn asset_to_currency_exchange_not_found() {
     new_test_ext().execute_with(|| {
@@ -896,8 +1333,10 @@ fn asset_to_currency_exchange_not_found() {
                     input_amount: 1,
                     min_output: 1
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::ExchangeNotFound
         );
@@ -915,8 +1354,10 @@ fn asset_to_currency_min_currency_too_high() {
                     input_amount: 10,
                     min_output: 50
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::MinCurrencyTooHigh
         );
@@ -934,8 +1375,10 @@ fn asset_to_currency_max_tokens_too_low() {
                     output_amount: 50,
                     max_input: 10
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::MaxTokensTooLow
         );
@@ -953,8 +1396,10 @@ fn asset_to_currency_not_enough_liquidity() {
                     output_amount: INIT_LIQUIDITY + 1000,
                     max_input: INIT_LIQUIDITY + 1000
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::NotEnoughLiquidity
         );
@@ -974,8 +1419,10 @@ fn asset_to_currency_transfer() {
                 input_amount: token_amount,
                 min_output: curr_amount
             },
-            1,
-            Some(ACCOUNT_C)
+            Deadline::Block(1),
+            Some(ACCOUNT_C),
+            None,
+            false
         ));
 
         assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_B), Some(INIT_BALANCE - token_amount));
@@ -989,6 +1436,8 @@ fn asset_to_currency_transfer() {
                 ACCOUNT_C,
                 curr_amount,
                 token_amount,
+                PROVIDER_FEE,
+                None,
             )
         );
     });
@@ -1007,8 +1456,10 @@ fn asset_to_currency_fixed_output() {
                 output_amount: curr_amount,
                 max_input: token_amount
             },
-            1,
-            None
+            Deadline::Block(1),
+            None,
+            None,
+            false
         ));
 
         let exchange = Dex::exchanges(ASSET_A).unwrap();
@@ -1016,7 +1467,7 @@ fn asset_to_currency_fixed_output() {
         assert_eq!(exchange.token_reserve, INIT_LIQUIDITY + token_amount);
         assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE + curr_amount);
         assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_B), Some(INIT_BALANCE - token_amount));
-        let pallet_account = Test::pallet_account();
+        let pallet_account = <Test as ConfigHelper>::pallet_account();
         assert_eq!(Balances::free_balance(pallet_account), INIT_LIQUIDITY - curr_amount);
         assert_eq!(
             Assets::maybe_balance(ASSET_A, pallet_account),
@@ -1030,6 +1481,8 @@ fn asset_to_currency_fixed_output() {
                 ACCOUNT_B,
                 curr_amount,
                 token_amount,
+                PROVIDER_FEE,
+                None,
             )
         );
     });
@@ -1044,6 +1497,7 @@ fn asset_to_asset_fixed_input() {
             LIQ_TOKEN_B,
             INIT_LIQUIDITY,
             INIT_LIQUIDITY,
+            Curve::ConstantProduct,
         )
         .unwrap();
 
@@ -1059,8 +1513,10 @@ fn asset_to_asset_fixed_input() {
                 input_amount: sold_token_amount,
                 min_output: bought_token_amount,
             },
-            1,
-            None
+            Deadline::Block(1),
+            None,
+            None,
+            false
         ));
 
         let exchange_a = Dex::exchanges(ASSET_A).unwrap();
@@ -1080,7 +1536,7 @@ fn asset_to_asset_fixed_input() {
             Some(INIT_BALANCE + bought_token_amount)
         );
 
-        let pallet_account = Test::pallet_account();
+        let pallet_account = <Test as ConfigHelper>::pallet_account();
         assert_eq!(Balances::free_balance(pallet_account), INIT_LIQUIDITY + INIT_LIQUIDITY);
         assert_eq!(
             Assets::maybe_balance(ASSET_A, pallet_account),
@@ -1100,6 +1556,8 @@ fn asset_to_asset_fixed_input() {
                     pallet_account,
                     curr_amount,
                     sold_token_amount,
+                    PROVIDER_FEE,
+                    None,
                 ),
                 crate::Event::CurrencyTradedForAsset(
                     ASSET_B,
@@ -1107,6 +1565,8 @@ fn asset_to_asset_fixed_input() {
                     ACCOUNT_B,
                     curr_amount,
                     bought_token_amount,
+                    PROVIDER_FEE,
+                    None,
                 ),
             ]
         );
@@ -1125,8 +1585,10 @@ fn asset_to_asset_unsigned() {
                     input_amount: 1,
                     min_output: 1
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             frame_support::error::BadOrigin
         );
@@ -1145,8 +1607,10 @@ fn asset_to_asset_deadline_passed() {
                     input_amount: 1,
                     min_output: 1
                 },
-                0,
-                None
+                Deadline::Block(0),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::DeadlinePassed
         );
@@ -1165,8 +1629,10 @@ fn asset_to_asset_sold_token_amount_zero() {
                     input_amount: 0,
                     min_output: 1
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::TradeAmountIsZero
         );
@@ -1185,8 +1651,10 @@ fn asset_to_asset_min_bought_tokens_zero() {
                     input_amount: 1,
                     min_output: 0
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::TradeAmountIsZero
         );
@@ -1205,8 +1673,10 @@ fn asset_to_asset_max_sold_tokens_amount_zero() {
                     output_amount: 1,
                     max_input: 0
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::TradeAmountIsZero
         );
@@ -1225,8 +1695,10 @@ fn asset_to_asset_output_bought_token_amount_zero() {
                     output_amount: 0,
                     max_input: 1
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::TradeAmountIsZero
         );
@@ -1242,6 +1714,7 @@ fn asset_to_asset_not_enough_tokens() {
             LIQ_TOKEN_B,
             INIT_LIQUIDITY,
             INIT_LIQUIDITY,
+            Curve::ConstantProduct,
         )
         .unwrap();
 
@@ -1267,8 +1740,10 @@ fn asset_to_asset_not_enough_tokens() {
                     input_amount: sold_token_amount,
                     min_output: bought_token_amount,
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::NotEnoughTokens
         );
@@ -1287,8 +1762,10 @@ fn asset_to_asset_sold_asset_exchange_not_found() {
                     input_amount: 1,
                     min_output: 1
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::ExchangeNotFound
         );
@@ -1307,8 +1784,10 @@ fn asset_to_asset_bought_asset_exchange_not_found() {
                     input_amount: 1,
                     min_output: 1
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::ExchangeNotFound
         );
@@ -1324,6 +1803,7 @@ fn asset_to_asset_min_bought_tokens_too_high() {
             LIQ_TOKEN_B,
             INIT_LIQUIDITY,
             INIT_LIQUIDITY,
+            Curve::ConstantProduct,
         )
         .unwrap();
         assert_noop!(
@@ -1335,8 +1815,10 @@ fn asset_to_asset_min_bought_tokens_too_high() {
                     input_amount: 10,
                     min_output: 50
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::MinBoughtTokensTooHigh
         );
@@ -1352,6 +1834,7 @@ fn asset_to_asset_max_sold_tokens_too_low() {
             LIQ_TOKEN_B,
             INIT_LIQUIDITY,
             INIT_LIQUIDITY,
+            Curve::ConstantProduct,
         )
         .unwrap();
         assert_noop!(
@@ -1363,8 +1846,10 @@ fn asset_to_asset_max_sold_tokens_too_low() {
                     output_amount: 50,
                     max_input: 10
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::MaxSoldTokensTooLow
         );
@@ -1380,6 +1865,7 @@ fn asset_to_asset_not_enough_liquidity() {
             LIQ_TOKEN_B,
             INIT_LIQUIDITY,
             INIT_LIQUIDITY,
+            Curve::ConstantProduct,
         )
         .unwrap();
         assert_noop!(
@@ -1391,8 +1877,10 @@ fn asset_to_asset_not_enough_liquidity() {
                     output_amount: INIT_LIQUIDITY + 1000,
                     max_input: INIT_LIQUIDITY + 1000
                 },
-                1,
-                None
+                Deadline::Block(1),
+                None,
+                None,
+                false
             ),
             crate::Error::<Test>::NotEnoughLiquidity
         );
@@ -1408,6 +1896,7 @@ fn asset_to_asset_transfer() {
             LIQ_TOKEN_B,
             INIT_LIQUIDITY,
             INIT_LIQUIDITY,
+            Curve::ConstantProduct,
         )
         .unwrap();
 
@@ -1423,8 +1912,10 @@ fn asset_to_asset_transfer() {
                 input_amount: sold_token_amount,
                 min_output: bought_token_amount
             },
-            1,
-            Some(ACCOUNT_C)
+            Deadline::Block(1),
+            Some(ACCOUNT_C),
+            None,
+            false
         ));
 
         assert_eq!(
@@ -1436,7 +1927,7 @@ fn asset_to_asset_transfer() {
             Some(INIT_BALANCE + bought_token_amount)
         );
 
-        let pallet_account = Test::pallet_account();
+        let pallet_account = <Test as ConfigHelper>::pallet_account();
         assert_eq!(
             last_n_events(2),
             vec![
@@ -1446,6 +1937,8 @@ fn asset_to_asset_transfer() {
                     pallet_account,
                     curr_amount,
                     sold_token_amount,
+                    PROVIDER_FEE,
+                    None,
                 ),
                 crate::Event::CurrencyTradedForAsset(
                     ASSET_B,
@@ -1453,6 +1946,8 @@ fn asset_to_asset_transfer() {
                     ACCOUNT_C,
                     curr_amount,
                     bought_token_amount,
+                    PROVIDER_FEE,
+                    None,
                 ),
             ]
         );
@@ -1468,6 +1963,7 @@ fn asset_to_asset_fixed_output() {
             LIQ_TOKEN_B,
             INIT_LIQUIDITY,
             INIT_LIQUIDITY,
+            Curve::ConstantProduct,
         )
         .unwrap();
 
@@ -1483,8 +1979,10 @@ fn asset_to_asset_fixed_output() {
                 output_amount: bought_token_amount,
                 max_input: sold_token_amount
             },
-            1,
-            None
+            Deadline::Block(1),
+            None,
+            None,
+            false
         ));
 
         let exchange_a = Dex::exchanges(ASSET_A).unwrap();
@@ -1504,7 +2002,7 @@ fn asset_to_asset_fixed_output() {
             Some(INIT_BALANCE + bought_token_amount)
         );
 
-        let pallet_account = Test::pallet_account();
+        let pallet_account = <Test as ConfigHelper>::pallet_account();
         assert_eq!(Balances::free_balance(pallet_account), INIT_LIQUIDITY + INIT_LIQUIDITY);
         assert_eq!(
             Assets::maybe_balance(ASSET_A, pallet_account),
@@ -1524,6 +2022,8 @@ fn asset_to_asset_fixed_output() {
                     pallet_account,
                     curr_amount,
                     sold_token_amount,
+                    PROVIDER_FEE,
+                    None,
                 ),
                 crate::Event::CurrencyTradedForAsset(
                     ASSET_B,
@@ -1531,6 +2031,8 @@ fn asset_to_asset_fixed_output() {
                     ACCOUNT_B,
                     curr_amount,
                     bought_token_amount,
+                    PROVIDER_FEE,
+                    None,
                 ),
             ]
         );
@@ -1546,6 +2048,7 @@ fn trade_assets_back_and_forth() {
             LIQ_TOKEN_B,
             INIT_LIQUIDITY,
             INIT_LIQUIDITY,
+            Curve::ConstantProduct,
         )
         .unwrap();
 
@@ -1562,8 +2065,10 @@ fn trade_assets_back_and_forth() {
                 output_amount: bought_token_amount,
                 max_input: sold_token_amount,
             },
-            1,
-            None
+            Deadline::Block(1),
+            None,
+            None,
+            false
         ));
         assert_ok!(Dex::asset_to_asset(
             RuntimeOrigin::signed(ACCOUNT_B),
@@ -1573,8 +2078,10 @@ fn trade_assets_back_and_forth() {
                 output_amount: bought_token_amount,
                 max_input: sold_token_amount,
             },
-            1,
-            None
+            Deadline::Block(1),
+            None,
+            None,
+            false
         ));
 
         // Remove all liquidity
@@ -1584,7 +2091,7 @@ fn trade_assets_back_and_forth() {
             INIT_LIQUIDITY,
             INIT_LIQUIDITY,
             INIT_LIQUIDITY + 4,
-            1,
+            Deadline::Block(1),
         ));
         assert_ok!(Dex::remove_liquidity(
             RuntimeOrigin::signed(ACCOUNT_A),
@@ -1592,7 +2099,7 @@ fn trade_assets_back_and_forth() {
             INIT_LIQUIDITY,
             INIT_LIQUIDITY,
             INIT_LIQUIDITY + 4,
-            1,
+            Deadline::Block(1),
         ));
 
         // Account A should have received 4 (500-496) of both tokens as tx fees from account B
@@ -1603,3 +2110,4528 @@ fn trade_assets_back_and_forth() {
         assert_eq!(Assets::maybe_balance(ASSET_B, ACCOUNT_B), Some(INIT_BALANCE - 4));
     });
 }
+
+fn half() -> FixedU128 {
+    FixedU128::saturating_from_rational(1, 2)
+}
+
+#[test]
+fn place_limit_order_sell_asset() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::place_limit_order(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            OrderAmount::SellAsset(1_000),
+            half(),
+            10,
+            false
+        ));
+
+        let order = Dex::limit_orders(0).unwrap();
+        assert_eq!(order.owner, ACCOUNT_B);
+        assert_eq!(order.asset_id, ASSET_A);
+        assert_eq!(order.amount, OrderAmount::SellAsset(1_000));
+        assert_eq!(order.limit_price, half());
+        assert_eq!(order.expiry, 10);
+        assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_B), Some(INIT_BALANCE - 1_000));
+        let pallet_account = <Test as ConfigHelper>::pallet_account();
+        assert_eq!(Assets::maybe_balance(ASSET_A, pallet_account), Some(INIT_LIQUIDITY + 1_000));
+        let exchange = Dex::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY);
+        assert_eq!(last_event(), crate::Event::LimitOrderPlaced(0, ACCOUNT_B, ASSET_A));
+    })
+}
+
+#[test]
+fn place_limit_order_sell_currency() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::place_limit_order(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            OrderAmount::SellCurrency(1_000),
+            half(),
+            10,
+            false
+        ));
+        assert_ok!(Dex::place_limit_order(
+            RuntimeOrigin::signed(ACCOUNT_C),
+            ASSET_A,
+            OrderAmount::SellCurrency(1_000),
+            half(),
+            10,
+            false
+        ));
+
+        assert_eq!(Dex::limit_orders(1).unwrap().owner, ACCOUNT_C);
+        assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE - 1_000);
+        let pallet_account = <Test as ConfigHelper>::pallet_account();
+        assert_eq!(Balances::free_balance(pallet_account), INIT_LIQUIDITY + 2_000);
+        let exchange = Dex::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY);
+        assert_eq!(last_event(), crate::Event::LimitOrderPlaced(1, ACCOUNT_C, ASSET_A));
+    })
+}
+
+#[test]
+fn place_limit_order_unsigned() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::place_limit_order(
+                RuntimeOrigin::none(),
+                ASSET_A,
+                OrderAmount::SellAsset(1_000),
+                half(),
+                10,
+                false
+            ),
+            frame_support::error::BadOrigin
+        );
+    })
+}
+
+#[test]
+fn place_limit_order_expiry_passed() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::place_limit_order(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                OrderAmount::SellAsset(1_000),
+                half(),
+                0,
+                false
+            ),
+            Error::<Test>::DeadlinePassed
+        );
+    })
+}
+
+#[test]
+fn place_limit_order_amount_zero() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::place_limit_order(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                OrderAmount::SellCurrency(0),
+                half(),
+                10,
+                false
+            ),
+            Error::<Test>::TradeAmountIsZero
+        );
+        assert_noop!(
+            Dex::place_limit_order(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                OrderAmount::SellAsset(0),
+                half(),
+                10,
+                false
+            ),
+            Error::<Test>::TradeAmountIsZero
+        );
+    })
+}
+
+#[test]
+fn place_limit_order_price_zero() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::place_limit_order(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                OrderAmount::SellAsset(1_000),
+                FixedU128::zero(),
+                10,
+                false
+            ),
+            Error::<Test>::LimitPriceIsZero
+        );
+    })
+}
+
+#[test]
+fn place_limit_order_exchange_not_found() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::place_limit_order(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_B,
+                OrderAmount::SellAsset(1_000),
+                half(),
+                10,
+                false
+            ),
+            Error::<Test>::ExchangeNotFound
+        );
+    })
+}
+
+#[test]
+fn place_limit_order_stable_swap() {
+    new_test_ext().execute_with(|| {
+        create_stable_swap_exchange();
+        assert_noop!(
+            Dex::place_limit_order(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_B,
+                OrderAmount::SellAsset(1_000),
+                half(),
+                10,
+                false
+            ),
+            Error::<Test>::UnsupportedCurve
+        );
+    })
+}
+
+#[test]
+fn place_limit_order_not_enough_funds() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::place_limit_order(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                OrderAmount::SellCurrency(INIT_BALANCE + 1),
+                half(),
+                10,
+                false
+            ),
+            Error::<Test>::BalanceTooLow
+        );
+        assert_noop!(
+            Dex::place_limit_order(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                OrderAmount::SellAsset(INIT_BALANCE + 1),
+                half(),
+                10,
+                false
+            ),
+            Error::<Test>::NotEnoughTokens
+        );
+    })
+}
+
+#[test]
+fn place_limit_order_keep_alive() {
+    new_test_ext().execute_with(|| {
+        let amount = OrderAmount::SellCurrency(INIT_BALANCE);
+        assert_noop!(
+            Dex::place_limit_order(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                amount.clone(),
+                half(),
+                10,
+                true
+            ),
+            Error::<Test>::BalanceTooLow
+        );
+        assert_ok!(Dex::place_limit_order(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            amount,
+            half(),
+            10,
+            false
+        ));
+        assert_eq!(Balances::free_balance(ACCOUNT_B), 0);
+    })
+}
+
+#[test]
+fn cancel_limit_order() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::place_limit_order(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            OrderAmount::SellAsset(1_000),
+            half(),
+            10,
+            false
+        ));
+        assert_ok!(Dex::cancel_limit_order(RuntimeOrigin::signed(ACCOUNT_B), 0));
+
+        assert!(Dex::limit_orders(0).is_none());
+        assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_B), Some(INIT_BALANCE));
+        let pallet_account = <Test as ConfigHelper>::pallet_account();
+        assert_eq!(Assets::maybe_balance(ASSET_A, pallet_account), Some(INIT_LIQUIDITY));
+        assert_eq!(last_event(), crate::Event::LimitOrderCancelled(0, ACCOUNT_B));
+    })
+}
+
+#[test]
+fn cancel_limit_order_not_found() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::cancel_limit_order(RuntimeOrigin::signed(ACCOUNT_B), 0),
+            Error::<Test>::LimitOrderNotFound
+        );
+    })
+}
+
+#[test]
+fn cancel_limit_order_not_owner() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::place_limit_order(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            OrderAmount::SellAsset(1_000),
+            half(),
+            10,
+            false
+        ));
+        assert_noop!(
+            Dex::cancel_limit_order(RuntimeOrigin::signed(ACCOUNT_C), 0),
+            Error::<Test>::NotLimitOrderOwner
+        );
+    })
+}
+
+#[test]
+fn limit_order_filled() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::place_limit_order(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            OrderAmount::SellAsset(1_000),
+            half(),
+            10,
+            false
+        ));
+        Dex::on_idle(1, Weight::MAX);
+
+        assert!(Dex::limit_orders(0).is_none());
+        let exchange = Dex::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY - 996);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY + 1_000);
+        assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE + 996);
+        assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_B), Some(INIT_BALANCE - 1_000));
+        let pallet_account = <Test as ConfigHelper>::pallet_account();
+        assert_eq!(Balances::free_balance(pallet_account), INIT_LIQUIDITY - 996);
+        assert_eq!(Assets::maybe_balance(ASSET_A, pallet_account), Some(INIT_LIQUIDITY + 1_000));
+        assert_eq!(last_event(), crate::Event::LimitOrderFilled(0, ACCOUNT_B, ASSET_A, 996, 1_000));
+    })
+}
+
+#[test]
+fn limit_order_partially_filled() {
+    new_test_ext().execute_with(|| {
+        // The limit price is crossed after selling ~0.7% of the pool
+        let limit_price = FixedU128::saturating_from_rational(99, 100);
+        assert_ok!(Dex::place_limit_order(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            OrderAmount::SellCurrency(10_000_000_000),
+            limit_price,
+            10,
+            false
+        ));
+        Dex::on_idle(1, Weight::MAX);
+
+        let currency_amount = 7_091_983_019;
+        let token_amount = 7_021_063_188;
+        let order = Dex::limit_orders(0).unwrap();
+        assert_eq!(order.amount, OrderAmount::SellCurrency(10_000_000_000 - currency_amount));
+        let exchange = Dex::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY + currency_amount);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY - token_amount);
+        assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_B), Some(INIT_BALANCE + token_amount));
+        assert_eq!(
+            last_event(),
+            crate::Event::LimitOrderFilled(0, ACCOUNT_B, ASSET_A, currency_amount, token_amount)
+        );
+
+        // Price doesn't satisfy the order anymore
+        Dex::on_idle(2, Weight::MAX);
+        assert_eq!(Dex::limit_orders(0), Some(order));
+        assert_eq!(Dex::exchanges(ASSET_A), Some(exchange));
+    })
+}
+
+#[test]
+fn limit_order_not_filled() {
+    new_test_ext().execute_with(|| {
+        let limit_price = FixedU128::saturating_from_integer(2);
+        assert_ok!(Dex::place_limit_order(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            OrderAmount::SellAsset(1_000),
+            limit_price,
+            10,
+            false
+        ));
+        Dex::on_idle(1, Weight::MAX);
+
+        assert_eq!(Dex::limit_orders(0).unwrap().amount, OrderAmount::SellAsset(1_000));
+        let exchange = Dex::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY);
+        assert_eq!(last_event(), crate::Event::LimitOrderPlaced(0, ACCOUNT_B, ASSET_A));
+    })
+}
+
+#[test]
+fn limit_order_expired() {
+    new_test_ext().execute_with(|| {
+        let limit_price = FixedU128::saturating_from_integer(2);
+        assert_ok!(Dex::place_limit_order(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            OrderAmount::SellCurrency(1_000),
+            limit_price,
+            1,
+            false
+        ));
+        System::set_block_number(2);
+        Dex::on_idle(2, Weight::MAX);
+
+        assert!(Dex::limit_orders(0).is_none());
+        assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE);
+        assert_eq!(
+            Balances::free_balance(<Test as ConfigHelper>::pallet_account()),
+            INIT_LIQUIDITY
+        );
+        assert_eq!(last_event(), crate::Event::LimitOrderExpired(0, ACCOUNT_B));
+    })
+}
+
+#[test]
+fn limit_orders_matching_weight_bounded() {
+    new_test_ext().execute_with(|| {
+        for _ in 0..3 {
+            assert_ok!(Dex::place_limit_order(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                OrderAmount::SellAsset(1_000),
+                half(),
+                10,
+                false
+            ));
+        }
+        let order_weight = <Test as crate::Config>::WeightInfo::match_limit_order();
+        let base_weight = <Test as frame_system::Config>::DbWeight::get().reads_writes(1, 1);
+        let remaining_weight = base_weight + order_weight * 2;
+
+        let open_orders = || {
+            (0..3u64)
+                .filter(|id| Dex::limit_orders(id).is_some())
+                .count()
+        };
+
+        // Not enough weight to match a single order
+        let too_low_weight = base_weight + order_weight - Weight::from_parts(1, 0);
+        assert_eq!(Dex::on_idle(1, too_low_weight), Weight::zero());
+        assert_eq!(open_orders(), 3);
+
+        assert_eq!(Dex::on_idle(1, remaining_weight), remaining_weight);
+        assert_eq!(open_orders(), 1);
+
+        // Matching continues from the next order
+        Dex::on_idle(2, remaining_weight);
+        assert_eq!(open_orders(), 0);
+        assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE + 3 * 996);
+    })
+}
+
+fn one_percent() -> Permill {
+    Permill::from_percent(1)
+}
+
+fn currency_to_asset_dca(currency_amount: u128) -> DcaSwap<u32, u128, u128> {
+    DcaSwap::CurrencyToAsset {
+        asset_id: ASSET_A,
+        currency_amount,
+    }
+}
+
+#[test]
+fn schedule_dca() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::schedule_dca(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            currency_to_asset_dca(1_000),
+            5,
+            3,
+            one_percent(),
+            false
+        ));
+
+        let schedule = Dex::dca_schedules(0).unwrap();
+        assert_eq!(schedule.owner, ACCOUNT_B);
+        assert_eq!(schedule.swap, currency_to_asset_dca(1_000));
+        assert_eq!(schedule.interval, 5);
+        assert_eq!(schedule.remaining_executions, 3);
+        assert_eq!(schedule.max_slippage, one_percent());
+        assert_eq!(schedule.next_execution, 2);
+        assert_eq!(Dex::dca_queue(2).into_inner(), vec![0]);
+        assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE - 3_000);
+        let pallet_account = <Test as ConfigHelper>::pallet_account();
+        assert_eq!(Balances::free_balance(pallet_account), INIT_LIQUIDITY + 3_000);
+        assert_eq!(last_event(), crate::Event::DcaScheduled(0, ACCOUNT_B));
+    })
+}
+
+#[test]
+fn schedule_dca_unsigned() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::schedule_dca(
+                RuntimeOrigin::none(),
+                currency_to_asset_dca(1_000),
+                5,
+                3,
+                one_percent(),
+                false
+            ),
+            frame_support::error::BadOrigin
+        );
+    })
+}
+
+#[test]
+fn schedule_dca_interval_zero() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::schedule_dca(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                currency_to_asset_dca(1_000),
+                0,
+                3,
+                one_percent(),
+                false
+            ),
+            Error::<Test>::DcaIntervalIsZero
+        );
+    })
+}
+
+#[test]
+fn schedule_dca_executions_zero() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::schedule_dca(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                currency_to_asset_dca(1_000),
+                5,
+                0,
+                one_percent(),
+                false
+            ),
+            Error::<Test>::DcaExecutionsIsZero
+        );
+    })
+}
+
+#[test]
+fn schedule_dca_amount_zero() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::schedule_dca(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                currency_to_asset_dca(0),
+                5,
+                3,
+                one_percent(),
+                false
+            ),
+            Error::<Test>::TradeAmountIsZero
+        );
+    })
+}
+
+#[test]
+fn schedule_dca_exchange_not_found() {
+    new_test_ext().execute_with(|| {
+        let swap = DcaSwap::AssetToAsset {
+            sold_asset_id: ASSET_A,
+            bought_asset_id: ASSET_B,
+            token_amount: 1_000,
+        };
+        assert_noop!(
+            Dex::schedule_dca(RuntimeOrigin::signed(ACCOUNT_B), swap, 5, 3, one_percent(), false),
+            Error::<Test>::ExchangeNotFound
+        );
+    })
+}
+
+#[test]
+fn schedule_dca_not_enough_funds() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::schedule_dca(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                currency_to_asset_dca(INIT_BALANCE),
+                5,
+                2,
+                one_percent(),
+                false
+            ),
+            Error::<Test>::BalanceTooLow
+        );
+        let swap = DcaSwap::AssetToCurrency {
+            asset_id: ASSET_A,
+            token_amount: INIT_BALANCE,
+        };
+        assert_noop!(
+            Dex::schedule_dca(RuntimeOrigin::signed(ACCOUNT_B), swap, 5, 2, one_percent(), false),
+            Error::<Test>::NotEnoughTokens
+        );
+    })
+}
+
+#[test]
+fn schedule_dca_overflow() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::schedule_dca(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                currency_to_asset_dca(u128::MAX / 2),
+                5,
+                3,
+                one_percent(),
+                false
+            ),
+            Error::<Test>::Overflow
+        );
+    })
+}
+
+#[test]
+fn schedule_dca_block_full() {
+    new_test_ext().execute_with(|| {
+        for _ in 0..3 {
+            assert_ok!(Dex::schedule_dca(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                currency_to_asset_dca(1_000),
+                5,
+                3,
+                one_percent(),
+                false
+            ));
+        }
+
+        // At most 2 executions per block
+        assert_eq!(Dex::dca_queue(2).into_inner(), vec![0, 1]);
+        assert_eq!(Dex::dca_queue(3).into_inner(), vec![2]);
+        assert_eq!(Dex::dca_schedules(2).unwrap().next_execution, 3);
+
+        Dex::on_initialize(2);
+        assert_eq!(Dex::dca_schedules(0).unwrap().remaining_executions, 2);
+        assert_eq!(Dex::dca_schedules(1).unwrap().remaining_executions, 2);
+        assert_eq!(Dex::dca_schedules(2).unwrap().remaining_executions, 3);
+    })
+}
+
+#[test]
+fn cancel_dca() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::schedule_dca(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            currency_to_asset_dca(1_000),
+            5,
+            3,
+            one_percent(),
+            false
+        ));
+        Dex::on_initialize(2);
+        assert_ok!(Dex::cancel_dca(RuntimeOrigin::signed(ACCOUNT_B), 0));
+
+        assert!(Dex::dca_schedules(0).is_none());
+        assert!(Dex::dca_queue(7).is_empty());
+        // Refund for the two remaining executions
+        assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE - 1_000);
+        let pallet_account = <Test as ConfigHelper>::pallet_account();
+        assert_eq!(Balances::free_balance(pallet_account), INIT_LIQUIDITY + 1_000);
+        assert_eq!(last_event(), crate::Event::DcaCancelled(0, ACCOUNT_B));
+    })
+}
+
+#[test]
+fn cancel_dca_not_found() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::cancel_dca(RuntimeOrigin::signed(ACCOUNT_B), 0),
+            Error::<Test>::DcaScheduleNotFound
+        );
+    })
+}
+
+#[test]
+fn cancel_dca_not_owner() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::schedule_dca(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            currency_to_asset_dca(1_000),
+            5,
+            3,
+            one_percent(),
+            false
+        ));
+        assert_noop!(
+            Dex::cancel_dca(RuntimeOrigin::signed(ACCOUNT_C), 0),
+            Error::<Test>::NotDcaScheduleOwner
+        );
+    })
+}
+
+#[test]
+fn dca_currency_to_asset() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::schedule_dca(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            currency_to_asset_dca(1_000),
+            5,
+            3,
+            one_percent(),
+            false
+        ));
+
+        Dex::on_initialize(2);
+        let schedule = Dex::dca_schedules(0).unwrap();
+        assert_eq!(schedule.remaining_executions, 2);
+        assert_eq!(schedule.next_execution, 7);
+        assert_eq!(Dex::dca_queue(7).into_inner(), vec![0]);
+        assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_B), Some(INIT_BALANCE + 996));
+        assert_eq!(last_event(), crate::Event::DcaExecuted(0, ACCOUNT_B, 2));
+
+        Dex::on_initialize(7);
+        Dex::on_initialize(12);
+        assert!(Dex::dca_schedules(0).is_none());
+        assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE - 3_000);
+        assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_B), Some(INIT_BALANCE + 3 * 996));
+        let exchange = Dex::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY + 3_000);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY - 3 * 996);
+        let pallet_account = <Test as ConfigHelper>::pallet_account();
+        assert_eq!(Balances::free_balance(pallet_account), INIT_LIQUIDITY + 3_000);
+        assert_eq!(
+            last_n_events(2),
+            vec![
+                crate::Event::DcaExecuted(0, ACCOUNT_B, 0),
+                crate::Event::DcaCompleted(0, ACCOUNT_B)
+            ]
+        );
+    })
+}
+
+#[test]
+fn dca_asset_to_currency() {
+    new_test_ext().execute_with(|| {
+        let swap = DcaSwap::AssetToCurrency {
+            asset_id: ASSET_A,
+            token_amount: 1_000,
+        };
+        assert_ok!(Dex::schedule_dca(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            swap,
+            5,
+            3,
+            one_percent(),
+            false
+        ));
+        assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_B), Some(INIT_BALANCE - 3_000));
+
+        Dex::on_initialize(2);
+        assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE + 996);
+        let exchange = Dex::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY - 996);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY + 1_000);
+        assert_eq!(last_event(), crate::Event::DcaExecuted(0, ACCOUNT_B, 2));
+    })
+}
+
+#[test]
+fn dca_asset_to_asset() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::create_exchange(
+            RuntimeOrigin::signed(ACCOUNT_A),
+            ASSET_B,
+            LIQ_TOKEN_B,
+            INIT_LIQUIDITY,
+            INIT_LIQUIDITY,
+            Curve::ConstantProduct,
+        ));
+        let swap = DcaSwap::AssetToAsset {
+            sold_asset_id: ASSET_A,
+            bought_asset_id: ASSET_B,
+            token_amount: 1_000,
+        };
+        assert_ok!(Dex::schedule_dca(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            swap,
+            5,
+            3,
+            one_percent(),
+            false
+        ));
+
+        Dex::on_initialize(2);
+        assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_B), Some(INIT_BALANCE - 3_000));
+        assert_eq!(Assets::maybe_balance(ASSET_B, ACCOUNT_B), Some(INIT_BALANCE + 993));
+        let exchange_a = Dex::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange_a.currency_reserve, INIT_LIQUIDITY - 996);
+        assert_eq!(exchange_a.token_reserve, INIT_LIQUIDITY + 1_000);
+        let exchange_b = Dex::exchanges(ASSET_B).unwrap();
+        assert_eq!(exchange_b.currency_reserve, INIT_LIQUIDITY + 996);
+        assert_eq!(exchange_b.token_reserve, INIT_LIQUIDITY - 993);
+        assert_eq!(last_event(), crate::Event::DcaExecuted(0, ACCOUNT_B, 2));
+    })
+}
+
+#[test]
+fn dca_stable_swap() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::create_exchange(
+            RuntimeOrigin::signed(ACCOUNT_A),
+            ASSET_B,
+            LIQ_TOKEN_B,
+            2 * INIT_LIQUIDITY,
+            INIT_LIQUIDITY,
+            Curve::StableSwap { amplification: 100 },
+        ));
+        let swap = DcaSwap::AssetToCurrency {
+            asset_id: ASSET_B,
+            token_amount: 1_000_000,
+        };
+        assert_ok!(Dex::schedule_dca(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            swap,
+            5,
+            3,
+            one_percent(),
+            false
+        ));
+
+        // The slippage bound is set by the marginal price of about 1.0042,
+        // rather than the reserve ratio of 2
+        Dex::on_initialize(2);
+        assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE + 1_001_184);
+        let exchange = Dex::exchanges(ASSET_B).unwrap();
+        assert_eq!(exchange.currency_reserve, 2 * INIT_LIQUIDITY - 1_001_184);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY + 1_000_000);
+        assert_eq!(last_event(), crate::Event::DcaExecuted(0, ACCOUNT_B, 2));
+    })
+}
+
+#[test]
+fn dca_failed_execution_retried() {
+    new_test_ext().execute_with(|| {
+        // No slippage is accepted, but the provider fee applies
+        assert_ok!(Dex::schedule_dca(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            currency_to_asset_dca(1_000),
+            1,
+            3,
+            Permill::zero(),
+            false
+        ));
+        // A schedule failing in the same block doesn't block other schedules
+        assert_ok!(Dex::schedule_dca(
+            RuntimeOrigin::signed(ACCOUNT_C),
+            currency_to_asset_dca(1_000),
+            1,
+            1,
+            one_percent(),
+            false
+        ));
+
+        Dex::on_initialize(2);
+        let schedule = Dex::dca_schedules(0).unwrap();
+        assert_eq!(schedule.remaining_executions, 3);
+        assert_eq!(schedule.failed_executions, 1);
+        assert_eq!(schedule.next_execution, 3);
+        assert_eq!(
+            last_n_events(4),
+            vec![
+                crate::Event::DcaExecutionFailed(
+                    0,
+                    ACCOUNT_B,
+                    Error::<Test>::MinTokensTooHigh.into()
+                ),
+                crate::Event::CurrencyTradedForAsset(
+                    ASSET_A,
+                    <Test as ConfigHelper>::pallet_account(),
+                    ACCOUNT_C,
+                    1_000,
+                    996,
+                    PROVIDER_FEE,
+                    None
+                ),
+                crate::Event::DcaExecuted(1, ACCOUNT_C, 0),
+                crate::Event::DcaCompleted(1, ACCOUNT_C),
+            ]
+        );
+
+        // Terminated after failing more than `MaxDcaRetries` times in a row
+        Dex::on_initialize(3);
+        assert_eq!(Dex::dca_schedules(0).unwrap().failed_executions, 2);
+        Dex::on_initialize(4);
+        assert!(Dex::dca_schedules(0).is_none());
+        assert!(Dex::dca_queue(5).is_empty());
+        assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE);
+        assert_eq!(last_event(), crate::Event::DcaTerminated(0, ACCOUNT_B));
+    })
+}
+
+fn enable_batch_auction() {
+    assert_ok!(Dex::set_batch_auction(RuntimeOrigin::root(), ASSET_A, true));
+}
+
+fn buy_in_batch(account: u64, amount: TradeAmount<u128, u128>) {
+    assert_ok!(Dex::currency_to_asset(
+        RuntimeOrigin::signed(account),
+        ASSET_A,
+        amount,
+        Deadline::Block(1),
+        None,
+        None,
+        false
+    ));
+}
+
+fn sell_in_batch(account: u64, amount: TradeAmount<u128, u128>) {
+    assert_ok!(Dex::asset_to_currency(
+        RuntimeOrigin::signed(account),
+        ASSET_A,
+        amount,
+        Deadline::Block(1),
+        None,
+        None,
+        false
+    ));
+}
+
+#[test]
+fn set_batch_auction() {
+    new_test_ext().execute_with(|| {
+        enable_batch_auction();
+        assert!(Dex::batch_auctions(ASSET_A));
+        assert_eq!(last_event(), crate::Event::BatchAuctionModeSet(ASSET_A, true));
+
+        assert_ok!(Dex::set_batch_auction(RuntimeOrigin::root(), ASSET_A, false));
+        assert!(!Dex::batch_auctions(ASSET_A));
+        assert_eq!(last_event(), crate::Event::BatchAuctionModeSet(ASSET_A, false));
+    })
+}
+
+#[test]
+fn set_batch_auction_bad_origin() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::set_batch_auction(RuntimeOrigin::signed(ACCOUNT_A), ASSET_A, true),
+            frame_support::error::BadOrigin
+        );
+    })
+}
+
+#[test]
+fn set_batch_auction_exchange_not_found() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::set_batch_auction(RuntimeOrigin::root(), ASSET_B, true),
+            Error::<Test>::ExchangeNotFound
+        );
+    })
+}
+
+#[test]
+fn batch_trade_queued() {
+    new_test_ext().execute_with(|| {
+        enable_batch_auction();
+        buy_in_batch(
+            ACCOUNT_B,
+            TradeAmount::FixedOutput {
+                max_input: 3_000,
+                output_amount: 2_000,
+            },
+        );
+        assert_eq!(Dex::batch_trades(ASSET_A).len(), 1);
+        assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE - 3_000);
+        assert_eq!(
+            Balances::free_balance(<Test as ConfigHelper>::pallet_account()),
+            INIT_LIQUIDITY + 3_000
+        );
+        let exchange = Dex::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY);
+        assert_eq!(last_event(), crate::Event::BatchTradeQueued(ASSET_A, ACCOUNT_B));
+    })
+}
+
+#[test]
+fn batch_trade_not_enough_tokens() {
+    new_test_ext().execute_with(|| {
+        enable_batch_auction();
+        let amount = TradeAmount::FixedInput {
+            input_amount: INIT_BALANCE + 1,
+            min_output: 1,
+        };
+        assert_noop!(
+            Dex::asset_to_currency(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                amount,
+                Deadline::Block(1),
+                None,
+                None,
+                false
+            ),
+            Error::<Test>::NotEnoughTokens
+        );
+    })
+}
+
+#[test]
+fn batch_full() {
+    new_test_ext().execute_with(|| {
+        enable_batch_auction();
+        let amount = TradeAmount::FixedInput {
+            input_amount: 1_000,
+            min_output: 1,
+        };
+        for _ in 0..3 {
+            buy_in_batch(ACCOUNT_B, amount.clone());
+        }
+        assert_noop!(
+            Dex::currency_to_asset(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                amount,
+                Deadline::Block(1),
+                None,
+                None,
+                false
+            ),
+            Error::<Test>::BatchFull
+        );
+    })
+}
+
+#[test]
+fn batch_auctions_on_several_exchanges() {
+    new_test_ext().execute_with(|| {
+        for (asset_id, liquidity_token_id) in
+            [(ASSET_B, LIQ_TOKEN_B), (STABLECOIN, LIQ_TOKEN_B + 1)]
+        {
+            assert_ok!(Dex::create_exchange(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                asset_id,
+                liquidity_token_id,
+                INIT_LIQUIDITY,
+                INIT_LIQUIDITY,
+                Curve::ConstantProduct,
+            ));
+        }
+        for asset_id in [ASSET_A, ASSET_B, STABLECOIN] {
+            assert_ok!(Dex::set_batch_auction(RuntimeOrigin::root(), asset_id, true));
+        }
+        let sell = |asset_id| {
+            Dex::asset_to_currency(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                asset_id,
+                TradeAmount::FixedInput {
+                    input_amount: 1_000,
+                    min_output: 1,
+                },
+                Deadline::Block(2),
+                None,
+                None,
+                false,
+            )
+        };
+
+        // At most `MaxBatchAuctions` exchanges have trades queued in a block
+        assert_ok!(sell(ASSET_A));
+        assert_ok!(sell(ASSET_B));
+        assert_ok!(sell(ASSET_A));
+        assert_eq!(Dex::batch_queue().into_inner(), vec![ASSET_A, ASSET_B]);
+        assert_noop!(sell(STABLECOIN), Error::<Test>::BatchFull);
+
+        // Both batches are settled at the end of the block
+        Dex::on_finalize(1);
+        assert!(Dex::batch_queue().is_empty());
+        for (asset_id, trade_count) in [(ASSET_A, 2), (ASSET_B, 1)] {
+            assert!(Dex::batch_trades(asset_id).is_empty());
+            let exchange = Dex::exchanges(asset_id).unwrap();
+            assert_eq!(exchange.token_reserve, INIT_LIQUIDITY + 1_000 * trade_count);
+        }
+
+        // The worst-case settlement of the next block is reserved when it starts
+        let max_batch = <() as WeightInfo>::settle_batch(3)
+            .saturating_add(<() as WeightInfo>::clearing_round(3).saturating_mul(2));
+        System::set_block_number(2);
+        assert!(Dex::on_initialize(2).all_gte(max_batch.saturating_mul(2)));
+        assert_ok!(sell(STABLECOIN));
+        assert_eq!(Dex::batch_queue().into_inner(), vec![STABLECOIN]);
+    })
+}
+
+#[test]
+fn batch_asset_to_asset_rejected() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::create_exchange(
+            RuntimeOrigin::signed(ACCOUNT_A),
+            ASSET_B,
+            LIQ_TOKEN_B,
+            INIT_LIQUIDITY,
+            INIT_LIQUIDITY,
+            Curve::ConstantProduct,
+        ));
+        enable_batch_auction();
+        let amount = TradeAmount::FixedInput {
+            input_amount: 1_000,
+            min_output: 1,
+        };
+        assert_noop!(
+            Dex::asset_to_asset(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_B,
+                ASSET_A,
+                amount,
+                Deadline::Block(1),
+                None,
+                None,
+                false
+            ),
+            Error::<Test>::BatchAuctionEnabled
+        );
+    })
+}
+
+#[test]
+fn batch_single_trade() {
+    new_test_ext().execute_with(|| {
+        enable_batch_auction();
+        sell_in_batch(
+            ACCOUNT_B,
+            TradeAmount::FixedInput {
+                input_amount: 1_000,
+                min_output: 1,
+            },
+        );
+        Dex::on_finalize(1);
+
+        // Same output as a regular trade
+        assert!(Dex::batch_trades(ASSET_A).is_empty());
+        let exchange = Dex::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY - 996);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY + 1_000);
+        assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE + 996);
+        assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_B), Some(INIT_BALANCE - 1_000));
+        let price = FixedU128::from_inner(996_999_999_005_991_000);
+        assert_eq!(
+            last_n_events(2),
+            vec![
+                crate::Event::AssetTradedForCurrency(
+                    ASSET_A,
+                    ACCOUNT_B,
+                    ACCOUNT_B,
+                    996,
+                    1_000,
+                    PROVIDER_FEE,
+                    None
+                ),
+                crate::Event::BatchSettled(ASSET_A, price),
+            ]
+        );
+    })
+}
+
+#[test]
+fn batch_uniform_clearing_price() {
+    new_test_ext().execute_with(|| {
+        enable_batch_auction();
+        buy_in_batch(
+            ACCOUNT_B,
+            TradeAmount::FixedInput {
+                input_amount: 10_000,
+                min_output: 9_900,
+            },
+        );
+        sell_in_batch(
+            ACCOUNT_C,
+            TradeAmount::FixedInput {
+                input_amount: 5_000,
+                min_output: 4_900,
+            },
+        );
+        buy_in_batch(
+            ACCOUNT_A,
+            TradeAmount::FixedOutput {
+                max_input: 3_000,
+                output_amount: 2_000,
+            },
+        );
+        Dex::on_finalize(1);
+
+        // All trades are filled at the same price, only the net amount is traded with the exchange
+        let price = FixedU128::from_inner(1_003_009_034_072_216_628);
+        assert_eq!(
+            last_n_events(4),
+            vec![
+                crate::Event::CurrencyTradedForAsset(
+                    ASSET_A,
+                    ACCOUNT_B,
+                    ACCOUNT_B,
+                    10_000,
+                    9_969,
+                    PROVIDER_FEE,
+                    None
+                ),
+                crate::Event::AssetTradedForCurrency(
+                    ASSET_A,
+                    ACCOUNT_C,
+                    ACCOUNT_C,
+                    5_015,
+                    5_000,
+                    PROVIDER_FEE,
+                    None
+                ),
+                crate::Event::CurrencyTradedForAsset(
+                    ASSET_A,
+                    ACCOUNT_A,
+                    ACCOUNT_A,
+                    2_007,
+                    2_000,
+                    PROVIDER_FEE,
+                    None
+                ),
+                crate::Event::BatchSettled(ASSET_A, price),
+            ]
+        );
+        let exchange = Dex::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY + 10_000 - 5_015 + 2_007);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY - 9_969 + 5_000 - 2_000);
+        assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_B), Some(INIT_BALANCE + 9_969));
+        assert_eq!(Balances::free_balance(ACCOUNT_C), INIT_BALANCE + 5_015);
+        // Unused input of the fixed output trade is refunded
+        assert_eq!(Balances::free_balance(ACCOUNT_A), INIT_BALANCE - INIT_LIQUIDITY - 2_007);
+        let pallet_account = <Test as ConfigHelper>::pallet_account();
+        assert_eq!(Balances::free_balance(pallet_account), exchange.currency_reserve);
+        assert_eq!(Assets::maybe_balance(ASSET_A, pallet_account), Some(exchange.token_reserve));
+    })
+}
+
+#[test]
+fn batch_matched_without_exchange() {
+    new_test_ext().execute_with(|| {
+        enable_batch_auction();
+        let amount = TradeAmount::FixedInput {
+            input_amount: 1_000,
+            min_output: 1_000,
+        };
+        buy_in_batch(ACCOUNT_B, amount.clone());
+        sell_in_batch(ACCOUNT_C, amount);
+        Dex::on_finalize(1);
+
+        // Both trades are filled at the spot price without paying the provider fee
+        let exchange = Dex::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY);
+        assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_B), Some(INIT_BALANCE + 1_000));
+        assert_eq!(Balances::free_balance(ACCOUNT_C), INIT_BALANCE + 1_000);
+        assert_eq!(
+            last_event(),
+            crate::Event::BatchSettled(ASSET_A, FixedU128::saturating_from_integer(1))
+        );
+    })
+}
+
+#[test]
+fn batch_trade_refunded() {
+    new_test_ext().execute_with(|| {
+        enable_batch_auction();
+        buy_in_batch(
+            ACCOUNT_B,
+            TradeAmount::FixedInput {
+                input_amount: 10_000,
+                min_output: 9_990,
+            },
+        );
+        sell_in_batch(
+            ACCOUNT_C,
+            TradeAmount::FixedInput {
+                input_amount: 5_000,
+                min_output: 4_900,
+            },
+        );
+        buy_in_batch(
+            ACCOUNT_A,
+            TradeAmount::FixedOutput {
+                max_input: 3_000,
+                output_amount: 2_000,
+            },
+        );
+        Dex::on_finalize(1);
+
+        // The first trade can't get 9_990 tokens, the clearing price is computed without it
+        let price = FixedU128::from_inner(996_999_997_017_973_008);
+        assert_eq!(
+            last_n_events(4),
+            vec![
+                crate::Event::AssetTradedForCurrency(
+                    ASSET_A,
+                    ACCOUNT_C,
+                    ACCOUNT_C,
+                    4_984,
+                    5_000,
+                    PROVIDER_FEE,
+                    None
+                ),
+                crate::Event::CurrencyTradedForAsset(
+                    ASSET_A,
+                    ACCOUNT_A,
+                    ACCOUNT_A,
+                    1_994,
+                    2_000,
+                    PROVIDER_FEE,
+                    None
+                ),
+                crate::Event::BatchSettled(ASSET_A, price),
+                crate::Event::BatchTradeRefunded(ASSET_A, ACCOUNT_B),
+            ]
+        );
+        assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE);
+        assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_B), Some(INIT_BALANCE));
+        let exchange = Dex::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY - 4_984 + 1_994);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY + 5_000 - 2_000);
+    })
+}
+
+#[test]
+fn batch_settlement_independent_of_order() {
+    let trades = vec![
+        (
+            ACCOUNT_B,
+            true,
+            TradeAmount::FixedInput {
+                input_amount: 10_000,
+                min_output: 9_900,
+            },
+        ),
+        (
+            ACCOUNT_C,
+            false,
+            TradeAmount::FixedInput {
+                input_amount: 5_000,
+                min_output: 4_900,
+            },
+        ),
+        (
+            ACCOUNT_A,
+            true,
+            TradeAmount::FixedOutput {
+                max_input: 3_000,
+                output_amount: 2_000,
+            },
+        ),
+    ];
+    let settle = |trades: Vec<(u64, bool, TradeAmount<u128, u128>)>| {
+        new_test_ext().execute_with(|| {
+            enable_batch_auction();
+            for (account, buy, amount) in trades {
+                if buy {
+                    buy_in_batch(account, amount);
+                } else {
+                    sell_in_batch(account, amount);
+                }
+            }
+            Dex::on_finalize(1);
+            let balances: Vec<_> = [ACCOUNT_A, ACCOUNT_B, ACCOUNT_C]
+                .into_iter()
+                .map(|account| {
+                    (Balances::free_balance(account), Assets::maybe_balance(ASSET_A, account))
+                })
+                .collect();
+            (Dex::exchanges(ASSET_A).unwrap(), balances, last_event())
+        })
+    };
+    let reversed = trades.iter().rev().cloned().collect();
+    assert_eq!(settle(trades), settle(reversed));
+}
+
+fn currency_to_asset_swap(input_amount: u128, min_output: u128) -> SwapAmount<u128, u128> {
+    SwapAmount::CurrencyToAsset(TradeAmount::FixedInput {
+        input_amount,
+        min_output,
+    })
+}
+
+fn trade_commitment(
+    amount: &SwapAmount<u128, u128>,
+    recipient: Option<u64>,
+    salt: [u8; 32],
+) -> H256 {
+    BlakeTwo256::hash_of(&(ASSET_A, amount, recipient, salt))
+}
+
+#[test]
+fn commit_trade() {
+    new_test_ext().execute_with(|| {
+        let commitment = trade_commitment(&currency_to_asset_swap(1_000, 1), None, [1; 32]);
+        assert_ok!(Dex::commit_trade(RuntimeOrigin::signed(ACCOUNT_B), commitment, false));
+
+        let trade_commitment = Dex::trade_commitments(ACCOUNT_B, commitment).unwrap();
+        assert_eq!(trade_commitment.deposit, COMMIT_DEPOSIT);
+        assert_eq!(trade_commitment.expiry, 6);
+        assert_eq!(Dex::commitment_expiries(7).into_inner(), vec![(ACCOUNT_B, commitment)]);
+        assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE - COMMIT_DEPOSIT);
+        assert_eq!(
+            Balances::free_balance(<Test as ConfigHelper>::pallet_account()),
+            INIT_LIQUIDITY + COMMIT_DEPOSIT
+        );
+        assert_eq!(last_event(), crate::Event::TradeCommitted(ACCOUNT_B, commitment));
+    })
+}
+
+#[test]
+fn commit_trade_unsigned() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::commit_trade(RuntimeOrigin::none(), H256::zero(), false),
+            frame_support::error::BadOrigin
+        );
+    })
+}
+
+#[test]
+fn commit_trade_already_exists() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::commit_trade(RuntimeOrigin::signed(ACCOUNT_B), H256::zero(), false));
+        assert_noop!(
+            Dex::commit_trade(RuntimeOrigin::signed(ACCOUNT_B), H256::zero(), false),
+            Error::<Test>::CommitmentAlreadyExists
+        );
+        // The same commitment can be made by another account
+        assert_ok!(Dex::commit_trade(RuntimeOrigin::signed(ACCOUNT_C), H256::zero(), false));
+    })
+}
+
+#[test]
+fn commit_trade_balance_too_low() {
+    new_test_ext().execute_with(|| {
+        let caller = 5;
+        let _ = Balances::deposit_creating(&caller, COMMIT_DEPOSIT - 1);
+        assert_noop!(
+            Dex::commit_trade(RuntimeOrigin::signed(caller), H256::zero(), false),
+            Error::<Test>::BalanceTooLow
+        );
+    })
+}
+
+#[test]
+fn commit_trade_keep_alive() {
+    new_test_ext().execute_with(|| {
+        let caller = 5;
+        let _ = Balances::deposit_creating(&caller, COMMIT_DEPOSIT);
+        assert_noop!(
+            Dex::commit_trade(RuntimeOrigin::signed(caller), H256::zero(), true),
+            Error::<Test>::BalanceTooLow
+        );
+        assert_ok!(Dex::commit_trade(RuntimeOrigin::signed(caller), H256::zero(), false));
+        assert_eq!(Balances::free_balance(caller), 0);
+    })
+}
+
+#[test]
+fn commit_trade_too_many_commitments() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::commit_trade(RuntimeOrigin::signed(ACCOUNT_A), H256::zero(), false));
+        assert_ok!(Dex::commit_trade(RuntimeOrigin::signed(ACCOUNT_B), H256::zero(), false));
+        assert_noop!(
+            Dex::commit_trade(RuntimeOrigin::signed(ACCOUNT_C), H256::zero(), false),
+            Error::<Test>::TooManyCommitments
+        );
+    })
+}
+
+#[test]
+fn reveal_trade() {
+    new_test_ext().execute_with(|| {
+        let amount = currency_to_asset_swap(1_000, 996);
+        let commitment = trade_commitment(&amount, None, [1; 32]);
+        assert_ok!(Dex::commit_trade(RuntimeOrigin::signed(ACCOUNT_B), commitment, false));
+
+        System::set_block_number(3);
+        assert_ok!(Dex::reveal_trade(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            amount,
+            None,
+            [1; 32],
+            false
+        ));
+        assert!(Dex::trade_commitments(ACCOUNT_B, commitment).is_none());
+        assert!(Dex::commitment_expiries(7).is_empty());
+        // The deposit is refunded
+        assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE - 1_000);
+        assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_B), Some(INIT_BALANCE + 996));
+        assert_eq!(
+            Balances::free_balance(<Test as ConfigHelper>::pallet_account()),
+            INIT_LIQUIDITY + 1_000
+        );
+        assert_eq!(
+            last_n_events(2),
+            vec![
+                crate::Event::CurrencyTradedForAsset(
+                    ASSET_A,
+                    ACCOUNT_B,
+                    ACCOUNT_B,
+                    1_000,
+                    996,
+                    PROVIDER_FEE,
+                    None
+                ),
+                crate::Event::TradeRevealed(ACCOUNT_B, commitment),
+            ]
+        );
+    })
+}
+
+#[test]
+fn reveal_trade_asset_to_currency() {
+    new_test_ext().execute_with(|| {
+        let amount = SwapAmount::AssetToCurrency(TradeAmount::FixedOutput {
+            max_input: 1_100,
+            output_amount: 1_000,
+        });
+        let commitment = trade_commitment(&amount, Some(ACCOUNT_C), [1; 32]);
+        assert_ok!(Dex::commit_trade(RuntimeOrigin::signed(ACCOUNT_B), commitment, false));
+        assert_ok!(Dex::reveal_trade(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            amount,
+            Some(ACCOUNT_C),
+            [1; 32],
+            false
+        ));
+
+        assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE);
+        assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_B), Some(INIT_BALANCE - 1_004));
+        assert_eq!(Balances::free_balance(ACCOUNT_C), INIT_BALANCE + 1_000);
+    })
+}
+
+#[test]
+fn reveal_trade_commitment_not_found() {
+    new_test_ext().execute_with(|| {
+        let amount = currency_to_asset_swap(1_000, 1);
+        let commitment = trade_commitment(&amount, None, [1; 32]);
+        assert_ok!(Dex::commit_trade(RuntimeOrigin::signed(ACCOUNT_B), commitment, false));
+
+        // Different salt
+        assert_noop!(
+            Dex::reveal_trade(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                amount.clone(),
+                None,
+                [2; 32],
+                false
+            ),
+            Error::<Test>::CommitmentNotFound
+        );
+        // Different trade
+        assert_noop!(
+            Dex::reveal_trade(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                currency_to_asset_swap(2_000, 1),
+                None,
+                [1; 32],
+                false
+            ),
+            Error::<Test>::CommitmentNotFound
+        );
+        // Different account
+        assert_noop!(
+            Dex::reveal_trade(
+                RuntimeOrigin::signed(ACCOUNT_C),
+                ASSET_A,
+                amount,
+                None,
+                [1; 32],
+                false
+            ),
+            Error::<Test>::CommitmentNotFound
+        );
+    })
+}
+
+#[test]
+fn reveal_trade_failed() {
+    new_test_ext().execute_with(|| {
+        let amount = currency_to_asset_swap(1_000, 997);
+        let commitment = trade_commitment(&amount, None, [1; 32]);
+        assert_ok!(Dex::commit_trade(RuntimeOrigin::signed(ACCOUNT_B), commitment, false));
+
+        // The commitment is kept
+        assert_noop!(
+            Dex::reveal_trade(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                amount,
+                None,
+                [1; 32],
+                false
+            ),
+            Error::<Test>::MinTokensTooHigh
+        );
+    })
+}
+
+#[test]
+fn trade_commitment_forfeited() {
+    new_test_ext().execute_with(|| {
+        let amount = currency_to_asset_swap(1_000, 1);
+        let commitment = trade_commitment(&amount, None, [1; 32]);
+        assert_ok!(Dex::commit_trade(RuntimeOrigin::signed(ACCOUNT_B), commitment, false));
+        let total_issuance = Balances::total_issuance();
+
+        // Can still be revealed in the last block of the reveal window
+        Dex::on_initialize(6);
+        assert!(Dex::trade_commitments(ACCOUNT_B, commitment).is_some());
+
+        System::set_block_number(7);
+        Dex::on_initialize(7);
+        assert!(Dex::trade_commitments(ACCOUNT_B, commitment).is_none());
+        assert!(Dex::commitment_expiries(7).is_empty());
+        assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE - COMMIT_DEPOSIT);
+        assert_eq!(
+            Balances::free_balance(<Test as ConfigHelper>::pallet_account()),
+            INIT_LIQUIDITY
+        );
+        assert_eq!(Balances::total_issuance(), total_issuance - COMMIT_DEPOSIT);
+        assert_eq!(last_event(), crate::Event::TradeCommitmentForfeited(ACCOUNT_B, commitment));
+        assert_noop!(
+            Dex::reveal_trade(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                amount,
+                None,
+                [1; 32],
+                false
+            ),
+            Error::<Test>::CommitmentNotFound
+        );
+    })
+}
+
+type TestSwapIntent = SwapIntent<u64, u32, u128, u128, u64>;
+
+fn swap_intent(amount: SwapAmount<u128, u128>, tip: IntentTip<u128, u128>) -> TestSwapIntent {
+    let signer = intent_signer(Sr25519Keyring::Alice);
+    Balances::make_free_balance_be(&signer, INIT_BALANCE);
+    assert_ok!(Assets::mint_into(ASSET_A, &signer, INIT_BALANCE));
+    SwapIntent {
+        signer,
+        asset_id: ASSET_A,
+        amount,
+        deadline: Deadline::Block(1),
+        recipient: None,
+        nonce: 0,
+        tip,
+        keep_alive: false,
+    }
+}
+
+fn execute_intent(intent: &TestSwapIntent, keyring: Sr25519Keyring) -> DispatchResultWithPostInfo {
+    let signature = sign_intent(keyring, &Dex::signed_intent_payload(intent));
+    Dex::execute_signed_intent(RuntimeOrigin::signed(ACCOUNT_C), intent.clone(), signature)
+}
+
+#[test]
+fn execute_signed_intent() {
+    new_test_ext().execute_with(|| {
+        let intent = swap_intent(currency_to_asset_swap(1_000, 996), IntentTip::Asset(10));
+        let signer = intent.signer;
+        assert_ok!(execute_intent(&intent, Sr25519Keyring::Alice));
+
+        assert_eq!(Dex::intent_nonces(signer), 1);
+        // The signer pays for the trade, the relayer gets the tip
+        assert_eq!(Balances::free_balance(signer), INIT_BALANCE - 1_000);
+        assert_eq!(Assets::maybe_balance(ASSET_A, signer), Some(INIT_BALANCE + 986));
+        assert_eq!(Balances::free_balance(ACCOUNT_C), INIT_BALANCE);
+        assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_C), Some(INIT_BALANCE + 10));
+        assert_eq!(
+            Assets::maybe_balance(ASSET_A, <Test as ConfigHelper>::pallet_account()),
+            Some(INIT_LIQUIDITY - 996)
+        );
+        let exchange = Dex::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY + 1_000);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY - 996);
+        assert_eq!(
+            last_n_events(2),
+            vec![
+                crate::Event::CurrencyTradedForAsset(
+                    ASSET_A,
+                    signer,
+                    <Test as ConfigHelper>::pallet_account(),
+                    1_000,
+                    996,
+                    PROVIDER_FEE,
+                    None
+                ),
+                crate::Event::SwapIntentExecuted(signer, 0, ACCOUNT_C, IntentTip::Asset(10)),
+            ]
+        );
+    })
+}
+
+#[test]
+fn execute_signed_intent_asset_to_currency() {
+    new_test_ext().execute_with(|| {
+        let amount = SwapAmount::AssetToCurrency(TradeAmount::FixedOutput {
+            max_input: 1_100,
+            output_amount: 1_000,
+        });
+        let intent = TestSwapIntent {
+            recipient: Some(ACCOUNT_B),
+            ..swap_intent(amount, IntentTip::Currency(10))
+        };
+        let signer = intent.signer;
+        assert_ok!(execute_intent(&intent, Sr25519Keyring::Alice));
+
+        assert_eq!(Balances::free_balance(signer), INIT_BALANCE);
+        assert_eq!(Assets::maybe_balance(ASSET_A, signer), Some(INIT_BALANCE - 1_004));
+        assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE + 990);
+        assert_eq!(Balances::free_balance(ACCOUNT_C), INIT_BALANCE + 10);
+        assert_eq!(
+            Balances::free_balance(<Test as ConfigHelper>::pallet_account()),
+            INIT_LIQUIDITY - 1_000
+        );
+    })
+}
+
+#[test]
+fn execute_signed_intent_unsigned() {
+    new_test_ext().execute_with(|| {
+        let intent = swap_intent(currency_to_asset_swap(1_000, 996), IntentTip::Asset(10));
+        let signature = sign_intent(Sr25519Keyring::Alice, &Dex::signed_intent_payload(&intent));
+        assert_noop!(
+            Dex::execute_signed_intent(RuntimeOrigin::none(), intent, signature),
+            frame_support::error::BadOrigin
+        );
+    })
+}
+
+#[test]
+fn execute_signed_intent_invalid_signature() {
+    new_test_ext().execute_with(|| {
+        let intent = swap_intent(currency_to_asset_swap(1_000, 996), IntentTip::Asset(10));
+        // Signed by another key
+        assert_noop!(execute_intent(&intent, Sr25519Keyring::Bob), Error::<Test>::InvalidSignature);
+        // Signature of another intent
+        let signature = sign_intent(Sr25519Keyring::Alice, &Dex::signed_intent_payload(&intent));
+        let tampered = TestSwapIntent {
+            tip: IntentTip::Asset(100),
+            ..intent
+        };
+        assert_noop!(
+            Dex::execute_signed_intent(RuntimeOrigin::signed(ACCOUNT_C), tampered, signature),
+            Error::<Test>::InvalidSignature
+        );
+    })
+}
+
+#[test]
+fn execute_signed_intent_replayed() {
+    new_test_ext().execute_with(|| {
+        let intent = swap_intent(currency_to_asset_swap(1_000, 1), IntentTip::Asset(10));
+        assert_ok!(execute_intent(&intent, Sr25519Keyring::Alice));
+        assert_noop!(
+            execute_intent(&intent, Sr25519Keyring::Alice),
+            Error::<Test>::InvalidIntentNonce
+        );
+        // Intents must be executed in the order of their nonces
+        let skipped = TestSwapIntent {
+            nonce: 2,
+            ..intent.clone()
+        };
+        assert_noop!(
+            execute_intent(&skipped, Sr25519Keyring::Alice),
+            Error::<Test>::InvalidIntentNonce
+        );
+        let next = TestSwapIntent { nonce: 1, ..intent };
+        assert_ok!(execute_intent(&next, Sr25519Keyring::Alice));
+        assert_eq!(Dex::intent_nonces(next.signer), 2);
+    })
+}
+
+#[test]
+fn execute_signed_intent_replayed_on_another_instance() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(StableDex::create_exchange(
+            RuntimeOrigin::signed(ACCOUNT_A),
+            ASSET_A,
+            STABLE_LIQ_TOKEN_A,
+            INIT_LIQUIDITY,
+            INIT_LIQUIDITY,
+            Curve::ConstantProduct,
+        ));
+        let intent = swap_intent(currency_to_asset_swap(1_000, 996), IntentTip::Asset(10));
+        let signature = sign_intent(Sr25519Keyring::Alice, &Dex::signed_intent_payload(&intent));
+        assert_ne!(Dex::signed_intent_payload(&intent), StableDex::signed_intent_payload(&intent));
+        assert_noop!(
+            StableDex::execute_signed_intent(
+                RuntimeOrigin::signed(ACCOUNT_C),
+                intent.clone(),
+                signature.clone()
+            ),
+            Error::<Test, Instance1>::InvalidSignature
+        );
+        assert_ok!(Dex::execute_signed_intent(RuntimeOrigin::signed(ACCOUNT_C), intent, signature));
+    })
+}
+
+#[test]
+fn execute_signed_intent_deadline_passed() {
+    new_test_ext().execute_with(|| {
+        let intent = swap_intent(currency_to_asset_swap(1_000, 996), IntentTip::Asset(10));
+        System::set_block_number(2);
+        assert_noop!(execute_intent(&intent, Sr25519Keyring::Alice), Error::<Test>::DeadlinePassed);
+    })
+}
+
+#[test]
+fn execute_signed_intent_keep_alive() {
+    new_test_ext().execute_with(|| {
+        let intent = TestSwapIntent {
+            keep_alive: true,
+            ..swap_intent(currency_to_asset_swap(INIT_BALANCE, 1), IntentTip::Asset(0))
+        };
+        assert_noop!(execute_intent(&intent, Sr25519Keyring::Alice), Error::<Test>::BalanceTooLow);
+        let intent = TestSwapIntent {
+            keep_alive: false,
+            ..intent
+        };
+        assert_ok!(execute_intent(&intent, Sr25519Keyring::Alice));
+        assert_eq!(Balances::free_balance(intent.signer), 0);
+    })
+}
+
+#[test]
+fn execute_signed_intent_tip_too_high() {
+    new_test_ext().execute_with(|| {
+        let intent = swap_intent(currency_to_asset_swap(1_000, 996), IntentTip::Asset(997));
+        assert_noop!(execute_intent(&intent, Sr25519Keyring::Alice), Error::<Test>::TipTooHigh);
+        // The whole output can be paid as the tip
+        let intent = TestSwapIntent {
+            tip: IntentTip::Asset(996),
+            ..intent
+        };
+        assert_ok!(execute_intent(&intent, Sr25519Keyring::Alice));
+        assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_C), Some(INIT_BALANCE + 996));
+    })
+}
+
+#[test]
+fn execute_signed_intent_tip_token_mismatch() {
+    new_test_ext().execute_with(|| {
+        let intent = swap_intent(currency_to_asset_swap(1_000, 996), IntentTip::Currency(10));
+        assert_noop!(
+            execute_intent(&intent, Sr25519Keyring::Alice),
+            Error::<Test>::TipTokenMismatch
+        );
+        let amount = SwapAmount::AssetToCurrency(TradeAmount::FixedInput {
+            input_amount: 1_000,
+            min_output: 1,
+        });
+        let intent = swap_intent(amount, IntentTip::Asset(10));
+        assert_noop!(
+            execute_intent(&intent, Sr25519Keyring::Alice),
+            Error::<Test>::TipTokenMismatch
+        );
+    })
+}
+
+#[test]
+fn execute_signed_intent_batch_auction_enabled() {
+    new_test_ext().execute_with(|| {
+        let intent = swap_intent(currency_to_asset_swap(1_000, 996), IntentTip::Asset(10));
+        enable_batch_auction();
+        assert_noop!(
+            execute_intent(&intent, Sr25519Keyring::Alice),
+            Error::<Test>::BatchAuctionEnabled
+        );
+    })
+}
+
+fn check_deadline(call: crate::Call<Test>) -> TransactionValidity {
+    let info = DispatchInfo::default();
+    CheckDeadline::<Test>::new().validate(&ACCOUNT_B, &RuntimeCall::Dex(call), &info, 0)
+}
+
+fn currency_to_asset_call(deadline: u64) -> crate::Call<Test> {
+    crate::Call::currency_to_asset {
+        asset_id: ASSET_A,
+        amount: TradeAmount::FixedInput {
+            input_amount: 1_000,
+            min_output: 1,
+        },
+        deadline: Deadline::Block(deadline),
+        recipient: None,
+        referrer: None,
+        keep_alive: false,
+    }
+}
+
+#[test]
+fn check_deadline_longevity() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(3);
+        // Valid in blocks 3, 4 & 5
+        assert_eq!(check_deadline(currency_to_asset_call(5)).unwrap().longevity, 3);
+        assert_eq!(check_deadline(currency_to_asset_call(3)).unwrap().longevity, 1);
+        let remove_liquidity = crate::Call::remove_liquidity {
+            asset_id: ASSET_A,
+            liquidity_amount: 1,
+            min_currency: 1,
+            min_tokens: 1,
+            deadline: Deadline::Block(10),
+        };
+        assert_eq!(check_deadline(remove_liquidity).unwrap().longevity, 8);
+        let intent = SwapIntent {
+            deadline: Deadline::Block(4),
+            ..swap_intent(currency_to_asset_swap(1_000, 1), IntentTip::Asset(0))
+        };
+        let signature = sign_intent(Sr25519Keyring::Alice, &Dex::signed_intent_payload(&intent));
+        let execute_signed_intent = crate::Call::execute_signed_intent { intent, signature };
+        assert_eq!(check_deadline(execute_signed_intent).unwrap().longevity, 2);
+    })
+}
+
+#[test]
+fn check_deadline_stale() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(3);
+        assert_eq!(
+            check_deadline(currency_to_asset_call(2)),
+            Err(InvalidTransaction::Stale.into())
+        );
+        let call = RuntimeCall::Dex(currency_to_asset_call(2));
+        let info = DispatchInfo::default();
+        assert_eq!(
+            CheckDeadline::<Test>::new().pre_dispatch(&ACCOUNT_B, &call, &info, 0),
+            Err(InvalidTransaction::Stale.into())
+        );
+    })
+}
+
+#[test]
+fn check_deadline_without_deadline() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(3);
+        let call = crate::Call::cancel_limit_order { order_id: 0 };
+        assert_eq!(check_deadline(call), Ok(ValidTransaction::default()));
+        let call = RuntimeCall::System(frame_system::Call::remark { remark: vec![] });
+        let info = DispatchInfo::default();
+        assert_eq!(
+            CheckDeadline::<Test>::new().validate(&ACCOUNT_B, &call, &info, 0),
+            Ok(ValidTransaction::default())
+        );
+    })
+}
+
+#[test]
+fn check_deadline_moment() {
+    new_test_ext().execute_with(|| {
+        let call = |deadline| crate::Call::<Test>::remove_liquidity {
+            asset_id: ASSET_A,
+            liquidity_amount: 1,
+            min_currency: 1,
+            min_tokens: 1,
+            deadline,
+        };
+        // Not limited, as block times aren't known
+        assert_eq!(check_deadline(call(Deadline::Moment(NOW))), Ok(ValidTransaction::default()));
+        assert_eq!(
+            check_deadline(call(Deadline::Moment(NOW - 1))),
+            Err(InvalidTransaction::Stale.into())
+        );
+        MockTime::set(None);
+        assert_eq!(
+            check_deadline(call(Deadline::Moment(NOW))),
+            Err(InvalidTransaction::Call.into())
+        );
+    })
+}
+
+#[test]
+fn zap_in() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::zap_in(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            1_000_000,
+            499_246,
+            Deadline::Block(1),
+            true
+        ));
+
+        // Rounding leftovers stay in the caller account
+        assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE - 999_998);
+        assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_B), Some(INIT_BALANCE + 1));
+        assert_eq!(Assets::maybe_balance(LIQ_TOKEN_A, ACCOUNT_B), Some(499_246));
+        let exchange = Dex::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY + 999_998);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY - 1);
+        assert_eq!(
+            last_n_events(2),
+            vec![
+                crate::Event::CurrencyTradedForAsset(
+                    ASSET_A,
+                    ACCOUNT_B,
+                    ACCOUNT_B,
+                    500_751,
+                    499_248,
+                    PROVIDER_FEE,
+                    None
+                ),
+                crate::Event::LiquidityAdded(ACCOUNT_B, ASSET_A, 499_247, 499_247, 499_246),
+            ]
+        );
+    })
+}
+
+#[test]
+fn zap_in_min_liquidity_too_high() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::zap_in(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                1_000_000,
+                499_247,
+                Deadline::Block(1),
+                true
+            ),
+            Error::<Test>::MinLiquidityTooHigh
+        );
+    })
+}
+
+#[test]
+fn zap_in_currency_amount_too_low() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::zap_in(RuntimeOrigin::signed(ACCOUNT_B), ASSET_A, 1, 1, Deadline::Block(1), true),
+            Error::<Test>::CurrencyAmountTooLow
+        );
+    })
+}
+
+#[test]
+fn zap_in_zero_amounts() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::zap_in(RuntimeOrigin::signed(ACCOUNT_B), ASSET_A, 0, 1, Deadline::Block(1), true),
+            Error::<Test>::CurrencyAmountIsZero
+        );
+        assert_noop!(
+            Dex::zap_in(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                1_000,
+                0,
+                Deadline::Block(1),
+                true
+            ),
+            Error::<Test>::MinLiquidityIsZero
+        );
+    })
+}
+
+#[test]
+fn zap_in_batch_auction_enabled() {
+    new_test_ext().execute_with(|| {
+        enable_batch_auction();
+        assert_noop!(
+            Dex::zap_in(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                1_000,
+                1,
+                Deadline::Block(1),
+                true
+            ),
+            Error::<Test>::BatchAuctionEnabled
+        );
+    })
+}
+
+#[test]
+fn zap_in_stable_swap() {
+    new_test_ext().execute_with(|| {
+        create_stable_swap_exchange();
+        assert_noop!(
+            Dex::zap_in(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_B,
+                1_000,
+                1,
+                Deadline::Block(1),
+                true
+            ),
+            Error::<Test>::UnsupportedCurve
+        );
+    })
+}
+
+#[test]
+fn zap_out_currency() {
+    new_test_ext().execute_with(|| {
+        let currency_balance = Balances::free_balance(ACCOUNT_A);
+        let token_balance = Assets::balance(ASSET_A, ACCOUNT_A);
+        assert_ok!(Dex::zap_out(
+            RuntimeOrigin::signed(ACCOUNT_A),
+            ASSET_A,
+            1_000_000,
+            ZapOutput::Currency(1_996_999),
+            Deadline::Block(1)
+        ));
+
+        assert_eq!(Balances::free_balance(ACCOUNT_A), currency_balance + 1_996_999);
+        assert_eq!(Assets::balance(ASSET_A, ACCOUNT_A), token_balance);
+        assert_eq!(Assets::balance(LIQ_TOKEN_A, ACCOUNT_A), INIT_LIQUIDITY - 1_000_000);
+        let exchange = Dex::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY - 1_996_999);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY);
+        assert_eq!(
+            last_n_events(2),
+            vec![
+                crate::Event::LiquidityRemoved(ACCOUNT_A, ASSET_A, 1_000_000, 1_000_000, 1_000_000),
+                crate::Event::AssetTradedForCurrency(
+                    ASSET_A,
+                    ACCOUNT_A,
+                    ACCOUNT_A,
+                    996_999,
+                    1_000_000,
+                    PROVIDER_FEE,
+                    None
+                ),
+            ]
+        );
+    })
+}
+
+#[test]
+fn zap_out_asset() {
+    new_test_ext().execute_with(|| {
+        let currency_balance = Balances::free_balance(ACCOUNT_A);
+        let token_balance = Assets::balance(ASSET_A, ACCOUNT_A);
+        assert_ok!(Dex::zap_out(
+            RuntimeOrigin::signed(ACCOUNT_A),
+            ASSET_A,
+            1_000_000,
+            ZapOutput::Asset(1_996_999),
+            Deadline::Block(1)
+        ));
+
+        assert_eq!(Balances::free_balance(ACCOUNT_A), currency_balance);
+        assert_eq!(Assets::balance(ASSET_A, ACCOUNT_A), token_balance + 1_996_999);
+        let exchange = Dex::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY - 1_996_999);
+    })
+}
+
+#[test]
+fn zap_out_min_output_too_high() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::zap_out(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                ASSET_A,
+                1_000_000,
+                ZapOutput::Currency(1_997_000),
+                Deadline::Block(1)
+            ),
+            Error::<Test>::MinCurrencyTooHigh
+        );
+        assert_noop!(
+            Dex::zap_out(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                ASSET_A,
+                1_000_000,
+                ZapOutput::Asset(1_997_000),
+                Deadline::Block(1)
+            ),
+            Error::<Test>::MinTokensTooHigh
+        );
+    })
+}
+
+#[test]
+fn zap_out_provider_liquidity_too_low() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::zap_out(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                1_000,
+                ZapOutput::Currency(1),
+                Deadline::Block(1)
+            ),
+            Error::<Test>::ProviderLiquidityTooLow
+        );
+    })
+}
+
+#[test]
+fn zap_out_not_enough_liquidity() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::zap_out(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                ASSET_A,
+                INIT_LIQUIDITY,
+                ZapOutput::Currency(1),
+                Deadline::Block(1)
+            ),
+            Error::<Test>::NotEnoughLiquidity
+        );
+    })
+}
+
+#[test]
+fn add_liquidity_exact_tokens() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::add_liquidity_exact_tokens(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            1_000,
+            1_000,
+            2_000,
+            Deadline::Block(1),
+            true,
+        ));
+
+        let exchange = Dex::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY + 1_001);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY + 1_000);
+        assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE - 1_001);
+        assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_B), Some(INIT_BALANCE - 1_000));
+        assert_eq!(
+            Assets::maybe_balance(exchange.liquidity_token_id.unwrap(), ACCOUNT_B),
+            Some(1_000)
+        );
+        assert_eq!(
+            last_event(),
+            crate::Event::LiquidityAdded(ACCOUNT_B, ASSET_A, 1_001, 1_000, 1_000)
+        );
+    })
+}
+
+#[test]
+fn add_liquidity_exact_tokens_zero_tokens() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::add_liquidity_exact_tokens(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                0,
+                1_000,
+                2_000,
+                Deadline::Block(1),
+                true
+            ),
+            Error::<Test>::TokenAmountIsZero
+        );
+    })
+}
+
+#[test]
+fn add_liquidity_exact_tokens_zero_max_currency() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::add_liquidity_exact_tokens(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                1_000,
+                1_000,
+                0,
+                Deadline::Block(1),
+                true
+            ),
+            Error::<Test>::MaxCurrencyIsZero
+        );
+    })
+}
+
+#[test]
+fn add_liquidity_exact_tokens_max_currency_too_low() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::add_liquidity_exact_tokens(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                1_000,
+                1_000,
+                1_000,
+                Deadline::Block(1),
+                true
+            ),
+            Error::<Test>::MaxCurrencyTooLow
+        );
+    })
+}
+
+#[test]
+fn add_liquidity_exact_tokens_min_liquidity_too_high() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::add_liquidity_exact_tokens(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                1_000,
+                1_001,
+                2_000,
+                Deadline::Block(1),
+                true
+            ),
+            Error::<Test>::MinLiquidityTooHigh
+        );
+    })
+}
+
+#[test]
+fn add_liquidity_bounded_excess_tokens() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::add_liquidity_bounded(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            1_000,
+            2_000,
+            1_000,
+            1_000,
+            1_000,
+            Deadline::Block(1),
+            true,
+        ));
+
+        let exchange = Dex::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY + 1_000);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY + 1_001);
+        assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE - 1_000);
+        assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_B), Some(INIT_BALANCE - 1_001));
+        assert_eq!(
+            last_event(),
+            crate::Event::LiquidityAdded(ACCOUNT_B, ASSET_A, 1_000, 1_001, 1_000)
+        );
+    })
+}
+
+#[test]
+fn add_liquidity_bounded_excess_currency() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::add_liquidity_bounded(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            2_000,
+            1_000,
+            1_000,
+            1_000,
+            1_000,
+            Deadline::Block(1),
+            true,
+        ));
+
+        let exchange = Dex::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY + 1_001);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY + 1_000);
+        assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE - 1_001);
+        assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_B), Some(INIT_BALANCE - 1_000));
+        assert_eq!(
+            last_event(),
+            crate::Event::LiquidityAdded(ACCOUNT_B, ASSET_A, 1_001, 1_000, 1_000)
+        );
+    })
+}
+
+#[test]
+fn add_liquidity_bounded_equal_amounts() {
+    new_test_ext().execute_with(|| {
+        // Neither side can be matched with the `+1` rounding, so both are deposited in full
+        assert_ok!(Dex::add_liquidity_bounded(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            1_000,
+            1_000,
+            1_000,
+            1_000,
+            1_000,
+            Deadline::Block(1),
+            true,
+        ));
+        assert_eq!(
+            last_event(),
+            crate::Event::LiquidityAdded(ACCOUNT_B, ASSET_A, 1_000, 1_000, 1_000)
+        );
+    })
+}
+
+#[test]
+fn add_liquidity_bounded_zero_min_currency() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::add_liquidity_bounded(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                1_000,
+                1_000,
+                0,
+                1_000,
+                1_000,
+                Deadline::Block(1),
+                true
+            ),
+            Error::<Test>::MinCurrencyIsZero
+        );
+    })
+}
+
+#[test]
+fn add_liquidity_bounded_min_currency_too_high() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::add_liquidity_bounded(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                2_000,
+                1_000,
+                1_002,
+                1_000,
+                1_000,
+                Deadline::Block(1),
+                true
+            ),
+            Error::<Test>::MinCurrencyTooHigh
+        );
+    })
+}
+
+#[test]
+fn add_liquidity_bounded_min_tokens_too_high() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::add_liquidity_bounded(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                1_000,
+                2_000,
+                1_000,
+                1_002,
+                1_000,
+                Deadline::Block(1),
+                true
+            ),
+            Error::<Test>::MinTokensTooHigh
+        );
+    })
+}
+
+#[test]
+fn add_liquidity_bounded_balance_too_low() {
+    new_test_ext().execute_with(|| {
+        // The matching currency for all the tokens is higher than the caller's balance
+        assert_noop!(
+            Dex::add_liquidity_bounded(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                2 * INIT_BALANCE,
+                INIT_BALANCE,
+                1,
+                1,
+                1,
+                Deadline::Block(1),
+                true
+            ),
+            Error::<Test>::BalanceTooLow
+        );
+    })
+}
+
+fn create_stable_swap_exchange() {
+    assert_ok!(Dex::create_exchange(
+        RuntimeOrigin::signed(ACCOUNT_A),
+        ASSET_B,
+        LIQ_TOKEN_B,
+        INIT_LIQUIDITY,
+        INIT_LIQUIDITY,
+        Curve::StableSwap { amplification: 100 },
+    ));
+}
+
+#[test]
+fn create_exchange_stable_swap() {
+    new_test_ext().execute_with(|| {
+        create_stable_swap_exchange();
+        let exchange = Dex::exchanges(ASSET_B).unwrap();
+        assert_eq!(exchange.curve, Curve::StableSwap { amplification: 100 });
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY);
+        // The invariant of balanced reserves is their sum
+        assert_eq!(Assets::balance(LIQ_TOKEN_B, ACCOUNT_A), 2 * INIT_LIQUIDITY);
+    })
+}
+
+#[test]
+fn create_exchange_invalid_amplification() {
+    new_test_ext().execute_with(|| {
+        for amplification in [0, 1_000_001] {
+            assert_noop!(
+                Dex::create_exchange(
+                    RuntimeOrigin::signed(ACCOUNT_A),
+                    ASSET_B,
+                    LIQ_TOKEN_B,
+                    INIT_LIQUIDITY,
+                    INIT_LIQUIDITY,
+                    Curve::StableSwap { amplification },
+                ),
+                Error::<Test>::InvalidAmplification
+            );
+        }
+    })
+}
+
+#[test]
+fn stable_swap_currency_to_asset_fixed_input() {
+    new_test_ext().execute_with(|| {
+        create_stable_swap_exchange();
+        let curr_amount = 100_000_000_000;
+        // Constant product exchange would only give 90_661_089_388 tokens
+        let token_amount = 99_650_080_568;
+        assert_ok!(Dex::currency_to_asset(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_B,
+            TradeAmount::FixedInput {
+                input_amount: curr_amount,
+                min_output: token_amount
+            },
+            Deadline::Block(1),
+            None,
+            None,
+            false
+        ));
+        let exchange = Dex::exchanges(ASSET_B).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY + curr_amount);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY - token_amount);
+    })
+}
+
+#[test]
+fn stable_swap_asset_to_currency_fixed_output() {
+    new_test_ext().execute_with(|| {
+        create_stable_swap_exchange();
+        let curr_amount = 100_000_000_000;
+        let token_amount = 100_351_328_219;
+        assert_ok!(Dex::asset_to_currency(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_B,
+            TradeAmount::FixedOutput {
+                max_input: token_amount,
+                output_amount: curr_amount
+            },
+            Deadline::Block(1),
+            None,
+            None,
+            false
+        ));
+        let exchange = Dex::exchanges(ASSET_B).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY - curr_amount);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY + token_amount);
+    })
+}
+
+#[test]
+fn ramp_amplification() {
+    new_test_ext().execute_with(|| {
+        create_stable_swap_exchange();
+        assert_ok!(Dex::ramp_amplification(RuntimeOrigin::root(), ASSET_B, 500, 11));
+        assert_eq!(last_event(), crate::Event::AmplificationRampStarted(ASSET_B, 100, 500, 11));
+        let exchange = Dex::exchanges(ASSET_B).unwrap();
+        assert_eq!(exchange.curve, Curve::StableSwap { amplification: 500 });
+        assert_eq!(Dex::pricing_curve(&exchange), Curve::StableSwap { amplification: 100 });
+
+        // Halfway through the ramp
+        System::set_block_number(6);
+        assert_eq!(Dex::pricing_curve(&exchange), Curve::StableSwap { amplification: 300 });
+        let token_amount = 99_683_298_082;
+        assert_ok!(Dex::currency_to_asset(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_B,
+            TradeAmount::FixedInput {
+                input_amount: 100_000_000_000,
+                min_output: token_amount
+            },
+            Deadline::Block(6),
+            None,
+            None,
+            false
+        ));
+        assert_eq!(Dex::exchanges(ASSET_B).unwrap().token_reserve, INIT_LIQUIDITY - token_amount);
+
+        System::set_block_number(20);
+        assert_eq!(Dex::pricing_curve(&exchange), Curve::StableSwap { amplification: 500 });
+    })
+}
+
+#[test]
+fn ramp_amplification_replaces_ramp_in_progress() {
+    new_test_ext().execute_with(|| {
+        create_stable_swap_exchange();
+        assert_ok!(Dex::ramp_amplification(RuntimeOrigin::root(), ASSET_B, 500, 11));
+        System::set_block_number(6);
+        assert_ok!(Dex::ramp_amplification(RuntimeOrigin::root(), ASSET_B, 100, 16));
+        assert_eq!(last_event(), crate::Event::AmplificationRampStarted(ASSET_B, 300, 100, 16));
+        let exchange = Dex::exchanges(ASSET_B).unwrap();
+        assert_eq!(Dex::pricing_curve(&exchange), Curve::StableSwap { amplification: 300 });
+        System::set_block_number(11);
+        assert_eq!(Dex::pricing_curve(&exchange), Curve::StableSwap { amplification: 200 });
+    })
+}
+
+#[test]
+fn ramp_amplification_bad_origin() {
+    new_test_ext().execute_with(|| {
+        create_stable_swap_exchange();
+        assert_noop!(
+            Dex::ramp_amplification(RuntimeOrigin::signed(ACCOUNT_A), ASSET_B, 500, 11),
+            frame_support::error::BadOrigin
+        );
+    })
+}
+
+#[test]
+fn ramp_amplification_exchange_not_found() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::ramp_amplification(RuntimeOrigin::root(), ASSET_B, 500, 11),
+            Error::<Test>::ExchangeNotFound
+        );
+    })
+}
+
+#[test]
+fn ramp_amplification_not_stable_swap_exchange() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::ramp_amplification(RuntimeOrigin::root(), ASSET_A, 500, 11),
+            Error::<Test>::NotStableSwapExchange
+        );
+    })
+}
+
+#[test]
+fn ramp_amplification_invalid_amplification() {
+    new_test_ext().execute_with(|| {
+        create_stable_swap_exchange();
+        for target in [0, 1_000_001] {
+            assert_noop!(
+                Dex::ramp_amplification(RuntimeOrigin::root(), ASSET_B, target, 11),
+                Error::<Test>::InvalidAmplification
+            );
+        }
+    })
+}
+
+#[test]
+fn ramp_amplification_invalid_ramp_end() {
+    new_test_ext().execute_with(|| {
+        create_stable_swap_exchange();
+        assert_noop!(
+            Dex::ramp_amplification(RuntimeOrigin::root(), ASSET_B, 500, 1),
+            Error::<Test>::InvalidRampEnd
+        );
+    })
+}
+
+#[test]
+fn set_batch_auction_unsupported_curve() {
+    new_test_ext().execute_with(|| {
+        create_stable_swap_exchange();
+        assert_noop!(
+            Dex::set_batch_auction(RuntimeOrigin::root(), ASSET_B, true),
+            Error::<Test>::UnsupportedCurve
+        );
+        assert_ok!(Dex::set_batch_auction(RuntimeOrigin::root(), ASSET_B, false));
+    })
+}
+
+fn create_weighted_exchange() {
+    assert_ok!(Dex::create_exchange(
+        RuntimeOrigin::signed(ACCOUNT_A),
+        ASSET_B,
+        LIQ_TOKEN_B,
+        INIT_LIQUIDITY,
+        INIT_LIQUIDITY,
+        Curve::Weighted {
+            currency_weight: Permill::from_percent(80)
+        },
+    ));
+}
+
+fn bootstrapping_schedule() -> WeightSchedule<u64> {
+    WeightSchedule {
+        start_weight: Permill::from_percent(90),
+        end_weight: Permill::from_percent(50),
+        start: 1,
+        end: 11,
+    }
+}
+
+#[test]
+fn create_exchange_weighted() {
+    new_test_ext().execute_with(|| {
+        create_weighted_exchange();
+        let exchange = Dex::exchanges(ASSET_B).unwrap();
+        assert_eq!(
+            exchange.curve,
+            Curve::Weighted {
+                currency_weight: Permill::from_percent(80)
+            }
+        );
+        assert_eq!(Assets::balance(LIQ_TOKEN_B, ACCOUNT_A), INIT_LIQUIDITY);
+    })
+}
+
+#[test]
+fn create_exchange_invalid_weight() {
+    new_test_ext().execute_with(|| {
+        for parts in [0, 9_999, 990_001, 1_000_000] {
+            assert_noop!(
+                Dex::create_exchange(
+                    RuntimeOrigin::signed(ACCOUNT_A),
+                    ASSET_B,
+                    LIQ_TOKEN_B,
+                    INIT_LIQUIDITY,
+                    INIT_LIQUIDITY,
+                    Curve::Weighted {
+                        currency_weight: Permill::from_parts(parts)
+                    },
+                ),
+                Error::<Test>::InvalidWeight
+            );
+        }
+    })
+}
+
+#[test]
+fn weighted_currency_to_asset_fixed_input() {
+    new_test_ext().execute_with(|| {
+        create_weighted_exchange();
+        let curr_amount = 100_000_000_000;
+        // The asset is priced at 4 units of currency, against 1 in an equally weighted exchange
+        let token_amount = 316_240_930_743;
+        assert_ok!(Dex::currency_to_asset(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_B,
+            TradeAmount::FixedInput {
+                input_amount: curr_amount,
+                min_output: token_amount
+            },
+            Deadline::Block(1),
+            None,
+            None,
+            false
+        ));
+        let exchange = Dex::exchanges(ASSET_B).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY + curr_amount);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY - token_amount);
+    })
+}
+
+#[test]
+fn weighted_asset_to_currency_fixed_output() {
+    new_test_ext().execute_with(|| {
+        create_weighted_exchange();
+        let curr_amount = 100_000_000_000;
+        let token_amount = 525_735_108_084;
+        assert_ok!(Dex::asset_to_currency(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_B,
+            TradeAmount::FixedOutput {
+                max_input: token_amount,
+                output_amount: curr_amount
+            },
+            Deadline::Block(1),
+            None,
+            None,
+            false
+        ));
+        let exchange = Dex::exchanges(ASSET_B).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY - curr_amount);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY + token_amount);
+    })
+}
+
+#[test]
+fn create_bootstrapping_exchange() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::create_bootstrapping_exchange(
+            RuntimeOrigin::signed(ACCOUNT_A),
+            ASSET_B,
+            LIQ_TOKEN_B,
+            INIT_LIQUIDITY,
+            INIT_LIQUIDITY,
+            bootstrapping_schedule(),
+        ));
+        assert_eq!(
+            last_event(),
+            crate::Event::WeightScheduleSet(
+                ASSET_B,
+                Permill::from_percent(90),
+                Permill::from_percent(50),
+                1,
+                11
+            )
+        );
+        assert_eq!(Dex::weight_schedules(ASSET_B), Some(bootstrapping_schedule()));
+        let exchange = Dex::exchanges(ASSET_B).unwrap();
+        assert_eq!(
+            exchange.curve,
+            Curve::Weighted {
+                currency_weight: Permill::from_percent(50)
+            }
+        );
+        assert_eq!(
+            Dex::pricing_curve(&exchange),
+            Curve::Weighted {
+                currency_weight: Permill::from_percent(90)
+            }
+        );
+
+        // Halfway through the schedule the asset is cheaper than at its start
+        System::set_block_number(6);
+        assert_eq!(
+            Dex::pricing_curve(&exchange),
+            Curve::Weighted {
+                currency_weight: Permill::from_percent(70)
+            }
+        );
+        let token_amount = 198_887_561_506;
+        assert_ok!(Dex::currency_to_asset(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_B,
+            TradeAmount::FixedInput {
+                input_amount: 100_000_000_000,
+                min_output: token_amount
+            },
+            Deadline::Block(6),
+            None,
+            None,
+            false
+        ));
+        assert_eq!(Dex::exchanges(ASSET_B).unwrap().token_reserve, INIT_LIQUIDITY - token_amount);
+
+        System::set_block_number(20);
+        assert_eq!(
+            Dex::pricing_curve(&exchange),
+            Curve::Weighted {
+                currency_weight: Permill::from_percent(50)
+            }
+        );
+    })
+}
+
+#[test]
+fn bootstrapping_exchange_spot_price() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::create_bootstrapping_exchange(
+            RuntimeOrigin::signed(ACCOUNT_A),
+            ASSET_B,
+            LIQ_TOKEN_B,
+            INIT_LIQUIDITY,
+            INIT_LIQUIDITY,
+            bootstrapping_schedule(),
+        ));
+        let exchange = Dex::exchanges(ASSET_B).unwrap();
+        // The price of balanced reserves is the ratio of the scheduled weights
+        assert_eq!(Dex::spot_price(&exchange), FixedU128::from_inner(111_111_111_111_111_111));
+        System::set_block_number(6);
+        assert_eq!(Dex::spot_price(&exchange), FixedU128::from_inner(428_571_428_571_428_571));
+        System::set_block_number(20);
+        assert_eq!(Dex::spot_price(&exchange), FixedU128::one());
+    })
+}
+
+#[test]
+fn create_bootstrapping_exchange_invalid_weight() {
+    new_test_ext().execute_with(|| {
+        for schedule in [
+            WeightSchedule {
+                start_weight: Permill::from_percent(100),
+                ..bootstrapping_schedule()
+            },
+            WeightSchedule {
+                end_weight: Permill::zero(),
+                ..bootstrapping_schedule()
+            },
+        ] {
+            assert_noop!(
+                Dex::create_bootstrapping_exchange(
+                    RuntimeOrigin::signed(ACCOUNT_A),
+                    ASSET_B,
+                    LIQ_TOKEN_B,
+                    INIT_LIQUIDITY,
+                    INIT_LIQUIDITY,
+                    schedule,
+                ),
+                Error::<Test>::InvalidWeight
+            );
+        }
+    })
+}
+
+#[test]
+fn create_bootstrapping_exchange_invalid_schedule() {
+    new_test_ext().execute_with(|| {
+        System::set_block_number(11);
+        for (start, end) in [(11, 11), (12, 11), (1, 11)] {
+            assert_noop!(
+                Dex::create_bootstrapping_exchange(
+                    RuntimeOrigin::signed(ACCOUNT_A),
+                    ASSET_B,
+                    LIQ_TOKEN_B,
+                    INIT_LIQUIDITY,
+                    INIT_LIQUIDITY,
+                    WeightSchedule {
+                        start,
+                        end,
+                        ..bootstrapping_schedule()
+                    },
+                ),
+                Error::<Test>::InvalidWeightSchedule
+            );
+        }
+    })
+}
+
+#[test]
+fn set_batch_auction_weighted_unsupported() {
+    new_test_ext().execute_with(|| {
+        create_weighted_exchange();
+        assert_noop!(
+            Dex::set_batch_auction(RuntimeOrigin::root(), ASSET_B, true),
+            Error::<Test>::UnsupportedCurve
+        );
+    })
+}
+
+const TICK_SPACING: u32 = 10;
+
+fn setup_concentrated_exchange() {
+    assert_ok!(Dex::create_concentrated_exchange(
+        RuntimeOrigin::signed(ACCOUNT_A),
+        ASSET_B,
+        TICK_SPACING,
+        FixedU128::saturating_from_integer(1),
+        -1_000,
+        1_000,
+        INIT_LIQUIDITY,
+        INIT_LIQUIDITY,
+        true,
+    ));
+}
+
+#[test]
+fn create_concentrated_exchange() {
+    new_test_ext().execute_with(|| {
+        setup_concentrated_exchange();
+        assert_eq!(
+            last_event(),
+            crate::Event::ConcentratedExchangeCreated(ASSET_B, TICK_SPACING, 0)
+        );
+        let exchange = Dex::exchanges(ASSET_B).unwrap();
+        assert_eq!(
+            exchange.curve,
+            Curve::Concentrated {
+                tick_spacing: TICK_SPACING
+            }
+        );
+        assert_eq!(exchange.liquidity_token_id, None);
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY);
+        let liquidity = 20_505_166_268_106;
+        assert_eq!(
+            Dex::positions(ACCOUNT_A, (ASSET_B, -1_000, 1_000))
+                .unwrap()
+                .liquidity,
+            liquidity
+        );
+        let pool = Dex::concentrated_pools(ASSET_B).unwrap();
+        assert_eq!(pool.tick, 0);
+        assert_eq!(pool.liquidity, liquidity);
+        assert_eq!(
+            Dex::concentrated_spot_price(&ASSET_B),
+            Some(FixedU128::saturating_from_integer(1))
+        );
+    })
+}
+
+#[test]
+fn create_concentrated_exchange_taken_slot() {
+    new_test_ext().execute_with(|| {
+        setup_concentrated_exchange();
+        assert_noop!(
+            Dex::create_concentrated_exchange(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_B,
+                TICK_SPACING,
+                FixedU128::saturating_from_integer(2),
+                -1_000,
+                1_000,
+                INIT_LIQUIDITY,
+                INIT_LIQUIDITY,
+                true,
+            ),
+            Error::<Test>::ExchangeAlreadyExists
+        );
+        assert_eq!(
+            Dex::concentrated_spot_price(&ASSET_B),
+            Some(FixedU128::saturating_from_integer(1))
+        );
+    })
+}
+
+#[test]
+fn create_concentrated_exchange_position_out_of_range() {
+    new_test_ext().execute_with(|| {
+        // The initial position must be in range of the initial price
+        for (lower_tick, upper_tick) in [(-1_000, 90), (100, 1_000)] {
+            assert_noop!(
+                Dex::create_concentrated_exchange(
+                    RuntimeOrigin::signed(ACCOUNT_A),
+                    ASSET_B,
+                    TICK_SPACING,
+                    FixedU128::saturating_from_rational(101, 100),
+                    lower_tick,
+                    upper_tick,
+                    INIT_LIQUIDITY,
+                    INIT_LIQUIDITY,
+                    true,
+                ),
+                Error::<Test>::InvalidTickRange
+            );
+        }
+    })
+}
+
+#[test]
+fn create_concentrated_exchange_without_liquidity() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::create_concentrated_exchange(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                ASSET_B,
+                TICK_SPACING,
+                FixedU128::saturating_from_integer(1),
+                -1_000,
+                1_000,
+                0,
+                0,
+                true,
+            ),
+            Error::<Test>::PositionLiquidityIsZero
+        );
+    })
+}
+
+#[test]
+fn create_concentrated_exchange_invalid_tick_spacing() {
+    new_test_ext().execute_with(|| {
+        for tick_spacing in [0, 16_385] {
+            assert_noop!(
+                Dex::create_concentrated_exchange(
+                    RuntimeOrigin::signed(ACCOUNT_A),
+                    ASSET_B,
+                    tick_spacing,
+                    FixedU128::saturating_from_integer(1),
+                    -1_000,
+                    1_000,
+                    INIT_LIQUIDITY,
+                    INIT_LIQUIDITY,
+                    true,
+                ),
+                Error::<Test>::InvalidTickSpacing
+            );
+        }
+    })
+}
+
+#[test]
+fn create_concentrated_exchange_invalid_price() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::create_concentrated_exchange(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                ASSET_B,
+                TICK_SPACING,
+                FixedU128::zero(),
+                -1_000,
+                1_000,
+                INIT_LIQUIDITY,
+                INIT_LIQUIDITY,
+                true,
+            ),
+            Error::<Test>::InvalidPrice
+        );
+    })
+}
+
+#[test]
+fn create_concentrated_exchange_already_exists() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::create_concentrated_exchange(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                ASSET_A,
+                TICK_SPACING,
+                FixedU128::saturating_from_integer(1),
+                -1_000,
+                1_000,
+                INIT_LIQUIDITY,
+                INIT_LIQUIDITY,
+                true,
+            ),
+            Error::<Test>::ExchangeAlreadyExists
+        );
+    })
+}
+
+#[test]
+fn mint_position() {
+    new_test_ext().execute_with(|| {
+        setup_concentrated_exchange();
+        assert_ok!(Dex::mint_position(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_B,
+            -1_000,
+            1_000,
+            INIT_LIQUIDITY,
+            INIT_LIQUIDITY,
+            Deadline::Block(1),
+            true,
+        ));
+        let liquidity = 20_505_166_268_106;
+        assert_eq!(
+            last_event(),
+            crate::Event::PositionMinted(
+                ACCOUNT_B,
+                ASSET_B,
+                -1_000,
+                1_000,
+                liquidity,
+                INIT_LIQUIDITY,
+                INIT_LIQUIDITY
+            )
+        );
+        assert_eq!(
+            Dex::positions(ACCOUNT_B, (ASSET_B, -1_000, 1_000))
+                .unwrap()
+                .liquidity,
+            liquidity
+        );
+        assert_eq!(Dex::concentrated_pools(ASSET_B).unwrap().liquidity, 2 * liquidity);
+        assert_eq!(Dex::initialized_ticks(ASSET_B).into_inner(), vec![-1_000, 1_000]);
+        assert_eq!(Dex::ticks(ASSET_B, 1_000).unwrap().liquidity_net, -2 * (liquidity as i128));
+        let exchange = Dex::exchanges(ASSET_B).unwrap();
+        assert_eq!(exchange.currency_reserve, 2 * INIT_LIQUIDITY);
+        assert_eq!(exchange.token_reserve, 2 * INIT_LIQUIDITY);
+        assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE - INIT_LIQUIDITY);
+        assert_eq!(Assets::balance(ASSET_B, ACCOUNT_B), INIT_BALANCE - INIT_LIQUIDITY);
+    })
+}
+
+#[test]
+fn mint_position_invalid_tick_range() {
+    new_test_ext().execute_with(|| {
+        setup_concentrated_exchange();
+        for (lower_tick, upper_tick) in [
+            (-1_005, 1_000),
+            (1_000, 1_000),
+            (1_000, -1_000),
+            (0, 887_280),
+        ] {
+            assert_noop!(
+                Dex::mint_position(
+                    RuntimeOrigin::signed(ACCOUNT_A),
+                    ASSET_B,
+                    lower_tick,
+                    upper_tick,
+                    INIT_LIQUIDITY,
+                    INIT_LIQUIDITY,
+                    Deadline::Block(1),
+                    true,
+                ),
+                Error::<Test>::InvalidTickRange
+            );
+        }
+    })
+}
+
+#[test]
+fn mint_position_liquidity_zero() {
+    new_test_ext().execute_with(|| {
+        setup_concentrated_exchange();
+        // The price is within the range, so both currency and tokens are needed
+        assert_noop!(
+            Dex::mint_position(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                ASSET_B,
+                -1_000,
+                1_000,
+                INIT_LIQUIDITY,
+                0,
+                Deadline::Block(1),
+                true,
+            ),
+            Error::<Test>::PositionLiquidityIsZero
+        );
+    })
+}
+
+#[test]
+fn add_liquidity_concentrated_unsupported() {
+    new_test_ext().execute_with(|| {
+        setup_concentrated_exchange();
+        assert_noop!(
+            Dex::add_liquidity(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_B,
+                1_000,
+                1,
+                1_000,
+                Deadline::Block(1),
+                true,
+            ),
+            Error::<Test>::UnsupportedCurve
+        );
+    })
+}
+
+#[test]
+fn concentrated_currency_to_asset_fixed_input() {
+    new_test_ext().execute_with(|| {
+        setup_concentrated_exchange();
+        let curr_amount = 100_000_000_000;
+        let token_amount = 99_217_585_318;
+        assert_ok!(Dex::currency_to_asset(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_B,
+            TradeAmount::FixedInput {
+                input_amount: curr_amount,
+                min_output: token_amount
+            },
+            Deadline::Block(1),
+            None,
+            None,
+            false
+        ));
+        let exchange = Dex::exchanges(ASSET_B).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY + curr_amount);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY - token_amount);
+        assert_eq!(Assets::balance(ASSET_B, ACCOUNT_B), INIT_BALANCE + token_amount);
+        assert_eq!(Dex::concentrated_pools(ASSET_B).unwrap().tick, -98);
+    })
+}
+
+#[test]
+fn concentrated_currency_to_asset_fixed_output() {
+    new_test_ext().execute_with(|| {
+        setup_concentrated_exchange();
+        let curr_amount = 100_792_449_317;
+        let token_amount = 100_000_000_000;
+        // The pool moves by exactly the bought tokens
+        let swap = Dex::concentrated_swap(
+            &ASSET_B,
+            Side::Currency,
+            token_amount,
+            false,
+            &Dex::trade_fee(&Dex::exchanges(ASSET_B).unwrap()),
+            Permill::zero(),
+        )
+        .unwrap();
+        assert_ok!(Dex::currency_to_asset(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_B,
+            TradeAmount::FixedOutput {
+                max_input: curr_amount,
+                output_amount: token_amount
+            },
+            Deadline::Block(1),
+            None,
+            None,
+            false
+        ));
+        let exchange = Dex::exchanges(ASSET_B).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY + curr_amount);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY - token_amount);
+        assert_eq!(Dex::concentrated_pools(ASSET_B).unwrap(), swap.pool);
+    })
+}
+
+#[test]
+fn concentrated_asset_to_currency_fixed_input() {
+    new_test_ext().execute_with(|| {
+        setup_concentrated_exchange();
+        let curr_amount = 99_217_585_318;
+        let token_amount = 100_000_000_000;
+        assert_ok!(Dex::asset_to_currency(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_B,
+            TradeAmount::FixedInput {
+                input_amount: token_amount,
+                min_output: curr_amount
+            },
+            Deadline::Block(1),
+            None,
+            None,
+            false
+        ));
+        let exchange = Dex::exchanges(ASSET_B).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY - curr_amount);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY + token_amount);
+    })
+}
+
+#[test]
+fn concentrated_too_many_ticks_crossed() {
+    new_test_ext().execute_with(|| {
+        setup_concentrated_exchange();
+        // Six positions ending below the price, each crossed by a large enough trade
+        for i in 1..=6 {
+            assert_ok!(Dex::mint_position(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                ASSET_B,
+                -10 * i,
+                10 * i,
+                1_000_000,
+                1_000_000,
+                Deadline::Block(1),
+                true,
+            ));
+        }
+        assert_noop!(
+            Dex::currency_to_asset(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_B,
+                TradeAmount::FixedInput {
+                    input_amount: 100_000_000_000,
+                    min_output: 1
+                },
+                Deadline::Block(1),
+                None,
+                None,
+                false
+            ),
+            Error::<Test>::TooManyTicksCrossed
+        );
+    })
+}
+
+#[test]
+fn concentrated_trade_weight() {
+    new_test_ext().execute_with(|| {
+        setup_concentrated_exchange();
+        // Three positions ending below the price, each crossed by the trade
+        for i in 1..=3 {
+            assert_ok!(Dex::mint_position(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                ASSET_B,
+                -10 * i,
+                10 * i,
+                1_000_000,
+                1_000_000,
+                Deadline::Block(1),
+                true,
+            ));
+        }
+        let post_info = Dex::currency_to_asset(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_B,
+            TradeAmount::FixedInput {
+                input_amount: 100_000_000_000,
+                min_output: 1,
+            },
+            Deadline::Block(1),
+            None,
+            None,
+            false,
+        )
+        .unwrap();
+        assert_eq!(
+            post_info.actual_weight,
+            Some(
+                <() as WeightInfo>::currency_to_asset()
+                    .saturating_add(<() as WeightInfo>::swap_crossing_ticks(3))
+                    .saturating_add(HANDLER_WEIGHT)
+            )
+        );
+    })
+}
+
+#[test]
+fn trade_weight_without_ticks() {
+    new_test_ext().execute_with(|| {
+        let post_info = Dex::currency_to_asset(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            TradeAmount::FixedInput {
+                input_amount: 1_000,
+                min_output: 1,
+            },
+            Deadline::Block(1),
+            None,
+            None,
+            false,
+        )
+        .unwrap();
+        // Only concentrated-liquidity exchanges are charged for crossed ticks
+        assert_eq!(
+            post_info.actual_weight,
+            Some(<() as WeightInfo>::currency_to_asset().saturating_add(HANDLER_WEIGHT))
+        );
+    })
+}
+
+#[test]
+fn burn_position_collects_fees() {
+    new_test_ext().execute_with(|| {
+        setup_concentrated_exchange();
+        assert_ok!(Dex::currency_to_asset(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_B,
+            TradeAmount::FixedInput {
+                input_amount: 100_000_000_000,
+                min_output: 1
+            },
+            Deadline::Block(1),
+            None,
+            None,
+            false
+        ));
+        let liquidity = 20_505_166_268_106;
+        // The currency includes the fee paid by the trade, less rounding
+        let curr_amount = 1_099_999_999_998;
+        let token_amount = 900_782_414_681;
+        assert_ok!(Dex::burn_position(
+            RuntimeOrigin::signed(ACCOUNT_A),
+            ASSET_B,
+            -1_000,
+            1_000,
+            liquidity,
+            curr_amount,
+            token_amount,
+            Deadline::Block(1),
+        ));
+        assert_eq!(
+            last_event(),
+            crate::Event::PositionBurned(
+                ACCOUNT_A,
+                ASSET_B,
+                -1_000,
+                1_000,
+                liquidity,
+                curr_amount,
+                token_amount
+            )
+        );
+        assert_eq!(Dex::positions(ACCOUNT_A, (ASSET_B, -1_000, 1_000)), None);
+        assert!(Dex::initialized_ticks(ASSET_B).is_empty());
+        assert_eq!(Dex::concentrated_pools(ASSET_B).unwrap().liquidity, 0);
+        let exchange = Dex::exchanges(ASSET_B).unwrap();
+        assert_eq!(exchange.currency_reserve, 2);
+        assert_eq!(exchange.token_reserve, 1);
+    })
+}
+
+#[test]
+fn burn_position_not_found() {
+    new_test_ext().execute_with(|| {
+        setup_concentrated_exchange();
+        assert_noop!(
+            Dex::burn_position(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_B,
+                -1_000,
+                1_000,
+                1,
+                0,
+                0,
+                Deadline::Block(1),
+            ),
+            Error::<Test>::PositionNotFound
+        );
+    })
+}
+
+#[test]
+fn burn_position_liquidity_too_low() {
+    new_test_ext().execute_with(|| {
+        setup_concentrated_exchange();
+        assert_noop!(
+            Dex::burn_position(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                ASSET_B,
+                -1_000,
+                1_000,
+                20_505_166_268_107,
+                0,
+                0,
+                Deadline::Block(1),
+            ),
+            Error::<Test>::PositionLiquidityTooLow
+        );
+    })
+}
+
+fn dynamic_fee_params() -> DynamicFeeParams {
+    DynamicFeeParams {
+        min_fee: Permill::from_percent(1),
+        max_fee: Permill::from_percent(5),
+        volatility_multiplier: 2,
+    }
+}
+
+fn buy_tokens(currency_amount: u128) -> u128 {
+    let token_reserve = Dex::exchanges(ASSET_A).unwrap().token_reserve;
+    assert_ok!(Dex::currency_to_asset(
+        RuntimeOrigin::signed(ACCOUNT_B),
+        ASSET_A,
+        TradeAmount::FixedInput {
+            input_amount: currency_amount,
+            min_output: 1
+        },
+        Deadline::Block(System::block_number()),
+        None,
+        None,
+        false
+    ));
+    token_reserve - Dex::exchanges(ASSET_A).unwrap().token_reserve
+}
+
+#[test]
+fn set_dynamic_fee() {
+    new_test_ext().execute_with(|| {
+        let params = dynamic_fee_params();
+        assert_ok!(Dex::set_dynamic_fee(RuntimeOrigin::root(), ASSET_A, Some(params)));
+        assert_eq!(last_event(), crate::Event::DynamicFeeSet(ASSET_A, Some(params)));
+        assert_eq!(
+            Dex::dynamic_fees(ASSET_A),
+            Some(DynamicFee {
+                params,
+                volatility: Permill::zero(),
+                price: FixedU128::saturating_from_integer(1),
+                updated_at: 1,
+            })
+        );
+
+        // Removing the dynamic fee restores the provider fee
+        assert_ok!(Dex::set_dynamic_fee(RuntimeOrigin::root(), ASSET_A, None));
+        assert_eq!(last_event(), crate::Event::DynamicFeeSet(ASSET_A, None));
+        assert_eq!(Dex::dynamic_fees(ASSET_A), None);
+        assert_eq!(buy_tokens(1_000_000), 996_999);
+    })
+}
+
+#[test]
+fn set_dynamic_fee_keeps_volatility() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::set_dynamic_fee(
+            RuntimeOrigin::root(),
+            ASSET_A,
+            Some(dynamic_fee_params())
+        ));
+        buy_tokens(100_000_000_000);
+        System::set_block_number(2);
+        buy_tokens(1_000_000);
+        let dynamic_fee = Dex::dynamic_fees(ASSET_A).unwrap();
+        assert!(!dynamic_fee.volatility.is_zero());
+
+        let params = DynamicFeeParams {
+            volatility_multiplier: 4,
+            ..dynamic_fee_params()
+        };
+        assert_ok!(Dex::set_dynamic_fee(RuntimeOrigin::root(), ASSET_A, Some(params)));
+        assert_eq!(
+            Dex::dynamic_fees(ASSET_A),
+            Some(DynamicFee {
+                params,
+                ..dynamic_fee
+            })
+        );
+    })
+}
+
+#[test]
+fn set_dynamic_fee_bad_origin() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::set_dynamic_fee(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                ASSET_A,
+                Some(dynamic_fee_params())
+            ),
+            frame_support::error::BadOrigin
+        );
+    })
+}
+
+#[test]
+fn set_dynamic_fee_exchange_not_found() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::set_dynamic_fee(RuntimeOrigin::root(), ASSET_B, Some(dynamic_fee_params())),
+            Error::<Test>::ExchangeNotFound
+        );
+    })
+}
+
+#[test]
+fn set_dynamic_fee_invalid_fee_range() {
+    new_test_ext().execute_with(|| {
+        for (min_fee, max_fee) in [
+            (Permill::from_percent(6), Permill::from_percent(5)),
+            (Permill::from_percent(1), Permill::one()),
+        ] {
+            let params = DynamicFeeParams {
+                min_fee,
+                max_fee,
+                ..dynamic_fee_params()
+            };
+            assert_noop!(
+                Dex::set_dynamic_fee(RuntimeOrigin::root(), ASSET_A, Some(params)),
+                Error::<Test>::InvalidFeeRange
+            );
+        }
+    })
+}
+
+#[test]
+fn dynamic_fee_applies_to_trades() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::set_dynamic_fee(
+            RuntimeOrigin::root(),
+            ASSET_A,
+            Some(dynamic_fee_params())
+        ));
+        assert_eq!(buy_tokens(1_000_000), 989_999);
+        assert_eq!(
+            last_event(),
+            crate::Event::CurrencyTradedForAsset(
+                ASSET_A,
+                ACCOUNT_B,
+                ACCOUNT_B,
+                1_000_000,
+                989_999,
+                Permill::from_percent(1),
+                None,
+            )
+        );
+    })
+}
+
+#[test]
+fn dynamic_fee_rises_with_volatility() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::set_dynamic_fee(
+            RuntimeOrigin::root(),
+            ASSET_A,
+            Some(dynamic_fee_params())
+        ));
+        // Trades in the block of the last sample don't change the volatility
+        buy_tokens(10_000_000_000);
+        let dynamic_fee = Dex::dynamic_fees(ASSET_A).unwrap();
+        assert!(dynamic_fee.volatility.is_zero());
+
+        // The first trade of the next block samples the price before it moves it
+        System::set_block_number(2);
+        let exchange = Dex::exchanges(ASSET_A).unwrap();
+        let price =
+            FixedU128::saturating_from_rational(exchange.currency_reserve, exchange.token_reserve);
+        let token_amount = buy_tokens(1_000_000);
+        let dynamic_fee = Dex::dynamic_fees(ASSET_A).unwrap();
+        assert_eq!(dynamic_fee.price, price);
+        assert_eq!(dynamic_fee.updated_at, 2);
+        let fee = dynamic_fee.fee();
+        assert!(fee > Permill::from_percent(1) && fee < Permill::from_percent(5));
+        assert_eq!(
+            last_event(),
+            crate::Event::CurrencyTradedForAsset(
+                ASSET_A,
+                ACCOUNT_B,
+                ACCOUNT_B,
+                1_000_000,
+                token_amount,
+                fee,
+                None,
+            )
+        );
+    })
+}
+
+#[test]
+fn currency_to_asset_referral() {
+    new_test_ext().execute_with(|| {
+        let curr_amount = 1_000_000;
+        let token_amount = 996_999;
+        let referral_amount = 600; // 20% of the provider fee (3_000)
+        assert_ok!(Dex::currency_to_asset(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            TradeAmount::FixedInput {
+                input_amount: curr_amount,
+                min_output: token_amount
+            },
+            Deadline::Block(1),
+            None,
+            Some(ACCOUNT_C),
+            false
+        ));
+
+        // The referral doesn't change the price, it's withheld from the reserve
+        let exchange = Dex::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY + curr_amount - referral_amount);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY - token_amount);
+        assert_eq!(Balances::free_balance(ACCOUNT_C), INIT_BALANCE + referral_amount);
+        assert_eq!(
+            last_event(),
+            crate::Event::CurrencyTradedForAsset(
+                ASSET_A,
+                ACCOUNT_B,
+                ACCOUNT_B,
+                curr_amount,
+                token_amount,
+                PROVIDER_FEE,
+                Some((ACCOUNT_C, referral_amount))
+            )
+        );
+    })
+}
+
+#[test]
+fn asset_to_currency_referral() {
+    new_test_ext().execute_with(|| {
+        let token_amount = 1_000_000;
+        let curr_amount = 996_999;
+        let referral_amount = 600; // 20% of the provider fee (3_000)
+        assert_ok!(Dex::asset_to_currency(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            TradeAmount::FixedInput {
+                input_amount: token_amount,
+                min_output: curr_amount
+            },
+            Deadline::Block(1),
+            None,
+            Some(ACCOUNT_C),
+            false
+        ));
+
+        let exchange = Dex::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY - curr_amount);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY + token_amount - referral_amount);
+        assert_eq!(Assets::balance(ASSET_A, ACCOUNT_C), INIT_BALANCE + referral_amount);
+        assert_eq!(
+            last_event(),
+            crate::Event::AssetTradedForCurrency(
+                ASSET_A,
+                ACCOUNT_B,
+                ACCOUNT_B,
+                curr_amount,
+                token_amount,
+                PROVIDER_FEE,
+                Some((ACCOUNT_C, referral_amount))
+            )
+        );
+    })
+}
+
+#[test]
+fn asset_to_asset_referral() {
+    new_test_ext().execute_with(|| {
+        Dex::create_exchange(
+            RuntimeOrigin::signed(ACCOUNT_A),
+            ASSET_B,
+            LIQ_TOKEN_B,
+            INIT_LIQUIDITY,
+            INIT_LIQUIDITY,
+            Curve::ConstantProduct,
+        )
+        .unwrap();
+
+        let sold_token_amount = 1_000_000;
+        let currency_amount = 996_999;
+        let bought_token_amount = 994_007;
+        let token_referral_amount = 600; // 20% of the provider fee (3_000)
+        let currency_referral_amount = 598; // 20% of the provider fee (2_990)
+        assert_ok!(Dex::asset_to_asset(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            ASSET_B,
+            TradeAmount::FixedInput {
+                input_amount: sold_token_amount,
+                min_output: bought_token_amount
+            },
+            Deadline::Block(1),
+            None,
+            Some(ACCOUNT_C),
+            false
+        ));
+
+        let sold_asset_exchange = Dex::exchanges(ASSET_A).unwrap();
+        assert_eq!(
+            sold_asset_exchange.token_reserve,
+            INIT_LIQUIDITY + sold_token_amount - token_referral_amount
+        );
+        let bought_asset_exchange = Dex::exchanges(ASSET_B).unwrap();
+        assert_eq!(
+            bought_asset_exchange.currency_reserve,
+            INIT_LIQUIDITY + currency_amount - currency_referral_amount
+        );
+        assert_eq!(bought_asset_exchange.token_reserve, INIT_LIQUIDITY - bought_token_amount);
+        assert_eq!(Assets::balance(ASSET_A, ACCOUNT_C), INIT_BALANCE + token_referral_amount);
+        assert_eq!(Balances::free_balance(ACCOUNT_C), INIT_BALANCE + currency_referral_amount);
+    })
+}
+
+#[test]
+fn concentrated_referral() {
+    new_test_ext().execute_with(|| {
+        setup_concentrated_exchange();
+        let curr_amount = 100_000_000_000;
+        let token_amount = 99_217_585_318;
+        let referral_amount = 60_000_000; // 20% of the provider fee (300_000_000)
+        assert_ok!(Dex::currency_to_asset(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_B,
+            TradeAmount::FixedInput {
+                input_amount: curr_amount,
+                min_output: token_amount
+            },
+            Deadline::Block(1),
+            None,
+            Some(ACCOUNT_C),
+            false
+        ));
+
+        let exchange = Dex::exchanges(ASSET_B).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY + curr_amount - referral_amount);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY - token_amount);
+        assert_eq!(Balances::free_balance(ACCOUNT_C), INIT_BALANCE + referral_amount);
+        assert_eq!(
+            last_event(),
+            crate::Event::CurrencyTradedForAsset(
+                ASSET_B,
+                ACCOUNT_B,
+                ACCOUNT_B,
+                curr_amount,
+                token_amount,
+                PROVIDER_FEE,
+                Some((ACCOUNT_C, referral_amount))
+            )
+        );
+    })
+}
+
+#[test]
+fn set_permissioned() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::set_permissioned(RuntimeOrigin::root(), ASSET_A, true));
+        assert_eq!(last_event(), crate::Event::PermissionedModeSet(ASSET_A, true));
+        assert!(Dex::permissioned_exchanges(ASSET_A));
+
+        assert_ok!(Dex::set_permissioned(RuntimeOrigin::root(), ASSET_A, false));
+        assert_eq!(last_event(), crate::Event::PermissionedModeSet(ASSET_A, false));
+        assert!(!Dex::permissioned_exchanges(ASSET_A));
+    })
+}
+
+#[test]
+fn set_permissioned_bad_origin() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::set_permissioned(RuntimeOrigin::signed(ACCOUNT_A), ASSET_A, true),
+            frame_support::error::BadOrigin
+        );
+    })
+}
+
+#[test]
+fn set_permissioned_exchange_not_found() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Dex::set_permissioned(RuntimeOrigin::root(), ASSET_B, true),
+            Error::<Test>::ExchangeNotFound
+        );
+    })
+}
+
+#[test]
+fn set_provider_approval() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::set_provider_approval(RuntimeOrigin::root(), ASSET_A, ACCOUNT_B, true));
+        assert_eq!(last_event(), crate::Event::ProviderApprovalSet(ASSET_A, ACCOUNT_B, true));
+        assert!(Dex::approved_providers(ASSET_A, ACCOUNT_B));
+
+        assert_ok!(Dex::set_provider_approval(RuntimeOrigin::root(), ASSET_A, ACCOUNT_B, false));
+        assert!(!Dex::approved_providers(ASSET_A, ACCOUNT_B));
+        assert_noop!(
+            Dex::set_provider_approval(RuntimeOrigin::signed(ACCOUNT_A), ASSET_A, ACCOUNT_B, true),
+            frame_support::error::BadOrigin
+        );
+    })
+}
+
+#[test]
+fn permissioned_add_liquidity() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::set_permissioned(RuntimeOrigin::root(), ASSET_A, true));
+        assert_noop!(
+            Dex::add_liquidity(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                1_000,
+                1_000,
+                1_001,
+                Deadline::Block(1),
+                true
+            ),
+            Error::<Test>::ProviderNotApproved
+        );
+
+        assert_ok!(Dex::set_provider_approval(RuntimeOrigin::root(), ASSET_A, ACCOUNT_B, true));
+        assert_ok!(Dex::add_liquidity(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            1_000,
+            1_000,
+            1_001,
+            Deadline::Block(1),
+            true
+        ));
+
+        // Revoked providers can still remove their liquidity, and anyone can trade
+        assert_ok!(Dex::set_provider_approval(RuntimeOrigin::root(), ASSET_A, ACCOUNT_B, false));
+        assert_ok!(Dex::remove_liquidity(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            1_000,
+            1,
+            1,
+            Deadline::Block(1)
+        ));
+        buy_tokens(1_000_000);
+    })
+}
+
+#[test]
+fn trade_filter_rejects_operations() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::add_liquidity(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            1_000,
+            1_000,
+            1_001,
+            Deadline::Block(1),
+            true
+        ));
+        BlockedAccount::set(Some(ACCOUNT_B));
+        assert_noop!(
+            Dex::currency_to_asset(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                TradeAmount::FixedInput {
+                    input_amount: 1_000_000,
+                    min_output: 1
+                },
+                Deadline::Block(1),
+                None,
+                None,
+                false
+            ),
+            Error::<Test>::OperationNotAllowed
+        );
+        // The recipient is checked too
+        assert_noop!(
+            Dex::asset_to_currency(
+                RuntimeOrigin::signed(ACCOUNT_C),
+                ASSET_A,
+                TradeAmount::FixedInput {
+                    input_amount: 1_000_000,
+                    min_output: 1
+                },
+                Deadline::Block(1),
+                Some(ACCOUNT_B),
+                None,
+                false
+            ),
+            Error::<Test>::OperationNotAllowed
+        );
+        assert_noop!(
+            Dex::add_liquidity(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                1_000,
+                1_000,
+                1_001,
+                Deadline::Block(1),
+                true
+            ),
+            Error::<Test>::OperationNotAllowed
+        );
+        assert_noop!(
+            Dex::remove_liquidity(
+                RuntimeOrigin::signed(ACCOUNT_B),
+                ASSET_A,
+                1_000,
+                1,
+                1,
+                Deadline::Block(1)
+            ),
+            Error::<Test>::OperationNotAllowed
+        );
+        BlockedAccount::set(None);
+        buy_tokens(1_000_000);
+    })
+}
+
+#[test]
+fn on_swap_handler() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::currency_to_asset(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            TradeAmount::FixedInput {
+                input_amount: 1_000_000,
+                min_output: 996_999
+            },
+            Deadline::Block(1),
+            None,
+            Some(ACCOUNT_C),
+            false
+        ));
+        assert_eq!(
+            SwapLog::take(),
+            vec![SwapDetails::CurrencyToAsset {
+                asset_id: ASSET_A,
+                buyer: ACCOUNT_B,
+                recipient: ACCOUNT_B,
+                currency_amount: 1_000_000,
+                token_amount: 996_999,
+                fee: PROVIDER_FEE,
+                referral: Some((ACCOUNT_C, 600)),
+            }]
+        );
+
+        // Both trades of an asset-to-asset swap are notified
+        Dex::create_exchange(
+            RuntimeOrigin::signed(ACCOUNT_A),
+            ASSET_B,
+            LIQ_TOKEN_B,
+            INIT_LIQUIDITY,
+            INIT_LIQUIDITY,
+            Curve::ConstantProduct,
+        )
+        .unwrap();
+        assert_ok!(Dex::asset_to_asset(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            ASSET_B,
+            TradeAmount::FixedInput {
+                input_amount: 1_000_000,
+                min_output: 1
+            },
+            Deadline::Block(1),
+            None,
+            None,
+            false
+        ));
+        let swaps = SwapLog::take();
+        assert_eq!(swaps.len(), 2);
+        assert!(matches!(
+            swaps[0],
+            SwapDetails::AssetToCurrency {
+                asset_id: ASSET_A,
+                buyer: ACCOUNT_B,
+                ..
+            }
+        ));
+        assert!(matches!(
+            swaps[1],
+            SwapDetails::CurrencyToAsset {
+                asset_id: ASSET_B,
+                recipient: ACCOUNT_B,
+                ..
+            }
+        ));
+    })
+}
+
+#[test]
+fn on_liquidity_changed_handler() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(Dex::add_liquidity(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            1_000,
+            1_000,
+            1_001,
+            Deadline::Block(1),
+            true
+        ));
+        assert_ok!(Dex::remove_liquidity(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            1_000,
+            1,
+            1,
+            Deadline::Block(1)
+        ));
+        assert_eq!(
+            LiquidityLog::take(),
+            vec![
+                LiquidityChange::Added {
+                    provider: ACCOUNT_B,
+                    asset_id: ASSET_A,
+                    currency_amount: 1_000,
+                    token_amount: 1_001,
+                    liquidity_minted: 1_000,
+                },
+                LiquidityChange::Removed {
+                    provider: ACCOUNT_B,
+                    asset_id: ASSET_A,
+                    currency_amount: 1_000,
+                    token_amount: 1_000,
+                    liquidity_amount: 1_000,
+                },
+            ]
+        );
+
+        // Positions of concentrated-liquidity exchanges are notified too
+        setup_concentrated_exchange();
+        let liquidity = 20_505_166_268_106;
+        assert_ok!(Dex::burn_position(
+            RuntimeOrigin::signed(ACCOUNT_A),
+            ASSET_B,
+            -1_000,
+            1_000,
+            liquidity,
+            1,
+            1,
+            Deadline::Block(1),
+        ));
+        let exchange = Dex::exchanges(ASSET_B).unwrap();
+        assert_eq!(
+            LiquidityLog::take(),
+            vec![
+                LiquidityChange::PositionMinted {
+                    provider: ACCOUNT_A,
+                    asset_id: ASSET_B,
+                    lower_tick: -1_000,
+                    upper_tick: 1_000,
+                    liquidity,
+                    currency_amount: INIT_LIQUIDITY,
+                    token_amount: INIT_LIQUIDITY,
+                },
+                LiquidityChange::PositionBurned {
+                    provider: ACCOUNT_A,
+                    asset_id: ASSET_B,
+                    lower_tick: -1_000,
+                    upper_tick: 1_000,
+                    liquidity,
+                    currency_amount: INIT_LIQUIDITY - exchange.currency_reserve,
+                    token_amount: INIT_LIQUIDITY - exchange.token_reserve,
+                },
+            ]
+        );
+    })
+}
+
+#[test]
+fn handlers_weight_is_charged() {
+    let call = crate::Call::<Test>::remove_liquidity {
+        asset_id: ASSET_A,
+        liquidity_amount: 1_000,
+        min_currency: 1,
+        min_tokens: 1,
+        deadline: Deadline::Block(1),
+    };
+    assert_eq!(
+        call.get_dispatch_info().weight,
+        <() as WeightInfo>::remove_liquidity().saturating_add(HANDLER_WEIGHT)
+    );
+    let call = crate::Call::<Test>::zap_in {
+        asset_id: ASSET_A,
+        currency_amount: 1_000,
+        min_liquidity: 1,
+        deadline: Deadline::Block(1),
+        keep_alive: true,
+    };
+    assert_eq!(
+        call.get_dispatch_info().weight,
+        <() as WeightInfo>::zap_in().saturating_add(HANDLER_WEIGHT.saturating_mul(2))
+    );
+    let call = crate::Call::<Test>::burn_position {
+        asset_id: ASSET_B,
+        lower_tick: -1_000,
+        upper_tick: 1_000,
+        liquidity: 1_000,
+        min_currency: 1,
+        min_tokens: 1,
+        deadline: Deadline::Block(1),
+    };
+    assert_eq!(
+        call.get_dispatch_info().weight,
+        <() as WeightInfo>::burn_position().saturating_add(HANDLER_WEIGHT)
+    );
+}
+
+#[test]
+fn create_exchange_on_second_instance() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(StableDex::create_exchange(
+            RuntimeOrigin::signed(ACCOUNT_A),
+            ASSET_A,
+            STABLE_LIQ_TOKEN_A,
+            INIT_LIQUIDITY,
+            INIT_LIQUIDITY,
+            Curve::ConstantProduct,
+        ));
+        let exchange = StableDex::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY);
+        assert_eq!(exchange.liquidity_token_id, Some(STABLE_LIQ_TOKEN_A));
+        assert_eq!(Dex::exchanges(ASSET_A).unwrap().liquidity_token_id, Some(LIQ_TOKEN_A));
+        assert_eq!(last_stable_event(), crate::Event::ExchangeCreated(ASSET_A, STABLE_LIQ_TOKEN_A));
+
+        // Each instance holds its reserves in its own pallet account, the second one in stablecoins
+        let pallet_account = <Test as ConfigHelper>::pallet_account();
+        let stable_pallet_account = <Test as ConfigHelper<Instance1>>::pallet_account();
+        assert_ne!(pallet_account, stable_pallet_account);
+        assert_eq!(Balances::free_balance(pallet_account), INIT_LIQUIDITY);
+        assert_eq!(Balances::free_balance(stable_pallet_account), 0);
+        assert_eq!(Assets::maybe_balance(STABLECOIN, stable_pallet_account), Some(INIT_LIQUIDITY));
+        assert_eq!(
+            Assets::maybe_balance(STABLECOIN, ACCOUNT_A),
+            Some(INIT_BALANCE - INIT_LIQUIDITY)
+        );
+        assert_eq!(Assets::maybe_balance(ASSET_A, stable_pallet_account), Some(INIT_LIQUIDITY));
+
+        assert_noop!(
+            StableDex::create_exchange(
+                RuntimeOrigin::signed(ACCOUNT_A),
+                ASSET_A,
+                STABLE_LIQ_TOKEN_A,
+                1,
+                1,
+                Curve::ConstantProduct,
+            ),
+            Error::<Test, Instance1>::ExchangeAlreadyExists
+        );
+    })
+}
+
+#[test]
+fn instances_trade_with_own_fees() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(StableDex::create_exchange(
+            RuntimeOrigin::signed(ACCOUNT_A),
+            ASSET_A,
+            STABLE_LIQ_TOKEN_A,
+            INIT_LIQUIDITY,
+            INIT_LIQUIDITY,
+            Curve::ConstantProduct,
+        ));
+        let curr_amount = 1_000_000;
+        let amount = TradeAmount::FixedInput {
+            input_amount: curr_amount,
+            min_output: 1,
+        };
+
+        assert_ok!(Dex::currency_to_asset(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            amount.clone(),
+            Deadline::Block(1),
+            None,
+            None,
+            false
+        ));
+        let token_amount = Assets::maybe_balance(ASSET_A, ACCOUNT_B).unwrap() - INIT_BALANCE;
+        assert_eq!(
+            last_event(),
+            crate::Event::CurrencyTradedForAsset(
+                ASSET_A,
+                ACCOUNT_B,
+                ACCOUNT_B,
+                curr_amount,
+                token_amount,
+                PROVIDER_FEE,
+                None,
+            )
+        );
+
+        assert_ok!(StableDex::currency_to_asset(
+            RuntimeOrigin::signed(ACCOUNT_C),
+            ASSET_A,
+            amount,
+            Deadline::Block(1),
+            None,
+            None,
+            false
+        ));
+        let stable_token_amount = Assets::maybe_balance(ASSET_A, ACCOUNT_C).unwrap() - INIT_BALANCE;
+        assert_eq!(Balances::free_balance(ACCOUNT_C), INIT_BALANCE);
+        assert_eq!(Assets::maybe_balance(STABLECOIN, ACCOUNT_C), Some(INIT_BALANCE - curr_amount));
+        assert_eq!(
+            last_stable_event(),
+            crate::Event::CurrencyTradedForAsset(
+                ASSET_A,
+                ACCOUNT_C,
+                ACCOUNT_C,
+                curr_amount,
+                stable_token_amount,
+                STABLE_PROVIDER_FEE,
+                None,
+            )
+        );
+
+        // Same reserves, lower fee
+        assert!(stable_token_amount > token_amount);
+        let exchange = Dex::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY + curr_amount);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY - token_amount);
+        let exchange = StableDex::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY + curr_amount);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY - stable_token_amount);
+    })
+}
+
+#[test]
+fn asset_to_stablecoin() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(StableDex::create_exchange(
+            RuntimeOrigin::signed(ACCOUNT_A),
+            ASSET_A,
+            STABLE_LIQ_TOKEN_A,
+            INIT_LIQUIDITY,
+            INIT_LIQUIDITY,
+            Curve::ConstantProduct,
+        ));
+        let token_amount = 1_000_000;
+
+        assert_ok!(StableDex::asset_to_currency(
+            RuntimeOrigin::signed(ACCOUNT_B),
+            ASSET_A,
+            TradeAmount::FixedInput {
+                input_amount: token_amount,
+                min_output: 1,
+            },
+            Deadline::Block(1),
+            None,
+            None,
+            false
+        ));
+
+        // The output is paid in stablecoins, native balances are unchanged
+        let curr_amount = Assets::maybe_balance(STABLECOIN, ACCOUNT_B).unwrap() - INIT_BALANCE;
+        assert!(curr_amount > 0);
+        assert_eq!(Balances::free_balance(ACCOUNT_B), INIT_BALANCE);
+        assert_eq!(Assets::maybe_balance(ASSET_A, ACCOUNT_B), Some(INIT_BALANCE - token_amount));
+        let stable_pallet_account = <Test as ConfigHelper<Instance1>>::pallet_account();
+        assert_eq!(
+            Assets::maybe_balance(STABLECOIN, stable_pallet_account),
+            Some(INIT_LIQUIDITY - curr_amount)
+        );
+        let exchange = StableDex::exchanges(ASSET_A).unwrap();
+        assert_eq!(exchange.currency_reserve, INIT_LIQUIDITY - curr_amount);
+        assert_eq!(exchange.token_reserve, INIT_LIQUIDITY + token_amount);
+        assert_eq!(
+            last_stable_event(),
+            crate::Event::AssetTradedForCurrency(
+                ASSET_A,
+                ACCOUNT_B,
+                ACCOUNT_B,
+                curr_amount,
+                token_amount,
+                STABLE_PROVIDER_FEE,
+                None,
+            )
+        );
+    })
+}
+
+#[test]
+fn rpc_on_second_instance() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(StableDex::create_exchange(
+            RuntimeOrigin::signed(ACCOUNT_A),
+            ASSET_A,
+            STABLE_LIQ_TOKEN_A,
+            INIT_LIQUIDITY,
+            INIT_LIQUIDITY / 2,
+            Curve::ConstantProduct,
+        ));
+        let stable = Some(StableDexPalletId::get());
+        let currency_amount = 1_000_000;
+
+        // Each instance prices trades with its own exchange, `Dex` being the default instance
+        let stable_quote = StableDex::get_currency_to_asset_output_amount(ASSET_A, currency_amount);
+        let quote = Dex::get_currency_to_asset_output_amount(ASSET_A, currency_amount);
+        assert_ne!(stable_quote, quote);
+        assert_eq!(
+            with_dex_instance!(stable, |DexInstance| {
+                DexInstance::get_currency_to_asset_output_amount(ASSET_A, currency_amount)
+            }),
+            stable_quote
+        );
+        assert_eq!(
+            with_dex_instance!(None, |DexInstance| {
+                DexInstance::get_currency_to_asset_output_amount(ASSET_A, currency_amount)
+            }),
+            quote
+        );
+        assert_eq!(
+            with_dex_instance!(Some(DexPalletId::get()), |DexInstance| {
+                DexInstance::get_currency_to_asset_output_amount(ASSET_A, currency_amount)
+            }),
+            quote
+        );
+
+        let state =
+            with_dex_instance!(stable, |DexInstance| DexInstance::get_exchange_state(ASSET_A))
+                .unwrap();
+        assert_eq!(state.currency_reserve, INIT_LIQUIDITY);
+        assert_eq!(state.token_reserve, INIT_LIQUIDITY / 2);
+
+        // Subscriptions watch the exchange in the storage of the selected instance
+        let stable_key = with_dex_instance!(stable, |DexInstance| Ok(
+            DexInstance::exchange_storage_key(ASSET_A)
+        ));
+        assert_eq!(stable_key, Ok(StableDex::exchange_storage_key(ASSET_A)));
+        assert_ne!(stable_key, Ok(Dex::exchange_storage_key(ASSET_A)));
+
+        assert_eq!(
+            with_dex_instance!(Some(PalletId(*b"dex_none")), |DexInstance| {
+                DexInstance::get_exchange_state(ASSET_A)
+            }),
+            Err(RpcError::InstanceNotFound)
+        );
+    })
+}
+
+#[test]
+fn simulate_on_second_instance() {
+    new_test_ext().execute_with(|| {
+        assert_ok!(StableDex::create_exchange(
+            RuntimeOrigin::signed(ACCOUNT_A),
+            ASSET_A,
+            STABLE_LIQ_TOKEN_A,
+            INIT_LIQUIDITY,
+            INIT_LIQUIDITY,
+            Curve::ConstantProduct,
+        ));
+        let exchange = StableDex::exchanges(ASSET_A).unwrap();
+        let call = RuntimeCall::StableDex(crate::Call::currency_to_asset {
+            asset_id: ASSET_A,
+            amount: TradeAmount::FixedInput {
+                input_amount: 1_000_000,
+                min_output: 1,
+            },
+            deadline: Deadline::Block(1),
+            recipient: None,
+            referrer: None,
+            keep_alive: false,
+        });
+
+        let simulation = simulate_runtime_call(ACCOUNT_B, call).unwrap();
+
+        // The call is simulated on the instance it was made to, paying in stablecoins
+        assert_ok!(simulation.result);
+        assert_eq!(simulation.exchanges.len(), 1);
+        assert_eq!(simulation.exchanges[0].currency_reserve, exchange.currency_reserve + 1_000_000);
+        assert_eq!(simulation.exchanges[0].liquidity_token_id, Some(STABLE_LIQ_TOKEN_A));
+        assert!(simulation
+            .events
+            .iter()
+            .any(|event| matches!(event, RuntimeEvent::StableDex(_))));
+        assert!(!simulation
+            .events
+            .iter()
+            .any(|event| matches!(event, RuntimeEvent::Dex(_))));
+        assert_eq!(StableDex::exchanges(ASSET_A).unwrap(), exchange);
+
+        assert_eq!(
+            simulate_runtime_call(
+                ACCOUNT_B,
+                RuntimeCall::System(frame_system::Call::remark { remark: vec![] })
+            ),
+            Err(RpcError::InstanceNotFound)
+        );
+    })
+}
+
+#[test]
+fn migrate_to_v1() {
+    new_test_ext().execute_with(|| {
+        // Exchanges encoded without a pricing curve
+        for (asset_id, liquidity_token_id) in [(ASSET_A, LIQ_TOKEN_A), (ASSET_B, LIQ_TOKEN_B)] {
+            let key = crate::Exchanges::<Test>::hashed_key_for(asset_id);
+            unhashed::put_raw(&key, &(asset_id, 1u128, 2u128, liquidity_token_id).encode());
+        }
+        StorageVersion::new(0).put::<Dex>();
+
+        crate::migrations::v1::MigrateToV1::<Test>::on_runtime_upgrade();
+        crate::migrations::v2::MigrateToV2::<Test>::on_runtime_upgrade();
+        assert_eq!(Dex::on_chain_storage_version(), 2);
+        let exchange = Dex::exchanges(ASSET_B).unwrap();
+        assert_eq!(exchange.curve, Curve::ConstantProduct);
+        assert_eq!(exchange.currency_reserve, 1);
+        assert_eq!(exchange.token_reserve, 2);
+        assert_eq!(exchange.liquidity_token_id, Some(LIQ_TOKEN_B));
+        assert_eq!(Dex::exchanges(ASSET_A).unwrap().curve, Curve::ConstantProduct);
+    })
+}
+
+#[test]
+fn migrate_to_v2() {
+    new_test_ext().execute_with(|| {
+        // An exchange encoded with a mandatory liquidity token
+        let key = crate::Exchanges::<Test>::hashed_key_for(ASSET_B);
+        let curve = Curve::StableSwap { amplification: 10 };
+        unhashed::put_raw(&key, &(ASSET_B, 1u128, 2u128, LIQ_TOKEN_B, curve).encode());
+        StorageVersion::new(1).put::<Dex>();
+
+        crate::migrations::v2::MigrateToV2::<Test>::on_runtime_upgrade();
+        assert_eq!(Dex::on_chain_storage_version(), 2);
+        let exchange = Dex::exchanges(ASSET_B).unwrap();
+        assert_eq!(exchange.curve, curve);
+        assert_eq!(exchange.currency_reserve, 1);
+        assert_eq!(exchange.token_reserve, 2);
+        assert_eq!(exchange.liquidity_token_id, Some(LIQ_TOKEN_B));
+    })
+}
+
+#[test]
+fn migrate_to_v3() {
+    new_test_ext().execute_with(|| {
+        let pallet_account = <Test as ConfigHelper>::pallet_account();
+        System::dec_providers(&pallet_account).unwrap();
+        StorageVersion::new(2).put::<Dex>();
+
+        crate::migrations::v3::MigrateToV3::<Test>::on_runtime_upgrade();
+        assert_eq!(Dex::on_chain_storage_version(), 3);
+        assert_eq!(System::providers(&pallet_account), 2);
+        // Already migrated
+        crate::migrations::v3::MigrateToV3::<Test>::on_runtime_upgrade();
+        assert_eq!(System::providers(&pallet_account), 2);
+    })
+}
@@ -1,13 +1,12 @@
-
-//! Autogenerated weights for `pallet_dex`
+//! Placeholder weights for `pallet_dex`
 //!
-//! THIS FILE WAS AUTO-GENERATED USING THE SUBSTRATE BENCHMARK CLI VERSION 4.0.0-dev
-//! DATE: 2023-05-03, STEPS: `20`, REPEAT: `100`, LOW RANGE: `[]`, HIGH RANGE: `[]`
-//! WORST CASE MAP SIZE: `1000000`
-//! HOSTNAME: `vale`, CPU: `11th Gen Intel(R) Core(TM) i7-1165G7 @ 2.80GHz`
-//! EXECUTION: Some(Wasm), WASM-EXECUTION: Compiled, CHAIN: Some("dev"), DB CACHE: 1024
+//! THESE WEIGHTS ARE HAND-ESTIMATED PLACEHOLDERS, NOT BENCHMARK RESULTS. Every entry is marked
+//! `PLACEHOLDER`. They were extrapolated from a run of the benchmark CLI on an older version of the
+//! pallet (DATE: 2023-05-03, STEPS: `20`, REPEAT: `100`, HOSTNAME: `vale`, CPU: `11th Gen Intel(R)
+//! Core(TM) i7-1165G7 @ 2.80GHz`), which only covered the exchange creation, liquidity and swap
+//! calls. Regenerate this file with the command below before relying on it in a runtime.
 
-// Executed Command:
+// Command to run:
 // ./target/release/node-template
 // benchmark
 // pallet
@@ -24,7 +23,7 @@
 // --repeat
 // 100
 // --output
-// /home/vale/Desktop/Parity/substrate-dex/src/weights.rs
+// ./src/weights.rs
 #![cfg_attr(rustfmt, rustfmt_skip)]
 #![allow(unused_parens)]
 #![allow(unused_imports)]
@@ -40,6 +39,33 @@ pub trait WeightInfo {
 	fn currency_to_asset() -> Weight;
 	fn asset_to_currency() -> Weight;
 	fn asset_to_asset() -> Weight;
+	fn place_limit_order() -> Weight;
+	fn cancel_limit_order() -> Weight;
+	fn match_limit_order() -> Weight;
+	fn schedule_dca() -> Weight;
+	fn cancel_dca() -> Weight;
+	fn execute_dca_schedule() -> Weight;
+	fn set_batch_auction() -> Weight;
+	fn batch_trade() -> Weight;
+	fn commit_trade() -> Weight;
+	fn reveal_trade() -> Weight;
+	fn expire_trade_commitment() -> Weight;
+	fn execute_signed_intent() -> Weight;
+	fn zap_in() -> Weight;
+	fn zap_out() -> Weight;
+	fn add_liquidity_exact_tokens() -> Weight;
+	fn add_liquidity_bounded() -> Weight;
+	fn ramp_amplification() -> Weight;
+	fn create_bootstrapping_exchange() -> Weight;
+	fn create_concentrated_exchange() -> Weight;
+	fn mint_position() -> Weight;
+	fn burn_position() -> Weight;
+	fn swap_crossing_ticks(n: u32, ) -> Weight;
+	fn set_dynamic_fee() -> Weight;
+	fn set_permissioned() -> Weight;
+	fn set_provider_approval() -> Weight;
+	fn settle_batch(n: u32, ) -> Weight;
+	fn clearing_round(n: u32, ) -> Weight;
 
 }
 
@@ -49,19 +75,18 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T>  {
 	/// Storage: Assets Asset (r:2 w:2)
 	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
 	/// Storage: Dex Exchanges (r:1 w:1)
-	/// Proof: Dex Exchanges (max_values: None, max_size: Some(52), added: 2527, mode: MaxEncodedLen)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
 	/// Storage: System Account (r:1 w:1)
 	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
 	/// Storage: Assets Account (r:3 w:3)
 	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	/// Storage: Dex PermissionedExchanges (r:1 w:0)
+	/// Proof: Dex PermissionedExchanges (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
 	fn create_exchange() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `393`
-		//  Estimated: `22191`
-		// Minimum execution time: 112_544_000 picoseconds.
-		Weight::from_parts(130_557_000, 0)
-			.saturating_add(Weight::from_parts(0, 22191))
-			.saturating_add(T::DbWeight::get().reads(7))
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(131_957_000, 0)
+			.saturating_add(Weight::from_parts(0, 24685))
+			.saturating_add(T::DbWeight::get().reads(8))
 			.saturating_add(T::DbWeight::get().writes(7))
 	}
 	/// Storage: Assets Asset (r:2 w:2)
@@ -69,21 +94,22 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T>  {
 	/// Storage: Assets Account (r:3 w:3)
 	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
 	/// Storage: Dex Exchanges (r:1 w:1)
-	/// Proof: Dex Exchanges (max_values: None, max_size: Some(52), added: 2527, mode: MaxEncodedLen)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
 	/// Storage: System Account (r:1 w:1)
 	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Dex PermissionedExchanges (r:1 w:0)
+	/// Proof: Dex PermissionedExchanges (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
+	/// Storage: Dex ApprovedProviders (r:1 w:0)
+	/// Proof: Dex ApprovedProviders (max_values: None, max_size: Some(61), added: 2536, mode: MaxEncodedLen)
 	fn add_liquidity() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `969`
-		//  Estimated: `22191`
-		// Minimum execution time: 98_087_000 picoseconds.
-		Weight::from_parts(100_291_000, 0)
-			.saturating_add(Weight::from_parts(0, 22191))
-			.saturating_add(T::DbWeight::get().reads(7))
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(103_191_000, 0)
+			.saturating_add(Weight::from_parts(0, 27221))
+			.saturating_add(T::DbWeight::get().reads(9))
 			.saturating_add(T::DbWeight::get().writes(7))
 	}
 	/// Storage: Dex Exchanges (r:1 w:1)
-	/// Proof: Dex Exchanges (max_values: None, max_size: Some(52), added: 2527, mode: MaxEncodedLen)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
 	/// Storage: Assets Asset (r:2 w:2)
 	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
 	/// Storage: Assets Account (r:3 w:3)
@@ -91,67 +117,518 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T>  {
 	/// Storage: System Account (r:1 w:1)
 	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
 	fn remove_liquidity() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `969`
-		//  Estimated: `22191`
-		// Minimum execution time: 104_010_000 picoseconds.
+		// PLACEHOLDER: hand-estimated, not measured.
 		Weight::from_parts(106_517_000, 0)
-			.saturating_add(Weight::from_parts(0, 22191))
+			.saturating_add(Weight::from_parts(0, 22197))
 			.saturating_add(T::DbWeight::get().reads(7))
 			.saturating_add(T::DbWeight::get().writes(7))
 	}
 	/// Storage: Dex Exchanges (r:1 w:1)
-	/// Proof: Dex Exchanges (max_values: None, max_size: Some(52), added: 2527, mode: MaxEncodedLen)
-	/// Storage: System Account (r:1 w:1)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex DynamicFees (r:1 w:1)
+	/// Proof: Dex DynamicFees (max_values: None, max_size: Some(48), added: 2523, mode: MaxEncodedLen)
+	/// Storage: System Account (r:2 w:2)
 	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
 	/// Storage: Assets Asset (r:1 w:1)
 	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
 	/// Storage: Assets Account (r:2 w:2)
 	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
 	fn currency_to_asset() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `726`
-		//  Estimated: `16929`
-		// Minimum execution time: 76_673_000 picoseconds.
-		Weight::from_parts(78_526_000, 0)
-			.saturating_add(Weight::from_parts(0, 16929))
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(86_913_000, 0)
+			.saturating_add(Weight::from_parts(0, 22061))
+			.saturating_add(T::DbWeight::get().reads(7))
+			.saturating_add(T::DbWeight::get().writes(7))
+	}
+	/// Storage: Dex Exchanges (r:1 w:1)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex DynamicFees (r:1 w:1)
+	/// Proof: Dex DynamicFees (max_values: None, max_size: Some(48), added: 2523, mode: MaxEncodedLen)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:3 w:3)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	fn asset_to_currency() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(87_788_000, 0)
+			.saturating_add(Weight::from_parts(0, 22035))
+			.saturating_add(T::DbWeight::get().reads(7))
+			.saturating_add(T::DbWeight::get().writes(7))
+	}
+	/// Storage: Dex Exchanges (r:2 w:2)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex DynamicFees (r:2 w:2)
+	/// Proof: Dex DynamicFees (max_values: None, max_size: Some(48), added: 2523, mode: MaxEncodedLen)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:5 w:5)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	fn asset_to_asset() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(125_806_000, 0)
+			.saturating_add(Weight::from_parts(0, 33940))
+			.saturating_add(T::DbWeight::get().reads(12))
+			.saturating_add(T::DbWeight::get().writes(12))
+	}
+	/// Storage: Dex Exchanges (r:1 w:0)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex NextLimitOrderId (r:1 w:1)
+	/// Proof: Dex NextLimitOrderId (max_values: Some(1), max_size: Some(8), added: 503, mode: MaxEncodedLen)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:2 w:2)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	/// Storage: Dex LimitOrders (r:0 w:1)
+	/// Proof: Dex LimitOrders (max_values: None, max_size: Some(89), added: 2564, mode: MaxEncodedLen)
+	fn place_limit_order() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(64_512_000, 0)
+			.saturating_add(Weight::from_parts(0, 14315))
 			.saturating_add(T::DbWeight::get().reads(5))
 			.saturating_add(T::DbWeight::get().writes(5))
 	}
+	/// Storage: Dex LimitOrders (r:1 w:1)
+	/// Proof: Dex LimitOrders (max_values: None, max_size: Some(89), added: 2564, mode: MaxEncodedLen)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:2 w:2)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	fn cancel_limit_order() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(58_134_000, 0)
+			.saturating_add(Weight::from_parts(0, 11782))
+			.saturating_add(T::DbWeight::get().reads(4))
+			.saturating_add(T::DbWeight::get().writes(4))
+	}
+	/// Storage: Dex LimitOrders (r:1 w:1)
+	/// Proof: Dex LimitOrders (max_values: None, max_size: Some(89), added: 2564, mode: MaxEncodedLen)
 	/// Storage: Dex Exchanges (r:1 w:1)
-	/// Proof: Dex Exchanges (max_values: None, max_size: Some(52), added: 2527, mode: MaxEncodedLen)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex DynamicFees (r:1 w:1)
+	/// Proof: Dex DynamicFees (max_values: None, max_size: Some(48), added: 2523, mode: MaxEncodedLen)
 	/// Storage: Assets Asset (r:1 w:1)
 	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
 	/// Storage: Assets Account (r:2 w:2)
 	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
 	/// Storage: System Account (r:1 w:1)
 	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
-	fn asset_to_currency() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `726`
-		//  Estimated: `16929`
-		// Minimum execution time: 77_788_000 picoseconds.
-		Weight::from_parts(79_399_000, 0)
-			.saturating_add(Weight::from_parts(0, 16929))
+	fn match_limit_order() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(95_771_000, 0)
+			.saturating_add(Weight::from_parts(0, 19458))
+			.saturating_add(T::DbWeight::get().reads(7))
+			.saturating_add(T::DbWeight::get().writes(7))
+	}
+	/// Storage: Dex Exchanges (r:2 w:0)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex NextDcaScheduleId (r:1 w:1)
+	/// Proof: Dex NextDcaScheduleId (max_values: Some(1), max_size: Some(8), added: 503, mode: MaxEncodedLen)
+	/// Storage: Dex DcaQueue (r:1 w:1)
+	/// Proof: Dex DcaQueue (max_values: None, max_size: Some(1046), added: 3521, mode: MaxEncodedLen)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:2 w:2)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	/// Storage: Dex DcaSchedules (r:0 w:1)
+	/// Proof: Dex DcaSchedules (max_values: None, max_size: Some(109), added: 2584, mode: MaxEncodedLen)
+	fn schedule_dca() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(68_953_000, 0)
+			.saturating_add(Weight::from_parts(0, 15257))
+			.saturating_add(T::DbWeight::get().reads(6))
+			.saturating_add(T::DbWeight::get().writes(6))
+	}
+	/// Storage: Dex DcaSchedules (r:1 w:1)
+	/// Proof: Dex DcaSchedules (max_values: None, max_size: Some(109), added: 2584, mode: MaxEncodedLen)
+	/// Storage: Dex DcaQueue (r:1 w:1)
+	/// Proof: Dex DcaQueue (max_values: None, max_size: Some(1046), added: 3521, mode: MaxEncodedLen)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:2 w:2)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	fn cancel_dca() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(61_207_000, 0)
+			.saturating_add(Weight::from_parts(0, 12802))
 			.saturating_add(T::DbWeight::get().reads(5))
 			.saturating_add(T::DbWeight::get().writes(5))
 	}
+	/// Storage: Dex DcaSchedules (r:1 w:1)
+	/// Proof: Dex DcaSchedules (max_values: None, max_size: Some(109), added: 2584, mode: MaxEncodedLen)
 	/// Storage: Dex Exchanges (r:2 w:2)
-	/// Proof: Dex Exchanges (max_values: None, max_size: Some(52), added: 2527, mode: MaxEncodedLen)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex DynamicFees (r:2 w:2)
+	/// Proof: Dex DynamicFees (max_values: None, max_size: Some(48), added: 2523, mode: MaxEncodedLen)
 	/// Storage: Assets Asset (r:2 w:2)
 	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
-	/// Storage: Assets Account (r:4 w:4)
+	/// Storage: Assets Account (r:3 w:3)
 	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
-	fn asset_to_asset() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `1146`
-		//  Estimated: `23702`
-		// Minimum execution time: 106_608_000 picoseconds.
-		Weight::from_parts(109_049_000, 0)
-			.saturating_add(Weight::from_parts(0, 23702))
+	/// Storage: Dex DcaQueue (r:1 w:1)
+	/// Proof: Dex DcaQueue (max_values: None, max_size: Some(1046), added: 3521, mode: MaxEncodedLen)
+	fn execute_dca_schedule() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(124_546_000, 0)
+			.saturating_add(Weight::from_parts(0, 29344))
+			.saturating_add(T::DbWeight::get().reads(11))
+			.saturating_add(T::DbWeight::get().writes(11))
+	}
+	/// Storage: Dex Exchanges (r:1 w:0)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex BatchAuctions (r:0 w:1)
+	/// Proof: Dex BatchAuctions (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
+	fn set_batch_auction() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(24_614_000, 0)
+			.saturating_add(Weight::from_parts(0, 3533))
+			.saturating_add(T::DbWeight::get().reads(1))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+	/// Storage: Dex Exchanges (r:1 w:0)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex BatchAuctions (r:1 w:0)
+	/// Proof: Dex BatchAuctions (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
+	/// Storage: Dex BatchTrades (r:1 w:1)
+	/// Proof: Dex BatchTrades (max_values: None, max_size: Some(3126), added: 5601, mode: MaxEncodedLen)
+	/// Storage: System Account (r:2 w:2)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	fn batch_trade() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(63_417_000, 0)
+			.saturating_add(Weight::from_parts(0, 15828))
+			.saturating_add(T::DbWeight::get().reads(5))
+			.saturating_add(T::DbWeight::get().writes(3))
+	}
+	/// Storage: Dex TradeCommitments (r:1 w:1)
+	/// Proof: Dex TradeCommitments (max_values: None, max_size: Some(96), added: 2571, mode: MaxEncodedLen)
+	/// Storage: Dex CommitmentExpiries (r:1 w:1)
+	/// Proof: Dex CommitmentExpiries (max_values: None, max_size: Some(3206), added: 5681, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	fn commit_trade() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(58_231_000, 0)
+			.saturating_add(Weight::from_parts(0, 10183))
+			.saturating_add(T::DbWeight::get().reads(3))
+			.saturating_add(T::DbWeight::get().writes(3))
+	}
+	/// Storage: Dex TradeCommitments (r:1 w:1)
+	/// Proof: Dex TradeCommitments (max_values: None, max_size: Some(96), added: 2571, mode: MaxEncodedLen)
+	/// Storage: Dex CommitmentExpiries (r:1 w:1)
+	/// Proof: Dex CommitmentExpiries (max_values: None, max_size: Some(3206), added: 5681, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	fn reveal_trade() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(49_874_000, 0)
+			.saturating_add(Weight::from_parts(0, 10183))
+			.saturating_add(T::DbWeight::get().reads(3))
+			.saturating_add(T::DbWeight::get().writes(3))
+	}
+	/// Storage: Dex TradeCommitments (r:1 w:1)
+	/// Proof: Dex TradeCommitments (max_values: None, max_size: Some(96), added: 2571, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	fn expire_trade_commitment() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(21_596_000, 0)
+			.saturating_add(Weight::from_parts(0, 6164))
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().writes(2))
+	}
+	/// Storage: Dex IntentNonces (r:1 w:1)
+	/// Proof: Dex IntentNonces (max_values: None, max_size: Some(56), added: 2531, mode: MaxEncodedLen)
+	/// Storage: Dex BatchAuctions (r:1 w:0)
+	/// Proof: Dex BatchAuctions (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
+	/// Storage: Dex Exchanges (r:1 w:1)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex DynamicFees (r:1 w:1)
+	/// Proof: Dex DynamicFees (max_values: None, max_size: Some(48), added: 2523, mode: MaxEncodedLen)
+	/// Storage: System Account (r:2 w:2)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:3 w:3)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	fn execute_signed_intent() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(145_488_000, 0)
+			.saturating_add(Weight::from_parts(0, 25697))
+			.saturating_add(T::DbWeight::get().reads(10))
+			.saturating_add(T::DbWeight::get().writes(9))
+	}
+	/// Storage: Dex Exchanges (r:1 w:1)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex DynamicFees (r:1 w:1)
+	/// Proof: Dex DynamicFees (max_values: None, max_size: Some(48), added: 2523, mode: MaxEncodedLen)
+	/// Storage: Dex BatchAuctions (r:1 w:0)
+	/// Proof: Dex BatchAuctions (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:3 w:3)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	/// Storage: Dex PermissionedExchanges (r:1 w:0)
+	/// Proof: Dex PermissionedExchanges (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
+	/// Storage: Dex ApprovedProviders (r:1 w:0)
+	/// Proof: Dex ApprovedProviders (max_values: None, max_size: Some(61), added: 2536, mode: MaxEncodedLen)
+	fn zap_in() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(164_212_000, 0)
+			.saturating_add(Weight::from_parts(0, 28272))
+			.saturating_add(T::DbWeight::get().reads(11))
+			.saturating_add(T::DbWeight::get().writes(8))
+	}
+	/// Storage: Dex Exchanges (r:1 w:1)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex DynamicFees (r:1 w:1)
+	/// Proof: Dex DynamicFees (max_values: None, max_size: Some(48), added: 2523, mode: MaxEncodedLen)
+	/// Storage: Dex BatchAuctions (r:1 w:0)
+	/// Proof: Dex BatchAuctions (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:3 w:3)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	fn zap_out() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(154_704_000, 0)
+			.saturating_add(Weight::from_parts(0, 23248))
+			.saturating_add(T::DbWeight::get().reads(9))
+			.saturating_add(T::DbWeight::get().writes(8))
+	}
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:3 w:3)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	/// Storage: Dex Exchanges (r:1 w:1)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Dex PermissionedExchanges (r:1 w:0)
+	/// Proof: Dex PermissionedExchanges (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
+	/// Storage: Dex ApprovedProviders (r:1 w:0)
+	/// Proof: Dex ApprovedProviders (max_values: None, max_size: Some(61), added: 2536, mode: MaxEncodedLen)
+	fn add_liquidity_exact_tokens() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(104_054_000, 0)
+			.saturating_add(Weight::from_parts(0, 27221))
+			.saturating_add(T::DbWeight::get().reads(9))
+			.saturating_add(T::DbWeight::get().writes(7))
+	}
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:3 w:3)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	/// Storage: Dex Exchanges (r:1 w:1)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Dex PermissionedExchanges (r:1 w:0)
+	/// Proof: Dex PermissionedExchanges (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
+	/// Storage: Dex ApprovedProviders (r:1 w:0)
+	/// Proof: Dex ApprovedProviders (max_values: None, max_size: Some(61), added: 2536, mode: MaxEncodedLen)
+	fn add_liquidity_bounded() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(105_773_000, 0)
+			.saturating_add(Weight::from_parts(0, 27221))
+			.saturating_add(T::DbWeight::get().reads(9))
+			.saturating_add(T::DbWeight::get().writes(7))
+	}
+	/// Storage: Dex Exchanges (r:1 w:1)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex AmplificationRamps (r:1 w:1)
+	/// Proof: Dex AmplificationRamps (max_values: None, max_size: Some(28), added: 2503, mode: MaxEncodedLen)
+	fn ramp_amplification() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(31_482_000, 0)
+			.saturating_add(Weight::from_parts(0, 6036))
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().writes(2))
+	}
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Dex Exchanges (r:1 w:1)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:3 w:3)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	/// Storage: Dex WeightSchedules (r:0 w:1)
+	/// Proof: Dex WeightSchedules (max_values: None, max_size: Some(28), added: 2503, mode: MaxEncodedLen)
+	/// Storage: Dex PermissionedExchanges (r:1 w:0)
+	/// Proof: Dex PermissionedExchanges (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
+	fn create_bootstrapping_exchange() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(134_614_000, 0)
+			.saturating_add(Weight::from_parts(0, 24685))
 			.saturating_add(T::DbWeight::get().reads(8))
 			.saturating_add(T::DbWeight::get().writes(8))
 	}
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Dex Exchanges (r:1 w:1)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex ConcentratedPools (r:1 w:1)
+	/// Proof: Dex ConcentratedPools (max_values: None, max_size: Some(96), added: 2571, mode: MaxEncodedLen)
+	/// Storage: Dex Ticks (r:2 w:2)
+	/// Proof: Dex Ticks (max_values: None, max_size: Some(88), added: 2563, mode: MaxEncodedLen)
+	/// Storage: Dex InitializedTicks (r:1 w:1)
+	/// Proof: Dex InitializedTicks (max_values: None, max_size: Some(270), added: 2745, mode: MaxEncodedLen)
+	/// Storage: Dex Positions (r:1 w:1)
+	/// Proof: Dex Positions (max_values: None, max_size: Some(156), added: 2631, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:2 w:2)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	/// Storage: Dex PermissionedExchanges (r:1 w:0)
+	/// Proof: Dex PermissionedExchanges (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
+	fn create_concentrated_exchange() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(174_512_000, 0)
+			.saturating_add(Weight::from_parts(0, 29536))
+			.saturating_add(T::DbWeight::get().reads(11))
+			.saturating_add(T::DbWeight::get().writes(10))
+	}
+	/// Storage: Dex Exchanges (r:1 w:1)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex ConcentratedPools (r:1 w:1)
+	/// Proof: Dex ConcentratedPools (max_values: None, max_size: Some(96), added: 2571, mode: MaxEncodedLen)
+	/// Storage: Dex Ticks (r:2 w:2)
+	/// Proof: Dex Ticks (max_values: None, max_size: Some(88), added: 2563, mode: MaxEncodedLen)
+	/// Storage: Dex InitializedTicks (r:1 w:1)
+	/// Proof: Dex InitializedTicks (max_values: None, max_size: Some(270), added: 2745, mode: MaxEncodedLen)
+	/// Storage: Dex Positions (r:1 w:1)
+	/// Proof: Dex Positions (max_values: None, max_size: Some(156), added: 2631, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:2 w:2)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	/// Storage: Dex PermissionedExchanges (r:1 w:0)
+	/// Proof: Dex PermissionedExchanges (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
+	/// Storage: Dex ApprovedProviders (r:1 w:0)
+	/// Proof: Dex ApprovedProviders (max_values: None, max_size: Some(61), added: 2536, mode: MaxEncodedLen)
+	fn mint_position() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(145_218_000, 0)
+			.saturating_add(Weight::from_parts(0, 32072))
+			.saturating_add(T::DbWeight::get().reads(12))
+			.saturating_add(T::DbWeight::get().writes(10))
+	}
+	/// Storage: Dex Positions (r:1 w:1)
+	/// Proof: Dex Positions (max_values: None, max_size: Some(156), added: 2631, mode: MaxEncodedLen)
+	/// Storage: Dex Exchanges (r:1 w:1)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex ConcentratedPools (r:1 w:1)
+	/// Proof: Dex ConcentratedPools (max_values: None, max_size: Some(96), added: 2571, mode: MaxEncodedLen)
+	/// Storage: Dex Ticks (r:2 w:2)
+	/// Proof: Dex Ticks (max_values: None, max_size: Some(88), added: 2563, mode: MaxEncodedLen)
+	/// Storage: Dex InitializedTicks (r:1 w:1)
+	/// Proof: Dex InitializedTicks (max_values: None, max_size: Some(270), added: 2745, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:2 w:2)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	fn burn_position() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(139_874_000, 0)
+			.saturating_add(Weight::from_parts(0, 27048))
+			.saturating_add(T::DbWeight::get().reads(10))
+			.saturating_add(T::DbWeight::get().writes(10))
+	}
+	/// Storage: Dex ConcentratedPools (r:1 w:1)
+	/// Proof: Dex ConcentratedPools (max_values: None, max_size: Some(96), added: 2571, mode: MaxEncodedLen)
+	/// Storage: Dex InitializedTicks (r:1 w:0)
+	/// Proof: Dex InitializedTicks (max_values: None, max_size: Some(270), added: 2745, mode: MaxEncodedLen)
+	/// Storage: Dex Ticks (r:5 w:5)
+	/// Proof: Dex Ticks (max_values: None, max_size: Some(88), added: 2563, mode: MaxEncodedLen)
+	/// The range of component `n` is `[0, 5]`.
+	fn swap_crossing_ticks(n: u32, ) -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(22_710_412, 0)
+			.saturating_add(Weight::from_parts(0, 6316))
+			.saturating_add(Weight::from_parts(6_032_118, 0).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().reads((1_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes(1))
+			.saturating_add(T::DbWeight::get().writes((1_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 2563).saturating_mul(n.into()))
+	}
+	/// Storage: Dex Exchanges (r:1 w:0)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex DynamicFees (r:1 w:1)
+	/// Proof: Dex DynamicFees (max_values: None, max_size: Some(48), added: 2523, mode: MaxEncodedLen)
+	fn set_dynamic_fee() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(24_871_000, 0)
+			.saturating_add(Weight::from_parts(0, 6056))
+			.saturating_add(T::DbWeight::get().reads(2))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+	/// Storage: Dex Exchanges (r:1 w:0)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex PermissionedExchanges (r:0 w:1)
+	/// Proof: Dex PermissionedExchanges (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
+	fn set_permissioned() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(19_482_000, 0)
+			.saturating_add(Weight::from_parts(0, 3523))
+			.saturating_add(T::DbWeight::get().reads(1))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+	/// Storage: Dex Exchanges (r:1 w:0)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex ApprovedProviders (r:0 w:1)
+	/// Proof: Dex ApprovedProviders (max_values: None, max_size: Some(61), added: 2536, mode: MaxEncodedLen)
+	fn set_provider_approval() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(20_137_000, 0)
+			.saturating_add(Weight::from_parts(0, 3523))
+			.saturating_add(T::DbWeight::get().reads(1))
+			.saturating_add(T::DbWeight::get().writes(1))
+	}
+	/// Storage: Dex BatchQueue (r:1 w:1)
+	/// Proof: Dex BatchQueue (max_values: Some(1), max_size: Some(33), added: 528, mode: MaxEncodedLen)
+	/// Storage: Dex BatchTrades (r:2 w:1)
+	/// Proof: Dex BatchTrades (max_values: None, max_size: Some(3126), added: 5601, mode: MaxEncodedLen)
+	/// Storage: Dex Exchanges (r:1 w:1)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex DynamicFees (r:1 w:0)
+	/// Proof: Dex DynamicFees (max_values: None, max_size: Some(48), added: 2523, mode: MaxEncodedLen)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:100 w:100)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	/// Storage: System Account (r:100 w:100)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// The range of component `n` is `[1, 100]`.
+	fn settle_batch(n: u32, ) -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(58_094_671, 0)
+			.saturating_add(Weight::from_parts(0, 17402))
+			.saturating_add(Weight::from_parts(41_862_540, 0).saturating_mul(n.into()))
+			.saturating_add(T::DbWeight::get().reads(5))
+			.saturating_add(T::DbWeight::get().reads((2_u64).saturating_mul(n.into())))
+			.saturating_add(T::DbWeight::get().writes(3))
+			.saturating_add(T::DbWeight::get().writes((2_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 5180).saturating_mul(n.into()))
+	}
+	/// The range of component `n` is `[1, 100]`.
+	fn clearing_round(n: u32, ) -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(1_203_518, 0)
+			.saturating_add(Weight::from_parts(0, 0))
+			.saturating_add(Weight::from_parts(487_236, 0).saturating_mul(n.into()))
+	}
 }
 
 // For backwards compatibility and tests
@@ -159,19 +636,18 @@ impl WeightInfo for () {
 	/// Storage: Assets Asset (r:2 w:2)
 	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
 	/// Storage: Dex Exchanges (r:1 w:1)
-	/// Proof: Dex Exchanges (max_values: None, max_size: Some(52), added: 2527, mode: MaxEncodedLen)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
 	/// Storage: System Account (r:1 w:1)
 	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
 	/// Storage: Assets Account (r:3 w:3)
 	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	/// Storage: Dex PermissionedExchanges (r:1 w:0)
+	/// Proof: Dex PermissionedExchanges (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
 	fn create_exchange() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `393`
-		//  Estimated: `22191`
-		// Minimum execution time: 112_544_000 picoseconds.
-		Weight::from_parts(130_557_000, 0)
-			.saturating_add(Weight::from_parts(0, 22191))
-			.saturating_add(RocksDbWeight::get().reads(7))
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(131_957_000, 0)
+			.saturating_add(Weight::from_parts(0, 24685))
+			.saturating_add(RocksDbWeight::get().reads(8))
 			.saturating_add(RocksDbWeight::get().writes(7))
 	}
 	/// Storage: Assets Asset (r:2 w:2)
@@ -179,21 +655,22 @@ impl WeightInfo for () {
 	/// Storage: Assets Account (r:3 w:3)
 	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
 	/// Storage: Dex Exchanges (r:1 w:1)
-	/// Proof: Dex Exchanges (max_values: None, max_size: Some(52), added: 2527, mode: MaxEncodedLen)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
 	/// Storage: System Account (r:1 w:1)
 	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Dex PermissionedExchanges (r:1 w:0)
+	/// Proof: Dex PermissionedExchanges (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
+	/// Storage: Dex ApprovedProviders (r:1 w:0)
+	/// Proof: Dex ApprovedProviders (max_values: None, max_size: Some(61), added: 2536, mode: MaxEncodedLen)
 	fn add_liquidity() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `969`
-		//  Estimated: `22191`
-		// Minimum execution time: 98_087_000 picoseconds.
-		Weight::from_parts(100_291_000, 0)
-			.saturating_add(Weight::from_parts(0, 22191))
-			.saturating_add(RocksDbWeight::get().reads(7))
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(103_191_000, 0)
+			.saturating_add(Weight::from_parts(0, 27221))
+			.saturating_add(RocksDbWeight::get().reads(9))
 			.saturating_add(RocksDbWeight::get().writes(7))
 	}
 	/// Storage: Dex Exchanges (r:1 w:1)
-	/// Proof: Dex Exchanges (max_values: None, max_size: Some(52), added: 2527, mode: MaxEncodedLen)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
 	/// Storage: Assets Asset (r:2 w:2)
 	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
 	/// Storage: Assets Account (r:3 w:3)
@@ -201,65 +678,516 @@ impl WeightInfo for () {
 	/// Storage: System Account (r:1 w:1)
 	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
 	fn remove_liquidity() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `969`
-		//  Estimated: `22191`
-		// Minimum execution time: 104_010_000 picoseconds.
+		// PLACEHOLDER: hand-estimated, not measured.
 		Weight::from_parts(106_517_000, 0)
-			.saturating_add(Weight::from_parts(0, 22191))
+			.saturating_add(Weight::from_parts(0, 22197))
 			.saturating_add(RocksDbWeight::get().reads(7))
 			.saturating_add(RocksDbWeight::get().writes(7))
 	}
 	/// Storage: Dex Exchanges (r:1 w:1)
-	/// Proof: Dex Exchanges (max_values: None, max_size: Some(52), added: 2527, mode: MaxEncodedLen)
-	/// Storage: System Account (r:1 w:1)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex DynamicFees (r:1 w:1)
+	/// Proof: Dex DynamicFees (max_values: None, max_size: Some(48), added: 2523, mode: MaxEncodedLen)
+	/// Storage: System Account (r:2 w:2)
 	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
 	/// Storage: Assets Asset (r:1 w:1)
 	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
 	/// Storage: Assets Account (r:2 w:2)
 	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
 	fn currency_to_asset() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `726`
-		//  Estimated: `16929`
-		// Minimum execution time: 76_673_000 picoseconds.
-		Weight::from_parts(78_526_000, 0)
-			.saturating_add(Weight::from_parts(0, 16929))
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(86_913_000, 0)
+			.saturating_add(Weight::from_parts(0, 22061))
+			.saturating_add(RocksDbWeight::get().reads(7))
+			.saturating_add(RocksDbWeight::get().writes(7))
+	}
+	/// Storage: Dex Exchanges (r:1 w:1)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex DynamicFees (r:1 w:1)
+	/// Proof: Dex DynamicFees (max_values: None, max_size: Some(48), added: 2523, mode: MaxEncodedLen)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:3 w:3)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	fn asset_to_currency() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(87_788_000, 0)
+			.saturating_add(Weight::from_parts(0, 22035))
+			.saturating_add(RocksDbWeight::get().reads(7))
+			.saturating_add(RocksDbWeight::get().writes(7))
+	}
+	/// Storage: Dex Exchanges (r:2 w:2)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex DynamicFees (r:2 w:2)
+	/// Proof: Dex DynamicFees (max_values: None, max_size: Some(48), added: 2523, mode: MaxEncodedLen)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:5 w:5)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	fn asset_to_asset() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(125_806_000, 0)
+			.saturating_add(Weight::from_parts(0, 33940))
+			.saturating_add(RocksDbWeight::get().reads(12))
+			.saturating_add(RocksDbWeight::get().writes(12))
+	}
+	/// Storage: Dex Exchanges (r:1 w:0)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex NextLimitOrderId (r:1 w:1)
+	/// Proof: Dex NextLimitOrderId (max_values: Some(1), max_size: Some(8), added: 503, mode: MaxEncodedLen)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:2 w:2)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	/// Storage: Dex LimitOrders (r:0 w:1)
+	/// Proof: Dex LimitOrders (max_values: None, max_size: Some(89), added: 2564, mode: MaxEncodedLen)
+	fn place_limit_order() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(64_512_000, 0)
+			.saturating_add(Weight::from_parts(0, 14315))
 			.saturating_add(RocksDbWeight::get().reads(5))
 			.saturating_add(RocksDbWeight::get().writes(5))
 	}
+	/// Storage: Dex LimitOrders (r:1 w:1)
+	/// Proof: Dex LimitOrders (max_values: None, max_size: Some(89), added: 2564, mode: MaxEncodedLen)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:2 w:2)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	fn cancel_limit_order() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(58_134_000, 0)
+			.saturating_add(Weight::from_parts(0, 11782))
+			.saturating_add(RocksDbWeight::get().reads(4))
+			.saturating_add(RocksDbWeight::get().writes(4))
+	}
+	/// Storage: Dex LimitOrders (r:1 w:1)
+	/// Proof: Dex LimitOrders (max_values: None, max_size: Some(89), added: 2564, mode: MaxEncodedLen)
 	/// Storage: Dex Exchanges (r:1 w:1)
-	/// Proof: Dex Exchanges (max_values: None, max_size: Some(52), added: 2527, mode: MaxEncodedLen)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex DynamicFees (r:1 w:1)
+	/// Proof: Dex DynamicFees (max_values: None, max_size: Some(48), added: 2523, mode: MaxEncodedLen)
 	/// Storage: Assets Asset (r:1 w:1)
 	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
 	/// Storage: Assets Account (r:2 w:2)
 	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
 	/// Storage: System Account (r:1 w:1)
 	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
-	fn asset_to_currency() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `726`
-		//  Estimated: `16929`
-		// Minimum execution time: 77_788_000 picoseconds.
-		Weight::from_parts(79_399_000, 0)
-			.saturating_add(Weight::from_parts(0, 16929))
+	fn match_limit_order() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(95_771_000, 0)
+			.saturating_add(Weight::from_parts(0, 19458))
+			.saturating_add(RocksDbWeight::get().reads(7))
+			.saturating_add(RocksDbWeight::get().writes(7))
+	}
+	/// Storage: Dex Exchanges (r:2 w:0)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex NextDcaScheduleId (r:1 w:1)
+	/// Proof: Dex NextDcaScheduleId (max_values: Some(1), max_size: Some(8), added: 503, mode: MaxEncodedLen)
+	/// Storage: Dex DcaQueue (r:1 w:1)
+	/// Proof: Dex DcaQueue (max_values: None, max_size: Some(1046), added: 3521, mode: MaxEncodedLen)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:2 w:2)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	/// Storage: Dex DcaSchedules (r:0 w:1)
+	/// Proof: Dex DcaSchedules (max_values: None, max_size: Some(109), added: 2584, mode: MaxEncodedLen)
+	fn schedule_dca() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(68_953_000, 0)
+			.saturating_add(Weight::from_parts(0, 15257))
+			.saturating_add(RocksDbWeight::get().reads(6))
+			.saturating_add(RocksDbWeight::get().writes(6))
+	}
+	/// Storage: Dex DcaSchedules (r:1 w:1)
+	/// Proof: Dex DcaSchedules (max_values: None, max_size: Some(109), added: 2584, mode: MaxEncodedLen)
+	/// Storage: Dex DcaQueue (r:1 w:1)
+	/// Proof: Dex DcaQueue (max_values: None, max_size: Some(1046), added: 3521, mode: MaxEncodedLen)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:2 w:2)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	fn cancel_dca() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(61_207_000, 0)
+			.saturating_add(Weight::from_parts(0, 12802))
 			.saturating_add(RocksDbWeight::get().reads(5))
 			.saturating_add(RocksDbWeight::get().writes(5))
 	}
+	/// Storage: Dex DcaSchedules (r:1 w:1)
+	/// Proof: Dex DcaSchedules (max_values: None, max_size: Some(109), added: 2584, mode: MaxEncodedLen)
 	/// Storage: Dex Exchanges (r:2 w:2)
-	/// Proof: Dex Exchanges (max_values: None, max_size: Some(52), added: 2527, mode: MaxEncodedLen)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex DynamicFees (r:2 w:2)
+	/// Proof: Dex DynamicFees (max_values: None, max_size: Some(48), added: 2523, mode: MaxEncodedLen)
 	/// Storage: Assets Asset (r:2 w:2)
 	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
-	/// Storage: Assets Account (r:4 w:4)
+	/// Storage: Assets Account (r:3 w:3)
 	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
-	fn asset_to_asset() -> Weight {
-		// Proof Size summary in bytes:
-		//  Measured:  `1146`
-		//  Estimated: `23702`
-		// Minimum execution time: 106_608_000 picoseconds.
-		Weight::from_parts(109_049_000, 0)
-			.saturating_add(Weight::from_parts(0, 23702))
+	/// Storage: Dex DcaQueue (r:1 w:1)
+	/// Proof: Dex DcaQueue (max_values: None, max_size: Some(1046), added: 3521, mode: MaxEncodedLen)
+	fn execute_dca_schedule() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(124_546_000, 0)
+			.saturating_add(Weight::from_parts(0, 29344))
+			.saturating_add(RocksDbWeight::get().reads(11))
+			.saturating_add(RocksDbWeight::get().writes(11))
+	}
+	/// Storage: Dex Exchanges (r:1 w:0)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex BatchAuctions (r:0 w:1)
+	/// Proof: Dex BatchAuctions (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
+	fn set_batch_auction() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(24_614_000, 0)
+			.saturating_add(Weight::from_parts(0, 3533))
+			.saturating_add(RocksDbWeight::get().reads(1))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+	/// Storage: Dex Exchanges (r:1 w:0)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex BatchAuctions (r:1 w:0)
+	/// Proof: Dex BatchAuctions (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
+	/// Storage: Dex BatchTrades (r:1 w:1)
+	/// Proof: Dex BatchTrades (max_values: None, max_size: Some(3126), added: 5601, mode: MaxEncodedLen)
+	/// Storage: System Account (r:2 w:2)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	fn batch_trade() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(63_417_000, 0)
+			.saturating_add(Weight::from_parts(0, 15828))
+			.saturating_add(RocksDbWeight::get().reads(5))
+			.saturating_add(RocksDbWeight::get().writes(3))
+	}
+	/// Storage: Dex TradeCommitments (r:1 w:1)
+	/// Proof: Dex TradeCommitments (max_values: None, max_size: Some(96), added: 2571, mode: MaxEncodedLen)
+	/// Storage: Dex CommitmentExpiries (r:1 w:1)
+	/// Proof: Dex CommitmentExpiries (max_values: None, max_size: Some(3206), added: 5681, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	fn commit_trade() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(58_231_000, 0)
+			.saturating_add(Weight::from_parts(0, 10183))
+			.saturating_add(RocksDbWeight::get().reads(3))
+			.saturating_add(RocksDbWeight::get().writes(3))
+	}
+	/// Storage: Dex TradeCommitments (r:1 w:1)
+	/// Proof: Dex TradeCommitments (max_values: None, max_size: Some(96), added: 2571, mode: MaxEncodedLen)
+	/// Storage: Dex CommitmentExpiries (r:1 w:1)
+	/// Proof: Dex CommitmentExpiries (max_values: None, max_size: Some(3206), added: 5681, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	fn reveal_trade() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(49_874_000, 0)
+			.saturating_add(Weight::from_parts(0, 10183))
+			.saturating_add(RocksDbWeight::get().reads(3))
+			.saturating_add(RocksDbWeight::get().writes(3))
+	}
+	/// Storage: Dex TradeCommitments (r:1 w:1)
+	/// Proof: Dex TradeCommitments (max_values: None, max_size: Some(96), added: 2571, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	fn expire_trade_commitment() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(21_596_000, 0)
+			.saturating_add(Weight::from_parts(0, 6164))
+			.saturating_add(RocksDbWeight::get().reads(2))
+			.saturating_add(RocksDbWeight::get().writes(2))
+	}
+	/// Storage: Dex IntentNonces (r:1 w:1)
+	/// Proof: Dex IntentNonces (max_values: None, max_size: Some(56), added: 2531, mode: MaxEncodedLen)
+	/// Storage: Dex BatchAuctions (r:1 w:0)
+	/// Proof: Dex BatchAuctions (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
+	/// Storage: Dex Exchanges (r:1 w:1)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex DynamicFees (r:1 w:1)
+	/// Proof: Dex DynamicFees (max_values: None, max_size: Some(48), added: 2523, mode: MaxEncodedLen)
+	/// Storage: System Account (r:2 w:2)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:3 w:3)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	fn execute_signed_intent() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(145_488_000, 0)
+			.saturating_add(Weight::from_parts(0, 25697))
+			.saturating_add(RocksDbWeight::get().reads(10))
+			.saturating_add(RocksDbWeight::get().writes(9))
+	}
+	/// Storage: Dex Exchanges (r:1 w:1)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex DynamicFees (r:1 w:1)
+	/// Proof: Dex DynamicFees (max_values: None, max_size: Some(48), added: 2523, mode: MaxEncodedLen)
+	/// Storage: Dex BatchAuctions (r:1 w:0)
+	/// Proof: Dex BatchAuctions (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:3 w:3)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	/// Storage: Dex PermissionedExchanges (r:1 w:0)
+	/// Proof: Dex PermissionedExchanges (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
+	/// Storage: Dex ApprovedProviders (r:1 w:0)
+	/// Proof: Dex ApprovedProviders (max_values: None, max_size: Some(61), added: 2536, mode: MaxEncodedLen)
+	fn zap_in() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(164_212_000, 0)
+			.saturating_add(Weight::from_parts(0, 28272))
+			.saturating_add(RocksDbWeight::get().reads(11))
+			.saturating_add(RocksDbWeight::get().writes(8))
+	}
+	/// Storage: Dex Exchanges (r:1 w:1)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex DynamicFees (r:1 w:1)
+	/// Proof: Dex DynamicFees (max_values: None, max_size: Some(48), added: 2523, mode: MaxEncodedLen)
+	/// Storage: Dex BatchAuctions (r:1 w:0)
+	/// Proof: Dex BatchAuctions (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:3 w:3)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	fn zap_out() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(154_704_000, 0)
+			.saturating_add(Weight::from_parts(0, 23248))
+			.saturating_add(RocksDbWeight::get().reads(9))
+			.saturating_add(RocksDbWeight::get().writes(8))
+	}
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:3 w:3)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	/// Storage: Dex Exchanges (r:1 w:1)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Dex PermissionedExchanges (r:1 w:0)
+	/// Proof: Dex PermissionedExchanges (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
+	/// Storage: Dex ApprovedProviders (r:1 w:0)
+	/// Proof: Dex ApprovedProviders (max_values: None, max_size: Some(61), added: 2536, mode: MaxEncodedLen)
+	fn add_liquidity_exact_tokens() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(104_054_000, 0)
+			.saturating_add(Weight::from_parts(0, 27221))
+			.saturating_add(RocksDbWeight::get().reads(9))
+			.saturating_add(RocksDbWeight::get().writes(7))
+	}
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:3 w:3)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	/// Storage: Dex Exchanges (r:1 w:1)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Dex PermissionedExchanges (r:1 w:0)
+	/// Proof: Dex PermissionedExchanges (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
+	/// Storage: Dex ApprovedProviders (r:1 w:0)
+	/// Proof: Dex ApprovedProviders (max_values: None, max_size: Some(61), added: 2536, mode: MaxEncodedLen)
+	fn add_liquidity_bounded() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(105_773_000, 0)
+			.saturating_add(Weight::from_parts(0, 27221))
+			.saturating_add(RocksDbWeight::get().reads(9))
+			.saturating_add(RocksDbWeight::get().writes(7))
+	}
+	/// Storage: Dex Exchanges (r:1 w:1)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex AmplificationRamps (r:1 w:1)
+	/// Proof: Dex AmplificationRamps (max_values: None, max_size: Some(28), added: 2503, mode: MaxEncodedLen)
+	fn ramp_amplification() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(31_482_000, 0)
+			.saturating_add(Weight::from_parts(0, 6036))
+			.saturating_add(RocksDbWeight::get().reads(2))
+			.saturating_add(RocksDbWeight::get().writes(2))
+	}
+	/// Storage: Assets Asset (r:2 w:2)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Dex Exchanges (r:1 w:1)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:3 w:3)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	/// Storage: Dex WeightSchedules (r:0 w:1)
+	/// Proof: Dex WeightSchedules (max_values: None, max_size: Some(28), added: 2503, mode: MaxEncodedLen)
+	/// Storage: Dex PermissionedExchanges (r:1 w:0)
+	/// Proof: Dex PermissionedExchanges (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
+	fn create_bootstrapping_exchange() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(134_614_000, 0)
+			.saturating_add(Weight::from_parts(0, 24685))
 			.saturating_add(RocksDbWeight::get().reads(8))
 			.saturating_add(RocksDbWeight::get().writes(8))
 	}
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Dex Exchanges (r:1 w:1)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex ConcentratedPools (r:1 w:1)
+	/// Proof: Dex ConcentratedPools (max_values: None, max_size: Some(96), added: 2571, mode: MaxEncodedLen)
+	/// Storage: Dex Ticks (r:2 w:2)
+	/// Proof: Dex Ticks (max_values: None, max_size: Some(88), added: 2563, mode: MaxEncodedLen)
+	/// Storage: Dex InitializedTicks (r:1 w:1)
+	/// Proof: Dex InitializedTicks (max_values: None, max_size: Some(270), added: 2745, mode: MaxEncodedLen)
+	/// Storage: Dex Positions (r:1 w:1)
+	/// Proof: Dex Positions (max_values: None, max_size: Some(156), added: 2631, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:2 w:2)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	/// Storage: Dex PermissionedExchanges (r:1 w:0)
+	/// Proof: Dex PermissionedExchanges (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
+	fn create_concentrated_exchange() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(174_512_000, 0)
+			.saturating_add(Weight::from_parts(0, 29536))
+			.saturating_add(RocksDbWeight::get().reads(11))
+			.saturating_add(RocksDbWeight::get().writes(10))
+	}
+	/// Storage: Dex Exchanges (r:1 w:1)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex ConcentratedPools (r:1 w:1)
+	/// Proof: Dex ConcentratedPools (max_values: None, max_size: Some(96), added: 2571, mode: MaxEncodedLen)
+	/// Storage: Dex Ticks (r:2 w:2)
+	/// Proof: Dex Ticks (max_values: None, max_size: Some(88), added: 2563, mode: MaxEncodedLen)
+	/// Storage: Dex InitializedTicks (r:1 w:1)
+	/// Proof: Dex InitializedTicks (max_values: None, max_size: Some(270), added: 2745, mode: MaxEncodedLen)
+	/// Storage: Dex Positions (r:1 w:1)
+	/// Proof: Dex Positions (max_values: None, max_size: Some(156), added: 2631, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:2 w:2)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	/// Storage: Dex PermissionedExchanges (r:1 w:0)
+	/// Proof: Dex PermissionedExchanges (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
+	/// Storage: Dex ApprovedProviders (r:1 w:0)
+	/// Proof: Dex ApprovedProviders (max_values: None, max_size: Some(61), added: 2536, mode: MaxEncodedLen)
+	fn mint_position() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(145_218_000, 0)
+			.saturating_add(Weight::from_parts(0, 32072))
+			.saturating_add(RocksDbWeight::get().reads(12))
+			.saturating_add(RocksDbWeight::get().writes(10))
+	}
+	/// Storage: Dex Positions (r:1 w:1)
+	/// Proof: Dex Positions (max_values: None, max_size: Some(156), added: 2631, mode: MaxEncodedLen)
+	/// Storage: Dex Exchanges (r:1 w:1)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex ConcentratedPools (r:1 w:1)
+	/// Proof: Dex ConcentratedPools (max_values: None, max_size: Some(96), added: 2571, mode: MaxEncodedLen)
+	/// Storage: Dex Ticks (r:2 w:2)
+	/// Proof: Dex Ticks (max_values: None, max_size: Some(88), added: 2563, mode: MaxEncodedLen)
+	/// Storage: Dex InitializedTicks (r:1 w:1)
+	/// Proof: Dex InitializedTicks (max_values: None, max_size: Some(270), added: 2745, mode: MaxEncodedLen)
+	/// Storage: System Account (r:1 w:1)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:2 w:2)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	fn burn_position() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(139_874_000, 0)
+			.saturating_add(Weight::from_parts(0, 27048))
+			.saturating_add(RocksDbWeight::get().reads(10))
+			.saturating_add(RocksDbWeight::get().writes(10))
+	}
+	/// Storage: Dex ConcentratedPools (r:1 w:1)
+	/// Proof: Dex ConcentratedPools (max_values: None, max_size: Some(96), added: 2571, mode: MaxEncodedLen)
+	/// Storage: Dex InitializedTicks (r:1 w:0)
+	/// Proof: Dex InitializedTicks (max_values: None, max_size: Some(270), added: 2745, mode: MaxEncodedLen)
+	/// Storage: Dex Ticks (r:5 w:5)
+	/// Proof: Dex Ticks (max_values: None, max_size: Some(88), added: 2563, mode: MaxEncodedLen)
+	/// The range of component `n` is `[0, 5]`.
+	fn swap_crossing_ticks(n: u32, ) -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(22_710_412, 0)
+			.saturating_add(Weight::from_parts(0, 6316))
+			.saturating_add(Weight::from_parts(6_032_118, 0).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(2))
+			.saturating_add(RocksDbWeight::get().reads((1_u64).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().writes(1))
+			.saturating_add(RocksDbWeight::get().writes((1_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 2563).saturating_mul(n.into()))
+	}
+	/// Storage: Dex Exchanges (r:1 w:0)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex DynamicFees (r:1 w:1)
+	/// Proof: Dex DynamicFees (max_values: None, max_size: Some(48), added: 2523, mode: MaxEncodedLen)
+	fn set_dynamic_fee() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(24_871_000, 0)
+			.saturating_add(Weight::from_parts(0, 6056))
+			.saturating_add(RocksDbWeight::get().reads(2))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+	/// Storage: Dex Exchanges (r:1 w:0)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex PermissionedExchanges (r:0 w:1)
+	/// Proof: Dex PermissionedExchanges (max_values: None, max_size: Some(13), added: 2488, mode: MaxEncodedLen)
+	fn set_permissioned() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(19_482_000, 0)
+			.saturating_add(Weight::from_parts(0, 3523))
+			.saturating_add(RocksDbWeight::get().reads(1))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+	/// Storage: Dex Exchanges (r:1 w:0)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex ApprovedProviders (r:0 w:1)
+	/// Proof: Dex ApprovedProviders (max_values: None, max_size: Some(61), added: 2536, mode: MaxEncodedLen)
+	fn set_provider_approval() -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(20_137_000, 0)
+			.saturating_add(Weight::from_parts(0, 3523))
+			.saturating_add(RocksDbWeight::get().reads(1))
+			.saturating_add(RocksDbWeight::get().writes(1))
+	}
+	/// Storage: Dex BatchQueue (r:1 w:1)
+	/// Proof: Dex BatchQueue (max_values: Some(1), max_size: Some(33), added: 528, mode: MaxEncodedLen)
+	/// Storage: Dex BatchTrades (r:2 w:1)
+	/// Proof: Dex BatchTrades (max_values: None, max_size: Some(3126), added: 5601, mode: MaxEncodedLen)
+	/// Storage: Dex Exchanges (r:1 w:1)
+	/// Proof: Dex Exchanges (max_values: None, max_size: Some(58), added: 2533, mode: MaxEncodedLen)
+	/// Storage: Dex DynamicFees (r:1 w:0)
+	/// Proof: Dex DynamicFees (max_values: None, max_size: Some(48), added: 2523, mode: MaxEncodedLen)
+	/// Storage: Assets Asset (r:1 w:1)
+	/// Proof: Assets Asset (max_values: None, max_size: Some(210), added: 2685, mode: MaxEncodedLen)
+	/// Storage: Assets Account (r:100 w:100)
+	/// Proof: Assets Account (max_values: None, max_size: Some(102), added: 2577, mode: MaxEncodedLen)
+	/// Storage: System Account (r:100 w:100)
+	/// Proof: System Account (max_values: None, max_size: Some(128), added: 2603, mode: MaxEncodedLen)
+	/// The range of component `n` is `[1, 100]`.
+	fn settle_batch(n: u32, ) -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(58_094_671, 0)
+			.saturating_add(Weight::from_parts(0, 17402))
+			.saturating_add(Weight::from_parts(41_862_540, 0).saturating_mul(n.into()))
+			.saturating_add(RocksDbWeight::get().reads(5))
+			.saturating_add(RocksDbWeight::get().reads((2_u64).saturating_mul(n.into())))
+			.saturating_add(RocksDbWeight::get().writes(3))
+			.saturating_add(RocksDbWeight::get().writes((2_u64).saturating_mul(n.into())))
+			.saturating_add(Weight::from_parts(0, 5180).saturating_mul(n.into()))
+	}
+	/// The range of component `n` is `[1, 100]`.
+	fn clearing_round(n: u32, ) -> Weight {
+		// PLACEHOLDER: hand-estimated, not measured.
+		Weight::from_parts(1_203_518, 0)
+			.saturating_add(Weight::from_parts(0, 0))
+			.saturating_add(Weight::from_parts(487_236, 0).saturating_mul(n.into()))
+	}
 }
\ No newline at end of file
@@ -0,0 +1,77 @@
+//! Single-sided liquidity: the amounts `zap_in` swaps before depositing, so that the deposit
+//! matches the exchange ratio after the trade.
+
+use crate::{AssetBalanceOf, BalanceOf, Config, ConfigHelper, Error, ExchangeOf, Pallet};
+use frame_support::sp_runtime::{
+    traits::{CheckedDiv, IntegerSquareRoot, One, Saturating},
+    FixedPointNumber, FixedU128,
+};
+
+impl<T: Config<I>, I: 'static> Pallet<T, I> {
+    /// The part of `currency_amount` to swap for tokens by `zap_in`, so that the bought tokens
+    /// and the rest of the currency match the exchange ratio after the trade.
+    /// With currency reserve `R` and `f` the part of the input left after the provider fee,
+    /// depositing `a - s` currency and the tokens bought for `s` requires
+    /// `f * s^2 + (1 + f) * R * s - a * R = 0`, so
+    /// `s = R * (sqrt((1 + f)^2 + 4 * f * a / R) - (1 + f)) / (2 * f)`.
+    pub(crate) fn zap_in_swap_amount(
+        exchange: &ExchangeOf<T, I>,
+        currency_amount: BalanceOf<T, I>,
+    ) -> BalanceOf<T, I> {
+        let currency_reserve = T::currency_to_asset(exchange.currency_reserve);
+        let trade_fee = Self::trade_fee(exchange);
+        let fee = FixedU128::saturating_from_rational(
+            T::currency_to_asset(trade_fee.net_numerator),
+            T::currency_to_asset(trade_fee.denominator),
+        );
+        let one_plus_fee = FixedU128::one().saturating_add(fee);
+        let deposit_ratio = FixedU128::saturating_from_rational(
+            T::currency_to_asset(currency_amount),
+            currency_reserve,
+        );
+        let discriminant = one_plus_fee.saturating_mul(one_plus_fee).saturating_add(
+            fee.saturating_mul(FixedU128::saturating_from_integer(4u32))
+                .saturating_mul(deposit_ratio),
+        );
+        let swapped_ratio = Self::fixed_sqrt(discriminant)
+            .saturating_sub(one_plus_fee)
+            .checked_div(&fee.saturating_mul(FixedU128::saturating_from_integer(2u32)))
+            .unwrap_or_default();
+        let swapped_currency = swapped_ratio.saturating_mul_int(currency_reserve);
+        T::asset_to_currency(swapped_currency).min(currency_amount)
+    }
+
+    /// Compute the (currency amount, token amount) of the `zap_in` deposit of at most
+    /// `currency_amount` and `token_amount`. The deposited currency is reduced
+    /// if rounding left fewer tokens than needed.
+    pub(crate) fn zap_in_deposit(
+        exchange: &ExchangeOf<T, I>,
+        currency_amount: BalanceOf<T, I>,
+        token_amount: AssetBalanceOf<T, I>,
+    ) -> Result<(BalanceOf<T, I>, AssetBalanceOf<T, I>), Error<T, I>> {
+        let (deposited_tokens, _) = Self::get_liquidity_deposit(exchange, currency_amount)?;
+        if deposited_tokens <= token_amount {
+            return Ok((currency_amount, deposited_tokens));
+        }
+        // A deposit of `currency_amount` requires `currency_amount / R * T + 1` tokens
+        let currency_amount = FixedU128::saturating_from_rational(
+            token_amount.saturating_sub(One::one()),
+            exchange.token_reserve,
+        )
+        .saturating_mul_int(T::currency_to_asset(exchange.currency_reserve));
+        let currency_amount = T::asset_to_currency(currency_amount);
+        let (deposited_tokens, _) = Self::get_liquidity_deposit(exchange, currency_amount)?;
+        Ok((currency_amount, deposited_tokens))
+    }
+
+    /// Square root of a fixed point number, rounded down
+    fn fixed_sqrt(value: FixedU128) -> FixedU128 {
+        let inner = value.into_inner();
+        let root = match inner.checked_mul(FixedU128::DIV) {
+            Some(scaled) => scaled.integer_sqrt(),
+            // sqrt(DIV) = 10^9
+            None => inner.integer_sqrt().saturating_mul(1_000_000_000),
+        };
+        FixedU128::from_inner(root)
+    }
+}